@@ -0,0 +1,238 @@
+#ifndef RUSTYSYNTH_H
+#define RUSTYSYNTH_H
+
+/* This file is generated by cbindgen. Do not edit it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// A loaded MIDI file.
+typedef struct RustySynthMidiFile RustySynthMidiFile;
+
+// A MIDI file sequencer with its own synthesizer.
+typedef struct RustySynthSequencer RustySynthSequencer;
+
+// A loaded SoundFont.
+typedef struct RustySynthSoundFont RustySynthSoundFont;
+
+// A synthesizer.
+typedef struct RustySynthSynthesizer RustySynthSynthesizer;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Gets the message of the last error occurred in the current thread.
+//
+// The returned string is valid until the next call of a function in this library
+// on the same thread.
+const char *rustysynth_get_last_error(void);
+
+// Loads a SoundFont from the content of a SoundFont file.
+//
+// # Safety
+//
+// `data` must point to `length` readable bytes.
+struct RustySynthSoundFont *rustysynth_soundfont_load(const uint8_t *data, size_t length);
+
+// Releases the SoundFont.
+//
+// The synthesizers created from the SoundFont remain valid.
+//
+// # Safety
+//
+// `sound_font` must be null or a pointer returned by `rustysynth_soundfont_load`
+// which has not been released yet.
+void rustysynth_soundfont_destroy(struct RustySynthSoundFont *sound_font);
+
+// Creates a synthesizer with the default settings.
+//
+// # Safety
+//
+// `sound_font` must be a valid pointer returned by `rustysynth_soundfont_load`.
+struct RustySynthSynthesizer *rustysynth_synthesizer_create(const struct RustySynthSoundFont *sound_font,
+                                                            int32_t sample_rate);
+
+// Releases the synthesizer.
+//
+// # Safety
+//
+// `synthesizer` must be null or a pointer returned by `rustysynth_synthesizer_create`
+// which has not been released yet.
+void rustysynth_synthesizer_destroy(struct RustySynthSynthesizer *synthesizer);
+
+// Processes a MIDI message.
+//
+// # Safety
+//
+// `synthesizer` must be a valid pointer returned by `rustysynth_synthesizer_create`.
+bool rustysynth_synthesizer_process_midi_message(struct RustySynthSynthesizer *synthesizer,
+                                                 int32_t channel,
+                                                 int32_t command,
+                                                 int32_t data1,
+                                                 int32_t data2);
+
+// Processes the Universal MIDI Packets of MIDI 2.0.
+//
+// # Safety
+//
+// `synthesizer` must be a valid pointer returned by `rustysynth_synthesizer_create`,
+// and `words` must point to `length` readable 32-bit words.
+bool rustysynth_synthesizer_process_ump(struct RustySynthSynthesizer *synthesizer,
+                                        const uint32_t *words,
+                                        size_t length);
+
+// Starts a note.
+//
+// # Safety
+//
+// `synthesizer` must be a valid pointer returned by `rustysynth_synthesizer_create`.
+bool rustysynth_synthesizer_note_on(struct RustySynthSynthesizer *synthesizer,
+                                    int32_t channel,
+                                    int32_t key,
+                                    int32_t velocity);
+
+// Stops a note.
+//
+// # Safety
+//
+// `synthesizer` must be a valid pointer returned by `rustysynth_synthesizer_create`.
+bool rustysynth_synthesizer_note_off(struct RustySynthSynthesizer *synthesizer,
+                                     int32_t channel,
+                                     int32_t key);
+
+// Stops all the notes.
+//
+// # Safety
+//
+// `synthesizer` must be a valid pointer returned by `rustysynth_synthesizer_create`.
+bool rustysynth_synthesizer_note_off_all(struct RustySynthSynthesizer *synthesizer, bool immediate);
+
+// Resets the synthesizer.
+//
+// # Safety
+//
+// `synthesizer` must be a valid pointer returned by `rustysynth_synthesizer_create`.
+bool rustysynth_synthesizer_reset(struct RustySynthSynthesizer *synthesizer);
+
+// Sets the master volume.
+//
+// # Safety
+//
+// `synthesizer` must be a valid pointer returned by `rustysynth_synthesizer_create`.
+bool rustysynth_synthesizer_set_master_volume(struct RustySynthSynthesizer *synthesizer,
+                                              float value);
+
+// Renders the waveform into the left and right buffers.
+//
+// # Safety
+//
+// `synthesizer` must be a valid pointer returned by `rustysynth_synthesizer_create`,
+// and `left` and `right` must point to `length` writable floats each.
+bool rustysynth_synthesizer_render(struct RustySynthSynthesizer *synthesizer,
+                                   float *left,
+                                   float *right,
+                                   size_t length);
+
+// Loads a MIDI file from the content of a MIDI file.
+//
+// # Safety
+//
+// `data` must point to `length` readable bytes.
+struct RustySynthMidiFile *rustysynth_midifile_load(const uint8_t *data, size_t length);
+
+// Releases the MIDI file.
+//
+// # Safety
+//
+// `midi_file` must be null or a pointer returned by `rustysynth_midifile_load`
+// which has not been released yet.
+void rustysynth_midifile_destroy(struct RustySynthMidiFile *midi_file);
+
+// Gets the length of the MIDI file in seconds.
+//
+// If it fails, NaN is returned.
+//
+// # Safety
+//
+// `midi_file` must be a valid pointer returned by `rustysynth_midifile_load`.
+double rustysynth_midifile_get_length(const struct RustySynthMidiFile *midi_file);
+
+// Gets the number of the tracks in the MIDI file.
+//
+// # Safety
+//
+// `midi_file` must be a valid pointer returned by `rustysynth_midifile_load`.
+size_t rustysynth_midifile_get_track_count(const struct RustySynthMidiFile *midi_file);
+
+// Creates a sequencer with a synthesizer of the default settings.
+//
+// # Safety
+//
+// `sound_font` must be a valid pointer returned by `rustysynth_soundfont_load`.
+struct RustySynthSequencer *rustysynth_sequencer_create(const struct RustySynthSoundFont *sound_font,
+                                                        int32_t sample_rate);
+
+// Releases the sequencer.
+//
+// # Safety
+//
+// `sequencer` must be null or a pointer returned by `rustysynth_sequencer_create`
+// which has not been released yet.
+void rustysynth_sequencer_destroy(struct RustySynthSequencer *sequencer);
+
+// Plays a track of the MIDI file.
+//
+// The track is copied, so the MIDI file can be released while playing.
+//
+// # Safety
+//
+// `sequencer` and `midi_file` must be valid pointers returned by
+// `rustysynth_sequencer_create` and `rustysynth_midifile_load`.
+bool rustysynth_sequencer_play(struct RustySynthSequencer *sequencer,
+                               const struct RustySynthMidiFile *midi_file,
+                               size_t track_index,
+                               bool play_loop);
+
+// Stops playing.
+//
+// # Safety
+//
+// `sequencer` must be a valid pointer returned by `rustysynth_sequencer_create`.
+bool rustysynth_sequencer_stop(struct RustySynthSequencer *sequencer);
+
+// Renders the waveform into the left and right buffers.
+//
+// # Safety
+//
+// `sequencer` must be a valid pointer returned by `rustysynth_sequencer_create`,
+// and `left` and `right` must point to `length` writable floats each.
+bool rustysynth_sequencer_render(struct RustySynthSequencer *sequencer,
+                                 float *left,
+                                 float *right,
+                                 size_t length);
+
+// Gets the current playback position in seconds.
+//
+// If it fails, NaN is returned.
+//
+// # Safety
+//
+// `sequencer` must be a valid pointer returned by `rustysynth_sequencer_create`.
+double rustysynth_sequencer_get_position(const struct RustySynthSequencer *sequencer);
+
+// Gets a value that indicates whether the current playback position is at the end of the sequence.
+//
+// # Safety
+//
+// `sequencer` must be a valid pointer returned by `rustysynth_sequencer_create`.
+bool rustysynth_sequencer_end_of_sequence(const struct RustySynthSequencer *sequencer);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RUSTYSYNTH_H */
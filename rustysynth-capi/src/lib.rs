@@ -0,0 +1,514 @@
+//! C API for RustySynth.
+//!
+//! The objects are created by the `*_create` or `*_load` functions and must be released
+//! by the corresponding `*_destroy` functions.
+//! If a function fails, it returns a null pointer or `false`,
+//! and the reason can be retrieved by `rustysynth_get_last_error`.
+//! A panic in the library is caught and reported as a failure in the same way,
+//! so it never unwinds into the caller.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::fmt::Display;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+use std::sync::Arc;
+
+use rustysynth::{MidiFile, MidiFileSequencer, SoundFont, Synthesizer, SynthesizerSettings};
+
+/// A loaded SoundFont.
+pub struct RustySynthSoundFont(Arc<SoundFont>);
+
+/// A synthesizer.
+pub struct RustySynthSynthesizer(Synthesizer);
+
+/// A loaded MIDI file.
+pub struct RustySynthMidiFile(MidiFile);
+
+/// A MIDI file sequencer with its own synthesizer.
+pub struct RustySynthSequencer(MidiFileSequencer);
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_last_error(err: impl Display) {
+    // The message never contains a null character, but replace it just in case.
+    let message = err.to_string().replace('\0', "?");
+    LAST_ERROR.with(|x| *x.borrow_mut() = CString::new(message).unwrap_or_default());
+}
+
+// Runs the function, and returns the default value if it panics.
+fn guard<T>(default: T, f: impl FnOnce() -> T) -> T {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(value) => value,
+        Err(payload) => {
+            set_last_error(format!("panicked: {}", get_panic_message(&*payload)));
+            default
+        }
+    }
+}
+
+fn get_panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
+
+fn into_raw<T>(value: T) -> *mut T {
+    Box::into_raw(Box::new(value))
+}
+
+unsafe fn destroy<T>(value: *mut T) {
+    if !value.is_null() {
+        drop(Box::from_raw(value));
+    }
+}
+
+unsafe fn bytes<'a>(data: *const u8, length: usize) -> Option<&'a [u8]> {
+    if data.is_null() {
+        set_last_error("the data must not be null");
+        return None;
+    }
+    Some(slice::from_raw_parts(data, length))
+}
+
+unsafe fn buffers<'a>(
+    left: *mut f32,
+    right: *mut f32,
+    length: usize,
+) -> Option<(&'a mut [f32], &'a mut [f32])> {
+    if left.is_null() || right.is_null() {
+        set_last_error("the output buffers must not be null");
+        return None;
+    }
+    Some((
+        slice::from_raw_parts_mut(left, length),
+        slice::from_raw_parts_mut(right, length),
+    ))
+}
+
+fn create_synthesizer(sound_font: &RustySynthSoundFont, sample_rate: i32) -> Option<Synthesizer> {
+    let settings = SynthesizerSettings::new(sample_rate);
+    match Synthesizer::new(&sound_font.0, &settings) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            set_last_error(err);
+            None
+        }
+    }
+}
+
+/// Gets the message of the last error occurred in the current thread.
+///
+/// The returned string is valid until the next call of a function in this library
+/// on the same thread.
+#[no_mangle]
+pub extern "C" fn rustysynth_get_last_error() -> *const c_char {
+    LAST_ERROR.with(|x| x.borrow().as_ptr())
+}
+
+/// Loads a SoundFont from the content of a SoundFont file.
+///
+/// # Safety
+///
+/// `data` must point to `length` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_soundfont_load(
+    data: *const u8,
+    length: usize,
+) -> *mut RustySynthSoundFont {
+    guard(ptr::null_mut(), || {
+        let Some(data) = bytes(data, length) else {
+            return ptr::null_mut();
+        };
+        match SoundFont::from_bytes(data) {
+            Ok(value) => into_raw(RustySynthSoundFont(Arc::new(value))),
+            Err(err) => {
+                set_last_error(err);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Releases the SoundFont.
+///
+/// The synthesizers created from the SoundFont remain valid.
+///
+/// # Safety
+///
+/// `sound_font` must be null or a pointer returned by `rustysynth_soundfont_load`
+/// which has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_soundfont_destroy(sound_font: *mut RustySynthSoundFont) {
+    guard((), || {
+        destroy(sound_font);
+    })
+}
+
+/// Creates a synthesizer with the default settings.
+///
+/// # Safety
+///
+/// `sound_font` must be a valid pointer returned by `rustysynth_soundfont_load`.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_synthesizer_create(
+    sound_font: *const RustySynthSoundFont,
+    sample_rate: i32,
+) -> *mut RustySynthSynthesizer {
+    guard(ptr::null_mut(), || {
+        match create_synthesizer(&*sound_font, sample_rate) {
+            Some(value) => into_raw(RustySynthSynthesizer(value)),
+            None => ptr::null_mut(),
+        }
+    })
+}
+
+/// Releases the synthesizer.
+///
+/// # Safety
+///
+/// `synthesizer` must be null or a pointer returned by `rustysynth_synthesizer_create`
+/// which has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_synthesizer_destroy(synthesizer: *mut RustySynthSynthesizer) {
+    guard((), || {
+        destroy(synthesizer);
+    })
+}
+
+/// Processes a MIDI message.
+///
+/// # Safety
+///
+/// `synthesizer` must be a valid pointer returned by `rustysynth_synthesizer_create`.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_synthesizer_process_midi_message(
+    synthesizer: *mut RustySynthSynthesizer,
+    channel: i32,
+    command: i32,
+    data1: i32,
+    data2: i32,
+) -> bool {
+    guard(false, || {
+        (*synthesizer)
+            .0
+            .process_midi_message(channel, command, data1, data2);
+        true
+    })
+}
+
+/// Processes the Universal MIDI Packets of MIDI 2.0.
+///
+/// # Safety
+///
+/// `synthesizer` must be a valid pointer returned by `rustysynth_synthesizer_create`,
+/// and `words` must point to `length` readable 32-bit words.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_synthesizer_process_ump(
+    synthesizer: *mut RustySynthSynthesizer,
+    words: *const u32,
+    length: usize,
+) -> bool {
+    guard(false, || {
+        if words.is_null() {
+            set_last_error("the words must not be null");
+            return false;
+        }
+        (*synthesizer)
+            .0
+            .process_ump(slice::from_raw_parts(words, length));
+        true
+    })
+}
+
+/// Starts a note.
+///
+/// # Safety
+///
+/// `synthesizer` must be a valid pointer returned by `rustysynth_synthesizer_create`.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_synthesizer_note_on(
+    synthesizer: *mut RustySynthSynthesizer,
+    channel: i32,
+    key: i32,
+    velocity: i32,
+) -> bool {
+    guard(false, || {
+        (*synthesizer).0.note_on(channel, key, velocity);
+        true
+    })
+}
+
+/// Stops a note.
+///
+/// # Safety
+///
+/// `synthesizer` must be a valid pointer returned by `rustysynth_synthesizer_create`.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_synthesizer_note_off(
+    synthesizer: *mut RustySynthSynthesizer,
+    channel: i32,
+    key: i32,
+) -> bool {
+    guard(false, || {
+        (*synthesizer).0.note_off(channel, key);
+        true
+    })
+}
+
+/// Stops all the notes.
+///
+/// # Safety
+///
+/// `synthesizer` must be a valid pointer returned by `rustysynth_synthesizer_create`.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_synthesizer_note_off_all(
+    synthesizer: *mut RustySynthSynthesizer,
+    immediate: bool,
+) -> bool {
+    guard(false, || {
+        (*synthesizer).0.note_off_all(immediate);
+        true
+    })
+}
+
+/// Resets the synthesizer.
+///
+/// # Safety
+///
+/// `synthesizer` must be a valid pointer returned by `rustysynth_synthesizer_create`.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_synthesizer_reset(
+    synthesizer: *mut RustySynthSynthesizer,
+) -> bool {
+    guard(false, || {
+        (*synthesizer).0.reset();
+        true
+    })
+}
+
+/// Sets the master volume.
+///
+/// # Safety
+///
+/// `synthesizer` must be a valid pointer returned by `rustysynth_synthesizer_create`.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_synthesizer_set_master_volume(
+    synthesizer: *mut RustySynthSynthesizer,
+    value: f32,
+) -> bool {
+    guard(false, || {
+        (*synthesizer).0.set_master_volume(value);
+        true
+    })
+}
+
+/// Renders the waveform into the left and right buffers.
+///
+/// # Safety
+///
+/// `synthesizer` must be a valid pointer returned by `rustysynth_synthesizer_create`,
+/// and `left` and `right` must point to `length` writable floats each.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_synthesizer_render(
+    synthesizer: *mut RustySynthSynthesizer,
+    left: *mut f32,
+    right: *mut f32,
+    length: usize,
+) -> bool {
+    guard(false, || {
+        let Some((left, right)) = buffers(left, right, length) else {
+            return false;
+        };
+        (*synthesizer).0.render(left, right);
+        true
+    })
+}
+
+/// Loads a MIDI file from the content of a MIDI file.
+///
+/// # Safety
+///
+/// `data` must point to `length` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_midifile_load(
+    data: *const u8,
+    length: usize,
+) -> *mut RustySynthMidiFile {
+    guard(ptr::null_mut(), || {
+        let Some(data) = bytes(data, length) else {
+            return ptr::null_mut();
+        };
+        match MidiFile::from_bytes(data) {
+            Ok(value) => into_raw(RustySynthMidiFile(value)),
+            Err(err) => {
+                set_last_error(err);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Releases the MIDI file.
+///
+/// # Safety
+///
+/// `midi_file` must be null or a pointer returned by `rustysynth_midifile_load`
+/// which has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_midifile_destroy(midi_file: *mut RustySynthMidiFile) {
+    guard((), || {
+        destroy(midi_file);
+    })
+}
+
+/// Gets the length of the MIDI file in seconds.
+///
+/// If it fails, NaN is returned.
+///
+/// # Safety
+///
+/// `midi_file` must be a valid pointer returned by `rustysynth_midifile_load`.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_midifile_get_length(
+    midi_file: *const RustySynthMidiFile,
+) -> f64 {
+    guard(f64::NAN, || (*midi_file).0.get_length())
+}
+
+/// Gets the number of the tracks in the MIDI file.
+///
+/// # Safety
+///
+/// `midi_file` must be a valid pointer returned by `rustysynth_midifile_load`.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_midifile_get_track_count(
+    midi_file: *const RustySynthMidiFile,
+) -> usize {
+    guard(0, || (*midi_file).0.get_tracks().len())
+}
+
+/// Creates a sequencer with a synthesizer of the default settings.
+///
+/// # Safety
+///
+/// `sound_font` must be a valid pointer returned by `rustysynth_soundfont_load`.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_sequencer_create(
+    sound_font: *const RustySynthSoundFont,
+    sample_rate: i32,
+) -> *mut RustySynthSequencer {
+    guard(ptr::null_mut(), || {
+        match create_synthesizer(&*sound_font, sample_rate) {
+            Some(value) => into_raw(RustySynthSequencer(MidiFileSequencer::new(value))),
+            None => ptr::null_mut(),
+        }
+    })
+}
+
+/// Releases the sequencer.
+///
+/// # Safety
+///
+/// `sequencer` must be null or a pointer returned by `rustysynth_sequencer_create`
+/// which has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_sequencer_destroy(sequencer: *mut RustySynthSequencer) {
+    guard((), || {
+        destroy(sequencer);
+    })
+}
+
+/// Plays a track of the MIDI file.
+///
+/// The track is copied, so the MIDI file can be released while playing.
+///
+/// # Safety
+///
+/// `sequencer` and `midi_file` must be valid pointers returned by
+/// `rustysynth_sequencer_create` and `rustysynth_midifile_load`.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_sequencer_play(
+    sequencer: *mut RustySynthSequencer,
+    midi_file: *const RustySynthMidiFile,
+    track_index: usize,
+    play_loop: bool,
+) -> bool {
+    guard(false, || {
+        let Some(track) = (*midi_file).0.get_tracks().get(track_index) else {
+            set_last_error("the track index is out of range");
+            return false;
+        };
+        (*sequencer).0.play(track.clone(), play_loop);
+        true
+    })
+}
+
+/// Stops playing.
+///
+/// # Safety
+///
+/// `sequencer` must be a valid pointer returned by `rustysynth_sequencer_create`.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_sequencer_stop(sequencer: *mut RustySynthSequencer) -> bool {
+    guard(false, || {
+        (*sequencer).0.stop();
+        true
+    })
+}
+
+/// Renders the waveform into the left and right buffers.
+///
+/// # Safety
+///
+/// `sequencer` must be a valid pointer returned by `rustysynth_sequencer_create`,
+/// and `left` and `right` must point to `length` writable floats each.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_sequencer_render(
+    sequencer: *mut RustySynthSequencer,
+    left: *mut f32,
+    right: *mut f32,
+    length: usize,
+) -> bool {
+    guard(false, || {
+        let Some((left, right)) = buffers(left, right, length) else {
+            return false;
+        };
+        (*sequencer).0.render(left, right);
+        true
+    })
+}
+
+/// Gets the current playback position in seconds.
+///
+/// If it fails, NaN is returned.
+///
+/// # Safety
+///
+/// `sequencer` must be a valid pointer returned by `rustysynth_sequencer_create`.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_sequencer_get_position(
+    sequencer: *const RustySynthSequencer,
+) -> f64 {
+    guard(f64::NAN, || (*sequencer).0.get_position())
+}
+
+/// Gets a value that indicates whether the current playback position is at the end of the sequence.
+///
+/// # Safety
+///
+/// `sequencer` must be a valid pointer returned by `rustysynth_sequencer_create`.
+#[no_mangle]
+pub unsafe extern "C" fn rustysynth_sequencer_end_of_sequence(
+    sequencer: *const RustySynthSequencer,
+) -> bool {
+    guard(false, || (*sequencer).0.end_of_sequence())
+}
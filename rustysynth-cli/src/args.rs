@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use clap::ValueEnum;
+
+/// Renders MIDI files to WAV files with a SoundFont.
+#[derive(Parser, Debug)]
+#[command(name = "rustysynth", version)]
+pub struct Args {
+    /// The SoundFont used for synthesis.
+    pub sound_font: PathBuf,
+
+    /// The MIDI files to be rendered. Glob patterns such as "songs/*.mid" are expanded.
+    #[arg(required = true)]
+    pub midi_files: Vec<String>,
+
+    /// The output WAV file, or the output directory if more than one MIDI file or a glob pattern is given.
+    /// By default, the WAV file is written next to each MIDI file.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// The sample rate of the output.
+    #[arg(short = 'r', long, default_value_t = 44100)]
+    pub sample_rate: i32,
+
+    /// The number of maximum polyphony, between 8 and 256.
+    #[arg(short, long)]
+    pub polyphony: Option<usize>,
+
+    /// The number of channels, a multiple of 16 up to 128, for the MIDI files using more than one port.
+    /// By default, 16 channels are used, and all the ports share them.
+    #[arg(short, long)]
+    pub channels: Option<usize>,
+
+    /// The number of times the loop of the MIDI file is played. By default, the file is played once.
+    #[arg(short, long)]
+    pub loops: Option<usize>,
+
+    /// The loop extension which marks the loop in the MIDI file.
+    /// By default, the whole file is looped.
+    #[arg(long, value_enum)]
+    pub loop_type: Option<LoopType>,
+
+    /// The maximum length in seconds of the sound rendered after the last event,
+    /// which is cut when it becomes silent.
+    #[arg(short, long, value_name = "SECONDS")]
+    pub tail: Option<f64>,
+
+    /// The playback speed of the output, such as 0.8 for 80%, which changes the tempo without changing the pitch.
+    /// The stems are stretched separately, so they may not sum up exactly to the mix.
+    #[arg(long, value_name = "SPEED")]
+    pub stretch: Option<f64>,
+
+    /// The sample format of the output.
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::F32)]
+    pub format: OutputFormat,
+
+    /// Also writes a WAV file for each track or channel, next to the mix.
+    #[arg(short, long, value_enum)]
+    pub stems: Option<StemMode>,
+
+    /// Does not show the progress.
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+/// The sample format of the output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// The 32-bit floating point.
+    F32,
+    /// The 16-bit integer.
+    S16,
+    /// The 24-bit integer.
+    S24,
+    /// The 32-bit integer.
+    S32,
+}
+
+/// The loop extension which marks the loop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum LoopType {
+    /// CC #111 marks the loop start.
+    RpgMaker,
+    /// CC #110 and #111 mark the loop start and end.
+    IncredibleMachine,
+    /// CC #116 and #117 mark the loop start and end.
+    FinalFantasy,
+}
+
+/// How the stems are divided.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum StemMode {
+    /// A stem for each track of the MIDI file.
+    Track,
+    /// A stem for each MIDI channel.
+    Channel,
+}
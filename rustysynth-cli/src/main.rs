@@ -0,0 +1,299 @@
+mod args;
+mod output;
+
+use std::error::Error;
+use std::fmt::Write;
+use std::fs;
+use std::fs::File;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use clap::Parser;
+use indicatif::{ProgressBar, ProgressState, ProgressStyle};
+use rustysynth::{
+    DiagnosticSeverity, LoopRenderMode, MidiFileError, MidiFileLoopType, RenderSplit, RenderTail,
+    SoundFont, Synthesizer, SynthesizerSettings, ThreadedRender, TimeStretch,
+};
+
+use crate::args::{Args, LoopType, StemMode};
+use crate::output::write_wave;
+
+// The tail is cut when the sound falls below this level.
+const TAIL_THRESHOLD: f64 = -90.0;
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Returns false if any of the MIDI files failed to render.
+fn run(args: &Args) -> Result<bool, Box<dyn Error>> {
+    if matches!(args.loops, Some(0)) {
+        return Err("the loop count must be greater than zero".into());
+    }
+
+    if matches!(args.tail, Some(tail) if tail.is_nan() || tail < 0.0) {
+        return Err("the tail length must be a non-negative value".into());
+    }
+
+    if matches!(args.stretch, Some(speed) if speed.is_nan() || speed <= 0.0) {
+        return Err("the stretch speed must be greater than zero".into());
+    }
+
+    let midi_files = expand_paths(&args.midi_files)?;
+    if midi_files.is_empty() {
+        return Err("no MIDI file matched".into());
+    }
+
+    let mut file = File::open(&args.sound_font)
+        .map_err(|err| format!("{}: {}", args.sound_font.display(), err))?;
+    let sound_font = Arc::new(
+        SoundFont::new(&mut file)
+            .map_err(|err| format!("{}: {}", args.sound_font.display(), err))?,
+    );
+
+    // The settings are checked once, instead of failing every track.
+    Synthesizer::new(&sound_font, &create_settings(args))?;
+
+    let batch = midi_files.len() > 1 || args.midi_files.iter().any(|x| is_pattern(x));
+    if batch {
+        if let Some(directory) = args.output.as_ref() {
+            fs::create_dir_all(directory)?;
+        }
+    }
+
+    let mut succeeded = true;
+    for midi_file in midi_files.iter() {
+        let output = get_output_path(args, midi_file, batch);
+        if let Err(err) = render_file(args, &sound_font, midi_file, &output) {
+            eprintln!("error: {}: {}", midi_file.display(), err);
+            succeeded = false;
+        }
+    }
+
+    Ok(succeeded)
+}
+
+// The arguments without the glob characters are kept as they are,
+// so that a missing file is reported instead of being skipped.
+fn expand_paths(patterns: &[String]) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for pattern in patterns.iter() {
+        if !is_pattern(pattern) {
+            paths.push(PathBuf::from(pattern));
+            continue;
+        }
+
+        for entry in glob::glob(pattern)? {
+            let path = entry?;
+            if path.is_file() {
+                paths.push(path);
+            }
+        }
+    }
+
+    Ok(paths)
+}
+
+fn is_pattern(value: &str) -> bool {
+    value.contains(['*', '?', '['])
+}
+
+fn get_output_path(args: &Args, midi_file: &Path, batch: bool) -> PathBuf {
+    match args.output.as_ref() {
+        Some(output) if batch => {
+            let mut path = output.join(midi_file.file_name().unwrap_or_default());
+            path.set_extension("wav");
+            path
+        }
+        Some(output) => output.clone(),
+        None => midi_file.with_extension("wav"),
+    }
+}
+
+fn get_stem_path(output: &Path, mode: StemMode, index: usize) -> PathBuf {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let suffix = match mode {
+        StemMode::Track => format!("track{:02}", index),
+        StemMode::Channel => format!("ch{:02}", index + 1),
+    };
+    output.with_file_name(format!("{}_{}.wav", stem, suffix))
+}
+
+fn create_settings(args: &Args) -> SynthesizerSettings {
+    let mut settings = SynthesizerSettings::new(args.sample_rate);
+    if let Some(polyphony) = args.polyphony {
+        settings.maximum_polyphony = polyphony;
+    }
+    if let Some(channels) = args.channels {
+        settings.channel_count = channels;
+    }
+    settings
+}
+
+fn render_file(
+    args: &Args,
+    sound_font: &Arc<SoundFont>,
+    midi_file: &Path,
+    output: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let path = midi_file.to_str().ok_or("the path is not valid UTF-8")?;
+
+    if !args.quiet {
+        println!("{} -> {}", midi_file.display(), output.display());
+    }
+
+    let (left, right, stems) = render(args, sound_font, path)?;
+    write_wave(output, &left, &right, args.sample_rate, args.format)?;
+
+    let mode = match args.stems {
+        Some(value) => value,
+        None => return Ok(()),
+    };
+
+    // The stems have the same length as the mix, so that they line up in a DAW.
+    for (index, (stem_left, stem_right)) in stems.iter().enumerate() {
+        // The tracks and the channels without any sound, such as the tempo track, are not written.
+        if stem_left
+            .iter()
+            .chain(stem_right.iter())
+            .all(|x| *x == 0_f32)
+        {
+            continue;
+        }
+
+        let stem_path = get_stem_path(output, mode, index);
+        if !args.quiet {
+            println!("  {}", stem_path.display());
+        }
+        write_wave(
+            &stem_path,
+            stem_left,
+            stem_right,
+            args.sample_rate,
+            args.format,
+        )?;
+    }
+
+    Ok(())
+}
+
+// Renders the whole file, along with the stems if any.
+#[allow(clippy::type_complexity)]
+fn render(
+    args: &Args,
+    sound_font: &Arc<SoundFont>,
+    path: &str,
+) -> Result<(Vec<f32>, Vec<f32>, Vec<(Vec<f32>, Vec<f32>)>), MidiFileError> {
+    let loop_type = match args.loop_type {
+        Some(LoopType::RpgMaker) => MidiFileLoopType::RpgMaker,
+        Some(LoopType::IncredibleMachine) => MidiFileLoopType::IncredibleMachine,
+        Some(LoopType::FinalFantasy) => MidiFileLoopType::FinalFantasy,
+        None => MidiFileLoopType::LoopPoint(0),
+    };
+    let mut renderer = ThreadedRender::new(sound_font, path, create_settings(args))?;
+    renderer.set_loop_type(loop_type);
+
+    if let Some(loops) = args.loops {
+        renderer.set_loop_mode(LoopRenderMode::Count(loops));
+    }
+
+    if let Some(tail) = args.tail {
+        renderer.set_tail(RenderTail::UntilSilence {
+            threshold: TAIL_THRESHOLD,
+            max_length: tail,
+        });
+    }
+
+    // The mix is split in the same way as the stems, so that the stems sum up to the mix.
+    if args.stems == Some(StemMode::Channel) {
+        renderer.set_split(RenderSplit::Channel);
+    }
+
+    // The file is rendered once, and the stems are the parts of the mix.
+    let render = |renderer: &mut ThreadedRender| match args.stems {
+        Some(_) => renderer.render_parts(),
+        None => {
+            let (left, right) = renderer.render();
+            (left, right, Vec::new())
+        }
+    };
+    let (left, right, stems) = if args.quiet {
+        render(&mut renderer)
+    } else {
+        render_with_progress(&mut renderer, render)
+    };
+
+    for diagnostic in renderer.get_diagnostics().get_items().iter() {
+        if diagnostic.get_severity() != DiagnosticSeverity::Info {
+            eprintln!("{}", diagnostic);
+        }
+    }
+
+    let (left, right, stems) = match args.stretch {
+        Some(speed) => {
+            let stretch = |left: &[f32], right: &[f32]| {
+                TimeStretch::new(args.sample_rate, speed).stretch(left, right)
+            };
+            let stems = stems
+                .iter()
+                .map(|(left, right)| stretch(left, right))
+                .collect();
+            let (left, right) = stretch(&left, &right);
+            (left, right, stems)
+        }
+        None => (left, right, stems),
+    };
+
+    Ok((left, right, stems))
+}
+
+fn render_with_progress<T>(
+    renderer: &mut ThreadedRender,
+    render: impl FnOnce(&mut ThreadedRender) -> T,
+) -> T {
+    let render_progress = renderer.get_render_progress();
+    let done = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            let pb = ProgressBar::new(0);
+            pb.set_style(
+                ProgressStyle::with_template(
+                    "{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {percent}% ETA: {eta}",
+                )
+                .unwrap()
+                .with_key("eta", |state: &ProgressState, w: &mut dyn Write| {
+                    write!(w, "{:.1}s", state.eta().as_secs_f64()).unwrap()
+                })
+                .progress_chars("#>-"),
+            );
+
+            while !done.load(Ordering::SeqCst) {
+                pb.set_length(render_progress.get_total_sample_count());
+                pb.set_position(render_progress.get_rendered_sample_count());
+                thread::sleep(Duration::from_millis(100));
+            }
+
+            pb.finish_and_clear();
+        });
+
+        let result = render(renderer);
+        done.store(true, Ordering::SeqCst);
+        result
+    })
+}
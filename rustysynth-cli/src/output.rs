@@ -0,0 +1,33 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+
+use rustysynth::WaveFormat;
+use rustysynth::WaveOptions;
+
+use crate::args::OutputFormat;
+
+// Writes a stereo WAV file. The integer formats are clipped and rounded.
+pub fn write_wave(
+    path: &Path,
+    left: &[f32],
+    right: &[f32],
+    sample_rate: i32,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    let mut options = WaveOptions::new();
+    options.format = match format {
+        OutputFormat::F32 => WaveFormat::Float32,
+        OutputFormat::S16 => WaveFormat::Int16,
+        OutputFormat::S24 => WaveFormat::Int24,
+        OutputFormat::S32 => WaveFormat::Int32,
+    };
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    options.write(&mut writer, left, right, sample_rate, None)?;
+    writer.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rustysynth::bench;
+use rustysynth::bench::BenchOptions;
+
+const SAMPLE_RATE: i32 = 44100;
+const LENGTH: f64 = 2.0;
+
+fn create_options() -> BenchOptions {
+    let mut options = BenchOptions::new(SAMPLE_RATE);
+    options.length = LENGTH;
+    options
+}
+
+fn render_effects(c: &mut Criterion) {
+    let sound_font = bench::create_sound_font();
+    let sequence = bench::create_sequence(LENGTH);
+
+    let mut group = c.benchmark_group("effects");
+    group.throughput(Throughput::Elements((SAMPLE_RATE as f64 * LENGTH) as u64));
+    for enable in [false, true] {
+        let mut options = create_options();
+        options.enable_reverb_and_chorus = enable;
+        group.bench_with_input(
+            BenchmarkId::from_parameter(enable),
+            &options,
+            |b, options| b.iter(|| bench::render_with(&sound_font, &sequence, options).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn render_block_size(c: &mut Criterion) {
+    let sound_font = bench::create_sound_font();
+    let sequence = bench::create_sequence(LENGTH);
+
+    let mut group = c.benchmark_group("block_size");
+    group.throughput(Throughput::Elements((SAMPLE_RATE as f64 * LENGTH) as u64));
+    for block_size in [16, 64, 256] {
+        let mut options = create_options();
+        options.block_size = block_size;
+        group.bench_with_input(
+            BenchmarkId::from_parameter(block_size),
+            &options,
+            |b, options| b.iter(|| bench::render_with(&sound_font, &sequence, options).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn render_parallel_voices(c: &mut Criterion) {
+    let sound_font = bench::create_sound_font();
+    let sequence = bench::create_sequence(LENGTH);
+
+    let mut group = c.benchmark_group("parallel_voices");
+    group.throughput(Throughput::Elements((SAMPLE_RATE as f64 * LENGTH) as u64));
+    group.bench_function("sequential", |b| {
+        let options = create_options();
+        b.iter(|| bench::render_with(&sound_font, &sequence, &options).unwrap())
+    });
+    for thread_count in [1, 2, 4] {
+        let mut options = create_options();
+        options.maximum_polyphony = 256;
+        options.parallel_voices = true;
+        options.thread_count = Some(thread_count);
+        group.bench_with_input(
+            BenchmarkId::new("threads", thread_count),
+            &options,
+            |b, options| b.iter(|| bench::render_with(&sound_font, &sequence, options).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    render_effects,
+    render_block_size,
+    render_parallel_voices
+);
+criterion_main!(benches);
@@ -0,0 +1,93 @@
+#![allow(dead_code)]
+
+use alloc::vec::Vec;
+use std::io;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+
+use zip::ZipArchive;
+
+// Extracts the file to be loaded from a zip archive,
+// so that the loaders accept the archives in which many banks and MIDI files are distributed.
+#[allow(unused)]
+#[non_exhaustive]
+pub(crate) struct Archive {}
+
+impl Archive {
+    pub(crate) const SIGNATURE: [u8; 4] = *b"PK\x03\x04";
+    pub(crate) const SOUNDFONT_EXTENSIONS: &'static [&'static str] = &[".sf2"];
+    pub(crate) const MIDI_EXTENSIONS: &'static [&'static str] = &[".mid", ".midi", ".smf", ".kar"];
+
+    // Reads the whole archive from a stream which cannot be seeked, whose signature has already been read.
+    pub(crate) fn read_to_end<R: Read>(
+        reader: &mut R,
+        head: [u8; 4],
+        max_size: usize,
+    ) -> Result<Vec<u8>, io::Error> {
+        let mut data = head.to_vec();
+        reader.take(max_size as u64 + 1).read_to_end(&mut data)?;
+        if data.len() > max_size + head.len() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "the archive is too large",
+            ));
+        }
+
+        Ok(data)
+    }
+
+    // Checks the signature without moving the position of the stream.
+    pub(crate) fn is_archive<R: Read + Seek>(reader: &mut R) -> Result<bool, io::Error> {
+        let start = reader.stream_position()?;
+        let mut head: Vec<u8> = Vec::with_capacity(4);
+        reader.take(4).read_to_end(&mut head)?;
+        reader.seek(SeekFrom::Start(start))?;
+        Ok(head == Archive::SIGNATURE)
+    }
+
+    // The first file with one of the extensions is extracted, ignoring the case.
+    // Only one level is unwrapped, so an archive in the archive is an error rather than being extracted again.
+    pub(crate) fn extract<R: Read + Seek>(
+        reader: R,
+        extensions: &[&str],
+        max_size: usize,
+    ) -> Result<Vec<u8>, io::Error> {
+        let mut archive = ZipArchive::new(reader).map_err(io::Error::other)?;
+
+        for i in 0..archive.len() {
+            let file = archive.by_index(i).map_err(io::Error::other)?;
+
+            // The resource forks added by macOS have the same names as the files.
+            let name = file.name().to_ascii_lowercase();
+            if !file.is_file()
+                || name.starts_with("__macosx/")
+                || !extensions.iter().any(|x| name.ends_with(x))
+            {
+                continue;
+            }
+
+            // The size in the header is not trusted, since the file might be crafted.
+            let mut data: Vec<u8> = Vec::new();
+            file.take(max_size as u64 + 1).read_to_end(&mut data)?;
+            if data.len() > max_size {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    "the file in the archive is too large",
+                ));
+            }
+
+            if data.starts_with(&Archive::SIGNATURE) {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    "the file in the archive is itself an archive",
+                ));
+            }
+
+            return Ok(data);
+        }
+
+        Err(io::Error::new(
+            ErrorKind::NotFound,
+            "the archive contains no file to be loaded",
+        ))
+    }
+}
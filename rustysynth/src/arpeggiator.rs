@@ -0,0 +1,413 @@
+#![allow(dead_code)]
+
+use alloc::vec::Vec;
+use core::cmp;
+
+use crate::arpeggiator_pattern::ArpeggiatorPattern;
+use crate::synthesizer::Synthesizer;
+
+/// An arpeggiator, which plays the held notes of a channel one by one in sync with a tempo.
+///
+/// # Remarks
+///
+/// The notes of the channel are taken by `note_on` and `note_off`, or `process_midi_message`,
+/// and the other messages are sent to the synthesizer as they are.
+/// The steps are processed at the start of each block of the synthesizer,
+/// so the timing is quantized to the block size.
+#[non_exhaustive]
+pub struct Arpeggiator {
+    synthesizer: Synthesizer,
+    channel: i32,
+
+    pattern: ArpeggiatorPattern,
+    tempo: f64,
+    rate: i32,
+    gate: f64,
+    octaves: i32,
+
+    // The held keys and their velocities in the order they were pressed.
+    held_notes: Vec<(i32, i32)>,
+    notes: Vec<(i32, i32)>,
+
+    step_index: usize,
+    // The time since the start of the current step in samples, which is None while no note is held.
+    step_position: Option<f64>,
+    playing_key: Option<i32>,
+    random_state: u32,
+
+    block_wrote: usize,
+}
+
+impl Arpeggiator {
+    const DEFAULT_TEMPO: f64 = 120.0;
+    const DEFAULT_RATE: i32 = 4;
+    const DEFAULT_GATE: f64 = 0.5;
+
+    /// Initializes a new arpeggiator.
+    ///
+    /// # Arguments
+    ///
+    /// * `synthesizer` - The synthesizer which plays the notes.
+    /// * `channel` - The channel whose notes are arpeggiated.
+    pub fn new(synthesizer: Synthesizer, channel: i32) -> Self {
+        if !(0 <= channel && channel < synthesizer.get_channel_count() as i32) {
+            panic!("The channel must be less than the channel count.");
+        }
+
+        let block_wrote = synthesizer.block_size;
+
+        Self {
+            synthesizer,
+            channel,
+            pattern: ArpeggiatorPattern::Up,
+            tempo: Arpeggiator::DEFAULT_TEMPO,
+            rate: Arpeggiator::DEFAULT_RATE,
+            gate: Arpeggiator::DEFAULT_GATE,
+            octaves: 1,
+            held_notes: Vec::new(),
+            notes: Vec::new(),
+            step_index: 0,
+            step_position: None,
+            playing_key: None,
+            random_state: 1,
+            block_wrote,
+        }
+    }
+
+    /// Processes a MIDI message.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel to which the message will be sent.
+    /// * `command` - The type of the message.
+    /// * `data1` - The first data part of the message.
+    /// * `data2` - The second data part of the message.
+    ///
+    /// # Remarks
+    ///
+    /// The note messages of the arpeggiated channel are taken by the arpeggiator,
+    /// and the other messages are sent to the synthesizer.
+    /// The all sound off and all notes off messages of the channel also release the held notes.
+    pub fn process_midi_message(&mut self, channel: i32, command: i32, data1: i32, data2: i32) {
+        if channel == self.channel {
+            match command {
+                0x80 => {
+                    self.note_off(data1);
+                    return;
+                }
+                0x90 => {
+                    self.note_on(data1, data2);
+                    return;
+                }
+                0xB0 if data1 == 0x78 || data1 == 0x7B => self.release_all(),
+                _ => (),
+            }
+        }
+
+        self.synthesizer
+            .process_midi_message(channel, command, data1, data2);
+    }
+
+    /// Holds a note.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the note.
+    /// * `velocity` - The velocity of the note.
+    ///
+    /// # Remarks
+    ///
+    /// When the first note is held, the arpeggio starts at the next block.
+    pub fn note_on(&mut self, key: i32, velocity: i32) {
+        if velocity == 0 {
+            self.note_off(key);
+            return;
+        }
+
+        if self.held_notes.iter().any(|(x, _)| *x == key) {
+            return;
+        }
+
+        self.held_notes.push((key, velocity));
+        self.update_notes();
+
+        if self.step_position.is_none() {
+            self.step_index = 0;
+            self.step_position = Some(self.get_step_length());
+        }
+    }
+
+    /// Releases a note.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the note.
+    ///
+    /// # Remarks
+    ///
+    /// When the last note is released, the playing note is stopped.
+    pub fn note_off(&mut self, key: i32) {
+        self.held_notes.retain(|(x, _)| *x != key);
+        self.update_notes();
+
+        if self.held_notes.is_empty() {
+            self.release_all();
+        }
+    }
+
+    /// Releases all the held notes and stops the playing note.
+    pub fn release_all(&mut self) {
+        self.held_notes.clear();
+        self.notes.clear();
+        self.step_position = None;
+        self.stop_playing_note();
+    }
+
+    /// Renders the waveform.
+    ///
+    /// # Arguments
+    ///
+    /// * `left` - The buffer of the left channel to store the rendered waveform.
+    /// * `right` - The buffer of the right channel to store the rendered waveform.
+    ///
+    /// # Remarks
+    ///
+    /// The output buffers for the left and right must be the same length.
+    pub fn render(&mut self, left: &mut [f32], right: &mut [f32]) {
+        if left.len() != right.len() {
+            panic!("The output buffers for the left and right must be the same length.");
+        }
+
+        let left_length = left.len();
+        let mut wrote: usize = 0;
+        while wrote < left_length {
+            if self.block_wrote == self.synthesizer.block_size {
+                self.synthesizer.apply_pending_changes();
+                self.process_steps();
+                self.block_wrote = 0;
+            }
+
+            let src_rem = self.synthesizer.block_size - self.block_wrote;
+            let dst_rem = left_length - wrote;
+            let rem = cmp::min(src_rem, dst_rem);
+
+            self.synthesizer.render(
+                &mut left[wrote..wrote + rem],
+                &mut right[wrote..wrote + rem],
+            );
+
+            self.block_wrote += rem;
+            wrote += rem;
+        }
+    }
+
+    fn process_steps(&mut self) {
+        let position = match self.step_position {
+            Some(value) => value,
+            None => return,
+        };
+
+        let step_length = self.get_step_length();
+
+        let position = if position >= step_length {
+            self.stop_playing_note();
+            self.start_next_note();
+            position % step_length
+        } else {
+            position
+        };
+
+        if position >= self.gate * step_length {
+            self.stop_playing_note();
+        }
+
+        self.step_position = Some(position + self.synthesizer.block_size as f64);
+    }
+
+    fn start_next_note(&mut self) {
+        if self.notes.is_empty() {
+            return;
+        }
+
+        let count = self.notes.len();
+        let index = match self.pattern {
+            ArpeggiatorPattern::Up | ArpeggiatorPattern::AsPlayed => self.step_index % count,
+            ArpeggiatorPattern::Down => count - 1 - self.step_index % count,
+            ArpeggiatorPattern::UpDown => {
+                if count == 1 {
+                    0
+                } else {
+                    let i = self.step_index % (2 * count - 2);
+                    if i < count {
+                        i
+                    } else {
+                        2 * count - 2 - i
+                    }
+                }
+            }
+            ArpeggiatorPattern::Random => self.next_random() as usize % count,
+        };
+        self.step_index = self.step_index.wrapping_add(1);
+
+        let (key, velocity) = self.notes[index];
+        self.synthesizer.note_on(self.channel, key, velocity);
+        self.playing_key = Some(key);
+    }
+
+    fn stop_playing_note(&mut self) {
+        if let Some(key) = self.playing_key.take() {
+            self.synthesizer.note_off(self.channel, key);
+        }
+    }
+
+    // The held notes are expanded over the octaves in the order of the pattern.
+    fn update_notes(&mut self) {
+        let mut base: Vec<(i32, i32)> = self.held_notes.clone();
+        if self.pattern != ArpeggiatorPattern::AsPlayed {
+            base.sort_by_key(|(key, _)| *key);
+        }
+
+        self.notes.clear();
+        for octave in 0..self.octaves {
+            for (key, velocity) in base.iter() {
+                let key = key + 12 * octave;
+                if key < 128 {
+                    self.notes.push((key, *velocity));
+                }
+            }
+        }
+    }
+
+    fn next_random(&mut self) -> u32 {
+        self.random_state = self
+            .random_state
+            .wrapping_mul(1664525)
+            .wrapping_add(1013904223);
+        self.random_state >> 16
+    }
+
+    fn get_step_length(&self) -> f64 {
+        60.0 * self.synthesizer.sample_rate as f64 / (self.tempo * self.rate as f64)
+    }
+
+    /// Gets the synthesizer which plays the notes.
+    pub fn get_synthesizer(&self) -> &Synthesizer {
+        &self.synthesizer
+    }
+
+    /// Gets the synthesizer which plays the notes for modification.
+    pub fn get_synthesizer_mut(&mut self) -> &mut Synthesizer {
+        &mut self.synthesizer
+    }
+
+    /// Gets the channel whose notes are arpeggiated.
+    pub fn get_channel(&self) -> i32 {
+        self.channel
+    }
+
+    /// Gets the order in which the held notes are played.
+    ///
+    /// # Remarks
+    ///
+    /// The default value is `ArpeggiatorPattern::Up`.
+    pub fn get_pattern(&self) -> ArpeggiatorPattern {
+        self.pattern
+    }
+
+    /// Sets the order in which the held notes are played.
+    pub fn set_pattern(&mut self, value: ArpeggiatorPattern) {
+        self.pattern = value;
+        self.update_notes();
+    }
+
+    /// Gets the tempo in beats per minute.
+    ///
+    /// # Remarks
+    ///
+    /// The default value is 120.
+    pub fn get_tempo(&self) -> f64 {
+        self.tempo
+    }
+
+    /// Sets the tempo in beats per minute.
+    ///
+    /// # Remarks
+    ///
+    /// The value must be greater than zero.
+    /// The change takes effect from the current step.
+    pub fn set_tempo(&mut self, value: f64) {
+        if value.is_nan() || value <= 0.0 {
+            panic!("The tempo must be greater than zero.");
+        }
+
+        self.tempo = value;
+    }
+
+    /// Gets the number of the steps per beat.
+    ///
+    /// # Remarks
+    ///
+    /// The default value is 4, which plays the sixteenth notes.
+    pub fn get_rate(&self) -> i32 {
+        self.rate
+    }
+
+    /// Sets the number of the steps per beat.
+    ///
+    /// # Remarks
+    ///
+    /// The value must be greater than zero.
+    pub fn set_rate(&mut self, value: i32) {
+        if value <= 0 {
+            panic!("The rate must be greater than zero.");
+        }
+
+        self.rate = value;
+    }
+
+    /// Gets the length of each note relative to the step.
+    ///
+    /// # Remarks
+    ///
+    /// The default value is 0.5.
+    pub fn get_gate(&self) -> f64 {
+        self.gate
+    }
+
+    /// Sets the length of each note relative to the step.
+    ///
+    /// # Remarks
+    ///
+    /// The value must be greater than zero and less than or equal to 1.
+    /// With 1, each note is held until the next note starts.
+    pub fn set_gate(&mut self, value: f64) {
+        if !(value > 0.0 && value <= 1.0) {
+            panic!("The gate must be greater than zero and less than or equal to 1.");
+        }
+
+        self.gate = value;
+    }
+
+    /// Gets the number of the octaves over which the held notes are repeated.
+    ///
+    /// # Remarks
+    ///
+    /// The default value is 1.
+    pub fn get_octaves(&self) -> i32 {
+        self.octaves
+    }
+
+    /// Sets the number of the octaves over which the held notes are repeated.
+    ///
+    /// # Remarks
+    ///
+    /// The value must be between 1 and 4.
+    pub fn set_octaves(&mut self, value: i32) {
+        if !(1..=4).contains(&value) {
+            panic!("The number of the octaves must be between 1 and 4.");
+        }
+
+        self.octaves = value;
+        self.update_notes();
+    }
+}
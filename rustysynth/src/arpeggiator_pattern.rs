@@ -0,0 +1,21 @@
+/// Specifies the order in which the arpeggiator plays the held notes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum ArpeggiatorPattern {
+    /// Plays the notes from the lowest to the highest.
+    /// This is the default pattern.
+    Up,
+
+    /// Plays the notes from the highest to the lowest.
+    Down,
+
+    /// Plays the notes up and then down.
+    /// The lowest and highest notes are not repeated at the turns.
+    UpDown,
+
+    /// Plays the notes in the order they were pressed.
+    AsPlayed,
+
+    /// Plays a note chosen at random at each step.
+    Random,
+}
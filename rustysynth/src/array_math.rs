@@ -1,27 +1,246 @@
 #![allow(dead_code)]
 
+#[cfg(target_arch = "aarch64")]
+use core::arch::aarch64::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+// The mix-down of the voices dominates the render time at high polyphony,
+// so the following functions have SIMD paths.
+// SSE2 and NEON are always available on x86_64 and aarch64, while AVX is detected at runtime
+// (only with the std feature, since the detection requires it).
+// The multiplication and the addition are not fused, so that every path gives the same result.
 #[allow(unused)]
 #[non_exhaustive]
 pub(crate) struct ArrayMath {}
 
 impl ArrayMath {
     pub(crate) fn multiply_add(a: f32, x: &[f32], destination: &mut [f32]) {
-        for (x, destination) in x.iter().zip(destination.iter_mut()) {
+        let length = x.len().min(destination.len());
+        let (x, destination) = (&x[..length], &mut destination[..length]);
+
+        #[cfg(target_arch = "x86_64")]
+        let done = if ArrayMath::has_avx() {
+            unsafe { ArrayMath::multiply_add_avx(a, x, destination) }
+        } else {
+            unsafe { ArrayMath::multiply_add_sse(a, x, destination) }
+        };
+        #[cfg(target_arch = "aarch64")]
+        let done = unsafe { ArrayMath::multiply_add_neon(a, x, destination) };
+        #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+        let done = 0;
+
+        for (x, destination) in x[done..].iter().zip(destination[done..].iter_mut()) {
             *destination += a * *x;
         }
     }
 
     pub(crate) fn multiply_add_slope(a: f32, step: f32, x: &[f32], destination: &mut [f32]) {
-        let mut a = a;
-        for (x, destination) in x.iter().zip(destination.iter_mut()) {
-            *destination += a * *x;
-            a += step;
+        let length = x.len().min(destination.len());
+        let (x, destination) = (&x[..length], &mut destination[..length]);
+
+        #[cfg(target_arch = "x86_64")]
+        let done = if ArrayMath::has_avx() {
+            unsafe { ArrayMath::multiply_add_slope_avx(a, step, x, destination) }
+        } else {
+            unsafe { ArrayMath::multiply_add_slope_sse(a, step, x, destination) }
+        };
+        #[cfg(target_arch = "aarch64")]
+        let done = unsafe { ArrayMath::multiply_add_slope_neon(a, step, x, destination) };
+        #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+        let done = 0;
+
+        ArrayMath::multiply_add_slope_scalar(a, step, x, destination, done);
+    }
+
+    // Processes the elements from the start without SIMD.
+    // The gain of each element is computed from its index in the same way as the SIMD paths,
+    // instead of adding the step, so that the result does not depend on where the SIMD paths stop.
+    fn multiply_add_slope_scalar(
+        a: f32,
+        step: f32,
+        x: &[f32],
+        destination: &mut [f32],
+        start: usize,
+    ) {
+        for (i, (x, destination)) in x.iter().zip(destination.iter_mut()).enumerate().skip(start) {
+            *destination += (a + i as f32 * step) * *x;
         }
     }
 
     pub(crate) fn sum(source: &[f32], target: &mut [f32]) {
-        for (src, dest) in source.iter().zip(target.iter_mut()) {
-            *dest += src;
+        ArrayMath::multiply_add(1_f32, source, target);
+    }
+
+    #[cfg(all(target_arch = "x86_64", feature = "std"))]
+    fn has_avx() -> bool {
+        std::is_x86_feature_detected!("avx")
+    }
+
+    #[cfg(all(target_arch = "x86_64", not(feature = "std")))]
+    fn has_avx() -> bool {
+        false
+    }
+
+    // Each of the following functions processes as many elements as possible
+    // and returns the number of the processed elements.
+
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "avx")]
+    unsafe fn multiply_add_avx(a: f32, x: &[f32], destination: &mut [f32]) -> usize {
+        let length = x.len() - x.len() % 8;
+        let a = _mm256_set1_ps(a);
+        for i in (0..length).step_by(8) {
+            let src = _mm256_loadu_ps(x.as_ptr().add(i));
+            let dst = _mm256_loadu_ps(destination.as_ptr().add(i));
+            let value = _mm256_add_ps(dst, _mm256_mul_ps(a, src));
+            _mm256_storeu_ps(destination.as_mut_ptr().add(i), value);
+        }
+        length
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn multiply_add_sse(a: f32, x: &[f32], destination: &mut [f32]) -> usize {
+        let length = x.len() - x.len() % 4;
+        let a = _mm_set1_ps(a);
+        for i in (0..length).step_by(4) {
+            let src = _mm_loadu_ps(x.as_ptr().add(i));
+            let dst = _mm_loadu_ps(destination.as_ptr().add(i));
+            let value = _mm_add_ps(dst, _mm_mul_ps(a, src));
+            _mm_storeu_ps(destination.as_mut_ptr().add(i), value);
+        }
+        length
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "avx")]
+    unsafe fn multiply_add_slope_avx(
+        a: f32,
+        step: f32,
+        x: &[f32],
+        destination: &mut [f32],
+    ) -> usize {
+        let length = x.len() - x.len() % 8;
+        let offsets = _mm256_setr_ps(0_f32, 1_f32, 2_f32, 3_f32, 4_f32, 5_f32, 6_f32, 7_f32);
+        let step = _mm256_set1_ps(step);
+        let a = _mm256_set1_ps(a);
+        for i in (0..length).step_by(8) {
+            let index = _mm256_add_ps(_mm256_set1_ps(i as f32), offsets);
+            let gain = _mm256_add_ps(a, _mm256_mul_ps(index, step));
+            let src = _mm256_loadu_ps(x.as_ptr().add(i));
+            let dst = _mm256_loadu_ps(destination.as_ptr().add(i));
+            let value = _mm256_add_ps(dst, _mm256_mul_ps(gain, src));
+            _mm256_storeu_ps(destination.as_mut_ptr().add(i), value);
+        }
+        length
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn multiply_add_slope_sse(
+        a: f32,
+        step: f32,
+        x: &[f32],
+        destination: &mut [f32],
+    ) -> usize {
+        let length = x.len() - x.len() % 4;
+        let offsets = _mm_setr_ps(0_f32, 1_f32, 2_f32, 3_f32);
+        let step = _mm_set1_ps(step);
+        let a = _mm_set1_ps(a);
+        for i in (0..length).step_by(4) {
+            let index = _mm_add_ps(_mm_set1_ps(i as f32), offsets);
+            let gain = _mm_add_ps(a, _mm_mul_ps(index, step));
+            let src = _mm_loadu_ps(x.as_ptr().add(i));
+            let dst = _mm_loadu_ps(destination.as_ptr().add(i));
+            let value = _mm_add_ps(dst, _mm_mul_ps(gain, src));
+            _mm_storeu_ps(destination.as_mut_ptr().add(i), value);
+        }
+        length
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    unsafe fn multiply_add_neon(a: f32, x: &[f32], destination: &mut [f32]) -> usize {
+        let length = x.len() - x.len() % 4;
+        let a = vdupq_n_f32(a);
+        for i in (0..length).step_by(4) {
+            let src = vld1q_f32(x.as_ptr().add(i));
+            let dst = vld1q_f32(destination.as_ptr().add(i));
+            let value = vaddq_f32(dst, vmulq_f32(a, src));
+            vst1q_f32(destination.as_mut_ptr().add(i), value);
+        }
+        length
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    unsafe fn multiply_add_slope_neon(
+        a: f32,
+        step: f32,
+        x: &[f32],
+        destination: &mut [f32],
+    ) -> usize {
+        let length = x.len() - x.len() % 4;
+        let offsets: [f32; 4] = [0_f32, 1_f32, 2_f32, 3_f32];
+        let offsets = vld1q_f32(offsets.as_ptr());
+        let step = vdupq_n_f32(step);
+        let a = vdupq_n_f32(a);
+        for i in (0..length).step_by(4) {
+            let index = vaddq_f32(vdupq_n_f32(i as f32), offsets);
+            let gain = vaddq_f32(a, vmulq_f32(index, step));
+            let src = vld1q_f32(x.as_ptr().add(i));
+            let dst = vld1q_f32(destination.as_ptr().add(i));
+            let value = vaddq_f32(dst, vmulq_f32(gain, src));
+            vst1q_f32(destination.as_mut_ptr().add(i), value);
+        }
+        length
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArrayMath;
+    use alloc::vec::Vec;
+
+    fn input(length: usize) -> (Vec<f32>, Vec<f32>) {
+        let x = (0..length).map(|i| 0.37_f32 * i as f32 - 1.1_f32).collect();
+        let destination = (0..length)
+            .map(|i| 0.5_f32 - 0.013_f32 * i as f32)
+            .collect();
+        (x, destination)
+    }
+
+    #[test]
+    fn multiply_add_slope_paths_give_same_result() {
+        let (a, step) = (0.3_f32, 0.0071_f32);
+        for length in 0..40 {
+            let (x, mut expected) = input(length);
+            ArrayMath::multiply_add_slope_scalar(a, step, &x, &mut expected, 0);
+
+            let (_, mut actual) = input(length);
+            ArrayMath::multiply_add_slope(a, step, &x, &mut actual);
+            assert_eq!(actual, expected);
+
+            #[cfg(target_arch = "x86_64")]
+            {
+                let (_, mut actual) = input(length);
+                let done = unsafe { ArrayMath::multiply_add_slope_sse(a, step, &x, &mut actual) };
+                ArrayMath::multiply_add_slope_scalar(a, step, &x, &mut actual, done);
+                assert_eq!(actual, expected);
+
+                if ArrayMath::has_avx() {
+                    let (_, mut actual) = input(length);
+                    let done =
+                        unsafe { ArrayMath::multiply_add_slope_avx(a, step, &x, &mut actual) };
+                    ArrayMath::multiply_add_slope_scalar(a, step, &x, &mut actual, done);
+                    assert_eq!(actual, expected);
+                }
+            }
+
+            #[cfg(target_arch = "aarch64")]
+            {
+                let (_, mut actual) = input(length);
+                let done = unsafe { ArrayMath::multiply_add_slope_neon(a, step, &x, &mut actual) };
+                ArrayMath::multiply_add_slope_scalar(a, step, &x, &mut actual, done);
+                assert_eq!(actual, expected);
+            }
         }
     }
 }
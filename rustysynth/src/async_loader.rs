@@ -0,0 +1,47 @@
+#![allow(dead_code)]
+
+use alloc::vec::Vec;
+use std::io;
+use std::panic;
+
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::task;
+
+// Reads the whole stream without blocking, and then parses it on the blocking threads of the runtime,
+// so that neither the IO nor the parsing of a large file stops the executor.
+#[allow(unused)]
+#[non_exhaustive]
+pub(crate) struct AsyncLoader {}
+
+impl AsyncLoader {
+    // The stream longer than the maximum size is rejected with the specified error before it is parsed.
+    pub(crate) async fn load<R, T, E, F>(
+        reader: &mut R,
+        max_size: usize,
+        too_large: E,
+        parse: F,
+    ) -> Result<T, E>
+    where
+        R: AsyncRead + Unpin,
+        T: Send + 'static,
+        E: From<io::Error> + Send + 'static,
+        F: FnOnce(&[u8]) -> Result<T, E> + Send + 'static,
+    {
+        let mut data = Vec::new();
+        reader
+            .take(max_size as u64 + 1)
+            .read_to_end(&mut data)
+            .await?;
+        if data.len() > max_size {
+            return Err(too_large);
+        }
+
+        match task::spawn_blocking(move || parse(&data)).await {
+            Ok(result) => result,
+            // The panic of the parser is the same as the synchronous one.
+            Err(err) if err.is_panic() => panic::resume_unwind(err.into_panic()),
+            Err(err) => Err(io::Error::other(err).into()),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+#![allow(dead_code)]
+
+use alloc::vec::Vec;
+
+/// Represents a breakpoint envelope which changes a parameter over time.
+///
+/// # Remarks
+///
+/// The value is linearly interpolated between the points,
+/// and held before the first point and after the last point.
+/// The points at the same time make a step, where the later pushed one takes effect after the time.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct Automation {
+    points: Vec<(f64, f32)>,
+}
+
+impl Automation {
+    /// Initializes a new empty automation.
+    pub fn new() -> Self {
+        Self { points: Vec::new() }
+    }
+
+    /// Adds a point to the automation.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time of the point in seconds.
+    /// * `value` - The value of the parameter at the time.
+    pub fn push(&mut self, time: f64, value: f32) {
+        if time.is_nan() || time < 0.0 {
+            panic!("The time must be a non-negative value.");
+        }
+
+        if !value.is_finite() {
+            panic!("The value must be a finite value.");
+        }
+
+        let index = self.points.partition_point(|point| point.0 <= time);
+        self.points.insert(index, (time, value));
+    }
+
+    /// Removes all the points.
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    /// Gets the points of the automation, sorted by time.
+    pub fn get_points(&self) -> &[(f64, f32)] {
+        &self.points[..]
+    }
+
+    /// Gets the value at the specified time.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time in seconds.
+    ///
+    /// # Remarks
+    ///
+    /// If the automation has no point, `None` is returned.
+    pub fn get_value(&self, time: f64) -> Option<f32> {
+        let index = self.points.partition_point(|point| point.0 <= time);
+        if index == 0 {
+            return self.points.first().map(|point| point.1);
+        }
+        if index == self.points.len() {
+            return self.points.last().map(|point| point.1);
+        }
+
+        let (t1, v1) = self.points[index - 1];
+        let (t2, v2) = self.points[index];
+        let x = ((time - t1) / (t2 - t1)) as f32;
+        Some(v1 + x * (v2 - v1))
+    }
+}
@@ -0,0 +1,32 @@
+/// Specifies the parameter changed by an automation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum AutomationTarget {
+    /// The master volume of the synthesizer, as a linear gain.
+    MasterVolume,
+    /// The output level of the reverb, as a linear gain.
+    ReverbLevel,
+    /// The output level of the chorus, as a linear gain.
+    ChorusLevel,
+    /// The stereo width of the master output, between 0 (mono) and 2.
+    StereoWidth,
+    /// The channel volume of the specified channel, between 0 and 1.
+    ChannelVolume(i32),
+    /// The expression of the specified channel, between 0 and 1.
+    ChannelExpression(i32),
+    /// The pan of the specified channel, between -1 (left) and 1 (right).
+    ChannelPan(i32),
+}
+
+impl AutomationTarget {
+    pub(crate) fn check(&self, channel_count: usize) {
+        if let AutomationTarget::ChannelVolume(channel)
+        | AutomationTarget::ChannelExpression(channel)
+        | AutomationTarget::ChannelPan(channel) = self
+        {
+            if !(0 <= *channel && *channel < channel_count as i32) {
+                panic!("The channel must be less than the channel count.");
+            }
+        }
+    }
+}
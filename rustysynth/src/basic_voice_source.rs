@@ -0,0 +1,181 @@
+#![allow(dead_code)]
+
+use alloc::boxed::Box;
+use core::f64::consts;
+
+use crate::basic_waveform::BasicWaveform;
+use crate::float_math::FloatMath;
+use crate::voice_source::VoiceGenerator;
+use crate::voice_source::VoiceSource;
+
+/// A simple built-in voice source, which plays a basic waveform with a linear envelope.
+///
+/// # Remarks
+///
+/// This is intended as the fallback for the presets missing from the SoundFont,
+/// so that such notes are heard with a plain tone instead of an unrelated preset.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct BasicVoiceSource {
+    waveform: BasicWaveform,
+    attack: f32,
+    release: f32,
+}
+
+impl BasicVoiceSource {
+    /// Initializes a new voice source.
+    ///
+    /// # Arguments
+    ///
+    /// * `waveform` - The waveform of the notes.
+    /// * `attack` - The time in seconds for the notes to reach the full level.
+    /// * `release` - The time in seconds for the notes to fade out after the note off.
+    pub fn new(waveform: BasicWaveform, attack: f32, release: f32) -> Self {
+        if !(attack >= 0_f32 && attack.is_finite() && release >= 0_f32 && release.is_finite()) {
+            panic!("The attack and release times must be non-negative.");
+        }
+
+        if let BasicWaveform::Fm { ratio, index } = waveform {
+            if !(ratio > 0_f32 && ratio.is_finite()) {
+                panic!("The frequency ratio of the modulator must be greater than zero.");
+            }
+            if !(index >= 0_f32 && index.is_finite()) {
+                panic!("The modulation index must be non-negative.");
+            }
+        }
+
+        Self {
+            waveform,
+            attack,
+            release,
+        }
+    }
+
+    /// Gets the waveform of the notes.
+    pub fn get_waveform(&self) -> BasicWaveform {
+        self.waveform
+    }
+
+    /// Gets the attack time in seconds.
+    pub fn get_attack(&self) -> f32 {
+        self.attack
+    }
+
+    /// Gets the release time in seconds.
+    pub fn get_release(&self) -> f32 {
+        self.release
+    }
+}
+
+impl VoiceSource for BasicVoiceSource {
+    fn start(
+        &self,
+        _bank_number: i32,
+        _patch_number: i32,
+        _key: i32,
+        velocity: i32,
+        sample_rate: i32,
+    ) -> Box<dyn VoiceGenerator> {
+        let sample_rate = sample_rate as f32;
+        let velocity = velocity as f32 / 127_f32;
+
+        Box::new(BasicGenerator {
+            waveform: self.waveform,
+            sample_rate,
+            phase: 0_f64,
+            modulator_phase: 0_f64,
+            gain: BasicGenerator::LEVEL * velocity * velocity,
+            level: 0_f32,
+            attack_step: 1_f32 / (self.attack * sample_rate).max(1_f32),
+            release_length: (self.release * sample_rate).max(1_f32),
+            release_step: 0_f32,
+            released: false,
+        })
+    }
+}
+
+// Generates a basic waveform, where the discontinuities of the square and sawtooth waves
+// are smoothed by the polynomial band-limited steps to reduce the aliasing.
+struct BasicGenerator {
+    waveform: BasicWaveform,
+    sample_rate: f32,
+
+    phase: f64,
+    modulator_phase: f64,
+
+    gain: f32,
+    level: f32,
+    attack_step: f32,
+    release_length: f32,
+    release_step: f32,
+    released: bool,
+}
+
+impl BasicGenerator {
+    // The peak level at the maximum velocity, which leaves the headroom for the chords.
+    const LEVEL: f32 = 0.3_f32;
+
+    fn poly_blep(t: f64, dt: f64) -> f64 {
+        if t < dt {
+            let t = t / dt;
+            2.0 * t - t * t - 1.0
+        } else if t > 1.0 - dt {
+            let t = (t - 1.0) / dt;
+            t * t + 2.0 * t + 1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn get_value(&mut self, dt: f64) -> f64 {
+        let t = self.phase;
+        match self.waveform {
+            BasicWaveform::Square => {
+                let value = if t < 0.5 { 1.0 } else { -1.0 };
+                let shifted = if t < 0.5 { t + 0.5 } else { t - 0.5 };
+                value + BasicGenerator::poly_blep(t, dt) - BasicGenerator::poly_blep(shifted, dt)
+            }
+            BasicWaveform::Sawtooth => 2.0 * t - 1.0 - BasicGenerator::poly_blep(t, dt),
+            BasicWaveform::Fm { ratio, index } => {
+                let modulator = FloatMath::sin_f64(2.0 * consts::PI * self.modulator_phase);
+                self.modulator_phase += ratio as f64 * dt;
+                self.modulator_phase -= FloatMath::floor_f64(self.modulator_phase);
+                FloatMath::sin_f64(2.0 * consts::PI * t + index as f64 * modulator)
+            }
+        }
+    }
+}
+
+impl VoiceGenerator for BasicGenerator {
+    fn process(&mut self, frequency: f32, block: &mut [f32]) -> bool {
+        if self.released && self.level <= 0_f32 {
+            return false;
+        }
+
+        // The frequency is limited below the Nyquist frequency.
+        let dt = (frequency / self.sample_rate).clamp(0_f32, 0.5_f32) as f64;
+
+        for value in block.iter_mut() {
+            if self.released {
+                self.level = (self.level - self.release_step).max(0_f32);
+            } else {
+                self.level = (self.level + self.attack_step).min(1_f32);
+            }
+
+            *value = self.gain * self.level * self.get_value(dt) as f32;
+
+            self.phase += dt;
+            if self.phase >= 1.0 {
+                self.phase -= 1.0;
+            }
+        }
+
+        true
+    }
+
+    fn release(&mut self) {
+        // The level falls linearly from where it is, even during the attack.
+        self.release_step = self.level.max(1_f32 / self.release_length) / self.release_length;
+        self.released = true;
+    }
+}
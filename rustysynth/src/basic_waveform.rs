@@ -0,0 +1,18 @@
+#![allow(dead_code)]
+
+/// Specifies the waveform of `BasicVoiceSource`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum BasicWaveform {
+    /// The square wave, which has only the odd harmonics.
+    Square,
+    /// The sawtooth wave, which has all the harmonics.
+    Sawtooth,
+    /// The 2-operator FM, where a sine wave modulates the phase of another sine wave.
+    Fm {
+        /// The frequency of the modulator relative to the note.
+        ratio: f32,
+        /// The modulation index, which makes the sound brighter as it increases.
+        index: f32,
+    },
+}
@@ -0,0 +1,18 @@
+#![allow(dead_code)]
+
+/// Specifies the state of a MIDI file rendered by `BatchRenderer`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum BatchFileState {
+    /// The file is waiting to be rendered.
+    Pending,
+
+    /// The file is being rendered.
+    Rendering,
+
+    /// The file has been rendered and passed to the output.
+    Completed,
+
+    /// The file could not be loaded, or the output failed.
+    Failed,
+}
@@ -0,0 +1,110 @@
+#![allow(dead_code)]
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::batch_file_state::BatchFileState;
+use crate::render_progress::RenderProgress;
+
+/// Represents the progress of a batch rendering, which can be read from another thread.
+///
+/// # Remarks
+///
+/// The progress of each file is available while it is being rendered.
+#[non_exhaustive]
+pub struct BatchProgress {
+    files: Mutex<Vec<(BatchFileState, Option<Arc<RenderProgress>>)>>,
+    completed_count: AtomicUsize,
+    failed_count: AtomicUsize,
+}
+
+impl BatchProgress {
+    pub(crate) fn new() -> Self {
+        Self {
+            files: Mutex::new(Vec::new()),
+            completed_count: AtomicUsize::new(0),
+            failed_count: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn start(&self, file_count: usize) {
+        let mut files = self.files.lock().unwrap();
+        files.clear();
+        files.resize(file_count, (BatchFileState::Pending, None));
+        self.completed_count.store(0, Ordering::SeqCst);
+        self.failed_count.store(0, Ordering::SeqCst);
+    }
+
+    pub(crate) fn set_rendering(&self, index: usize, progress: Arc<RenderProgress>) {
+        self.files.lock().unwrap()[index] = (BatchFileState::Rendering, Some(progress));
+    }
+
+    pub(crate) fn set_finished(&self, index: usize, succeeded: bool) {
+        let state = if succeeded {
+            self.completed_count.fetch_add(1, Ordering::SeqCst);
+            BatchFileState::Completed
+        } else {
+            self.failed_count.fetch_add(1, Ordering::SeqCst);
+            BatchFileState::Failed
+        };
+        self.files.lock().unwrap()[index] = (state, None);
+    }
+
+    /// Gets the number of the files in the batch.
+    ///
+    /// # Remarks
+    ///
+    /// The value is zero until the rendering starts.
+    pub fn get_file_count(&self) -> usize {
+        self.files.lock().unwrap().len()
+    }
+
+    /// Gets the number of the files rendered successfully so far.
+    pub fn get_completed_count(&self) -> usize {
+        self.completed_count.load(Ordering::SeqCst)
+    }
+
+    /// Gets the number of the files failed so far.
+    pub fn get_failed_count(&self) -> usize {
+        self.failed_count.load(Ordering::SeqCst)
+    }
+
+    /// Gets the state of the specified file.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the file in the batch.
+    pub fn get_state(&self, index: usize) -> BatchFileState {
+        self.files.lock().unwrap()[index].0
+    }
+
+    /// Gets the progress of the specified file as a value between 0 and 1.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the file in the batch.
+    pub fn get_file_ratio(&self, index: usize) -> f64 {
+        match &self.files.lock().unwrap()[index] {
+            (BatchFileState::Pending, _) => 0.0,
+            (BatchFileState::Rendering, Some(progress)) => progress.get_ratio(),
+            _ => 1.0,
+        }
+    }
+
+    /// Gets the progress of the whole batch as a value between 0 and 1.
+    ///
+    /// # Remarks
+    ///
+    /// Each file counts the same regardless of its length.
+    pub fn get_ratio(&self) -> f64 {
+        let file_count = self.get_file_count();
+        if file_count == 0 {
+            return 0.0;
+        }
+
+        let sum: f64 = (0..file_count).map(|i| self.get_file_ratio(i)).sum();
+        sum / file_count as f64
+    }
+}
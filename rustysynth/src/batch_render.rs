@@ -0,0 +1,337 @@
+#![allow(dead_code)]
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+
+use crate::batch_progress::BatchProgress;
+use crate::error::MidiFileError;
+use crate::error::SynthesizerError;
+use crate::loop_render_mode::LoopRenderMode;
+use crate::midi_render::ThreadedRender;
+use crate::normalization::Normalization;
+use crate::render_split::RenderSplit;
+use crate::render_tail::RenderTail;
+use crate::soundfont::SoundFont;
+use crate::synthesizer_settings::SynthesizerSettings;
+use crate::wave_options::WaveOptions;
+
+/// Renders many MIDI files with a shared SoundFont.
+///
+/// # Remarks
+///
+/// The files are rendered by a fixed number of workers at the same time,
+/// and each file is rendered by `ThreadedRender` with the same options.
+/// The SoundFont is loaded once and shared by all the workers.
+#[non_exhaustive]
+pub struct BatchRenderer {
+    sound_font: Arc<SoundFont>,
+    synthesizer_settings: SynthesizerSettings,
+
+    worker_count: usize,
+    loop_mode: LoopRenderMode,
+    tail: RenderTail,
+    split: RenderSplit,
+    normalization: Normalization,
+    wave_options: WaveOptions,
+    thread_pool: Option<Arc<ThreadPool>>,
+
+    progress: Arc<BatchProgress>,
+}
+
+impl BatchRenderer {
+    const DEFAULT_WORKER_COUNT: usize = 2;
+
+    /// Initializes a new batch renderer.
+    ///
+    /// # Arguments
+    ///
+    /// * `sound_font` - The SoundFont instance.
+    /// * `synthesizer_settings` - The settings for synthesis.
+    ///
+    /// # Remarks
+    ///
+    /// The settings are checked here, so that they do not fail every file in the batch.
+    pub fn new(
+        sound_font: &Arc<SoundFont>,
+        synthesizer_settings: SynthesizerSettings,
+    ) -> Result<Self, SynthesizerError> {
+        synthesizer_settings.validate()?;
+
+        Ok(Self {
+            sound_font: Arc::clone(sound_font),
+            synthesizer_settings,
+            worker_count: BatchRenderer::DEFAULT_WORKER_COUNT,
+            loop_mode: LoopRenderMode::Once,
+            tail: RenderTail::None,
+            split: RenderSplit::Track,
+            normalization: Normalization::None,
+            wave_options: WaveOptions::new(),
+            thread_pool: None,
+            progress: Arc::new(BatchProgress::new()),
+        })
+    }
+
+    /// Renders the MIDI files and passes each result to the output.
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - The paths of the MIDI files.
+    /// * `output` - The function called with the index of the file, the renderer,
+    ///   and the left and right channels, when a file has been rendered.
+    ///
+    /// # Remarks
+    ///
+    /// The result for each file is returned in the order of the files.
+    /// A file which cannot be loaded, or whose output fails, does not stop the other files.
+    /// The output is called from the workers, so the files may be passed in any order.
+    /// The renderer is passed to the output to write the loop points by `ThreadedRender::write_wave`,
+    /// and to read the diagnostics and the render report of the file.
+    pub fn render<S, F>(&self, files: &[S], output: F) -> Vec<Result<(), MidiFileError>>
+    where
+        S: AsRef<str> + Sync,
+        F: Fn(usize, &ThreadedRender, Vec<f32>, Vec<f32>) -> Result<(), io::Error> + Sync,
+    {
+        self.progress.start(files.len());
+
+        let results: Mutex<Vec<Option<Result<(), MidiFileError>>>> =
+            Mutex::new((0..files.len()).map(|_| None).collect());
+        let next = AtomicUsize::new(0);
+
+        let worker_count = self.worker_count.min(files.len());
+        thread::scope(|scope| {
+            for _ in 0..worker_count {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    if index >= files.len() {
+                        break;
+                    }
+
+                    let result = self.render_file(index, files[index].as_ref(), &output);
+                    self.progress.set_finished(index, result.is_ok());
+                    results.lock().unwrap()[index] = Some(result);
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|result| result.unwrap())
+            .collect()
+    }
+
+    /// Renders the MIDI files and writes them to the directory as WAV files.
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - The paths of the MIDI files.
+    /// * `directory` - The directory of the WAV files.
+    ///
+    /// # Remarks
+    ///
+    /// Each WAV file has the name of the MIDI file with the `wav` extension,
+    /// and is written with the WAV options of the batch.
+    /// The directory is created if it does not exist.
+    /// The files with the same name in the directory are overwritten.
+    pub fn render_to_directory<S, P>(
+        &self,
+        files: &[S],
+        directory: P,
+    ) -> Result<Vec<Result<(), MidiFileError>>, io::Error>
+    where
+        S: AsRef<str> + Sync,
+        P: AsRef<Path>,
+    {
+        let directory = directory.as_ref();
+        fs::create_dir_all(directory)?;
+
+        Ok(self.render(files, |index, renderer, left, right| {
+            let name = Path::new(files[index].as_ref())
+                .file_name()
+                .unwrap_or_default();
+            let path = directory.join(name).with_extension("wav");
+            let mut writer = BufWriter::new(File::create(path)?);
+            renderer.write_wave_with_options(&mut writer, &left, &right, &self.wave_options)
+        }))
+    }
+
+    fn render_file<F>(&self, index: usize, file: &str, output: &F) -> Result<(), MidiFileError>
+    where
+        F: Fn(usize, &ThreadedRender, Vec<f32>, Vec<f32>) -> Result<(), io::Error>,
+    {
+        let mut renderer =
+            ThreadedRender::new(&self.sound_font, file, self.synthesizer_settings.clone())?;
+        renderer.set_loop_mode(self.loop_mode);
+        renderer.set_tail(self.tail);
+        renderer.set_split(self.split);
+        renderer.set_normalization(self.normalization);
+        renderer.set_thread_pool(self.thread_pool.clone());
+
+        self.progress
+            .set_rendering(index, renderer.get_render_progress());
+        let (left, right) = renderer.render();
+        output(index, &renderer, left, right)?;
+
+        Ok(())
+    }
+
+    /// Gets the SoundFont shared by the files.
+    pub fn get_sound_font(&self) -> &Arc<SoundFont> {
+        &self.sound_font
+    }
+
+    /// Gets the number of the files rendered at the same time.
+    pub fn get_worker_count(&self) -> usize {
+        self.worker_count
+    }
+
+    /// Sets the number of the files rendered at the same time.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The number of the workers.
+    ///
+    /// # Remarks
+    ///
+    /// Each worker holds the waveform of a file until it is passed to the output,
+    /// so the value bounds the memory used by the batch.
+    /// The value must be greater than zero.
+    pub fn set_worker_count(&mut self, value: usize) {
+        if value == 0 {
+            panic!("The worker count must be greater than zero.");
+        }
+
+        self.worker_count = value;
+    }
+
+    /// Gets the loop mode of each file.
+    pub fn get_loop_mode(&self) -> LoopRenderMode {
+        self.loop_mode
+    }
+
+    /// Sets the loop mode of each file.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The loop mode, which is the same as `ThreadedRender::set_loop_mode`.
+    pub fn set_loop_mode(&mut self, value: LoopRenderMode) {
+        value.check();
+        self.loop_mode = value;
+    }
+
+    /// Gets the tail of each file.
+    pub fn get_tail(&self) -> RenderTail {
+        self.tail
+    }
+
+    /// Sets the tail of each file.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The tail, which is the same as `ThreadedRender::set_tail`.
+    pub fn set_tail(&mut self, value: RenderTail) {
+        value.check();
+        self.tail = value;
+    }
+
+    /// Gets the split mode of each file.
+    pub fn get_split(&self) -> RenderSplit {
+        self.split
+    }
+
+    /// Sets the split mode of each file.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The split mode, which is the same as `ThreadedRender::set_split`.
+    pub fn set_split(&mut self, value: RenderSplit) {
+        self.split = value;
+    }
+
+    /// Gets the normalization of each file.
+    pub fn get_normalization(&self) -> Normalization {
+        self.normalization
+    }
+
+    /// Sets the normalization of each file.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The normalization, which is the same as `ThreadedRender::set_normalization`.
+    pub fn set_normalization(&mut self, value: Normalization) {
+        value.check();
+        self.normalization = value;
+    }
+
+    /// Gets the options of the WAV files written by `render_to_directory`.
+    pub fn get_wave_options(&self) -> &WaveOptions {
+        &self.wave_options
+    }
+
+    /// Sets the options of the WAV files written by `render_to_directory`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The sample format and the broadcast WAV metadata.
+    ///
+    /// # Remarks
+    ///
+    /// The default value writes the 32-bit floating-point samples without the metadata.
+    pub fn set_wave_options(&mut self, value: WaveOptions) {
+        value.check();
+        self.wave_options = value;
+    }
+
+    /// Gets the thread pool shared by the files.
+    pub fn get_thread_pool(&self) -> Option<&Arc<ThreadPool>> {
+        self.thread_pool.as_ref()
+    }
+
+    /// Sets the thread pool shared by the files.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The thread pool, or `None` to use the global rayon pool.
+    ///
+    /// # Remarks
+    ///
+    /// The tracks of all the files being rendered run on the pool,
+    /// so the workers do not multiply the number of the threads.
+    pub fn set_thread_pool(&mut self, value: Option<Arc<ThreadPool>>) {
+        self.thread_pool = value;
+    }
+
+    /// Confines the rendering to a new thread pool with the specified number of threads.
+    ///
+    /// # Arguments
+    ///
+    /// * `thread_count` - The number of the threads.
+    ///
+    /// # Remarks
+    ///
+    /// The value must be greater than zero.
+    pub fn set_thread_count(&mut self, thread_count: usize) -> Result<(), ThreadPoolBuildError> {
+        if thread_count == 0 {
+            panic!("The thread count must be greater than zero.");
+        }
+
+        let thread_pool = ThreadPoolBuilder::new().num_threads(thread_count).build()?;
+        self.thread_pool = Some(Arc::new(thread_pool));
+
+        Ok(())
+    }
+
+    /// Gets the progress of the batch, which can be read from another thread while rendering.
+    pub fn get_progress(&self) -> Arc<BatchProgress> {
+        Arc::clone(&self.progress)
+    }
+}
@@ -0,0 +1,232 @@
+//! The helpers to measure the rendering performance reproducibly.
+//!
+//! A synthetic song is rendered with a tiny SoundFont built in memory,
+//! so the results do not depend on any external file,
+//! and can be compared between the machines, the settings and the versions of the crate.
+//!
+//! ```
+//! use rustysynth::bench;
+//! use rustysynth::bench::BenchOptions;
+//!
+//! let mut options = BenchOptions::new(44100);
+//! options.length = 1.0;
+//! let (left, right) = bench::render(&options).unwrap();
+//! assert_eq!(left.len(), 44100);
+//! ```
+
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use std::time::Duration;
+use std::time::Instant;
+
+use rayon::ThreadPoolBuilder;
+
+use crate::error::SynthesizerError;
+use crate::midifile_sequencer::MidiFileSequencer;
+use crate::sequence::Sequence;
+use crate::soundfont::SoundFont;
+use crate::synthesizer::Synthesizer;
+
+pub use crate::bench_options::BenchOptions;
+
+// The song is 120 BPM, and the pattern is repeated every bar.
+const SIXTEENTH: f64 = 0.125;
+const BAR: f64 = 16.0 * SIXTEENTH;
+
+/// Creates the SoundFont used by the benchmarks.
+///
+/// # Remarks
+///
+/// This is the SoundFont created by `SoundFont::test_bank`.
+pub fn create_sound_font() -> Arc<SoundFont> {
+    Arc::new(SoundFont::test_bank())
+}
+
+/// Creates the synthetic song used by the benchmarks.
+///
+/// # Arguments
+///
+/// * `length` - The length of the song in seconds.
+///
+/// # Remarks
+///
+/// The song has the chords, the bass, the drums and the fast arpeggios on 8 channels,
+/// which keep 20 to 30 voices active at the same time.
+pub fn create_sequence(length: f64) -> Sequence {
+    let mut sequence = Sequence::new();
+
+    // The strings, the bass, and the piano, the organ, the guitar, the brass, the flute and the lead.
+    let programs: [i32; 8] = [48, 33, 0, 16, 24, 56, 73, 81];
+    for (channel, program) in programs.iter().enumerate() {
+        let channel = channel as i32;
+        sequence.push(0.0, channel, 0xC0, *program, 0);
+        sequence.push(0.0, channel, 0xB0, 0x0A, 16 * channel % 128);
+    }
+
+    let chords: [[i32; 4]; 4] = [
+        [60, 64, 67, 71],
+        [57, 60, 64, 67],
+        [53, 57, 60, 64],
+        [55, 59, 62, 65],
+    ];
+
+    let bar_count = (length / BAR).ceil() as usize;
+    for bar in 0..bar_count {
+        let start = bar as f64 * BAR;
+        let chord = &chords[bar % chords.len()];
+
+        // The chord is held for the whole bar.
+        for key in chord.iter() {
+            push_note(&mut sequence, start, BAR, 0, *key, 80, length);
+        }
+
+        // The bass plays the eighth notes.
+        for i in 0..8 {
+            let time = start + 2.0 * i as f64 * SIXTEENTH;
+            push_note(
+                &mut sequence,
+                time,
+                SIXTEENTH,
+                1,
+                chord[0] - 24,
+                100,
+                length,
+            );
+        }
+
+        // The arpeggios play the sixteenth notes in the different orders.
+        for channel in 2..8 {
+            for i in 0..16 {
+                let time = start + i as f64 * SIXTEENTH;
+                let key = chord[(i * (channel as usize - 1)) % chord.len()] + 12 * (channel % 3);
+                push_note(
+                    &mut sequence,
+                    time,
+                    2.0 * SIXTEENTH,
+                    channel,
+                    key,
+                    64,
+                    length,
+                );
+            }
+        }
+
+        // The drums play the kick and the hi-hat.
+        for i in 0..16 {
+            let time = start + i as f64 * SIXTEENTH;
+            if i % 4 == 0 {
+                push_note(&mut sequence, time, SIXTEENTH, 9, 36, 120, length);
+            }
+            if i % 2 == 0 {
+                push_note(&mut sequence, time, SIXTEENTH, 9, 42, 80, length);
+            }
+        }
+    }
+
+    sequence
+}
+
+// The notes after the end of the song are not pushed, and the notes are cut at the end.
+fn push_note(
+    sequence: &mut Sequence,
+    time: f64,
+    duration: f64,
+    channel: i32,
+    key: i32,
+    velocity: i32,
+    length: f64,
+) {
+    if time >= length {
+        return;
+    }
+
+    sequence.push(time, channel, 0x90, key, velocity);
+    sequence.push((time + duration).min(length), channel, 0x80, key, 0);
+}
+
+/// Renders the synthetic song with the SoundFont created by `create_sound_font`.
+///
+/// # Arguments
+///
+/// * `options` - The conditions of the benchmark.
+///
+/// # Remarks
+///
+/// The result is the same for the same options, except for the order of the summation
+/// of the parallel voices.
+pub fn render(options: &BenchOptions) -> Result<(Vec<f32>, Vec<f32>), SynthesizerError> {
+    let sound_font = create_sound_font();
+    let sequence = create_sequence(options.length);
+    render_with(&sound_font, &sequence, options)
+}
+
+/// Renders a song with a SoundFont under the conditions of a benchmark.
+///
+/// # Arguments
+///
+/// * `sound_font` - The SoundFont used for synthesis.
+/// * `sequence` - The song to be rendered.
+/// * `options` - The conditions of the benchmark.
+///
+/// # Remarks
+///
+/// This allows to measure the performance with a real SoundFont,
+/// for example, in the benchmarks of an application.
+pub fn render_with(
+    sound_font: &Arc<SoundFont>,
+    sequence: &Sequence,
+    options: &BenchOptions,
+) -> Result<(Vec<f32>, Vec<f32>), SynthesizerError> {
+    let (left, right, _) = render_timed(sound_font, sequence, options)?;
+    Ok((left, right))
+}
+
+/// Measures the time to render the synthetic song.
+///
+/// # Arguments
+///
+/// * `options` - The conditions of the benchmark.
+///
+/// # Remarks
+///
+/// Only the rendering is measured,
+/// and the time to create the SoundFont, the song and the synthesizer is not included.
+/// Divide the length of the song by the result to get the speed relative to the real time.
+pub fn measure(options: &BenchOptions) -> Result<Duration, SynthesizerError> {
+    let sound_font = create_sound_font();
+    let sequence = create_sequence(options.length);
+    let (_, _, elapsed) = render_timed(&sound_font, &sequence, options)?;
+    Ok(elapsed)
+}
+
+#[allow(clippy::type_complexity)]
+fn render_timed(
+    sound_font: &Arc<SoundFont>,
+    sequence: &Sequence,
+    options: &BenchOptions,
+) -> Result<(Vec<f32>, Vec<f32>, Duration), SynthesizerError> {
+    let synthesizer = Synthesizer::new(sound_font, &options.create_settings())?;
+    let mut sequencer = MidiFileSequencer::new(synthesizer);
+    sequencer.play_sequence(sequence, false);
+
+    let sample_count = (options.sample_rate as f64 * options.length) as usize;
+    let mut left: Vec<f32> = vec![0_f32; sample_count];
+    let mut right: Vec<f32> = vec![0_f32; sample_count];
+
+    let thread_pool = options.thread_count.map(|thread_count| {
+        ThreadPoolBuilder::new()
+            .num_threads(thread_count)
+            .build()
+            .unwrap_or_else(|err| panic!("Failed to create the thread pool: {}", err))
+    });
+
+    let start = Instant::now();
+    match thread_pool {
+        Some(thread_pool) => thread_pool.install(|| sequencer.render(&mut left, &mut right)),
+        None => sequencer.render(&mut left, &mut right),
+    }
+    let elapsed = start.elapsed();
+
+    Ok((left, right, elapsed))
+}
@@ -0,0 +1,56 @@
+#![allow(dead_code)]
+
+use crate::synthesizer_settings::SynthesizerSettings;
+
+/// Specifies the conditions of a benchmark run by `bench::render`.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct BenchOptions {
+    /// The sample rate for synthesis.
+    pub sample_rate: i32,
+    /// The block size for rendering waveform.
+    pub block_size: usize,
+    /// The number of maximum polyphony.
+    pub maximum_polyphony: usize,
+    /// The value indicating whether reverb and chorus are enabled.
+    pub enable_reverb_and_chorus: bool,
+    /// The value indicating whether the voices are rendered in parallel.
+    pub parallel_voices: bool,
+    /// The number of the worker threads for the parallel voices,
+    /// or `None` to use the global rayon pool.
+    pub thread_count: Option<usize>,
+    /// The length of the rendered waveform in seconds.
+    pub length: f64,
+}
+
+impl BenchOptions {
+    const DEFAULT_LENGTH: f64 = 10.0;
+
+    /// Initializes new options with the default settings of the synthesizer.
+    ///
+    /// # Arguments
+    ///
+    /// * `sample_rate` - The sample rate for synthesis.
+    pub fn new(sample_rate: i32) -> Self {
+        let settings = SynthesizerSettings::new(sample_rate);
+
+        Self {
+            sample_rate,
+            block_size: settings.block_size,
+            maximum_polyphony: settings.maximum_polyphony,
+            enable_reverb_and_chorus: settings.enable_reverb_and_chorus,
+            parallel_voices: settings.parallel_voices,
+            thread_count: None,
+            length: BenchOptions::DEFAULT_LENGTH,
+        }
+    }
+
+    pub(crate) fn create_settings(&self) -> SynthesizerSettings {
+        let mut settings = SynthesizerSettings::new(self.sample_rate);
+        settings.block_size = self.block_size;
+        settings.maximum_polyphony = self.maximum_polyphony;
+        settings.enable_reverb_and_chorus = self.enable_reverb_and_chorus;
+        settings.parallel_voices = self.parallel_voices;
+        settings
+    }
+}
@@ -1,9 +1,11 @@
 #![allow(dead_code)]
 
-use std::f32::consts;
+use core::f32::consts;
 
+use crate::float_math::FloatMath;
 use crate::synthesizer_settings::SynthesizerSettings;
 
+#[derive(Clone)]
 #[non_exhaustive]
 pub(crate) struct BiQuadFilter {
     sample_rate: i32,
@@ -58,8 +60,8 @@ impl BiQuadFilter {
                 - BiQuadFilter::RESONANCE_PEAK_OFFSET / (1_f32 + 6_f32 * (resonance - 1_f32));
 
             let w = 2_f32 * consts::PI * cutoff_frequency / self.sample_rate as f32;
-            let cosw = w.cos();
-            let alpha = w.sin() / (2_f32 * q);
+            let cosw = FloatMath::cos(w);
+            let alpha = FloatMath::sin(w) / (2_f32 * q);
 
             let b0 = (1_f32 - cosw) / 2_f32;
             let b1 = 1_f32 - cosw;
@@ -1,13 +1,18 @@
 #![allow(dead_code)]
 
-use std::io;
-use std::io::ErrorKind;
-use std::io::Read;
-use std::io::Seek;
-use std::slice;
-use std::str;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::slice;
+use core::str;
 
 use crate::four_cc::FourCC;
+use crate::io;
+use crate::io::ErrorKind;
+use crate::io::Read;
+use crate::io::Seek;
+use crate::io::SeekFrom;
 
 #[allow(unused)]
 #[non_exhaustive]
@@ -122,9 +127,23 @@ impl BinaryReader {
         Ok(str::from_utf8(&data[0..actual_length]).unwrap().to_string())
     }
 
-    pub(crate) fn discard_data<R: Read + Seek>(reader: &mut R, size: usize) -> Result<(), io::Error> {
-        let mut data: Vec<u8> = vec![0; size];
-        reader.read_exact(&mut data)
+    pub(crate) fn discard_data<R: Read>(reader: &mut R, size: usize) -> Result<(), io::Error> {
+        // Copy to a sink instead of allocating a buffer of the given size.
+        let discarded = io::copy(&mut reader.take(size as u64), &mut io::sink())?;
+        if discarded < size as u64 {
+            return Err(io::Error::new(
+                ErrorKind::UnexpectedEof,
+                "failed to discard the whole data",
+            ));
+        }
+
+        Ok(())
+    }
+
+    // Seeks over the data, so that the skipped part is never read from the stream.
+    pub(crate) fn skip_data<R: Read + Seek>(reader: &mut R, size: usize) -> Result<(), io::Error> {
+        reader.seek(SeekFrom::Current(size as i64))?;
+        Ok(())
     }
 
     pub(crate) fn read_wave_data<R: Read>(
@@ -133,11 +152,22 @@ impl BinaryReader {
     ) -> Result<Vec<i16>, io::Error> {
         let length = size / 2;
         let mut samples: Vec<i16> = vec![0; length];
+        BinaryReader::read_samples(reader, &mut samples)?;
 
-        let ptr = samples.as_mut_ptr() as *mut u8;
-        let data = unsafe { slice::from_raw_parts_mut(ptr, size) };
-        reader.read_exact(data)?;
+        // The odd byte at the end, if any, cannot be a sample.
+        if size % 2 != 0 {
+            BinaryReader::read_u8(reader)?;
+        }
 
         Ok(samples)
     }
+
+    pub(crate) fn read_samples<R: Read>(
+        reader: &mut R,
+        samples: &mut [i16],
+    ) -> Result<(), io::Error> {
+        let ptr = samples.as_mut_ptr() as *mut u8;
+        let data = unsafe { slice::from_raw_parts_mut(ptr, 2 * samples.len()) };
+        reader.read_exact(data)
+    }
 }
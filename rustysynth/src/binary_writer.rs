@@ -0,0 +1,112 @@
+#![allow(dead_code)]
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::io;
+use crate::io::Write;
+
+use crate::four_cc::FourCC;
+
+#[allow(unused)]
+#[non_exhaustive]
+pub(crate) struct BinaryWriter {}
+
+impl BinaryWriter {
+    pub(crate) fn write_u8<W: Write>(writer: &mut W, value: u8) -> Result<(), io::Error> {
+        writer.write_all(&[value])
+    }
+
+    pub(crate) fn write_i16<W: Write>(writer: &mut W, value: i16) -> Result<(), io::Error> {
+        writer.write_all(&value.to_le_bytes())
+    }
+
+    pub(crate) fn write_u16<W: Write>(writer: &mut W, value: u16) -> Result<(), io::Error> {
+        writer.write_all(&value.to_le_bytes())
+    }
+
+    pub(crate) fn write_i32<W: Write>(writer: &mut W, value: i32) -> Result<(), io::Error> {
+        writer.write_all(&value.to_le_bytes())
+    }
+
+    pub(crate) fn write_u32<W: Write>(writer: &mut W, value: u32) -> Result<(), io::Error> {
+        writer.write_all(&value.to_le_bytes())
+    }
+
+    pub(crate) fn write_i16_big_endian<W: Write>(
+        writer: &mut W,
+        value: i16,
+    ) -> Result<(), io::Error> {
+        writer.write_all(&value.to_be_bytes())
+    }
+
+    pub(crate) fn write_i32_big_endian<W: Write>(
+        writer: &mut W,
+        value: i32,
+    ) -> Result<(), io::Error> {
+        writer.write_all(&value.to_be_bytes())
+    }
+
+    pub(crate) fn write_i32_variable_length<W: Write>(
+        writer: &mut W,
+        value: i32,
+    ) -> Result<(), io::Error> {
+        if !(0..0x1000_0000).contains(&value) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the value must fit in a variable-length quantity of 4 bytes",
+            ));
+        }
+
+        let mut buffer: [u8; 4] = [0; 4];
+        let mut count: usize = 0;
+        let mut rest = value;
+        loop {
+            buffer[count] = (rest & 127) as u8;
+            count += 1;
+            rest >>= 7;
+            if rest == 0 {
+                break;
+            }
+        }
+
+        for i in (0..count).rev() {
+            let continuation = if i > 0 { 128 } else { 0 };
+            BinaryWriter::write_u8(writer, buffer[i] | continuation)?;
+        }
+
+        Ok(())
+    }
+
+    // The string is truncated or padded with zeros to fill the length.
+    // At least one zero is always written as the terminator.
+    pub(crate) fn write_fixed_length_string<W: Write>(
+        writer: &mut W,
+        value: &str,
+        length: usize,
+    ) -> Result<(), io::Error> {
+        let mut data: Vec<u8> = vec![0; length];
+        let bytes = value.as_bytes();
+        let count = usize::min(bytes.len(), length - 1);
+        data[0..count].copy_from_slice(&bytes[0..count]);
+        writer.write_all(&data)
+    }
+
+    // The string is truncated or padded with zeros to fill the length, without the terminator,
+    // as the fixed-size text fields of the WAV chunks.
+    pub(crate) fn write_padded_string<W: Write>(
+        writer: &mut W,
+        value: &str,
+        length: usize,
+    ) -> Result<(), io::Error> {
+        let mut data: Vec<u8> = vec![0; length];
+        let bytes = value.as_bytes();
+        let count = usize::min(bytes.len(), length);
+        data[0..count].copy_from_slice(&bytes[0..count]);
+        writer.write_all(&data)
+    }
+
+    pub(crate) fn write_four_cc<W: Write>(writer: &mut W, value: &FourCC) -> Result<(), io::Error> {
+        writer.write_all(value.as_bytes())
+    }
+}
@@ -0,0 +1,65 @@
+#![allow(dead_code)]
+
+use alloc::string::String;
+
+/// Specifies the broadcast WAV metadata written in the `bext` chunk.
+///
+/// # Remarks
+///
+/// The texts should be ASCII, and the longer texts are truncated to the size of each field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BroadcastInfo {
+    /// The description of the sound, up to 256 characters.
+    pub description: String,
+    /// The name of the originator, up to 32 characters.
+    pub originator: String,
+    /// The unique reference given by the originator, up to 32 characters.
+    pub originator_reference: String,
+    /// The date of the creation in the format of "yyyy-mm-dd", or empty.
+    pub origination_date: String,
+    /// The time of the creation in the format of "hh:mm:ss", or empty.
+    pub origination_time: String,
+    /// The timecode of the first sample, in samples since midnight.
+    pub time_reference: u64,
+}
+
+impl BroadcastInfo {
+    /// Initializes a new instance of broadcast WAV metadata, where all the fields are empty.
+    pub fn new() -> Self {
+        Self {
+            description: String::new(),
+            originator: String::new(),
+            originator_reference: String::new(),
+            origination_date: String::new(),
+            origination_time: String::new(),
+            time_reference: 0,
+        }
+    }
+
+    pub(crate) fn check(&self) {
+        if !BroadcastInfo::matches(&self.origination_date, b"0000-00-00") {
+            panic!("The origination date must be in the format of yyyy-mm-dd.");
+        }
+
+        if !BroadcastInfo::matches(&self.origination_time, b"00:00:00") {
+            panic!("The origination time must be in the format of hh:mm:ss.");
+        }
+    }
+
+    // The zeros in the pattern match any digit.
+    fn matches(value: &str, pattern: &[u8]) -> bool {
+        value.is_empty()
+            || (value.len() == pattern.len()
+                && value.bytes().zip(pattern.iter()).all(|(x, y)| match y {
+                    b'0' => x.is_ascii_digit(),
+                    _ => x == *y,
+                }))
+    }
+}
+
+impl Default for BroadcastInfo {
+    fn default() -> Self {
+        BroadcastInfo::new()
+    }
+}
@@ -0,0 +1,36 @@
+#![allow(dead_code)]
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Signals a long rendering to stop from another thread.
+///
+/// # Remarks
+///
+/// The token can be cloned, and all the clones share the same state.
+/// The rendering checks the token between the render blocks,
+/// so it stops shortly after `cancel` is called.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Initializes a new token, which is not cancelled.
+    pub fn new() -> Self {
+        Self {
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Requests the rendering to stop.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Gets a value that indicates whether the cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
@@ -1,47 +1,113 @@
 #![allow(dead_code)]
 
+use alloc::sync::Arc;
+
+use crate::channel_state::ChannelState;
+use crate::controller_target::ControllerTarget;
+use crate::device_profile::DeviceProfile;
+use crate::envelope_override::EnvelopeOverride;
+use crate::filter_override::FilterOverride;
+use crate::key_split::KeySplit;
+use crate::lfo_override::LfoOverride;
+use crate::pedal_curve::PedalCurve;
+use crate::voice_source::VoiceSource;
+use crate::volume_curve::VolumeCurve;
+
+#[derive(Clone)]
 #[non_exhaustive]
 pub(crate) struct Channel {
     pub(crate) is_percussion_channel: bool,
 
     bank_number: i32,
+    pending_bank_number: Option<i32>,
     patch_number: i32,
 
     modulation: i16,
     volume: i16,
     pan: i16,
     expression: i16,
-    hold_pedal: bool,
+    pressure: u8,
+    hold_pedal: u8,
 
     reverb_send: u8,
     chorus_send: u8,
 
     rpn: i16,
     pitch_bend_range: i16,
+    note_pitch_bend_range: i16,
     coarse_tune: i16,
     fine_tune: i16,
 
     pitch_bend: f32,
+
+    // Not MIDI controllers, so they are kept on reset.
+    pub(crate) priority: i32,
+    pub(crate) modulation_target: ControllerTarget,
+    pub(crate) modulation_depth: f32,
+    pub(crate) vibrato_rate: Option<f32>,
+    pub(crate) program_override: Option<(i32, i32)>,
+    pub(crate) key_split: Option<KeySplit>,
+    pub(crate) exclusive_groups: [u8; 128],
+    pub(crate) envelope_override: Option<EnvelopeOverride>,
+    pub(crate) vibrato_lfo_override: Option<LfoOverride>,
+    pub(crate) modulation_lfo_override: Option<LfoOverride>,
+    pub(crate) filter_override: Option<FilterOverride>,
+    pub(crate) voice_source: Option<Arc<dyn VoiceSource>>,
+    pub(crate) pressure_target: ControllerTarget,
+    pub(crate) pressure_depth: f32,
+    pub(crate) volume_curve: VolumeCurve,
+    pub(crate) expression_curve: VolumeCurve,
+    pub(crate) pedal_curve: PedalCurve,
+    profile: DeviceProfile,
 }
 
 impl Channel {
-    pub(crate) fn new(is_percussion_channel: bool) -> Self {
+    // The default modulator of the SoundFont spec, which adds the vibrato up to 50 cents.
+    pub(crate) const DEFAULT_MODULATION_TARGET: ControllerTarget = ControllerTarget::Vibrato;
+    pub(crate) const DEFAULT_MODULATION_DEPTH: f32 = 50_f32;
+
+    // The channel pressure has no effect by default, as the GM2 spec.
+    pub(crate) const DEFAULT_PRESSURE_TARGET: ControllerTarget = ControllerTarget::None;
+    pub(crate) const DEFAULT_PRESSURE_DEPTH: f32 = 50_f32;
+
+    pub(crate) fn new(is_percussion_channel: bool, profile: DeviceProfile) -> Self {
         let mut channel = Self {
             is_percussion_channel,
             bank_number: 0,
+            pending_bank_number: None,
             patch_number: 0,
             modulation: 0,
             volume: 0,
             pan: 0,
             expression: 0,
-            hold_pedal: false,
+            pressure: 0,
+            hold_pedal: 0,
             reverb_send: 0,
             chorus_send: 0,
             rpn: 0,
             pitch_bend_range: 0,
+            note_pitch_bend_range: 0,
             coarse_tune: 0,
             fine_tune: 0,
             pitch_bend: 0_f32,
+            priority: 0,
+            modulation_target: Channel::DEFAULT_MODULATION_TARGET,
+            modulation_depth: Channel::DEFAULT_MODULATION_DEPTH,
+            vibrato_rate: None,
+            program_override: None,
+            key_split: None,
+            exclusive_groups: [0; 128],
+            envelope_override: None,
+            vibrato_lfo_override: None,
+            modulation_lfo_override: None,
+            filter_override: None,
+            voice_source: None,
+            pressure_target: Channel::DEFAULT_PRESSURE_TARGET,
+            pressure_depth: Channel::DEFAULT_PRESSURE_DEPTH,
+            volume_curve: VolumeCurve::Square,
+            expression_curve: VolumeCurve::Square,
+            pedal_curve: PedalCurve::Switch,
+            profile,
         };
 
         channel.reset();
@@ -51,19 +117,22 @@ impl Channel {
 
     pub(crate) fn reset(&mut self) {
         self.bank_number = if self.is_percussion_channel { 128 } else { 0 };
+        self.pending_bank_number = None;
         self.patch_number = 0;
 
         self.modulation = 0;
         self.volume = 100 << 7;
         self.pan = 64 << 7;
         self.expression = 127 << 7;
-        self.hold_pedal = false;
+        self.pressure = 0;
+        self.hold_pedal = 0;
 
-        self.reverb_send = 40;
-        self.chorus_send = 0;
+        self.reverb_send = self.profile.get_default_reverb_send();
+        self.chorus_send = self.profile.get_default_chorus_send();
 
         self.rpn = -1;
         self.pitch_bend_range = 2 << 7;
+        self.note_pitch_bend_range = 48 << 7;
         self.coarse_tune = 0;
         self.fine_tune = 8192;
 
@@ -73,14 +142,55 @@ impl Channel {
     pub(crate) fn reset_all_controllers(&mut self) {
         self.modulation = 0;
         self.expression = 127 << 7;
-        self.hold_pedal = false;
+        self.pressure = 0;
+        self.hold_pedal = 0;
 
         self.rpn = -1;
 
         self.pitch_bend = 0_f32;
     }
 
+    // Only the state changed by the MIDI messages is copied, so the settings given by code are kept.
+    pub(crate) fn restore_controllers(&mut self, other: &Channel) {
+        self.is_percussion_channel = other.is_percussion_channel;
+
+        self.bank_number = other.bank_number;
+        self.pending_bank_number = other.pending_bank_number;
+        self.patch_number = other.patch_number;
+
+        self.modulation = other.modulation;
+        self.volume = other.volume;
+        self.pan = other.pan;
+        self.expression = other.expression;
+        self.pressure = other.pressure;
+        self.hold_pedal = other.hold_pedal;
+
+        self.reverb_send = other.reverb_send;
+        self.chorus_send = other.chorus_send;
+
+        self.rpn = other.rpn;
+        self.pitch_bend_range = other.pitch_bend_range;
+        self.note_pitch_bend_range = other.note_pitch_bend_range;
+        self.coarse_tune = other.coarse_tune;
+        self.fine_tune = other.fine_tune;
+
+        self.pitch_bend = other.pitch_bend;
+    }
+
     pub(crate) fn set_bank(&mut self, value: i32) {
+        if self.is_percussion_channel && self.profile.ignores_percussion_bank_select() {
+            return;
+        }
+
+        if self.profile.latches_bank_select() {
+            self.pending_bank_number = Some(value);
+            return;
+        }
+
+        self.apply_bank(value);
+    }
+
+    fn apply_bank(&mut self, value: i32) {
         self.bank_number = value;
 
         if self.is_percussion_channel {
@@ -88,7 +198,25 @@ impl Channel {
         }
     }
 
+    // The bank selected so far is kept, and moved between the melodic and percussion banks.
+    pub(crate) fn set_percussion_channel(&mut self, value: bool) {
+        if self.is_percussion_channel == value {
+            return;
+        }
+
+        self.is_percussion_channel = value;
+        if value {
+            self.bank_number += 128;
+        } else {
+            self.bank_number -= 128;
+        }
+    }
+
     pub(crate) fn set_patch(&mut self, value: i32) {
+        if let Some(bank_number) = self.pending_bank_number.take() {
+            self.apply_bank(bank_number);
+        }
+
         self.patch_number = value;
     }
 
@@ -124,8 +252,29 @@ impl Channel {
         self.expression = (((self.expression as i32) & 0xFF80) | value) as i16;
     }
 
+    pub(crate) fn set_pressure(&mut self, value: i32) {
+        self.pressure = value as u8;
+    }
+
+    // The value between 0 and 1 is converted to the 14-bit value.
+    fn to_14bit(value: f32) -> i16 {
+        (16383_f32 * value.clamp(0_f32, 1_f32) + 0.5_f32) as i16
+    }
+
+    pub(crate) fn set_volume(&mut self, value: f32) {
+        self.volume = Channel::to_14bit(value);
+    }
+
+    pub(crate) fn set_expression(&mut self, value: f32) {
+        self.expression = Channel::to_14bit(value);
+    }
+
+    pub(crate) fn set_pan(&mut self, value: f32) {
+        self.pan = Channel::to_14bit(0.5_f32 * (value + 1_f32));
+    }
+
     pub(crate) fn set_hold_pedal(&mut self, value: i32) {
-        self.hold_pedal = value >= 64;
+        self.hold_pedal = value as u8;
     }
 
     pub(crate) fn set_reverb_send(&mut self, value: i32) {
@@ -174,8 +323,41 @@ impl Channel {
         self.patch_number
     }
 
+    // The split and the override replace the bank and the patch selected by the MIDI messages.
+    pub(crate) fn get_preset_number(&self, key: i32) -> (i32, i32) {
+        if let Some(split) = self.key_split.as_ref() {
+            return split.get_preset_number(key);
+        }
+
+        match self.program_override {
+            Some(value) => value,
+            None => (self.bank_number, self.patch_number),
+        }
+    }
+
+    // The amount in the unit of the target, which reaches the depth at the maximum.
     pub(crate) fn get_modulation(&self) -> f32 {
-        (50_f32 / 16383_f32) * self.modulation as f32
+        (self.modulation_depth / 16383_f32) * self.modulation as f32
+    }
+
+    pub(crate) fn get_pressure(&self) -> f32 {
+        (self.pressure_depth / 127_f32) * self.pressure as f32
+    }
+
+    // The total amount of the controllers routed to the target.
+    pub(crate) fn get_controller_amount(&self, target: ControllerTarget) -> f32 {
+        let mut amount = 0_f32;
+        if self.modulation_target == target {
+            amount += self.get_modulation();
+        }
+        if self.pressure_target == target {
+            amount += self.get_pressure();
+        }
+        amount
+    }
+
+    pub(crate) fn is_controller_target(&self, target: ControllerTarget) -> bool {
+        self.modulation_target == target || self.pressure_target == target
     }
 
     pub(crate) fn get_volume(&self) -> f32 {
@@ -190,8 +372,25 @@ impl Channel {
         (1_f32 / 16383_f32) * self.expression as f32
     }
 
+    pub(crate) fn get_gain(&self) -> f32 {
+        if matches!(self.volume_curve, VolumeCurve::Square)
+            && matches!(self.expression_curve, VolumeCurve::Square)
+        {
+            // According to the GM spec, the following value should be squared.
+            let ve = self.get_volume() * self.get_expression();
+            return ve * ve;
+        }
+
+        self.volume_curve.get_gain(self.volume) * self.expression_curve.get_gain(self.expression)
+    }
+
     pub(crate) fn get_hold_pedal(&self) -> bool {
-        self.hold_pedal
+        self.get_sustain_amount() >= 1_f32
+    }
+
+    // The amount of the sustain between 0 and 1, given by the hold pedal and its curve.
+    pub(crate) fn get_sustain_amount(&self) -> f32 {
+        self.pedal_curve.get_amount(self.hold_pedal)
     }
 
     pub(crate) fn get_reverb_send(&self) -> f32 {
@@ -206,6 +405,16 @@ impl Channel {
         (self.pitch_bend_range >> 7) as f32 + 0.01_f32 * (self.pitch_bend_range & 0x7F) as f32
     }
 
+    // The range of the MIDI 2.0 per-note pitch bend, which is 48 semitones by default.
+    pub(crate) fn set_note_pitch_bend_range(&mut self, semitones: i32, cents: i32) {
+        self.note_pitch_bend_range = ((semitones << 7) | cents) as i16;
+    }
+
+    pub(crate) fn get_note_pitch_bend_range(&self) -> f32 {
+        (self.note_pitch_bend_range >> 7) as f32
+            + 0.01_f32 * (self.note_pitch_bend_range & 0x7F) as f32
+    }
+
     pub(crate) fn get_tune(&self) -> f32 {
         self.coarse_tune as f32 + (1_f32 / 8192_f32) * (self.fine_tune - 8192) as f32
     }
@@ -213,4 +422,27 @@ impl Channel {
     pub(crate) fn get_pitch_bend(&self) -> f32 {
         self.get_pitch_bend_range() * self.pitch_bend
     }
+
+    pub(crate) fn get_state(&self) -> ChannelState {
+        ChannelState {
+            is_percussion_channel: self.is_percussion_channel,
+            bank_number: self.bank_number,
+            patch_number: self.patch_number,
+            modulation: self.modulation as i32,
+            volume: self.volume as i32,
+            pan: self.pan as i32,
+            expression: self.expression as i32,
+            pressure: self.pressure as i32,
+            hold_pedal: self.get_hold_pedal(),
+            hold_pedal_value: self.hold_pedal as i32,
+            reverb_send: self.reverb_send as i32,
+            chorus_send: self.chorus_send as i32,
+            pitch_bend: (8192_f32 * self.pitch_bend) as i32 + 8192,
+            rpn: self.rpn as i32,
+            pitch_bend_range: self.pitch_bend_range as i32,
+            coarse_tune: self.coarse_tune as i32,
+            fine_tune: self.fine_tune as i32,
+            priority: self.priority,
+        }
+    }
 }
@@ -0,0 +1,49 @@
+#![allow(dead_code)]
+
+/// Represents the state of a MIDI channel of the synthesizer.
+///
+/// # Remarks
+///
+/// The 14-bit controllers are given as the combination of the coarse and fine values,
+/// that is, `(coarse << 7) | fine`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChannelState {
+    /// The value indicating whether the channel is the percussion channel.
+    pub is_percussion_channel: bool,
+    /// The bank number used to find the preset. On the percussion channel, 128 is added.
+    pub bank_number: i32,
+    /// The patch number selected by the program change.
+    pub patch_number: i32,
+    /// The modulation as a 14-bit value.
+    pub modulation: i32,
+    /// The channel volume as a 14-bit value.
+    pub volume: i32,
+    /// The pan as a 14-bit value. The center is 8192.
+    pub pan: i32,
+    /// The expression as a 14-bit value.
+    pub expression: i32,
+    /// The channel pressure (aftertouch) between 0 and 127.
+    pub pressure: i32,
+    /// The value indicating whether the hold (sustain) pedal is down.
+    /// With a continuous pedal curve, this is `true` only while the pedal is fully down.
+    pub hold_pedal: bool,
+    /// The value of the hold pedal between 0 and 127.
+    pub hold_pedal_value: i32,
+    /// The reverb send between 0 and 127.
+    pub reverb_send: i32,
+    /// The chorus send between 0 and 127.
+    pub chorus_send: i32,
+    /// The pitch bend as a 14-bit value. The center is 8192.
+    pub pitch_bend: i32,
+    /// The selected RPN as a 14-bit value, or -1 if no RPN is selected.
+    pub rpn: i32,
+    /// The pitch bend range. The coarse part is in semitones, and the fine part is in cents.
+    pub pitch_bend_range: i32,
+    /// The coarse tuning in semitones.
+    pub coarse_tune: i32,
+    /// The fine tuning as a 14-bit value. The center is 8192.
+    pub fine_tune: i32,
+    /// The priority used by `VoiceStealingPolicy::LowestChannelPriority`.
+    pub priority: i32,
+}
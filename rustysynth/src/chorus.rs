@@ -1,7 +1,13 @@
 #![allow(dead_code)]
 
-use std::f64::consts;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f64::consts;
+use core::mem;
 
+use crate::float_math::FloatMath;
+
+#[derive(Clone)]
 #[non_exhaustive]
 pub(crate) struct Chorus {
     buffer_l: Vec<f32>,
@@ -20,11 +26,12 @@ impl Chorus {
         let buffer_l = vec![0_f32; ((sample_rate as f64) * (delay + depth)) as usize + 2];
         let buffer_r = vec![0_f32; ((sample_rate as f64) * (delay + depth)) as usize + 2];
 
-        let mut delay_table = vec![0_f32; ((sample_rate as f64) / frequency).round() as usize];
+        let mut delay_table =
+            vec![0_f32; FloatMath::round_f64((sample_rate as f64) / frequency) as usize];
         let delay_table_length = delay_table.len();
         for (t, input) in delay_table.iter_mut().enumerate().take(delay_table_length) {
             let phase = 2.0 * consts::PI * (t as f64) / (delay_table_length as f64);
-            *input = ((sample_rate as f64) * (delay + depth * phase.sin())) as f32;
+            *input = ((sample_rate as f64) * (delay + depth * FloatMath::sin_f64(phase))) as f32;
         }
 
         let buffer_index: usize = 0;
@@ -111,6 +118,12 @@ impl Chorus {
         }
     }
 
+    // The size of the delay lines in bytes.
+    pub(crate) fn get_memory_size(&self) -> usize {
+        let length = self.buffer_l.len() + self.buffer_r.len() + self.delay_table.len();
+        length * mem::size_of::<f32>()
+    }
+
     pub(crate) fn mute(&mut self) {
         let buffer_length = self.buffer_l.len();
 
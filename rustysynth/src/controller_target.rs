@@ -0,0 +1,19 @@
+/// Specifies what a controller of a channel modulates.
+///
+/// # Remarks
+///
+/// The vibrato and the tremolo are driven by the vibrato LFO of each voice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ControllerTarget {
+    /// The controller has no effect.
+    None,
+    /// The controller adds the vibrato, where the depth is in cents.
+    Vibrato,
+    /// The controller adds the tremolo, where the depth is in decibels.
+    Tremolo,
+    /// The controller shifts the cutoff frequency of the filter, where the depth is in cents.
+    FilterCutoff,
+    /// The controller changes the volume, where the depth is in decibels.
+    Volume,
+}
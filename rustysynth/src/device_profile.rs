@@ -0,0 +1,88 @@
+#![allow(dead_code)]
+
+use crate::soundfont_math::SoundFontMath;
+
+/// Specifies the hardware whose behavior the synthesizer approximates.
+///
+/// # Remarks
+///
+/// The profile does not change the sound of the SoundFont itself.
+/// It only changes how the MIDI messages are interpreted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DeviceProfile {
+    /// A generic GM module. This is the default.
+    GeneralMidi,
+    /// The Roland SC-55.
+    /// The velocity curve is gentler than the GM one,
+    /// the bank select takes effect at the next program change,
+    /// the bank select is ignored on the percussion channels,
+    /// and the hi-hats and some other drum notes cut each other off.
+    Sc55,
+    /// The Roland SC-88.
+    /// Same as the SC-55, except that the surdos also cut each other off.
+    Sc88,
+}
+
+impl DeviceProfile {
+    // The groups of the drum notes which cut each other off, as the GS standard kit.
+    const SC55_EXCLUSIVE_DRUM_NOTES: [&'static [i32]; 5] = [
+        &[42, 44, 46], // Hi-hats
+        &[71, 72],     // Whistles
+        &[73, 74],     // Guiros
+        &[78, 79],     // Cuicas
+        &[80, 81],     // Triangles
+    ];
+    const SC88_EXCLUSIVE_DRUM_NOTES: [&'static [i32]; 6] = [
+        &[42, 44, 46], // Hi-hats
+        &[71, 72],     // Whistles
+        &[73, 74],     // Guiros
+        &[78, 79],     // Cuicas
+        &[80, 81],     // Triangles
+        &[86, 87],     // Surdos
+    ];
+
+    pub(crate) fn velocity_to_decibels(&self, velocity: i32) -> f32 {
+        let x = velocity as f32 / 127_f32;
+        match self {
+            // The gain is the square of the velocity.
+            DeviceProfile::GeneralMidi => 2_f32 * SoundFontMath::linear_to_decibels(x),
+            DeviceProfile::Sc55 | DeviceProfile::Sc88 => {
+                1.5_f32 * SoundFontMath::linear_to_decibels(x)
+            }
+        }
+    }
+
+    // The GS defaults, which the generic GM modules also commonly use.
+    pub(crate) fn get_default_reverb_send(&self) -> u8 {
+        40
+    }
+
+    pub(crate) fn get_default_chorus_send(&self) -> u8 {
+        0
+    }
+
+    // If true, the bank select is held until the next program change.
+    pub(crate) fn latches_bank_select(&self) -> bool {
+        !matches!(self, DeviceProfile::GeneralMidi)
+    }
+
+    // If true, the drum kit is selected only by the program change.
+    pub(crate) fn ignores_percussion_bank_select(&self) -> bool {
+        !matches!(self, DeviceProfile::GeneralMidi)
+    }
+
+    // Returns a non-zero group for the drum notes which cut each other off.
+    pub(crate) fn get_exclusive_drum_group(&self, key: i32) -> i32 {
+        let groups: &[&[i32]] = match self {
+            DeviceProfile::GeneralMidi => return 0,
+            DeviceProfile::Sc55 => &DeviceProfile::SC55_EXCLUSIVE_DRUM_NOTES,
+            DeviceProfile::Sc88 => &DeviceProfile::SC88_EXCLUSIVE_DRUM_NOTES,
+        };
+
+        groups
+            .iter()
+            .position(|group| group.contains(&key))
+            .map_or(0, |i| i as i32 + 1)
+    }
+}
@@ -0,0 +1,129 @@
+#![allow(dead_code)]
+
+use alloc::string::String;
+use core::fmt;
+
+use crate::four_cc::FourCC;
+
+/// Specifies the severity of a diagnostic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum DiagnosticSeverity {
+    /// Something was ignored, but the result is not affected much.
+    Info,
+    /// Something was recovered from, and the result may differ from the intent.
+    Warning,
+    /// Something failed, and a part of the result is missing.
+    Error,
+}
+
+/// Specifies the kind of a diagnostic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DiagnosticCode {
+    /// A MIDI track has no EOT event.
+    MidiMissingEndOfTrack,
+    /// A MIDI track is shorter than its declared size.
+    MidiTruncatedTrack,
+    /// A MIDI file contains fewer tracks than declared.
+    MidiMissingTracks,
+    /// A chunk which is not a track was skipped in a MIDI file.
+    MidiUnknownChunk,
+    /// A MIDI track contains SysEx messages which are not supported and were ignored.
+    MidiSysExIgnored,
+    /// A note was started again on the same key before the previous note was turned off.
+    MidiOverlappingNotes,
+    /// A note is never turned off in a MIDI file.
+    MidiNotesWithoutOff,
+    /// The modulators in a SoundFont are not supported and were ignored.
+    SoundFontModulatorsIgnored,
+    /// The 24-bit sample data in a SoundFont does not match the 16-bit sample data and was ignored.
+    SoundFont24BitSamplesIgnored,
+    /// A SoundFont contains generators of unknown types, which were ignored.
+    SoundFontUnknownGenerator,
+    /// A chunk in a SoundFont declares a size larger than the space left for it.
+    SoundFontTruncatedChunk,
+    /// The chunks in a SoundFont do not follow the structure of the SF2 format.
+    SoundFontInvalidStructure,
+    /// A preset was not found and the fallback preset was used instead.
+    PresetNotFound,
+    /// A control change which is not supported was ignored.
+    UnsupportedController,
+    /// A track could not be rendered.
+    TrackRenderFailed,
+}
+
+/// Specifies where a diagnostic was found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DiagnosticLocation {
+    /// The location is not specific.
+    None,
+    /// The chunk with the specified ID.
+    Chunk(FourCC),
+    /// The MIDI track with the specified index.
+    Track(usize),
+    /// The MIDI channel with the specified index.
+    Channel(usize),
+    /// The preset with the specified bank and patch numbers.
+    Preset { bank_number: i32, patch_number: i32 },
+    /// The controller with the specified number in the specified MIDI channel.
+    Controller { channel: usize, number: i32 },
+}
+
+/// Represents a problem reported by the parsers, the synthesizer or the renderers.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Diagnostic {
+    pub(crate) code: DiagnosticCode,
+    pub(crate) severity: DiagnosticSeverity,
+    pub(crate) location: DiagnosticLocation,
+    pub(crate) message: String,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(
+        code: DiagnosticCode,
+        severity: DiagnosticSeverity,
+        location: DiagnosticLocation,
+        message: String,
+    ) -> Self {
+        Self {
+            code,
+            severity,
+            location,
+            message,
+        }
+    }
+
+    /// Gets the kind of the diagnostic.
+    pub fn get_code(&self) -> DiagnosticCode {
+        self.code
+    }
+
+    /// Gets the severity of the diagnostic.
+    pub fn get_severity(&self) -> DiagnosticSeverity {
+        self.severity
+    }
+
+    /// Gets where the diagnostic was found.
+    pub fn get_location(&self) -> DiagnosticLocation {
+        self.location
+    }
+
+    /// Gets the human-readable message of the diagnostic.
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            DiagnosticSeverity::Info => "info",
+            DiagnosticSeverity::Warning => "warning",
+            DiagnosticSeverity::Error => "error",
+        };
+        write!(f, "{}: {}", severity, self.message)
+    }
+}
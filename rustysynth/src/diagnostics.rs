@@ -0,0 +1,87 @@
+#![allow(dead_code)]
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::diagnostic::Diagnostic;
+use crate::diagnostic::DiagnosticCode;
+use crate::diagnostic::DiagnosticLocation;
+use crate::diagnostic::DiagnosticSeverity;
+
+/// A collection of the diagnostics reported by a subsystem.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct Diagnostics {
+    items: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub(crate) fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+
+    pub(crate) fn push(
+        &mut self,
+        code: DiagnosticCode,
+        severity: DiagnosticSeverity,
+        location: DiagnosticLocation,
+        message: String,
+    ) {
+        self.items
+            .push(Diagnostic::new(code, severity, location, message));
+    }
+
+    // Repeated problems, such as a missing preset used by many notes, are reported only once.
+    // The message is formatted only for the first report, since this is called on the audio thread.
+    pub(crate) fn push_once<F: FnOnce() -> String>(
+        &mut self,
+        code: DiagnosticCode,
+        severity: DiagnosticSeverity,
+        location: DiagnosticLocation,
+        message: F,
+    ) {
+        if !self.contains(code, location) {
+            self.push(code, severity, location, message());
+        }
+    }
+
+    pub(crate) fn extend(&mut self, other: &Diagnostics) {
+        for item in other.items.iter() {
+            if !self.contains(item.code, item.location) {
+                self.items.push(item.clone());
+            }
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    fn contains(&self, code: DiagnosticCode, location: DiagnosticLocation) -> bool {
+        self.items
+            .iter()
+            .any(|x| x.code == code && x.location == location)
+    }
+
+    /// Gets the diagnostics in the order they were reported.
+    pub fn get_items(&self) -> &[Diagnostic] {
+        &self.items[..]
+    }
+
+    /// Gets the number of the diagnostics.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Gets a value that indicates whether no diagnostic was reported.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Gets a value that indicates whether any diagnostic has the error severity.
+    pub fn has_errors(&self) -> bool {
+        self.items
+            .iter()
+            .any(|x| x.severity == DiagnosticSeverity::Error)
+    }
+}
@@ -0,0 +1,50 @@
+#![allow(dead_code)]
+
+/// Specifies the scales applied to the volume envelope of the notes on a channel.
+///
+/// # Remarks
+///
+/// The times are multiplied by the scales, so 2 makes the stage twice as long.
+/// The sustain level is multiplied by the sustain scale, and is limited to the peak.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct EnvelopeOverride {
+    /// The scale of the attack time.
+    pub attack: f32,
+    /// The scale of the decay time.
+    pub decay: f32,
+    /// The scale of the sustain level.
+    pub sustain: f32,
+    /// The scale of the release time.
+    pub release: f32,
+}
+
+impl EnvelopeOverride {
+    /// Initializes a new envelope override, which does not change the envelope.
+    pub fn new() -> Self {
+        Self {
+            attack: 1_f32,
+            decay: 1_f32,
+            sustain: 1_f32,
+            release: 1_f32,
+        }
+    }
+
+    pub(crate) fn check(&self) {
+        for value in [self.attack, self.decay, self.release] {
+            if !(value > 0_f32 && value.is_finite()) {
+                panic!("The scales of the times must be greater than zero.");
+            }
+        }
+
+        if !(self.sustain >= 0_f32 && self.sustain.is_finite()) {
+            panic!("The scale of the sustain level must be non-negative.");
+        }
+    }
+}
+
+impl Default for EnvelopeOverride {
+    fn default() -> Self {
+        EnvelopeOverride::new()
+    }
+}
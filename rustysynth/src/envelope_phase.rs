@@ -0,0 +1,19 @@
+#![allow(dead_code)]
+
+/// Specifies the phase of an envelope of a voice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EnvelopePhase {
+    /// The envelope has not started yet.
+    Delay,
+    /// The envelope is rising to the peak.
+    Attack,
+    /// The envelope is held at the peak.
+    Hold,
+    /// The envelope is falling to the sustain level.
+    Decay,
+    /// The envelope has reached the sustain level, and waits for the note off.
+    Sustain,
+    /// The envelope is falling to zero after the note off.
+    Release,
+}
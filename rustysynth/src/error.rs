@@ -1,8 +1,11 @@
-use std::error;
-use std::fmt;
-use std::io;
+use alloc::boxed::Box;
+#[cfg(feature = "std")]
+use alloc::string::String;
+use core::error;
+use core::fmt;
 
 use crate::four_cc::FourCC;
+use crate::io;
 
 /// Represents an error when initializing a synthesizer.
 #[derive(Debug)]
@@ -11,6 +14,11 @@ pub enum SynthesizerError {
     SampleRateOutOfRange(i32),
     BlockSizeOutOfRange(usize),
     MaximumPolyphonyOutOfRange(usize),
+    ChannelCountOutOfRange(usize),
+    InvalidVolumeCurve,
+    IncompatibleState,
+    InvalidExclusiveClassMode,
+    InvalidPedalCurve,
 }
 
 impl error::Error for SynthesizerError {}
@@ -35,6 +43,27 @@ impl fmt::Display for SynthesizerError {
                     value
                 )
             }
+            SynthesizerError::ChannelCountOutOfRange(value) => write!(
+                f,
+                "the number of channels must be a multiple of 16 between 16 and 128, but was {}",
+                value
+            ),
+            SynthesizerError::InvalidVolumeCurve => write!(
+                f,
+                "the volume curve must have a positive range or non-negative gains"
+            ),
+            SynthesizerError::IncompatibleState => write!(
+                f,
+                "the state was saved with a different sample rate, block size, or number of channels"
+            ),
+            SynthesizerError::InvalidExclusiveClassMode => write!(
+                f,
+                "the fade length of the exclusive class must be between 0 and 1 second"
+            ),
+            SynthesizerError::InvalidPedalCurve => write!(
+                f,
+                "the pedal curve must have the start before the end or the amounts between 0 and 1"
+            ),
         }
     }
 }
@@ -58,28 +87,49 @@ pub enum SoundFontError {
     SampleDataNotFound,
     UnsupportedSampleFormat,
     SubChunkNotFound(FourCC),
-    InvalidPresetList,
+    InvalidPresetList {
+        id: FourCC,
+        offset: u64,
+    },
     InvalidInstrumentId {
         preset_id: usize,
         instrument_id: usize,
     },
     InvalidPreset(usize),
     PresetNotFound,
-    InvalidInstrumentList,
+    InvalidInstrumentList {
+        id: FourCC,
+        offset: u64,
+    },
     InvalidSampleId {
         instrument_id: usize,
         sample_id: usize,
     },
     InvalidInstrument(usize),
     InstrumentNotFound,
-    InvalidSampleHeaderList,
-    InvalidZoneList,
-    ZoneNotFound,
-    InvalidGeneratorList,
+    InvalidSampleHeaderList {
+        id: FourCC,
+        offset: u64,
+    },
+    InvalidZoneList {
+        id: FourCC,
+        offset: u64,
+    },
+    ZoneNotFound {
+        id: FourCC,
+        offset: u64,
+    },
+    InvalidGeneratorList {
+        id: FourCC,
+        offset: u64,
+    },
+    ChunkTooLarge(FourCC),
+    FileTooLarge,
+    MergedTooLarge,
 }
 
 impl error::Error for SoundFontError {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
             SoundFontError::IoError(ref err) => Some(err),
             _ => None,
@@ -104,14 +154,17 @@ impl fmt::Display for SoundFontError {
                 expected, actual
             ),
             SoundFontError::ListContainsUnknownId(id) => {
-                write!(f, "the INFO list contains an unknown ID '{id}'")
+                write!(f, "the LIST chunk contains an unknown ID '{id}'")
             }
             SoundFontError::SampleDataNotFound => write!(f, "no valid sample data was found"),
             SoundFontError::UnsupportedSampleFormat => write!(f, "SoundFont3 is not yet supported"),
             SoundFontError::SubChunkNotFound(id) => {
                 write!(f, "the '{}' sub-chunk was not found", id)
             }
-            SoundFontError::InvalidPresetList => write!(f, "the preset list is invalid"),
+            SoundFontError::InvalidPresetList { id, offset } => write!(
+                f,
+                "the preset list in the '{id}' chunk at 0x{offset:X} is invalid"
+            ),
             SoundFontError::InvalidInstrumentId {
                 preset_id,
                 instrument_id,
@@ -123,7 +176,10 @@ impl fmt::Display for SoundFontError {
                 write!(f, "the preset with the ID '{preset_id}' has no zone")
             }
             SoundFontError::PresetNotFound => write!(f, "no valid preset was found"),
-            SoundFontError::InvalidInstrumentList => write!(f, "the instrument list is invalid"),
+            SoundFontError::InvalidInstrumentList { id, offset } => write!(
+                f,
+                "the instrument list in the '{id}' chunk at 0x{offset:X} is invalid"
+            ),
             SoundFontError::InvalidSampleId {
                 instrument_id,
                 sample_id,
@@ -135,12 +191,29 @@ impl fmt::Display for SoundFontError {
                 write!(f, "the instrument with the ID '{instrument_id}' has no zone")
             }
             SoundFontError::InstrumentNotFound => write!(f, "no valid instrument was found"),
-            SoundFontError::InvalidSampleHeaderList => {
-                write!(f, "the sample header list is invalid")
+            SoundFontError::InvalidSampleHeaderList { id, offset } => write!(
+                f,
+                "the sample header list in the '{id}' chunk at 0x{offset:X} is invalid"
+            ),
+            SoundFontError::InvalidZoneList { id, offset } => write!(
+                f,
+                "the zone list in the '{id}' chunk at 0x{offset:X} is invalid"
+            ),
+            SoundFontError::ZoneNotFound { id, offset } => write!(
+                f,
+                "no valid zone was found in the '{id}' chunk at 0x{offset:X}"
+            ),
+            SoundFontError::InvalidGeneratorList { id, offset } => write!(
+                f,
+                "the generator list in the '{id}' chunk at 0x{offset:X} is invalid"
+            ),
+            SoundFontError::ChunkTooLarge(id) => {
+                write!(f, "the '{}' chunk exceeds the size limit", id)
+            }
+            SoundFontError::FileTooLarge => write!(f, "the file exceeds the size limit"),
+            SoundFontError::MergedTooLarge => {
+                write!(f, "the merged SoundFont exceeds the limits of the SF2 format")
             }
-            SoundFontError::InvalidZoneList => write!(f, "the zone list is invalid"),
-            SoundFontError::ZoneNotFound => write!(f, "no valid zone was found"),
-            SoundFontError::InvalidGeneratorList => write!(f, "the generator list is invalid"),
         }
     }
 }
@@ -152,10 +225,16 @@ impl From<io::Error> for SoundFontError {
 }
 
 /// Represents an error when loading a MIDI file.
+///
+/// # Remarks
+///
+/// An error found while reading a track is wrapped in `TrackError`,
+/// with the offset of the event from the start of the track chunk and the tick where it occurred.
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum MidiFileError {
     IoError(io::Error),
+    #[cfg(feature = "std")]
     ThreadPoolBuild(rayon::ThreadPoolBuildError),
     InvalidChunkType {
         expected: FourCC,
@@ -165,12 +244,23 @@ pub enum MidiFileError {
     InvalidChunkData(FourCC),
     UnsupportedFormat(i16),
     InvalidTempoValue,
+    ChunkTooLarge(FourCC),
+    TooManyTracks(usize),
+    TooManyEvents,
+    FileTooLarge,
+    TrackError {
+        track: usize,
+        offset: u64,
+        tick: i32,
+        error: Box<MidiFileError>,
+    },
 }
 
 impl error::Error for MidiFileError {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
             MidiFileError::IoError(ref err) => Some(err),
+            MidiFileError::TrackError { ref error, .. } => Some(error.as_ref()),
             _ => None,
         }
     }
@@ -180,6 +270,7 @@ impl fmt::Display for MidiFileError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             MidiFileError::IoError(err) => err.fmt(f),
+            #[cfg(feature = "std")]
             MidiFileError::ThreadPoolBuild(err) => err.fmt(f),
             MidiFileError::InvalidChunkType {
                 expected,
@@ -194,6 +285,23 @@ impl fmt::Display for MidiFileError {
                 write!(f, "the format {} is not supported", format)
             }
             MidiFileError::InvalidTempoValue => write!(f, "failed to read the tempo value"),
+            MidiFileError::ChunkTooLarge(id) => {
+                write!(f, "the '{}' chunk exceeds the size limit", id)
+            }
+            MidiFileError::TooManyTracks(count) => {
+                write!(f, "the number of tracks {} exceeds the limit", count)
+            }
+            MidiFileError::TooManyEvents => write!(f, "the number of events exceeds the limit"),
+            MidiFileError::FileTooLarge => write!(f, "the file exceeds the size limit"),
+            MidiFileError::TrackError {
+                track,
+                offset,
+                tick,
+                error,
+            } => write!(
+                f,
+                "track {track}, offset 0x{offset:X}, tick {tick}: {error}"
+            ),
         }
     }
 }
@@ -204,8 +312,120 @@ impl From<io::Error> for MidiFileError {
     }
 }
 
+#[cfg(feature = "std")]
 impl From<rayon::ThreadPoolBuildError> for MidiFileError {
     fn from(err: rayon::ThreadPoolBuildError) -> Self {
         MidiFileError::ThreadPoolBuild(err)
     }
 }
+
+/// Represents a failure of a track rendered by `ThreadedRender`.
+///
+/// # Remarks
+///
+/// The track is the index of the track in the MIDI file,
+/// or the index of the channel in the channel split mode, except for `ReadFailed`.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum TrackRenderError {
+    ReadFailed { track: usize, message: String },
+    SynthesizerFailed { track: usize, message: String },
+    Panicked { track: usize, message: String },
+}
+
+#[cfg(feature = "std")]
+impl TrackRenderError {
+    /// Gets the index of the track which failed.
+    pub fn get_track(&self) -> usize {
+        match self {
+            TrackRenderError::ReadFailed { track, .. } => *track,
+            TrackRenderError::SynthesizerFailed { track, .. } => *track,
+            TrackRenderError::Panicked { track, .. } => *track,
+        }
+    }
+
+    pub(crate) fn get_message(&self) -> &str {
+        match self {
+            TrackRenderError::ReadFailed { message, .. } => message,
+            TrackRenderError::SynthesizerFailed { message, .. } => message,
+            TrackRenderError::Panicked { message, .. } => message,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for TrackRenderError {}
+
+#[cfg(feature = "std")]
+impl fmt::Display for TrackRenderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TrackRenderError::ReadFailed { track, message } => {
+                write!(f, "failed to read the track {track}: {message}")
+            }
+            TrackRenderError::SynthesizerFailed { track, message } => write!(
+                f,
+                "failed to create the synthesizer for the track {track}: {message}"
+            ),
+            TrackRenderError::Panicked { track, message } => {
+                write!(f, "the rendering of the track {track} panicked: {message}")
+            }
+        }
+    }
+}
+
+/// Represents an error when opening a live MIDI input session.
+#[cfg(feature = "midir")]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LiveMidiError {
+    InitError(midir::InitError),
+    PortNotFound(usize),
+    PortInfoError(midir::PortInfoError),
+    ConnectError(midir::ConnectErrorKind),
+}
+
+#[cfg(feature = "midir")]
+impl error::Error for LiveMidiError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            LiveMidiError::InitError(ref err) => Some(err),
+            LiveMidiError::PortInfoError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "midir")]
+impl fmt::Display for LiveMidiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LiveMidiError::InitError(err) => err.fmt(f),
+            LiveMidiError::PortNotFound(index) => {
+                write!(
+                    f,
+                    "the MIDI input port with the index '{index}' was not found"
+                )
+            }
+            LiveMidiError::PortInfoError(err) => err.fmt(f),
+            LiveMidiError::ConnectError(kind) => {
+                write!(f, "failed to connect to the MIDI input port: {kind}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "midir")]
+impl From<midir::InitError> for LiveMidiError {
+    fn from(err: midir::InitError) -> Self {
+        LiveMidiError::InitError(err)
+    }
+}
+
+#[cfg(feature = "midir")]
+impl From<midir::PortInfoError> for LiveMidiError {
+    fn from(err: midir::PortInfoError) -> Self {
+        LiveMidiError::PortInfoError(err)
+    }
+}
@@ -0,0 +1,37 @@
+#![allow(dead_code)]
+
+/// Specifies how a voice is stopped by a new note in the same exclusive class,
+/// such as an open hi-hat choked by a closed hi-hat.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[non_exhaustive]
+pub enum ExclusiveClassMode {
+    /// Stops the voice immediately, and reuses it for the new note.
+    /// This is the default mode.
+    Cut,
+
+    /// Fades out the voice in the specified length in seconds,
+    /// while the new note starts in another voice.
+    /// This avoids the click of the immediate cut, at the cost of a voice during the fade.
+    Fade(f32),
+}
+
+impl ExclusiveClassMode {
+    pub(crate) const MAX_FADE_LENGTH: f32 = 1_f32;
+
+    pub(crate) fn is_valid(&self) -> bool {
+        match self {
+            ExclusiveClassMode::Cut => true,
+            ExclusiveClassMode::Fade(length) => {
+                0_f32 <= *length && *length <= ExclusiveClassMode::MAX_FADE_LENGTH
+            }
+        }
+    }
+
+    // The fade length in samples, or zero to stop the voice immediately.
+    pub(crate) fn get_fade_length(&self, sample_rate: i32) -> usize {
+        match self {
+            ExclusiveClassMode::Cut => 0,
+            ExclusiveClassMode::Fade(length) => (length * sample_rate as f32) as usize,
+        }
+    }
+}
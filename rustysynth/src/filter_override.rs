@@ -0,0 +1,55 @@
+#![allow(dead_code)]
+
+use crate::filter_type::FilterType;
+
+/// Specifies the filter of the notes on a channel.
+///
+/// # Remarks
+///
+/// The cutoff and the resonance replace the initial values given by the SoundFont,
+/// and the cutoff is still modulated by the envelope, the LFO and the controllers.
+/// Most presets leave the cutoff near the top of the audible range,
+/// so the high-pass and band-pass filters usually need the cutoff to be set.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct FilterOverride {
+    /// The type of the filter.
+    pub filter_type: FilterType,
+    /// The cutoff frequency in hertz, or `None` to use the cutoff given by the SoundFont.
+    pub cutoff: Option<f32>,
+    /// The resonance in decibels, or `None` to use the resonance given by the SoundFont.
+    pub resonance: Option<f32>,
+}
+
+impl FilterOverride {
+    pub(crate) const MAX_RESONANCE: f32 = 96_f32;
+
+    /// Initializes a new filter override, which does not change the filter.
+    pub fn new() -> Self {
+        Self {
+            filter_type: FilterType::LowPass,
+            cutoff: None,
+            resonance: None,
+        }
+    }
+
+    pub(crate) fn check(&self) {
+        if let Some(cutoff) = self.cutoff {
+            if !(cutoff > 0_f32 && cutoff.is_finite()) {
+                panic!("The cutoff frequency must be greater than zero.");
+            }
+        }
+
+        if let Some(resonance) = self.resonance {
+            if !(0_f32..=FilterOverride::MAX_RESONANCE).contains(&resonance) {
+                panic!("The resonance must be between 0 and 96 dB.");
+            }
+        }
+    }
+}
+
+impl Default for FilterOverride {
+    fn default() -> Self {
+        FilterOverride::new()
+    }
+}
@@ -0,0 +1,19 @@
+#![allow(dead_code)]
+
+/// Specifies the filter applied to each voice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FilterType {
+    /// The 2-pole low-pass filter defined in the SoundFont specification.
+    /// This is the default filter.
+    LowPass,
+    /// The 4-pole ladder low-pass filter, which cuts the highs more steeply
+    /// and has a fatter resonance than the default one.
+    LadderLowPass,
+    /// The 2-pole state-variable low-pass filter.
+    StateVariableLowPass,
+    /// The 2-pole state-variable high-pass filter, which removes the lows below the cutoff.
+    StateVariableHighPass,
+    /// The 2-pole state-variable band-pass filter, which passes the band around the cutoff.
+    StateVariableBandPass,
+}
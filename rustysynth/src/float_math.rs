@@ -0,0 +1,77 @@
+#![allow(dead_code)]
+
+// The floating-point functions which are not available in core.
+// Without the std feature, they are provided by libm.
+#[allow(unused)]
+#[non_exhaustive]
+pub(crate) struct FloatMath {}
+
+#[cfg(feature = "std")]
+impl FloatMath {
+    pub(crate) fn sin(x: f32) -> f32 {
+        x.sin()
+    }
+
+    pub(crate) fn cos(x: f32) -> f32 {
+        x.cos()
+    }
+
+    pub(crate) fn powf(x: f32, y: f32) -> f32 {
+        x.powf(y)
+    }
+
+    pub(crate) fn exp_f64(x: f64) -> f64 {
+        x.exp()
+    }
+
+    pub(crate) fn log10(x: f32) -> f32 {
+        x.log10()
+    }
+
+    pub(crate) fn sin_f64(x: f64) -> f64 {
+        x.sin()
+    }
+
+    pub(crate) fn round_f64(x: f64) -> f64 {
+        x.round()
+    }
+
+    pub(crate) fn floor_f64(x: f64) -> f64 {
+        x.floor()
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl FloatMath {
+    pub(crate) fn sin(x: f32) -> f32 {
+        libm::sinf(x)
+    }
+
+    pub(crate) fn cos(x: f32) -> f32 {
+        libm::cosf(x)
+    }
+
+    pub(crate) fn powf(x: f32, y: f32) -> f32 {
+        libm::powf(x, y)
+    }
+
+    pub(crate) fn exp_f64(x: f64) -> f64 {
+        libm::exp(x)
+    }
+
+    pub(crate) fn log10(x: f32) -> f32 {
+        libm::log10f(x)
+    }
+
+    pub(crate) fn sin_f64(x: f64) -> f64 {
+        libm::sin(x)
+    }
+
+    pub(crate) fn round_f64(x: f64) -> f64 {
+        libm::round(x)
+    }
+
+    pub(crate) fn floor_f64(x: f64) -> f64 {
+        libm::floor(x)
+    }
+}
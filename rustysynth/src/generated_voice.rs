@@ -0,0 +1,141 @@
+#![allow(dead_code)]
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts;
+use core::mem;
+
+use crate::channel::Channel;
+use crate::float_math::FloatMath;
+use crate::soundfont_math::SoundFontMath;
+use crate::voice_source::VoiceGenerator;
+
+// A voice whose sound is generated by a voice source instead of the SoundFont.
+// The channel is applied in the same way as the SoundFont voices.
+#[non_exhaustive]
+pub(crate) struct GeneratedVoice {
+    generator: Box<dyn VoiceGenerator>,
+
+    pub(crate) channel: i32,
+    pub(crate) key: i32,
+    // The detune of the note in semitones, given by `Synthesizer::note_on_detuned`.
+    pub(crate) detune: f32,
+
+    release_requested: bool,
+    released: bool,
+    started: bool,
+
+    pub(crate) block: Vec<f32>,
+
+    pub(crate) previous_mix_gain_left: f32,
+    pub(crate) previous_mix_gain_right: f32,
+    pub(crate) current_mix_gain_left: f32,
+    pub(crate) current_mix_gain_right: f32,
+
+    pub(crate) previous_reverb_send: f32,
+    pub(crate) previous_chorus_send: f32,
+    pub(crate) current_reverb_send: f32,
+    pub(crate) current_chorus_send: f32,
+}
+
+impl GeneratedVoice {
+    pub(crate) fn new(
+        generator: Box<dyn VoiceGenerator>,
+        channel: i32,
+        key: i32,
+        block_size: usize,
+    ) -> Self {
+        Self {
+            generator,
+            channel,
+            key,
+            detune: 0_f32,
+            release_requested: false,
+            released: false,
+            started: false,
+            block: vec![0_f32; block_size],
+            previous_mix_gain_left: 0_f32,
+            previous_mix_gain_right: 0_f32,
+            current_mix_gain_left: 0_f32,
+            current_mix_gain_right: 0_f32,
+            previous_reverb_send: 0_f32,
+            previous_chorus_send: 0_f32,
+            current_reverb_send: 0_f32,
+            current_chorus_send: 0_f32,
+        }
+    }
+
+    // Reuses the voice for a new note, keeping the buffer.
+    pub(crate) fn restart(&mut self, generator: Box<dyn VoiceGenerator>, channel: i32, key: i32) {
+        self.generator = generator;
+        self.channel = channel;
+        self.key = key;
+        self.detune = 0_f32;
+        self.release_requested = false;
+        self.released = false;
+        self.started = false;
+        self.previous_mix_gain_left = 0_f32;
+        self.previous_mix_gain_right = 0_f32;
+        self.current_mix_gain_left = 0_f32;
+        self.current_mix_gain_right = 0_f32;
+        self.previous_reverb_send = 0_f32;
+        self.previous_chorus_send = 0_f32;
+        self.current_reverb_send = 0_f32;
+        self.current_chorus_send = 0_f32;
+    }
+
+    pub(crate) fn end(&mut self) {
+        self.release_requested = true;
+    }
+
+    pub(crate) fn get_memory_size(&self) -> usize {
+        mem::size_of::<GeneratedVoice>() + self.block.len() * mem::size_of::<f32>()
+    }
+
+    pub(crate) fn set_block_size(&mut self, block_size: usize) {
+        self.block.resize(block_size, 0_f32);
+    }
+
+    // Returns false if the generator has finished.
+    pub(crate) fn process(&mut self, channels: &[Channel]) -> bool {
+        let channel_info = &channels[self.channel as usize];
+
+        // The note is held while the hold pedal is pressed.
+        if self.release_requested && !self.released && !channel_info.get_hold_pedal() {
+            self.generator.release();
+            self.released = true;
+        }
+
+        let pitch =
+            self.key as f32 + channel_info.get_tune() + channel_info.get_pitch_bend() + self.detune;
+        let frequency = SoundFontMath::cents_to_hertz(100_f32 * pitch);
+
+        self.block.fill(0_f32);
+        if !self.generator.process(frequency, &mut self.block[..]) {
+            return false;
+        }
+
+        self.previous_mix_gain_left = self.current_mix_gain_left;
+        self.previous_mix_gain_right = self.current_mix_gain_right;
+        self.previous_reverb_send = self.current_reverb_send;
+        self.previous_chorus_send = self.current_chorus_send;
+
+        let mix_gain = channel_info.get_gain();
+        let angle = (consts::PI / 200_f32) * (channel_info.get_pan() + 50_f32);
+        self.current_mix_gain_left = mix_gain * FloatMath::cos(angle);
+        self.current_mix_gain_right = mix_gain * FloatMath::sin(angle);
+        self.current_reverb_send = channel_info.get_reverb_send();
+        self.current_chorus_send = channel_info.get_chorus_send();
+
+        if !self.started {
+            self.previous_mix_gain_left = self.current_mix_gain_left;
+            self.previous_mix_gain_right = self.current_mix_gain_right;
+            self.previous_reverb_send = self.current_reverb_send;
+            self.previous_chorus_send = self.current_chorus_send;
+            self.started = true;
+        }
+
+        true
+    }
+}
@@ -1,7 +1,10 @@
 #![allow(dead_code)]
 
-use std::io::Read;
+use alloc::vec::Vec;
 
+use crate::io::Read;
+
+use crate::four_cc::FourCC;
 use crate::{binary_reader::BinaryReader, error::SoundFontError};
 
 #[derive(Clone, Copy)]
@@ -24,10 +27,12 @@ impl Generator {
 
     pub(crate) fn read_from_chunk<R: Read>(
         reader: &mut R,
+        id: FourCC,
+        offset: u64,
         size: usize,
     ) -> Result<Vec<Generator>, SoundFontError> {
-        if size % 4 != 0 {
-            return Err(SoundFontError::InvalidGeneratorList);
+        if size % 4 != 0 || size == 0 {
+            return Err(SoundFontError::InvalidGeneratorList { id, offset });
         }
 
         let count = size / 4 - 1;
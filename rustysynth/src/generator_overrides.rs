@@ -0,0 +1,269 @@
+#![allow(dead_code)]
+
+use alloc::collections::BTreeMap;
+
+use crate::generator_type::GeneratorType;
+use crate::preset_lookup::PresetLookup;
+use crate::soundfont::SoundFont;
+
+// The regions whose generators are replaced.
+// The ones for all the regions are sorted before the ones for a single region,
+// so that a single region can override the value given to all the regions.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum OverrideTarget {
+    Preset {
+        bank_number: i32,
+        patch_number: i32,
+        region: Option<usize>,
+    },
+    Instrument {
+        instrument_id: usize,
+        region: Option<usize>,
+    },
+}
+
+/// Represents a set of generator values which replace the ones in a SoundFont.
+///
+/// # Remarks
+///
+/// The overrides do not modify the SoundFont they are applied to.
+/// Instead, `apply` creates a new SoundFont which shares the sample data with the original one,
+/// so that a badly-authored bank can be fixed without editing the SF2 file.
+/// The result can be passed to `Synthesizer::set_sound_font` to change the sound while playing.
+#[derive(Clone, Default)]
+#[non_exhaustive]
+pub struct GeneratorOverrides {
+    items: BTreeMap<(OverrideTarget, u16), i16>,
+}
+
+impl GeneratorOverrides {
+    /// Initializes a new empty set of overrides.
+    pub fn new() -> Self {
+        Self {
+            items: BTreeMap::new(),
+        }
+    }
+
+    /// Replaces a generator value in the preset regions.
+    ///
+    /// # Arguments
+    ///
+    /// * `bank_number` - The bank number of the preset.
+    /// * `patch_number` - The patch number of the preset.
+    /// * `region` - The index of the region in the preset, or `None` for all the regions.
+    /// * `generator_type` - The type of the generator. See `GeneratorType` for the values.
+    /// * `value` - The raw value of the generator.
+    ///
+    /// # Remarks
+    ///
+    /// As defined in the SoundFont specification, the value in a preset region is added to
+    /// the value in the instrument region, except for the key and velocity ranges.
+    /// The generators which are not allowed in the preset zones,
+    /// such as the sample offsets and the sample modes, cannot be used.
+    pub fn set_preset_generator(
+        &mut self,
+        bank_number: i32,
+        patch_number: i32,
+        region: Option<usize>,
+        generator_type: u16,
+        value: i16,
+    ) {
+        GeneratorOverrides::check_generator_type(generator_type);
+        if !GeneratorOverrides::is_preset_generator(generator_type) {
+            panic!("The generator type must be allowed in the preset zones.");
+        }
+
+        let target = OverrideTarget::Preset {
+            bank_number,
+            patch_number,
+            region,
+        };
+        self.items.insert((target, generator_type), value);
+    }
+
+    /// Replaces a generator value in the instrument regions.
+    ///
+    /// # Arguments
+    ///
+    /// * `instrument_id` - The index of the instrument in `SoundFont::get_instruments`.
+    /// * `region` - The index of the region in the instrument, or `None` for all the regions.
+    /// * `generator_type` - The type of the generator. See `GeneratorType` for the values.
+    /// * `value` - The raw value of the generator.
+    ///
+    /// # Remarks
+    ///
+    /// The sample of the region cannot be replaced.
+    pub fn set_instrument_generator(
+        &mut self,
+        instrument_id: usize,
+        region: Option<usize>,
+        generator_type: u16,
+        value: i16,
+    ) {
+        GeneratorOverrides::check_generator_type(generator_type);
+        if generator_type == GeneratorType::SAMPLE_ID {
+            panic!("The generator type must not be the sample.");
+        }
+
+        let target = OverrideTarget::Instrument {
+            instrument_id,
+            region,
+        };
+        self.items.insert((target, generator_type), value);
+    }
+
+    /// Removes a replaced generator value in the preset regions.
+    ///
+    /// # Arguments
+    ///
+    /// * `bank_number` - The bank number of the preset.
+    /// * `patch_number` - The patch number of the preset.
+    /// * `region` - The index of the region in the preset, or `None` for all the regions.
+    /// * `generator_type` - The type of the generator.
+    pub fn remove_preset_generator(
+        &mut self,
+        bank_number: i32,
+        patch_number: i32,
+        region: Option<usize>,
+        generator_type: u16,
+    ) {
+        let target = OverrideTarget::Preset {
+            bank_number,
+            patch_number,
+            region,
+        };
+        self.items.remove(&(target, generator_type));
+    }
+
+    /// Removes a replaced generator value in the instrument regions.
+    ///
+    /// # Arguments
+    ///
+    /// * `instrument_id` - The index of the instrument.
+    /// * `region` - The index of the region in the instrument, or `None` for all the regions.
+    /// * `generator_type` - The type of the generator.
+    pub fn remove_instrument_generator(
+        &mut self,
+        instrument_id: usize,
+        region: Option<usize>,
+        generator_type: u16,
+    ) {
+        let target = OverrideTarget::Instrument {
+            instrument_id,
+            region,
+        };
+        self.items.remove(&(target, generator_type));
+    }
+
+    /// Removes all the overrides.
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// Gets the number of the overrides.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Gets a value that indicates whether there is no override.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Creates a SoundFont with the generator values replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `sound_font` - The SoundFont to which the overrides are applied.
+    ///
+    /// # Remarks
+    ///
+    /// The sample data is shared with the original SoundFont, so only the presets and the instruments are copied.
+    /// The presets are matched by the exact bank and patch numbers, without the fallback of the synthesizer.
+    /// The overrides which do not match any preset, instrument or region are ignored,
+    /// so the same overrides can be applied to other versions of the SoundFont.
+    pub fn apply(&self, sound_font: &SoundFont) -> SoundFont {
+        let mut presets = sound_font.presets.clone();
+        let mut instruments = sound_font.instruments.clone();
+
+        for ((target, generator_type), value) in self.items.iter() {
+            let index = *generator_type as usize;
+            match *target {
+                OverrideTarget::Preset {
+                    bank_number,
+                    patch_number,
+                    region,
+                } => {
+                    let preset = match sound_font.preset_lookup.get(bank_number, patch_number) {
+                        Some(value) => &mut presets[value],
+                        None => continue,
+                    };
+                    for (i, preset_region) in preset.regions.iter_mut().enumerate() {
+                        if region.map_or(true, |x| x == i) {
+                            preset_region.gs[index] = *value;
+                        }
+                    }
+                }
+                OverrideTarget::Instrument {
+                    instrument_id,
+                    region,
+                } => {
+                    let instrument = match instruments.get_mut(instrument_id) {
+                        Some(value) => value,
+                        None => continue,
+                    };
+                    for (i, instrument_region) in instrument.regions.iter_mut().enumerate() {
+                        if region.map_or(true, |x| x == i) {
+                            instrument_region.gs[index] = *value;
+                        }
+                    }
+                }
+            }
+        }
+
+        SoundFont {
+            info: sound_font.info.clone(),
+            bits_per_sample: sound_font.bits_per_sample,
+            wave_data: sound_font.wave_data.clone(),
+            wave_data_24: sound_font.wave_data_24.clone(),
+            merged_wave_data: sound_font.merged_wave_data.clone(),
+            sample_headers: sound_font.sample_headers.clone(),
+            preset_lookup: PresetLookup::new(&presets),
+            presets,
+            instruments,
+            diagnostics: sound_font.diagnostics.clone(),
+        }
+    }
+
+    fn check_generator_type(generator_type: u16) {
+        if generator_type as usize >= GeneratorType::COUNT {
+            panic!("The generator type must be less than GeneratorType::COUNT.");
+        }
+
+        if generator_type == GeneratorType::INSTRUMENT {
+            panic!("The generator type must not be the instrument.");
+        }
+    }
+
+    // The generators which are only allowed in the instrument zones,
+    // and the ones which are ignored by the synthesizer in the preset zones.
+    fn is_preset_generator(generator_type: u16) -> bool {
+        !matches!(
+            generator_type,
+            GeneratorType::START_ADDRESS_OFFSET
+                | GeneratorType::END_ADDRESS_OFFSET
+                | GeneratorType::START_LOOP_ADDRESS_OFFSET
+                | GeneratorType::END_LOOP_ADDRESS_OFFSET
+                | GeneratorType::START_ADDRESS_COARSE_OFFSET
+                | GeneratorType::END_ADDRESS_COARSE_OFFSET
+                | GeneratorType::START_LOOP_ADDRESS_COARSE_OFFSET
+                | GeneratorType::END_LOOP_ADDRESS_COARSE_OFFSET
+                | GeneratorType::KEY_NUMBER
+                | GeneratorType::VELOCITY
+                | GeneratorType::SAMPLE_ID
+                | GeneratorType::SAMPLE_MODES
+                | GeneratorType::EXCLUSIVE_CLASS
+                | GeneratorType::OVERRIDING_ROOT_KEY
+        )
+    }
+}
@@ -0,0 +1,40 @@
+#![allow(dead_code)]
+
+/// Specifies how much the notes are randomized by `MidiFile::humanize`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct HumanizeOptions {
+    /// The maximum amount of the timing change in ticks.
+    pub timing: f64,
+    /// The maximum amount of the velocity change.
+    pub velocity: i32,
+    /// The seed of the random numbers, where the same seed always gives the same result.
+    pub seed: u64,
+}
+
+impl HumanizeOptions {
+    /// Initializes a new instance of humanize options, which changes nothing.
+    pub fn new() -> Self {
+        Self {
+            timing: 0.0,
+            velocity: 0,
+            seed: 0,
+        }
+    }
+
+    pub(crate) fn check(&self) {
+        if !(self.timing >= 0.0 && self.timing.is_finite()) {
+            panic!("The timing must be non-negative.");
+        }
+
+        if !(0..=127).contains(&self.velocity) {
+            panic!("The velocity must be between 0 and 127.");
+        }
+    }
+}
+
+impl Default for HumanizeOptions {
+    fn default() -> Self {
+        HumanizeOptions::new()
+    }
+}
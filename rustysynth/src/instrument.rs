@@ -1,5 +1,8 @@
 #![allow(dead_code)]
 
+use alloc::string::String;
+use alloc::vec::Vec;
+
 use crate::error::SoundFontError;
 use crate::instrument_info::InstrumentInfo;
 use crate::instrument_region::InstrumentRegion;
@@ -7,6 +10,7 @@ use crate::sample_header::SampleHeader;
 use crate::zone::Zone;
 
 /// Represents an instrument in the SoundFont.
+#[derive(Clone)]
 #[non_exhaustive]
 pub struct Instrument {
     pub(crate) name: String,
@@ -29,7 +33,10 @@ impl Instrument {
 
         let span_start = info.zone_start_index as usize;
         let span_end = span_start + zone_count as usize;
-        let zone_span = &zones[span_start..span_end];
+        let zone_span = match zones.get(span_start..span_end) {
+            Some(value) => value,
+            None => return Err(SoundFontError::InvalidInstrument(instrument_id)),
+        };
         let regions = InstrumentRegion::create(instrument_id, zone_span, samples)?;
 
         Ok(Self { name, regions })
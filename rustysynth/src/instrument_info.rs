@@ -1,9 +1,13 @@
 #![allow(dead_code)]
 
-use std::io::Read;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::io::Read;
 
 use crate::binary_reader::BinaryReader;
 use crate::error::SoundFontError;
+use crate::four_cc::FourCC;
 
 #[non_exhaustive]
 pub(crate) struct InstrumentInfo {
@@ -26,10 +30,12 @@ impl InstrumentInfo {
 
     pub(crate) fn read_from_chunk<R: Read>(
         reader: &mut R,
+        id: FourCC,
+        offset: u64,
         size: usize,
     ) -> Result<Vec<InstrumentInfo>, SoundFontError> {
-        if size % 22 != 0 {
-            return Err(SoundFontError::InvalidInstrumentList);
+        if size % 22 != 0 || size == 0 {
+            return Err(SoundFontError::InvalidInstrumentList { id, offset });
         }
 
         let count = size / 22;
@@ -1,10 +1,13 @@
 #![allow(dead_code)]
 
+use alloc::vec::Vec;
+
 use crate::error::SoundFontError;
 use crate::generator::Generator;
 use crate::generator_type::GeneratorType;
 use crate::loop_mode::LoopMode;
 use crate::sample_header::SampleHeader;
+use crate::soundfont::SoundFont;
 use crate::soundfont_math::SoundFontMath;
 use crate::zone::Zone;
 
@@ -19,9 +22,11 @@ fn set_parameter(gs: &mut [i16; GeneratorType::COUNT], generator: &Generator) {
 
 /// Represents an instrument region.
 /// An instrument region contains all the parameters necessary to synthesize a note.
+#[derive(Clone)]
 #[non_exhaustive]
 pub struct InstrumentRegion {
     pub(crate) gs: [i16; GeneratorType::COUNT],
+    pub(crate) sample: usize,
     pub(crate) sample_start: i32,
     pub(crate) sample_end: i32,
     pub(crate) sample_start_loop: i32,
@@ -38,26 +43,7 @@ impl InstrumentRegion {
         local: &Zone,
         samples: &[SampleHeader],
     ) -> Result<Self, SoundFontError> {
-        let mut gs: [i16; GeneratorType::COUNT] = [0; GeneratorType::COUNT];
-        gs[GeneratorType::INITIAL_FILTER_CUTOFF_FREQUENCY as usize] = 13500;
-        gs[GeneratorType::DELAY_MODULATION_LFO as usize] = -12000;
-        gs[GeneratorType::DELAY_VIBRATO_LFO as usize] = -12000;
-        gs[GeneratorType::DELAY_MODULATION_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::ATTACK_MODULATION_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::HOLD_MODULATION_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::DECAY_MODULATION_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::RELEASE_MODULATION_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::DELAY_VOLUME_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::ATTACK_VOLUME_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::HOLD_VOLUME_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::DECAY_VOLUME_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::RELEASE_VOLUME_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::KEY_RANGE as usize] = 0x7F00;
-        gs[GeneratorType::VELOCITY_RANGE as usize] = 0x7F00;
-        gs[GeneratorType::KEY_NUMBER as usize] = -1;
-        gs[GeneratorType::VELOCITY as usize] = -1;
-        gs[GeneratorType::SCALE_TUNING as usize] = 100;
-        gs[GeneratorType::OVERRIDING_ROOT_KEY as usize] = -1;
+        let mut gs = Self::default_generators();
 
         for generator in global.generators.iter() {
             set_parameter(&mut gs, generator);
@@ -67,7 +53,8 @@ impl InstrumentRegion {
             set_parameter(&mut gs, generator);
         }
 
-        let sample_id = gs[GeneratorType::SAMPLE_ID as usize] as usize;
+        // The generator value is unsigned.
+        let sample_id = gs[GeneratorType::SAMPLE_ID as usize] as u16 as usize;
         if sample_id >= samples.len() {
             return Err(SoundFontError::InvalidSampleId {
                 instrument_id,
@@ -78,6 +65,7 @@ impl InstrumentRegion {
 
         Ok(Self {
             gs,
+            sample: sample_id,
             sample_start: sample.start,
             sample_end: sample.end,
             sample_start_loop: sample.start_loop,
@@ -88,6 +76,32 @@ impl InstrumentRegion {
         })
     }
 
+    // The default values of the generators, which are used when a zone does not specify them.
+    pub(crate) fn default_generators() -> [i16; GeneratorType::COUNT] {
+        let mut gs: [i16; GeneratorType::COUNT] = [0; GeneratorType::COUNT];
+        gs[GeneratorType::INITIAL_FILTER_CUTOFF_FREQUENCY as usize] = 13500;
+        gs[GeneratorType::DELAY_MODULATION_LFO as usize] = -12000;
+        gs[GeneratorType::DELAY_VIBRATO_LFO as usize] = -12000;
+        gs[GeneratorType::DELAY_MODULATION_ENVELOPE as usize] = -12000;
+        gs[GeneratorType::ATTACK_MODULATION_ENVELOPE as usize] = -12000;
+        gs[GeneratorType::HOLD_MODULATION_ENVELOPE as usize] = -12000;
+        gs[GeneratorType::DECAY_MODULATION_ENVELOPE as usize] = -12000;
+        gs[GeneratorType::RELEASE_MODULATION_ENVELOPE as usize] = -12000;
+        gs[GeneratorType::DELAY_VOLUME_ENVELOPE as usize] = -12000;
+        gs[GeneratorType::ATTACK_VOLUME_ENVELOPE as usize] = -12000;
+        gs[GeneratorType::HOLD_VOLUME_ENVELOPE as usize] = -12000;
+        gs[GeneratorType::DECAY_VOLUME_ENVELOPE as usize] = -12000;
+        gs[GeneratorType::RELEASE_VOLUME_ENVELOPE as usize] = -12000;
+        gs[GeneratorType::KEY_RANGE as usize] = 0x7F00;
+        gs[GeneratorType::VELOCITY_RANGE as usize] = 0x7F00;
+        gs[GeneratorType::KEY_NUMBER as usize] = -1;
+        gs[GeneratorType::VELOCITY as usize] = -1;
+        gs[GeneratorType::SCALE_TUNING as usize] = 100;
+        gs[GeneratorType::OVERRIDING_ROOT_KEY as usize] = -1;
+
+        gs
+    }
+
     pub(crate) fn create(
         instrument_id: usize,
         zones: &[Zone],
@@ -385,6 +399,32 @@ impl InstrumentRegion {
     }
 
     pub fn get_sample_id(&self) -> usize {
-        self.gs[GeneratorType::SAMPLE_ID as usize] as usize
+        self.sample
+    }
+
+    /// Gets the sample header of the region.
+    ///
+    /// # Arguments
+    ///
+    /// * `sound_font` - The SoundFont which contains the region.
+    pub fn get_sample<'a>(&self, sound_font: &'a SoundFont) -> &'a SampleHeader {
+        &sound_font.sample_headers[self.get_sample_id()]
+    }
+
+    /// Gets the raw value of the generator, after the global zone is applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `generator_type` - The type of the generator. See `GeneratorType` for the values.
+    ///
+    /// # Remarks
+    ///
+    /// Unlike the other getters, the value is not converted to any unit.
+    pub fn get_generator(&self, generator_type: u16) -> i16 {
+        if generator_type as usize >= GeneratorType::COUNT {
+            panic!("The generator type must be less than GeneratorType::COUNT.");
+        }
+
+        self.gs[generator_type as usize]
     }
 }
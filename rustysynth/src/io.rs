@@ -0,0 +1,253 @@
+// The readers and writers used by the parsers.
+// With the std feature, they are the ones of std::io.
+// Without it, a minimal subset of std::io is provided here,
+// so that the SoundFont and MIDI parsers work with byte slices.
+
+#[cfg(feature = "std")]
+pub(crate) use std::io::*;
+
+#[cfg(not(feature = "std"))]
+pub(crate) use self::no_std_io::*;
+
+#[cfg(not(feature = "std"))]
+mod no_std_io {
+    use alloc::vec::Vec;
+    use core::cmp;
+    use core::fmt;
+
+    /// Specifies the kind of an I/O error.
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    #[non_exhaustive]
+    pub enum ErrorKind {
+        InvalidData,
+        InvalidInput,
+        UnexpectedEof,
+        WriteZero,
+        Unsupported,
+    }
+
+    /// Represents an I/O error.
+    #[derive(Debug)]
+    pub struct Error {
+        kind: ErrorKind,
+        message: &'static str,
+    }
+
+    impl Error {
+        pub fn new(kind: ErrorKind, message: &'static str) -> Self {
+            Self { kind, message }
+        }
+
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+
+    impl core::error::Error for Error {}
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.message)
+        }
+    }
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    pub enum SeekFrom {
+        Start(u64),
+        End(i64),
+        Current(i64),
+    }
+
+    pub trait Read {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+        fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.read(buf)? {
+                    0 => {
+                        return Err(Error::new(
+                            ErrorKind::UnexpectedEof,
+                            "failed to fill whole buffer",
+                        ))
+                    }
+                    n => buf = &mut buf[n..],
+                }
+            }
+            Ok(())
+        }
+
+        fn read_to_end(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
+            let mut chunk = [0_u8; 4096];
+            let mut total: usize = 0;
+            loop {
+                match self.read(&mut chunk)? {
+                    0 => return Ok(total),
+                    n => {
+                        buf.extend_from_slice(&chunk[..n]);
+                        total += n;
+                    }
+                }
+            }
+        }
+
+        fn take(self, limit: u64) -> Take<Self>
+        where
+            Self: Sized,
+        {
+            Take { inner: self, limit }
+        }
+    }
+
+    pub trait Seek {
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64>;
+
+        fn stream_position(&mut self) -> Result<u64> {
+            self.seek(SeekFrom::Current(0))
+        }
+    }
+
+    pub trait Write {
+        fn write(&mut self, buf: &[u8]) -> Result<usize>;
+
+        fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.write(buf)? {
+                    0 => {
+                        return Err(Error::new(
+                            ErrorKind::WriteZero,
+                            "failed to write whole buffer",
+                        ))
+                    }
+                    n => buf = &buf[n..],
+                }
+            }
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<R: Read + ?Sized> Read for &mut R {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            (**self).read(buf)
+        }
+    }
+
+    impl<S: Seek + ?Sized> Seek for &mut S {
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+            (**self).seek(pos)
+        }
+    }
+
+    impl<W: Write + ?Sized> Write for &mut W {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            (**self).write(buf)
+        }
+    }
+
+    impl Read for &[u8] {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let n = cmp::min(buf.len(), self.len());
+            buf[..n].copy_from_slice(&self[..n]);
+            *self = &self[n..];
+            Ok(n)
+        }
+    }
+
+    impl Write for Vec<u8> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+    }
+
+    pub struct Take<R> {
+        inner: R,
+        limit: u64,
+    }
+
+    impl<R: Read> Read for Take<R> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let max = cmp::min(buf.len() as u64, self.limit) as usize;
+            let n = self.inner.read(&mut buf[..max])?;
+            self.limit -= n as u64;
+            Ok(n)
+        }
+    }
+
+    pub struct Sink;
+
+    pub fn sink() -> Sink {
+        Sink
+    }
+
+    impl Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            Ok(buf.len())
+        }
+    }
+
+    pub fn copy<R: Read + ?Sized, W: Write + ?Sized>(
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<u64> {
+        let mut chunk = [0_u8; 4096];
+        let mut total: u64 = 0;
+        loop {
+            match reader.read(&mut chunk)? {
+                0 => return Ok(total),
+                n => {
+                    writer.write_all(&chunk[..n])?;
+                    total += n as u64;
+                }
+            }
+        }
+    }
+
+    pub struct Cursor<T> {
+        inner: T,
+        position: u64,
+    }
+
+    impl<T> Cursor<T> {
+        pub fn new(inner: T) -> Self {
+            Self { inner, position: 0 }
+        }
+    }
+
+    impl<T: AsRef<[u8]>> Read for Cursor<T> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let data = self.inner.as_ref();
+            let start = cmp::min(self.position, data.len() as u64) as usize;
+            let n = (&data[start..]).read(buf)?;
+            self.position += n as u64;
+            Ok(n)
+        }
+    }
+
+    impl<T: AsRef<[u8]>> Seek for Cursor<T> {
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+            let (base, offset) = match pos {
+                SeekFrom::Start(n) => {
+                    self.position = n;
+                    return Ok(n);
+                }
+                SeekFrom::End(n) => (self.inner.as_ref().len() as u64, n),
+                SeekFrom::Current(n) => (self.position, n),
+            };
+            match base.checked_add_signed(offset) {
+                Some(n) => {
+                    self.position = n;
+                    Ok(n)
+                }
+                None => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "invalid seek to a negative or overflowing position",
+                )),
+            }
+        }
+    }
+}
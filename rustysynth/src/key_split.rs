@@ -0,0 +1,48 @@
+#![allow(dead_code)]
+
+/// Specifies the presets played below and above the split point of a channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct KeySplit {
+    /// The lowest key played by the upper preset.
+    pub key: i32,
+    /// The bank number and the program number of the preset for the keys below the split point.
+    pub lower: (i32, i32),
+    /// The bank number and the program number of the preset for the split point and the keys above.
+    pub upper: (i32, i32),
+}
+
+impl KeySplit {
+    /// Initializes a new key split.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The lowest key played by the upper preset.
+    /// * `lower` - The bank number and the program number of the preset for the keys below the split point.
+    /// * `upper` - The bank number and the program number of the preset for the split point and the keys above.
+    ///
+    /// # Remarks
+    ///
+    /// The bank number is that of the SoundFont, which is 128 for the percussion presets.
+    pub fn new(key: i32, lower: (i32, i32), upper: (i32, i32)) -> Self {
+        Self { key, lower, upper }
+    }
+
+    pub(crate) fn check(&self) {
+        if !(0..128).contains(&self.key) {
+            panic!("The split key must be between 0 and 127.");
+        }
+
+        if !(0..128).contains(&self.lower.1) || !(0..128).contains(&self.upper.1) {
+            panic!("The program must be between 0 and 127.");
+        }
+    }
+
+    pub(crate) fn get_preset_number(&self, key: i32) -> (i32, i32) {
+        if key < self.key {
+            self.lower
+        } else {
+            self.upper
+        }
+    }
+}
@@ -0,0 +1,69 @@
+#![allow(dead_code)]
+
+use core::f32::consts;
+
+use crate::float_math::FloatMath;
+use crate::synthesizer_settings::SynthesizerSettings;
+
+// The linear 4-pole ladder low-pass filter, discretized with the trapezoidal integrators.
+#[derive(Clone)]
+#[non_exhaustive]
+pub(crate) struct LadderFilter {
+    sample_rate: i32,
+
+    g: f32,
+    feedback: f32,
+
+    s: [f32; 4],
+}
+
+impl LadderFilter {
+    // The feedback is limited below 4 to keep the filter from self-oscillating.
+    const MAX_FEEDBACK: f32 = 3.9_f32;
+
+    pub(crate) fn new(settings: &SynthesizerSettings) -> Self {
+        Self {
+            sample_rate: settings.sample_rate,
+            g: 0_f32,
+            feedback: 0_f32,
+            s: [0_f32; 4],
+        }
+    }
+
+    pub(crate) fn clear_buffer(&mut self) {
+        self.s = [0_f32; 4];
+    }
+
+    pub(crate) fn set_low_pass_filter(&mut self, cutoff_frequency: f32, resonance: f32) {
+        let cutoff_frequency = cutoff_frequency.min(0.49_f32 * self.sample_rate as f32);
+        let w = consts::PI * cutoff_frequency / self.sample_rate as f32;
+        self.g = FloatMath::sin(w) / FloatMath::cos(w);
+
+        // With the passband gain compensated, the peak height at the cutoff is about the resonance.
+        self.feedback =
+            (4_f32 * (1_f32 - 1_f32 / resonance)).clamp(0_f32, LadderFilter::MAX_FEEDBACK);
+    }
+
+    pub(crate) fn process(&mut self, block: &mut [f32]) {
+        let a = self.g / (1_f32 + self.g);
+        let b = 1_f32 / (1_f32 + self.g);
+        let a4 = a * a * a * a;
+        let k = self.feedback;
+
+        for input in block.iter_mut() {
+            // Solve the feedback loop from the output estimated by the current states.
+            let sum = b * (a * (a * (a * self.s[0] + self.s[1]) + self.s[2]) + self.s[3]);
+            let output = (a4 * *input + sum) / (1_f32 + k * a4);
+
+            let mut u = *input - k * output;
+            for s in self.s.iter_mut() {
+                let v = a * (u - *s);
+                let y = v + *s;
+                *s = y + v;
+                u = y;
+            }
+
+            *input = (1_f32 + k) * u;
+        }
+    }
+}
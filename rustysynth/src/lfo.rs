@@ -2,6 +2,7 @@
 
 use crate::synthesizer_settings::SynthesizerSettings;
 
+#[derive(Clone)]
 #[non_exhaustive]
 pub(crate) struct Lfo {
     sample_rate: i32,
@@ -44,6 +45,10 @@ impl Lfo {
         }
     }
 
+    pub(crate) fn set_block_size(&mut self, block_size: usize) {
+        self.block_size = block_size;
+    }
+
     pub(crate) fn process(&mut self) {
         if !self.active {
             return;
@@ -0,0 +1,73 @@
+#![allow(dead_code)]
+
+/// Specifies the rate of an LFO.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum LfoRate {
+    /// The frequency in hertz.
+    Hertz(f32),
+
+    /// The length of a cycle in beats, which follows the tempo of the synthesizer.
+    /// For example, 0.25 gives a sixteenth note cycle, and 4 gives a whole note cycle in 4/4.
+    Beats(f32),
+}
+
+impl LfoRate {
+    pub(crate) fn is_valid(&self) -> bool {
+        let value = match self {
+            LfoRate::Hertz(value) => *value,
+            LfoRate::Beats(value) => *value,
+        };
+        value > 0_f32 && value.is_finite()
+    }
+
+    // The frequency in hertz at the specified tempo in beats per minute.
+    pub(crate) fn get_frequency(&self, tempo: f64) -> f32 {
+        match self {
+            LfoRate::Hertz(value) => *value,
+            LfoRate::Beats(value) => (tempo / (60.0 * *value as f64)) as f32,
+        }
+    }
+}
+
+/// Specifies the rate and the depth of an LFO of the notes on a channel.
+///
+/// # Remarks
+///
+/// The depth scales the amounts given by the SoundFont, so 0 disables the LFO and 2 doubles it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct LfoOverride {
+    /// The rate, or `None` to use the rate given by the SoundFont.
+    pub rate: Option<LfoRate>,
+    /// The scale of the depth.
+    pub depth: f32,
+}
+
+impl LfoOverride {
+    /// Initializes a new LFO override, which does not change the LFO.
+    pub fn new() -> Self {
+        Self {
+            rate: None,
+            depth: 1_f32,
+        }
+    }
+
+    pub(crate) fn check(&self) {
+        if let Some(rate) = self.rate.as_ref() {
+            if !rate.is_valid() {
+                panic!("The rate of the LFO must be greater than zero.");
+            }
+        }
+
+        if !(self.depth >= 0_f32 && self.depth.is_finite()) {
+            panic!("The scale of the depth must be non-negative.");
+        }
+    }
+}
+
+impl Default for LfoOverride {
+    fn default() -> Self {
+        LfoOverride::new()
+    }
+}
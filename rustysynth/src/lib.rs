@@ -1,68 +1,323 @@
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("Either the std or the libm feature must be enabled.");
+
+#[cfg(feature = "std")]
+pub mod bench;
+pub mod prelude;
+
+mod device_profile;
+mod diagnostic;
+mod diagnostics;
 mod error;
 
+#[cfg(feature = "zip")]
+mod archive;
 mod array_math;
+#[cfg(feature = "tokio")]
+mod async_loader;
+mod automation;
+mod automation_target;
 mod binary_reader;
+mod binary_writer;
+mod float_math;
 mod four_cc;
+mod io;
+mod parallel;
+mod parser_limits;
 mod read_counter;
+mod stopwatch;
+mod test_bank;
+mod trace;
 
 mod generator;
+mod generator_overrides;
 mod generator_type;
 mod instrument;
 mod instrument_info;
 mod instrument_region;
 mod loop_mode;
+#[cfg(feature = "std")]
+mod loop_render_mode;
 mod preset;
 mod preset_info;
+mod preset_lookup;
+mod preset_profile;
 mod preset_region;
 mod sample_header;
+mod sound_bank_set;
 mod soundfont;
 mod soundfont_info;
+mod soundfont_inspector;
 mod soundfont_math;
 mod soundfont_parameters;
 mod soundfont_sampledata;
 mod soundfont_version;
+mod soundfont_writer;
 mod zone;
 mod zone_info;
 
+mod basic_voice_source;
+mod basic_waveform;
 mod bi_quad_filter;
+mod cancellation_token;
 mod channel;
+mod channel_state;
+mod controller_target;
+mod envelope_override;
+mod envelope_phase;
 mod envelope_stage;
+mod exclusive_class_mode;
+mod filter_override;
+mod filter_type;
+mod generated_voice;
+mod key_split;
+mod ladder_filter;
 mod lfo;
+mod lfo_override;
 mod modulation_envelope;
 mod oscillator;
+mod pedal_curve;
 mod region_ex;
 mod region_pair;
+mod release_layer;
+mod sample_voice;
+mod state_variable_filter;
 mod synthesizer;
 mod synthesizer_settings;
+mod synthesizer_state;
+mod synthesizer_statistics;
 mod voice;
 mod voice_collection;
+mod voice_event;
+mod voice_events;
+mod voice_info;
+mod voice_source;
+mod voice_stealing_policy;
+mod volume_curve;
 mod volume_envelope;
+mod wave_data;
 
+mod arpeggiator;
+mod arpeggiator_pattern;
+mod broadcast_info;
+mod humanize_options;
+mod lyric_syllable;
+mod lyrics;
+mod metronome;
+mod midi_event;
+mod midi_message;
 mod midifile;
+mod midifile_analysis;
 mod midifile_looptype;
+mod midifile_options;
 mod midifile_sequencer;
+mod midifile_transform;
+mod midifile_warning;
+mod midifile_writer;
+mod sequence;
+mod sequencer_loop_mode;
+mod tempo_map;
+mod time_stretch;
+mod ump_message;
+mod wave_format;
+mod wave_options;
+mod wave_writer;
 
+#[cfg(feature = "std")]
+mod batch_file_state;
+#[cfg(feature = "std")]
+mod batch_progress;
+#[cfg(feature = "std")]
+mod batch_render;
+#[cfg(feature = "std")]
+mod bench_options;
+#[cfg(feature = "std")]
+mod loudness;
+#[cfg(feature = "std")]
 mod midi_render;
+#[cfg(feature = "std")]
+mod normalization;
+#[cfg(feature = "std")]
+mod playlist_render;
+#[cfg(feature = "std")]
+mod playlist_transition;
+#[cfg(feature = "std")]
+mod progressive_render;
+#[cfg(feature = "std")]
+mod render_analysis;
+#[cfg(feature = "std")]
+mod render_cache;
+#[cfg(feature = "std")]
+mod render_progress;
+#[cfg(feature = "std")]
+mod render_report;
+#[cfg(feature = "std")]
+mod render_split;
+#[cfg(feature = "std")]
+mod render_tail;
+#[cfg(feature = "std")]
+mod resampler;
+#[cfg(feature = "std")]
+mod track_error_policy;
+#[cfg(feature = "std")]
+mod track_mix;
+#[cfg(feature = "std")]
+mod track_render;
+#[cfg(feature = "std")]
+mod track_segment;
+
+#[cfg(feature = "midir")]
+mod live_midi_session;
+
+#[cfg(feature = "opl")]
+mod opl_gm_bank;
+#[cfg(feature = "opl")]
+mod opl_operator;
+#[cfg(feature = "opl")]
+mod opl_patch;
+#[cfg(feature = "opl")]
+mod opl_voice_source;
 
 mod chorus;
 mod reverb;
 
+pub use self::arpeggiator::Arpeggiator;
+pub use self::arpeggiator_pattern::ArpeggiatorPattern;
+pub use self::automation::Automation;
+pub use self::automation_target::AutomationTarget;
+pub use self::basic_voice_source::BasicVoiceSource;
+pub use self::basic_waveform::BasicWaveform;
+#[cfg(feature = "std")]
+pub use self::batch_file_state::BatchFileState;
+#[cfg(feature = "std")]
+pub use self::batch_progress::BatchProgress;
+#[cfg(feature = "std")]
+pub use self::batch_render::BatchRenderer;
+pub use self::broadcast_info::BroadcastInfo;
+pub use self::cancellation_token::CancellationToken;
+pub use self::channel_state::ChannelState;
+pub use self::controller_target::ControllerTarget;
+pub use self::device_profile::DeviceProfile;
+pub use self::diagnostic::Diagnostic;
+pub use self::diagnostic::DiagnosticCode;
+pub use self::diagnostic::DiagnosticLocation;
+pub use self::diagnostic::DiagnosticSeverity;
+pub use self::diagnostics::Diagnostics;
+pub use self::envelope_override::EnvelopeOverride;
+pub use self::envelope_phase::EnvelopePhase;
+#[cfg(feature = "midir")]
+pub use self::error::LiveMidiError;
 pub use self::error::MidiFileError;
 pub use self::error::SoundFontError;
 pub use self::error::SynthesizerError;
+#[cfg(feature = "std")]
+pub use self::error::TrackRenderError;
+pub use self::exclusive_class_mode::ExclusiveClassMode;
+pub use self::filter_override::FilterOverride;
+pub use self::filter_type::FilterType;
+pub use self::four_cc::FourCC;
+pub use self::generator_overrides::GeneratorOverrides;
+pub use self::generator_type::GeneratorType;
+pub use self::humanize_options::HumanizeOptions;
 pub use self::instrument::Instrument;
 pub use self::instrument_region::InstrumentRegion;
+pub use self::key_split::KeySplit;
+pub use self::lfo_override::LfoOverride;
+pub use self::lfo_override::LfoRate;
+#[cfg(feature = "std")]
+pub use self::loop_render_mode::LoopRenderMode;
+pub use self::lyric_syllable::LyricSyllable;
+pub use self::lyrics::Lyrics;
+pub use self::metronome::Metronome;
+pub use self::midi_event::MidiEvent;
+pub use self::midi_message::MidiMessage;
+#[cfg(feature = "std")]
+pub use self::midi_render::ThreadedRender;
 pub use self::midifile::MidiFile;
+pub use self::midifile::MidiTrack;
+pub use self::midifile_analysis::ChannelAnalysis;
+pub use self::midifile_analysis::MidiFileAnalysis;
 pub use self::midifile_looptype::MidiFileLoopType;
+pub use self::midifile_options::MidiFileOptions;
 pub use self::midifile_sequencer::MidiFileSequencer;
+pub use self::midifile_warning::MidiFileWarning;
+#[cfg(feature = "std")]
+pub use self::normalization::Normalization;
+#[cfg(feature = "opl")]
+pub use self::opl_operator::OplOperator;
+#[cfg(feature = "opl")]
+pub use self::opl_patch::OplPatch;
+#[cfg(feature = "opl")]
+pub use self::opl_voice_source::OplVoiceSource;
+pub use self::parser_limits::ParserLimits;
+pub use self::pedal_curve::PedalCurve;
+#[cfg(feature = "std")]
+pub use self::playlist_render::PlaylistRender;
+#[cfg(feature = "std")]
+pub use self::playlist_transition::PlaylistTransition;
 pub use self::preset::Preset;
 pub use self::preset_region::PresetRegion;
+#[cfg(feature = "std")]
+pub use self::progressive_render::ProgressiveRender;
+pub use self::release_layer::ReleaseLayer;
+#[cfg(feature = "std")]
+pub use self::render_analysis::RenderAnalysis;
+#[cfg(feature = "std")]
+pub use self::render_analysis::TrackAnalysis;
+#[cfg(feature = "std")]
+pub use self::render_cache::RenderCache;
+#[cfg(feature = "std")]
+pub use self::render_progress::RenderProgress;
+#[cfg(feature = "std")]
+pub use self::render_report::LevelReport;
+#[cfg(feature = "std")]
+pub use self::render_report::RenderReport;
+#[cfg(feature = "std")]
+pub use self::render_split::RenderSplit;
+#[cfg(feature = "std")]
+pub use self::render_tail::RenderTail;
 pub use self::sample_header::SampleHeader;
+pub use self::sequence::Sequence;
+pub use self::sequencer_loop_mode::SequencerLoopMode;
+pub use self::sound_bank_set::SoundBankSet;
 pub use self::soundfont::SoundFont;
 pub use self::soundfont_info::SoundFontInfo;
+pub use self::soundfont_inspector::RiffChunk;
+pub use self::soundfont_inspector::SoundFontInspector;
 pub use self::soundfont_version::SoundFontVersion;
 pub use self::synthesizer::Synthesizer;
 pub use self::synthesizer_settings::SynthesizerSettings;
-pub use self::midi_render::ThreadedRender;
+pub use self::synthesizer_state::SynthesizerState;
+pub use self::synthesizer_statistics::PresetStatistics;
+pub use self::synthesizer_statistics::SynthesizerStatistics;
+pub use self::tempo_map::TempoChange;
+pub use self::tempo_map::TempoMap;
+pub use self::tempo_map::TimeSignature;
+pub use self::time_stretch::TimeStretch;
+#[cfg(feature = "std")]
+pub use self::track_error_policy::TrackErrorPolicy;
+#[cfg(feature = "std")]
+pub use self::track_mix::TrackMix;
+pub use self::voice_event::VoiceEvent;
+pub use self::voice_event::VoiceEventKind;
+pub use self::voice_info::VoiceInfo;
+pub use self::voice_source::VoiceGenerator;
+pub use self::voice_source::VoiceSource;
+pub use self::voice_stealing_policy::VoiceStealingPolicy;
+pub use self::volume_curve::VolumeCurve;
+pub use self::wave_format::WaveFormat;
+pub use self::wave_options::WaveOptions;
+
+#[cfg(feature = "midir")]
+pub use self::live_midi_session::LiveMidiSession;
 
+// Re-exported only for the workspace application, and not a part of the stable API.
+#[cfg(feature = "std")]
+#[doc(hidden)]
 pub use rayon;
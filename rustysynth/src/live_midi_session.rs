@@ -0,0 +1,248 @@
+#![allow(dead_code)]
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use std::cmp;
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+
+use midir::MidiInput;
+use midir::MidiInputConnection;
+
+use crate::error::LiveMidiError;
+use crate::synthesizer::Synthesizer;
+
+/// A live MIDI input session, which drives a synthesizer from a MIDI input port.
+///
+/// # Remarks
+///
+/// Incoming messages are timestamped by the MIDI driver.
+/// They are scheduled relative to the rendered audio with a constant latency,
+/// so the timing between notes is preserved regardless of the size of the audio buffers.
+pub struct LiveMidiSession {
+    synthesizer: Synthesizer,
+
+    connection: Option<MidiInputConnection<()>>,
+    receiver: Receiver<(u64, RawMessage)>,
+
+    latency: f64,
+
+    // The mapping from the driver's timestamp (in microseconds) to the sample position.
+    anchor: Option<(u64, u64)>,
+    pending: VecDeque<(u64, RawMessage)>,
+
+    block_wrote: usize,
+    block_start: u64,
+    rendered_sample_count: u64,
+}
+
+#[derive(Clone, Copy)]
+struct RawMessage {
+    status: u8,
+    data1: u8,
+    data2: u8,
+}
+
+impl LiveMidiSession {
+    const CLIENT_NAME: &'static str = "RustySynth";
+    const DEFAULT_LATENCY: f64 = 0.01;
+
+    /// Gets the names of the available MIDI input ports.
+    pub fn get_port_names() -> Result<Vec<String>, LiveMidiError> {
+        let input = MidiInput::new(LiveMidiSession::CLIENT_NAME)?;
+
+        let mut names: Vec<String> = Vec::new();
+        for port in input.ports().iter() {
+            names.push(input.port_name(port)?);
+        }
+
+        Ok(names)
+    }
+
+    /// Opens a MIDI input port and connects it to the synthesizer.
+    ///
+    /// # Arguments
+    ///
+    /// * `synthesizer` - The synthesizer to be driven by the MIDI input.
+    /// * `port_index` - The index of the port in the list returned by `get_port_names`.
+    pub fn new(synthesizer: Synthesizer, port_index: usize) -> Result<Self, LiveMidiError> {
+        let input = MidiInput::new(LiveMidiSession::CLIENT_NAME)?;
+
+        let ports = input.ports();
+        let port = match ports.get(port_index) {
+            Some(value) => value,
+            None => return Err(LiveMidiError::PortNotFound(port_index)),
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let connection = input
+            .connect(
+                port,
+                LiveMidiSession::CLIENT_NAME,
+                move |timestamp, bytes, _| {
+                    if let Some(message) = RawMessage::parse(bytes) {
+                        // The receiver is gone only if the session has been dropped.
+                        let _ = sender.send((timestamp, message));
+                    }
+                },
+                (),
+            )
+            .map_err(|err| LiveMidiError::ConnectError(err.kind()))?;
+
+        let block_wrote = synthesizer.block_size;
+
+        Ok(Self {
+            synthesizer,
+            connection: Some(connection),
+            receiver,
+            latency: LiveMidiSession::DEFAULT_LATENCY,
+            anchor: None,
+            pending: VecDeque::new(),
+            block_wrote,
+            block_start: 0,
+            rendered_sample_count: 0,
+        })
+    }
+
+    /// Renders the waveform.
+    ///
+    /// # Arguments
+    ///
+    /// * `left` - The buffer of the left channel to store the rendered waveform.
+    /// * `right` - The buffer of the right channel to store the rendered waveform.
+    ///
+    /// # Remarks
+    ///
+    /// The output buffers for the left and right must be the same length.
+    pub fn render(&mut self, left: &mut [f32], right: &mut [f32]) {
+        if left.len() != right.len() {
+            panic!("The output buffers for the left and right must be the same length.");
+        }
+
+        let left_length = left.len();
+        let mut wrote: usize = 0;
+        while wrote < left_length {
+            if self.block_wrote == self.synthesizer.block_size {
+                self.synthesizer.apply_pending_changes();
+                self.block_start = self.rendered_sample_count;
+                self.process_events();
+                self.block_wrote = 0;
+            }
+
+            let src_rem = self.synthesizer.block_size - self.block_wrote;
+            let dst_rem = left_length - wrote;
+            let rem = cmp::min(src_rem, dst_rem);
+
+            self.synthesizer.render(
+                &mut left[wrote..wrote + rem],
+                &mut right[wrote..wrote + rem],
+            );
+
+            self.block_wrote += rem;
+            self.rendered_sample_count += rem as u64;
+            wrote += rem;
+        }
+    }
+
+    fn process_events(&mut self) {
+        let sample_rate = self.synthesizer.sample_rate as f64;
+        let latency = (sample_rate * self.latency) as u64;
+
+        while let Ok((timestamp, message)) = self.receiver.try_recv() {
+            let (anchor_timestamp, anchor_position) = match self.anchor {
+                Some(value) => value,
+                None => (timestamp, self.block_start + latency),
+            };
+
+            let elapsed = timestamp.saturating_sub(anchor_timestamp) as f64 / 1_000_000_f64;
+            let mut position = anchor_position + (sample_rate * elapsed) as u64;
+
+            // The audio clock and the MIDI clock may drift apart.
+            // If an event is scheduled too late or too early, re-anchor the clocks.
+            if position + latency < self.block_start || position > self.block_start + 2 * latency {
+                position = self.block_start + latency;
+                self.anchor = Some((timestamp, position));
+            } else if self.anchor.is_none() {
+                self.anchor = Some((anchor_timestamp, anchor_position));
+            }
+
+            self.pending.push_back((position, message));
+        }
+
+        let block_end = self.block_start + self.synthesizer.block_size as u64;
+        while let Some((position, message)) = self.pending.front() {
+            if *position >= block_end {
+                break;
+            }
+
+            self.synthesizer.process_midi_message(
+                (message.status & 0x0F) as i32,
+                (message.status & 0xF0) as i32,
+                message.data1 as i32,
+                message.data2 as i32,
+            );
+            self.pending.pop_front();
+        }
+    }
+
+    /// Closes the MIDI input port and returns the synthesizer.
+    pub fn close(mut self) -> Synthesizer {
+        if let Some(connection) = self.connection.take() {
+            connection.close();
+        }
+
+        self.synthesizer
+    }
+
+    /// Gets the synthesizer driven by the session.
+    pub fn get_synthesizer(&self) -> &Synthesizer {
+        &self.synthesizer
+    }
+
+    /// Gets the synthesizer driven by the session for modification.
+    pub fn get_synthesizer_mut(&mut self) -> &mut Synthesizer {
+        &mut self.synthesizer
+    }
+
+    /// Gets the scheduling latency in seconds.
+    ///
+    /// # Remarks
+    ///
+    /// The default value is 0.01.
+    pub fn get_latency(&self) -> f64 {
+        self.latency
+    }
+
+    /// Sets the scheduling latency in seconds.
+    ///
+    /// # Remarks
+    ///
+    /// The value must be non-negative.
+    /// A larger value makes the timing more stable against jitter in the audio callback.
+    pub fn set_latency(&mut self, value: f64) {
+        if value < 0.0 {
+            panic!("The latency must be a non-negative value.");
+        }
+
+        self.latency = value;
+        self.anchor = None;
+    }
+}
+
+impl RawMessage {
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        let status = *bytes.first()?;
+
+        // Only channel messages are handled.
+        if !(0x80..0xF0).contains(&status) {
+            return None;
+        }
+
+        Some(Self {
+            status,
+            data1: bytes.get(1).copied().unwrap_or(0),
+            data2: bytes.get(2).copied().unwrap_or(0),
+        })
+    }
+}
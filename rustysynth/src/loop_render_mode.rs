@@ -0,0 +1,59 @@
+/// Specifies how the loop of a MIDI file is rendered by `ThreadedRender`.
+///
+/// # Remarks
+///
+/// The loop is given by the loop type of the renderer.
+/// If no loop start point is found, the loop starts at the beginning.
+/// If no loop end point is found, the loop ends at the end of the MIDI file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum LoopRenderMode {
+    /// The MIDI file is rendered once, and the loop is ignored.
+    Once,
+
+    /// The loop is repeated the specified number of times,
+    /// and the rest of the MIDI file after the loop end point follows.
+    Count(usize),
+
+    /// The loop is repeated until the waveform becomes longer than the specified number of seconds.
+    Duration(f64),
+
+    /// The loop is rendered once for seamless loop playback.
+    /// The output ends at the loop end point,
+    /// and the sound ringing after it is faded out over the specified number of seconds
+    /// and mixed into the beginning of the loop.
+    Tail(f64),
+
+    /// The loop is repeated the specified number of times,
+    /// and then the playback continues into the next pass while it fades out over the specified number of seconds.
+    /// The output ends when the fade-out is complete.
+    FadeOut {
+        /// The number of times the loop is played before the fade-out starts.
+        count: usize,
+        /// The length of the fade-out in seconds.
+        fade: f64,
+    },
+}
+
+impl LoopRenderMode {
+    pub(crate) fn check(&self) {
+        match *self {
+            LoopRenderMode::Count(0) => {
+                panic!("The loop count must be greater than zero.")
+            }
+            LoopRenderMode::Duration(duration) if duration < 0.0 => {
+                panic!("The duration must be a non-negative value.")
+            }
+            LoopRenderMode::Tail(tail_length) if tail_length < 0.0 => {
+                panic!("The tail length must be a non-negative value.")
+            }
+            LoopRenderMode::FadeOut { count: 0, .. } => {
+                panic!("The loop count must be greater than zero.")
+            }
+            LoopRenderMode::FadeOut { fade, .. } if fade.is_nan() || fade < 0.0 => {
+                panic!("The fade length must be a non-negative value.")
+            }
+            _ => (),
+        }
+    }
+}
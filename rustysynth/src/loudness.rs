@@ -0,0 +1,195 @@
+#![allow(dead_code)]
+
+use alloc::vec::Vec;
+use core::f64::consts;
+
+// Measures the integrated loudness as specified in ITU-R BS.1770-4.
+#[non_exhaustive]
+pub(crate) struct Loudness {}
+
+impl Loudness {
+    const BLOCK_LENGTH: f64 = 0.4;
+    const ABSOLUTE_GATE: f64 = -70.0;
+    const RELATIVE_GATE: f64 = -10.0;
+
+    // Returns the integrated loudness in LUFS, or `None` if the waveform is too short or silent.
+    pub(crate) fn integrated(left: &[f32], right: &[f32], sample_rate: i32) -> Option<f64> {
+        let left = Loudness::k_weight(left, sample_rate);
+        let right = Loudness::k_weight(right, sample_rate);
+
+        // The mean square of the 400 ms blocks overlapping by 75%.
+        let block_length = (sample_rate as f64 * Loudness::BLOCK_LENGTH) as usize;
+        let step = block_length / 4;
+        if block_length == 0 || left.len() < block_length {
+            return None;
+        }
+
+        let mut powers = Vec::new();
+        let mut start: usize = 0;
+        while start + block_length <= left.len() {
+            let end = start + block_length;
+            let sum: f64 = left[start..end]
+                .iter()
+                .chain(right[start..end].iter())
+                .map(|x| x * x)
+                .sum();
+            powers.push(sum / block_length as f64);
+            start += step;
+        }
+
+        let absolute: Vec<f64> = powers
+            .into_iter()
+            .filter(|x| Loudness::to_lufs(*x) > Loudness::ABSOLUTE_GATE)
+            .collect();
+        if absolute.is_empty() {
+            return None;
+        }
+
+        let threshold = Loudness::to_lufs(Loudness::mean(&absolute)) + Loudness::RELATIVE_GATE;
+        let relative: Vec<f64> = absolute
+            .into_iter()
+            .filter(|x| Loudness::to_lufs(*x) > threshold)
+            .collect();
+
+        Some(Loudness::to_lufs(Loudness::mean(&relative)))
+    }
+
+    fn to_lufs(power: f64) -> f64 {
+        -0.691 + 10.0 * power.log10()
+    }
+
+    fn mean(values: &[f64]) -> f64 {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+
+    // Applies the high shelf modeling the head, followed by the high-pass filter.
+    fn k_weight(data: &[f32], sample_rate: i32) -> Vec<f64> {
+        let sample_rate = sample_rate as f64;
+
+        let k = (consts::PI * 1681.974450955533 / sample_rate).tan();
+        let q = 0.7071752369554196;
+        let vh = 10_f64.powf(3.999843853973347 / 20.0);
+        let vb = vh.powf(0.4996667741545416);
+        let a0 = 1.0 + k / q + k * k;
+        let shelf = [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+            2.0 * (k * k - 1.0) / a0,
+            (1.0 - k / q + k * k) / a0,
+        ];
+
+        let k = (consts::PI * 38.13547087602444 / sample_rate).tan();
+        let q = 0.5003270373238773;
+        let a0 = 1.0 + k / q + k * k;
+        let high_pass = [
+            1.0,
+            -2.0,
+            1.0,
+            2.0 * (k * k - 1.0) / a0,
+            (1.0 - k / q + k * k) / a0,
+        ];
+
+        let data: Vec<f64> = data.iter().map(|x| *x as f64).collect();
+        let data = Loudness::filter(&data, &shelf);
+        Loudness::filter(&data, &high_pass)
+    }
+
+    // The coefficients are b0, b1, b2, a1 and a2, normalized by a0.
+    fn filter(data: &[f64], c: &[f64; 5]) -> Vec<f64> {
+        let mut x1 = 0.0;
+        let mut x2 = 0.0;
+        let mut y1 = 0.0;
+        let mut y2 = 0.0;
+        data.iter()
+            .map(|x| {
+                let y = c[0] * x + c[1] * x1 + c[2] * x2 - c[3] * y1 - c[4] * y2;
+                x2 = x1;
+                x1 = *x;
+                y2 = y1;
+                y1 = y;
+                y
+            })
+            .collect()
+    }
+}
+
+impl Loudness {
+    // The 4x oversampling filter specified in Annex 2 of ITU-R BS.1770-4, split into the phases.
+    const TRUE_PEAK_FILTER: [[f64; 12]; 4] = [
+        [
+            0.0017089843750,
+            0.0109863281250,
+            -0.0196533203125,
+            0.0332031250000,
+            -0.0594482421875,
+            0.1373291015625,
+            0.9721679687500,
+            -0.1022949218750,
+            0.0476074218750,
+            -0.0266113281250,
+            0.0148925781250,
+            -0.0083007812500,
+        ],
+        [
+            -0.0291748046875,
+            0.0292968750000,
+            -0.0517578125000,
+            0.0891113281250,
+            -0.1665039062500,
+            0.4650878906250,
+            0.7797851562500,
+            -0.2003173828125,
+            0.1015625000000,
+            -0.0582275390625,
+            0.0330810546875,
+            -0.0189208984375,
+        ],
+        [
+            -0.0189208984375,
+            0.0330810546875,
+            -0.0582275390625,
+            0.1015625000000,
+            -0.2003173828125,
+            0.7797851562500,
+            0.4650878906250,
+            -0.1665039062500,
+            0.0891113281250,
+            -0.0517578125000,
+            0.0292968750000,
+            -0.0291748046875,
+        ],
+        [
+            -0.0083007812500,
+            0.0148925781250,
+            -0.0266113281250,
+            0.0476074218750,
+            -0.1022949218750,
+            0.9721679687500,
+            0.1373291015625,
+            -0.0594482421875,
+            0.0332031250000,
+            -0.0196533203125,
+            0.0109863281250,
+            0.0017089843750,
+        ],
+    ];
+
+    // Returns the maximum absolute value of the waveform oversampled by 4x.
+    pub(crate) fn true_peak(data: &[f32]) -> f32 {
+        let mut peak = data.iter().fold(0_f32, |max, x| max.max(x.abs())) as f64;
+        let taps = Loudness::TRUE_PEAK_FILTER[0].len();
+        for t in 0..data.len() + taps - 1 {
+            for phase in Loudness::TRUE_PEAK_FILTER.iter() {
+                let mut y = 0.0;
+                for (k, h) in phase.iter().enumerate() {
+                    if k <= t && t - k < data.len() {
+                        y += h * data[t - k] as f64;
+                    }
+                }
+                peak = peak.max(y.abs());
+            }
+        }
+        peak as f32
+    }
+}
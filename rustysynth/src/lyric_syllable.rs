@@ -0,0 +1,45 @@
+#![allow(dead_code)]
+
+use alloc::string::String;
+
+/// Represents a syllable of the lyrics, which is sung from the specified time.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct LyricSyllable {
+    pub(crate) time: f64,
+    pub(crate) text: String,
+    pub(crate) line_start: bool,
+    pub(crate) paragraph_start: bool,
+}
+
+impl LyricSyllable {
+    /// Gets the time of the syllable in seconds.
+    pub fn get_time(&self) -> f64 {
+        self.time
+    }
+
+    /// Gets the text of the syllable.
+    ///
+    /// # Remarks
+    ///
+    /// The markers of the line breaks are removed,
+    /// and the spaces between the words are kept as they are.
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+
+    /// Gets a value that indicates whether the syllable starts a new line.
+    ///
+    /// # Remarks
+    ///
+    /// This is also `true` for the syllable which starts a new paragraph.
+    pub fn is_line_start(&self) -> bool {
+        self.line_start
+    }
+
+    /// Gets a value that indicates whether the syllable starts a new paragraph,
+    /// where karaoke players usually clear the screen.
+    pub fn is_paragraph_start(&self) -> bool {
+        self.paragraph_start
+    }
+}
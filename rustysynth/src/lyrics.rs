@@ -0,0 +1,237 @@
+#![allow(dead_code)]
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::lyric_syllable::LyricSyllable;
+use crate::tempo_map::TempoMap;
+
+// A text meta event, which is kept aside from the MIDI messages.
+pub(crate) struct TextEvent {
+    pub(crate) tick: i32,
+    pub(crate) kind: u8,
+    pub(crate) data: Vec<u8>,
+}
+
+impl TextEvent {
+    pub(crate) const TEXT: u8 = 0x01;
+    pub(crate) const LYRIC: u8 = 0x05;
+}
+
+/// Represents the lyrics of a MIDI file, synchronized with the playback.
+///
+/// # Remarks
+///
+/// The lyrics of a karaoke file (.kar) are read from the text events of the track
+/// starting with the `@K` header, where `/` starts a new line and `\` starts a new paragraph.
+/// Otherwise, the lyric events of all the tracks are read,
+/// where a line feed or a carriage return starts a new line, and two of them start a new paragraph.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct Lyrics {
+    syllables: Vec<LyricSyllable>,
+    karaoke: bool,
+    titles: Vec<String>,
+    information: Vec<String>,
+    language: Option<String>,
+}
+
+// The line breaks which have not been attached to a syllable yet.
+#[derive(Clone, Copy, Default)]
+struct PendingBreak {
+    line: bool,
+    paragraph: bool,
+}
+
+impl Lyrics {
+    pub(crate) fn new(tempo_map: &TempoMap, tracks: &[Vec<TextEvent>]) -> Self {
+        let karaoke_track = tracks.iter().find(|track| {
+            track
+                .iter()
+                .any(|x| x.kind == TextEvent::TEXT && x.data.starts_with(b"@K"))
+        });
+
+        let mut lyrics = Lyrics::default();
+        match karaoke_track {
+            Some(track) => {
+                lyrics.karaoke = true;
+                lyrics.read_karaoke(tempo_map, track);
+            }
+            None => {
+                let mut events = tracks
+                    .iter()
+                    .flat_map(|track| track.iter())
+                    .filter(|x| x.kind == TextEvent::LYRIC)
+                    .collect::<Vec<&TextEvent>>();
+                // The sort is stable, so the events at the same tick keep the order of the tracks.
+                events.sort_by_key(|x| x.tick);
+                lyrics.read_lyric_events(tempo_map, &events);
+            }
+        }
+
+        lyrics
+    }
+
+    fn read_karaoke(&mut self, tempo_map: &TempoMap, track: &[TextEvent]) {
+        let mut pending = PendingBreak::default();
+        for event in track.iter().filter(|x| x.kind == TextEvent::TEXT) {
+            let text = Lyrics::decode(&event.data);
+
+            if let Some(header) = text.strip_prefix('@') {
+                let mut chars = header.chars();
+                let kind = chars.next();
+                let value = String::from(chars.as_str().trim());
+                match kind {
+                    Some('T') => self.titles.push(value),
+                    Some('I') => self.information.push(value),
+                    Some('L') => self.language = Some(value),
+                    _ => (),
+                }
+                continue;
+            }
+
+            let mut body = text.as_str();
+            loop {
+                if let Some(rest) = body.strip_prefix('\\') {
+                    pending.paragraph = true;
+                    body = rest;
+                } else if let Some(rest) = body.strip_prefix('/') {
+                    pending.line = true;
+                    body = rest;
+                } else {
+                    break;
+                }
+            }
+
+            self.push(tempo_map, event.tick, body, &mut pending);
+        }
+    }
+
+    fn read_lyric_events(&mut self, tempo_map: &TempoMap, events: &[&TextEvent]) {
+        let mut pending = PendingBreak::default();
+        for event in events.iter() {
+            let text = Lyrics::decode(&event.data);
+
+            let body = text.trim_start_matches(['\r', '\n']);
+            Lyrics::add_breaks(&text[..text.len() - body.len()], &mut pending);
+
+            let trimmed = body.trim_end_matches(['\r', '\n']);
+            let mut trailing = PendingBreak::default();
+            Lyrics::add_breaks(&body[trimmed.len()..], &mut trailing);
+
+            self.push(tempo_map, event.tick, trimmed, &mut pending);
+            pending.line |= trailing.line;
+            pending.paragraph |= trailing.paragraph;
+        }
+    }
+
+    // A CR LF pair counts as a single line break.
+    fn add_breaks(breaks: &str, pending: &mut PendingBreak) {
+        let count = breaks.replace("\r\n", "\n").len();
+        if count >= 2 {
+            pending.paragraph = true;
+        } else if count == 1 {
+            pending.line = true;
+        }
+    }
+
+    // An empty syllable only passes its line break to the next one.
+    fn push(&mut self, tempo_map: &TempoMap, tick: i32, text: &str, pending: &mut PendingBreak) {
+        if text.is_empty() {
+            return;
+        }
+
+        // The first syllable always starts a line.
+        let paragraph_start = pending.paragraph || self.syllables.is_empty();
+        self.syllables.push(LyricSyllable {
+            time: tempo_map.ticks_to_seconds(tick as f64),
+            text: String::from(text),
+            line_start: pending.line || paragraph_start,
+            paragraph_start,
+        });
+        *pending = PendingBreak::default();
+    }
+
+    // Most of the files are in UTF-8 or ASCII, and the others are read as Latin-1.
+    fn decode(data: &[u8]) -> String {
+        match core::str::from_utf8(data) {
+            Ok(value) => String::from(value),
+            Err(_) => data.iter().map(|x| *x as char).collect(),
+        }
+    }
+
+    /// Gets the syllables in the order of the time.
+    pub fn get_syllables(&self) -> &[LyricSyllable] {
+        &self.syllables[..]
+    }
+
+    /// Gets a value that indicates whether the MIDI file has no lyrics.
+    pub fn is_empty(&self) -> bool {
+        self.syllables.is_empty()
+    }
+
+    /// Gets a value that indicates whether the lyrics were read from a karaoke file.
+    pub fn is_karaoke(&self) -> bool {
+        self.karaoke
+    }
+
+    /// Gets the `@T` headers of the karaoke file,
+    /// which are usually the title, the artist and the sequencer in this order.
+    pub fn get_titles(&self) -> &[String] {
+        &self.titles[..]
+    }
+
+    /// Gets the `@I` headers of the karaoke file, which contain any information.
+    pub fn get_information(&self) -> &[String] {
+        &self.information[..]
+    }
+
+    /// Gets the `@L` header of the karaoke file, which is the language of the lyrics.
+    pub fn get_language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Gets the index of the syllable being sung at the playback position.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The playback position in seconds, such as `MidiFileSequencer::get_position`.
+    ///
+    /// # Remarks
+    ///
+    /// This is the last syllable which has started, so all the syllables up to it can be highlighted.
+    /// The result is `None` before the first syllable.
+    pub fn get_syllable_index(&self, position: f64) -> Option<usize> {
+        match self.syllables.partition_point(|x| x.time <= position) {
+            0 => None,
+            count => Some(count - 1),
+        }
+    }
+
+    /// Gets the range of the syllables in the line which contains the specified syllable.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the syllable.
+    ///
+    /// # Remarks
+    ///
+    /// The index must be less than the number of the syllables.
+    pub fn get_line(&self, index: usize) -> Range<usize> {
+        if index >= self.syllables.len() {
+            panic!("The syllable index is out of range.");
+        }
+
+        let start = self.syllables[..=index]
+            .iter()
+            .rposition(|x| x.line_start)
+            .unwrap_or(0);
+        let end = self.syllables[index + 1..]
+            .iter()
+            .position(|x| x.line_start)
+            .map_or(self.syllables.len(), |x| index + 1 + x);
+
+        start..end
+    }
+}
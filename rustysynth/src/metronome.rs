@@ -0,0 +1,145 @@
+#![allow(dead_code)]
+
+use alloc::vec::Vec;
+use core::f64::consts::PI;
+
+use crate::float_math::FloatMath;
+use crate::tempo_map::TempoMap;
+
+/// Represents a click track mixed into the output of the sequencer.
+///
+/// # Remarks
+///
+/// The clicks follow the tempo changes and the time signatures of the tempo map,
+/// with the first beat of each bar accented.
+/// The clicks are synthesized unless the sounds are given by `set_sounds`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Metronome {
+    tempo_map: TempoMap,
+    volume: f32,
+    sounds: Option<(Vec<f32>, Vec<f32>)>,
+}
+
+impl Metronome {
+    const DEFAULT_VOLUME: f32 = 0.5;
+
+    const CLICK_LENGTH: f64 = 0.03;
+    const CLICK_DECAY: f64 = 0.005;
+    const DOWNBEAT_FREQUENCY: f64 = 1760.0;
+    const UPBEAT_FREQUENCY: f64 = 880.0;
+
+    /// Initializes a new metronome.
+    ///
+    /// # Arguments
+    ///
+    /// * `tempo_map` - The tempo map of the MIDI file, which is copied.
+    ///
+    /// # Remarks
+    ///
+    /// No click is generated for the MIDI files with the SMPTE-based time division,
+    /// since they have no bars and beats.
+    pub fn new(tempo_map: &TempoMap) -> Self {
+        Self {
+            tempo_map: tempo_map.clone(),
+            volume: Metronome::DEFAULT_VOLUME,
+            sounds: None,
+        }
+    }
+
+    /// Gets the tempo map which the clicks follow.
+    pub fn get_tempo_map(&self) -> &TempoMap {
+        &self.tempo_map
+    }
+
+    /// Gets the volume of the clicks as a linear gain.
+    ///
+    /// # Remarks
+    ///
+    /// The default value is 0.5.
+    pub fn get_volume(&self) -> f32 {
+        self.volume
+    }
+
+    /// Sets the volume of the clicks as a linear gain.
+    ///
+    /// # Remarks
+    ///
+    /// The value must be non-negative.
+    pub fn set_volume(&mut self, value: f32) {
+        if value.is_nan() || value < 0_f32 {
+            panic!("The volume must be a non-negative value.");
+        }
+
+        self.volume = value;
+    }
+
+    /// Sets the sounds of the clicks.
+    ///
+    /// # Arguments
+    ///
+    /// * `downbeat` - The sound played at the first beat of each bar.
+    /// * `upbeat` - The sound played at the other beats.
+    ///
+    /// # Remarks
+    ///
+    /// The sounds are mono, and must be at the sample rate of the synthesizer.
+    pub fn set_sounds(&mut self, downbeat: &[f32], upbeat: &[f32]) {
+        self.sounds = Some((downbeat.to_vec(), upbeat.to_vec()));
+    }
+
+    /// Restores the synthesized sounds of the clicks.
+    pub fn clear_sounds(&mut self) {
+        self.sounds = None;
+    }
+
+    // Returns the sounds of the downbeat and the upbeat at the sample rate.
+    pub(crate) fn create_sounds(&self, sample_rate: i32) -> (Vec<f32>, Vec<f32>) {
+        match self.sounds.as_ref() {
+            Some(value) => value.clone(),
+            None => (
+                Metronome::synthesize_click(sample_rate, Metronome::DOWNBEAT_FREQUENCY),
+                Metronome::synthesize_click(sample_rate, Metronome::UPBEAT_FREQUENCY),
+            ),
+        }
+    }
+
+    // A short sine wave with the exponential decay.
+    fn synthesize_click(sample_rate: i32, frequency: f64) -> Vec<f32> {
+        let length = (sample_rate as f64 * Metronome::CLICK_LENGTH) as usize;
+        (0..length)
+            .map(|t| {
+                let time = t as f64 / sample_rate as f64;
+                let envelope = FloatMath::exp_f64(-time / Metronome::CLICK_DECAY);
+                (envelope * FloatMath::sin_f64(2.0 * PI * frequency * time)) as f32
+            })
+            .collect()
+    }
+
+    // Returns the times of the beats before the end, and whether each beat is the first one of a bar.
+    pub(crate) fn get_clicks(&self, length: f64) -> Vec<(f64, bool)> {
+        let mut clicks: Vec<(f64, bool)> = Vec::new();
+
+        let mut bar: i32 = 0;
+        loop {
+            // A time signature in the middle of a bar cuts the bar short.
+            let next_bar_time = match self.tempo_map.bars_to_seconds(bar + 1, 0.0) {
+                Some(value) => value,
+                None => return clicks,
+            };
+
+            for beat in 0..self.tempo_map.get_beat_count(bar) {
+                let time = self.tempo_map.bars_to_seconds(bar, beat as f64).unwrap();
+                if time >= length {
+                    return clicks;
+                }
+
+                if time < next_bar_time {
+                    clicks.push((time, beat == 0));
+                }
+            }
+
+            bar += 1;
+        }
+    }
+}
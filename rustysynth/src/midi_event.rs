@@ -0,0 +1,39 @@
+#![allow(dead_code)]
+
+use crate::midi_message::MidiMessage;
+use crate::midifile::Message;
+
+/// Represents an event in a MIDI track.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum MidiEvent {
+    /// A channel message, which is sent to the synthesizer.
+    /// The channels from 16 are those of the following MIDI ports selected by the port meta events.
+    Message(MidiMessage),
+    /// A change of the channel between the percussion and melodic by a GS SysEx message.
+    PercussionChannel { channel: u8, value: bool },
+    /// A tempo change in beats per minute.
+    TempoChange(f64),
+    /// The loop start point.
+    LoopStart,
+    /// The loop end point.
+    LoopEnd,
+    /// The end of the track.
+    EndOfTrack,
+}
+
+impl MidiEvent {
+    pub(crate) fn from_message(message: &Message) -> Self {
+        match message.get_message_type() {
+            Message::PERCUSSION_CHANNEL => MidiEvent::PercussionChannel {
+                channel: message.command,
+                value: message.data1 != 0,
+            },
+            Message::TEMPO_CHANGE => MidiEvent::TempoChange(message.get_tempo()),
+            Message::LOOP_START => MidiEvent::LoopStart,
+            Message::LOOP_END => MidiEvent::LoopEnd,
+            Message::END_OF_TRACK => MidiEvent::EndOfTrack,
+            _ => MidiEvent::Message(MidiMessage::from_message(message)),
+        }
+    }
+}
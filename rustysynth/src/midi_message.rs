@@ -0,0 +1,45 @@
+#![allow(dead_code)]
+
+use crate::midifile::Message;
+
+/// Represents a channel message in a MIDI track, which is also passed to the event filter of the sequencer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MidiMessage {
+    /// The channel of the message, where the channels from 16 are those of the following MIDI ports.
+    pub channel: u8,
+    /// The command of the message, without the channel.
+    pub command: u8,
+    /// The first data byte of the message.
+    pub data1: u8,
+    /// The second data byte of the message.
+    pub data2: u8,
+}
+
+impl MidiMessage {
+    /// Initializes a new channel message.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel of the message.
+    /// * `command` - The command of the message, such as `0x90` for the note-on.
+    /// * `data1` - The first data byte of the message.
+    /// * `data2` - The second data byte of the message.
+    pub fn new(channel: u8, command: u8, data1: u8, data2: u8) -> Self {
+        Self {
+            channel,
+            command,
+            data1,
+            data2,
+        }
+    }
+
+    pub(crate) fn from_message(message: &Message) -> Self {
+        Self {
+            channel: message.channel,
+            command: message.command,
+            data1: message.data1,
+            data2: message.data2,
+        }
+    }
+}
@@ -1,41 +1,193 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
 use std::{
+    any::Any,
+    cmp,
     fs::File,
-    io::{Cursor, Read, Seek},
-    sync::{atomic::AtomicI32, Arc, Mutex},
+    io::{self, Cursor, Read, Seek, Write},
+    mem,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, AtomicI32, AtomicUsize},
+        Arc, Mutex,
+    },
+    thread,
 };
 
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
-use rayon::prelude::ParallelSliceMut;
+use rayon::iter::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
+    IntoParallelRefMutIterator, ParallelIterator,
+};
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
 
+#[cfg(feature = "zip")]
+use crate::archive::Archive;
+use crate::automation::Automation;
+use crate::automation_target::AutomationTarget;
+use crate::loudness::Loudness;
+#[cfg(feature = "zip")]
+use crate::parser_limits::ParserLimits;
+use crate::render_analysis::{RenderAnalysis, TrackAnalysis};
+use crate::render_cache::{CacheKey, RenderCache};
+use crate::render_progress::RenderProgress;
+use crate::render_report::{LevelReport, RenderReport};
+use crate::resampler::Resampler;
+use crate::track_error_policy::TrackErrorPolicy;
+use crate::track_mix::TrackMix;
+use crate::track_render::TrackRender;
+use crate::track_segment::TrackSegment;
+use crate::wave_options::WaveOptions;
 use crate::{
     array_math::ArrayMath, binary_reader::BinaryReader, four_cc::FourCC, midifile::*,
-    MidiFileError, MidiFileLoopType, MidiFileSequencer, SoundFont, Synthesizer,
-    SynthesizerSettings,
+    CancellationToken, DiagnosticCode, DiagnosticLocation, DiagnosticSeverity, Diagnostics,
+    LoopRenderMode, MidiFileError, MidiFileLoopType, MidiFileSequencer, Normalization,
+    ProgressiveRender, RenderSplit, RenderTail, SoundFont, Synthesizer, SynthesizerSettings,
+    TrackRenderError, VoiceSource,
 };
 
-pub struct ThreadedRender<'a> {
-    file: &'a str,
+/// Renders a MIDI file offline, where the tracks are rendered in parallel.
+///
+/// # Remarks
+///
+/// Each worker creates its own synthesizer, but all of them share the same SoundFont.
+/// The sample data and the preset tables are never copied,
+/// so the memory usage does not grow with the number of the tracks even for a large SoundFont.
+pub struct ThreadedRender {
+    // The whole MIDI file, which the workers read their tracks from without opening the file again.
+    data: Arc<[u8]>,
     sound_font: Arc<SoundFont>,
 
     resolution: i32,
 
-    tempo_map: Vec<(Message, i32)>,
+    tempo_map: Vec<MergedEvent>,
+    // The positions and the sizes of the track chunks in the data.
     track_addr: Vec<(usize, usize)>,
 
     synthesizer_settings: SynthesizerSettings,
 
+    loop_type: MidiFileLoopType,
+    loop_mode: LoopRenderMode,
+    loop_points: Option<(usize, usize)>,
+
+    tail: RenderTail,
+    split: RenderSplit,
+    segment_length: Option<f64>,
+    normalization: Normalization,
+    report: Option<RenderReport>,
+    track_mixes: BTreeMap<usize, TrackMix>,
+    automations: BTreeMap<AutomationTarget, Automation>,
+    render_cache: Option<Arc<RenderCache>>,
+    voice_sources: BTreeMap<i32, Arc<dyn VoiceSource>>,
+
+    cancellation_token: Option<CancellationToken>,
+
+    error_policy: TrackErrorPolicy,
+    track_errors: Mutex<Vec<TrackRenderError>>,
+    // Set at the first failure in the fail-fast mode, so that the remaining tracks are not started.
+    failed: AtomicBool,
+
+    thread_pool: Option<Arc<ThreadPool>>,
+
+    // Kept public for compatibility. Use `get_track_count` and `get_progress` instead.
+    #[doc(hidden)]
     pub track_count: i32,
+    #[doc(hidden)]
     pub rendered_track_count: Arc<AtomicI32>,
+
+    progress: Arc<RenderProgress>,
+
+    diagnostics: Arc<Mutex<Diagnostics>>,
 }
 
-impl<'a> ThreadedRender<'a> {
+impl ThreadedRender {
+    // The segments except the last one of each track are rendered until the sound stops.
+    const SEGMENT_TAIL: RenderTail = RenderTail::UntilSilence {
+        threshold: -120.0,
+        max_length: 10.0,
+    };
+
+    /// Initializes a new renderer for the MIDI file.
+    ///
+    /// # Arguments
+    ///
+    /// * `sound_font` - The SoundFont instance.
+    /// * `file` - The path of the MIDI file.
+    /// * `synthesizer_settings` - The settings for synthesis.
+    ///
+    /// # Remarks
+    ///
+    /// Only format 0 and 1 files are supported.
+    /// The tempo changes in any track apply to all the tracks,
+    /// and the tempo is 120 BPM until the first of them.
+    /// With the zip feature, the file can also be a zip archive,
+    /// from which the first MIDI file is extracted.
+    /// The file is read into memory once, and the tracks are read from there when rendering.
     pub fn new(
         sound_font: &Arc<SoundFont>,
-        file: &'a str,
+        file: &str,
         synthesizer_settings: SynthesizerSettings,
     ) -> Result<Self, MidiFileError> {
         let mut reader = File::open(file)?;
 
+        #[cfg(feature = "zip")]
+        let data: Vec<u8> = if Archive::is_archive(&mut reader)? {
+            let limits = ParserLimits::new();
+            Archive::extract(&mut reader, Archive::MIDI_EXTENSIONS, limits.max_chunk_size)?
+        } else {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+            data
+        };
+        #[cfg(not(feature = "zip"))]
+        let data: Vec<u8> = {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+            data
+        };
+
+        let (resolution, track_count, track_addr) =
+            ThreadedRender::read_header(&mut Cursor::new(&data[..]))?;
+        let tempo_map = ThreadedRender::read_tempo_map(&data, &track_addr);
+
+        Ok(Self {
+            data: Arc::from(data),
+            resolution,
+            sound_font: Arc::clone(sound_font),
+            synthesizer_settings,
+            loop_type: MidiFileLoopType::LoopPoint(0),
+            loop_mode: LoopRenderMode::Once,
+            loop_points: None,
+            tail: RenderTail::None,
+            split: RenderSplit::Track,
+            segment_length: None,
+            normalization: Normalization::None,
+            report: None,
+            track_mixes: BTreeMap::new(),
+            automations: BTreeMap::new(),
+            render_cache: None,
+            voice_sources: BTreeMap::new(),
+            cancellation_token: None,
+            error_policy: TrackErrorPolicy::Skip,
+            track_errors: Mutex::new(Vec::new()),
+            failed: AtomicBool::new(false),
+            thread_pool: None,
+            track_addr,
+            tempo_map,
+            track_count,
+            rendered_track_count: Arc::new(AtomicI32::new(0)),
+            progress: Arc::new(RenderProgress::new()),
+            diagnostics: Arc::new(Mutex::new(Diagnostics::new())),
+        })
+    }
+
+    // Reads the resolution, the number of the tracks and the positions of the tracks.
+    #[allow(clippy::type_complexity)]
+    fn read_header<R: Read + Seek>(
+        mut reader: &mut R,
+    ) -> Result<(i32, i32, Vec<(usize, usize)>), MidiFileError> {
         let chunk_type = BinaryReader::read_four_cc(&mut reader)?;
         if chunk_type != b"MThd" {
             return Err(MidiFileError::InvalidChunkType {
@@ -46,7 +198,7 @@ impl<'a> ThreadedRender<'a> {
         }
 
         let size = BinaryReader::read_i32_big_endian(&mut reader)?;
-        if size != 6 {
+        if size < 6 {
             return Err(MidiFileError::InvalidChunkData(FourCC::from_bytes(
                 *b"MThd",
             )));
@@ -59,101 +211,1776 @@ impl<'a> ThreadedRender<'a> {
 
         let track_count = BinaryReader::read_u16_big_endian(&mut reader)? as i32;
         let resolution = BinaryReader::read_i16_big_endian(&mut reader)? as i32;
-
-        let mut tempo_map = None;
-        while let Ok(track) = MidiFile::read_track(&mut reader, MidiFileLoopType::LoopPoint(0)) {
-            if track
-                .iter()
-                .any(|(msg, _)| msg.get_message_type() == Message::TEMPO_CHANGE)
-            {
-                tempo_map = Some(track);
-                break;
-            }
-        }
-        if tempo_map.is_none() {
-            return Err(MidiFileError::UnsupportedFormat(format));
+        if !MidiFile::is_valid_division(resolution) {
+            return Err(MidiFileError::InvalidChunkData(FourCC::from_bytes(
+                *b"MThd",
+            )));
         }
 
+        // The tracks start right after the header, which may be longer than usual.
+        BinaryReader::discard_data(&mut reader, size as usize - 6)?;
+        let header_end = reader.stream_position()?;
+
         let track_addr = {
-            let mut reader = File::open(file)?;
-            reader.seek(std::io::SeekFrom::Current(0xe))?;
-            MidiFile::track_addr(&mut reader, track_count)?
+            let file_size = reader.seek(std::io::SeekFrom::End(0))? as usize;
+            reader.seek(std::io::SeekFrom::Start(header_end))?;
+            MidiFile::track_addr(&mut reader, track_count, file_size)?
+                .into_iter()
+                .map(|(start, size)| (header_end as usize + start, size))
+                .collect()
         };
 
-        Ok(Self {
-            file,
-            resolution,
-            sound_font: Arc::clone(&sound_font),
-            synthesizer_settings,
-            track_addr,
-            tempo_map: tempo_map.unwrap(),
-            track_count,
-            rendered_track_count: Arc::new(AtomicI32::new(0)),
-        })
+        Ok((resolution, track_count, track_addr))
+    }
+
+    // Collects the tempo changes from all the tracks, since some files have them in more than one track.
+    // If there is none, the tempo stays at the default 120 BPM.
+    // The tracks which cannot be read are skipped here, and reported when rendering.
+    fn read_tempo_map(data: &[u8], track_addr: &[(usize, usize)]) -> Vec<MergedEvent> {
+        let tracks = track_addr
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (start, size))| {
+                let mut reader = Cursor::new(&data[*start..*start + *size]);
+                MidiFile::read_track(&mut reader, index, MidiFileLoopType::LoopPoint(0))
+                    .ok()
+                    .map(|track| (index, track))
+            });
+
+        MidiFile::collect_tempo_changes(
+            tracks.flat_map(|(index, track)| track.into_iter().map(move |(x, y)| (index, x, y))),
+        )
     }
 
+    /// Renders the whole MIDI file and returns the left and right channels.
+    ///
+    /// # Remarks
+    ///
+    /// The loop is rendered according to the loop mode,
+    /// and the sound after the last event is rendered according to the tail.
+    /// Then, the mix is normalized according to the normalization.
+    /// The levels of the tracks and the output are available from `get_render_report` afterwards.
+    /// If the rendering is cancelled, the part rendered so far is returned as it is.
+    /// The tracks which fail are handled according to the error policy,
+    /// and the failures are available from `get_track_errors` afterwards.
     pub fn render(&mut self) -> (Vec<f32>, Vec<f32>) {
-        let loop_type = MidiFileLoopType::LoopPoint(0);
+        let thread_pool = self.thread_pool.clone();
+        let (left, right, _) =
+            ThreadedRender::install(thread_pool.as_deref(), || self.render_tracks(false));
+        (left, right)
+    }
+
+    /// Renders the whole MIDI file in the same way as `render`,
+    /// and also returns the output of each part divided by the split.
+    ///
+    /// # Remarks
+    ///
+    /// Each part is a track, or a channel with `RenderSplit::Channel`, in the same order as the indices.
+    /// The parts are processed in the same way as the mix, including the track mix and the loop,
+    /// and are scaled by the same gain as the normalization of the mix.
+    /// So the parts have the same length as the mix, and sum up to it.
+    /// The parts which failed or have no sound are silent.
+    /// This is much faster than rendering the file again for each part, such as for the stems.
+    #[allow(clippy::type_complexity)]
+    pub fn render_parts(&mut self) -> (Vec<f32>, Vec<f32>, Vec<(Vec<f32>, Vec<f32>)>) {
+        let thread_pool = self.thread_pool.clone();
+        ThreadedRender::install(thread_pool.as_deref(), || self.render_tracks(true))
+    }
+
+    /// Renders the whole MIDI file in the same way as `render`,
+    /// but returns the first failure of the tracks in the fail-fast mode.
+    ///
+    /// # Remarks
+    ///
+    /// With `TrackErrorPolicy::Skip`, this never fails,
+    /// and the skipped tracks are available from `get_track_errors`.
+    pub fn try_render(&mut self) -> Result<(Vec<f32>, Vec<f32>), TrackRenderError> {
+        let output = self.render();
+        if self.error_policy == TrackErrorPolicy::FailFast {
+            if let Some(error) = self.track_errors.lock().unwrap().first() {
+                return Err(error.clone());
+            }
+        }
+
+        Ok(output)
+    }
+
+    // With `keep_parts`, the output of each part is also kept, which is returned with the mix.
+    #[allow(clippy::type_complexity)]
+    fn render_tracks(
+        &mut self,
+        keep_parts: bool,
+    ) -> (Vec<f32>, Vec<f32>, Vec<(Vec<f32>, Vec<f32>)>) {
+        self.report = None;
+        self.rendered_track_count
+            .store(0, std::sync::atomic::Ordering::SeqCst);
+
+        let tracks = self.start_tracks();
+        let (tracks, loop_points) = self.prepare_tracks(tracks);
+        self.loop_points = loop_points;
+
+        // The tracks which failed to load will never be rendered.
+        if self.split == RenderSplit::Track {
+            self.rendered_track_count.fetch_add(
+                tracks.iter().filter(|x| x.is_none()).count() as i32,
+                std::sync::atomic::Ordering::SeqCst,
+            );
+        }
+
+        let track_reports: Mutex<Vec<LevelReport>> =
+            Mutex::new(vec![LevelReport::default(); tracks.len()]);
+
+        let segments = self.create_segments(tracks, self.segment_length);
+        self.start_progress(&segments);
+
+        // A track is done when all the segments of it are done.
+        let mut remaining_counts: Vec<AtomicUsize> = Vec::new();
+        for segment in segments.iter() {
+            if remaining_counts.len() <= segment.index {
+                remaining_counts.resize_with(segment.index + 1, || AtomicUsize::new(0));
+            }
+            remaining_counts[segment.index].fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }
 
         let master_left: Mutex<Vec<f32>> = Mutex::new(Vec::new());
         let master_right: Mutex<Vec<f32>> = Mutex::new(Vec::new());
+        let part_count = if keep_parts {
+            remaining_counts.len()
+        } else {
+            0
+        };
+        let parts: Vec<(Mutex<Vec<f32>>, Mutex<Vec<f32>>)> = (0..part_count)
+            .map(|_| (Mutex::new(Vec::new()), Mutex::new(Vec::new())))
+            .collect();
 
-        self.track_addr
-            .par_iter()
-            .for_each(|(start, size)| {
-                let mut reader = {
-                    let mut file = File::open(self.file).unwrap();
-                    file.seek(std::io::SeekFrom::Current(0xe)).unwrap();
-                    file
-                        .seek(std::io::SeekFrom::Current(*start as i64))
-                        .unwrap();
-                    let mut buf = vec![0; *size];
-                    file.read_exact(&mut buf).unwrap();
-                    Cursor::new(buf)
-                };
+        // The SoundFont is the same for all the tracks, so it is hashed only once.
+        // The voice sources cannot be hashed, so the cache is not used with them.
+        let sound_font_key = self
+            .render_cache
+            .as_ref()
+            .filter(|_| self.voice_sources.is_empty())
+            .map(|_| {
+                let mut key = CacheKey::new();
+                key.write_sound_font(&self.sound_font);
+                key
+            });
 
-                let mut track = MidiFile::read_track(&mut reader, loop_type).unwrap();
-                track.extend(self.tempo_map.iter());
-                track.par_sort_by(|a, b| a.1.cmp(&b.1));
+        let event_length = segments
+            .into_par_iter()
+            .map(|segment| {
+                let mut event_length = 0;
+                let index = segment.index;
+                let offset = segment.offset;
+                let tail = self.get_segment_tail(&segment);
 
-                let (casted, _) = MidiFile::cast_delta(track, self.resolution);
+                // A panic in a track is caught, so that it does not stop the other tracks.
+                let rendered = if self.has_failed() {
+                    None
+                } else {
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                        let key = sound_font_key
+                            .as_ref()
+                            .map(|x| self.get_cache_key(x, &segment, tail));
+                        let cached = match (&self.render_cache, key) {
+                            (Some(render_cache), Some(key)) => render_cache.get(key),
+                            _ => None,
+                        };
 
-                let synthesizer =
-                    Synthesizer::new(&self.sound_font, &self.synthesizer_settings).unwrap();
-                let mut sequencer = MidiFileSequencer::new(synthesizer);
-                let length = casted.get_length();
-                sequencer.play(casted, false);
+                        match cached {
+                            Some(value) => {
+                                let sample_rate = self.synthesizer_settings.sample_rate;
+                                self.progress.add(TrackRender::get_max_length(
+                                    sample_rate,
+                                    segment.length,
+                                    tail,
+                                ));
+                                let (left, right) = value.as_ref().clone();
+                                Some((left, right, (sample_rate as f64 * segment.length) as usize))
+                            }
+                            None => self
+                                .create_track_render(
+                                    index,
+                                    segment.track,
+                                    segment.length,
+                                    tail,
+                                    &self.progress,
+                                )
+                                .map(|mut track_render| {
+                                    track_render
+                                        .sequencer
+                                        .skip_blocks(offset / self.synthesizer_settings.block_size);
 
-                let sample_count = (self.synthesizer_settings.sample_rate as f64 * length) as usize;
-                let mut left: Vec<f32> = vec![0_f32; sample_count];
-                let mut right: Vec<f32> = vec![0_f32; sample_count];
+                                    let (left, right) = track_render.render(usize::MAX);
+                                    ThreadedRender::report(
+                                        &self.diagnostics,
+                                        &track_render.sequencer,
+                                    );
 
-                sequencer.render(&mut left[..], &mut right[..]);
+                                    // A cancelled track is incomplete, so it must not be reused.
+                                    if let (Some(render_cache), Some(key)) =
+                                        (&self.render_cache, key)
+                                    {
+                                        if !self.is_cancelled() {
+                                            render_cache.insert(key, &left, &right);
+                                        }
+                                    }
 
-                {
-                    let mut left_handler = master_left.lock().unwrap();
-                    let len = left_handler.len();
-                    if len < left.len() {
-                        left_handler.resize(left.len(), 0.0);
+                                    (left, right, track_render.get_event_length())
+                                }),
+                        }
+                    }));
+                    match result {
+                        Ok(value) => value,
+                        Err(payload) => {
+                            self.report_failure(
+                                self.get_location(index),
+                                TrackRenderError::Panicked {
+                                    track: index,
+                                    message: ThreadedRender::get_panic_message(payload.as_ref()),
+                                },
+                            );
+                            None
+                        }
+                    }
+                };
+
+                if let Some((mut left, mut right, length)) = rendered {
+                    if let Some(track_mix) = self.track_mixes.get(&index) {
+                        track_mix.apply_gain(&mut left, &mut right);
                     }
-                    ArrayMath::sum(&left, &mut left_handler);
-                }
 
-                {
-                    let mut right_handler = master_right.lock().unwrap();
-                    let len = right_handler.len();
-                    if len < right.len() {
-                        right_handler.resize(right.len(), 0.0);
+                    let level = LevelReport::measure(&left, &right);
+                    track_reports.lock().unwrap()[index].merge(&level);
+
+                    ThreadedRender::mix(&master_left, &left, offset);
+                    ThreadedRender::mix(&master_right, &right, offset);
+                    if let Some((part_left, part_right)) = parts.get(index) {
+                        ThreadedRender::mix(part_left, &left, offset);
+                        ThreadedRender::mix(part_right, &right, offset);
                     }
-                    ArrayMath::sum(&right, &mut right_handler);
+
+                    event_length = offset + length;
                 }
 
-                self.rendered_track_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
-            });
+                let remaining_count =
+                    remaining_counts[index].fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+                if self.split == RenderSplit::Track && remaining_count == 1 {
+                    self.rendered_track_count
+                        .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                }
+
+                event_length
+            })
+            .max()
+            .unwrap_or(0);
+
+        // The tracks are merged into the channels, so all of them are done at once.
+        if self.split == RenderSplit::Channel {
+            self.rendered_track_count.fetch_add(
+                self.track_addr.len() as i32,
+                std::sync::atomic::Ordering::SeqCst,
+            );
+        }
+
+        let mut left = master_left.into_inner().unwrap();
+        let mut right = master_right.into_inner().unwrap();
+        let mut parts: Vec<(Vec<f32>, Vec<f32>)> = parts
+            .into_iter()
+            .map(|(left, right)| (left.into_inner().unwrap(), right.into_inner().unwrap()))
+            .collect();
+
+        if self.is_cancelled() || self.has_failed() {
+            return (left, right, parts);
+        }
+
+        // The parts are processed in the same way as the mix, so that they sum up to it.
+        if let (LoopRenderMode::Tail(tail_length), Some((loop_start, loop_end))) =
+            (self.loop_mode, self.loop_points)
+        {
+            let tail_length = (self.synthesizer_settings.sample_rate as f64 * tail_length) as usize;
+            for data in [&mut left, &mut right]
+                .into_iter()
+                .chain(parts.iter_mut().flat_map(|(left, right)| [left, right]))
+            {
+                ThreadedRender::fold_tail(data, loop_start, loop_end, tail_length);
+            }
+        } else if let (LoopRenderMode::FadeOut { count, fade }, Some((loop_start, loop_end))) =
+            (self.loop_mode, self.loop_points)
+        {
+            let fade_start = loop_start + count * (loop_end - loop_start);
+            let fade_length = (self.synthesizer_settings.sample_rate as f64 * fade) as usize;
+            for data in [&mut left, &mut right]
+                .into_iter()
+                .chain(parts.iter_mut().flat_map(|(left, right)| [left, right]))
+            {
+                ThreadedRender::fade_out(data, fade_start, fade_length);
+            }
+        } else {
+            ThreadedRender::trim_tail(&mut left, &mut right, 0, event_length, self.tail);
+        }
+
+        let gain = self.normalize(&mut left, &mut right);
+        for (part_left, part_right) in parts.iter_mut() {
+            part_left.resize(left.len(), 0_f32);
+            part_right.resize(right.len(), 0_f32);
+            part_left.iter_mut().for_each(|x| *x *= gain);
+            part_right.iter_mut().for_each(|x| *x *= gain);
+        }
+
+        self.report = Some(RenderReport {
+            tracks: track_reports.into_inner().unwrap(),
+            master: LevelReport::measure(&left, &right),
+        });
+
+        (left, right, parts)
+    }
+
+    /// Renders the whole MIDI file once, and returns the output converted to each of the specified sample rates.
+    ///
+    /// # Arguments
+    ///
+    /// * `sample_rates` - The sample rates of the outputs, such as 44100 and 48000.
+    ///
+    /// # Remarks
+    ///
+    /// The MIDI file is rendered at the sample rate of the synthesizer settings, in the same way as `render`,
+    /// and then the mixed output is resampled for each rate in parallel.
+    /// This is much faster than rendering the file again for each rate.
+    /// The output at the same rate as the synthesizer settings is returned as it is.
+    /// The outputs are in the same order as `sample_rates`.
+    /// The render report and the loop points are those of the output at the rate of the synthesizer settings.
+    /// Use `write_resampled_wave` to write the loop points converted to the other rates.
+    pub fn render_resampled(&mut self, sample_rates: &[i32]) -> Vec<(Vec<f32>, Vec<f32>)> {
+        for sample_rate in sample_rates.iter() {
+            ThreadedRender::check_sample_rate(*sample_rate);
+        }
+
+        let (left, right) = self.render();
+        let source_rate = self.synthesizer_settings.sample_rate;
+
+        let thread_pool = self.thread_pool.clone();
+        ThreadedRender::install(thread_pool.as_deref(), || {
+            sample_rates
+                .par_iter()
+                .map(|sample_rate| {
+                    let resampler = Resampler::new(source_rate, *sample_rate);
+                    rayon::join(|| resampler.process(&left), || resampler.process(&right))
+                })
+                .collect()
+        })
+    }
+
+    /// Renders the specified time range of the MIDI file and returns the left and right channels.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The start of the range in seconds.
+    /// * `end` - The end of the range in seconds.
+    ///
+    /// # Remarks
+    ///
+    /// Each track fast-forwards the program changes and the controllers before the start without rendering,
+    /// and then renders only the range, so any part of a long piece can be auditioned instantly.
+    /// The notes held at the start are started again from the beginning,
+    /// and the notes sounding beyond the end are cut off.
+    /// The range is on the timeline of the output, where the loop is expanded according to the loop mode.
+    /// The normalization is not applied, and neither the render report nor the progress is updated.
+    pub fn render_range(&mut self, start: f64, end: f64) -> (Vec<f32>, Vec<f32>) {
+        if start.is_nan() || start < 0.0 {
+            panic!("The start must be a non-negative value.");
+        }
+
+        if end.is_nan() || end < start {
+            panic!("The end must not be before the start.");
+        }
+
+        let thread_pool = self.thread_pool.clone();
+        ThreadedRender::install(thread_pool.as_deref(), || {
+            self.render_tracks_range(start, end)
+        })
+    }
+
+    fn render_tracks_range(&mut self, start: f64, end: f64) -> (Vec<f32>, Vec<f32>) {
+        let tracks = self.start_tracks();
+        let (tracks, loop_points) = self.prepare_tracks(tracks);
+        self.loop_points = loop_points;
+
+        let length = end - start;
+        let sample_count = (self.synthesizer_settings.sample_rate as f64 * length) as usize;
+
+        // The range is rendered apart from the whole, so it has its own progress.
+        let segments = self.create_segments(tracks, None);
+        let progress = Arc::new(RenderProgress::new());
+        progress.start(segments.len() as u64 * sample_count as u64);
+
+        let master_left: Mutex<Vec<f32>> = Mutex::new(vec![0_f32; sample_count]);
+        let master_right: Mutex<Vec<f32>> = Mutex::new(vec![0_f32; sample_count]);
+
+        segments.into_par_iter().for_each(|segment| {
+            let index = segment.index;
+            if let Some(mut track_render) =
+                self.create_track_render(index, segment.track, length, RenderTail::None, &progress)
+            {
+                // The events are processed at the same timing as `render` from the block boundary.
+                let (aligned_start, lead) = track_render.sequencer.align_position(start);
+                track_render.sequencer.seek(aligned_start);
+                track_render.skip_lead(lead);
+
+                let (mut left, mut right) = track_render.render(usize::MAX);
+                if let Some(track_mix) = self.track_mixes.get(&index) {
+                    track_mix.apply_gain(&mut left, &mut right);
+                }
+
+                ThreadedRender::mix(&master_left, &left, 0);
+                ThreadedRender::mix(&master_right, &right, 0);
+                ThreadedRender::report(&self.diagnostics, &track_render.sequencer);
+            }
+        });
 
         (
             master_left.into_inner().unwrap(),
             master_right.into_inner().unwrap(),
         )
     }
+
+    /// Renders the first `preview_length` seconds immediately,
+    /// and the rest on a background thread.
+    ///
+    /// The loop is rendered according to the loop mode, except that the tail and fade-out modes are not supported.
+    /// The normalization is not supported either, since the gain is unknown until the end.
+    /// The sequencer of each track keeps running from the preview into the rest,
+    /// so the two parts join seamlessly.
+    /// All the tracks are kept in memory until the rendering completes.
+    /// If the rendering is cancelled, the background thread stops shortly,
+    /// and the part rendered so far is returned as it is.
+    pub fn render_progressive(&mut self, preview_length: f64) -> ProgressiveRender {
+        if preview_length < 0.0 {
+            panic!("The preview length must be a non-negative value.");
+        }
+
+        if let LoopRenderMode::Tail(_) = self.loop_mode {
+            panic!("The loop tail mode is not supported by the progressive rendering.");
+        }
+
+        if let LoopRenderMode::FadeOut { .. } = self.loop_mode {
+            panic!("The fade-out is not supported by the progressive rendering.");
+        }
+
+        if self.normalization != Normalization::None {
+            panic!("The normalization is not supported by the progressive rendering.");
+        }
+
+        let thread_pool = self.thread_pool.clone();
+        ThreadedRender::install(thread_pool.as_deref(), || {
+            self.render_tracks_progressive(preview_length)
+        })
+    }
+
+    fn render_tracks_progressive(&mut self, preview_length: f64) -> ProgressiveRender {
+        let preview_sample_count =
+            (self.synthesizer_settings.sample_rate as f64 * preview_length) as usize;
+
+        self.rendered_track_count
+            .store(0, std::sync::atomic::Ordering::SeqCst);
+
+        let tracks = self.start_tracks();
+        let (tracks, loop_points) = self.prepare_tracks(tracks);
+        self.loop_points = loop_points;
+
+        // The tracks are not split into segments, since each of them continues from the preview.
+        let segments = self.create_segments(tracks, None);
+        self.start_progress(&segments);
+
+        let tail = self.get_track_tail();
+        let mut track_renders = segments
+            .into_par_iter()
+            .filter_map(|segment| {
+                let track_mix = self.track_mixes.get(&segment.index).copied();
+                self.create_track_render(
+                    segment.index,
+                    segment.track,
+                    segment.length,
+                    tail,
+                    &self.progress,
+                )
+                .map(|x| (track_mix, x))
+            })
+            .collect::<Vec<(Option<TrackMix>, TrackRender)>>();
+
+        // The tracks which failed to load will never be rendered.
+        if self.split == RenderSplit::Track {
+            self.rendered_track_count.fetch_add(
+                (self.track_addr.len() - track_renders.len()) as i32,
+                std::sync::atomic::Ordering::SeqCst,
+            );
+        }
+
+        let preview_left: Mutex<Vec<f32>> = Mutex::new(Vec::new());
+        let preview_right: Mutex<Vec<f32>> = Mutex::new(Vec::new());
+
+        track_renders
+            .par_iter_mut()
+            .for_each(|(track_mix, track_render)| {
+                let (mut left, mut right) = track_render.render(preview_sample_count);
+                if let Some(track_mix) = track_mix {
+                    track_mix.apply_gain(&mut left, &mut right);
+                }
+
+                ThreadedRender::mix(&preview_left, &left, 0);
+                ThreadedRender::mix(&preview_right, &right, 0);
+            });
+
+        let mut preview_left = preview_left.into_inner().unwrap();
+        let mut preview_right = preview_right.into_inner().unwrap();
+
+        // The rest starts right after the preview, even if some tracks end earlier.
+        let preview_end = if track_renders.iter().all(|(_, x)| x.is_finished()) {
+            preview_left.len()
+        } else {
+            preview_sample_count
+        };
+        preview_left.resize(preview_end, 0_f32);
+        preview_right.resize(preview_end, 0_f32);
+
+        let event_length = track_renders
+            .iter()
+            .map(|(_, x)| x.get_event_length())
+            .max()
+            .unwrap_or(0);
+        let tail = self.tail;
+        let cancellation_token = self.cancellation_token.clone();
+        let thread_pool = self.thread_pool.clone();
+        let split = self.split;
+        let track_count = self.track_addr.len();
+
+        let rendered_track_count = Arc::clone(&self.rendered_track_count);
+        let diagnostics = Arc::clone(&self.diagnostics);
+        let handle = thread::spawn(move || {
+            ThreadedRender::install(thread_pool.as_deref(), || {
+                let rest_left: Mutex<Vec<f32>> = Mutex::new(Vec::new());
+                let rest_right: Mutex<Vec<f32>> = Mutex::new(Vec::new());
+
+                track_renders
+                    .into_par_iter()
+                    .for_each(|(track_mix, mut track_render)| {
+                        let (mut left, mut right) = track_render.render(usize::MAX);
+                        if let Some(track_mix) = track_mix {
+                            track_mix.apply_gain(&mut left, &mut right);
+                        }
+
+                        ThreadedRender::mix(&rest_left, &left, 0);
+                        ThreadedRender::mix(&rest_right, &right, 0);
+                        ThreadedRender::report(&diagnostics, &track_render.sequencer);
+
+                        if split == RenderSplit::Track {
+                            rendered_track_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                        }
+                    });
+
+                if split == RenderSplit::Channel {
+                    rendered_track_count
+                        .fetch_add(track_count as i32, std::sync::atomic::Ordering::SeqCst);
+                }
+
+                let mut rest_left = rest_left.into_inner().unwrap();
+                let mut rest_right = rest_right.into_inner().unwrap();
+                if matches!(&cancellation_token, Some(token) if token.is_cancelled()) {
+                    return (rest_left, rest_right);
+                }
+
+                ThreadedRender::trim_tail(
+                    &mut rest_left,
+                    &mut rest_right,
+                    preview_end,
+                    event_length,
+                    tail,
+                );
+
+                (rest_left, rest_right)
+            })
+        });
+
+        ProgressiveRender::new(preview_left, preview_right, handle)
+    }
+
+    /// Estimates the cost of the rendering without synthesizing any sound.
+    ///
+    /// # Remarks
+    ///
+    /// All the tracks are read and prepared in the same way as `render`,
+    /// according to the loop mode, the tail, the split mode and the segment length.
+    /// This takes much less time than the rendering, so batch tools can schedule the work
+    /// and warn the users before starting a long rendering.
+    /// The tracks which cannot be read are reported in the diagnostics of the analysis,
+    /// and the failures and the diagnostics of the renderer are left as they are.
+    pub fn analyze(&self) -> RenderAnalysis {
+        let thread_pool = self.thread_pool.as_deref();
+        ThreadedRender::install(thread_pool, || self.analyze_tracks())
+    }
+
+    fn analyze_tracks(&self) -> RenderAnalysis {
+        let (tracks, errors) = self.read_tracks();
+        let (tracks, _) = self.prepare_tracks(tracks);
+
+        // The failures are reported only in the analysis, and not to the renderer.
+        let mut diagnostics = self.sound_font.get_diagnostics().clone();
+        diagnostics.extend(&self.diagnostics.lock().unwrap());
+        for error in errors.iter() {
+            ThreadedRender::push_failure(
+                &mut diagnostics,
+                DiagnosticLocation::Track(error.get_track()),
+                error,
+            );
+        }
+
+        let segments = self.create_segments(tracks, self.segment_length);
+        let sample_rate = self.synthesizer_settings.sample_rate;
+
+        let mut analyses: Vec<TrackAnalysis> = Vec::new();
+        let mut jobs: Vec<usize> = Vec::new();
+        let mut track_memory: usize = 0;
+        let mut sample_count: usize = 0;
+        for segment in segments.iter() {
+            let tail = self.get_segment_tail(segment);
+            let segment_sample_count =
+                TrackRender::get_max_length(sample_rate, segment.length, tail);
+            sample_count = cmp::max(sample_count, segment.offset + segment_sample_count);
+
+            let event_count = segment.track.get_event_count();
+            track_memory += event_count * (mem::size_of::<Message>() + mem::size_of::<f64>());
+            jobs.push(2 * segment_sample_count * mem::size_of::<f32>());
+
+            let analysis = match analyses.iter_mut().find(|x| x.index == segment.index) {
+                Some(value) => value,
+                None => {
+                    analyses.push(TrackAnalysis {
+                        index: segment.index,
+                        event_count: 0,
+                        note_count: 0,
+                        length: 0.0,
+                        sample_count: 0,
+                        segment_count: 0,
+                        presets: Vec::new(),
+                    });
+                    analyses.last_mut().unwrap()
+                }
+            };
+            analysis.event_count += event_count;
+            analysis.note_count += segment
+                .track
+                .messages
+                .iter()
+                .filter(|x| {
+                    x.get_message_type() == Message::NORMAL && x.command == 0x90 && x.data2 > 0
+                })
+                .count();
+            analysis.length = f64::max(
+                analysis.length,
+                segment.offset as f64 / sample_rate as f64 + segment.length,
+            );
+            analysis.sample_count += segment_sample_count;
+            analysis.segment_count += 1;
+
+            let mut presets: BTreeSet<(i32, i32)> = analysis.presets.iter().copied().collect();
+            MidiFile::find_presets(&[&segment.track], &self.synthesizer_settings, &mut presets);
+            analysis.presets = presets.into_iter().collect();
+        }
+        analyses.sort_by_key(|x| x.index);
+
+        for (bank_number, patch_number) in analyses.iter().flat_map(|x| x.presets.iter()) {
+            if self
+                .sound_font
+                .preset_lookup
+                .get(*bank_number, *patch_number)
+                .is_none()
+            {
+                diagnostics.push_once(
+                    DiagnosticCode::PresetNotFound,
+                    DiagnosticSeverity::Warning,
+                    DiagnosticLocation::Preset {
+                        bank_number: *bank_number,
+                        patch_number: *patch_number,
+                    },
+                    || {
+                        format!(
+                            "the preset {}:{} was not found and a fallback preset will be used",
+                            bank_number, patch_number
+                        )
+                    },
+                );
+            }
+        }
+
+        // Each worker holds a synthesizer and the waveform of a track until it is mixed.
+        let thread_count = match self.thread_pool.as_ref() {
+            Some(thread_pool) => thread_pool.current_num_threads(),
+            None => rayon::current_num_threads(),
+        };
+        let synthesizer_memory =
+            match Synthesizer::new(&self.sound_font, &self.synthesizer_settings) {
+                Ok(synthesizer) => synthesizer.get_memory_size(),
+                Err(_) => 0,
+            };
+        jobs.sort_by_key(|x| cmp::Reverse(*x));
+        let worker_memory: usize = jobs
+            .iter()
+            .take(thread_count)
+            .map(|x| x + synthesizer_memory)
+            .sum();
+        let output_memory = 2 * sample_count * mem::size_of::<f32>();
+
+        RenderAnalysis {
+            total_sample_count: analyses.iter().map(|x| x.sample_count as u64).sum(),
+            tracks: analyses,
+            sample_count,
+            thread_count,
+            peak_memory: track_memory + output_memory + worker_memory,
+            diagnostics,
+        }
+    }
+
+    /// Gets the number of the tracks declared in the MIDI file.
+    pub fn get_track_count(&self) -> i32 {
+        self.track_count
+    }
+
+    /// Gets the SoundFont shared by the workers.
+    pub fn get_sound_font(&self) -> &Arc<SoundFont> {
+        &self.sound_font
+    }
+
+    /// Gets the counter of the rendered tracks.
+    ///
+    /// # Remarks
+    ///
+    /// The counter is shared, so the progress can be read from another thread while rendering.
+    /// It is reset to zero when each rendering starts.
+    pub fn get_progress(&self) -> Arc<AtomicI32> {
+        Arc::clone(&self.rendered_track_count)
+    }
+
+    /// Gets the thread pool used by the rendering.
+    pub fn get_thread_pool(&self) -> Option<&Arc<ThreadPool>> {
+        self.thread_pool.as_ref()
+    }
+
+    /// Sets the thread pool used by the rendering.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The thread pool, or `None` to use the global rayon pool.
+    ///
+    /// # Remarks
+    ///
+    /// All the parallel work of the rendering, including the parallel voice processing,
+    /// runs on the pool, so the rendering does not compete with the other users of the global pool.
+    pub fn set_thread_pool(&mut self, value: Option<Arc<ThreadPool>>) {
+        self.thread_pool = value;
+    }
+
+    /// Confines the rendering to a new thread pool with the specified number of threads.
+    ///
+    /// # Arguments
+    ///
+    /// * `thread_count` - The number of the worker threads.
+    ///
+    /// # Remarks
+    ///
+    /// The value must be greater than zero.
+    pub fn set_thread_count(&mut self, thread_count: usize) -> Result<(), ThreadPoolBuildError> {
+        if thread_count == 0 {
+            panic!("The thread count must be greater than zero.");
+        }
+
+        let thread_pool = ThreadPoolBuilder::new().num_threads(thread_count).build()?;
+        self.thread_pool = Some(Arc::new(thread_pool));
+
+        Ok(())
+    }
+
+    /// Gets the cancellation token checked by the rendering.
+    pub fn get_cancellation_token(&self) -> Option<&CancellationToken> {
+        self.cancellation_token.as_ref()
+    }
+
+    /// Sets the cancellation token checked by the rendering.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The token, or `None` to disable the cancellation.
+    ///
+    /// # Remarks
+    ///
+    /// The token is checked between the render blocks of each track,
+    /// so calling `cancel` from another thread stops all the workers shortly.
+    /// The token is not reset by the rendering, so a new token is needed to render again.
+    pub fn set_cancellation_token(&mut self, value: Option<CancellationToken>) {
+        self.cancellation_token = value;
+    }
+
+    /// Gets a value that indicates whether the cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        match self.cancellation_token.as_ref() {
+            Some(token) => token.is_cancelled(),
+            None => false,
+        }
+    }
+
+    /// Gets how the tracks which fail to render are handled.
+    pub fn get_error_policy(&self) -> TrackErrorPolicy {
+        self.error_policy
+    }
+
+    /// Sets how the tracks which fail to render are handled.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The error policy. The default is `TrackErrorPolicy::Skip`.
+    pub fn set_error_policy(&mut self, value: TrackErrorPolicy) {
+        self.error_policy = value;
+    }
+
+    /// Gets the failures of the tracks in the last rendering, in the order they occurred.
+    ///
+    /// # Remarks
+    ///
+    /// A panic is caught only by `render` and the methods based on it.
+    /// With the other rendering methods, the tracks which fail to load are still reported.
+    pub fn get_track_errors(&self) -> Vec<TrackRenderError> {
+        self.track_errors.lock().unwrap().clone()
+    }
+
+    /// Gets the progress of the rendering in sample frames.
+    ///
+    /// # Remarks
+    ///
+    /// Unlike `get_progress`, this advances while each track is being rendered,
+    /// so it moves smoothly even for a MIDI file with a single long track.
+    /// The progress is shared, so it can be read from another thread while rendering.
+    pub fn get_render_progress(&self) -> Arc<RenderProgress> {
+        Arc::clone(&self.progress)
+    }
+
+    /// Gets the diagnostics reported while rendering.
+    ///
+    /// # Remarks
+    ///
+    /// A track which fails to load is skipped and reported as an error,
+    /// instead of aborting the whole rendering.
+    pub fn get_diagnostics(&self) -> Diagnostics {
+        self.diagnostics.lock().unwrap().clone()
+    }
+
+    /// Gets the type of the loop extension used to find the loop.
+    pub fn get_loop_type(&self) -> MidiFileLoopType {
+        self.loop_type
+    }
+
+    /// Sets the type of the loop extension used to find the loop.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The loop type.
+    ///
+    /// # Remarks
+    ///
+    /// The default value is `MidiFileLoopType::LoopPoint(0)`.
+    /// The loop start and end are searched in all the tracks, and shared by them.
+    /// The loop is rendered only if the loop mode is not `LoopRenderMode::Once`.
+    pub fn set_loop_type(&mut self, value: MidiFileLoopType) {
+        self.loop_type = value;
+    }
+
+    /// Gets how the loop is rendered.
+    pub fn get_loop_mode(&self) -> LoopRenderMode {
+        self.loop_mode
+    }
+
+    /// Sets how the loop is rendered.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The loop mode.
+    ///
+    /// # Remarks
+    ///
+    /// The default value is `LoopRenderMode::Once`, which ignores the loop.
+    pub fn set_loop_mode(&mut self, value: LoopRenderMode) {
+        value.check();
+        self.loop_mode = value;
+    }
+
+    /// Gets how long the sound is rendered after the last event.
+    pub fn get_tail(&self) -> RenderTail {
+        self.tail
+    }
+
+    /// Sets how long the sound is rendered after the last event.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The tail.
+    ///
+    /// # Remarks
+    ///
+    /// The default value is `RenderTail::None`, which cuts the waveform at the last event.
+    /// The tail is applied to each track, and the silence at the end of the mix is trimmed.
+    /// With `LoopRenderMode::Tail`, the tail is not used, since the output ends at the loop end point.
+    pub fn set_tail(&mut self, value: RenderTail) {
+        value.check();
+        self.tail = value;
+    }
+
+    /// Gets how the MIDI file is divided into the parts rendered in parallel.
+    pub fn get_split(&self) -> RenderSplit {
+        self.split
+    }
+
+    /// Sets how the MIDI file is divided into the parts rendered in parallel.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The split mode.
+    ///
+    /// # Remarks
+    ///
+    /// The default value is `RenderSplit::Track`.
+    /// With `RenderSplit::Channel`, a synthesizer is used for each channel of each MIDI port
+    /// regardless of the number of the tracks,
+    /// and the controllers of a channel take effect even if they are in another track than the notes.
+    /// In this mode, the counter returned by `get_progress` advances only when all the channels are done,
+    /// so use `get_render_progress` instead.
+    pub fn set_split(&mut self, value: RenderSplit) {
+        self.split = value;
+    }
+
+    /// Gets the minimum length of the segments which the long tracks are split into.
+    pub fn get_segment_length(&self) -> Option<f64> {
+        self.segment_length
+    }
+
+    /// Sets the minimum length of the segments which the long tracks are split into.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The minimum length in seconds, or `None` to render each track in one go.
+    ///
+    /// # Remarks
+    ///
+    /// The default value is `None`.
+    /// If set, each track is split where no note is sounding and the sustain is off,
+    /// and the segments are rendered in parallel by their own synthesizers.
+    /// The controllers before each split point are sent again at the start of the segment,
+    /// and the release and the reverb ringing after the split point are rendered with the previous segment.
+    /// This keeps all the workers busy even if a few tracks are much longer than the others.
+    /// The segments are used only by `render`, and not by `render_progressive`.
+    pub fn set_segment_length(&mut self, value: Option<f64>) {
+        if let Some(length) = value {
+            if length <= 0.0 {
+                panic!("The segment length must be greater than zero.");
+            }
+        }
+
+        self.segment_length = value;
+    }
+
+    /// Gets the loop start and end points in samples found by the last rendering.
+    ///
+    /// # Remarks
+    ///
+    /// The points are those of the first iteration of the loop.
+    /// With `LoopRenderMode::Tail`, the output can be played back seamlessly
+    /// by jumping from the end point to the start point.
+    /// If the loop mode is `LoopRenderMode::Once`, or the loop is empty, this is `None`.
+    pub fn get_loop_points(&self) -> Option<(usize, usize)> {
+        self.loop_points
+    }
+
+    /// Writes the rendered waveform to the stream as a WAV file with the loop points.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The data stream used to write the WAV file.
+    /// * `left` - The left channel returned by `render`.
+    /// * `right` - The right channel returned by `render`.
+    ///
+    /// # Remarks
+    ///
+    /// The samples are written in the 32-bit floating-point format.
+    /// If the last rendering found the loop points, they are written as a sampler loop in the `smpl` chunk
+    /// and as two cue points in the `cue ` chunk, so that game engines can loop the file seamlessly.
+    /// The points are meaningful for the playback only with `LoopRenderMode::Tail`.
+    pub fn write_wave<W: Write>(
+        &self,
+        writer: &mut W,
+        left: &[f32],
+        right: &[f32],
+    ) -> Result<(), io::Error> {
+        self.write_wave_with_options(writer, left, right, &WaveOptions::new())
+    }
+
+    /// Writes the rendered waveform to the stream as a WAV file with the loop points and the specified options.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The data stream used to write the WAV file.
+    /// * `left` - The left channel returned by `render`.
+    /// * `right` - The right channel returned by `render`.
+    /// * `options` - The sample format and the broadcast WAV metadata.
+    ///
+    /// # Remarks
+    ///
+    /// This is the same as `write_wave`, except that the samples are written in the format of the options.
+    pub fn write_wave_with_options<W: Write>(
+        &self,
+        writer: &mut W,
+        left: &[f32],
+        right: &[f32],
+        options: &WaveOptions,
+    ) -> Result<(), io::Error> {
+        let loop_points = self
+            .loop_points
+            .filter(|(start, end)| start < end && *end <= left.len());
+        options.write(
+            writer,
+            left,
+            right,
+            self.synthesizer_settings.sample_rate,
+            loop_points,
+        )
+    }
+
+    /// Writes the waveform returned by `render_resampled` to the stream as a WAV file with the loop points.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The data stream used to write the WAV file.
+    /// * `left` - The left channel returned by `render_resampled`.
+    /// * `right` - The right channel returned by `render_resampled`.
+    /// * `sample_rate` - The sample rate of the waveform.
+    ///
+    /// # Remarks
+    ///
+    /// This is the same as `write_wave`, except that the loop points are converted to the sample rate,
+    /// and are rounded to the nearest sample.
+    pub fn write_resampled_wave<W: Write>(
+        &self,
+        writer: &mut W,
+        left: &[f32],
+        right: &[f32],
+        sample_rate: i32,
+    ) -> Result<(), io::Error> {
+        self.write_resampled_wave_with_options(
+            writer,
+            left,
+            right,
+            sample_rate,
+            &WaveOptions::new(),
+        )
+    }
+
+    /// Writes the waveform returned by `render_resampled` to the stream as a WAV file
+    /// with the loop points and the specified options.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The data stream used to write the WAV file.
+    /// * `left` - The left channel returned by `render_resampled`.
+    /// * `right` - The right channel returned by `render_resampled`.
+    /// * `sample_rate` - The sample rate of the waveform.
+    /// * `options` - The sample format and the broadcast WAV metadata.
+    ///
+    /// # Remarks
+    ///
+    /// This is the same as `write_resampled_wave`, except that the samples are written in the format of the options.
+    pub fn write_resampled_wave_with_options<W: Write>(
+        &self,
+        writer: &mut W,
+        left: &[f32],
+        right: &[f32],
+        sample_rate: i32,
+        options: &WaveOptions,
+    ) -> Result<(), io::Error> {
+        ThreadedRender::check_sample_rate(sample_rate);
+
+        let resampler = Resampler::new(self.synthesizer_settings.sample_rate, sample_rate);
+        let loop_points = self
+            .loop_points
+            .map(|(start, end)| {
+                (
+                    resampler.convert_position(start),
+                    resampler.convert_position(end),
+                )
+            })
+            .filter(|(start, end)| start < end && *end <= left.len());
+        options.write(writer, left, right, sample_rate, loop_points)
+    }
+
+    fn check_sample_rate(sample_rate: i32) {
+        if !(16_000..=192_000).contains(&sample_rate) {
+            panic!("The sample rate must be between 16000 and 192000.");
+        }
+    }
+
+    // Clears the failures of the previous rendering, and reads all the tracks to be rendered.
+    // The tracks which cannot be read are reported as failed.
+    fn start_tracks(&self) -> Vec<Option<MidiTrack>> {
+        self.track_errors.lock().unwrap().clear();
+        self.failed
+            .store(false, std::sync::atomic::Ordering::SeqCst);
+
+        let (tracks, errors) = self.read_tracks();
+        for error in errors {
+            self.report_failure(DiagnosticLocation::Track(error.get_track()), error);
+        }
+
+        tracks
+    }
+
+    // Reads all the tracks, and returns the failures along with them.
+    fn read_tracks(&self) -> (Vec<Option<MidiTrack>>, Vec<TrackRenderError>) {
+        let results = self
+            .track_addr
+            .par_iter()
+            .enumerate()
+            .map(|(index, (start, size))| {
+                if self.is_cancelled() {
+                    return Ok(None);
+                }
+
+                match self.read_track(index, *start, *size) {
+                    Ok(value) => Ok(Some(value)),
+                    Err(err) => Err(TrackRenderError::ReadFailed {
+                        track: index,
+                        message: err.to_string(),
+                    }),
+                }
+            })
+            .collect::<Vec<Result<Option<MidiTrack>, TrackRenderError>>>();
+
+        let mut tracks = Vec::with_capacity(results.len());
+        let mut errors = Vec::new();
+        for result in results {
+            match result {
+                Ok(track) => tracks.push(track),
+                Err(err) => {
+                    tracks.push(None);
+                    errors.push(err);
+                }
+            }
+        }
+
+        (tracks, errors)
+    }
+
+    // Divides the tracks according to the split mode, and expands the loop in each of them.
+    // The loop points are shared by all the tracks, so that they stay in sync.
+    #[allow(clippy::type_complexity)]
+    fn prepare_tracks(
+        &self,
+        tracks: Vec<Option<MidiTrack>>,
+    ) -> (Vec<Option<(MidiTrack, f64)>>, Option<(usize, usize)>) {
+        let tracks = match self.split {
+            RenderSplit::Track => tracks,
+            RenderSplit::Channel => {
+                let tracks = tracks.iter().flatten().collect::<Vec<&MidiTrack>>();
+                MidiTrack::split_channels(&tracks, self.synthesizer_settings.channel_count)
+            }
+        };
+
+        let tracks = tracks
+            .into_iter()
+            .enumerate()
+            .map(
+                |(index, track)| match (track, self.track_mixes.get(&index)) {
+                    (Some(track), Some(track_mix)) => {
+                        Some(track_mix.apply_events(&track, &self.synthesizer_settings))
+                    }
+                    (track, _) => track,
+                },
+            )
+            .collect::<Vec<Option<MidiTrack>>>();
+
+        let once = || {
+            let tracks = tracks
+                .iter()
+                .map(|x| x.as_ref().map(|x| (x.clone(), x.get_length())))
+                .collect();
+            (tracks, None)
+        };
+
+        if self.loop_mode == LoopRenderMode::Once {
+            return once();
+        }
+
+        let length = tracks
+            .iter()
+            .flatten()
+            .map(|x| x.get_length())
+            .fold(0.0, f64::max);
+        let loop_start =
+            ThreadedRender::find_marker(&tracks, Message::LOOP_START, -1.0).unwrap_or(0.0);
+        let loop_end =
+            ThreadedRender::find_marker(&tracks, Message::LOOP_END, loop_start).unwrap_or(length);
+        if loop_end <= loop_start {
+            return once();
+        }
+
+        let loop_length = loop_end - loop_start;
+        let (count, tail_length) = match self.loop_mode {
+            LoopRenderMode::Count(count) => (count, None),
+            // The passes after the count are played while fading out, and cut at the end of the fade-out.
+            LoopRenderMode::FadeOut { count, fade } => {
+                let fade_count = cmp::max((fade / loop_length).ceil() as usize, 1);
+                let fade_end = loop_start + loop_length * count as f64 + fade;
+                (count + fade_count, Some(fade_end - loop_end))
+            }
+            LoopRenderMode::Duration(duration) => {
+                let count = ((duration - loop_start) / loop_length).ceil();
+                (cmp::max(count as usize, 1), None)
+            }
+            LoopRenderMode::Tail(tail_length) => (1, Some(tail_length)),
+            _ => (1, None),
+        };
+
+        let tracks = tracks
+            .iter()
+            .map(|x| {
+                x.as_ref().map(|x| {
+                    let keep_rest = tail_length.is_none();
+                    let track = x.unroll_loop(loop_start, loop_end, count, keep_rest);
+                    let length = match tail_length {
+                        Some(tail_length) => loop_end + tail_length,
+                        None => track.get_length(),
+                    };
+                    (track, length)
+                })
+            })
+            .collect();
+
+        let sample_rate = self.synthesizer_settings.sample_rate as f64;
+        let loop_points = (
+            (sample_rate * loop_start) as usize,
+            (sample_rate * loop_end) as usize,
+        );
+
+        (tracks, Some(loop_points))
+    }
+
+    // The earliest marker after the specified time in any track.
+    fn find_marker(tracks: &[Option<MidiTrack>], message_type: u8, after: f64) -> Option<f64> {
+        tracks
+            .iter()
+            .flatten()
+            .flat_map(|x| x.messages.iter().zip(x.times.iter()))
+            .filter(|(message, time)| message.get_message_type() == message_type && **time > after)
+            .map(|(_, time)| *time)
+            .reduce(f64::min)
+    }
+
+    fn read_track(
+        &self,
+        index: usize,
+        start: usize,
+        size: usize,
+    ) -> Result<MidiTrack, MidiFileError> {
+        let mut reader = Cursor::new(&self.data[start..start + size]);
+        let track = MidiFile::read_track(&mut reader, index, self.loop_type)?;
+        let mut track = track.into_iter().map(|(x, y)| (x, y, None)).collect();
+
+        // The tempo changes of the track itself are already in the tempo map.
+        MidiFile::merge_tempo_changes(index, &mut track, &self.tempo_map);
+        if let MidiFileLoopType::LoopPoint(loop_point) = self.loop_type {
+            if loop_point != 0 {
+                let loop_point = loop_point as i32;
+                let position = track.partition_point(|x| x.1 <= loop_point);
+                track.insert(position, (Message::loop_start(), loop_point, None));
+            }
+        }
+
+        let (casted, _) = MidiFile::cast_delta(track, self.resolution);
+
+        Ok(casted)
+    }
+
+    // Splits each track into the segments no shorter than the specified length.
+    // The segments start at the block boundaries, so that the events are processed at the same timing.
+    fn create_segments(
+        &self,
+        tracks: Vec<Option<(MidiTrack, f64)>>,
+        segment_length: Option<f64>,
+    ) -> Vec<TrackSegment> {
+        let sample_rate = self.synthesizer_settings.sample_rate as f64;
+        let quantum = self.synthesizer_settings.block_size as f64 / sample_rate;
+
+        let mut segments = Vec::new();
+        for (index, track) in tracks.into_iter().enumerate() {
+            let (track, length) = match track {
+                Some(value) => value,
+                None => continue,
+            };
+
+            let points = match segment_length {
+                Some(segment_length) => track.find_split_points(segment_length, quantum),
+                None => Vec::new(),
+            };
+            if points.is_empty() {
+                segments.push(TrackSegment {
+                    index,
+                    offset: 0,
+                    track,
+                    length,
+                    is_last: true,
+                });
+                continue;
+            }
+
+            let count = points.len() + 1;
+            for (i, segment) in track.split(&points).into_iter().enumerate() {
+                let start = if i == 0 { 0.0 } else { points[i - 1] };
+                let end = if i < points.len() { points[i] } else { length };
+                let block_count = (start / quantum).round() as usize;
+                let offset = block_count * self.synthesizer_settings.block_size;
+
+                // The length is taken from the sample position of the end,
+                // so that the segments end at the same sample as the whole track.
+                // Half a sample is added to keep it from being truncated by the rounding error.
+                let end_position = (sample_rate * end) as usize;
+                let length = (end_position.saturating_sub(offset) as f64 + 0.5) / sample_rate;
+
+                segments.push(TrackSegment {
+                    index,
+                    offset,
+                    track: segment,
+                    length,
+                    is_last: i == count - 1,
+                });
+            }
+        }
+
+        // The longest segments go first to keep all the workers busy until the end.
+        segments.sort_by(|a, b| b.length.total_cmp(&a.length));
+
+        segments
+    }
+
+    fn create_track_render(
+        &self,
+        index: usize,
+        track: MidiTrack,
+        length: f64,
+        tail: RenderTail,
+        progress: &Arc<RenderProgress>,
+    ) -> Option<TrackRender> {
+        let synthesizer = match Synthesizer::new(&self.sound_font, &self.synthesizer_settings) {
+            Ok(value) => value,
+            Err(err) => {
+                self.report_failure(
+                    self.get_location(index),
+                    TrackRenderError::SynthesizerFailed {
+                        track: index,
+                        message: err.to_string(),
+                    },
+                );
+                // The track will never be rendered, so skip it in the progress.
+                progress.add(TrackRender::get_max_length(
+                    self.synthesizer_settings.sample_rate,
+                    length,
+                    tail,
+                ));
+                return None;
+            }
+        };
+
+        let mut sequencer = MidiFileSequencer::new(synthesizer);
+        for (channel, voice_source) in self.voice_sources.iter() {
+            sequencer
+                .get_synthesizer_mut()
+                .set_channel_voice_source(*channel, Some(Arc::clone(voice_source)));
+        }
+        sequencer.set_skip_silence(true);
+        sequencer.set_cancellation_token(self.cancellation_token.clone());
+        for (target, automation) in self.automations.iter() {
+            sequencer.set_automation(*target, Some(automation.clone()));
+        }
+        sequencer.play(track, false);
+
+        Some(TrackRender::new(index, sequencer, length, tail, progress))
+    }
+
+    fn get_track_tail(&self) -> RenderTail {
+        // The tail after the loop end is already included in the length.
+        match (self.loop_mode, self.loop_points) {
+            (LoopRenderMode::Tail(_), Some(_)) => RenderTail::None,
+            (LoopRenderMode::FadeOut { .. }, Some(_)) => RenderTail::None,
+            _ => self.tail,
+        }
+    }
+
+    fn get_segment_tail(&self, segment: &TrackSegment) -> RenderTail {
+        if segment.is_last {
+            self.get_track_tail()
+        } else {
+            ThreadedRender::SEGMENT_TAIL
+        }
+    }
+
+    // Everything which changes the sound of the segment before the track mix gain.
+    fn get_cache_key(
+        &self,
+        sound_font_key: &CacheKey,
+        segment: &TrackSegment,
+        tail: RenderTail,
+    ) -> u64 {
+        let mut key = sound_font_key.clone();
+        key.write_str(env!("CARGO_PKG_VERSION"));
+
+        let settings = &self.synthesizer_settings;
+        key.write_u64(settings.sample_rate as u64);
+        key.write_u64(settings.block_size as u64);
+        key.write_u64(settings.maximum_polyphony as u64);
+        key.write_u64(settings.enable_reverb_and_chorus as u64);
+        key.write_u64(settings.parallel_voices as u64);
+        key.write_u64(settings.channel_count as u64);
+        key.write_u64(settings.enable_gs_rhythm_parts as u64);
+        for percussion in settings.percussion_channels.iter() {
+            key.write_u64(*percussion as u64);
+        }
+        key.write_str(&format!(
+            "{:?} {:?} {:?} {:?} {:?} {:?}",
+            settings.voice_stealing,
+            settings.volume_curve,
+            settings.expression_curve,
+            settings.pedal_curve,
+            settings.profile,
+            settings.exclusive_class
+        ));
+        key.write_str(&format!("{:?} {:?}", self.automations, tail));
+
+        key.write_u64(segment.offset as u64);
+        key.write_f64(segment.length);
+        for (message, time) in segment
+            .track
+            .messages
+            .iter()
+            .zip(segment.track.times.iter())
+        {
+            key.write_bytes(&[
+                message.channel,
+                message.command,
+                message.data1,
+                message.data2,
+            ]);
+            key.write_f64(*time);
+        }
+
+        key.finish()
+    }
+
+    fn start_progress(&self, segments: &[TrackSegment]) {
+        let total = segments
+            .iter()
+            .map(|segment| {
+                let tail = self.get_segment_tail(segment);
+                TrackRender::get_max_length(
+                    self.synthesizer_settings.sample_rate,
+                    segment.length,
+                    tail,
+                ) as u64
+            })
+            .sum();
+        self.progress.start(total);
+    }
+
+    fn report_failure(&self, location: DiagnosticLocation, error: TrackRenderError) {
+        ThreadedRender::push_failure(&mut self.diagnostics.lock().unwrap(), location, &error);
+
+        self.track_errors.lock().unwrap().push(error);
+        if self.error_policy == TrackErrorPolicy::FailFast {
+            self.failed.store(true, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    fn push_failure(
+        diagnostics: &mut Diagnostics,
+        location: DiagnosticLocation,
+        error: &TrackRenderError,
+    ) {
+        diagnostics.push_once(
+            DiagnosticCode::TrackRenderFailed,
+            DiagnosticSeverity::Error,
+            location,
+            || format!("the track was skipped: {}", error.get_message()),
+        );
+    }
+
+    fn has_failed(&self) -> bool {
+        self.failed.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    fn get_location(&self, index: usize) -> DiagnosticLocation {
+        match self.split {
+            RenderSplit::Track => DiagnosticLocation::Track(index),
+            RenderSplit::Channel => DiagnosticLocation::Channel(index),
+        }
+    }
+
+    fn get_panic_message(payload: &(dyn Any + Send)) -> String {
+        if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "unknown panic".to_string()
+        }
+    }
+
+    /// Gets the mix settings of the specified track.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the track, or the channel in the channel split mode.
+    pub fn get_track_mix(&self, index: usize) -> Option<TrackMix> {
+        self.track_mixes.get(&index).copied()
+    }
+
+    /// Sets the mix settings of the specified track.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the track, or the channel in the channel split mode.
+    /// * `value` - The mix settings, or `None` to render the track as it is.
+    ///
+    /// # Remarks
+    ///
+    /// The events of the track are changed before the rendering,
+    /// and the gain and the pan are applied to the waveform of the track before it is mixed.
+    pub fn set_track_mix(&mut self, index: usize, value: Option<TrackMix>) {
+        match value {
+            Some(track_mix) => {
+                track_mix.check();
+                self.track_mixes.insert(index, track_mix);
+            }
+            None => {
+                self.track_mixes.remove(&index);
+            }
+        }
+    }
+
+    /// Gets the automation of the specified parameter.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The parameter changed by the automation.
+    pub fn get_automation(&self, target: AutomationTarget) -> Option<&Automation> {
+        self.automations.get(&target)
+    }
+
+    /// Sets the automation of the specified parameter.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The parameter changed by the automation.
+    /// * `value` - The automation, or `None` to remove it.
+    ///
+    /// # Remarks
+    ///
+    /// The automation is applied to every track in the same way,
+    /// and its time is measured from the start of the output.
+    /// See `MidiFileSequencer::set_automation` for the details.
+    pub fn set_automation(&mut self, target: AutomationTarget, value: Option<Automation>) {
+        target.check(self.synthesizer_settings.channel_count);
+
+        match value {
+            Some(automation) => {
+                self.automations.insert(target, automation);
+            }
+            None => {
+                self.automations.remove(&target);
+            }
+        }
+    }
+
+    /// Gets the voice source which plays all the notes of the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The MIDI channel.
+    pub fn get_channel_voice_source(&self, channel: i32) -> Option<&Arc<dyn VoiceSource>> {
+        self.voice_sources.get(&channel)
+    }
+
+    /// Plays all the notes of the specified channel with the voice source instead of the SoundFont.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The MIDI channel, which must be less than the channel count of the settings.
+    /// * `value` - The voice source, or `None` to play the channel with the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// The source is set to the synthesizer of every track.
+    /// To render the whole MIDI file with the source, set it to all the channels.
+    /// See `Synthesizer::set_channel_voice_source` for the details.
+    pub fn set_channel_voice_source(&mut self, channel: i32, value: Option<Arc<dyn VoiceSource>>) {
+        if !(0 <= channel && channel < self.synthesizer_settings.channel_count as i32) {
+            panic!("The channel must be less than the channel count.");
+        }
+
+        match value {
+            Some(voice_source) => {
+                self.voice_sources.insert(channel, voice_source);
+            }
+            None => {
+                self.voice_sources.remove(&channel);
+            }
+        }
+    }
+
+    /// Gets the cache of the rendered tracks.
+    pub fn get_render_cache(&self) -> Option<&Arc<RenderCache>> {
+        self.render_cache.as_ref()
+    }
+
+    /// Sets the cache of the rendered tracks.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The cache, or `None` to render all the tracks every time.
+    ///
+    /// # Remarks
+    ///
+    /// With the cache, `render` reuses the tracks rendered before with the same events and settings,
+    /// and renders only the modified tracks.
+    /// The diagnostics of the reused tracks are not reported again.
+    /// The other rendering methods do not use the cache, and neither does `render` with the voice sources.
+    pub fn set_render_cache(&mut self, value: Option<Arc<RenderCache>>) {
+        self.render_cache = value;
+    }
+
+    /// Gets the levels measured by the last call of `render`.
+    ///
+    /// # Remarks
+    ///
+    /// This is `None` until the rendering completes, or if it was cancelled.
+    pub fn get_render_report(&self) -> Option<&RenderReport> {
+        self.report.as_ref()
+    }
+
+    /// Gets how the output is normalized after mixing.
+    pub fn get_normalization(&self) -> Normalization {
+        self.normalization
+    }
+
+    /// Sets how the output is normalized after mixing.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The normalization.
+    ///
+    /// # Remarks
+    ///
+    /// The default value is `Normalization::None`.
+    /// The sum of many tracks easily exceeds the full scale,
+    /// so `Normalization::Peak(-1.0)` is recommended to avoid clipping.
+    /// The gain is applied to the whole output, so the balance of the tracks is kept.
+    /// A silent output is not amplified.
+    pub fn set_normalization(&mut self, value: Normalization) {
+        value.check();
+        self.normalization = value;
+    }
+
+    // Returns the gain applied to the output.
+    fn normalize(&self, left: &mut [f32], right: &mut [f32]) -> f32 {
+        let peak = left
+            .iter()
+            .chain(right.iter())
+            .fold(0_f32, |max, x| max.max(x.abs())) as f64;
+        if peak == 0.0 {
+            return 1_f32;
+        }
+
+        let gain = match self.normalization {
+            Normalization::None => return 1_f32,
+            Normalization::Peak(level) => 10_f64.powf(level / 20.0) / peak,
+            Normalization::Loudness { target, max_peak } => {
+                let sample_rate = self.synthesizer_settings.sample_rate;
+                let max_gain = 10_f64.powf(max_peak / 20.0) / peak;
+                match Loudness::integrated(left, right, sample_rate) {
+                    Some(loudness) => 10_f64.powf((target - loudness) / 20.0).min(max_gain),
+                    None => return 1_f32,
+                }
+            }
+        } as f32;
+
+        left.iter_mut().for_each(|x| *x *= gain);
+        right.iter_mut().for_each(|x| *x *= gain);
+        gain
+    }
+
+    // The sound after the loop end is faded out and mixed into the beginning of the loop,
+    // so that it continues when the playback jumps back to the loop start.
+    fn fold_tail(data: &mut Vec<f32>, loop_start: usize, loop_end: usize, tail_length: usize) {
+        data.resize(cmp::max(data.len(), loop_end), 0_f32);
+        let tail_length = cmp::min(
+            tail_length,
+            cmp::min(loop_end - loop_start, data.len() - loop_end),
+        );
+        for t in 0..tail_length {
+            let gain = 1_f32 - t as f32 / tail_length as f32;
+            data[loop_start + t] += gain * data[loop_end + t];
+        }
+        data.truncate(loop_end);
+    }
+
+    fn fade_out(data: &mut Vec<f32>, fade_start: usize, fade_length: usize) {
+        data.resize(fade_start + fade_length, 0_f32);
+        for t in 0..fade_length {
+            let gain = 1_f32 - t as f32 / fade_length as f32;
+            data[fade_start + t] *= gain;
+        }
+    }
+
+    // The mix of the tracks can be still audible where each track is not,
+    // so the silence at the end of the master is trimmed again.
+    // The data starts at `offset` in the whole waveform, and the events part is kept as it is.
+    fn trim_tail(
+        left: &mut Vec<f32>,
+        right: &mut Vec<f32>,
+        offset: usize,
+        event_length: usize,
+        tail: RenderTail,
+    ) {
+        if let RenderTail::UntilSilence { threshold, .. } = tail {
+            let threshold = 10_f64.powf(threshold / 20.0) as f32;
+            let length = TrackRender::get_audible_length(left, right, threshold);
+            let length = cmp::max(length, event_length.saturating_sub(offset));
+            left.truncate(length);
+            right.truncate(length);
+        }
+    }
+
+    fn report(diagnostics: &Mutex<Diagnostics>, sequencer: &MidiFileSequencer) {
+        let synthesizer = sequencer.get_synthesizer();
+        diagnostics
+            .lock()
+            .unwrap()
+            .extend(synthesizer.get_diagnostics());
+    }
+
+    fn install<R: Send>(thread_pool: Option<&ThreadPool>, op: impl FnOnce() -> R + Send) -> R {
+        match thread_pool {
+            Some(thread_pool) => thread_pool.install(op),
+            None => op(),
+        }
+    }
+
+    fn mix(master: &Mutex<Vec<f32>>, source: &[f32], offset: usize) {
+        let mut handler = master.lock().unwrap();
+        let len = handler.len();
+        if len < offset + source.len() {
+            handler.resize(offset + source.len(), 0.0);
+        }
+        ArrayMath::sum(source, &mut handler[offset..]);
+    }
 }
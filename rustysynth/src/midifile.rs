@@ -1,22 +1,57 @@
 #![allow(dead_code)]
 
-use std::cmp;
-use std::io::Cursor;
-use std::io::Read;
-use std::io::Seek;
-
-use rayon::iter::IntoParallelIterator;
-use rayon::iter::IntoParallelRefIterator;
-use rayon::iter::IntoParallelRefMutIterator;
-use rayon::iter::ParallelIterator;
-
+use alloc::boxed::Box;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp;
+#[cfg(feature = "tokio")]
+use tokio::io::AsyncRead;
+
+#[cfg(feature = "zip")]
+use crate::archive::Archive;
+#[cfg(feature = "tokio")]
+use crate::async_loader::AsyncLoader;
 use crate::binary_reader::BinaryReader;
+use crate::channel::Channel;
+use crate::diagnostic::DiagnosticCode;
+use crate::diagnostic::DiagnosticLocation;
+use crate::diagnostic::DiagnosticSeverity;
+use crate::diagnostics::Diagnostics;
+use crate::float_math::FloatMath;
 use crate::four_cc::FourCC;
+use crate::humanize_options::HumanizeOptions;
+use crate::io;
+use crate::io::Cursor;
+use crate::io::Read;
+use crate::io::Seek;
+use crate::io::Write;
+use crate::lyrics::Lyrics;
+use crate::lyrics::TextEvent;
+use crate::midi_event::MidiEvent;
+use crate::midifile_analysis::MidiFileAnalysis;
+use crate::midifile_options::MidiFileOptions;
+use crate::midifile_transform::MidiFileTransform;
+use crate::midifile_warning::MidiFileWarning;
+use crate::midifile_writer::MidiFileWriter;
+use crate::parallel::*;
 use crate::read_counter::ReadCounter;
+use crate::synthesizer::Synthesizer;
+use crate::synthesizer_settings::SynthesizerSettings;
+use crate::tempo_map::TempoMap;
+use crate::trace::debug;
+use crate::trace::debug_span;
 use crate::MidiFileError;
 use crate::MidiFileLoopType;
 
-#[derive(Clone, Copy)]
+// The events, whether the track was terminated properly, and the number of the ignored SysEx messages.
+type TrackReadResult = (Vec<(Message, i32)>, bool, usize, Vec<TextEvent>);
+
+// An event with its tick and the index of the track from which it was merged.
+pub(crate) type MergedEvent = (Message, i32, Option<u16>);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
 #[non_exhaustive]
 pub(crate) struct Message {
     pub(crate) channel: u8,
@@ -27,6 +62,8 @@ pub(crate) struct Message {
 
 impl Message {
     pub(crate) const NORMAL: u8 = 0;
+    pub(crate) const PERCUSSION_CHANNEL: u8 = 250;
+    pub(crate) const TIME_SIGNATURE: u8 = 251;
     pub(crate) const TEMPO_CHANGE: u8 = 252;
     pub(crate) const LOOP_START: u8 = 253;
     pub(crate) const LOOP_END: u8 = 254;
@@ -47,10 +84,8 @@ impl Message {
 
         if command == 0xB0 {
             match loop_type {
-                MidiFileLoopType::RpgMaker => {
-                    if data1 == 111 {
-                        return Message::loop_start();
-                    }
+                MidiFileLoopType::RpgMaker if data1 == 111 => {
+                    return Message::loop_start();
                 }
 
                 MidiFileLoopType::IncredibleMachine => {
@@ -92,6 +127,25 @@ impl Message {
         }
     }
 
+    pub(crate) fn time_signature(numerator: u8, denominator: u8) -> Self {
+        Self {
+            channel: Message::TIME_SIGNATURE,
+            command: numerator,
+            data1: denominator,
+            data2: 0,
+        }
+    }
+
+    // The channel is switched between the percussion and melodic by the GS "use for rhythm part" SysEx.
+    pub(crate) fn percussion_channel(channel: u8, value: bool) -> Self {
+        Self {
+            channel: Message::PERCUSSION_CHANNEL,
+            command: channel,
+            data1: value as u8,
+            data2: 0,
+        }
+    }
+
     pub(crate) fn loop_start() -> Self {
         Self {
             channel: Message::LOOP_START,
@@ -121,6 +175,8 @@ impl Message {
 
     pub(crate) fn get_message_type(&self) -> u8 {
         match self.channel {
+            Message::PERCUSSION_CHANNEL => Message::PERCUSSION_CHANNEL,
+            Message::TIME_SIGNATURE => Message::TIME_SIGNATURE,
             Message::TEMPO_CHANGE => Message::TEMPO_CHANGE,
             Message::LOOP_START => Message::LOOP_START,
             Message::LOOP_END => Message::LOOP_END,
@@ -129,6 +185,24 @@ impl Message {
         }
     }
 
+    // The channels of the port are placed after those of the preceding ports.
+    pub(crate) fn with_port(mut self, port: u8) -> Self {
+        match self.get_message_type() {
+            Message::NORMAL => self.channel += 16 * port,
+            Message::PERCUSSION_CHANNEL => self.command += 16 * port,
+            _ => (),
+        }
+        self
+    }
+
+    pub(crate) fn get_port(&self) -> u8 {
+        match self.get_message_type() {
+            Message::NORMAL => self.channel / 16,
+            Message::PERCUSSION_CHANNEL => self.command / 16,
+            _ => 0,
+        }
+    }
+
     pub(crate) fn get_tempo(&self) -> f64 {
         60000000.0
             / (((self.command as i32) << 16) | ((self.data1 as i32) << 8) | (self.data2 as i32))
@@ -139,8 +213,15 @@ impl Message {
 /// Represents a standard MIDI file.
 #[non_exhaustive]
 pub struct MidiFile {
+    // Kept public for compatibility. Use `get_tracks` instead.
+    #[doc(hidden)]
     pub tracks: Vec<MidiTrack>,
+    pub(crate) format: i16,
     pub(crate) length: f64,
+    pub(crate) warnings: Vec<MidiFileWarning>,
+    pub(crate) diagnostics: Diagnostics,
+    pub(crate) tempo_map: TempoMap,
+    pub(crate) lyrics: Lyrics,
 }
 
 impl MidiFile {
@@ -149,10 +230,72 @@ impl MidiFile {
     /// # Arguments
     ///
     /// * `reader` - The data stream used to load the MIDI file.
+    ///
+    /// # Remarks
+    ///
+    /// With the zip feature, the stream can also be a zip archive,
+    /// from which the first MIDI file is extracted and loaded.
+    /// This applies to all the other loaders taking a stream.
+    /// The archives in the archive are not extracted, and the other formats such as 7z are not supported.
     pub fn new<R: Read>(reader: &mut R) -> Result<Self, MidiFileError> {
         MidiFile::new_with_loop_type(reader, MidiFileLoopType::LoopPoint(0))
     }
 
+    /// Loads a MIDI file from the byte slice.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The content of the MIDI file.
+    ///
+    /// # Remarks
+    ///
+    /// This is available without the std feature.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, MidiFileError> {
+        MidiFile::new(&mut Cursor::new(data))
+    }
+
+    /// Loads a MIDI file from the asynchronous stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The data stream used to load the MIDI file.
+    ///
+    /// # Remarks
+    ///
+    /// The stream is read into the memory without blocking,
+    /// and then the MIDI file is parsed on the blocking threads of the Tokio runtime.
+    /// A stream longer than the maximum chunk size of the limits fails with `MidiFileError::FileTooLarge`
+    /// before it is parsed.
+    #[cfg(feature = "tokio")]
+    pub async fn from_async_reader<R: AsyncRead + Unpin>(
+        reader: &mut R,
+    ) -> Result<Self, MidiFileError> {
+        MidiFile::from_async_reader_with_options(reader, &MidiFileOptions::new()).await
+    }
+
+    /// Loads a MIDI file from the asynchronous stream with the specified options.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The data stream used to load the MIDI file.
+    /// * `options` - The options for loading the MIDI file.
+    ///
+    /// # Remarks
+    ///
+    /// See `from_async_reader` for the details.
+    #[cfg(feature = "tokio")]
+    pub async fn from_async_reader_with_options<R: AsyncRead + Unpin>(
+        reader: &mut R,
+        options: &MidiFileOptions,
+    ) -> Result<Self, MidiFileError> {
+        let options = *options;
+        let max_size = options.limits.max_chunk_size;
+        AsyncLoader::load(reader, max_size, MidiFileError::FileTooLarge, move |data| {
+            MidiFile::new_with_options(&mut Cursor::new(data), &options)
+        })
+        .await
+    }
+
     /// Loads a MIDI file from the stream with a specified loop type.
     ///
     /// # Arguments
@@ -165,16 +308,67 @@ impl MidiFile {
     /// `MidiFileLoopType` has the following variants:
     /// * `LoopPoint(usize)` - Specifies the loop start point by a tick value.
     /// * `RpgMaker` - The RPG Maker style loop.
-    /// CC #111 will be the loop start point.
+    ///   CC #111 will be the loop start point.
     /// * `IncredibleMachine` - The Incredible Machine style loop.
-    /// CC #110 and #111 will be the start and end points of the loop.
+    ///   CC #110 and #111 will be the start and end points of the loop.
     /// * `FinalFantasy` - The Final Fantasy style loop.
-    /// CC #116 and #117 will be the start and end points of the loop.
+    ///   CC #116 and #117 will be the start and end points of the loop.
     pub fn new_with_loop_type<R: Read>(
         reader: &mut R,
         loop_type: MidiFileLoopType,
     ) -> Result<Self, MidiFileError> {
-        let chunk_type = BinaryReader::read_four_cc(reader)?;
+        let mut options = MidiFileOptions::new();
+        options.loop_type = loop_type;
+        MidiFile::new_with_options(reader, &options)
+    }
+
+    /// Loads a MIDI file from the stream with the specified options.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The data stream used to load the MIDI file.
+    /// * `options` - The options for loading the MIDI file.
+    ///
+    /// # Remarks
+    ///
+    /// If the lenient mode is enabled, the problems recovered from can be retrieved by `get_warnings`.
+    pub fn new_with_options<R: Read>(
+        reader: &mut R,
+        options: &MidiFileOptions,
+    ) -> Result<Self, MidiFileError> {
+        let _span = debug_span!("midi_parse").entered();
+
+        let mut head: [u8; 4] = [0; 4];
+        reader.read_exact(&mut head)?;
+
+        // The stream cannot be seeked, so the whole archive is read into memory.
+        #[cfg(feature = "zip")]
+        if head == Archive::SIGNATURE {
+            let archive = Archive::read_to_end(reader, head, options.limits.max_chunk_size)?;
+            let data = Archive::extract(
+                Cursor::new(archive),
+                Archive::MIDI_EXTENSIONS,
+                options.limits.max_chunk_size,
+            )?;
+            let mut reader = Cursor::new(data);
+            reader.read_exact(&mut head)?;
+            return MidiFile::read_smf(&mut reader, head, options);
+        }
+
+        MidiFile::read_smf(reader, head, options)
+    }
+
+    // Reads the standard MIDI file, which is not an archive, following the first four bytes.
+    fn read_smf<R: Read>(
+        reader: &mut R,
+        head: [u8; 4],
+        options: &MidiFileOptions,
+    ) -> Result<Self, MidiFileError> {
+        let loop_type = options.loop_type;
+        let lenient = options.lenient;
+        let mut warnings: Vec<MidiFileWarning> = Vec::new();
+
+        let chunk_type = FourCC::from_bytes(head);
         if chunk_type != b"MThd" {
             return Err(MidiFileError::InvalidChunkType {
                 expected: FourCC::from_bytes(*b"MThd"),
@@ -184,84 +378,220 @@ impl MidiFile {
         }
 
         let size = BinaryReader::read_i32_big_endian(reader)?;
-        if size != 6 {
+        if size < 6 {
             return Err(MidiFileError::InvalidChunkData(FourCC::from_bytes(
                 *b"MThd",
             )));
         }
 
         let format = BinaryReader::read_i16_big_endian(reader)?;
-        if format != 1 {
+        if !(0..=2).contains(&format) {
             return Err(MidiFileError::UnsupportedFormat(format));
         }
 
-        let track_count = BinaryReader::read_i16_big_endian(reader)? as i32;
+        let track_count = BinaryReader::read_u16_big_endian(reader)? as i32;
+        if track_count as usize > options.limits.max_track_count {
+            return Err(MidiFileError::TooManyTracks(track_count as usize));
+        }
+
         let resolution = BinaryReader::read_i16_big_endian(reader)? as i32;
+        if !MidiFile::is_valid_division(resolution) {
+            return Err(MidiFileError::InvalidChunkData(FourCC::from_bytes(
+                *b"MThd",
+            )));
+        }
 
-        let mut cursor = {
-            let mut rest_data = Vec::new();
-            reader.read_to_end(&mut rest_data)?;
-            Cursor::new(rest_data)
-        };
+        // Some files have a longer header, whose extra bytes are ignored.
+        BinaryReader::discard_data(reader, size as usize - 6)?;
 
-        let track_addrs = MidiFile::track_addr(&mut cursor, track_count)?;
-        cursor.set_position(0);
         let mut data = Vec::new();
-        cursor.read_to_end(&mut data)?;
-        drop(cursor);
+        reader.read_to_end(&mut data)?;
+
+        let track_addrs = if lenient {
+            MidiFile::track_addr_lenient(&data, track_count, &mut warnings)
+        } else {
+            let max_chunk_size = cmp::min(options.limits.max_chunk_size, data.len());
+            MidiFile::track_addr(&mut Cursor::new(&data), track_count, max_chunk_size)?
+        };
 
-        let mut tracks_result = track_addrs
+        let tracks_result = track_addrs
             .par_iter()
-            .map(|(start, len)| {
+            .enumerate()
+            .map(|(track, (start, len))| {
                 let mut reader = Cursor::new(&data[*start..*start + len]);
-                MidiFile::read_track(&mut reader, loop_type)
+                MidiFile::read_track_with_options(&mut reader, track, loop_type, lenient)
             })
-            .collect::<Vec<Result<Vec<(Message, i32)>, MidiFileError>>>();
+            .collect::<Vec<Result<TrackReadResult, MidiFileError>>>();
         drop(data);
 
-        let mut tracks = Vec::new();
-        while let Some(track) = tracks_result.pop() {
-            tracks.push(track?);
+        let mut tracks: Vec<Vec<MergedEvent>> = Vec::new();
+        let mut ignored_sysex_counts = Vec::new();
+        let mut texts = Vec::new();
+        for (i, track) in tracks_result.into_iter().enumerate() {
+            let (track, complete, ignored_sysex_count, track_texts) = track?;
+            if !complete {
+                warnings.push(MidiFileWarning::MissingEndOfTrack(i));
+            }
+            tracks.push(track.into_iter().map(|(x, y)| (x, y, None)).collect());
+            ignored_sysex_counts.push(ignored_sysex_count);
+            texts.push(track_texts);
         }
 
-        let tempo_track = tracks
+        // In format 2, each track is an independent pattern with its own tempo.
+        let tempo_track_index = tracks
             .iter()
-            .filter(|x| {
+            .position(|x| {
                 x.iter()
-                    .any(|(y, _)| y.get_message_type() == Message::TEMPO_CHANGE)
+                    .any(|(y, _, _)| y.get_message_type() == Message::TEMPO_CHANGE)
             })
-            .cloned()
-            .collect::<Vec<Vec<(Message, i32)>>>();
+            .filter(|_| format != 2);
+
+        // Some files have the tempo changes split across the tracks, so all of them are collected.
+        let tempo_changes = if format != 2 {
+            MidiFile::collect_tempo_changes(
+                tracks
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(i, x)| x.iter().map(move |(y, tick, _)| (i, *y, *tick))),
+            )
+        } else {
+            Vec::new()
+        };
+        let is_tempo_split = tempo_changes.iter().any(|x| x.2 != tempo_changes[0].2);
 
-        if let Some(track) = tempo_track.first() {
-            tracks.par_iter_mut().for_each(|x| {
-                x.extend(track);
-                x.sort_unstable_by(|a, b| a.1.cmp(&b.1));
+        // The time signatures are usually in the first track, which may not have the tempo.
+        let mut tempo_events: Vec<(Message, i32)> =
+            tempo_changes.iter().map(|(x, y, _)| (*x, *y)).collect();
+        if format != 2 {
+            if let Some(track) = tracks.iter().find(|x| {
+                x.iter()
+                    .any(|(y, _, _)| y.get_message_type() == Message::TIME_SIGNATURE)
+            }) {
+                tempo_events.extend(
+                    track
+                        .iter()
+                        .filter(|(x, _, _)| x.get_message_type() == Message::TIME_SIGNATURE)
+                        .map(|(x, y, _)| (*x, *y)),
+                );
+            }
+            tempo_events.sort_by_key(|x| x.1);
+        }
+        let tempo_map = TempoMap::new(resolution, &tempo_events);
+        let lyrics = Lyrics::new(&tempo_map, &texts);
+        drop(texts);
+
+        // The time signatures are only needed by the tempo map, so they are not merged into the tracks.
+        tracks.par_iter_mut().for_each(|x| {
+            x.retain(|(y, _, _)| y.get_message_type() != Message::TIME_SIGNATURE);
+        });
+
+        let tempo_track = tempo_track_index.map(|i| {
+            tracks[i]
+                .iter()
+                .map(|(x, y, _)| (*x, *y, Some(i as u16)))
+                .collect::<Vec<MergedEvent>>()
+        });
+
+        // Merging the tempo track multiplies its events by the number of tracks.
+        let mut event_count: usize = 0;
+        for (i, track) in tracks.iter().enumerate() {
+            event_count = event_count.saturating_add(track.len());
+            if let Some(tempo_track) = tempo_track
+                .as_ref()
+                .filter(|_| Some(i) != tempo_track_index)
+            {
+                event_count = event_count.saturating_add(tempo_track.len());
+            }
+            if is_tempo_split {
+                event_count = event_count.saturating_add(tempo_changes.len());
+            }
+        }
+        if event_count > options.limits.max_event_count {
+            return Err(MidiFileError::TooManyEvents);
+        }
+
+        // The tempo track itself is not merged, or its events would be played twice.
+        // The sort is stable, so the events at the same tick are played in the order of the file.
+        if let Some(track) = tempo_track.as_ref() {
+            tracks.par_iter_mut().enumerate().for_each(|(i, x)| {
+                if Some(i) != tempo_track_index {
+                    x.extend(track.iter().copied());
+                    x.sort_by_key(|a| a.1);
+                }
+            });
+        }
+
+        // Each track is timed only by the tempo changes in it,
+        // so the ones in the other tracks are added when they are not all in the tempo track.
+        if is_tempo_split {
+            tracks.par_iter_mut().enumerate().for_each(|(i, x)| {
+                MidiFile::merge_tempo_changes(i, x, &tempo_changes);
             });
         }
 
         match loop_type {
             MidiFileLoopType::LoopPoint(loop_point) if loop_point != 0 => {
                 let loop_point = loop_point as i32;
-                let track = &mut tracks[0];
+                let track = match tracks.first_mut() {
+                    Some(value) => value,
+                    None => {
+                        return Err(MidiFileError::InvalidChunkData(FourCC::from_bytes(
+                            *b"MTrk",
+                        )))
+                    }
+                };
 
-                if loop_point <= track.last().unwrap().1 {
+                if loop_point <= track.last().map_or(0, |x| x.1) {
                     for i in 0..track.len() {
                         if track[i].1 >= loop_point {
-                            track.insert(i, (Message::loop_start(), loop_point));
+                            track.insert(i, (Message::loop_start(), loop_point, None));
                             break;
                         }
                     }
                 } else {
-                    track.push((Message::loop_start(), loop_point));
+                    track.push((Message::loop_start(), loop_point, None));
                 }
             }
             _ => (),
         }
 
-        let (tracks, length) = MidiFile::merge_tracks(tracks, resolution);
+        let (tracks, length) = MidiFile::cast_tracks(tracks, resolution);
 
-        Ok(Self { tracks, length })
+        let mut diagnostics = Diagnostics::new();
+        for warning in warnings.iter() {
+            warning.report(&mut diagnostics);
+        }
+        for (i, count) in ignored_sysex_counts.into_iter().enumerate() {
+            if count > 0 {
+                diagnostics.push(
+                    DiagnosticCode::MidiSysExIgnored,
+                    DiagnosticSeverity::Info,
+                    DiagnosticLocation::Track(i),
+                    format!(
+                        "the track {} has {} unsupported SysEx messages which were ignored",
+                        i, count
+                    ),
+                );
+            }
+        }
+
+        debug!(
+            format,
+            tracks = tracks.len(),
+            events = event_count,
+            length,
+            "parsed the MIDI file"
+        );
+
+        Ok(Self {
+            tracks,
+            format,
+            length,
+            warnings,
+            diagnostics,
+            tempo_map,
+            lyrics,
+        })
     }
 
     fn discard_data<R: Read + Seek>(reader: &mut R) -> Result<(), MidiFileError> {
@@ -283,9 +613,102 @@ impl MidiFile {
         Ok((b1 << 16) | (b2 << 8) | b3)
     }
 
+    // Only the GS "use for rhythm part" message is recognized, and the other ones are ignored.
+    // The data is F0 41 dev 42 12 40 1x 15 vv sum F7, where x is the part and vv is the drum map.
+    fn read_system_exclusive<R: Read + Seek>(
+        reader: &mut R,
+    ) -> Result<Option<Message>, MidiFileError> {
+        let size = BinaryReader::read_i32_variable_length(reader)? as usize;
+        if size != 10 {
+            BinaryReader::discard_data(reader, size)?;
+            return Ok(None);
+        }
+
+        let mut data: [u8; 10] = [0; 10];
+        reader.read_exact(&mut data)?;
+        let is_rhythm_part = data[0] == 0x41
+            && data[2] == 0x42
+            && data[3] == 0x12
+            && data[4] == 0x40
+            && data[5] & 0xF0 == 0x10
+            && data[6] == 0x15;
+        if !is_rhythm_part {
+            return Ok(None);
+        }
+
+        // The part 10 comes first in the GS block numbers.
+        let channel = match data[5] & 0x0F {
+            0 => 9,
+            part @ 1..=9 => part - 1,
+            part => part,
+        };
+        Ok(Some(Message::percussion_channel(channel, data[7] != 0)))
+    }
+
+    // The data is read as far as it exists, so that a broken size does not allocate a huge buffer.
+    fn read_text<R: Read + Seek>(reader: &mut R) -> Result<Vec<u8>, MidiFileError> {
+        let size = BinaryReader::read_i32_variable_length(reader)? as usize;
+        let mut data = Vec::new();
+        reader.take(size as u64).read_to_end(&mut data)?;
+        if data.len() < size {
+            return Err(MidiFileError::IoError(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to read the whole text",
+            )));
+        }
+        Ok(data)
+    }
+
+    // The realtime messages may be interleaved even between the data bytes of a message.
+    fn read_data_byte<R: Read>(reader: &mut R) -> Result<u8, MidiFileError> {
+        loop {
+            let value = BinaryReader::read_u8(reader)?;
+            if !(0xF8..=0xFE).contains(&value) {
+                return Ok(value);
+            }
+        }
+    }
+
+    // The port applies to the following events in the track.
+    // The ports beyond the maximum channel count are sent to the first ports,
+    // since the channels from 250 are used for the other events.
+    fn read_port<R: Read + Seek>(reader: &mut R) -> Result<Option<u8>, MidiFileError> {
+        let size = BinaryReader::read_i32_variable_length(reader)? as usize;
+        if size < 1 {
+            return Ok(None);
+        }
+
+        let port = BinaryReader::read_u8(reader)?;
+        BinaryReader::discard_data(reader, size - 1)?;
+
+        let port_count = (Synthesizer::MAX_CHANNEL_COUNT / Synthesizer::CHANNEL_COUNT) as u8;
+        Ok(Some(port % port_count))
+    }
+
+    // A malformed time signature is ignored as the other meta events.
+    fn read_time_signature<R: Read + Seek>(
+        reader: &mut R,
+    ) -> Result<Option<Message>, MidiFileError> {
+        let size = BinaryReader::read_i32_variable_length(reader)? as usize;
+        if size < 2 {
+            BinaryReader::discard_data(reader, size)?;
+            return Ok(None);
+        }
+
+        let numerator = BinaryReader::read_u8(reader)?;
+        let denominator = BinaryReader::read_u8(reader)?;
+        BinaryReader::discard_data(reader, size - 2)?;
+        if numerator == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(Message::time_signature(numerator, denominator)))
+    }
+
     pub(crate) fn track_addr<R: Read + Seek>(
         reader: &mut R,
         track_count: i32,
+        max_chunk_size: usize,
     ) -> Result<Vec<(usize, usize)>, MidiFileError> {
         let mut result = Vec::new();
 
@@ -299,7 +722,10 @@ impl MidiFile {
                     at: index as u64,
                 });
             }
-            let mut size = BinaryReader::read_i32_big_endian(reader)? as usize;
+            let mut size = BinaryReader::read_i32_big_endian(reader)? as u32 as usize;
+            if size > max_chunk_size {
+                return Err(MidiFileError::ChunkTooLarge(chunk_type));
+            }
             BinaryReader::discard_data(reader, size)?;
 
             size += 8;
@@ -310,10 +736,117 @@ impl MidiFile {
         Ok(result)
     }
 
+    // Unlike the strict mode, the lenient mode skips the chunks other than tracks,
+    // and the last track is cut at the end of the data.
+    fn track_addr_lenient(
+        data: &[u8],
+        track_count: i32,
+        warnings: &mut Vec<MidiFileWarning>,
+    ) -> Vec<(usize, usize)> {
+        let expected = cmp::max(track_count, 0) as usize;
+        let mut result = Vec::new();
+
+        let mut index: usize = 0;
+        while result.len() < expected && index + 8 <= data.len() {
+            let chunk_type = FourCC::from_bytes([
+                data[index],
+                data[index + 1],
+                data[index + 2],
+                data[index + 3],
+            ]);
+            let size = u32::from_be_bytes([
+                data[index + 4],
+                data[index + 5],
+                data[index + 6],
+                data[index + 7],
+            ]) as usize;
+            let end = index.saturating_add(8).saturating_add(size);
+
+            if chunk_type != b"MTrk" {
+                warnings.push(MidiFileWarning::UnknownChunk(chunk_type));
+                index = end;
+                continue;
+            }
+
+            if end > data.len() {
+                warnings.push(MidiFileWarning::TruncatedTrack(result.len()));
+                result.push((index, data.len() - index));
+                break;
+            }
+
+            result.push((index, end - index));
+            index = end;
+        }
+
+        if result.len() < expected {
+            warnings.push(MidiFileWarning::MissingTracks {
+                expected,
+                actual: result.len(),
+            });
+        }
+
+        result
+    }
+
     pub(crate) fn read_track<R: Read + Seek>(
         reader: &mut R,
+        track: usize,
         loop_type: MidiFileLoopType,
     ) -> Result<Vec<(Message, i32)>, MidiFileError> {
+        let (events, _, _, _) = MidiFile::read_track_with_options(reader, track, loop_type, false)?;
+        Ok(events)
+    }
+
+    // In the lenient mode, a track which ends without the EOT event is closed at the last event.
+    // The other errors are wrapped with the position where they occurred.
+    pub(crate) fn read_track_with_options<R: Read + Seek>(
+        reader: &mut R,
+        track: usize,
+        loop_type: MidiFileLoopType,
+        lenient: bool,
+    ) -> Result<TrackReadResult, MidiFileError> {
+        let mut events = Vec::new();
+        let mut ignored_sysex_count: usize = 0;
+        let mut texts = Vec::new();
+        let mut position: (u64, i32) = (0, 0);
+
+        match MidiFile::read_events(
+            reader,
+            loop_type,
+            lenient,
+            &mut events,
+            &mut ignored_sysex_count,
+            &mut texts,
+            &mut position,
+        ) {
+            Ok(()) => Ok((events, true, ignored_sysex_count, texts)),
+            Err(MidiFileError::IoError(err))
+                if lenient && err.kind() == io::ErrorKind::UnexpectedEof =>
+            {
+                let tick = events.last().map_or(0, |x| x.1);
+                events.push((Message::end_of_track(), tick));
+                Ok((events, false, ignored_sysex_count, texts))
+            }
+            Err(err) => Err(MidiFileError::TrackError {
+                track,
+                offset: position.0,
+                tick: position.1,
+                error: Box::new(err),
+            }),
+        }
+    }
+
+    // The position is the offset of the event from the start of the chunk and its tick,
+    // which are kept up to date for the error messages.
+    fn read_events<R: Read + Seek>(
+        reader: &mut R,
+        loop_type: MidiFileLoopType,
+        lenient: bool,
+        events: &mut Vec<(Message, i32)>,
+        ignored_sysex_count: &mut usize,
+        texts: &mut Vec<TextEvent>,
+        position: &mut (u64, i32),
+    ) -> Result<(), MidiFileError> {
         let chunk_type = BinaryReader::read_four_cc(reader)?;
         if chunk_type != b"MTrk" {
             return Err(MidiFileError::InvalidChunkType {
@@ -326,32 +859,61 @@ impl MidiFile {
         let size = BinaryReader::read_i32_big_endian(reader)? as usize;
         let reader = &mut ReadCounter::new(reader);
 
-        let mut events = Vec::new();
-
         let mut tick: i32 = 0;
         let mut last_status: u8 = 0;
+        let mut port: u8 = 0;
 
         loop {
+            position.0 = 8 + reader.bytes_read() as u64;
+
+            // An empty track is treated as if it had only the EOT event,
+            // and the other tracks must not run into the next chunk.
+            if reader.bytes_read() >= size {
+                if size == 0 {
+                    events.push((Message::end_of_track(), tick));
+                    return Ok(());
+                }
+                return Err(MidiFileError::IoError(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "the track ended without the EOT event",
+                )));
+            }
+
             let delta = BinaryReader::read_i32_variable_length(reader)?;
             let first = BinaryReader::read_u8(reader)?;
 
-            tick += delta;
+            tick = match tick.checked_add(delta) {
+                Some(value) => value,
+                None => {
+                    return Err(MidiFileError::InvalidChunkData(FourCC::from_bytes(
+                        *b"MTrk",
+                    )))
+                }
+            };
+            position.1 = tick;
 
             if (first & 128) == 0 {
                 let command = last_status & 0xF0;
                 if command == 0xC0 || command == 0xD0 {
-                    events.push((Message::common1(last_status, first), tick));
+                    events.push((Message::common1(last_status, first).with_port(port), tick));
                 } else {
-                    let data2 = BinaryReader::read_u8(reader)?;
-                    events.push((Message::common2(last_status, first, data2, loop_type), tick));
+                    let data2 = MidiFile::read_data_byte(reader)?;
+                    let message = Message::common2(last_status, first, data2, loop_type);
+                    events.push((message.with_port(port), tick));
                 }
 
                 continue;
             }
 
             match first {
-                0xF0 => MidiFile::discard_data(reader)?,
-                0xF7 => MidiFile::discard_data(reader)?,
+                0xF0 => match MidiFile::read_system_exclusive(reader)? {
+                    Some(message) => events.push((message.with_port(port), tick)),
+                    None => *ignored_sysex_count += 1,
+                },
+                0xF7 => {
+                    MidiFile::discard_data(reader)?;
+                    *ignored_sysex_count += 1;
+                }
                 0xFF => match BinaryReader::read_u8(reader)? {
                     0x2F => {
                         BinaryReader::read_u8(reader)?;
@@ -359,40 +921,102 @@ impl MidiFile {
 
                         // Some MIDI files may have events inserted after the EOT.
                         // Such events should be ignored.
-                        if reader.bytes_read() < size {
+                        if reader.bytes_read() < size && !lenient {
                             BinaryReader::discard_data(reader, size - reader.bytes_read())?;
                         }
 
-                        return Ok(events);
+                        return Ok(());
                     }
                     0x51 => {
                         events.push((Message::tempo_change(MidiFile::read_tempo(reader)?), tick));
                     }
+                    0x21 => {
+                        if let Some(value) = MidiFile::read_port(reader)? {
+                            port = value;
+                        }
+                    }
+                    0x58 => {
+                        if let Some(message) = MidiFile::read_time_signature(reader)? {
+                            events.push((message, tick));
+                        }
+                    }
+                    kind @ (TextEvent::TEXT | TextEvent::LYRIC) => {
+                        let data = MidiFile::read_text(reader)?;
+                        texts.push(TextEvent { tick, kind, data });
+                    }
                     _ => MidiFile::discard_data(reader)?,
                 },
+                // The system common and realtime messages are not allowed in MIDI files,
+                // but some trackers export them, so they are skipped with their data.
+                0xF1 | 0xF3 => {
+                    MidiFile::read_data_byte(reader)?;
+                }
+                0xF2 => {
+                    MidiFile::read_data_byte(reader)?;
+                    MidiFile::read_data_byte(reader)?;
+                }
+                0xF4..=0xFE => (),
                 _ => {
                     let command = first & 0xF0;
                     if command == 0xC0 || command == 0xD0 {
-                        let data1 = BinaryReader::read_u8(reader)?;
-                        events.push((Message::common1(first, data1), tick));
+                        let data1 = MidiFile::read_data_byte(reader)?;
+                        events.push((Message::common1(first, data1).with_port(port), tick));
                     } else {
-                        let data1 = BinaryReader::read_u8(reader)?;
-                        let data2 = BinaryReader::read_u8(reader)?;
-                        events.push((Message::common2(first, data1, data2, loop_type), tick));
+                        let data1 = MidiFile::read_data_byte(reader)?;
+                        let data2 = MidiFile::read_data_byte(reader)?;
+                        let message = Message::common2(first, data1, data2, loop_type);
+                        events.push((message.with_port(port), tick));
                     }
                 }
             }
 
-            last_status = first
+            // Some MIDI files rely on the running status surviving SysEx and meta events,
+            // so only the channel messages change it.
+            if first < 0xF0 {
+                last_status = first
+            }
         }
     }
 
-    pub(crate) fn cast_delta(track: Vec<(Message, i32)>, resolution: i32) -> (MidiTrack, f64) {
+    // Collects the tempo changes from the events of the tracks, each of which has the index of its track.
+    // The sort is stable, so the tempo changes at the same tick are applied in the order of the tracks.
+    pub(crate) fn collect_tempo_changes<I>(events: I) -> Vec<MergedEvent>
+    where
+        I: IntoIterator<Item = (usize, Message, i32)>,
+    {
+        let mut tempo_changes: Vec<MergedEvent> = events
+            .into_iter()
+            .filter(|(_, x, _)| x.get_message_type() == Message::TEMPO_CHANGE)
+            .map(|(i, x, tick)| (x, tick, Some(i as u16)))
+            .collect();
+        tempo_changes.sort_by_key(|x| x.1);
+        tempo_changes
+    }
+
+    // Replaces the tempo changes in the sorted track with the collected ones,
+    // where the ones from the other tracks are recorded as merged.
+    pub(crate) fn merge_tempo_changes(
+        index: usize,
+        track: &mut Vec<MergedEvent>,
+        tempo_changes: &[MergedEvent],
+    ) {
+        track.retain(|(x, _, _)| x.get_message_type() != Message::TEMPO_CHANGE);
+        track.extend(
+            tempo_changes
+                .iter()
+                .map(|(x, tick, origin)| (*x, *tick, origin.filter(|y| *y as usize != index))),
+        );
+        // The sort is stable, so the tempo changes come after the other events at the same tick.
+        track.sort_by_key(|x| x.1);
+    }
+
+    pub(crate) fn cast_delta(track: Vec<MergedEvent>, resolution: i32) -> (MidiTrack, f64) {
         if track.is_empty() {
             return (
                 MidiTrack {
                     messages: Vec::new(),
                     times: Vec::new(),
+                    origins: Vec::new(),
                 },
                 0.0,
             );
@@ -400,6 +1024,7 @@ impl MidiFile {
 
         let mut messages = Vec::new();
         let mut times = Vec::new();
+        let mut origins = Vec::new();
 
         let mut index = 0;
 
@@ -408,6 +1033,9 @@ impl MidiFile {
 
         let mut tempo: f64 = 120.0;
 
+        // SMPTE-based time divisions are independent of the tempo.
+        let smpte_tick_length = MidiFile::get_smpte_tick_length(resolution);
+
         loop {
             if index >= track.len() {
                 break;
@@ -415,7 +1043,10 @@ impl MidiFile {
 
             let next_tick = track[index].1;
             let delta_tick = next_tick - current_tick;
-            let delta_time = 60.0 / (resolution as f64 * tempo) * delta_tick as f64;
+            let delta_time = match smpte_tick_length {
+                Some(tick_length) => tick_length * delta_tick as f64,
+                None => 60.0 / (resolution as f64 * tempo) * delta_tick as f64,
+            };
 
             current_tick += delta_tick;
             current_time += delta_time;
@@ -423,18 +1054,55 @@ impl MidiFile {
             let message = track[index].0;
             if message.get_message_type() == Message::TEMPO_CHANGE {
                 tempo = message.get_tempo();
+            } else if message.get_message_type() == Message::TIME_SIGNATURE {
+                // The time signatures are available only from the tempo map.
             } else {
                 messages.push(message);
                 times.push(current_time);
+                origins.push(track[index].2);
             }
 
             index += 1;
         }
 
-        (MidiTrack { messages, times }, current_time)
+        if origins.iter().all(|x| x.is_none()) {
+            origins = Vec::new();
+        }
+
+        (
+            MidiTrack {
+                messages,
+                times,
+                origins,
+            },
+            current_time,
+        )
+    }
+
+    // If the high bit of the division is set, the upper byte is the negative frame rate
+    // and the lower byte is the number of ticks per frame.
+    pub(crate) fn get_smpte_tick_length(division: i32) -> Option<f64> {
+        if division >= 0 {
+            return None;
+        }
+
+        let frames_per_second = match -(division >> 8) {
+            29 => 30000.0 / 1001.0,
+            value => value as f64,
+        };
+        // The division without the ticks is rejected when reading,
+        // but it is also guarded here so that the tick length is always finite.
+        let ticks_per_frame = cmp::max(division & 0xFF, 1) as f64;
+
+        Some(1.0 / (frames_per_second * ticks_per_frame))
+    }
+
+    // Neither the resolution nor the number of ticks per frame can be zero.
+    pub(crate) fn is_valid_division(division: i32) -> bool {
+        division > 0 || (division < 0 && division & 0xFF != 0)
     }
 
-    fn merge_tracks(tracks: Vec<Vec<(Message, i32)>>, resolution: i32) -> (Vec<MidiTrack>, f64) {
+    fn cast_tracks(tracks: Vec<Vec<MergedEvent>>, resolution: i32) -> (Vec<MidiTrack>, f64) {
         let tracks = tracks
             .into_par_iter()
             .map(|track| MidiFile::cast_delta(track, resolution))
@@ -461,16 +1129,645 @@ impl MidiFile {
     pub fn get_length(&self) -> f64 {
         self.length
     }
+
+    /// Gets the tracks of the MIDI file.
+    ///
+    /// # Remarks
+    ///
+    /// The tracks are in the order of the file.
+    /// For format 0 and 1 files, each track is merged with the tempo track,
+    /// so it can be played by the sequencer alone.
+    /// If the tempo changes are split across the tracks, all of them are applied to every track.
+    /// For format 2 files, each track is a pattern.
+    pub fn get_tracks(&self) -> &[MidiTrack] {
+        &self.tracks[..]
+    }
+
+    /// Gets the tempo changes and the time signatures of the MIDI file.
+    ///
+    /// # Remarks
+    ///
+    /// The tempo changes in all the tracks are included.
+    /// For format 2 files, where each pattern has its own tempo, the tempo map is empty.
+    pub fn get_tempo_map(&self) -> &TempoMap {
+        &self.tempo_map
+    }
+
+    /// Gets the lyrics of the MIDI file, which are empty if the file has no lyrics.
+    ///
+    /// # Remarks
+    ///
+    /// The times of the syllables are the same as the playback position of the sequencer,
+    /// so `Lyrics::get_syllable_index` with `MidiFileSequencer::get_position` gives the syllable to be highlighted.
+    pub fn get_lyrics(&self) -> &Lyrics {
+        &self.lyrics
+    }
+
+    /// Gets the problems recovered from when loading the MIDI file in the lenient mode.
+    pub fn get_warnings(&self) -> &[MidiFileWarning] {
+        &self.warnings
+    }
+
+    /// Gets the diagnostics reported when loading the MIDI file.
+    pub fn get_diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    /// Gets the format of the MIDI file.
+    ///
+    /// # Remarks
+    ///
+    /// The value is 0 (single track), 1 (simultaneous tracks) or 2 (independent patterns).
+    pub fn get_format(&self) -> i16 {
+        self.format
+    }
+
+    /// Gets the number of the patterns in the MIDI file.
+    ///
+    /// # Remarks
+    ///
+    /// Only format 2 files contain patterns, where each track is an independent song.
+    /// For the other formats, this value will be 0.
+    pub fn get_pattern_count(&self) -> usize {
+        if self.format == 2 {
+            self.tracks.len()
+        } else {
+            0
+        }
+    }
+
+    /// Gets the number of the MIDI ports used by the MIDI file.
+    ///
+    /// # Remarks
+    ///
+    /// The ports are selected by the port meta events (FF 21), and this is 1 if the file has none.
+    /// To play the channels of all the ports separately,
+    /// the channel count of the synthesizer must be 16 times this value.
+    /// Up to 8 ports are distinguished, and the higher ports are shared with the first ones.
+    pub fn get_port_count(&self) -> usize {
+        self.tracks
+            .iter()
+            .map(|x| x.get_port_count())
+            .max()
+            .unwrap_or(1)
+    }
+
+    /// Gets the pairs of the bank and patch numbers selected for the notes in the MIDI file.
+    ///
+    /// # Remarks
+    ///
+    /// The bank selection and the program change are tracked in the same way as the synthesizer,
+    /// so the bank numbers for the percussion channel start from 128.
+    /// The channels of each MIDI port are tracked separately.
+    /// The channel 10 and the channels switched by the GS SysEx messages are treated as the percussion channels.
+    /// The pairs are sorted and contain no duplicates.
+    /// This can be used with `SoundFont::write_subset` to extract the presets needed to play the MIDI file.
+    pub fn get_used_presets(&self) -> Vec<(i32, i32)> {
+        let mut presets: BTreeSet<(i32, i32)> = BTreeSet::new();
+
+        // Only the percussion channels and the channel count are used, which do not depend on the sample rate.
+        let mut settings = SynthesizerSettings::new(44100);
+        settings.channel_count = Synthesizer::CHANNEL_COUNT * self.get_port_count();
+
+        if self.format == 2 {
+            // Each pattern starts from the initial state.
+            for track in self.tracks.iter() {
+                MidiFile::find_presets(&[track], &settings, &mut presets);
+            }
+        } else {
+            let tracks: Vec<&MidiTrack> = self.tracks.iter().collect();
+            MidiFile::find_presets(&tracks, &settings, &mut presets);
+        }
+
+        presets.into_iter().collect()
+    }
+
+    // The channel states are shared by all the tracks, so the events are processed in time order.
+    // The channels beyond the channel count of the settings are sent to the first ports as the synthesizer does.
+    pub(crate) fn find_presets(
+        tracks: &[&MidiTrack],
+        settings: &SynthesizerSettings,
+        presets: &mut BTreeSet<(i32, i32)>,
+    ) {
+        let mut events: Vec<(f64, Message)> = Vec::new();
+        for track in tracks.iter() {
+            for (message, time) in track.messages.iter().zip(track.times.iter()) {
+                let message_type = message.get_message_type();
+                if message_type == Message::NORMAL || message_type == Message::PERCUSSION_CHANNEL {
+                    events.push((*time, *message));
+                }
+            }
+        }
+        events.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+        // The bank and patch are followed in the same way as the synthesizer.
+        let channel_count = settings.channel_count;
+        let mut channels: Vec<Channel> = (0..channel_count)
+            .map(|i| {
+                let percussion = settings.percussion_channels[i % Synthesizer::CHANNEL_COUNT];
+                Channel::new(percussion, settings.profile)
+            })
+            .collect();
+
+        for (_, message) in events.iter() {
+            if message.get_message_type() == Message::PERCUSSION_CHANNEL {
+                if settings.enable_gs_rhythm_parts {
+                    let channel = &mut channels[message.command as usize % channel_count];
+                    channel.set_percussion_channel(message.data1 != 0);
+                }
+                continue;
+            }
+
+            let channel = &mut channels[message.channel as usize % channel_count];
+            match message.command {
+                0x90 if message.data2 > 0 => {
+                    presets.insert((channel.get_bank_number(), channel.get_patch_number()));
+                }
+                0xB0 if message.data1 == 0x00 => channel.set_bank(message.data2 as i32),
+                0xC0 => channel.set_patch(message.data1 as i32),
+                _ => (),
+            }
+        }
+    }
+
+    /// Analyzes the events in the MIDI file.
+    ///
+    /// # Remarks
+    ///
+    /// The analysis gives the statistics of each channel and finds the notes which overlap or are never turned off,
+    /// so that the broken files can be found before the rendering.
+    /// For format 2 files, each pattern is analyzed from the initial state and the statistics are summed.
+    pub fn analyze(&self) -> MidiFileAnalysis {
+        MidiFileAnalysis::new(self)
+    }
+
+    /// Returns a copy of the MIDI file with the start of the notes moved to the grid.
+    ///
+    /// # Arguments
+    ///
+    /// * `grid` - The interval of the grid in ticks.
+    /// * `strength` - The amount of the movement toward the grid, between 0 and 1.
+    ///
+    /// # Remarks
+    ///
+    /// The note-off events are moved together with their note-on events, so the lengths of the notes are kept.
+    /// The positions in ticks are given by the tempo map,
+    /// so format 2 files, whose tempo map is empty, are quantized at the default tempo of 120 BPM.
+    pub fn quantize(&self, grid: i32, strength: f64) -> MidiFile {
+        if grid <= 0 {
+            panic!("The grid must be greater than zero.");
+        }
+
+        if !(0.0..=1.0).contains(&strength) {
+            panic!("The strength must be between 0 and 1.");
+        }
+
+        MidiFileTransform::quantize(self, grid, strength)
+    }
+
+    /// Returns a copy of the MIDI file with the timing and the velocity of the notes randomized.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The amount of the changes and the seed of the random numbers.
+    ///
+    /// # Remarks
+    ///
+    /// The note-off events are moved together with their note-on events, so the lengths of the notes are kept.
+    /// The result is the same for the same seed.
+    /// As with `quantize`, the timing of format 2 files is changed at the default tempo of 120 BPM.
+    pub fn humanize(&self, options: &HumanizeOptions) -> MidiFile {
+        options.check();
+
+        MidiFileTransform::humanize(self, options)
+    }
+
+    /// Returns a copy of the MIDI file with the notes transposed.
+    ///
+    /// # Arguments
+    ///
+    /// * `semitones` - The amount of the transposition in semitones.
+    /// * `skip_drums` - If `true`, the notes in the percussion channels are not transposed.
+    ///
+    /// # Remarks
+    ///
+    /// The polyphonic key pressure is transposed together with the notes.
+    /// The notes moved out of the range between 0 and 127 are removed.
+    /// The percussion channels are the channel 10 and the channels switched by the GS SysEx messages,
+    /// in the same way as `get_used_presets`.
+    pub fn transposed(&self, semitones: i32, skip_drums: bool) -> MidiFile {
+        MidiFileTransform::transpose(self, semitones, skip_drums)
+    }
+
+    /// Returns a copy of the MIDI file which contains only the events of the specified channels.
+    ///
+    /// # Arguments
+    ///
+    /// * `mask` - The bit mask of the channels to be kept, where the bit 0 is the first channel.
+    ///
+    /// # Remarks
+    ///
+    /// The tempo changes, the loop points and the other non-channel events are kept.
+    /// The mask is applied to the channels of every MIDI port.
+    pub fn with_channels(&self, mask: u16) -> MidiFile {
+        MidiFileTransform::filter_channels(self, mask)
+    }
+
+    /// Returns a MIDI file which contains only the specified track.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the track.
+    ///
+    /// # Remarks
+    ///
+    /// For format 0 and 1 files, the track already contains the events of the tempo track,
+    /// so the result can be played or rendered alone.
+    /// The tempo map and the lyrics are kept.
+    pub fn extract_track(&self, index: usize) -> MidiFile {
+        self.check_track(index);
+
+        MidiFileTransform::merge(self, &[index])
+    }
+
+    /// Returns a MIDI file where the specified tracks are merged into one.
+    ///
+    /// # Arguments
+    ///
+    /// * `indices` - The indices of the tracks to be merged.
+    ///
+    /// # Remarks
+    ///
+    /// The events of the tempo track, which are contained in every track of format 0 and 1 files,
+    /// are kept only once.
+    /// The tempo map and the lyrics are kept.
+    /// The result can be written with `write`, and then rendered by `ThreadedRender` as a stem.
+    pub fn merge_tracks(&self, indices: &[usize]) -> MidiFile {
+        for index in indices.iter() {
+            self.check_track(*index);
+        }
+
+        MidiFileTransform::merge(self, indices)
+    }
+
+    fn check_track(&self, index: usize) {
+        if index >= self.tracks.len() {
+            panic!("The track index must be less than the number of the tracks.");
+        }
+    }
+
+    /// Writes the event timeline to the stream as a standard MIDI file.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The data stream used to write the MIDI file.
+    /// * `loop_count` - The number of times the loop section is played.
+    ///
+    /// # Remarks
+    ///
+    /// The output is exactly what the sequencer plays after the tracks are merged with the tempo track.
+    /// The loop section is unrolled `loop_count` times, and the loop markers are removed.
+    /// All the notes are stopped at each loop end point, in the same way as the sequencer.
+    /// The output is a format 1 file with a constant tempo of 120 BPM and a resolution of 960,
+    /// so the timing is quantized to about 0.5 ms.
+    pub fn write<W: Write>(&self, writer: &mut W, loop_count: usize) -> Result<(), MidiFileError> {
+        MidiFileWriter::write(self, writer, loop_count)
+    }
 }
 
+/// Represents a track of the MIDI file, where the time of each event is in seconds.
+#[derive(Clone)]
 #[non_exhaustive]
 pub struct MidiTrack {
     pub(crate) messages: Vec<Message>,
     pub(crate) times: Vec<f64>,
+    // The index of the track from which each event was merged, or `None` for the events of the track itself.
+    // This is empty if no event was merged.
+    pub(crate) origins: Vec<Option<u16>>,
 }
 
 impl MidiTrack {
+    /// Gets the length of the track in seconds.
     pub fn get_length(&self) -> f64 {
-        *self.times.last().unwrap()
+        self.times.last().copied().unwrap_or(0.0)
+    }
+
+    /// Gets the number of the events in the track.
+    pub fn get_event_count(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Gets the events in the track with their times in seconds.
+    pub fn get_events(&self) -> impl Iterator<Item = (f64, MidiEvent)> + '_ {
+        self.times
+            .iter()
+            .zip(self.messages.iter())
+            .map(|(time, message)| (*time, MidiEvent::from_message(message)))
+    }
+
+    // Gets the index of the track from which the event was merged, or `None` if it is of the track itself.
+    pub(crate) fn get_origin(&self, index: usize) -> Option<usize> {
+        self.origins
+            .get(index)
+            .copied()
+            .flatten()
+            .map(|x| x as usize)
+    }
+
+    // Expands the loop between the two points, in the same way as the sequencer plays it.
+    // All the notes are stopped at each loop end point, and the loop markers are removed.
+    // If `keep_rest` is `false`, the events after the last loop end point are discarded.
+    pub(crate) fn unroll_loop(
+        &self,
+        loop_start: f64,
+        loop_end: f64,
+        count: usize,
+        keep_rest: bool,
+    ) -> Self {
+        let loop_length = loop_end - loop_start;
+        let port_count = self.get_port_count();
+
+        let mut messages = Vec::new();
+        let mut times = Vec::new();
+
+        let events = self
+            .messages
+            .iter()
+            .zip(self.times.iter())
+            .filter(|(message, _)| {
+                let message_type = message.get_message_type();
+                message_type != Message::LOOP_START && message_type != Message::LOOP_END
+            });
+
+        for (message, time) in events.clone() {
+            if *time < loop_start {
+                messages.push(*message);
+                times.push(*time);
+            }
+        }
+
+        for i in 0..count {
+            let offset = loop_length * i as f64;
+            if i > 0 {
+                MidiTrack::push_all_notes_off(
+                    &mut messages,
+                    &mut times,
+                    loop_start + offset,
+                    port_count,
+                );
+            }
+            for (message, time) in events.clone() {
+                if loop_start <= *time && *time < loop_end {
+                    messages.push(*message);
+                    times.push(offset + *time);
+                }
+            }
+        }
+
+        let offset = loop_length * (count as f64 - 1.0);
+        if keep_rest {
+            for (message, time) in events {
+                if loop_end <= *time {
+                    messages.push(*message);
+                    times.push(offset + *time);
+                }
+            }
+        } else {
+            MidiTrack::push_all_notes_off(&mut messages, &mut times, loop_end + offset, port_count);
+        }
+
+        Self {
+            messages,
+            times,
+            origins: Vec::new(),
+        }
+    }
+
+    fn push_all_notes_off(
+        messages: &mut Vec<Message>,
+        times: &mut Vec<f64>,
+        time: f64,
+        port_count: usize,
+    ) {
+        for port in 0..port_count as u8 {
+            for channel in 0..Synthesizer::CHANNEL_COUNT as u8 {
+                let message =
+                    Message::common2(0xB0 | channel, 0x7B, 0, MidiFileLoopType::LoopPoint(0));
+                messages.push(message.with_port(port));
+                times.push(time);
+            }
+        }
+    }
+
+    // The ports are counted up to the highest one used by the channel events.
+    pub(crate) fn get_port_count(&self) -> usize {
+        self.messages
+            .iter()
+            .map(|x| x.get_port() as usize + 1)
+            .max()
+            .unwrap_or(1)
+    }
+
+    // Merges the tracks and divides the events by the channel of the synthesizer,
+    // to which the channels beyond the channel count are sent.
+    // The events not bound to a channel, such as the loop markers, are copied to all the channels.
+    // The channels without any channel event are `None`.
+    pub(crate) fn split_channels(tracks: &[&MidiTrack], channel_count: usize) -> Vec<Option<Self>> {
+        let mut events = tracks
+            .iter()
+            .flat_map(|x| x.messages.iter().zip(x.times.iter()))
+            .collect::<Vec<(&Message, &f64)>>();
+        // The sort is stable, so the events at the same time keep the order of the tracks.
+        events.sort_by(|a, b| a.1.total_cmp(b.1));
+
+        let is_channel = |message: &Message, channel: usize| {
+            message.get_message_type() == Message::NORMAL
+                && message.channel as usize % channel_count == channel
+        };
+
+        (0..channel_count)
+            .map(|channel| {
+                let is_used = events
+                    .iter()
+                    .any(|(message, _)| is_channel(message, channel));
+                if !is_used {
+                    return None;
+                }
+
+                let (messages, times) = events
+                    .iter()
+                    .filter(|(message, _)| {
+                        message.get_message_type() != Message::NORMAL
+                            || is_channel(message, channel)
+                    })
+                    .map(|(message, time)| (**message, **time))
+                    .unzip();
+                Some(Self {
+                    messages,
+                    times,
+                    origins: Vec::new(),
+                })
+            })
+            .collect()
+    }
+
+    // Finds the points where the track can be split without cutting any note.
+    // A point is the start of a note after a moment with no sounding note and the sustain off.
+    // The points are snapped down to the multiples of `quantum`, but not before the silence starts,
+    // and each segment is at least `min_length` long.
+    pub(crate) fn find_split_points(&self, min_length: f64, quantum: f64) -> Vec<f64> {
+        let mut points = Vec::new();
+
+        let channel_count = Synthesizer::CHANNEL_COUNT * self.get_port_count();
+        let mut notes = vec![[false; 128]; channel_count];
+        let mut sustain = vec![false; channel_count];
+        let mut sounding_count: usize = 0;
+        let mut silence_start: f64 = 0.0;
+        let mut segment_start: f64 = 0.0;
+
+        for (message, time) in self.messages.iter().zip(self.times.iter()) {
+            if message.get_message_type() != Message::NORMAL {
+                continue;
+            }
+
+            let channel = message.channel as usize;
+            let key = message.data1 as usize & 0x7F;
+            let was_silent = sounding_count == 0 && sustain.iter().all(|x| !x);
+
+            let is_note_off =
+                message.command == 0x80 || (message.command == 0x90 && message.data2 == 0);
+
+            match message.command {
+                _ if is_note_off => {
+                    sounding_count -= notes[channel][key] as usize;
+                    notes[channel][key] = false;
+                }
+                0x90 => {
+                    if was_silent {
+                        let point = FloatMath::floor_f64(*time / quantum) * quantum;
+                        if point >= silence_start && point - segment_start >= min_length {
+                            points.push(point);
+                            segment_start = point;
+                        }
+                    }
+                    sounding_count += !notes[channel][key] as usize;
+                    notes[channel][key] = true;
+                }
+                0xB0 => match message.data1 {
+                    // Sustain.
+                    0x40 => sustain[channel] = message.data2 >= 64,
+                    // Reset all controllers.
+                    0x79 => sustain[channel] = false,
+                    // All sound off and all notes off.
+                    0x78 | 0x7B => {
+                        sounding_count -= notes[channel].iter().filter(|x| **x).count();
+                        notes[channel] = [false; 128];
+                    }
+                    _ => (),
+                },
+                _ => (),
+            }
+
+            if !was_silent && sounding_count == 0 && sustain.iter().all(|x| !x) {
+                silence_start = *time;
+            }
+        }
+
+        points
+    }
+
+    // Splits the track at the points.
+    // Each segment begins with the preceding channel events other than the notes,
+    // so that the state of the channels is restored.
+    // The times are kept as they are, so the segment is played from its start point.
+    pub(crate) fn split(&self, points: &[f64]) -> Vec<Self> {
+        let mut segments = Vec::new();
+
+        let mut state = Vec::new();
+        let mut index: usize = 0;
+        for (i, start) in core::iter::once(0.0)
+            .chain(points.iter().copied())
+            .enumerate()
+        {
+            let end = points.get(i).copied().unwrap_or(f64::INFINITY);
+
+            let mut messages = state.clone();
+            let mut times = vec![start; state.len()];
+            while index < self.messages.len() && self.times[index] < end {
+                let message = self.messages[index];
+                messages.push(message);
+                times.push(self.times[index]);
+
+                let is_note = matches!(message.command, 0x80 | 0x90 | 0xA0);
+                let message_type = message.get_message_type();
+                if (message_type == Message::NORMAL && !is_note)
+                    || message_type == Message::PERCUSSION_CHANNEL
+                {
+                    state.push(message);
+                }
+
+                index += 1;
+            }
+
+            segments.push(Self {
+                messages,
+                times,
+                origins: Vec::new(),
+            });
+        }
+
+        segments
+    }
+
+    // Merges the tracks into one, which can be played by a single sequencer.
+    // Only the last end of track is kept, so that the track ends at the longest one.
+    pub(crate) fn merge(tracks: &[&MidiTrack]) -> Self {
+        let mut events = tracks
+            .iter()
+            .flat_map(|x| x.messages.iter().zip(x.times.iter()))
+            .filter(|(message, _)| message.get_message_type() != Message::END_OF_TRACK)
+            .collect::<Vec<(&Message, &f64)>>();
+        // The sort is stable, so the events at the same time keep the order of the tracks.
+        events.sort_by(|a, b| a.1.total_cmp(b.1));
+
+        let (mut messages, mut times): (Vec<Message>, Vec<f64>) = events
+            .iter()
+            .map(|(message, time)| (**message, **time))
+            .unzip();
+
+        let length = tracks.iter().fold(0.0, |max, x| x.get_length().max(max));
+        messages.push(Message::end_of_track());
+        times.push(length);
+
+        Self {
+            messages,
+            times,
+            origins: Vec::new(),
+        }
+    }
+
+    pub(crate) fn concat(tracks: &[&MidiTrack]) -> Self {
+        let mut messages = Vec::new();
+        let mut times = Vec::new();
+
+        let mut offset: f64 = 0.0;
+        for (i, track) in tracks.iter().enumerate() {
+            let is_last = i == tracks.len() - 1;
+            for (message, time) in track.messages.iter().zip(track.times.iter()) {
+                // Only the last track keeps its end of track.
+                if message.get_message_type() == Message::END_OF_TRACK && !is_last {
+                    continue;
+                }
+                messages.push(*message);
+                times.push(offset + *time);
+            }
+            offset += track.times.last().copied().unwrap_or(0.0);
+        }
+
+        Self {
+            messages,
+            times,
+            origins: Vec::new(),
+        }
     }
 }
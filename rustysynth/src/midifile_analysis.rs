@@ -0,0 +1,266 @@
+#![allow(dead_code)]
+
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::diagnostic::DiagnosticCode;
+use crate::diagnostic::DiagnosticLocation;
+use crate::diagnostic::DiagnosticSeverity;
+use crate::diagnostics::Diagnostics;
+use crate::midifile::Message;
+use crate::midifile::MidiFile;
+use crate::midifile::MidiTrack;
+use crate::synthesizer::Synthesizer;
+
+/// Represents the statistics of the events in a MIDI channel by `MidiFile::analyze`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ChannelAnalysis {
+    pub(crate) channel: usize,
+    pub(crate) event_count: usize,
+    pub(crate) note_count: usize,
+    pub(crate) key_range: Option<(i32, i32)>,
+    pub(crate) controllers: Vec<(i32, usize)>,
+    pub(crate) program_changes: Vec<(f64, i32)>,
+    pub(crate) overlapping_note_count: usize,
+    pub(crate) unterminated_note_count: usize,
+}
+
+impl ChannelAnalysis {
+    fn new(channel: usize) -> Self {
+        Self {
+            channel,
+            event_count: 0,
+            note_count: 0,
+            key_range: None,
+            controllers: Vec::new(),
+            program_changes: Vec::new(),
+            overlapping_note_count: 0,
+            unterminated_note_count: 0,
+        }
+    }
+
+    /// Gets the index of the channel, where the channels from 16 are those of the following MIDI ports.
+    pub fn get_channel(&self) -> usize {
+        self.channel
+    }
+
+    /// Gets the number of the channel events, including the note-off events.
+    pub fn get_event_count(&self) -> usize {
+        self.event_count
+    }
+
+    /// Gets the number of the note-on events.
+    pub fn get_note_count(&self) -> usize {
+        self.note_count
+    }
+
+    /// Gets the lowest and the highest keys of the notes, or `None` if the channel has no notes.
+    pub fn get_key_range(&self) -> Option<(i32, i32)> {
+        self.key_range
+    }
+
+    /// Gets the pairs of the controller numbers used in the channel and the number of their events.
+    ///
+    /// # Remarks
+    ///
+    /// The pairs are sorted by the controller number.
+    pub fn get_controllers(&self) -> &[(i32, usize)] {
+        &self.controllers
+    }
+
+    /// Gets the pairs of the time in seconds and the program number of each program change.
+    pub fn get_program_changes(&self) -> &[(f64, i32)] {
+        &self.program_changes
+    }
+
+    /// Gets the number of the notes started again before the previous note on the same key was turned off.
+    pub fn get_overlapping_note_count(&self) -> usize {
+        self.overlapping_note_count
+    }
+
+    /// Gets the number of the notes which are never turned off.
+    ///
+    /// # Remarks
+    ///
+    /// The notes turned off by the all-notes-off and all-sound-off controllers are not counted.
+    pub fn get_unterminated_note_count(&self) -> usize {
+        self.unterminated_note_count
+    }
+}
+
+/// Represents the statistics and the problems of the events in a MIDI file by `MidiFile::analyze`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct MidiFileAnalysis {
+    pub(crate) channels: Vec<ChannelAnalysis>,
+    pub(crate) note_count: usize,
+    pub(crate) length: f64,
+    pub(crate) diagnostics: Diagnostics,
+}
+
+impl MidiFileAnalysis {
+    pub(crate) fn new(file: &MidiFile) -> Self {
+        // The channels of each MIDI port are analyzed separately.
+        let channel_count = Synthesizer::CHANNEL_COUNT * file.get_port_count();
+        let mut channels: Vec<ChannelAnalysis> =
+            (0..channel_count).map(ChannelAnalysis::new).collect();
+        let mut controllers = vec![[0_usize; 128]; channel_count];
+
+        if file.format == 2 {
+            // Each pattern starts from the initial state.
+            for track in file.tracks.iter() {
+                let events = MidiFileAnalysis::get_events(track);
+                MidiFileAnalysis::analyze_events(&events, &mut channels, &mut controllers);
+            }
+        } else {
+            let mut events: Vec<(f64, Message)> = Vec::new();
+            for track in file.tracks.iter() {
+                events.extend(MidiFileAnalysis::get_events(track));
+            }
+            events.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+            MidiFileAnalysis::analyze_events(&events, &mut channels, &mut controllers);
+        }
+
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.extend(&file.diagnostics);
+
+        for (channel, counts) in channels.iter_mut().zip(controllers.iter()) {
+            channel.controllers = counts
+                .iter()
+                .enumerate()
+                .filter(|(_, count)| **count > 0)
+                .map(|(number, count)| (number as i32, *count))
+                .collect();
+
+            let location = DiagnosticLocation::Channel(channel.channel);
+            if channel.overlapping_note_count > 0 {
+                diagnostics.push(
+                    DiagnosticCode::MidiOverlappingNotes,
+                    DiagnosticSeverity::Warning,
+                    location,
+                    format!(
+                        "{} notes in the channel {} overlap the previous notes on the same key",
+                        channel.overlapping_note_count, channel.channel
+                    ),
+                );
+            }
+            if channel.unterminated_note_count > 0 {
+                diagnostics.push(
+                    DiagnosticCode::MidiNotesWithoutOff,
+                    DiagnosticSeverity::Warning,
+                    location,
+                    format!(
+                        "{} notes in the channel {} are never turned off",
+                        channel.unterminated_note_count, channel.channel
+                    ),
+                );
+            }
+        }
+
+        channels.retain(|x| x.event_count > 0);
+
+        Self {
+            note_count: channels.iter().map(|x| x.note_count).sum(),
+            channels,
+            length: file.length,
+            diagnostics,
+        }
+    }
+
+    // The events merged from the other tracks are counted only in their own tracks.
+    fn get_events(track: &MidiTrack) -> Vec<(f64, Message)> {
+        track
+            .messages
+            .iter()
+            .zip(track.times.iter())
+            .enumerate()
+            .filter(|(i, (message, _))| {
+                message.get_message_type() == Message::NORMAL && track.get_origin(*i).is_none()
+            })
+            .map(|(_, (message, time))| (*time, *message))
+            .collect()
+    }
+
+    // The events must be sorted in time order, since the channels are shared by all the tracks.
+    fn analyze_events(
+        events: &[(f64, Message)],
+        channels: &mut [ChannelAnalysis],
+        controllers: &mut [[usize; 128]],
+    ) {
+        // The number of the notes sounding on each key.
+        let mut active_notes = vec![[0_usize; 128]; channels.len()];
+
+        for (time, message) in events.iter() {
+            let index = message.channel as usize;
+            let channel = &mut channels[index];
+            let active = &mut active_notes[index];
+            // The parser does not check the data bytes, so the keys above 127 are masked.
+            let key = message.data1 as usize & 0x7F;
+
+            match message.command {
+                0x80..=0xEF => channel.event_count += 1,
+                _ => continue,
+            }
+
+            match message.command {
+                0x90 if message.data2 > 0 => {
+                    channel.note_count += 1;
+                    channel.key_range = match channel.key_range {
+                        Some((lowest, highest)) => {
+                            Some((lowest.min(key as i32), highest.max(key as i32)))
+                        }
+                        None => Some((key as i32, key as i32)),
+                    };
+                    if active[key] > 0 {
+                        channel.overlapping_note_count += 1;
+                    }
+                    active[key] += 1;
+                }
+                0x80 | 0x90 => {
+                    active[key] = active[key].saturating_sub(1);
+                }
+                0xB0 => {
+                    controllers[index][key] += 1;
+                    // The all-sound-off, the all-notes-off and the mode messages turn off all the notes.
+                    if key == 0x78 || key >= 0x7B {
+                        active.fill(0);
+                    }
+                }
+                0xC0 => channel.program_changes.push((*time, message.data1 as i32)),
+                _ => (),
+            }
+        }
+
+        for (channel, active) in channels.iter_mut().zip(active_notes.iter()) {
+            channel.unterminated_note_count += active.iter().sum::<usize>();
+        }
+    }
+
+    /// Gets the statistics of each channel which has any event, in the order of the channel.
+    pub fn get_channels(&self) -> &[ChannelAnalysis] {
+        &self.channels
+    }
+
+    /// Gets the number of the note-on events in all the channels.
+    pub fn get_note_count(&self) -> usize {
+        self.note_count
+    }
+
+    /// Gets the length of the MIDI file in seconds.
+    pub fn get_length(&self) -> f64 {
+        self.length
+    }
+
+    /// Gets the problems found by the analysis.
+    ///
+    /// # Remarks
+    ///
+    /// This includes the diagnostics reported when loading the MIDI file,
+    /// the overlapping notes and the notes which are never turned off.
+    pub fn get_diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+}
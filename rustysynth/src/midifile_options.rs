@@ -0,0 +1,38 @@
+#![allow(dead_code)]
+
+use crate::midifile_looptype::MidiFileLoopType;
+use crate::parser_limits::ParserLimits;
+
+/// Specifies a set of options for loading a MIDI file.
+#[derive(Clone, Copy)]
+#[non_exhaustive]
+pub struct MidiFileOptions {
+    /// The type of the loop extension to be used.
+    pub loop_type: MidiFileLoopType,
+    /// The value indicating whether broken MIDI files are loaded as much as possible.
+    ///
+    /// If `true`, the loader recovers from missing EOT events and truncated tracks.
+    /// The problems found are reported as warnings instead of an error.
+    pub lenient: bool,
+    /// The limits applied when parsing the MIDI file.
+    pub limits: ParserLimits,
+}
+
+impl MidiFileOptions {
+    const DEFAULT_LENIENT: bool = false;
+
+    /// Initializes a new instance of MIDI file options.
+    pub fn new() -> Self {
+        Self {
+            loop_type: MidiFileLoopType::LoopPoint(0),
+            lenient: MidiFileOptions::DEFAULT_LENIENT,
+            limits: ParserLimits::new(),
+        }
+    }
+}
+
+impl Default for MidiFileOptions {
+    fn default() -> Self {
+        MidiFileOptions::new()
+    }
+}
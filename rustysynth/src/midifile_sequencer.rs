@@ -1,10 +1,30 @@
 #![allow(dead_code)]
 
-use std::cmp;
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp;
+#[cfg(feature = "tokio")]
+use tokio::task;
 
+use crate::automation::Automation;
+use crate::automation_target::AutomationTarget;
+use crate::cancellation_token::CancellationToken;
+use crate::float_math::FloatMath;
+use crate::metronome::Metronome;
+use crate::midi_message::MidiMessage;
 use crate::midifile::Message;
+use crate::midifile::MidiFile;
 use crate::midifile::MidiTrack;
+use crate::sequence::Sequence;
+use crate::sequencer_loop_mode::SequencerLoopMode;
 use crate::synthesizer::Synthesizer;
+use crate::synthesizer_state::SynthesizerState;
+use crate::tempo_map::TempoMap;
+use crate::voice_event::VoiceEvent;
+
+// The filter returns `false` to drop the message.
+type EventFilter = Box<dyn FnMut(&mut MidiMessage) -> bool + Send>;
 
 /// An instance of the MIDI file sequencer.
 #[non_exhaustive]
@@ -15,12 +35,41 @@ pub struct MidiFileSequencer {
 
     midi_track: Option<MidiTrack>,
     play_loop: bool,
+    // The number of the loops left before the rest of the track follows,
+    // or before the fade-out starts if the length of the fade-out in samples is given.
+    remaining_loop_count: Option<usize>,
+    fade_length: Option<u64>,
+    fade_position: Option<u64>,
+    // The loop range in seconds, which overrides the loop points in the track.
+    loop_range: Option<(f64, f64)>,
+    // The controllers at the loop start, which are restored at each loop.
+    // The snapshot is allocated beforehand, so that saving it at the loop start does not allocate.
+    loop_state: SynthesizerState,
+    has_loop_state: bool,
+    skip_silence: bool,
 
     block_wrote: usize,
+    block_skipped: bool,
 
     current_time: f64,
     msg_index: usize,
     loop_index: usize,
+
+    cancellation_token: Option<CancellationToken>,
+
+    event_filter: Option<EventFilter>,
+
+    metronome: Option<Metronome>,
+    // The sounds of the downbeat and the upbeat, and the times of the clicks in the track.
+    click_sounds: (Vec<f32>, Vec<f32>),
+    clicks: Vec<(f64, bool)>,
+    // The clicks being played, and their positions in the sounds, which are negative until they start.
+    playing_clicks: Vec<(bool, i64)>,
+
+    automations: Vec<(AutomationTarget, Automation)>,
+    tempo_map: Option<TempoMap>,
+    // The number of the output samples since the playback started, which is the time base of the automations.
+    output_position: u64,
 }
 
 impl MidiFileSequencer {
@@ -30,15 +79,33 @@ impl MidiFileSequencer {
     ///
     /// * `synthesizer` - The synthesizer to be handled by the sequencer.
     pub fn new(synthesizer: Synthesizer) -> Self {
+        let loop_state = synthesizer.save_state(false);
         Self {
             synthesizer,
             speed: 1.0,
             midi_track: None,
             play_loop: false,
+            remaining_loop_count: None,
+            fade_length: None,
+            fade_position: None,
+            loop_range: None,
+            loop_state,
+            has_loop_state: false,
+            skip_silence: false,
             block_wrote: 0,
+            block_skipped: false,
             current_time: 0.0,
             msg_index: 0,
             loop_index: 0,
+            cancellation_token: None,
+            event_filter: None,
+            metronome: None,
+            click_sounds: (Vec::new(), Vec::new()),
+            clicks: Vec::new(),
+            playing_clicks: Vec::new(),
+            automations: Vec::new(),
+            tempo_map: None,
+            output_position: 0,
         }
     }
 
@@ -49,24 +116,400 @@ impl MidiFileSequencer {
     /// * `midi_track` - The MIDI track to be played.
     /// * `play_loop` - If `true`, the MIDI file loops after reaching the end.
     pub fn play(&mut self, midi_track: MidiTrack, play_loop: bool) {
+        let loop_mode = if play_loop {
+            SequencerLoopMode::Infinite
+        } else {
+            SequencerLoopMode::Once
+        };
+        self.play_with_loop_mode(midi_track, loop_mode);
+    }
+
+    /// Plays the MIDI track with the specified loop behavior.
+    ///
+    /// # Arguments
+    ///
+    /// * `midi_track` - The MIDI track to be played.
+    /// * `loop_mode` - How the track is looped.
+    ///
+    /// # Remarks
+    ///
+    /// At each loop, the playing notes are released,
+    /// and the controllers and the programs are restored to those at the loop start,
+    /// so the loop sounds the same every time even if they are changed within the loop.
+    /// The settings given by code, such as the program overrides, are kept.
+    pub fn play_with_loop_mode(&mut self, midi_track: MidiTrack, loop_mode: SequencerLoopMode) {
+        let (play_loop, remaining_loop_count, fade_length, loop_range) = match loop_mode {
+            SequencerLoopMode::Once => (false, None, None, None),
+            SequencerLoopMode::Infinite => (true, None, None, None),
+            SequencerLoopMode::Count(count) => {
+                if count == 0 {
+                    panic!("The loop count must be greater than zero.");
+                }
+                (count > 1, Some(count - 1).filter(|x| *x > 0), None, None)
+            }
+            SequencerLoopMode::FadeOut { count, fade } => {
+                if count == 0 {
+                    panic!("The loop count must be greater than zero.");
+                }
+                if fade.is_nan() || fade < 0.0 {
+                    panic!("The fade length must be a non-negative value.");
+                }
+                let fade_length = (self.synthesizer.sample_rate as f64 * fade) as u64;
+                (true, Some(count), Some(fade_length), None)
+            }
+            SequencerLoopMode::Range { start, end } => {
+                if start.is_nan() || start < 0.0 {
+                    panic!("The loop start must be a non-negative value.");
+                }
+                if end.is_nan() || end <= start {
+                    panic!("The loop end must be after the loop start.");
+                }
+                (true, None, None, Some((start, end)))
+            }
+        };
+
+        self.loop_index = match loop_range {
+            Some((start, _)) => midi_track.times.partition_point(|x| *x < start),
+            None => 0,
+        };
         self.midi_track = Some(midi_track);
         self.play_loop = play_loop;
+        self.remaining_loop_count = remaining_loop_count;
+        self.fade_length = fade_length;
+        self.fade_position = None;
+        self.loop_range = loop_range;
 
         self.block_wrote = self.synthesizer.block_size;
+        self.block_skipped = false;
 
         self.current_time = 0.0;
         self.msg_index = 0;
-        self.loop_index = 0;
 
-        self.synthesizer.reset()
+        self.output_position = 0;
+
+        self.update_clicks();
+        self.playing_clicks.clear();
+
+        self.synthesizer.reset();
+        MidiFileSequencer::reset_loop_state(
+            &self.synthesizer,
+            self.loop_range,
+            &mut self.loop_state,
+            &mut self.has_loop_state,
+        );
+    }
+
+    // Without the loop range, the loop starts at the beginning until a loop start point is found.
+    // Otherwise, the controllers are saved when the playback reaches the loop start.
+    // The fields are borrowed separately, as the track is borrowed while it is read.
+    fn reset_loop_state(
+        synthesizer: &Synthesizer,
+        loop_range: Option<(f64, f64)>,
+        loop_state: &mut SynthesizerState,
+        has_loop_state: &mut bool,
+    ) {
+        match loop_range {
+            Some(_) => *has_loop_state = false,
+            None => MidiFileSequencer::save_loop_state(synthesizer, loop_state, has_loop_state),
+        }
+    }
+
+    fn save_loop_state(
+        synthesizer: &Synthesizer,
+        loop_state: &mut SynthesizerState,
+        has_loop_state: &mut bool,
+    ) {
+        synthesizer.save_controllers(loop_state);
+        *has_loop_state = true;
+    }
+
+    /// Plays the MIDI track with the loop repeated a fixed number of times, and then fades it out.
+    ///
+    /// # Arguments
+    ///
+    /// * `midi_track` - The MIDI track to be played.
+    /// * `count` - The number of times the loop is played before the fade-out starts.
+    /// * `fade_out` - The length of the fade-out in seconds.
+    ///
+    /// # Remarks
+    ///
+    /// This is the same as `play_with_loop_mode` with `SequencerLoopMode::FadeOut`.
+    /// The playback keeps looping during the fade-out,
+    /// and the output is silent after the fade-out is complete.
+    /// `end_of_sequence` becomes `true` at that point.
+    pub fn play_loops(&mut self, midi_track: MidiTrack, count: usize, fade_out: f64) {
+        let loop_mode = SequencerLoopMode::FadeOut {
+            count,
+            fade: fade_out,
+        };
+        self.play_with_loop_mode(midi_track, loop_mode);
+    }
+
+    /// Plays a pattern of the format 2 MIDI file.
+    ///
+    /// # Arguments
+    ///
+    /// * `midi_file` - The MIDI file which contains the pattern.
+    /// * `index` - The index of the pattern to be played.
+    /// * `play_loop` - If `true`, the pattern loops after reaching the end.
+    pub fn play_pattern(&mut self, midi_file: &MidiFile, index: usize, play_loop: bool) {
+        self.play_patterns(midi_file, &[index], play_loop);
+    }
+
+    /// Plays the selected patterns of the format 2 MIDI file one after another.
+    ///
+    /// # Arguments
+    ///
+    /// * `midi_file` - The MIDI file which contains the patterns.
+    /// * `indices` - The indices of the patterns to be played in order.
+    /// * `play_loop` - If `true`, the sequence loops after reaching the end.
+    ///
+    /// # Remarks
+    ///
+    /// The same pattern can be selected more than once.
+    pub fn play_patterns(&mut self, midi_file: &MidiFile, indices: &[usize], play_loop: bool) {
+        if indices.is_empty() {
+            panic!("At least one pattern must be selected.");
+        }
+
+        let pattern_count = midi_file.get_pattern_count();
+        let mut patterns: Vec<&MidiTrack> = Vec::new();
+        for index in indices.iter() {
+            if *index >= pattern_count {
+                panic!("The pattern index is out of range.");
+            }
+            patterns.push(&midi_file.tracks[*index]);
+        }
+
+        self.play(MidiTrack::concat(&patterns), play_loop);
+    }
+
+    // Starts the playback from the specified block, as if the preceding blocks were rendered.
+    // The time advances in the same way as the rendering, so the events are processed at the same timing.
+    pub(crate) fn skip_blocks(&mut self, count: usize) {
+        for _ in 0..count {
+            self.current_time += self.speed * self.synthesizer.block_size as f64
+                / self.synthesizer.sample_rate as f64;
+        }
+        self.synthesizer
+            .skip_voice_event_position(count * self.synthesizer.block_size);
+        self.output_position += (count * self.synthesizer.block_size) as u64;
+    }
+
+    /// Plays the sequence built by code.
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence` - The sequence to be played.
+    /// * `play_loop` - If `true`, the sequence loops after reaching the end.
+    ///
+    /// # Remarks
+    ///
+    /// The sequence is copied, so it can be modified or reused after this call.
+    pub fn play_sequence(&mut self, sequence: &Sequence, play_loop: bool) {
+        self.play(sequence.to_track(), play_loop);
     }
 
     /// Stops playing.
     pub fn stop(&mut self) {
         self.midi_track = None;
+        self.clicks.clear();
+        self.playing_clicks.clear();
         self.synthesizer.reset();
     }
 
+    /// Moves the playback position to the specified time, as if the track had been played up to there.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The new position in seconds.
+    ///
+    /// # Remarks
+    ///
+    /// The sound is stopped, and the messages before the position other than the notes,
+    /// such as the program changes and the controllers, are sent to the synthesizer without rendering.
+    /// So the seek is almost instant even in a long track.
+    /// The notes held at the position are started again from the beginning.
+    /// The position is on the timeline of the track, and the loop is not followed while seeking.
+    /// This does nothing if no track is playing.
+    pub fn seek(&mut self, position: f64) {
+        if position.is_nan() || position < 0.0 {
+            panic!("The position must be a non-negative value.");
+        }
+
+        let midi_track = match self.midi_track.as_ref() {
+            Some(value) => value,
+            None => return,
+        };
+
+        self.synthesizer.reset();
+        self.block_wrote = self.synthesizer.block_size;
+        self.block_skipped = false;
+        self.playing_clicks.clear();
+
+        self.current_time = position;
+        self.msg_index = 0;
+        self.loop_index = match self.loop_range {
+            Some((start, _)) => midi_track.times.partition_point(|x| *x < start),
+            None => 0,
+        };
+        MidiFileSequencer::reset_loop_state(
+            &self.synthesizer,
+            self.loop_range,
+            &mut self.loop_state,
+            &mut self.has_loop_state,
+        );
+
+        // The notes which have been started but not stopped before the position.
+        let mut held_notes: Vec<MidiMessage> = Vec::new();
+        while self.msg_index < midi_track.messages.len()
+            && midi_track.times[self.msg_index] < position
+        {
+            if !self.has_loop_state && self.msg_index == self.loop_index {
+                MidiFileSequencer::save_loop_state(
+                    &self.synthesizer,
+                    &mut self.loop_state,
+                    &mut self.has_loop_state,
+                );
+            }
+
+            let msg = midi_track.messages[self.msg_index];
+            if msg.get_message_type() == Message::NORMAL {
+                let mut message = MidiMessage::from_message(&msg);
+                let accepted = match self.event_filter.as_mut() {
+                    Some(filter) => filter(&mut message),
+                    None => true,
+                };
+                if accepted {
+                    match (message.command, message.data1) {
+                        (0x80 | 0x90, _) => {
+                            held_notes.retain(|x| {
+                                x.channel != message.channel || x.data1 != message.data1
+                            });
+                            if message.command == 0x90 && message.data2 > 0 {
+                                held_notes.push(message);
+                            }
+                        }
+                        (0xA0, _) => (),
+                        (0xB0, 0x78 | 0x7B) => {
+                            held_notes.retain(|x| x.channel != message.channel);
+                        }
+                        _ => self.synthesizer.process_midi_message(
+                            message.channel as i32,
+                            message.command as i32,
+                            message.data1 as i32,
+                            message.data2 as i32,
+                        ),
+                    }
+                }
+            } else if msg.get_message_type() == Message::PERCUSSION_CHANNEL {
+                self.synthesizer
+                    .process_gs_rhythm_part(msg.command as i32, msg.data1 != 0);
+            } else if msg.get_message_type() == Message::LOOP_START && self.loop_range.is_none() {
+                self.loop_index = self.msg_index;
+                MidiFileSequencer::save_loop_state(
+                    &self.synthesizer,
+                    &mut self.loop_state,
+                    &mut self.has_loop_state,
+                );
+            }
+            self.msg_index += 1;
+        }
+
+        for note in held_notes.iter() {
+            self.synthesizer.process_midi_message(
+                note.channel as i32,
+                note.command as i32,
+                note.data1 as i32,
+                note.data2 as i32,
+            );
+        }
+
+        self.output_position = (position * self.synthesizer.sample_rate as f64) as u64;
+    }
+
+    /// Renders the specified time range of the track.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The start of the range in seconds.
+    /// * `end` - The end of the range in seconds.
+    ///
+    /// # Remarks
+    ///
+    /// The playback position is moved by `seek` to the block boundary just before the start,
+    /// so that the events are processed at the same timing as playing from the beginning.
+    /// Then, the range is rendered, where the part before the start is discarded.
+    /// This allows a preview player to audition any part of a long piece instantly.
+    /// The range is rendered at the current speed, and the playback continues from the end afterwards.
+    pub fn render_range(&mut self, start: f64, end: f64) -> (Vec<f32>, Vec<f32>) {
+        if start.is_nan() || start < 0.0 {
+            panic!("The start must be a non-negative value.");
+        }
+
+        if end.is_nan() || end < start {
+            panic!("The end must not be before the start.");
+        }
+
+        if self.speed == 0.0 {
+            panic!("The playback speed must be greater than zero.");
+        }
+
+        let (aligned_start, lead) = self.align_position(start);
+        self.seek(aligned_start);
+
+        let sample_count =
+            ((end - start) / self.speed * self.synthesizer.sample_rate as f64) as usize;
+        let mut left = vec![0_f32; lead + sample_count];
+        let mut right = vec![0_f32; lead + sample_count];
+        self.render(&mut left, &mut right);
+        left.drain(..lead);
+        right.drain(..lead);
+
+        (left, right)
+    }
+
+    // The block boundary at or before the position in the track,
+    // and the number of the output samples from there to the position.
+    pub(crate) fn align_position(&self, position: f64) -> (f64, usize) {
+        let block_length =
+            self.speed * self.synthesizer.block_size as f64 / self.synthesizer.sample_rate as f64;
+        let block_count = FloatMath::floor_f64(position / block_length);
+        let aligned = block_count * block_length;
+        let lead = FloatMath::round_f64(
+            (position - aligned) / self.speed * self.synthesizer.sample_rate as f64,
+        ) as usize;
+        (aligned, lead)
+    }
+
+    /// Renders the waveform asynchronously, yielding to the executor after each block.
+    ///
+    /// # Arguments
+    ///
+    /// * `left` - The buffer of the left channel to store the rendered waveform.
+    /// * `right` - The buffer of the right channel to store the rendered waveform.
+    ///
+    /// # Remarks
+    ///
+    /// The output buffers for the left and right must be the same length.
+    /// The result is the same as `render`, but a long rendering does not block the other tasks
+    /// running on the same executor thread.
+    #[cfg(feature = "tokio")]
+    pub async fn render_async(&mut self, left: &mut [f32], right: &mut [f32]) {
+        if left.len() != right.len() {
+            panic!("The output buffers for the left and right must be the same length.");
+        }
+
+        let block_size = self.synthesizer.block_size;
+        for (left, right) in left
+            .chunks_mut(block_size)
+            .zip(right.chunks_mut(block_size))
+        {
+            self.render(left, right);
+            task::yield_now().await;
+        }
+    }
+
     /// Renders the waveform.
     ///
     /// # Arguments
@@ -86,71 +529,332 @@ impl MidiFileSequencer {
         let mut wrote: usize = 0;
         while wrote < left_length {
             if self.block_wrote == self.synthesizer.block_size {
+                if self.is_cancelled() {
+                    left[wrote..].fill(0_f32);
+                    right[wrote..].fill(0_f32);
+                    return;
+                }
+
+                self.synthesizer.apply_pending_changes();
+                self.update_tempo();
                 self.process_events();
+                self.schedule_clicks();
+                self.apply_automations();
+                self.output_position += self.synthesizer.block_size as u64;
                 self.block_wrote = 0;
                 self.current_time += self.speed * self.synthesizer.block_size as f64
                     / self.synthesizer.sample_rate as f64;
+
+                let was_skipped = self.block_skipped;
+                self.block_skipped = self.skip_silence && self.synthesizer.is_silent();
+                if self.block_skipped && !was_skipped {
+                    // Drop the inaudible tail so that it will not come back after the silence.
+                    self.synthesizer.mute_effects();
+                }
             }
 
             let src_rem = self.synthesizer.block_size - self.block_wrote;
             let dst_rem = left_length - wrote;
             let rem = cmp::min(src_rem, dst_rem);
 
-            self.synthesizer.render(
+            if self.block_skipped {
+                left[wrote..wrote + rem].fill(0_f32);
+                right[wrote..wrote + rem].fill(0_f32);
+                self.synthesizer.skip_voice_event_position(rem);
+            } else {
+                self.synthesizer.render(
+                    &mut left[wrote..wrote + rem],
+                    &mut right[wrote..wrote + rem],
+                );
+            }
+
+            self.mix_clicks(
                 &mut left[wrote..wrote + rem],
                 &mut right[wrote..wrote + rem],
             );
 
+            if let (Some(fade_position), Some(fade_length)) =
+                (self.fade_position.as_mut(), self.fade_length)
+            {
+                for t in wrote..wrote + rem {
+                    let gain = if *fade_position < fade_length {
+                        1_f32 - *fade_position as f32 / fade_length as f32
+                    } else {
+                        0_f32
+                    };
+                    left[t] *= gain;
+                    right[t] *= gain;
+                    *fade_position += 1;
+                }
+            }
+
             self.block_wrote += rem;
             wrote += rem;
         }
     }
 
+    fn update_clicks(&mut self) {
+        match (self.metronome.as_ref(), self.midi_track.as_ref()) {
+            (Some(metronome), Some(midi_track)) => {
+                self.clicks = metronome.get_clicks(midi_track.get_length());
+                self.click_sounds = metronome.create_sounds(self.synthesizer.sample_rate);
+            }
+            _ => self.clicks.clear(),
+        }
+    }
+
+    // The clicks in the current block are started at the exact sample, unlike the MIDI messages.
+    fn schedule_clicks(&mut self) {
+        if self.clicks.is_empty() || self.speed == 0.0 {
+            return;
+        }
+
+        let start = self.current_time;
+        let end = start
+            + self.speed * self.synthesizer.block_size as f64 / self.synthesizer.sample_rate as f64;
+
+        let first = self.clicks.partition_point(|(time, _)| *time < start);
+        for (time, downbeat) in self.clicks[first..].iter() {
+            if *time >= end {
+                break;
+            }
+
+            // Half a sample is added against the truncation,
+            // since the current time is the sum of the block lengths and may fall short.
+            let delay = (*time - start) / self.speed * self.synthesizer.sample_rate as f64;
+            self.playing_clicks
+                .push((*downbeat, -((delay + 0.5) as i64)));
+        }
+    }
+
+    fn mix_clicks(&mut self, left: &mut [f32], right: &mut [f32]) {
+        if self.playing_clicks.is_empty() {
+            return;
+        }
+
+        let volume = match self.metronome.as_ref() {
+            Some(metronome) => metronome.get_volume(),
+            None => 0_f32,
+        };
+
+        let (downbeat, upbeat) = &self.click_sounds;
+        for (is_downbeat, position) in self.playing_clicks.iter_mut() {
+            let sound = if *is_downbeat { downbeat } else { upbeat };
+            for t in 0..left.len() {
+                let index = *position + t as i64;
+                if 0 <= index && (index as usize) < sound.len() {
+                    let value = volume * sound[index as usize];
+                    left[t] += value;
+                    right[t] += value;
+                }
+            }
+            *position += left.len() as i64;
+        }
+
+        self.playing_clicks.retain(|(is_downbeat, position)| {
+            let sound = if *is_downbeat { downbeat } else { upbeat };
+            *position < sound.len() as i64
+        });
+    }
+
+    // The tempo of the synthesizer follows the playback position and the speed.
+    fn update_tempo(&mut self) {
+        if let Some(tempo_map) = self.tempo_map.as_ref() {
+            if self.speed > 0.0 {
+                let tempo = tempo_map.get_tempo_at(self.current_time);
+                self.synthesizer.set_tempo(self.speed * tempo);
+            }
+        }
+    }
+
+    // The automations override the MIDI messages processed in the same block.
+    fn apply_automations(&mut self) {
+        let time = self.output_position as f64 / self.synthesizer.sample_rate as f64;
+        for (target, automation) in self.automations.iter() {
+            if let Some(value) = automation.get_value(time) {
+                self.synthesizer.apply_automation(*target, value);
+            }
+        }
+    }
+
     fn process_events(&mut self) {
-        let midi_file = match self.midi_track.as_ref() {
-            Some(value) => value,
-            None => return,
+        // The events from the end of the loop range are not processed, as the playback goes back before them.
+        let loop_end = match self.loop_range {
+            Some((_, end)) => end,
+            None => f64::INFINITY,
         };
 
-        while self.msg_index < midi_file.messages.len() {
-            let time = midi_file.times[self.msg_index];
-            let msg = midi_file.messages[self.msg_index];
+        loop {
+            let midi_track = match self.midi_track.as_ref() {
+                Some(value) => value,
+                None => return,
+            };
 
-            if time <= self.current_time {
-                if msg.get_message_type() == Message::NORMAL {
+            if self.msg_index == midi_track.messages.len() {
+                break;
+            }
+
+            let time = midi_track.times[self.msg_index];
+            let msg = midi_track.messages[self.msg_index];
+            if time > self.current_time || time >= loop_end {
+                break;
+            }
+
+            if !self.has_loop_state && self.msg_index == self.loop_index {
+                MidiFileSequencer::save_loop_state(
+                    &self.synthesizer,
+                    &mut self.loop_state,
+                    &mut self.has_loop_state,
+                );
+            }
+
+            if msg.get_message_type() == Message::NORMAL {
+                let mut message = MidiMessage::from_message(&msg);
+                let accepted = match self.event_filter.as_mut() {
+                    Some(filter) => filter(&mut message),
+                    None => true,
+                };
+                if accepted {
                     self.synthesizer.process_midi_message(
-                        msg.channel as i32,
-                        msg.command as i32,
-                        msg.data1 as i32,
-                        msg.data2 as i32,
+                        message.channel as i32,
+                        message.command as i32,
+                        message.data1 as i32,
+                        message.data2 as i32,
                     );
-                } else if self.play_loop {
-                    if msg.get_message_type() == Message::LOOP_START {
-                        self.loop_index = self.msg_index;
-                    } else if msg.get_message_type() == Message::LOOP_END {
-                        self.current_time = midi_file.times[self.loop_index];
-                        self.msg_index = self.loop_index;
-                        self.synthesizer.note_off_all(false);
-                    }
                 }
-                self.msg_index += 1;
-            } else {
-                break;
+            } else if msg.get_message_type() == Message::PERCUSSION_CHANNEL {
+                self.synthesizer
+                    .process_gs_rhythm_part(msg.command as i32, msg.data1 != 0);
+            } else if self.play_loop && self.loop_range.is_none() {
+                if msg.get_message_type() == Message::LOOP_START {
+                    self.loop_index = self.msg_index;
+                    MidiFileSequencer::save_loop_state(
+                        &self.synthesizer,
+                        &mut self.loop_state,
+                        &mut self.has_loop_state,
+                    );
+                } else if msg.get_message_type() == Message::LOOP_END {
+                    let loop_time = midi_track.times[self.loop_index];
+                    self.loop_back(loop_time);
+                    continue;
+                }
+            }
+            self.msg_index += 1;
+        }
+
+        let midi_track = match self.midi_track.as_ref() {
+            Some(value) => value,
+            None => return,
+        };
+
+        match self.loop_range {
+            Some((start, end)) => {
+                if self.current_time >= end {
+                    self.loop_back(start);
+                }
+            }
+            None => {
+                if self.msg_index == midi_track.messages.len() && self.play_loop {
+                    let loop_time = midi_track.times[self.loop_index];
+                    self.loop_back(loop_time);
+                }
+            }
+        }
+    }
+
+    // Moves the playback position back to the loop start, where the controllers are restored.
+    fn loop_back(&mut self, time: f64) {
+        // If no event is found in the loop range, the current controllers are those at the loop start.
+        if !self.has_loop_state {
+            MidiFileSequencer::save_loop_state(
+                &self.synthesizer,
+                &mut self.loop_state,
+                &mut self.has_loop_state,
+            );
+        }
+
+        self.current_time = time;
+        self.msg_index = self.loop_index;
+        self.synthesizer.note_off_all(false);
+        self.synthesizer.restore_controllers(&self.loop_state);
+
+        self.count_loop();
+    }
+
+    // After the specified number of loops, the loop points are ignored and the rest of the track follows,
+    // or the fade-out starts in the case of `SequencerLoopMode::FadeOut`.
+    fn count_loop(&mut self) {
+        if let Some(count) = self.remaining_loop_count.as_mut() {
+            *count -= 1;
+            if *count == 0 {
+                self.remaining_loop_count = None;
+                match self.fade_length {
+                    Some(_) => self.fade_position = Some(0),
+                    None => self.play_loop = false,
+                }
             }
         }
+    }
+
+    /// Gets the automation of the specified parameter.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The parameter changed by the automation.
+    pub fn get_automation(&self, target: AutomationTarget) -> Option<&Automation> {
+        self.automations
+            .iter()
+            .find(|(x, _)| *x == target)
+            .map(|(_, automation)| automation)
+    }
 
-        if self.msg_index == midi_file.messages.len() && self.play_loop {
-            self.current_time = midi_file.times[self.loop_index];
-            self.msg_index = self.loop_index;
-            self.synthesizer.note_off_all(false);
+    /// Sets the automation of the specified parameter.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The parameter changed by the automation.
+    /// * `value` - The automation, or `None` to remove it.
+    ///
+    /// # Remarks
+    ///
+    /// The time of the automation is measured in the output from the start of the playback,
+    /// so it is not affected by the speed and keeps increasing across the loops.
+    /// The value is applied at the start of each block, and the voices smooth the change within the block.
+    /// While an automation is set, it overrides the MIDI messages which change the same parameter.
+    /// The parameter keeps the last value after the automation is removed.
+    pub fn set_automation(&mut self, target: AutomationTarget, value: Option<Automation>) {
+        target.check(self.synthesizer.get_channel_count());
+
+        self.automations.retain(|(x, _)| *x != target);
+        if let Some(automation) = value {
+            self.automations.push((target, automation));
         }
     }
 
+    /// Removes all the automations.
+    pub fn clear_automations(&mut self) {
+        self.automations.clear();
+    }
+
     /// Gets the synthesizer handled by the sequencer.
     pub fn get_synthesizer(&self) -> &Synthesizer {
         &self.synthesizer
     }
 
+    pub(crate) fn get_synthesizer_mut(&mut self) -> &mut Synthesizer {
+        &mut self.synthesizer
+    }
+
+    /// Takes the voice events recorded by the synthesizer since the last call.
+    ///
+    /// # Remarks
+    ///
+    /// The recording must be enabled by `Synthesizer::set_voice_event_capacity`
+    /// before the synthesizer is passed to the sequencer.
+    pub fn drain_voice_events(&mut self) -> impl Iterator<Item = VoiceEvent> + '_ {
+        self.synthesizer.drain_voice_events()
+    }
+
     /// Gets the currently playing MIDI track.
     pub fn get_midi_track(&self) -> Option<&MidiTrack> {
         match &self.midi_track {
@@ -169,11 +873,18 @@ impl MidiFileSequencer {
     /// # Remarks
     ///
     /// If the `play` method has not yet been called, this value will be `true`.
-    /// This value will never be `true` if loop playback is enabled.
+    /// This value will never be `true` if loop playback is enabled, except after the fade-out.
+    /// With `SequencerLoopMode::Count`, this value becomes `true` at the end of the track after the loops.
     pub fn end_of_sequence(&self) -> bool {
+        if let (Some(position), Some(fade_length)) = (self.fade_position, self.fade_length) {
+            if position >= fade_length {
+                return true;
+            }
+        }
+
         match &self.midi_track {
             None => true,
-            Some(value) => self.msg_index == value.messages.len(),
+            Some(value) => self.loop_range.is_none() && self.msg_index == value.messages.len(),
         }
     }
 
@@ -187,6 +898,123 @@ impl MidiFileSequencer {
         self.speed
     }
 
+    /// Gets a value that indicates whether silent regions are skipped.
+    ///
+    /// # Remarks
+    ///
+    /// The default value is `false`.
+    pub fn get_skip_silence(&self) -> bool {
+        self.skip_silence
+    }
+
+    /// Sets a value that indicates whether silent regions are skipped.
+    ///
+    /// # Remarks
+    ///
+    /// If `true`, the synthesis is bypassed while no voice is playing and the output is not audible,
+    /// and the silence is written instead.
+    /// This greatly speeds up offline rendering of sparse MIDI files.
+    /// The inaudible tail of the reverb and chorus is cut at the start of the silence.
+    pub fn set_skip_silence(&mut self, value: bool) {
+        self.skip_silence = value;
+    }
+
+    /// Gets the cancellation token checked by the sequencer.
+    pub fn get_cancellation_token(&self) -> Option<&CancellationToken> {
+        self.cancellation_token.as_ref()
+    }
+
+    /// Sets the cancellation token checked by the sequencer.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The token, or `None` to disable the cancellation.
+    ///
+    /// # Remarks
+    ///
+    /// Once the token is cancelled, `render` writes the silence without processing the events.
+    pub fn set_cancellation_token(&mut self, value: Option<CancellationToken>) {
+        self.cancellation_token = value;
+    }
+
+    /// Sets the filter applied to the channel messages before they are sent to the synthesizer.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The function which modifies the message in place,
+    ///   and returns `false` to drop it.
+    ///
+    /// # Remarks
+    ///
+    /// The filter allows to drop the controllers, remap the channels, transpose the keys,
+    /// or limit the velocity without rewriting the MIDI file.
+    /// Only the channel messages are filtered,
+    /// and the tempo changes, the loop points and the SysEx messages are processed as they are.
+    /// The filter is kept across the calls to `play`.
+    pub fn set_event_filter<F>(&mut self, filter: F)
+    where
+        F: FnMut(&mut MidiMessage) -> bool + Send + 'static,
+    {
+        self.event_filter = Some(Box::new(filter));
+    }
+
+    /// Removes the event filter.
+    pub fn clear_event_filter(&mut self) {
+        self.event_filter = None;
+    }
+
+    /// Gets the metronome mixed into the output.
+    pub fn get_metronome(&self) -> Option<&Metronome> {
+        self.metronome.as_ref()
+    }
+
+    /// Sets the metronome mixed into the output.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The metronome, or `None` to disable the clicks.
+    ///
+    /// # Remarks
+    ///
+    /// The clicks follow the playback position, so they are affected by the speed and the loops,
+    /// and keep sounding while the silence is skipped.
+    /// The clicks are generated up to the end of the playing track, and the change takes effect immediately.
+    pub fn set_metronome(&mut self, value: Option<Metronome>) {
+        self.metronome = value;
+        self.update_clicks();
+        if self.metronome.is_none() {
+            self.playing_clicks.clear();
+        }
+    }
+
+    /// Gets the tempo map which the tempo of the synthesizer follows.
+    pub fn get_tempo_map(&self) -> Option<&TempoMap> {
+        self.tempo_map.as_ref()
+    }
+
+    /// Sets the tempo map which the tempo of the synthesizer follows.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The tempo map of the playing MIDI file, or `None` to keep the tempo of the synthesizer as it is.
+    ///
+    /// # Remarks
+    ///
+    /// The tempo is multiplied by the speed, and is updated at each block,
+    /// so that the LFO rates in beats follow the tempo changes of the MIDI file.
+    /// See `Synthesizer::set_vibrato_lfo_override` for the LFO rates.
+    pub fn set_tempo_map(&mut self, value: Option<TempoMap>) {
+        self.tempo_map = value;
+    }
+
+    /// Gets a value that indicates whether the cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        match self.cancellation_token.as_ref() {
+            Some(token) => token.is_cancelled(),
+            None => false,
+        }
+    }
+
     /// Sets the playback speed.
     ///
     /// # Remarks
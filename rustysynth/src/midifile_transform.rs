@@ -0,0 +1,329 @@
+#![allow(dead_code)]
+
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::float_math::FloatMath;
+use crate::humanize_options::HumanizeOptions;
+use crate::midifile::Message;
+use crate::midifile::MidiFile;
+use crate::midifile::MidiTrack;
+use crate::synthesizer::Synthesizer;
+use crate::synthesizer_settings::SynthesizerSettings;
+use crate::tempo_map::TempoMap;
+
+// Creates the MIDI files derived from another one.
+// When the notes are moved, the note-off events follow their note-on events,
+// so that the lengths of the notes are kept.
+#[allow(unused)]
+#[non_exhaustive]
+pub(crate) struct MidiFileTransform {}
+
+impl MidiFileTransform {
+    pub(crate) fn quantize(midi_file: &MidiFile, grid: i32, strength: f64) -> MidiFile {
+        let grid = grid as f64;
+
+        MidiFileTransform::transform(midi_file, |tick, message| {
+            let target = FloatMath::round_f64(tick / grid) * grid;
+            (tick + strength * (target - tick), message.data2)
+        })
+    }
+
+    pub(crate) fn humanize(midi_file: &MidiFile, options: &HumanizeOptions) -> MidiFile {
+        MidiFileTransform::transform(midi_file, |tick, message| {
+            // The random numbers are derived from the note, so the tempo track merged into
+            // each track is changed in the same way everywhere.
+            let note = ((FloatMath::round_f64(tick) as i64 as u64) << 16)
+                | ((message.channel as u64) << 8)
+                | message.data1 as u64;
+            let timing = MidiFileTransform::get_random(options.seed, 2 * note);
+            let velocity = MidiFileTransform::get_random(options.seed, 2 * note + 1);
+
+            let change = FloatMath::round_f64(velocity * options.velocity as f64) as i32;
+            (
+                tick + timing * options.timing,
+                (message.data2 as i32 + change).clamp(1, 127) as u8,
+            )
+        })
+    }
+
+    // Gets a random number between -1 and 1 by the SplitMix64 hash.
+    fn get_random(seed: u64, value: u64) -> f64 {
+        let mut x = seed ^ value.wrapping_mul(0x9E3779B97F4A7C15);
+        x = (x ^ (x >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94D049BB133111EB);
+        x ^= x >> 31;
+        2.0 * (x >> 11) as f64 / (1_u64 << 53) as f64 - 1.0
+    }
+
+    // The function gives the new tick and velocity of each note-on event.
+    fn transform<F>(midi_file: &MidiFile, mut move_note: F) -> MidiFile
+    where
+        F: FnMut(f64, &Message) -> (f64, u8),
+    {
+        let tracks: Vec<MidiTrack> = midi_file
+            .tracks
+            .iter()
+            .map(|track| {
+                MidiFileTransform::transform_track(track, &midi_file.tempo_map, &mut move_note)
+            })
+            .collect();
+
+        MidiFileTransform::with_tracks(midi_file, tracks)
+    }
+
+    pub(crate) fn transpose(midi_file: &MidiFile, semitones: i32, skip_drums: bool) -> MidiFile {
+        // The percussion channels are followed in the same way as the synthesizer.
+        let settings = SynthesizerSettings::new(44100);
+        let switches = |tracks: &[&MidiTrack]| {
+            let mut switches: Vec<(f64, u8, bool)> = Vec::new();
+            if settings.enable_gs_rhythm_parts {
+                for track in tracks.iter() {
+                    for (message, time) in track.messages.iter().zip(track.times.iter()) {
+                        if message.get_message_type() == Message::PERCUSSION_CHANNEL {
+                            switches.push((*time, message.command, message.data1 != 0));
+                        }
+                    }
+                }
+            }
+            switches.sort_by(|x, y| x.0.total_cmp(&y.0));
+            switches
+        };
+
+        let all_tracks: Vec<&MidiTrack> = midi_file.tracks.iter().collect();
+        let all_switches = switches(&all_tracks);
+
+        let tracks = midi_file
+            .tracks
+            .iter()
+            .map(|track| {
+                // Each pattern of format 2 files starts from the initial state.
+                let pattern_switches;
+                let switches = if midi_file.format == 2 {
+                    pattern_switches = switches(&[track]);
+                    &pattern_switches
+                } else {
+                    &all_switches
+                };
+
+                MidiFileTransform::filter_track(track, |time, message| {
+                    if message.get_message_type() != Message::NORMAL
+                        || !matches!(message.command, 0x80 | 0x90 | 0xA0)
+                    {
+                        return Some(*message);
+                    }
+
+                    if skip_drums {
+                        let mut percussion = settings.percussion_channels
+                            [message.channel as usize % Synthesizer::CHANNEL_COUNT];
+                        for (_, channel, value) in switches.iter().take_while(|x| x.0 <= time) {
+                            if *channel == message.channel {
+                                percussion = *value;
+                            }
+                        }
+                        if percussion {
+                            return Some(*message);
+                        }
+                    }
+
+                    // The notes moved out of the range are removed.
+                    let key = message.data1 as i32 + semitones;
+                    if !(0..128).contains(&key) {
+                        return None;
+                    }
+
+                    let mut message = *message;
+                    message.data1 = key as u8;
+                    Some(message)
+                })
+            })
+            .collect();
+
+        MidiFileTransform::with_tracks(midi_file, tracks)
+    }
+
+    pub(crate) fn filter_channels(midi_file: &MidiFile, mask: u16) -> MidiFile {
+        let tracks = midi_file
+            .tracks
+            .iter()
+            .map(|track| {
+                MidiFileTransform::filter_track(track, |_, message| {
+                    if message.get_message_type() == Message::NORMAL
+                        && mask & (1 << (message.channel % 16)) == 0
+                    {
+                        None
+                    } else {
+                        Some(*message)
+                    }
+                })
+            })
+            .collect();
+
+        MidiFileTransform::with_tracks(midi_file, tracks)
+    }
+
+    pub(crate) fn merge(midi_file: &MidiFile, indices: &[usize]) -> MidiFile {
+        let mut indices = indices.to_vec();
+        indices.sort();
+        indices.dedup();
+
+        // The events merged from the other tracks are the same in all the tracks.
+        // They are taken from their own track if it is merged, or from the first track otherwise.
+        let tracks: Vec<MidiTrack> = indices
+            .iter()
+            .enumerate()
+            .map(|(i, index)| {
+                let track = &midi_file.tracks[*index];
+                MidiFileTransform::filter_merged(track, |origin| match origin {
+                    Some(origin) => i == 0 && !indices.contains(&origin),
+                    None => true,
+                })
+            })
+            .collect();
+
+        let tracks: Vec<&MidiTrack> = tracks.iter().collect();
+        let track = MidiTrack::merge(&tracks);
+
+        MidiFileTransform::with_tracks(midi_file, vec![track])
+    }
+
+    // Keeps the events whose origin satisfies the predicate.
+    // The result has no origin, since it is no longer a track of the MIDI file.
+    fn filter_merged<F>(track: &MidiTrack, mut predicate: F) -> MidiTrack
+    where
+        F: FnMut(Option<usize>) -> bool,
+    {
+        let (messages, times) = track
+            .messages
+            .iter()
+            .zip(track.times.iter())
+            .enumerate()
+            .filter(|(i, _)| predicate(track.get_origin(*i)))
+            .map(|(_, (message, time))| (*message, *time))
+            .unzip();
+
+        MidiTrack {
+            messages,
+            times,
+            origins: Vec::new(),
+        }
+    }
+
+    fn filter_track<F>(track: &MidiTrack, mut filter: F) -> MidiTrack
+    where
+        F: FnMut(f64, &Message) -> Option<Message>,
+    {
+        let mut messages = Vec::with_capacity(track.messages.len());
+        let mut times = Vec::with_capacity(track.times.len());
+        let mut origins = Vec::with_capacity(track.origins.len());
+
+        for (i, (message, time)) in track.messages.iter().zip(track.times.iter()).enumerate() {
+            if let Some(message) = filter(*time, message) {
+                messages.push(message);
+                times.push(*time);
+                if !track.origins.is_empty() {
+                    origins.push(track.origins[i]);
+                }
+            }
+        }
+
+        MidiTrack {
+            messages,
+            times,
+            origins,
+        }
+    }
+
+    fn with_tracks(midi_file: &MidiFile, tracks: Vec<MidiTrack>) -> MidiFile {
+        let length = tracks
+            .iter()
+            .fold(midi_file.length, |max, x| x.get_length().max(max));
+
+        MidiFile {
+            tracks,
+            format: midi_file.format,
+            length,
+            warnings: midi_file.warnings.clone(),
+            diagnostics: midi_file.diagnostics.clone(),
+            tempo_map: midi_file.tempo_map.clone(),
+            lyrics: midi_file.lyrics.clone(),
+        }
+    }
+
+    fn transform_track<F>(track: &MidiTrack, tempo_map: &TempoMap, move_note: &mut F) -> MidiTrack
+    where
+        F: FnMut(f64, &Message) -> (f64, u8),
+    {
+        // The movements in ticks of the sounding notes for each channel and key.
+        let channel_count = Synthesizer::CHANNEL_COUNT * track.get_port_count();
+        let mut movements: Vec<VecDeque<f64>> =
+            (0..128 * channel_count).map(|_| VecDeque::new()).collect();
+
+        let mut events: Vec<(f64, Message, Option<u16>)> = Vec::with_capacity(track.messages.len());
+        let mut end_time: f64 = 0.0;
+
+        for (i, (message, time)) in track.messages.iter().zip(track.times.iter()).enumerate() {
+            let mut message = *message;
+            let mut time = *time;
+
+            match message.get_message_type() {
+                Message::NORMAL if message.command == 0x80 || message.command == 0x90 => {
+                    // The parser does not check the data bytes, so the keys above 127 are masked.
+                    let channel = message.channel as usize % channel_count;
+                    let index = 128 * channel + (message.data1 as usize & 0x7F);
+                    let tick = tempo_map.seconds_to_ticks(time);
+
+                    let movement = if message.command == 0x90 && message.data2 > 0 {
+                        let (new_tick, velocity) = move_note(tick, &message);
+                        message.data2 = velocity;
+                        movements[index].push_back(new_tick - tick);
+                        Some(new_tick - tick)
+                    } else {
+                        movements[index].pop_front()
+                    };
+
+                    if let Some(movement) = movement {
+                        time = tempo_map.ticks_to_seconds((tick + movement).max(0.0));
+                    }
+                }
+                Message::END_OF_TRACK => {
+                    end_time = end_time.max(time);
+                    continue;
+                }
+                _ => (),
+            }
+
+            end_time = end_time.max(time);
+            events.push((time, message, track.origins.get(i).copied().flatten()));
+        }
+
+        // The sort is stable, so the events at the same time keep their order.
+        events.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut times = Vec::with_capacity(events.len() + 1);
+        let mut messages = Vec::with_capacity(events.len() + 1);
+        let mut origins = Vec::new();
+        if !track.origins.is_empty() {
+            origins.reserve(events.len() + 1);
+        }
+        for (time, message, origin) in events {
+            times.push(time);
+            messages.push(message);
+            if !track.origins.is_empty() {
+                origins.push(origin);
+            }
+        }
+        messages.push(Message::end_of_track());
+        times.push(end_time);
+        if !track.origins.is_empty() {
+            origins.push(None);
+        }
+
+        MidiTrack {
+            messages,
+            times,
+            origins,
+        }
+    }
+}
@@ -0,0 +1,72 @@
+#![allow(dead_code)]
+
+use alloc::string::ToString;
+use core::fmt;
+
+use crate::diagnostic::DiagnosticCode;
+use crate::diagnostic::DiagnosticLocation;
+use crate::diagnostic::DiagnosticSeverity;
+use crate::diagnostics::Diagnostics;
+use crate::four_cc::FourCC;
+
+/// Represents a problem which was recovered from when loading a MIDI file in the lenient mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MidiFileWarning {
+    /// The track with the specified index has no EOT event.
+    MissingEndOfTrack(usize),
+    /// The track with the specified index is shorter than its declared size.
+    TruncatedTrack(usize),
+    /// The file contains fewer tracks than declared in the header.
+    MissingTracks { expected: usize, actual: usize },
+    /// A chunk which is not a track was skipped.
+    UnknownChunk(FourCC),
+}
+
+impl MidiFileWarning {
+    pub(crate) fn report(&self, diagnostics: &mut Diagnostics) {
+        let (code, location) = match self {
+            MidiFileWarning::MissingEndOfTrack(track) => (
+                DiagnosticCode::MidiMissingEndOfTrack,
+                DiagnosticLocation::Track(*track),
+            ),
+            MidiFileWarning::TruncatedTrack(track) => (
+                DiagnosticCode::MidiTruncatedTrack,
+                DiagnosticLocation::Track(*track),
+            ),
+            MidiFileWarning::MissingTracks { .. } => {
+                (DiagnosticCode::MidiMissingTracks, DiagnosticLocation::None)
+            }
+            MidiFileWarning::UnknownChunk(id) => (
+                DiagnosticCode::MidiUnknownChunk,
+                DiagnosticLocation::Chunk(*id),
+            ),
+        };
+
+        diagnostics.push(
+            code,
+            DiagnosticSeverity::Warning,
+            location,
+            self.to_string(),
+        );
+    }
+}
+
+impl fmt::Display for MidiFileWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MidiFileWarning::MissingEndOfTrack(track) => {
+                write!(f, "the track {} has no end of track", track)
+            }
+            MidiFileWarning::TruncatedTrack(track) => {
+                write!(f, "the track {} is truncated", track)
+            }
+            MidiFileWarning::MissingTracks { expected, actual } => write!(
+                f,
+                "the file should contain {} tracks, but only {} were found",
+                expected, actual
+            ),
+            MidiFileWarning::UnknownChunk(id) => write!(f, "the '{}' chunk was skipped", id),
+        }
+    }
+}
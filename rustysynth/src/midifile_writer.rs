@@ -0,0 +1,241 @@
+#![allow(dead_code)]
+
+use alloc::vec::Vec;
+
+use crate::float_math::FloatMath;
+use crate::io;
+use crate::io::Write;
+
+use crate::binary_writer::BinaryWriter;
+use crate::four_cc::FourCC;
+use crate::midifile::Message;
+use crate::midifile::MidiFile;
+use crate::midifile::MidiTrack;
+use crate::MidiFileError;
+use crate::MidiFileLoopType;
+
+// The exported file uses a fixed tempo, and the times in seconds are converted to ticks.
+// With 960 ticks per quarter note at 120 BPM, a tick is about 0.5 ms long.
+
+#[allow(unused)]
+#[non_exhaustive]
+pub(crate) struct MidiFileWriter {}
+
+impl MidiFileWriter {
+    const RESOLUTION: i16 = 960;
+    const TEMPO: i32 = 500000;
+    const TICKS_PER_SECOND: f64 =
+        MidiFileWriter::RESOLUTION as f64 * 1_000_000_f64 / MidiFileWriter::TEMPO as f64;
+
+    pub(crate) fn write<W: Write>(
+        midi_file: &MidiFile,
+        writer: &mut W,
+        loop_count: usize,
+    ) -> Result<(), MidiFileError> {
+        let (loop_start, loop_end) = MidiFileWriter::get_loop_range(midi_file);
+
+        let track_count = midi_file.tracks.len() + 1;
+        if track_count > i16::MAX as usize {
+            return Err(MidiFileError::IoError(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "too many tracks to be written to a MIDI file",
+            )));
+        }
+
+        BinaryWriter::write_four_cc(writer, &FourCC::from_bytes(*b"MThd"))?;
+        BinaryWriter::write_i32_big_endian(writer, 6)?;
+        BinaryWriter::write_i16_big_endian(writer, 1)?;
+        BinaryWriter::write_i16_big_endian(writer, track_count as i16)?;
+        BinaryWriter::write_i16_big_endian(writer, MidiFileWriter::RESOLUTION)?;
+
+        // The first track only contains the tempo.
+        let mut data: Vec<u8> = Vec::new();
+        BinaryWriter::write_i32_variable_length(&mut data, 0)?;
+        data.extend_from_slice(&[0xFF, 0x51, 0x03]);
+        data.extend_from_slice(&MidiFileWriter::TEMPO.to_be_bytes()[1..]);
+        MidiFileWriter::write_end_of_track(&mut data, 0)?;
+        MidiFileWriter::write_track_chunk(writer, &data)?;
+
+        for track in midi_file.tracks.iter() {
+            let events = MidiFileWriter::unroll(track, loop_start, loop_end, loop_count);
+            let end_time = if loop_count > 1 {
+                midi_file.length + (loop_count - 1) as f64 * (loop_end - loop_start)
+            } else {
+                track.get_length()
+            };
+
+            let mut data: Vec<u8> = Vec::new();
+            let mut previous_tick: i32 = 0;
+            let mut port: u8 = 0;
+            for (time, message) in events.iter() {
+                let tick = MidiFileWriter::to_tick(*time);
+                BinaryWriter::write_i32_variable_length(&mut data, tick - previous_tick)?;
+
+                // The port meta event is written only when the port changes.
+                if message.get_port() != port {
+                    port = message.get_port();
+                    data.extend_from_slice(&[0xFF, 0x21, 0x01, port]);
+                    BinaryWriter::write_i32_variable_length(&mut data, 0)?;
+                }
+
+                MidiFileWriter::write_message(&mut data, message)?;
+                previous_tick = tick;
+            }
+
+            // The track ends where the last pass ends, so that the release of the last notes is not lost.
+            let end_tick = i32::max(previous_tick, MidiFileWriter::to_tick(end_time));
+            MidiFileWriter::write_end_of_track(&mut data, end_tick - previous_tick)?;
+            MidiFileWriter::write_track_chunk(writer, &data)?;
+        }
+
+        Ok(())
+    }
+
+    // The loop start and end points are shared by all the tracks.
+    // If the MIDI file has no explicit loop end, the end of the file will be used.
+    fn get_loop_range(midi_file: &MidiFile) -> (f64, f64) {
+        let mut loop_start = f64::MAX;
+        let mut loop_end = f64::MAX;
+
+        for track in midi_file.tracks.iter() {
+            for (message, time) in track.messages.iter().zip(track.times.iter()) {
+                match message.get_message_type() {
+                    Message::LOOP_START => loop_start = f64::min(loop_start, *time),
+                    Message::LOOP_END => loop_end = f64::min(loop_end, *time),
+                    _ => (),
+                }
+            }
+        }
+
+        if loop_start == f64::MAX {
+            loop_start = 0.0;
+        }
+        if loop_end == f64::MAX {
+            loop_end = midi_file.length;
+        }
+
+        (loop_start, f64::max(loop_start, loop_end))
+    }
+
+    fn unroll(
+        track: &MidiTrack,
+        loop_start: f64,
+        loop_end: f64,
+        loop_count: usize,
+    ) -> Vec<(f64, Message)> {
+        let mut events: Vec<(f64, Message)> = Vec::new();
+
+        if loop_count <= 1 || loop_end <= loop_start {
+            for (message, time) in track.messages.iter().zip(track.times.iter()) {
+                if MidiFileWriter::is_written(message) {
+                    events.push((*time, *message));
+                }
+            }
+            return events;
+        }
+
+        let loop_length = loop_end - loop_start;
+        let port_count = track.get_port_count();
+
+        for pass in 0..loop_count {
+            let offset = pass as f64 * loop_length;
+
+            for (message, time) in track.messages.iter().zip(track.times.iter()) {
+                if !MidiFileWriter::is_written(message) {
+                    continue;
+                }
+
+                // The first pass also plays the intro before the loop start point,
+                // and the last pass also plays the rest after the loop end point.
+                let in_range = (pass == 0 || loop_start <= *time)
+                    && (pass == loop_count - 1 || *time < loop_end);
+
+                if in_range {
+                    events.push((*time + offset, *message));
+                }
+            }
+
+            // The sequencer stops all the notes when jumping to the loop start point.
+            if pass < loop_count - 1 {
+                for port in 0..port_count as u8 {
+                    for channel in 0..16 {
+                        let message = Message::common2(
+                            0xB0 | channel,
+                            0x7B,
+                            0,
+                            MidiFileLoopType::LoopPoint(0),
+                        );
+                        events.push((loop_end + offset, message.with_port(port)));
+                    }
+                }
+            }
+        }
+
+        events
+    }
+
+    fn is_written(message: &Message) -> bool {
+        let message_type = message.get_message_type();
+        message_type == Message::NORMAL || message_type == Message::PERCUSSION_CHANNEL
+    }
+
+    fn to_tick(time: f64) -> i32 {
+        FloatMath::round_f64(MidiFileWriter::TICKS_PER_SECOND * time) as i32
+    }
+
+    fn write_message<W: Write>(writer: &mut W, message: &Message) -> Result<(), MidiFileError> {
+        if message.get_message_type() == Message::PERCUSSION_CHANNEL {
+            return MidiFileWriter::write_rhythm_part(writer, message.command % 16, message.data1);
+        }
+
+        // The port is written by the meta event.
+        BinaryWriter::write_u8(writer, message.command | (message.channel % 16))?;
+        BinaryWriter::write_u8(writer, message.data1)?;
+        if !(message.command == 0xC0 || message.command == 0xD0) {
+            BinaryWriter::write_u8(writer, message.data2)?;
+        }
+
+        Ok(())
+    }
+
+    // The GS "use for rhythm part" SysEx, which is the inverse of the one recognized by the parser.
+    fn write_rhythm_part<W: Write>(
+        writer: &mut W,
+        channel: u8,
+        value: u8,
+    ) -> Result<(), MidiFileError> {
+        let part = match channel {
+            9 => 0,
+            0..=8 => channel + 1,
+            _ => channel,
+        };
+        let body: [u8; 4] = [0x40, 0x10 | part, 0x15, value];
+        let sum = body.iter().map(|x| *x as u32).sum::<u32>();
+        let checksum = ((128 - sum % 128) % 128) as u8;
+
+        BinaryWriter::write_u8(writer, 0xF0)?;
+        BinaryWriter::write_i32_variable_length(writer, 10)?;
+        writer.write_all(&[0x41, 0x10, 0x42, 0x12])?;
+        writer.write_all(&body)?;
+        writer.write_all(&[checksum, 0xF7])?;
+
+        Ok(())
+    }
+
+    fn write_end_of_track<W: Write>(writer: &mut W, delta: i32) -> Result<(), MidiFileError> {
+        BinaryWriter::write_i32_variable_length(writer, delta)?;
+        BinaryWriter::write_u8(writer, 0xFF)?;
+        BinaryWriter::write_u8(writer, 0x2F)?;
+        BinaryWriter::write_u8(writer, 0x00)?;
+
+        Ok(())
+    }
+
+    fn write_track_chunk<W: Write>(writer: &mut W, data: &[u8]) -> Result<(), MidiFileError> {
+        BinaryWriter::write_four_cc(writer, &FourCC::from_bytes(*b"MTrk"))?;
+        BinaryWriter::write_i32_big_endian(writer, data.len() as i32)?;
+        writer.write_all(data)?;
+
+        Ok(())
+    }
+}
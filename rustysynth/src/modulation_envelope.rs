@@ -1,9 +1,11 @@
 #![allow(dead_code)]
 
+use crate::envelope_phase::EnvelopePhase;
 use crate::envelope_stage::EnvelopeStage;
 use crate::soundfont_math::SoundFontMath;
 use crate::synthesizer_settings::SynthesizerSettings;
 
+#[derive(Clone)]
 #[non_exhaustive]
 pub(crate) struct ModulationEnvelope {
     sample_rate: i32,
@@ -134,4 +136,15 @@ impl ModulationEnvelope {
     pub(crate) fn get_value(&self) -> f32 {
         self.value
     }
+
+    pub(crate) fn get_phase(&self) -> EnvelopePhase {
+        match self.stage {
+            EnvelopeStage::DELAY => EnvelopePhase::Delay,
+            EnvelopeStage::ATTACK => EnvelopePhase::Attack,
+            EnvelopeStage::HOLD => EnvelopePhase::Hold,
+            EnvelopeStage::DECAY if self.value <= self.sustain_level => EnvelopePhase::Sustain,
+            EnvelopeStage::DECAY => EnvelopePhase::Decay,
+            _ => EnvelopePhase::Release,
+        }
+    }
 }
@@ -0,0 +1,38 @@
+/// Specifies how the output of `ThreadedRender` is normalized after mixing.
+///
+/// # Remarks
+///
+/// The gain is computed from the whole mix and applied in a second pass,
+/// so the level changes uniformly without any gap or pumping.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Normalization {
+    /// The output is not normalized.
+    None,
+
+    /// The output is scaled so that the sample peak reaches the specified level in dBFS, such as -1.
+    Peak(f64),
+
+    /// The output is scaled so that the integrated loudness reaches the target,
+    /// but the sample peak does not exceed the maximum.
+    Loudness {
+        /// The target integrated loudness in LUFS, such as -14.
+        target: f64,
+        /// The maximum sample peak in dBFS, such as -1.
+        max_peak: f64,
+    },
+}
+
+impl Normalization {
+    pub(crate) fn check(&self) {
+        match *self {
+            Normalization::Peak(peak) if peak > 0.0 => {
+                panic!("The peak level must be a non-positive value.")
+            }
+            Normalization::Loudness { max_peak, .. } if max_peak > 0.0 => {
+                panic!("The maximum peak level must be a non-positive value.")
+            }
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,248 @@
+#![allow(dead_code)]
+
+use crate::opl_operator::OplOperator;
+use crate::opl_patch::OplPatch;
+
+// The operator in the order of the multiplier, the total level, the key scale level,
+// the attack, decay, sustain and release, the waveform, and the flags,
+// where the flags are the sustain (1), the key scale rate (2), the vibrato (4) and the tremolo (8).
+type OperatorData = [u8; 9];
+
+// The modulator, the carrier, and the feedback with the connection in the fourth bit.
+type PatchData = (OperatorData, OperatorData, u8);
+
+// The GM sound set made of the 2-operator patches in the style of the OPL drivers for the DOS games.
+// The patches are designed for each of the GM families, so they are not a copy of any particular driver.
+#[non_exhaustive]
+pub(crate) struct OplGmBank {}
+
+impl OplGmBank {
+    const AM: u8 = 8;
+
+    #[rustfmt::skip]
+    const MELODIC: [PatchData; 128] = [
+        // Piano
+        ([1, 30, 1, 15, 3, 6, 6, 0, 0], [1, 0, 0, 15, 3, 15, 5, 0, 0], 6),
+        ([1, 24, 1, 15, 3, 6, 6, 0, 0], [1, 0, 0, 15, 3, 15, 5, 0, 0], 6),
+        ([1, 26, 1, 15, 4, 5, 6, 0, 0], [1, 0, 0, 15, 3, 15, 5, 0, 0], 5),
+        ([1, 28, 1, 15, 3, 6, 6, 0, 4], [1, 0, 0, 15, 3, 15, 5, 0, 4], 6),
+        ([1, 36, 1, 15, 5, 8, 6, 0, 0], [1, 0, 0, 15, 2, 15, 6, 0, 0], 3),
+        ([1, 28, 0, 15, 4, 8, 6, 1, 0], [1, 0, 0, 15, 2, 15, 6, 0, 0], 4),
+        ([2, 20, 2, 15, 4, 4, 6, 0, 0], [1, 0, 0, 15, 4, 15, 7, 1, 0], 7),
+        ([3, 22, 1, 15, 5, 4, 7, 0, 0], [1, 0, 0, 15, 4, 15, 8, 1, 0], 6),
+        // Chromatic percussion
+        ([4, 32, 0, 15, 4, 6, 5, 0, 0], [1, 0, 0, 15, 4, 15, 5, 0, 0], 2),
+        ([7, 30, 0, 15, 5, 8, 5, 0, 0], [2, 0, 0, 15, 4, 15, 5, 0, 0], 0),
+        ([6, 30, 0, 15, 5, 8, 5, 0, 0], [2, 0, 0, 15, 4, 15, 5, 0, 0], 0),
+        ([4, 36, 0, 15, 3, 6, 5, 0, 8], [1, 0, 0, 15, 3, 15, 5, 0, 8], 0),
+        ([4, 30, 0, 15, 7, 15, 6, 0, 0], [1, 0, 0, 15, 5, 15, 6, 0, 0], 0),
+        ([3, 28, 0, 15, 7, 15, 6, 0, 0], [1, 0, 0, 15, 6, 15, 6, 0, 0], 0),
+        ([7, 28, 0, 15, 3, 6, 4, 0, 0], [2, 0, 0, 15, 2, 15, 4, 0, 0], 0),
+        ([1, 26, 0, 15, 4, 6, 5, 0, 0], [1, 0, 0, 15, 4, 15, 5, 1, 4], 5),
+        // Organ
+        ([1, 4, 0, 15, 0, 0, 7, 0, 1], [2, 6, 0, 15, 0, 0, 7, 0, 1], OplGmBank::AM),
+        ([1, 4, 0, 15, 0, 0, 7, 0, 1], [3, 10, 0, 15, 5, 8, 7, 0, 1], OplGmBank::AM),
+        ([1, 6, 0, 15, 0, 0, 7, 1, 1], [2, 8, 0, 15, 0, 0, 7, 0, 1], 4 | OplGmBank::AM),
+        ([0, 6, 0, 13, 0, 0, 6, 0, 1], [1, 4, 0, 13, 0, 0, 6, 0, 1], OplGmBank::AM),
+        ([1, 24, 0, 12, 0, 0, 6, 0, 1], [1, 0, 0, 12, 0, 0, 6, 0, 1], 6),
+        ([1, 22, 0, 11, 0, 0, 7, 0, 5], [1, 0, 0, 11, 0, 0, 7, 0, 5], 6),
+        ([1, 20, 0, 12, 0, 0, 7, 0, 5], [1, 0, 0, 12, 0, 0, 7, 1, 5], 5),
+        ([2, 24, 0, 11, 0, 0, 7, 0, 5], [1, 0, 0, 11, 0, 0, 7, 0, 5], 6),
+        // Guitar
+        ([1, 30, 1, 15, 5, 8, 6, 0, 0], [1, 0, 0, 15, 3, 15, 6, 0, 0], 4),
+        ([1, 26, 1, 15, 4, 6, 6, 0, 0], [1, 0, 0, 15, 3, 15, 6, 0, 0], 6),
+        ([1, 34, 1, 15, 4, 6, 6, 0, 0], [1, 0, 0, 15, 3, 15, 6, 0, 0], 2),
+        ([2, 32, 1, 15, 4, 6, 6, 0, 0], [1, 0, 0, 15, 3, 15, 6, 0, 0], 3),
+        ([1, 26, 1, 15, 7, 15, 7, 0, 0], [1, 0, 0, 15, 7, 15, 7, 0, 0], 5),
+        ([1, 16, 0, 15, 1, 2, 6, 0, 1], [1, 0, 0, 15, 1, 4, 6, 0, 1], 7),
+        ([1, 10, 0, 15, 1, 2, 6, 0, 1], [1, 0, 0, 15, 1, 4, 6, 6, 1], 7),
+        ([2, 28, 0, 15, 4, 8, 6, 0, 0], [2, 0, 0, 15, 4, 15, 6, 0, 0], 3),
+        // Bass
+        ([1, 28, 0, 15, 5, 8, 6, 0, 0], [1, 0, 0, 15, 3, 15, 6, 0, 0], 4),
+        ([1, 24, 0, 15, 4, 6, 6, 0, 0], [1, 0, 0, 15, 3, 15, 6, 0, 0], 4),
+        ([1, 20, 0, 15, 5, 6, 6, 0, 0], [1, 0, 0, 15, 3, 15, 6, 0, 0], 5),
+        ([1, 30, 0, 13, 3, 4, 6, 0, 0], [1, 0, 0, 13, 2, 15, 6, 0, 4], 3),
+        ([1, 14, 0, 15, 6, 8, 6, 0, 0], [1, 0, 0, 15, 3, 15, 6, 0, 0], 6),
+        ([2, 18, 0, 15, 6, 8, 6, 0, 0], [1, 0, 0, 15, 3, 15, 6, 0, 0], 6),
+        ([1, 16, 0, 15, 3, 4, 6, 0, 1], [1, 0, 0, 15, 2, 15, 6, 0, 0], 7),
+        ([1, 20, 0, 15, 4, 6, 6, 0, 1], [1, 0, 0, 15, 2, 15, 6, 1, 0], 6),
+        // Strings
+        ([1, 24, 0, 10, 0, 0, 6, 0, 5], [1, 0, 0, 9, 0, 0, 6, 0, 5], 6),
+        ([1, 26, 0, 9, 0, 0, 6, 0, 5], [1, 0, 0, 9, 0, 0, 6, 0, 5], 5),
+        ([1, 24, 0, 9, 0, 0, 6, 0, 5], [1, 0, 0, 9, 0, 0, 6, 0, 5], 6),
+        ([1, 26, 0, 9, 0, 0, 6, 0, 1], [1, 0, 0, 9, 0, 0, 6, 0, 1], 5),
+        ([1, 26, 0, 10, 0, 0, 6, 0, 9], [1, 0, 0, 10, 0, 0, 6, 0, 9], 5),
+        ([1, 28, 0, 15, 6, 15, 6, 0, 0], [1, 0, 0, 15, 5, 15, 6, 0, 0], 4),
+        ([2, 32, 0, 15, 4, 8, 5, 0, 0], [1, 0, 0, 15, 3, 15, 5, 0, 0], 3),
+        ([1, 22, 0, 15, 4, 6, 5, 0, 0], [1, 0, 0, 15, 3, 15, 5, 0, 0], 6),
+        // Ensemble
+        ([1, 28, 0, 8, 0, 0, 6, 0, 5], [1, 0, 0, 8, 0, 0, 6, 0, 5], 5),
+        ([1, 28, 0, 6, 0, 0, 6, 0, 5], [1, 0, 0, 6, 0, 0, 6, 0, 5], 5),
+        ([1, 22, 0, 9, 0, 0, 6, 0, 5], [1, 0, 0, 8, 0, 0, 6, 0, 5], 7),
+        ([1, 26, 0, 7, 0, 0, 6, 0, 5], [1, 0, 0, 7, 0, 0, 6, 1, 5], 6),
+        ([1, 36, 0, 8, 0, 0, 6, 0, 5], [1, 0, 0, 8, 0, 0, 6, 0, 5], 3),
+        ([1, 44, 0, 8, 0, 0, 6, 0, 5], [1, 0, 0, 8, 0, 0, 6, 0, 5], 1),
+        ([2, 36, 0, 9, 0, 0, 6, 0, 5], [1, 0, 0, 9, 0, 0, 6, 0, 5], 2),
+        ([1, 10, 0, 15, 3, 6, 5, 0, 0], [1, 0, 0, 15, 3, 15, 5, 0, 0], 7),
+        // Brass
+        ([1, 20, 1, 12, 2, 3, 7, 0, 5], [1, 0, 0, 12, 1, 1, 7, 0, 5], 6),
+        ([1, 22, 1, 11, 2, 3, 7, 0, 5], [1, 0, 0, 11, 1, 1, 7, 0, 5], 6),
+        ([1, 24, 1, 11, 2, 3, 7, 0, 1], [1, 0, 0, 11, 1, 1, 7, 0, 1], 5),
+        ([1, 26, 1, 12, 2, 4, 7, 0, 5], [1, 0, 0, 12, 1, 1, 7, 1, 5], 7),
+        ([1, 30, 1, 10, 2, 3, 7, 0, 5], [1, 0, 0, 10, 1, 1, 7, 0, 5], 4),
+        ([1, 18, 1, 12, 2, 3, 7, 0, 5], [1, 0, 0, 12, 1, 1, 7, 0, 5], 6),
+        ([1, 16, 0, 13, 3, 4, 7, 0, 1], [1, 0, 0, 13, 1, 1, 7, 0, 1], 7),
+        ([1, 20, 0, 11, 2, 4, 7, 0, 1], [1, 0, 0, 11, 1, 1, 7, 0, 1], 6),
+        // Reed
+        ([1, 22, 0, 12, 0, 0, 7, 0, 5], [1, 0, 0, 12, 0, 0, 7, 0, 5], 7),
+        ([1, 20, 0, 12, 0, 0, 7, 0, 5], [1, 0, 0, 12, 0, 0, 7, 0, 5], 7),
+        ([1, 18, 0, 12, 0, 0, 7, 0, 5], [1, 0, 0, 12, 0, 0, 7, 0, 5], 7),
+        ([1, 18, 0, 12, 0, 0, 7, 0, 1], [1, 0, 0, 12, 0, 0, 7, 0, 1], 7),
+        ([2, 28, 0, 12, 0, 0, 7, 0, 5], [1, 0, 0, 12, 0, 0, 7, 0, 5], 5),
+        ([2, 30, 0, 11, 0, 0, 7, 0, 5], [1, 0, 0, 11, 0, 0, 7, 0, 5], 4),
+        ([1, 24, 0, 11, 0, 0, 7, 0, 1], [1, 0, 0, 11, 0, 0, 7, 1, 1], 5),
+        ([2, 24, 0, 12, 0, 0, 7, 0, 5], [1, 0, 0, 12, 0, 0, 7, 0, 5], 3),
+        // Pipe
+        ([1, 44, 0, 11, 0, 0, 7, 0, 5], [2, 0, 0, 11, 0, 0, 7, 0, 5], 2),
+        ([1, 44, 0, 10, 0, 0, 7, 0, 5], [1, 0, 0, 10, 0, 0, 7, 0, 5], 2),
+        ([1, 40, 0, 11, 0, 0, 7, 0, 1], [1, 0, 0, 11, 0, 0, 7, 0, 1], 1),
+        ([1, 46, 0, 10, 0, 0, 7, 0, 5], [1, 0, 0, 10, 0, 0, 7, 0, 5], 4),
+        ([1, 50, 0, 9, 0, 0, 7, 0, 5], [1, 0, 0, 9, 0, 0, 7, 0, 5], 5),
+        ([1, 40, 0, 10, 0, 0, 7, 0, 5], [1, 0, 0, 10, 0, 0, 7, 0, 5], 6),
+        ([1, 56, 0, 11, 0, 0, 7, 0, 5], [1, 0, 0, 11, 0, 0, 7, 0, 5], 0),
+        ([1, 52, 0, 11, 0, 0, 7, 0, 1], [1, 0, 0, 11, 0, 0, 7, 0, 1], 0),
+        // Synth lead
+        ([1, 63, 0, 15, 0, 0, 7, 0, 1], [1, 0, 0, 15, 0, 0, 7, 6, 1], 0),
+        ([1, 0, 0, 15, 0, 0, 7, 0, 1], [1, 63, 0, 15, 0, 0, 7, 0, 1], 6 | OplGmBank::AM),
+        ([1, 4, 0, 14, 0, 0, 7, 0, 5], [2, 10, 0, 14, 0, 0, 7, 0, 5], 3 | OplGmBank::AM),
+        ([2, 22, 0, 15, 3, 6, 7, 0, 1], [1, 0, 0, 14, 0, 0, 7, 0, 1], 5),
+        ([1, 12, 0, 15, 1, 3, 7, 0, 1], [1, 0, 0, 15, 0, 0, 7, 1, 1], 7),
+        ([1, 34, 0, 12, 0, 0, 7, 0, 5], [1, 0, 0, 12, 0, 0, 7, 0, 5], 3),
+        ([3, 6, 0, 15, 0, 0, 7, 0, 1], [2, 6, 0, 15, 0, 0, 7, 0, 1], 6 | OplGmBank::AM),
+        ([1, 16, 0, 15, 1, 2, 7, 0, 1], [1, 0, 0, 15, 0, 0, 7, 0, 1], 7),
+        // Synth pad
+        ([2, 30, 0, 10, 2, 4, 6, 0, 5], [1, 0, 0, 9, 0, 0, 6, 0, 5], 3),
+        ([1, 34, 0, 7, 0, 0, 5, 0, 5], [1, 0, 0, 7, 0, 0, 5, 0, 5], 4),
+        ([1, 22, 0, 12, 2, 4, 6, 0, 1], [1, 0, 0, 12, 0, 0, 6, 0, 1], 6),
+        ([1, 38, 0, 7, 0, 0, 5, 0, 5], [1, 0, 0, 7, 0, 0, 5, 0, 5], 2),
+        ([2, 34, 0, 6, 0, 0, 5, 0, 5], [1, 0, 0, 6, 0, 0, 5, 0, 5], 4),
+        ([7, 28, 0, 10, 0, 0, 5, 0, 1], [2, 0, 0, 10, 0, 0, 5, 0, 1], 2),
+        ([1, 40, 0, 6, 0, 0, 4, 0, 13], [1, 0, 0, 6, 0, 0, 4, 0, 13], 3),
+        ([1, 24, 0, 5, 0, 0, 5, 0, 5], [1, 0, 0, 8, 0, 0, 5, 0, 5], 6),
+        // Synth effects
+        ([5, 30, 0, 15, 4, 8, 5, 0, 0], [1, 0, 0, 15, 3, 15, 5, 0, 0], 3),
+        ([1, 32, 0, 6, 0, 0, 4, 0, 5], [1, 0, 0, 6, 0, 0, 4, 0, 5], 5),
+        ([9, 30, 0, 15, 3, 6, 4, 0, 0], [2, 0, 0, 15, 2, 15, 4, 0, 0], 1),
+        ([2, 28, 0, 14, 2, 4, 5, 0, 5], [1, 0, 0, 14, 1, 2, 5, 0, 5], 4),
+        ([4, 26, 0, 13, 2, 4, 5, 0, 5], [2, 0, 0, 13, 1, 2, 5, 0, 5], 4),
+        ([1, 30, 0, 4, 0, 0, 5, 0, 13], [1, 0, 0, 5, 0, 0, 5, 0, 13], 6),
+        ([3, 32, 0, 12, 2, 4, 4, 0, 5], [1, 0, 0, 12, 1, 2, 4, 0, 5], 3),
+        ([11, 26, 0, 10, 0, 0, 5, 0, 9], [1, 0, 0, 10, 0, 0, 5, 0, 9], 5),
+        // Ethnic
+        ([3, 22, 0, 15, 3, 6, 6, 0, 0], [1, 0, 0, 15, 2, 15, 6, 1, 0], 6),
+        ([2, 24, 0, 15, 5, 8, 6, 0, 0], [1, 0, 0, 15, 4, 15, 6, 0, 0], 5),
+        ([3, 24, 0, 15, 5, 8, 6, 0, 0], [1, 0, 0, 15, 5, 15, 6, 0, 0], 5),
+        ([2, 30, 0, 15, 4, 8, 5, 0, 0], [1, 0, 0, 15, 3, 15, 5, 0, 0], 4),
+        ([5, 32, 0, 15, 5, 10, 6, 0, 0], [1, 0, 0, 15, 4, 15, 6, 0, 0], 0),
+        ([1, 16, 0, 13, 0, 0, 7, 0, 1], [1, 0, 0, 13, 0, 0, 7, 0, 1], 7),
+        ([1, 22, 0, 12, 0, 0, 6, 0, 5], [1, 0, 0, 11, 0, 0, 6, 0, 5], 6),
+        ([2, 22, 0, 12, 0, 0, 7, 0, 5], [1, 0, 0, 12, 0, 0, 7, 0, 5], 6),
+        // Percussive
+        ([7, 28, 0, 15, 4, 8, 5, 0, 0], [3, 0, 0, 15, 4, 15, 5, 0, 0], 0),
+        ([5, 26, 0, 15, 5, 10, 6, 0, 0], [2, 0, 0, 15, 5, 15, 6, 0, 0], 0),
+        ([3, 28, 0, 15, 4, 8, 5, 0, 0], [1, 0, 0, 15, 4, 15, 5, 0, 0], 1),
+        ([4, 24, 0, 15, 8, 15, 8, 0, 0], [2, 0, 0, 15, 8, 15, 8, 0, 0], 0),
+        ([1, 20, 0, 15, 5, 8, 6, 0, 0], [1, 0, 0, 15, 4, 15, 6, 0, 0], 7),
+        ([1, 26, 0, 15, 5, 8, 6, 0, 0], [1, 0, 0, 15, 4, 15, 6, 0, 0], 5),
+        ([1, 30, 0, 15, 5, 8, 6, 0, 0], [1, 0, 0, 15, 4, 15, 6, 0, 0], 3),
+        ([15, 0, 0, 4, 0, 0, 9, 0, 1], [1, 0, 0, 4, 0, 0, 9, 0, 1], 7),
+        // Sound effects
+        ([15, 16, 0, 15, 8, 15, 8, 0, 0], [1, 6, 0, 15, 8, 15, 8, 0, 0], 7),
+        ([15, 20, 0, 11, 0, 0, 8, 0, 1], [1, 8, 0, 11, 0, 0, 8, 0, 1], 7),
+        ([15, 8, 0, 3, 0, 0, 4, 0, 9], [1, 4, 0, 3, 0, 0, 4, 0, 9], 7),
+        ([4, 24, 0, 13, 0, 0, 8, 0, 5], [4, 6, 0, 13, 0, 0, 8, 0, 5], 3),
+        ([5, 20, 0, 15, 0, 0, 9, 0, 1], [4, 4, 0, 15, 0, 0, 9, 6, 1], 0),
+        ([15, 10, 0, 6, 0, 0, 5, 0, 9], [1, 4, 0, 6, 0, 0, 5, 0, 9], 7),
+        ([15, 6, 0, 5, 0, 0, 4, 0, 9], [1, 6, 0, 5, 0, 0, 4, 0, 9], 7),
+        ([15, 0, 0, 15, 6, 15, 6, 0, 0], [1, 0, 0, 15, 5, 15, 6, 0, 0], 7),
+    ];
+
+    #[rustfmt::skip]
+    const KICK: PatchData = ([1, 24, 0, 15, 6, 15, 6, 0, 0], [1, 0, 0, 15, 5, 15, 6, 0, 0], 5);
+    #[rustfmt::skip]
+    const SNARE: PatchData = ([15, 0, 0, 15, 6, 15, 7, 0, 0], [2, 0, 0, 15, 6, 15, 7, 0, 0], 7);
+    #[rustfmt::skip]
+    const TOM: PatchData = ([1, 28, 0, 15, 5, 15, 6, 0, 0], [1, 0, 0, 15, 4, 15, 6, 0, 0], 4);
+    #[rustfmt::skip]
+    const CLOSED_HI_HAT: PatchData = ([15, 0, 0, 15, 8, 15, 8, 0, 0], [10, 4, 0, 15, 8, 15, 8, 0, 0], 7);
+    #[rustfmt::skip]
+    const OPEN_HI_HAT: PatchData = ([15, 0, 0, 15, 5, 15, 5, 0, 0], [10, 4, 0, 15, 5, 15, 5, 0, 0], 7);
+    #[rustfmt::skip]
+    const CYMBAL: PatchData = ([15, 0, 0, 15, 3, 15, 4, 0, 0], [10, 2, 0, 15, 3, 15, 4, 0, 0], 7);
+    #[rustfmt::skip]
+    const RIDE: PatchData = ([7, 10, 0, 15, 4, 15, 4, 0, 0], [9, 2, 0, 15, 3, 15, 4, 0, 0], 6);
+    #[rustfmt::skip]
+    const BELL: PatchData = ([5, 22, 0, 15, 5, 15, 5, 0, 0], [2, 2, 0, 15, 5, 15, 5, 1, 0], 0);
+    #[rustfmt::skip]
+    const WOOD: PatchData = ([4, 22, 0, 15, 8, 15, 8, 0, 0], [2, 0, 0, 15, 8, 15, 8, 0, 0], 4);
+    #[rustfmt::skip]
+    const OTHER: PatchData = ([2, 24, 0, 15, 6, 15, 6, 0, 0], [1, 0, 0, 15, 5, 15, 6, 0, 0], 3);
+
+    pub(crate) fn get_melodic(program: usize) -> OplPatch {
+        OplGmBank::create_patch(&OplGmBank::MELODIC[program], None)
+    }
+
+    // The drums of the GM percussion map, which are played at the fixed pitches.
+    pub(crate) fn get_percussion(key: usize) -> Option<OplPatch> {
+        let (data, fixed_key) = match key {
+            35 => (&OplGmBank::KICK, 30),
+            36 => (&OplGmBank::KICK, 33),
+            37 => (&OplGmBank::WOOD, 72),
+            38 => (&OplGmBank::SNARE, 60),
+            39 => (&OplGmBank::SNARE, 72),
+            40 => (&OplGmBank::SNARE, 64),
+            41 | 43 | 45 | 47 | 48 | 50 => (&OplGmBank::TOM, key as u8),
+            42 | 44 => (&OplGmBank::CLOSED_HI_HAT, 84),
+            46 => (&OplGmBank::OPEN_HI_HAT, 84),
+            49 | 52 | 55 | 57 => (&OplGmBank::CYMBAL, 76),
+            51 | 59 => (&OplGmBank::RIDE, 80),
+            53 => (&OplGmBank::BELL, 84),
+            54 => (&OplGmBank::CLOSED_HI_HAT, 90),
+            56 => (&OplGmBank::BELL, 80),
+            75..=77 => (&OplGmBank::WOOD, key as u8 + 12),
+            58..=81 => (&OplGmBank::OTHER, key as u8),
+            _ => return None,
+        };
+
+        Some(OplGmBank::create_patch(data, Some(fixed_key)))
+    }
+
+    fn create_patch(data: &PatchData, fixed_key: Option<u8>) -> OplPatch {
+        let (modulator, carrier, feedback) = data;
+
+        let mut patch = OplPatch::new();
+        patch.operators[0] = OplGmBank::create_operator(modulator);
+        patch.operators[1] = OplGmBank::create_operator(carrier);
+        patch.connection = (feedback & OplGmBank::AM != 0) as u8;
+        patch.feedback = feedback & 7;
+        patch.fixed_key = fixed_key;
+        patch
+    }
+
+    fn create_operator(data: &OperatorData) -> OplOperator {
+        let mut operator = OplOperator::new();
+        operator.multiplier = data[0];
+        operator.total_level = data[1];
+        operator.key_scale_level = data[2];
+        operator.attack_rate = data[3];
+        operator.decay_rate = data[4];
+        operator.sustain_level = data[5];
+        operator.release_rate = data[6];
+        operator.waveform = data[7];
+        operator.sustain = data[8] & 1 != 0;
+        operator.key_scale_rate = data[8] & 2 != 0;
+        operator.vibrato = data[8] & 4 != 0;
+        operator.tremolo = data[8] & 8 != 0;
+        operator
+    }
+}
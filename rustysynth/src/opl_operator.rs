@@ -0,0 +1,92 @@
+#![allow(dead_code)]
+
+/// Specifies an operator of an OPL patch, in the same ranges as the registers of the chip.
+///
+/// # Remarks
+///
+/// The rates are between 0 and 15, where 0 never changes the level and 15 is the fastest.
+/// The levels are the attenuations, so 0 is the loudest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OplOperator {
+    /// The frequency multiplier between 0 and 15, where 0 means the half of the note frequency.
+    pub multiplier: u8,
+    /// The attenuation between 0 and 63, in steps of 0.75 dB.
+    pub total_level: u8,
+    /// The attenuation per octave between 0 and 3, which is 0, 3, 1.5 or 6 dB.
+    pub key_scale_level: u8,
+    /// The attack rate between 0 and 15.
+    pub attack_rate: u8,
+    /// The decay rate between 0 and 15.
+    pub decay_rate: u8,
+    /// The sustain level between 0 and 15, in steps of 3 dB.
+    pub sustain_level: u8,
+    /// The release rate between 0 and 15.
+    pub release_rate: u8,
+    /// The waveform between 0 and 7, which are the sine and its variations of the OPL3.
+    pub waveform: u8,
+    /// If `true`, the level is held at the sustain level until the note off.
+    /// Otherwise, the level keeps falling at the release rate.
+    pub sustain: bool,
+    /// If `true`, the envelope gets faster for the higher notes.
+    pub key_scale_rate: bool,
+    /// If `true`, the vibrato of 7 cents is applied.
+    pub vibrato: bool,
+    /// If `true`, the tremolo of 1 dB is applied.
+    pub tremolo: bool,
+}
+
+impl OplOperator {
+    /// Initializes a new operator, which plays a sine wave at the note frequency while the note is held.
+    pub fn new() -> Self {
+        Self {
+            multiplier: 1,
+            total_level: 0,
+            key_scale_level: 0,
+            attack_rate: 15,
+            decay_rate: 0,
+            sustain_level: 0,
+            release_rate: 8,
+            waveform: 0,
+            sustain: true,
+            key_scale_rate: false,
+            vibrato: false,
+            tremolo: false,
+        }
+    }
+
+    pub(crate) fn check(&self) {
+        if self.multiplier > 15 {
+            panic!("The multiplier must be between 0 and 15.");
+        }
+
+        if self.total_level > 63 {
+            panic!("The total level must be between 0 and 63.");
+        }
+
+        if self.key_scale_level > 3 {
+            panic!("The key scale level must be between 0 and 3.");
+        }
+
+        for value in [
+            self.attack_rate,
+            self.decay_rate,
+            self.sustain_level,
+            self.release_rate,
+        ] {
+            if value > 15 {
+                panic!("The rates and the sustain level must be between 0 and 15.");
+            }
+        }
+
+        if self.waveform > 7 {
+            panic!("The waveform must be between 0 and 7.");
+        }
+    }
+}
+
+impl Default for OplOperator {
+    fn default() -> Self {
+        OplOperator::new()
+    }
+}
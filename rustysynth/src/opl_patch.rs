@@ -0,0 +1,79 @@
+#![allow(dead_code)]
+
+use crate::opl_operator::OplOperator;
+
+/// Specifies a patch of the OPL FM synthesis.
+///
+/// # Remarks
+///
+/// The 2-operator patches use the first two operators, where the first one is the modulator.
+/// The connections are the same as the OPL3:
+///
+/// * 2-operator, 0 - The first operator modulates the second one.
+/// * 2-operator, 1 - Both operators are mixed.
+/// * 4-operator, 0 - The operators modulate the next ones in series.
+/// * 4-operator, 1 - The first operator is mixed with the series of the others.
+/// * 4-operator, 2 - The first two in series are mixed with the last two in series.
+/// * 4-operator, 3 - The first, the second two in series and the last one are mixed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OplPatch {
+    /// The operators, which are in the order of the connection.
+    pub operators: [OplOperator; 4],
+    /// If `true`, all the four operators are used.
+    pub four_operator: bool,
+    /// The connection of the operators, which is 0 or 1 for the 2-operator patches and between 0 and 3 otherwise.
+    pub connection: u8,
+    /// The feedback of the first operator to itself, between 0 and 7.
+    pub feedback: u8,
+    /// The key at which the patch is played regardless of the key of the note, which is mainly used for the drums.
+    pub fixed_key: Option<u8>,
+}
+
+impl OplPatch {
+    /// Initializes a new 2-operator patch, which plays a sine wave.
+    pub fn new() -> Self {
+        let mut modulator = OplOperator::new();
+        modulator.total_level = 63;
+
+        Self {
+            operators: [
+                modulator,
+                OplOperator::new(),
+                OplOperator::new(),
+                OplOperator::new(),
+            ],
+            four_operator: false,
+            connection: 0,
+            feedback: 0,
+            fixed_key: None,
+        }
+    }
+
+    pub(crate) fn check(&self) {
+        for operator in self.operators.iter() {
+            operator.check();
+        }
+
+        let max_connection = if self.four_operator { 3 } else { 1 };
+        if self.connection > max_connection {
+            panic!("The connection must be 0 or 1 for the 2-operator patches and between 0 and 3 otherwise.");
+        }
+
+        if self.feedback > 7 {
+            panic!("The feedback must be between 0 and 7.");
+        }
+
+        if let Some(key) = self.fixed_key {
+            if key > 127 {
+                panic!("The fixed key must be between 0 and 127.");
+            }
+        }
+    }
+}
+
+impl Default for OplPatch {
+    fn default() -> Self {
+        OplPatch::new()
+    }
+}
@@ -0,0 +1,598 @@
+#![allow(dead_code)]
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::f64::consts;
+
+use crate::float_math::FloatMath;
+use crate::opl_gm_bank::OplGmBank;
+use crate::opl_patch::OplPatch;
+use crate::soundfont_math::SoundFontMath;
+use crate::voice_source::VoiceGenerator;
+use crate::voice_source::VoiceSource;
+
+/// A voice source which emulates the FM synthesis of the OPL3 chip,
+/// to render the MIDI files with the character of the sound cards for the DOS games.
+///
+/// # Remarks
+///
+/// The source is initialized with the GM sound set of the 2-operator patches,
+/// and any of the patches can be replaced with the 2-operator or 4-operator ones.
+/// The channels of the bank 128 or more play the percussion patches by the key.
+/// To render the whole song with the FM synthesis, set the source to all the channels
+/// with `Synthesizer::set_channel_voice_source`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OplVoiceSource {
+    melodic: Vec<OplPatch>,
+    percussion: Vec<Option<OplPatch>>,
+}
+
+impl OplVoiceSource {
+    /// Initializes a new voice source with the GM sound set.
+    pub fn new() -> Self {
+        Self {
+            melodic: (0..128).map(OplGmBank::get_melodic).collect(),
+            percussion: (0..128).map(OplGmBank::get_percussion).collect(),
+        }
+    }
+
+    /// Gets the melodic patch for the program.
+    ///
+    /// # Arguments
+    ///
+    /// * `program` - The program number, between 0 and 127.
+    pub fn get_patch(&self, program: i32) -> OplPatch {
+        OplVoiceSource::check_number(program);
+
+        self.melodic[program as usize]
+    }
+
+    /// Sets the melodic patch for the program.
+    ///
+    /// # Arguments
+    ///
+    /// * `program` - The program number, between 0 and 127.
+    /// * `value` - The patch to play the program.
+    pub fn set_patch(&mut self, program: i32, value: OplPatch) {
+        OplVoiceSource::check_number(program);
+        value.check();
+
+        self.melodic[program as usize] = value;
+    }
+
+    /// Gets the percussion patch for the key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key number, between 0 and 127.
+    ///
+    /// # Remarks
+    ///
+    /// Returns `None` if the key plays no sound.
+    pub fn get_percussion_patch(&self, key: i32) -> Option<OplPatch> {
+        OplVoiceSource::check_number(key);
+
+        self.percussion[key as usize]
+    }
+
+    /// Sets the percussion patch for the key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key number, between 0 and 127.
+    /// * `value` - The patch to play the key, or `None` to play no sound.
+    ///
+    /// # Remarks
+    ///
+    /// The patch is played at the key of the note unless its fixed key is set.
+    pub fn set_percussion_patch(&mut self, key: i32, value: Option<OplPatch>) {
+        OplVoiceSource::check_number(key);
+        if let Some(patch) = value.as_ref() {
+            patch.check();
+        }
+
+        self.percussion[key as usize] = value;
+    }
+
+    fn check_number(number: i32) {
+        if !(0..128).contains(&number) {
+            panic!("The program and key numbers must be between 0 and 127.");
+        }
+    }
+}
+
+impl Default for OplVoiceSource {
+    fn default() -> Self {
+        OplVoiceSource::new()
+    }
+}
+
+impl VoiceSource for OplVoiceSource {
+    fn start(
+        &self,
+        bank_number: i32,
+        patch_number: i32,
+        key: i32,
+        velocity: i32,
+        sample_rate: i32,
+    ) -> Box<dyn VoiceGenerator> {
+        let patch = if bank_number >= 128 {
+            self.percussion[key.clamp(0, 127) as usize]
+        } else {
+            Some(self.melodic[patch_number.clamp(0, 127) as usize])
+        };
+
+        Box::new(OplGenerator::new(patch, key, velocity, sample_rate))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OplStage {
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+    Off,
+}
+
+// An operator with its envelope, where the level is the linear amplitude.
+// The attack rises linearly and the others fall exponentially, in the same way as the chip.
+#[derive(Clone, Copy)]
+struct OplOperatorState {
+    multiplier: f64,
+    waveform: u8,
+    gain: f32,
+    vibrato: bool,
+    tremolo: bool,
+    sustain: bool,
+
+    attack_step: f32,
+    decay_factor: f32,
+    sustain_level: f32,
+    release_factor: f32,
+
+    stage: OplStage,
+    level: f32,
+    phase: f64,
+    output: f32,
+    previous_output: f32,
+}
+
+// Renders a note of an OPL patch.
+struct OplGenerator {
+    operators: [OplOperatorState; 4],
+    carriers: &'static [usize],
+    four_operator: bool,
+    connection: u8,
+    feedback: f32,
+
+    sample_rate: f64,
+    frequency_ratio: f32,
+    gain: f32,
+
+    lfo_time: f64,
+}
+
+impl OplGenerator {
+    const MULTIPLIERS: [f64; 16] = [
+        0.5, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 10.0, 12.0, 12.0, 15.0, 15.0,
+    ];
+
+    // The attenuation per octave in decibels, for each key scale level.
+    const KEY_SCALE_LEVELS: [f32; 4] = [0_f32, 3_f32, 1.5_f32, 6_f32];
+
+    // The times in seconds of the attack and of the decay for 96 dB, at the lowest effective rate.
+    const ATTACK_TIME: f32 = 2.826_f32;
+    const DECAY_TIME: f32 = 39.28_f32;
+
+    // The envelope is finished below this level, which is -96 dB.
+    const MIN_LEVEL: f32 = 1.6E-5_f32;
+
+    // The modulation in cycles by the full output of the modulator.
+    const MODULATION: f64 = 2.0;
+
+    const TREMOLO_DEPTH: f32 = 1_f32;
+    const TREMOLO_FREQUENCY: f64 = 3.7;
+    const VIBRATO_DEPTH: f32 = 7_f32;
+    const VIBRATO_FREQUENCY: f64 = 6.1;
+
+    // The peak level at the maximum velocity, which leaves the headroom for the chords.
+    const LEVEL: f32 = 0.3_f32;
+
+    fn new(patch: Option<OplPatch>, key: i32, velocity: i32, sample_rate: i32) -> Self {
+        let velocity = velocity as f32 / 127_f32;
+
+        let mut generator = Self {
+            operators: [OplGenerator::create_silent_operator(); 4],
+            carriers: &[],
+            four_operator: false,
+            connection: 0,
+            feedback: 0_f32,
+            sample_rate: sample_rate as f64,
+            frequency_ratio: 1_f32,
+            gain: OplGenerator::LEVEL * velocity * velocity,
+            lfo_time: 0.0,
+        };
+
+        let patch = match patch {
+            Some(value) => value,
+            None => return generator,
+        };
+
+        // The drums are played at their own pitches, which are also used for the key scaling.
+        let scaled_key = match patch.fixed_key {
+            Some(fixed_key) => {
+                generator.frequency_ratio = SoundFontMath::cents_to_multiplying_factor(
+                    100_f32 * (fixed_key as i32 - key) as f32,
+                );
+                fixed_key as i32
+            }
+            None => key,
+        };
+
+        let count = if patch.four_operator { 4 } else { 2 };
+        for i in 0..count {
+            generator.operators[i] =
+                OplGenerator::create_operator(&patch, i, scaled_key, sample_rate as f32);
+        }
+
+        generator.carriers = match (patch.four_operator, patch.connection) {
+            (false, 0) => &[1],
+            (false, _) => &[0, 1],
+            (true, 0) => &[3],
+            (true, 1) => &[0, 3],
+            (true, 2) => &[1, 3],
+            (true, _) => &[0, 2, 3],
+        };
+        generator.four_operator = patch.four_operator;
+        generator.connection = patch.connection;
+        generator.feedback = if patch.feedback == 0 {
+            0_f32
+        } else {
+            SoundFontMath::cents_to_multiplying_factor(1200_f32 * (patch.feedback as f32 - 7_f32))
+        };
+
+        generator
+    }
+
+    fn create_silent_operator() -> OplOperatorState {
+        OplOperatorState {
+            multiplier: 1.0,
+            waveform: 0,
+            gain: 0_f32,
+            vibrato: false,
+            tremolo: false,
+            sustain: true,
+            attack_step: 0_f32,
+            decay_factor: 1_f32,
+            sustain_level: 0_f32,
+            release_factor: 1_f32,
+            stage: OplStage::Off,
+            level: 0_f32,
+            phase: 0.0,
+            output: 0_f32,
+            previous_output: 0_f32,
+        }
+    }
+
+    fn create_operator(
+        patch: &OplPatch,
+        index: usize,
+        key: i32,
+        sample_rate: f32,
+    ) -> OplOperatorState {
+        let operator = &patch.operators[index];
+
+        // The higher notes shorten the envelope by the key scale rate.
+        let rate_offset = if operator.key_scale_rate {
+            key / 8
+        } else {
+            key / 32
+        };
+        let get_time = |rate: u8, time: f32| {
+            if rate == 0 {
+                None
+            } else {
+                let effective_rate = (4 * rate as i32 + rate_offset).min(60);
+                Some(
+                    time * SoundFontMath::cents_to_multiplying_factor(
+                        -300_f32 * (effective_rate - 4) as f32,
+                    ),
+                )
+            }
+        };
+        let get_factor = |rate: u8| match get_time(rate, OplGenerator::DECAY_TIME) {
+            Some(time) => SoundFontMath::decibels_to_linear(-96_f32 / (time * sample_rate)),
+            None => 1_f32,
+        };
+
+        let attack_step = match get_time(operator.attack_rate, OplGenerator::ATTACK_TIME) {
+            _ if operator.attack_rate == 15 => 1_f32,
+            Some(time) => 1_f32 / (time * sample_rate).max(1_f32),
+            None => 0_f32,
+        };
+
+        let octaves = ((key - 36) as f32 / 12_f32).max(0_f32);
+        let attenuation = 0.75_f32 * operator.total_level as f32
+            + OplGenerator::KEY_SCALE_LEVELS[operator.key_scale_level as usize] * octaves;
+
+        // The sustain level of 15 is the same as 93 dB of the chip.
+        let sustain_attenuation = 3_f32 * operator.sustain_level as f32;
+
+        OplOperatorState {
+            multiplier: OplGenerator::MULTIPLIERS[operator.multiplier as usize],
+            waveform: operator.waveform,
+            gain: SoundFontMath::decibels_to_linear(-attenuation),
+            vibrato: operator.vibrato,
+            tremolo: operator.tremolo,
+            sustain: operator.sustain,
+            attack_step,
+            decay_factor: get_factor(operator.decay_rate),
+            sustain_level: SoundFontMath::decibels_to_linear(-sustain_attenuation),
+            release_factor: get_factor(operator.release_rate),
+            stage: OplStage::Attack,
+            level: 0_f32,
+            phase: 0.0,
+            output: 0_f32,
+            previous_output: 0_f32,
+        }
+    }
+
+    // The waveforms of the OPL3, where the phase is in cycles.
+    fn get_wave(waveform: u8, phase: f64) -> f32 {
+        let phase = phase - FloatMath::floor_f64(phase);
+        let sine = FloatMath::sin_f64(2.0 * consts::PI * phase) as f32;
+        let first_half = phase < 0.5;
+
+        match waveform {
+            0 => sine,
+            1 => sine.max(0_f32),
+            2 => sine.abs(),
+            3 => {
+                if phase - FloatMath::floor_f64(2.0 * phase) / 2.0 < 0.25 {
+                    sine.abs()
+                } else {
+                    0_f32
+                }
+            }
+            4 => {
+                if first_half {
+                    FloatMath::sin_f64(4.0 * consts::PI * phase) as f32
+                } else {
+                    0_f32
+                }
+            }
+            5 => {
+                if first_half {
+                    (FloatMath::sin_f64(4.0 * consts::PI * phase) as f32).abs()
+                } else {
+                    0_f32
+                }
+            }
+            6 => {
+                if first_half {
+                    1_f32
+                } else {
+                    -1_f32
+                }
+            }
+            _ => {
+                if first_half {
+                    FloatMath::exp_f64(-16.0 * phase) as f32
+                } else {
+                    -FloatMath::exp_f64(-16.0 * (1.0 - phase)) as f32
+                }
+            }
+        }
+    }
+
+    fn process_envelope(operator: &mut OplOperatorState) {
+        match operator.stage {
+            OplStage::Attack => {
+                operator.level += operator.attack_step;
+                if operator.level >= 1_f32 {
+                    operator.level = 1_f32;
+                    operator.stage = OplStage::Decay;
+                }
+            }
+            OplStage::Decay => {
+                operator.level *= operator.decay_factor;
+                if operator.level <= operator.sustain_level {
+                    operator.level = operator.sustain_level;
+                    // The non-sustaining operator keeps falling at the release rate.
+                    operator.stage = if operator.sustain {
+                        OplStage::Sustain
+                    } else {
+                        OplStage::Release
+                    };
+                }
+            }
+            OplStage::Sustain => (),
+            OplStage::Release => {
+                operator.level *= operator.release_factor;
+            }
+            OplStage::Off => return,
+        }
+
+        if operator.level < OplGenerator::MIN_LEVEL && operator.stage != OplStage::Attack {
+            operator.level = 0_f32;
+            operator.stage = OplStage::Off;
+        }
+    }
+
+    // Renders a sample of the operator, where the modulation is in cycles.
+    fn process_operator(
+        operator: &mut OplOperatorState,
+        modulation: f64,
+        phase_step: f64,
+        tremolo: f32,
+    ) -> f32 {
+        OplGenerator::process_envelope(operator);
+
+        let mut gain = operator.gain * operator.level;
+        if operator.tremolo {
+            gain *= tremolo;
+        }
+
+        let value = gain * OplGenerator::get_wave(operator.waveform, operator.phase + modulation);
+        operator.phase += phase_step * operator.multiplier;
+        operator.phase -= FloatMath::floor_f64(operator.phase);
+
+        operator.previous_output = operator.output;
+        operator.output = value;
+        value
+    }
+}
+
+impl VoiceGenerator for OplGenerator {
+    fn process(&mut self, frequency: f32, block: &mut [f32]) -> bool {
+        if self
+            .carriers
+            .iter()
+            .all(|&i| self.operators[i].stage == OplStage::Off)
+        {
+            return false;
+        }
+
+        // The LFOs are updated for each block, which is fine enough for their slow rates.
+        let lfo = 2.0 * consts::PI * self.lfo_time;
+        let tremolo = SoundFontMath::decibels_to_linear(
+            -0.5_f32
+                * OplGenerator::TREMOLO_DEPTH
+                * (1_f32 + FloatMath::sin_f64(OplGenerator::TREMOLO_FREQUENCY * lfo) as f32),
+        );
+        let vibrato = SoundFontMath::cents_to_multiplying_factor(
+            OplGenerator::VIBRATO_DEPTH
+                * FloatMath::sin_f64(OplGenerator::VIBRATO_FREQUENCY * lfo) as f32,
+        );
+        self.lfo_time += block.len() as f64 / self.sample_rate;
+
+        // The frequency is limited below the Nyquist frequency.
+        let phase_step =
+            ((self.frequency_ratio * frequency) as f64 / self.sample_rate).clamp(0.0, 0.5);
+        let vibrato_step = phase_step * vibrato as f64;
+        let steps = self.operators.map(|operator| {
+            if operator.vibrato {
+                vibrato_step
+            } else {
+                phase_step
+            }
+        });
+
+        let operators = &mut self.operators;
+        let feedback = self.feedback as f64 * OplGenerator::MODULATION;
+        let modulation = OplGenerator::MODULATION;
+
+        for value in block.iter_mut() {
+            let first = &operators[0];
+            let feedback = feedback * 0.5 * (first.output + first.previous_output) as f64;
+            let o0 = OplGenerator::process_operator(&mut operators[0], feedback, steps[0], tremolo);
+            let m0 = modulation * o0 as f64;
+
+            let output = if !self.four_operator {
+                if self.connection == 0 {
+                    OplGenerator::process_operator(&mut operators[1], m0, steps[1], tremolo)
+                } else {
+                    o0 + OplGenerator::process_operator(&mut operators[1], 0.0, steps[1], tremolo)
+                }
+            } else {
+                match self.connection {
+                    0 => {
+                        let o1 = OplGenerator::process_operator(
+                            &mut operators[1],
+                            m0,
+                            steps[1],
+                            tremolo,
+                        );
+                        let o2 = OplGenerator::process_operator(
+                            &mut operators[2],
+                            modulation * o1 as f64,
+                            steps[2],
+                            tremolo,
+                        );
+                        OplGenerator::process_operator(
+                            &mut operators[3],
+                            modulation * o2 as f64,
+                            steps[3],
+                            tremolo,
+                        )
+                    }
+                    1 => {
+                        let o1 = OplGenerator::process_operator(
+                            &mut operators[1],
+                            0.0,
+                            steps[1],
+                            tremolo,
+                        );
+                        let o2 = OplGenerator::process_operator(
+                            &mut operators[2],
+                            modulation * o1 as f64,
+                            steps[2],
+                            tremolo,
+                        );
+                        o0 + OplGenerator::process_operator(
+                            &mut operators[3],
+                            modulation * o2 as f64,
+                            steps[3],
+                            tremolo,
+                        )
+                    }
+                    2 => {
+                        let o1 = OplGenerator::process_operator(
+                            &mut operators[1],
+                            m0,
+                            steps[1],
+                            tremolo,
+                        );
+                        let o2 = OplGenerator::process_operator(
+                            &mut operators[2],
+                            0.0,
+                            steps[2],
+                            tremolo,
+                        );
+                        o1 + OplGenerator::process_operator(
+                            &mut operators[3],
+                            modulation * o2 as f64,
+                            steps[3],
+                            tremolo,
+                        )
+                    }
+                    _ => {
+                        let o1 = OplGenerator::process_operator(
+                            &mut operators[1],
+                            0.0,
+                            steps[1],
+                            tremolo,
+                        );
+                        let o2 = OplGenerator::process_operator(
+                            &mut operators[2],
+                            modulation * o1 as f64,
+                            steps[2],
+                            tremolo,
+                        );
+                        o0 + o2
+                            + OplGenerator::process_operator(
+                                &mut operators[3],
+                                0.0,
+                                steps[3],
+                                tremolo,
+                            )
+                    }
+                }
+            };
+
+            *value = self.gain * output;
+        }
+
+        true
+    }
+
+    fn release(&mut self) {
+        for operator in self.operators.iter_mut() {
+            if operator.stage != OplStage::Off {
+                operator.stage = OplStage::Release;
+            }
+        }
+    }
+}
@@ -1,13 +1,18 @@
 #![allow(dead_code)]
 
+use core::cmp;
+
+use crate::float_math::FloatMath;
 use crate::loop_mode::LoopMode;
 use crate::synthesizer_settings::SynthesizerSettings;
+use crate::wave_data::WaveSlice;
 
 // In this class, fixed-point numbers are used for speed-up.
 // A fixed-point number is expressed by Int64, whose lower 24 bits represent the fraction part,
 // and the rest represent the integer part.
 // For clarity, fixed-point number variables have a suffix "_fp".
 
+#[derive(Clone)]
 #[non_exhaustive]
 pub(crate) struct Oscillator {
     synthesizer_sample_rate: i32,
@@ -33,6 +38,7 @@ impl Oscillator {
     const FRAC_BITS: i32 = 24;
     const FRAC_UNIT: i64 = 1_i64 << Oscillator::FRAC_BITS;
     const FP_TO_SAMPLE: f32 = 1_f32 / (32768 * Oscillator::FRAC_UNIT) as f32;
+    const FP_TO_SAMPLE_24: f32 = 1_f32 / (8388608 * Oscillator::FRAC_UNIT) as f32;
 
     pub(crate) fn new(settings: &SynthesizerSettings) -> Self {
         Self {
@@ -65,7 +71,16 @@ impl Oscillator {
         coarse_tune: i32,
         fine_tune: i32,
         scale_tuning: i32,
+        sample_count: usize,
     ) {
+        // The positions given by a broken SoundFont or by the address offsets may be outside the sample data.
+        // They are clamped, so that the interpolation never reads past the end of the data.
+        let last = cmp::min(sample_count.saturating_sub(1), i32::MAX as usize) as i32;
+        let start = start.clamp(0, last);
+        let end = end.clamp(start, last);
+        let start_loop = start_loop.clamp(0, last + 1);
+        let end_loop = end_loop.clamp(0, last + 1);
+
         self.loop_mode = loop_mode;
         self.sample_sample_rate = sample_rate;
         self.start = start;
@@ -77,33 +92,61 @@ impl Oscillator {
         self.tune = coarse_tune as f32 + 0.01_f32 * fine_tune as f32;
         self.pitch_change_scale = 0.01_f32 * scale_tuning as f32;
         self.sample_rate_ratio = sample_rate as f32 / self.synthesizer_sample_rate as f32;
-        self.looping = self.loop_mode != LoopMode::NO_LOOP;
+        self.looping = self.loop_mode != LoopMode::NO_LOOP && start_loop < end_loop;
         self.position_fp = (start as i64) << Oscillator::FRAC_BITS;
     }
 
+    // Plays the rest of the sample once, ignoring the loop.
+    pub(crate) fn play_once(&mut self) {
+        self.looping = false;
+    }
+
     pub(crate) fn release(&mut self) {
         if self.loop_mode == LoopMode::LOOP_UNTIL_NOTE_OFF {
             self.looping = false;
         }
     }
 
-    pub(crate) fn process(&mut self, data: &[i16], block: &mut [f32], pitch: f32) -> bool {
+    pub(crate) fn process(&mut self, data: WaveSlice, block: &mut [f32], pitch: f32) -> bool {
         let pitch_change = self.pitch_change_scale * (pitch - self.root_key as f32) + self.tune;
-        let pitch_ratio = self.sample_rate_ratio * 2_f32.powf(pitch_change / 12_f32);
+        let pitch_ratio = self.sample_rate_ratio * FloatMath::powf(2_f32, pitch_change / 12_f32);
         self.fill_block(data, block, pitch_ratio as f64)
     }
 
-    fn fill_block(&mut self, data: &[i16], block: &mut [f32], pitch_ratio: f64) -> bool {
+    fn fill_block(&mut self, data: WaveSlice, block: &mut [f32], pitch_ratio: f64) -> bool {
         let pitch_ratio_fp = (Oscillator::FRAC_UNIT as f64 * pitch_ratio) as i64;
 
+        // The 24-bit samples are made from the 16-bit samples and the lower 8 bits.
+        if data.data_24.is_empty() {
+            let read = |index: usize| data.data[index] as i64;
+            self.fill_block_with(read, Oscillator::FP_TO_SAMPLE, block, pitch_ratio_fp)
+        } else {
+            let read = |index: usize| ((data.data[index] as i64) << 8) | data.data_24[index] as i64;
+            self.fill_block_with(read, Oscillator::FP_TO_SAMPLE_24, block, pitch_ratio_fp)
+        }
+    }
+
+    fn fill_block_with(
+        &mut self,
+        read: impl Fn(usize) -> i64,
+        scale: f32,
+        block: &mut [f32],
+        pitch_ratio_fp: i64,
+    ) -> bool {
         if self.looping {
-            self.fill_block_continuous(data, block, pitch_ratio_fp)
+            self.fill_block_continuous(read, scale, block, pitch_ratio_fp)
         } else {
-            self.fill_block_no_loop(data, block, pitch_ratio_fp)
+            self.fill_block_no_loop(read, scale, block, pitch_ratio_fp)
         }
     }
 
-    fn fill_block_no_loop(&mut self, data: &[i16], block: &mut [f32], pitch_ratio_fp: i64) -> bool {
+    fn fill_block_no_loop(
+        &mut self,
+        read: impl Fn(usize) -> i64,
+        scale: f32,
+        block: &mut [f32],
+        pitch_ratio_fp: i64,
+    ) -> bool {
         for t in 0..block.len() {
             let index = (self.position_fp >> Oscillator::FRAC_BITS) as usize;
             if index >= self.end as usize {
@@ -117,11 +160,10 @@ impl Oscillator {
                 }
             }
 
-            let x1 = data[index] as i64;
-            let x2 = data[index + 1] as i64;
+            let x1 = read(index);
+            let x2 = read(index + 1);
             let a_fp = self.position_fp & (Oscillator::FRAC_UNIT - 1);
-            block[t] = Oscillator::FP_TO_SAMPLE
-                * ((x1 << Oscillator::FRAC_BITS) + a_fp * (x2 - x1)) as f32;
+            block[t] = scale * ((x1 << Oscillator::FRAC_BITS) + a_fp * (x2 - x1)) as f32;
 
             self.position_fp += pitch_ratio_fp;
         }
@@ -131,7 +173,8 @@ impl Oscillator {
 
     fn fill_block_continuous(
         &mut self,
-        data: &[i16],
+        read: impl Fn(usize) -> i64,
+        scale: f32,
         block: &mut [f32],
         pitch_ratio_fp: i64,
     ) -> bool {
@@ -140,8 +183,10 @@ impl Oscillator {
         let loop_length_fp = loop_length << Oscillator::FRAC_BITS;
 
         for sample in block.iter_mut() {
+            // The end of a short loop can be passed more than once by a high pitch.
             if self.position_fp >= end_loop_fp {
-                self.position_fp -= loop_length_fp;
+                self.position_fp = end_loop_fp - loop_length_fp
+                    + (self.position_fp - end_loop_fp) % loop_length_fp;
             }
 
             let index1 = (self.position_fp >> Oscillator::FRAC_BITS) as usize;
@@ -150,11 +195,10 @@ impl Oscillator {
                 index2 -= loop_length as usize;
             }
 
-            let x1 = data[index1] as i64;
-            let x2 = data[index2] as i64;
+            let x1 = read(index1);
+            let x2 = read(index2);
             let a_fp = self.position_fp & (Oscillator::FRAC_UNIT - 1);
-            *sample = Oscillator::FP_TO_SAMPLE
-                * ((x1 << Oscillator::FRAC_BITS) + a_fp * (x2 - x1)) as f32;
+            *sample = scale * ((x1 << Oscillator::FRAC_BITS) + a_fp * (x2 - x1)) as f32;
 
             self.position_fp += pitch_ratio_fp;
         }
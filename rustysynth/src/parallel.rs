@@ -0,0 +1,68 @@
+// The parallel iterators used for the voices and the MIDI tracks.
+// With the std feature, they are the ones of rayon.
+// Without it, the same methods are provided by the sequential iterators,
+// so that the callers do not have to care about the feature.
+
+#[cfg(feature = "std")]
+pub(crate) use rayon::current_num_threads;
+#[cfg(feature = "std")]
+pub(crate) use rayon::prelude::*;
+
+#[cfg(not(feature = "std"))]
+pub(crate) use self::sequential::*;
+
+#[cfg(not(feature = "std"))]
+mod sequential {
+    use alloc::vec::Vec;
+    use core::slice;
+
+    pub(crate) fn current_num_threads() -> usize {
+        1
+    }
+
+    pub(crate) trait IntoParallelIterator {
+        type Iter: Iterator;
+        fn into_par_iter(self) -> Self::Iter;
+    }
+
+    impl<T> IntoParallelIterator for Vec<T> {
+        type Iter = alloc::vec::IntoIter<T>;
+        fn into_par_iter(self) -> Self::Iter {
+            self.into_iter()
+        }
+    }
+
+    pub(crate) trait IntoParallelRefIterator<'a> {
+        type Iter: Iterator;
+        fn par_iter(&'a self) -> Self::Iter;
+    }
+
+    impl<'a, T: 'a> IntoParallelRefIterator<'a> for [T] {
+        type Iter = slice::Iter<'a, T>;
+        fn par_iter(&'a self) -> Self::Iter {
+            self.iter()
+        }
+    }
+
+    pub(crate) trait IntoParallelRefMutIterator<'a> {
+        type Iter: Iterator;
+        fn par_iter_mut(&'a mut self) -> Self::Iter;
+    }
+
+    impl<'a, T: 'a> IntoParallelRefMutIterator<'a> for [T] {
+        type Iter = slice::IterMut<'a, T>;
+        fn par_iter_mut(&'a mut self) -> Self::Iter {
+            self.iter_mut()
+        }
+    }
+
+    pub(crate) trait ParallelSlice<T> {
+        fn par_chunks(&self, chunk_size: usize) -> slice::Chunks<'_, T>;
+    }
+
+    impl<T> ParallelSlice<T> for [T] {
+        fn par_chunks(&self, chunk_size: usize) -> slice::Chunks<'_, T> {
+            self.chunks(chunk_size)
+        }
+    }
+}
@@ -0,0 +1,39 @@
+#![allow(dead_code)]
+
+/// Specifies the limits applied when parsing SoundFonts and MIDI files.
+///
+/// # Remarks
+///
+/// Regardless of these limits, a chunk can never be larger than the rest of the stream.
+/// Lower the limits to safely load files from untrusted sources.
+#[derive(Clone, Copy)]
+#[non_exhaustive]
+pub struct ParserLimits {
+    /// The maximum size of a chunk in bytes.
+    pub max_chunk_size: usize,
+    /// The maximum number of tracks in a MIDI file.
+    pub max_track_count: usize,
+    /// The maximum number of events in a MIDI file, after the tempo track is merged into each track.
+    pub max_event_count: usize,
+}
+
+impl ParserLimits {
+    const DEFAULT_MAX_CHUNK_SIZE: usize = u32::MAX as usize;
+    const DEFAULT_MAX_TRACK_COUNT: usize = u16::MAX as usize;
+    const DEFAULT_MAX_EVENT_COUNT: usize = 500_000_000;
+
+    /// Initializes a new instance of parser limits.
+    pub fn new() -> Self {
+        Self {
+            max_chunk_size: ParserLimits::DEFAULT_MAX_CHUNK_SIZE,
+            max_track_count: ParserLimits::DEFAULT_MAX_TRACK_COUNT,
+            max_event_count: ParserLimits::DEFAULT_MAX_EVENT_COUNT,
+        }
+    }
+}
+
+impl Default for ParserLimits {
+    fn default() -> Self {
+        ParserLimits::new()
+    }
+}
@@ -0,0 +1,66 @@
+#![allow(dead_code)]
+
+use alloc::boxed::Box;
+
+/// Specifies how the value of the hold (sustain) pedal (CC #64) is interpreted.
+///
+/// # Remarks
+///
+/// The pedal gives the amount of the sustain between 0 and 1.
+/// At 1, the notes are held after the note offs until the pedal is released.
+/// Below 1, the held notes are released, and with a continuous curve,
+/// the released notes decay slower as the amount increases, like the dampers of a piano touching the strings lightly.
+/// Pressing the pedal again then catches the decaying notes (repedaling).
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum PedalCurve {
+    /// The pedal is a switch, which is down at 64 or above.
+    /// The released notes are not affected by the pedal.
+    /// This is the behavior of the GM spec, and the default.
+    Switch,
+    /// The amount increases linearly from 0 at the start value to 1 at the end value.
+    HalfDamper {
+        /// The value at and below which the dampers are on the strings, such as 32.
+        start: u8,
+        /// The value at and above which the pedal is fully down, such as 96.
+        end: u8,
+    },
+    /// The amounts for each value, between 0 and 1.
+    Custom(Box<[f32; 128]>),
+}
+
+impl PedalCurve {
+    pub(crate) fn get_amount(&self, value: u8) -> f32 {
+        match self {
+            PedalCurve::Switch => {
+                if value >= 64 {
+                    1_f32
+                } else {
+                    0_f32
+                }
+            }
+            PedalCurve::HalfDamper { start, end } => {
+                if value <= *start {
+                    0_f32
+                } else if value >= *end {
+                    1_f32
+                } else {
+                    (value - start) as f32 / (end - start) as f32
+                }
+            }
+            PedalCurve::Custom(table) => table[value.min(127) as usize],
+        }
+    }
+
+    pub(crate) fn is_continuous(&self) -> bool {
+        !matches!(self, PedalCurve::Switch)
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        match self {
+            PedalCurve::Switch => true,
+            PedalCurve::HalfDamper { start, end } => start < end && *end <= 127,
+            PedalCurve::Custom(table) => table.iter().all(|x| (0_f32..=1_f32).contains(x)),
+        }
+    }
+}
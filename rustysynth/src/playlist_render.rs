@@ -0,0 +1,344 @@
+#![allow(dead_code)]
+
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp;
+use core::f32::consts::FRAC_PI_2;
+
+use crate::error::SynthesizerError;
+use crate::midifile::MidiFile;
+use crate::midifile::MidiTrack;
+use crate::midifile_sequencer::MidiFileSequencer;
+use crate::playlist_transition::PlaylistTransition;
+use crate::render_progress::RenderProgress;
+use crate::render_tail::RenderTail;
+use crate::soundfont::SoundFont;
+use crate::synthesizer::Synthesizer;
+use crate::synthesizer_settings::SynthesizerSettings;
+use crate::synthesizer_state::SynthesizerState;
+use crate::track_render::TrackRender;
+
+/// Renders a playlist of MIDI files into one continuous waveform.
+///
+/// # Remarks
+///
+/// The files are played one after another, joined by the transition.
+/// Each file is played once, ignoring its loop, and all its tracks are played by one synthesizer.
+#[non_exhaustive]
+pub struct PlaylistRender {
+    sound_font: Arc<SoundFont>,
+    synthesizer_settings: SynthesizerSettings,
+
+    tracks: Vec<MidiTrack>,
+    transition: PlaylistTransition,
+    keep_state: bool,
+    tail: RenderTail,
+
+    progress: Arc<RenderProgress>,
+}
+
+// The position of a file in the output, and the lengths of its events and its fades in samples.
+struct Placement {
+    start: usize,
+    event_length: usize,
+    fade_in: usize,
+    fade_out: usize,
+}
+
+impl PlaylistRender {
+    // The files except the last one ring out until the sound stops, under the next file.
+    const RING_OUT: RenderTail = RenderTail::UntilSilence {
+        threshold: -120.0,
+        max_length: 10.0,
+    };
+
+    /// Initializes a new renderer with an empty playlist.
+    ///
+    /// # Arguments
+    ///
+    /// * `sound_font` - The SoundFont instance.
+    /// * `synthesizer_settings` - The settings for synthesis.
+    pub fn new(sound_font: &Arc<SoundFont>, synthesizer_settings: SynthesizerSettings) -> Self {
+        Self {
+            sound_font: Arc::clone(sound_font),
+            synthesizer_settings,
+            tracks: Vec::new(),
+            transition: PlaylistTransition::default(),
+            keep_state: false,
+            tail: RenderTail::None,
+            progress: Arc::new(RenderProgress::new()),
+        }
+    }
+
+    /// Adds a MIDI file to the end of the playlist.
+    ///
+    /// # Arguments
+    ///
+    /// * `midi_file` - The MIDI file to be added.
+    ///
+    /// # Remarks
+    ///
+    /// The tracks of a format 0 or 1 file are played together,
+    /// and the patterns of a format 2 file are played one after another.
+    pub fn push(&mut self, midi_file: &MidiFile) {
+        let tracks = midi_file.get_tracks().iter().collect::<Vec<&MidiTrack>>();
+        let track = if midi_file.get_format() == 2 {
+            MidiTrack::concat(&tracks)
+        } else {
+            MidiTrack::merge(&tracks)
+        };
+        self.tracks.push(track);
+    }
+
+    /// Adds a MIDI track to the end of the playlist.
+    ///
+    /// # Arguments
+    ///
+    /// * `midi_track` - The MIDI track to be added, such as the one converted from a `Sequence`.
+    pub fn push_track(&mut self, midi_track: MidiTrack) {
+        self.tracks.push(midi_track);
+    }
+
+    /// Removes all the files from the playlist.
+    pub fn clear(&mut self) {
+        self.tracks.clear();
+    }
+
+    /// Gets the number of the files in the playlist.
+    pub fn get_file_count(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// Gets the start time of each file in the rendered waveform in seconds.
+    ///
+    /// # Remarks
+    ///
+    /// The times are known before rendering, so they can be used to write the cue points or a track list.
+    pub fn get_start_times(&self) -> Vec<f64> {
+        let sample_rate = self.synthesizer_settings.sample_rate as f64;
+        self.get_placements()
+            .iter()
+            .map(|x| x.start as f64 / sample_rate)
+            .collect()
+    }
+
+    /// Renders the whole playlist and returns the left and right channels.
+    ///
+    /// # Remarks
+    ///
+    /// The files are rendered one by one, so that the state can be passed to the next file.
+    /// The sound after the last event of the last file is rendered according to the tail.
+    pub fn render(&self) -> Result<(Vec<f32>, Vec<f32>), SynthesizerError> {
+        let sample_rate = self.synthesizer_settings.sample_rate;
+        let placements = self.get_placements();
+
+        let tails = (0..self.tracks.len())
+            .map(|i| self.get_file_tail(i))
+            .collect::<Vec<RenderTail>>();
+        let total = self
+            .tracks
+            .iter()
+            .zip(tails.iter())
+            .map(|(track, tail)| {
+                TrackRender::get_max_length(sample_rate, track.get_length(), *tail)
+            })
+            .sum::<usize>();
+        self.progress.start(total as u64);
+
+        let mut left: Vec<f32> = Vec::new();
+        let mut right: Vec<f32> = Vec::new();
+        let mut state: Option<SynthesizerState> = None;
+
+        for (index, track) in self.tracks.iter().enumerate() {
+            let synthesizer = Synthesizer::new(&self.sound_font, &self.synthesizer_settings)?;
+            let mut sequencer = MidiFileSequencer::new(synthesizer);
+            sequencer.play(track.clone(), false);
+            if let Some(state) = state.as_ref() {
+                sequencer.get_synthesizer_mut().load_state(state)?;
+            }
+
+            let placement = &placements[index];
+            let mut render = TrackRender::new(
+                index,
+                sequencer,
+                track.get_length(),
+                tails[index],
+                &self.progress,
+            );
+
+            // The state is taken at the last event, before the tail.
+            let (mut file_left, mut file_right) = render.render(placement.event_length);
+            if self.keep_state {
+                state = Some(render.sequencer.get_synthesizer().save_state(false));
+            }
+            let (tail_left, tail_right) = render.render(usize::MAX);
+            file_left.extend_from_slice(&tail_left);
+            file_right.extend_from_slice(&tail_right);
+
+            PlaylistRender::apply_fades(&mut file_left, &mut file_right, placement);
+
+            let end = placement.start + file_left.len();
+            if left.len() < end {
+                left.resize(end, 0_f32);
+                right.resize(end, 0_f32);
+            }
+            for (t, (l, r)) in file_left.iter().zip(file_right.iter()).enumerate() {
+                left[placement.start + t] += l;
+                right[placement.start + t] += r;
+            }
+        }
+
+        Ok((left, right))
+    }
+
+    fn get_placements(&self) -> Vec<Placement> {
+        let sample_rate = self.synthesizer_settings.sample_rate as f64;
+        let event_lengths = self
+            .tracks
+            .iter()
+            .map(|x| (sample_rate * x.get_length()) as usize)
+            .collect::<Vec<usize>>();
+
+        let mut placements: Vec<Placement> = Vec::with_capacity(self.tracks.len());
+        let mut start: usize = 0;
+        let mut fade_in: usize = 0;
+        for (i, event_length) in event_lengths.iter().enumerate() {
+            let next_length = match event_lengths.get(i + 1) {
+                Some(value) => *value,
+                None => {
+                    placements.push(Placement {
+                        start,
+                        event_length: *event_length,
+                        fade_in,
+                        fade_out: 0,
+                    });
+                    break;
+                }
+            };
+
+            // The crossfade cannot be longer than the files on both sides.
+            let (gap, fade_out) = match self.transition {
+                PlaylistTransition::Gap(gap) => ((sample_rate * gap) as usize, 0),
+                PlaylistTransition::Crossfade(length) => {
+                    let length = (sample_rate * length) as usize;
+                    (0, cmp::min(length, cmp::min(*event_length, next_length)))
+                }
+            };
+
+            placements.push(Placement {
+                start,
+                event_length: *event_length,
+                fade_in,
+                fade_out,
+            });
+
+            start = start + event_length + gap - fade_out;
+            fade_in = fade_out;
+        }
+
+        placements
+    }
+
+    fn get_file_tail(&self, index: usize) -> RenderTail {
+        if index == self.tracks.len() - 1 {
+            return self.tail;
+        }
+
+        // The file faded out has nothing to ring out.
+        match self.transition {
+            PlaylistTransition::Gap(_) => PlaylistRender::RING_OUT,
+            PlaylistTransition::Crossfade(_) => RenderTail::None,
+        }
+    }
+
+    fn apply_fades(left: &mut [f32], right: &mut [f32], placement: &Placement) {
+        let mut gains = vec![1_f32; left.len()];
+
+        for (t, gain) in gains.iter_mut().take(placement.fade_in).enumerate() {
+            *gain *= (FRAC_PI_2 * t as f32 / placement.fade_in as f32).sin();
+        }
+
+        if placement.fade_out > 0 {
+            let fade_start = placement.event_length - placement.fade_out;
+            for (t, gain) in gains.iter_mut().skip(fade_start).enumerate() {
+                *gain *= if t < placement.fade_out {
+                    (FRAC_PI_2 * t as f32 / placement.fade_out as f32).cos()
+                } else {
+                    0_f32
+                };
+            }
+        }
+
+        for ((l, r), gain) in left.iter_mut().zip(right.iter_mut()).zip(gains.iter()) {
+            *l *= gain;
+            *r *= gain;
+        }
+    }
+
+    /// Gets the transition between the files.
+    pub fn get_transition(&self) -> PlaylistTransition {
+        self.transition
+    }
+
+    /// Sets the transition between the files.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The transition, where the length must be a non-negative value.
+    pub fn set_transition(&mut self, value: PlaylistTransition) {
+        let length = match value {
+            PlaylistTransition::Gap(value) => value,
+            PlaylistTransition::Crossfade(value) => value,
+        };
+        if length.is_nan() || length < 0.0 {
+            panic!("The transition length must be a non-negative value.");
+        }
+
+        self.transition = value;
+    }
+
+    /// Gets a value that indicates whether the state of the synthesizer is passed to the next file.
+    pub fn get_keep_state(&self) -> bool {
+        self.keep_state
+    }
+
+    /// Sets a value that indicates whether the state of the synthesizer is passed to the next file.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - If `true`, each file starts with the programs and the controllers
+    ///   at the last event of the previous file. If `false`, each file starts from the reset state.
+    ///
+    /// # Remarks
+    ///
+    /// The default value is `false`.
+    /// Keeping the state is useful for the files which rely on the setup sent by the first one.
+    /// In either case, the notes of the previous file are not carried over, but ring out on their own.
+    pub fn set_keep_state(&mut self, value: bool) {
+        self.keep_state = value;
+    }
+
+    /// Gets the tail after the last file.
+    pub fn get_tail(&self) -> RenderTail {
+        self.tail
+    }
+
+    /// Sets the tail after the last file.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The tail, which is the same as `ThreadedRender::set_tail`.
+    ///
+    /// # Remarks
+    ///
+    /// The other files ring out under the next file, unless they are crossfaded.
+    pub fn set_tail(&mut self, value: RenderTail) {
+        self.tail = value;
+    }
+
+    /// Gets the progress of the rendering, which can be read from another thread.
+    pub fn get_render_progress(&self) -> Arc<RenderProgress> {
+        Arc::clone(&self.progress)
+    }
+}
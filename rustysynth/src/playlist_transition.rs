@@ -0,0 +1,24 @@
+#![allow(dead_code)]
+
+/// Specifies how a MIDI file of a playlist is joined to the next one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum PlaylistTransition {
+    /// The next file starts the specified number of seconds after the last event of the file.
+    ///
+    /// The sound of the file rings out under the gap and the next file,
+    /// and zero makes the playlist gapless.
+    Gap(f64),
+
+    /// The next file fades in while the file fades out, over the specified number of seconds
+    /// before the last event of the file.
+    ///
+    /// The fades are equal-power, so the loudness stays constant for the unrelated songs.
+    Crossfade(f64),
+}
+
+impl Default for PlaylistTransition {
+    fn default() -> Self {
+        PlaylistTransition::Gap(0.0)
+    }
+}
@@ -0,0 +1,126 @@
+//! The commonly used types of the crate.
+//!
+//! The items re-exported here are the stable public API,
+//! and `use rustysynth::prelude::*;` is enough for typical applications.
+
+pub use crate::Arpeggiator;
+pub use crate::ArpeggiatorPattern;
+pub use crate::Automation;
+pub use crate::AutomationTarget;
+pub use crate::BasicVoiceSource;
+pub use crate::BasicWaveform;
+#[cfg(feature = "std")]
+pub use crate::BatchFileState;
+#[cfg(feature = "std")]
+pub use crate::BatchProgress;
+#[cfg(feature = "std")]
+pub use crate::BatchRenderer;
+pub use crate::BroadcastInfo;
+pub use crate::CancellationToken;
+pub use crate::ChannelAnalysis;
+pub use crate::ChannelState;
+pub use crate::ControllerTarget;
+pub use crate::DeviceProfile;
+pub use crate::Diagnostic;
+pub use crate::DiagnosticSeverity;
+pub use crate::Diagnostics;
+pub use crate::EnvelopeOverride;
+pub use crate::EnvelopePhase;
+pub use crate::ExclusiveClassMode;
+pub use crate::FilterOverride;
+pub use crate::FilterType;
+pub use crate::FourCC;
+pub use crate::GeneratorOverrides;
+pub use crate::HumanizeOptions;
+pub use crate::Instrument;
+pub use crate::InstrumentRegion;
+pub use crate::KeySplit;
+#[cfg(feature = "std")]
+pub use crate::LevelReport;
+pub use crate::LfoOverride;
+pub use crate::LfoRate;
+#[cfg(feature = "std")]
+pub use crate::LoopRenderMode;
+pub use crate::LyricSyllable;
+pub use crate::Lyrics;
+pub use crate::Metronome;
+pub use crate::MidiEvent;
+pub use crate::MidiFile;
+pub use crate::MidiFileAnalysis;
+pub use crate::MidiFileError;
+pub use crate::MidiFileLoopType;
+pub use crate::MidiFileOptions;
+pub use crate::MidiFileSequencer;
+pub use crate::MidiMessage;
+pub use crate::MidiTrack;
+#[cfg(feature = "std")]
+pub use crate::Normalization;
+#[cfg(feature = "opl")]
+pub use crate::OplOperator;
+#[cfg(feature = "opl")]
+pub use crate::OplPatch;
+#[cfg(feature = "opl")]
+pub use crate::OplVoiceSource;
+pub use crate::PedalCurve;
+#[cfg(feature = "std")]
+pub use crate::PlaylistRender;
+#[cfg(feature = "std")]
+pub use crate::PlaylistTransition;
+pub use crate::Preset;
+pub use crate::PresetRegion;
+pub use crate::PresetStatistics;
+#[cfg(feature = "std")]
+pub use crate::ProgressiveRender;
+pub use crate::ReleaseLayer;
+#[cfg(feature = "std")]
+pub use crate::RenderAnalysis;
+#[cfg(feature = "std")]
+pub use crate::RenderCache;
+#[cfg(feature = "std")]
+pub use crate::RenderProgress;
+#[cfg(feature = "std")]
+pub use crate::RenderReport;
+#[cfg(feature = "std")]
+pub use crate::RenderSplit;
+#[cfg(feature = "std")]
+pub use crate::RenderTail;
+pub use crate::RiffChunk;
+pub use crate::SampleHeader;
+pub use crate::Sequence;
+pub use crate::SequencerLoopMode;
+pub use crate::SoundBankSet;
+pub use crate::SoundFont;
+pub use crate::SoundFontError;
+pub use crate::SoundFontInfo;
+pub use crate::SoundFontInspector;
+pub use crate::Synthesizer;
+pub use crate::SynthesizerError;
+pub use crate::SynthesizerSettings;
+pub use crate::SynthesizerState;
+pub use crate::SynthesizerStatistics;
+pub use crate::TempoMap;
+#[cfg(feature = "std")]
+pub use crate::ThreadedRender;
+pub use crate::TimeStretch;
+#[cfg(feature = "std")]
+pub use crate::TrackAnalysis;
+#[cfg(feature = "std")]
+pub use crate::TrackErrorPolicy;
+#[cfg(feature = "std")]
+pub use crate::TrackMix;
+#[cfg(feature = "std")]
+pub use crate::TrackRenderError;
+pub use crate::VoiceEvent;
+pub use crate::VoiceEventKind;
+pub use crate::VoiceGenerator;
+pub use crate::VoiceInfo;
+pub use crate::VoiceSource;
+pub use crate::VoiceStealingPolicy;
+pub use crate::VolumeCurve;
+pub use crate::WaveFormat;
+pub use crate::WaveOptions;
+
+#[cfg(feature = "midir")]
+pub use crate::LiveMidiError;
+#[cfg(feature = "midir")]
+pub use crate::LiveMidiSession;
@@ -1,5 +1,8 @@
 #![allow(dead_code)]
 
+use alloc::string::String;
+use alloc::vec::Vec;
+
 use crate::error::SoundFontError;
 use crate::instrument::Instrument;
 use crate::preset_info::PresetInfo;
@@ -7,6 +10,7 @@ use crate::preset_region::PresetRegion;
 use crate::zone::Zone;
 
 /// Represents a preset in the SoundFont.
+#[derive(Clone)]
 #[non_exhaustive]
 pub struct Preset {
     pub(crate) name: String,
@@ -34,7 +38,10 @@ impl Preset {
 
         let span_start = info.zone_start_index as usize;
         let span_end = span_start + zone_count as usize;
-        let zone_span = &zones[span_start..span_end];
+        let zone_span = match zones.get(span_start..span_end) {
+            Some(value) => value,
+            None => return Err(SoundFontError::InvalidPreset(preset_id)),
+        };
         let regions = PresetRegion::create(preset_id, zone_span, instruments)?;
 
         Ok(Self {
@@ -1,9 +1,13 @@
 #![allow(dead_code)]
 
-use std::io::Read;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::io::Read;
 
 use crate::binary_reader::BinaryReader;
 use crate::error::SoundFontError;
+use crate::four_cc::FourCC;
 
 #[non_exhaustive]
 pub(crate) struct PresetInfo {
@@ -41,10 +45,12 @@ impl PresetInfo {
 
     pub(crate) fn read_from_chunk<R: Read>(
         reader: &mut R,
+        id: FourCC,
+        offset: u64,
         size: usize,
     ) -> Result<Vec<PresetInfo>, SoundFontError> {
-        if size % 38 != 0 {
-            return Err(SoundFontError::InvalidPresetList);
+        if size % 38 != 0 || size == 0 {
+            return Err(SoundFontError::InvalidPresetList { id, offset });
         }
 
         let count = size / 38;
@@ -0,0 +1,72 @@
+#![allow(dead_code)]
+
+use alloc::collections::BTreeMap;
+
+use crate::preset::Preset;
+
+// The lookup table is built once when the SoundFont is loaded,
+// and shared by all the synthesizers using the SoundFont.
+#[non_exhaustive]
+pub(crate) struct PresetLookup {
+    lookup: BTreeMap<i32, usize>,
+    default_preset: usize,
+}
+
+impl PresetLookup {
+    pub(crate) fn new(presets: &[Preset]) -> Self {
+        let mut lookup: BTreeMap<i32, usize> = BTreeMap::new();
+
+        let mut min_preset_id = i32::MAX;
+        let mut default_preset: usize = 0;
+        for (i, preset) in presets.iter().enumerate() {
+            // The preset ID is Int32, where the upper 16 bits represent the bank number
+            // and the lower 16 bits represent the patch number.
+            // This ID is used to search for presets by the combination of bank number
+            // and patch number.
+            let preset_id = (preset.bank_number << 16) | preset.patch_number;
+            lookup.insert(preset_id, i);
+
+            // The preset with the minimum ID number will be default.
+            // If the SoundFont is GM compatible, the piano will be chosen.
+            if preset_id < min_preset_id {
+                default_preset = i;
+                min_preset_id = preset_id;
+            }
+        }
+
+        Self {
+            lookup,
+            default_preset,
+        }
+    }
+
+    pub(crate) fn get(&self, bank_number: i32, patch_number: i32) -> Option<usize> {
+        self.lookup
+            .get(&((bank_number << 16) | patch_number))
+            .copied()
+    }
+
+    // Try fallback to the GM sound set.
+    // Normally, the given patch number + the bank number 0 will work.
+    // For drums (bank number >= 128), the same kit in the bank 128 is tried first,
+    // and then the standard set (128:0).
+    // If no corresponding preset was found, the default one is used.
+    pub(crate) fn get_fallback(&self, bank_number: i32, patch_number: i32) -> usize {
+        let gm_preset = if bank_number < 128 {
+            self.get(0, patch_number)
+        } else {
+            self.get(128, patch_number).or_else(|| self.get(128, 0))
+        };
+
+        gm_preset.unwrap_or(self.default_preset)
+    }
+
+    pub(crate) fn resolve(&self, bank_number: i32, patch_number: i32) -> usize {
+        self.get(bank_number, patch_number)
+            .unwrap_or_else(|| self.get_fallback(bank_number, patch_number))
+    }
+
+    pub(crate) fn get_default_preset(&self) -> usize {
+        self.default_preset
+    }
+}
@@ -0,0 +1,35 @@
+#![allow(dead_code)]
+
+use alloc::collections::BTreeMap;
+use core::time::Duration;
+
+use crate::voice::Voice;
+
+// The time spent for the voices, grouped by the preset they play.
+// The presets are identified by the bank and patch numbers,
+// so that the result is still valid after the SoundFont is replaced.
+#[non_exhaustive]
+pub(crate) struct PresetProfile {
+    pub(crate) items: BTreeMap<(i32, i32), (Duration, u64)>,
+}
+
+impl PresetProfile {
+    pub(crate) fn new() -> Self {
+        Self {
+            items: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn add(&mut self, voice: &Voice) {
+        let item = self
+            .items
+            .entry((voice.bank_number, voice.patch_number))
+            .or_insert((Duration::ZERO, 0));
+        item.0 += voice.process_time;
+        item.1 += 1;
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.items.clear();
+    }
+}
@@ -1,9 +1,12 @@
 #![allow(dead_code)]
 
+use alloc::vec::Vec;
+
 use crate::error::SoundFontError;
 use crate::generator::Generator;
 use crate::generator_type::GeneratorType;
 use crate::instrument::Instrument;
+use crate::soundfont::SoundFont;
 use crate::soundfont_math::SoundFontMath;
 use crate::zone::Zone;
 
@@ -18,6 +21,7 @@ fn set_parameter(gs: &mut [i16; GeneratorType::COUNT], generator: &Generator) {
 
 /// Represents a preset region.
 /// A preset region indicates how the parameters of the instrument should be modified in the preset.
+#[derive(Clone)]
 #[non_exhaustive]
 pub struct PresetRegion {
     pub(crate) gs: [i16; GeneratorType::COUNT],
@@ -31,9 +35,7 @@ impl PresetRegion {
         local: &Zone,
         samples: &[Instrument],
     ) -> Result<Self, SoundFontError> {
-        let mut gs: [i16; GeneratorType::COUNT] = [0; GeneratorType::COUNT];
-        gs[GeneratorType::KEY_RANGE as usize] = 0x7F00;
-        gs[GeneratorType::VELOCITY_RANGE as usize] = 0x7F00;
+        let mut gs = Self::default_generators();
 
         for generator in global.generators.iter() {
             set_parameter(&mut gs, generator);
@@ -43,7 +45,8 @@ impl PresetRegion {
             set_parameter(&mut gs, generator);
         }
 
-        let instrument_id = gs[GeneratorType::INSTRUMENT as usize] as usize;
+        // The generator value is unsigned.
+        let instrument_id = gs[GeneratorType::INSTRUMENT as usize] as u16 as usize;
         if instrument_id >= samples.len() {
             return Err(SoundFontError::InvalidInstrumentId {
                 preset_id,
@@ -57,6 +60,15 @@ impl PresetRegion {
         })
     }
 
+    // The default values of the generators, which are used when a zone does not specify them.
+    pub(crate) fn default_generators() -> [i16; GeneratorType::COUNT] {
+        let mut gs: [i16; GeneratorType::COUNT] = [0; GeneratorType::COUNT];
+        gs[GeneratorType::KEY_RANGE as usize] = 0x7F00;
+        gs[GeneratorType::VELOCITY_RANGE as usize] = 0x7F00;
+
+        gs
+    }
+
     pub(crate) fn create(
         preset_id: usize,
         zones: &[Zone],
@@ -302,4 +314,30 @@ impl PresetRegion {
     pub fn get_instrument_id(&self) -> usize {
         self.instrument
     }
+
+    /// Gets the instrument of the region.
+    ///
+    /// # Arguments
+    ///
+    /// * `sound_font` - The SoundFont which contains the region.
+    pub fn get_instrument<'a>(&self, sound_font: &'a SoundFont) -> &'a Instrument {
+        &sound_font.instruments[self.get_instrument_id()]
+    }
+
+    /// Gets the raw value of the generator, after the global zone is applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `generator_type` - The type of the generator. See `GeneratorType` for the values.
+    ///
+    /// # Remarks
+    ///
+    /// Unlike the other getters, the value is not converted to any unit.
+    pub fn get_generator(&self, generator_type: u16) -> i16 {
+        if generator_type as usize >= GeneratorType::COUNT {
+            panic!("The generator type must be less than GeneratorType::COUNT.");
+        }
+
+        self.gs[generator_type as usize]
+    }
 }
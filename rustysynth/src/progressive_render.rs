@@ -0,0 +1,61 @@
+#![allow(dead_code)]
+
+use alloc::vec::Vec;
+use std::panic;
+use std::thread::JoinHandle;
+
+/// The result of a progressive render, which is available before the rendering completes.
+#[non_exhaustive]
+pub struct ProgressiveRender {
+    preview_left: Vec<f32>,
+    preview_right: Vec<f32>,
+    handle: JoinHandle<(Vec<f32>, Vec<f32>)>,
+}
+
+impl ProgressiveRender {
+    pub(crate) fn new(
+        preview_left: Vec<f32>,
+        preview_right: Vec<f32>,
+        handle: JoinHandle<(Vec<f32>, Vec<f32>)>,
+    ) -> Self {
+        Self {
+            preview_left,
+            preview_right,
+            handle,
+        }
+    }
+
+    /// Gets the left channel of the preview, which is the beginning of the waveform.
+    pub fn get_preview_left(&self) -> &[f32] {
+        &self.preview_left[..]
+    }
+
+    /// Gets the right channel of the preview, which is the beginning of the waveform.
+    pub fn get_preview_right(&self) -> &[f32] {
+        &self.preview_right[..]
+    }
+
+    /// Gets a value that indicates whether the rest of the waveform has been rendered.
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Waits for the rendering to complete and returns the whole waveform.
+    ///
+    /// # Remarks
+    ///
+    /// The returned waveform starts with the preview.
+    pub fn wait(self) -> (Vec<f32>, Vec<f32>) {
+        let (rest_left, rest_right) = match self.handle.join() {
+            Ok(value) => value,
+            Err(err) => panic::resume_unwind(err),
+        };
+
+        let mut left = self.preview_left;
+        let mut right = self.preview_right;
+        left.extend_from_slice(&rest_left);
+        right.extend_from_slice(&rest_right);
+
+        (left, right)
+    }
+}
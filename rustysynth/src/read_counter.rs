@@ -1,4 +1,4 @@
-use std::io::{Read, Seek, SeekFrom};
+use crate::io::{self, Read, Seek, SeekFrom};
 
 pub(crate) struct ReadCounter<'a, R: Read + Seek> {
     reader: &'a mut R,
@@ -16,7 +16,7 @@ impl<'a, R: Read + Seek> ReadCounter<'a, R> {
 }
 
 impl<'a, R: Read + Seek> Read for ReadCounter<'a, R> {
-    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         let len = self.reader.read(buf)?;
         self.count += len;
         Ok(len)
@@ -24,11 +24,11 @@ impl<'a, R: Read + Seek> Read for ReadCounter<'a, R> {
 }
 
 impl<'a, R: Read + Seek> Seek for ReadCounter<'a, R> {
-    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
         match pos {
             SeekFrom::Start(n) => self.count = n as usize,
             SeekFrom::Current(n) => self.count += n as usize,
-            _ => unimplemented!() // QwQ
+            _ => unimplemented!(), // QwQ
         }
         self.reader.seek(pos)
     }
@@ -1,5 +1,6 @@
 #![allow(dead_code)]
 
+use crate::envelope_override::EnvelopeOverride;
 use crate::lfo::Lfo;
 use crate::modulation_envelope::ModulationEnvelope;
 use crate::oscillator::Oscillator;
@@ -12,7 +13,11 @@ use crate::volume_envelope::VolumeEnvelope;
 pub(crate) struct RegionEx {}
 
 impl RegionEx {
-    pub(crate) fn start_oscillator(oscillator: &mut Oscillator, region: &RegionPair) {
+    pub(crate) fn start_oscillator(
+        oscillator: &mut Oscillator,
+        region: &RegionPair,
+        sample_count: usize,
+    ) {
         let sample_rate = region.instrument.sample_sample_rate;
         let loop_mode = region.get_sample_modes();
         let start = region.get_sample_start();
@@ -35,19 +40,36 @@ impl RegionEx {
             coarse_tune,
             fine_tune,
             scale_tuning,
+            sample_count,
         );
     }
 
     pub(crate) fn start_volume_envelope(
+        envelope: &mut VolumeEnvelope,
+        region: &RegionPair,
+        key: i32,
+        velocity: i32,
+    ) {
+        RegionEx::start_scaled_volume_envelope(
+            envelope,
+            region,
+            key,
+            velocity,
+            &EnvelopeOverride::new(),
+        );
+    }
+
+    pub(crate) fn start_scaled_volume_envelope(
         envelope: &mut VolumeEnvelope,
         region: &RegionPair,
         key: i32,
         _velocity: i32,
+        scale: &EnvelopeOverride,
     ) {
         // If the release time is shorter than 10 ms, it will be clamped to 10 ms to avoid pop noise.
 
         let delay = region.get_delay_volume_envelope();
-        let attack = region.get_attack_volume_envelope();
+        let attack = region.get_attack_volume_envelope() * scale.attack;
         let hold = region.get_hold_volume_envelope()
             * SoundFontMath::key_number_to_multiplying_factor(
                 region.get_key_number_to_volume_envelope_hold(),
@@ -57,9 +79,14 @@ impl RegionEx {
             * SoundFontMath::key_number_to_multiplying_factor(
                 region.get_key_number_to_volume_envelope_decay(),
                 key,
-            );
-        let sustain = SoundFontMath::decibels_to_linear(-region.get_sustain_volume_envelope());
-        let release = SoundFontMath::max(region.get_release_volume_envelope(), 0.01_f32);
+            )
+            * scale.decay;
+        let sustain = SoundFontMath::decibels_to_linear(-region.get_sustain_volume_envelope())
+            * scale.sustain;
+        let release = SoundFontMath::max(
+            region.get_release_volume_envelope() * scale.release,
+            0.01_f32,
+        );
 
         envelope.start(delay, attack, hold, decay, sustain, release);
     }
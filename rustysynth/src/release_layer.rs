@@ -0,0 +1,65 @@
+#![allow(dead_code)]
+
+/// Specifies the preset which is played when the notes of another preset are released.
+///
+/// # Remarks
+///
+/// This is used for the banks which have the release samples, such as the damper noises of a piano
+/// or the key-off clicks of a harpsichord, as separate presets.
+/// The layer is triggered when the note is actually released, so the notes held by the hold pedal
+/// trigger it when the pedal is released.
+/// The note is played with the same key, velocity and detune as the released one.
+/// The samples of the layer are played once without the loop, even if the regions loop,
+/// as the note is not stopped by `note_off`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ReleaseLayer {
+    /// The bank number of the preset which is played.
+    pub bank_number: i32,
+    /// The patch number of the preset which is played.
+    pub patch_number: i32,
+    /// The gain in decibels.
+    pub gain: f32,
+    /// The decrease of the gain in decibels per second the note was held.
+    ///
+    /// The strings of a piano lose their energy while the key is held,
+    /// so the damper noise is quieter after a long note.
+    pub decay: f32,
+}
+
+impl ReleaseLayer {
+    // The layer is not played below this gain.
+    const MIN_GAIN: f32 = -100_f32;
+
+    /// Initializes a new instance of the release layer, which plays the preset as it is.
+    ///
+    /// # Arguments
+    ///
+    /// * `bank_number` - The bank number of the preset.
+    /// * `patch_number` - The patch number of the preset.
+    pub fn new(bank_number: i32, patch_number: i32) -> Self {
+        Self {
+            bank_number,
+            patch_number,
+            gain: 0_f32,
+            decay: 0_f32,
+        }
+    }
+
+    // Gets the gain in decibels for the note held for the specified seconds.
+    pub(crate) fn get_gain(&self, seconds: f32) -> Option<f32> {
+        let gain = self.gain - self.decay * seconds;
+        if gain > ReleaseLayer::MIN_GAIN {
+            Some(gain)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        (0..128).contains(&self.patch_number)
+            && self.gain.is_finite()
+            && self.decay.is_finite()
+            && self.decay >= 0_f32
+    }
+}
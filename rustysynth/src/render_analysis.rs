@@ -0,0 +1,117 @@
+use alloc::vec::Vec;
+
+use crate::diagnostics::Diagnostics;
+
+/// Represents the analysis of a track by `ThreadedRender::analyze`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct TrackAnalysis {
+    pub(crate) index: usize,
+    pub(crate) event_count: usize,
+    pub(crate) note_count: usize,
+    pub(crate) length: f64,
+    pub(crate) sample_count: usize,
+    pub(crate) segment_count: usize,
+    pub(crate) presets: Vec<(i32, i32)>,
+}
+
+impl TrackAnalysis {
+    /// Gets the index of the track, or the channel in the channel split mode.
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    /// Gets the number of the events in the track, including the expanded loop.
+    pub fn get_event_count(&self) -> usize {
+        self.event_count
+    }
+
+    /// Gets the number of the note-on events in the track.
+    pub fn get_note_count(&self) -> usize {
+        self.note_count
+    }
+
+    /// Gets the length of the events in seconds.
+    pub fn get_length(&self) -> f64 {
+        self.length
+    }
+
+    /// Gets the maximum number of the sample frames to be rendered for the track.
+    ///
+    /// # Remarks
+    ///
+    /// This includes the tail, and the tail of each segment if the track is split.
+    /// A tail which continues until silence is counted with its maximum length.
+    pub fn get_sample_count(&self) -> usize {
+        self.sample_count
+    }
+
+    /// Gets the number of the segments which the track is split into.
+    pub fn get_segment_count(&self) -> usize {
+        self.segment_count
+    }
+
+    /// Gets the presets used by the track as pairs of the bank and patch numbers.
+    pub fn get_used_presets(&self) -> &[(i32, i32)] {
+        &self.presets
+    }
+}
+
+/// Represents the estimated cost of the rendering by `ThreadedRender`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct RenderAnalysis {
+    pub(crate) tracks: Vec<TrackAnalysis>,
+    pub(crate) sample_count: usize,
+    pub(crate) total_sample_count: u64,
+    pub(crate) thread_count: usize,
+    pub(crate) peak_memory: usize,
+    pub(crate) diagnostics: Diagnostics,
+}
+
+impl RenderAnalysis {
+    /// Gets the analysis of each track which will be rendered.
+    pub fn get_tracks(&self) -> &[TrackAnalysis] {
+        &self.tracks
+    }
+
+    /// Gets the maximum length of the output in sample frames.
+    pub fn get_sample_count(&self) -> usize {
+        self.sample_count
+    }
+
+    /// Gets the number of the sample frames to be rendered, summed over all the tracks.
+    ///
+    /// # Remarks
+    ///
+    /// The render time is roughly proportional to this value.
+    /// This is the same as the total of the render progress.
+    pub fn get_total_sample_count(&self) -> u64 {
+        self.total_sample_count
+    }
+
+    /// Gets the number of the worker threads used by the rendering.
+    pub fn get_thread_count(&self) -> usize {
+        self.thread_count
+    }
+
+    /// Gets the estimated peak memory usage of the rendering in bytes.
+    ///
+    /// # Remarks
+    ///
+    /// The estimate includes the parsed tracks, the output, and the synthesizers and buffers of the busiest workers.
+    /// The SoundFont is shared and not included.
+    pub fn get_estimated_peak_memory(&self) -> usize {
+        self.peak_memory
+    }
+
+    /// Gets the problems found by the analysis.
+    ///
+    /// # Remarks
+    ///
+    /// This includes the tracks which failed to load, the presets not found in the SoundFont,
+    /// and the features of the SoundFont which are not supported.
+    pub fn get_diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+}
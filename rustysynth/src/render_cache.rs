@@ -0,0 +1,270 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::vec::Vec;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::soundfont::SoundFont;
+
+// The left and right channels of a rendered track.
+type Track = (Vec<f32>, Vec<f32>);
+
+/// Keeps the rendered tracks of `ThreadedRender`, so that only the modified tracks are rendered again.
+///
+/// # Remarks
+///
+/// Each track is identified by its events and everything else which changes its sound,
+/// such as the synthesizer settings, the automations and the SoundFont.
+/// The gain and the pan of the track mix are applied after the cache,
+/// so changing them never renders the track again.
+/// The same cache can be shared by the renderers of different MIDI files.
+///
+/// The SoundFont is identified by its presets, instruments and sample headers, but not by the sample data,
+/// so the cache must be cleared when only the sample data of the SoundFont is modified.
+pub struct RenderCache {
+    directory: Option<PathBuf>,
+    entries: Mutex<BTreeMap<u64, Arc<Track>>>,
+    hit_count: AtomicUsize,
+    miss_count: AtomicUsize,
+}
+
+impl RenderCache {
+    const EXTENSION: &'static str = "pcm";
+
+    /// Initializes a new cache, which keeps the tracks in memory.
+    pub fn new() -> Self {
+        Self {
+            directory: None,
+            entries: Mutex::new(BTreeMap::new()),
+            hit_count: AtomicUsize::new(0),
+            miss_count: AtomicUsize::new(0),
+        }
+    }
+
+    /// Initializes a new cache, which keeps the tracks as files in the directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory` - The directory of the cache files.
+    ///
+    /// # Remarks
+    ///
+    /// The directory is created if it does not exist.
+    /// The files are reused by the later processes with the same directory,
+    /// and the tracks are not kept in memory.
+    /// A file which cannot be read or written is regarded as missing.
+    pub fn with_directory<P: AsRef<Path>>(directory: P) -> Result<Self, io::Error> {
+        let directory = directory.as_ref();
+        fs::create_dir_all(directory)?;
+
+        Ok(Self {
+            directory: Some(directory.to_path_buf()),
+            ..RenderCache::new()
+        })
+    }
+
+    pub(crate) fn get(&self, key: u64) -> Option<Arc<Track>> {
+        let value = match &self.directory {
+            Some(directory) => RenderCache::read_file(&RenderCache::get_path(directory, key))
+                .ok()
+                .map(Arc::new),
+            None => self.entries.lock().unwrap().get(&key).cloned(),
+        };
+
+        match value {
+            Some(_) => self.hit_count.fetch_add(1, Ordering::SeqCst),
+            None => self.miss_count.fetch_add(1, Ordering::SeqCst),
+        };
+
+        value
+    }
+
+    pub(crate) fn insert(&self, key: u64, left: &[f32], right: &[f32]) {
+        match &self.directory {
+            Some(directory) => {
+                // A broken file is never reused, since its size does not match.
+                let _ =
+                    RenderCache::write_file(&RenderCache::get_path(directory, key), left, right);
+            }
+            None => {
+                self.entries
+                    .lock()
+                    .unwrap()
+                    .insert(key, Arc::new((left.to_vec(), right.to_vec())));
+            }
+        }
+    }
+
+    /// Removes all the tracks from the cache.
+    ///
+    /// # Remarks
+    ///
+    /// For a cache with a directory, the cache files in the directory are deleted.
+    pub fn clear(&self) -> Result<(), io::Error> {
+        self.entries.lock().unwrap().clear();
+
+        if let Some(directory) = &self.directory {
+            for entry in fs::read_dir(directory)? {
+                let path = entry?.path();
+                if path
+                    .extension()
+                    .is_some_and(|x| x == RenderCache::EXTENSION)
+                {
+                    fs::remove_file(path)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the number of the tracks reused from the cache.
+    pub fn get_hit_count(&self) -> usize {
+        self.hit_count.load(Ordering::SeqCst)
+    }
+
+    /// Gets the number of the tracks which were not found in the cache and were rendered.
+    pub fn get_miss_count(&self) -> usize {
+        self.miss_count.load(Ordering::SeqCst)
+    }
+
+    fn get_path(directory: &Path, key: u64) -> PathBuf {
+        directory.join(format!("{:016x}.{}", key, RenderCache::EXTENSION))
+    }
+
+    fn read_file(path: &Path) -> Result<(Vec<f32>, Vec<f32>), io::Error> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut length = [0_u8; 8];
+        reader.read_exact(&mut length)?;
+        let length = u64::from_le_bytes(length) as usize;
+
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        if data.len() != 8 * length {
+            return Err(io::Error::from(io::ErrorKind::InvalidData));
+        }
+
+        let mut samples = data
+            .chunks_exact(4)
+            .map(|x| f32::from_le_bytes([x[0], x[1], x[2], x[3]]));
+        let left = samples.by_ref().take(length).collect();
+        let right = samples.collect();
+
+        Ok((left, right))
+    }
+
+    fn write_file(path: &Path, left: &[f32], right: &[f32]) -> Result<(), io::Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writer.write_all(&(left.len() as u64).to_le_bytes())?;
+        for value in left.iter().chain(right.iter()) {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+
+        writer.flush()
+    }
+}
+
+impl Default for RenderCache {
+    fn default() -> Self {
+        RenderCache::new()
+    }
+}
+
+// Computes the key of a track by the 64-bit FNV-1a hash,
+// which stays the same across the processes unlike the default hasher.
+#[derive(Clone)]
+#[non_exhaustive]
+pub(crate) struct CacheKey {
+    hash: u64,
+}
+
+impl CacheKey {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    pub(crate) fn new() -> Self {
+        Self {
+            hash: CacheKey::OFFSET_BASIS,
+        }
+    }
+
+    pub(crate) fn write_bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.hash ^= *byte as u64;
+            self.hash = self.hash.wrapping_mul(CacheKey::PRIME);
+        }
+    }
+
+    pub(crate) fn write_u64(&mut self, value: u64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub(crate) fn write_f64(&mut self, value: f64) {
+        self.write_u64(value.to_bits());
+    }
+
+    pub(crate) fn write_str(&mut self, value: &str) {
+        // The length keeps the adjacent strings from being mixed up.
+        self.write_u64(value.len() as u64);
+        self.write_bytes(value.as_bytes());
+    }
+
+    pub(crate) fn write_sound_font(&mut self, sound_font: &SoundFont) {
+        let info = &sound_font.info;
+        self.write_str(&info.bank_name);
+        self.write_str(&info.creation_date);
+        self.write_str(&info.tools);
+        self.write_u64(sound_font.bits_per_sample as u64);
+        self.write_u64(sound_font.get_wave_data().len() as u64);
+        for (wave_data, _) in sound_font.merged_wave_data.iter() {
+            self.write_u64(wave_data.len() as u64);
+        }
+
+        for preset in sound_font.presets.iter() {
+            self.write_str(&preset.name);
+            self.write_u64(preset.bank_number as u64);
+            self.write_u64(preset.patch_number as u64);
+            for region in preset.regions.iter() {
+                self.write_u64(region.instrument as u64);
+                for value in region.gs.iter() {
+                    self.write_bytes(&value.to_le_bytes());
+                }
+            }
+        }
+
+        for instrument in sound_font.instruments.iter() {
+            self.write_str(&instrument.name);
+            for region in instrument.regions.iter() {
+                self.write_u64(region.sample as u64);
+                for value in region.gs.iter() {
+                    self.write_bytes(&value.to_le_bytes());
+                }
+            }
+        }
+
+        for header in sound_font.sample_headers.iter() {
+            self.write_str(&header.name);
+            for value in [
+                header.start,
+                header.end,
+                header.start_loop,
+                header.end_loop,
+                header.sample_rate,
+                header.original_pitch as i32,
+                header.pitch_correction as i32,
+            ] {
+                self.write_bytes(&value.to_le_bytes());
+            }
+            self.write_u64(header.source as u64);
+        }
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.hash
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Represents the progress of the rendering, which can be read from another thread.
+///
+/// # Remarks
+///
+/// The progress is counted in sample frames, summed over all the tracks,
+/// so it advances smoothly even if most of the MIDI file is in a single track.
+#[non_exhaustive]
+pub struct RenderProgress {
+    rendered_sample_count: AtomicU64,
+    total_sample_count: AtomicU64,
+}
+
+impl RenderProgress {
+    pub(crate) fn new() -> Self {
+        Self {
+            rendered_sample_count: AtomicU64::new(0),
+            total_sample_count: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn start(&self, total_sample_count: u64) {
+        self.rendered_sample_count.store(0, Ordering::SeqCst);
+        self.total_sample_count
+            .store(total_sample_count, Ordering::SeqCst);
+    }
+
+    pub(crate) fn add(&self, sample_count: usize) {
+        self.rendered_sample_count
+            .fetch_add(sample_count as u64, Ordering::SeqCst);
+    }
+
+    /// Gets the number of the sample frames rendered so far, summed over all the tracks.
+    pub fn get_rendered_sample_count(&self) -> u64 {
+        self.rendered_sample_count.load(Ordering::SeqCst)
+    }
+
+    /// Gets the number of the sample frames to be rendered, summed over all the tracks.
+    ///
+    /// # Remarks
+    ///
+    /// The value is zero until the rendering starts.
+    /// If the tail continues until silence, its maximum length is counted,
+    /// and the unused part is added to the rendered count when the track ends.
+    pub fn get_total_sample_count(&self) -> u64 {
+        self.total_sample_count.load(Ordering::SeqCst)
+    }
+
+    /// Gets the progress as a value between 0 and 1.
+    pub fn get_ratio(&self) -> f64 {
+        let total = self.get_total_sample_count();
+        if total == 0 {
+            return 0.0;
+        }
+
+        (self.get_rendered_sample_count() as f64 / total as f64).min(1.0)
+    }
+}
@@ -0,0 +1,179 @@
+use alloc::vec::Vec;
+
+use crate::loudness::Loudness;
+
+/// Represents the levels of a rendered waveform.
+///
+/// # Remarks
+///
+/// The values are linear, where 1 is the full scale, and are measured over both channels.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct LevelReport {
+    pub(crate) sample_count: usize,
+    pub(crate) peak: f32,
+    pub(crate) true_peak: f32,
+    pub(crate) sum: f64,
+    pub(crate) square_sum: f64,
+    pub(crate) clipped_sample_count: usize,
+    // The sums of the products of the channels, for the mono compatibility.
+    pub(crate) left_square_sum: f64,
+    pub(crate) right_square_sum: f64,
+    pub(crate) product_sum: f64,
+}
+
+impl LevelReport {
+    /// Measures the levels of a stereo waveform.
+    ///
+    /// # Arguments
+    ///
+    /// * `left` - The left channel of the waveform.
+    /// * `right` - The right channel of the waveform.
+    ///
+    /// # Remarks
+    ///
+    /// This allows to check the output of `Synthesizer` or `MidiFileSequencer`
+    /// in the same way as the report of `ThreadedRender`.
+    /// The channels must be the same length.
+    pub fn measure(left: &[f32], right: &[f32]) -> Self {
+        if left.len() != right.len() {
+            panic!("The output buffers for the left and right must be the same length.");
+        }
+
+        let mut report = Self {
+            sample_count: left.len() + right.len(),
+            true_peak: Loudness::true_peak(left).max(Loudness::true_peak(right)),
+            ..Default::default()
+        };
+        for x in left.iter().chain(right.iter()) {
+            report.peak = report.peak.max(x.abs());
+            report.sum += *x as f64;
+            report.square_sum += *x as f64 * *x as f64;
+            if x.abs() > 1_f32 {
+                report.clipped_sample_count += 1;
+            }
+        }
+        for (l, r) in left.iter().zip(right.iter()) {
+            report.left_square_sum += *l as f64 * *l as f64;
+            report.right_square_sum += *r as f64 * *r as f64;
+            report.product_sum += *l as f64 * *r as f64;
+        }
+        report
+    }
+
+    pub(crate) fn merge(&mut self, other: &LevelReport) {
+        self.sample_count += other.sample_count;
+        self.peak = self.peak.max(other.peak);
+        self.true_peak = self.true_peak.max(other.true_peak);
+        self.sum += other.sum;
+        self.square_sum += other.square_sum;
+        self.clipped_sample_count += other.clipped_sample_count;
+        self.left_square_sum += other.left_square_sum;
+        self.right_square_sum += other.right_square_sum;
+        self.product_sum += other.product_sum;
+    }
+
+    /// Gets the number of the samples measured, counting both channels.
+    pub fn get_sample_count(&self) -> usize {
+        self.sample_count
+    }
+
+    /// Gets the maximum absolute value of the samples.
+    pub fn get_peak(&self) -> f32 {
+        self.peak
+    }
+
+    /// Gets the maximum absolute value of the waveform between the samples.
+    ///
+    /// # Remarks
+    ///
+    /// The waveform is oversampled by 4x as specified in ITU-R BS.1770-4.
+    /// A true peak above 1 can clip in the DAC or the lossy encoder even if the samples do not.
+    pub fn get_true_peak(&self) -> f32 {
+        self.true_peak
+    }
+
+    /// Gets the root mean square of the samples.
+    pub fn get_rms(&self) -> f32 {
+        if self.sample_count == 0 {
+            return 0_f32;
+        }
+        (self.square_sum / self.sample_count as f64).sqrt() as f32
+    }
+
+    /// Gets the number of the samples whose absolute value exceeds 1.
+    pub fn get_clipped_sample_count(&self) -> usize {
+        self.clipped_sample_count
+    }
+
+    /// Gets the correlation between the left and right channels.
+    ///
+    /// # Remarks
+    ///
+    /// The value is between -1 and 1, where 1 is mono and 0 is unrelated channels.
+    /// A negative value means that the channels are out of phase,
+    /// and the output collapses badly when folded down to mono.
+    /// Silence is regarded as mono.
+    pub fn get_correlation(&self) -> f32 {
+        let denominator = (self.left_square_sum * self.right_square_sum).sqrt();
+        if denominator == 0.0 {
+            return 1_f32;
+        }
+        (self.product_sum / denominator) as f32
+    }
+
+    /// Gets the loss of the level in decibels when the output is folded down to mono.
+    ///
+    /// # Remarks
+    ///
+    /// The mono output is the average of the channels, which is compared with the average power of the channels.
+    /// The loss is 0 for mono, about 3 dB for unrelated channels,
+    /// and increases without limit as the channels cancel out each other.
+    /// Silence has no loss.
+    pub fn get_mono_fold_down_loss(&self) -> f32 {
+        let stereo = 0.5 * (self.left_square_sum + self.right_square_sum);
+        if stereo == 0.0 {
+            return 0_f32;
+        }
+        let mono = 0.25 * (self.left_square_sum + 2.0 * self.product_sum + self.right_square_sum);
+        if mono <= 0.0 {
+            return f32::INFINITY;
+        }
+        (10.0 * (stereo / mono).log10()) as f32
+    }
+
+    /// Gets the mean of the samples.
+    pub fn get_dc_offset(&self) -> f32 {
+        if self.sample_count == 0 {
+            return 0_f32;
+        }
+        (self.sum / self.sample_count as f64) as f32
+    }
+}
+
+/// Represents the levels of the output of `ThreadedRender::render`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct RenderReport {
+    pub(crate) tracks: Vec<LevelReport>,
+    pub(crate) master: LevelReport,
+}
+
+impl RenderReport {
+    /// Gets the levels of each track, or each channel in the channel split mode.
+    ///
+    /// # Remarks
+    ///
+    /// The tracks are measured before the mixing and the normalization.
+    /// If a track is split into segments, the tail of each segment is measured
+    /// separately from the next one, where they overlap.
+    /// A track which was not rendered has no samples.
+    pub fn get_tracks(&self) -> &[LevelReport] {
+        &self.tracks
+    }
+
+    /// Gets the levels of the final output.
+    pub fn get_master(&self) -> &LevelReport {
+        &self.master
+    }
+}
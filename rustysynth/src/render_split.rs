@@ -0,0 +1,15 @@
+/// Specifies how `ThreadedRender` divides a MIDI file into the parts rendered in parallel.
+///
+/// # Remarks
+///
+/// Each part is rendered by its own synthesizer, and the results are summed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RenderSplit {
+    /// Each track is rendered separately.
+    Track,
+
+    /// Each MIDI channel is rendered separately, regardless of the tracks.
+    /// This restores the parallelism for format 0 files, where all the events are in a single track.
+    Channel,
+}
@@ -0,0 +1,37 @@
+/// Specifies how long the sound is rendered after the last event of a MIDI file.
+///
+/// # Remarks
+///
+/// Without the tail, the reverb and the release of the last notes are cut off.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum RenderTail {
+    /// The waveform ends at the last event.
+    None,
+
+    /// The waveform continues for the specified number of seconds after the last event.
+    Fixed(f64),
+
+    /// The waveform continues until the sound falls below the threshold,
+    /// but not longer than the maximum length.
+    UntilSilence {
+        /// The threshold in dBFS, such as -90.
+        threshold: f64,
+        /// The maximum length of the tail in seconds.
+        max_length: f64,
+    },
+}
+
+impl RenderTail {
+    pub(crate) fn check(&self) {
+        match *self {
+            RenderTail::Fixed(tail_length) if tail_length < 0.0 => {
+                panic!("The tail length must be a non-negative value.")
+            }
+            RenderTail::UntilSilence { max_length, .. } if max_length < 0.0 => {
+                panic!("The maximum length of the tail must be a non-negative value.")
+            }
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use std::f64::consts;
+
+// Converts the sample rate of a waveform by the windowed sinc interpolation.
+// The filter is precomputed for each fractional position of the output samples,
+// which repeats with the ratio of the rates reduced to the lowest terms.
+#[non_exhaustive]
+pub(crate) struct Resampler {
+    up: usize,
+    down: usize,
+    half_length: usize,
+    phase_count: usize,
+    table: Vec<f32>,
+}
+
+impl Resampler {
+    // The number of the zero crossings of the sinc function on each side.
+    const ZERO_CROSSINGS: f64 = 16.0;
+    // The passband edge relative to the Nyquist frequency of the lower rate.
+    const ROLLOFF: f64 = 0.95;
+    // The phases are quantized above this count to keep the table small.
+    const MAX_PHASE_COUNT: usize = 4096;
+
+    pub(crate) fn new(source_rate: i32, target_rate: i32) -> Self {
+        let gcd = Resampler::gcd(source_rate as usize, target_rate as usize);
+        let up = target_rate as usize / gcd;
+        let down = source_rate as usize / gcd;
+
+        // The cutoff in cycles per input sample, below the Nyquist frequency of the lower rate.
+        let cutoff = 0.5 * Resampler::ROLLOFF * (up as f64 / down as f64).min(1.0);
+        let width = Resampler::ZERO_CROSSINGS / (2.0 * cutoff);
+        let half_length = width.ceil() as usize;
+
+        let phase_count = up.min(Resampler::MAX_PHASE_COUNT);
+        let mut table = vec![0_f32; phase_count * 2 * half_length];
+        for phase in 0..phase_count {
+            let fraction = phase as f64 / phase_count as f64;
+            let taps = &mut table[phase * 2 * half_length..(phase + 1) * 2 * half_length];
+            for (j, tap) in taps.iter_mut().enumerate() {
+                let t = (j as f64 - half_length as f64 + 1.0) - fraction;
+                *tap = (2.0
+                    * cutoff
+                    * Resampler::sinc(2.0 * cutoff * t)
+                    * Resampler::window(t / width)) as f32;
+            }
+        }
+
+        Self {
+            up,
+            down,
+            half_length,
+            phase_count,
+            table,
+        }
+    }
+
+    pub(crate) fn process(&self, input: &[f32]) -> Vec<f32> {
+        if self.up == self.down {
+            return input.to_vec();
+        }
+
+        let output_length = (input.len() * self.up).div_ceil(self.down);
+        let mut output = vec![0_f32; output_length];
+
+        let length = 2 * self.half_length;
+        for (n, value) in output.iter_mut().enumerate() {
+            let position = n * self.down;
+            let index = position / self.up;
+            let phase = (position % self.up) * self.phase_count / self.up;
+            let taps = &self.table[phase * length..(phase + 1) * length];
+
+            // The samples outside the input are regarded as zero.
+            let first = index as isize - self.half_length as isize + 1;
+            let mut sum = 0_f32;
+            for (j, tap) in taps.iter().enumerate() {
+                let k = first + j as isize;
+                if 0 <= k && (k as usize) < input.len() {
+                    sum += tap * input[k as usize];
+                }
+            }
+            *value = sum;
+        }
+
+        output
+    }
+
+    // Converts a position in samples at the source rate to the target rate.
+    pub(crate) fn convert_position(&self, position: usize) -> usize {
+        (position * self.up + self.down / 2) / self.down
+    }
+
+    fn sinc(x: f64) -> f64 {
+        if x == 0.0 {
+            1.0
+        } else {
+            (consts::PI * x).sin() / (consts::PI * x)
+        }
+    }
+
+    // The 4-term Blackman-Harris window, where x is between -1 and 1.
+    fn window(x: f64) -> f64 {
+        if x.abs() >= 1.0 {
+            return 0.0;
+        }
+        let a = consts::PI * (x + 1.0);
+        0.35875 - 0.48829 * a.cos() + 0.14128 * (2.0 * a).cos() - 0.01168 * (3.0 * a).cos()
+    }
+
+    fn gcd(a: usize, b: usize) -> usize {
+        if b == 0 {
+            a
+        } else {
+            Resampler::gcd(b, a % b)
+        }
+    }
+}
@@ -1,7 +1,13 @@
 #![allow(dead_code)]
 
-use std::cmp;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp;
+use core::mem;
 
+use crate::float_math::FloatMath;
+
+#[derive(Clone)]
 #[non_exhaustive]
 pub(crate) struct Reverb {
     cfs_l: Vec<CombFilter>,
@@ -126,6 +132,15 @@ impl Reverb {
         reverb
     }
 
+    // The size of the delay lines in bytes.
+    pub(crate) fn get_memory_size(&self) -> usize {
+        let comb_filters = self.cfs_l.iter().chain(self.cfs_r.iter());
+        let all_pass_filters = self.apfs_l.iter().chain(self.apfs_r.iter());
+        let length: usize = comb_filters.map(|x| x.buffer.len()).sum::<usize>()
+            + all_pass_filters.map(|x| x.buffer.len()).sum::<usize>();
+        length * mem::size_of::<f32>()
+    }
+
     pub fn mute(&mut self) {
         for cf in self.cfs_l.iter_mut() {
             cf.mute();
@@ -145,7 +160,7 @@ impl Reverb {
     }
 
     fn scale_tuning(sample_rate: i32, tuning: usize) -> usize {
-        ((sample_rate as f64) / 44100_f64 * (tuning as f64)).round() as usize
+        FloatMath::round_f64((sample_rate as f64) / 44100_f64 * (tuning as f64)) as usize
     }
 
     pub(crate) fn process(
@@ -232,6 +247,7 @@ impl Reverb {
     }
 }
 
+#[derive(Clone)]
 #[non_exhaustive]
 struct CombFilter {
     buffer: Vec<f32>,
@@ -319,6 +335,7 @@ impl CombFilter {
     }
 }
 
+#[derive(Clone)]
 #[non_exhaustive]
 struct AllPassFilter {
     buffer: Vec<f32>,
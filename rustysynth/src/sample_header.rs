@@ -1,11 +1,16 @@
 #![allow(dead_code)]
 
-use std::io::Read;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::io::Read;
 
 use crate::binary_reader::BinaryReader;
 use crate::error::SoundFontError;
+use crate::four_cc::FourCC;
 
 /// Represents a sample in the SoundFont.
+#[derive(Clone)]
 #[non_exhaustive]
 pub struct SampleHeader {
     pub(crate) name: String,
@@ -18,6 +23,8 @@ pub struct SampleHeader {
     pub(crate) pitch_correction: i8,
     pub(crate) link: u16,
     pub(crate) sample_type: u16,
+    // The index of the sample data to which the positions refer. See `SoundFont::get_wave_slice`.
+    pub(crate) source: usize,
 }
 
 impl SampleHeader {
@@ -44,15 +51,18 @@ impl SampleHeader {
             pitch_correction,
             link,
             sample_type,
+            source: 0,
         })
     }
 
     pub(crate) fn read_from_chunk<R: Read>(
         reader: &mut R,
+        id: FourCC,
+        offset: u64,
         size: usize,
     ) -> Result<Vec<SampleHeader>, SoundFontError> {
-        if size % 46 != 0 {
-            return Err(SoundFontError::InvalidSampleHeaderList);
+        if size % 46 != 0 || size == 0 {
+            return Err(SoundFontError::InvalidSampleHeaderList { id, offset });
         }
 
         let count = size / 46 - 1;
@@ -0,0 +1,75 @@
+#![allow(dead_code)]
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts;
+use core::mem;
+
+use crate::float_math::FloatMath;
+
+// A transient voice which plays a raw waveform given by the user, without the SoundFont.
+#[derive(Clone)]
+#[non_exhaustive]
+pub(crate) struct SampleVoice {
+    data: Vec<f32>,
+    position: f64,
+    step: f64,
+
+    pub(crate) gain_left: f32,
+    pub(crate) gain_right: f32,
+
+    pub(crate) block: Vec<f32>,
+}
+
+impl SampleVoice {
+    pub(crate) fn new(data: &[f32], pitch: f32, pan: f32, gain: f32, block_size: usize) -> Self {
+        // The same equal-power panning as the voices.
+        let angle = (consts::PI / 4_f32) * (pan.clamp(-1_f32, 1_f32) + 1_f32);
+
+        Self {
+            data: data.to_vec(),
+            position: 0_f64,
+            step: FloatMath::powf(2_f32, pitch / 12_f32) as f64,
+            gain_left: gain * FloatMath::cos(angle),
+            gain_right: gain * FloatMath::sin(angle),
+            block: vec![0_f32; block_size],
+        }
+    }
+
+    pub(crate) fn get_memory_size(&self) -> usize {
+        mem::size_of::<SampleVoice>() + (self.data.len() + self.block.len()) * mem::size_of::<f32>()
+    }
+
+    pub(crate) fn set_block_size(&mut self, block_size: usize) {
+        self.block.resize(block_size, 0_f32);
+    }
+
+    // Returns false if the end of the waveform has been reached before the block.
+    pub(crate) fn process(&mut self) -> bool {
+        let length = self.data.len();
+        if self.position >= length as f64 {
+            return false;
+        }
+
+        for value in self.block.iter_mut() {
+            let index = self.position as usize;
+            if index >= length {
+                *value = 0_f32;
+                continue;
+            }
+
+            // The sample after the end is regarded as zero.
+            let next = if index + 1 < length {
+                self.data[index + 1]
+            } else {
+                0_f32
+            };
+            let a = (self.position - index as f64) as f32;
+            *value = self.data[index] + a * (next - self.data[index]);
+
+            self.position += self.step;
+        }
+
+        true
+    }
+}
@@ -0,0 +1,101 @@
+#![allow(dead_code)]
+
+use alloc::vec::Vec;
+
+use crate::midifile::{Message, MidiTrack};
+use crate::synthesizer::Synthesizer;
+
+/// Represents a list of MIDI messages built by code, which can be played without a MIDI file.
+///
+/// # Remarks
+///
+/// The events can be pushed in any order, and the events at the same time are played in the pushed order.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct Sequence {
+    events: Vec<(f64, u8, u8, u8, u8)>,
+}
+
+impl Sequence {
+    /// Initializes a new empty sequence.
+    pub fn new() -> Self {
+        Self { events: Vec::new() }
+    }
+
+    /// Adds a MIDI message to the sequence.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time of the message in seconds.
+    /// * `channel` - The channel to which the message will be sent,
+    ///   where the channels from 16 are those of the following MIDI ports.
+    /// * `command` - The type of the message, such as 0x90 for a note-on.
+    /// * `data1` - The first data part of the message.
+    /// * `data2` - The second data part of the message.
+    pub fn push(&mut self, time: f64, channel: i32, command: i32, data1: i32, data2: i32) {
+        if time.is_nan() || time < 0.0 {
+            panic!("The time must be a non-negative value.");
+        }
+
+        if !(0..Synthesizer::MAX_CHANNEL_COUNT as i32).contains(&channel) {
+            panic!("The channel must be between 0 and 127.");
+        }
+
+        if !(0x80..0xF0).contains(&command) || command & 0x0F != 0 {
+            panic!("The command must be a channel message type between 0x80 and 0xE0.");
+        }
+
+        if !(0..128).contains(&data1) || !(0..128).contains(&data2) {
+            panic!("The data must be between 0 and 127.");
+        }
+
+        self.events
+            .push((time, channel as u8, command as u8, data1 as u8, data2 as u8));
+    }
+
+    /// Removes all the events.
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    /// Gets the number of the events in the sequence.
+    pub fn get_event_count(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Gets the time of the last event in seconds.
+    pub fn get_length(&self) -> f64 {
+        self.events.iter().fold(0.0, |max, event| max.max(event.0))
+    }
+
+    /// Converts the sequence to a MIDI track.
+    ///
+    /// # Remarks
+    ///
+    /// The track ends at the last event.
+    pub fn to_track(&self) -> MidiTrack {
+        let mut events = self.events.clone();
+        events.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+        let mut messages = Vec::with_capacity(events.len() + 1);
+        let mut times = Vec::with_capacity(events.len() + 1);
+        for (time, channel, command, data1, data2) in events {
+            messages.push(Message {
+                channel,
+                command,
+                data1,
+                data2,
+            });
+            times.push(time);
+        }
+
+        messages.push(Message::end_of_track());
+        times.push(self.get_length());
+
+        MidiTrack {
+            messages,
+            times,
+            origins: Vec::new(),
+        }
+    }
+}
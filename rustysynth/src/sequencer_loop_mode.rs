@@ -0,0 +1,40 @@
+/// Specifies how the MIDI track is looped by `MidiFileSequencer`.
+///
+/// # Remarks
+///
+/// Except for `Range`, the loop is given by the loop points in the track.
+/// If no loop start point is found, the loop starts at the beginning.
+/// If no loop end point is found, the loop ends at the end of the track.
+/// At each loop, the controllers and the programs are restored to those at the loop start.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum SequencerLoopMode {
+    /// The track is played once, and the loop points are ignored.
+    Once,
+
+    /// The loop is repeated until the playback is stopped or cancelled.
+    Infinite,
+
+    /// The loop is played the specified number of times,
+    /// and the rest of the track after the loop end point follows.
+    Count(usize),
+
+    /// The loop is played the specified number of times,
+    /// and then the playback keeps looping while it fades out over the specified number of seconds.
+    /// The output is silent after the fade-out is complete.
+    FadeOut {
+        /// The number of times the loop is played before the fade-out starts.
+        count: usize,
+        /// The length of the fade-out in seconds.
+        fade: f64,
+    },
+
+    /// The range between the specified times is repeated until the playback is stopped or cancelled.
+    /// The loop points in the track are ignored.
+    Range {
+        /// The start of the range in seconds.
+        start: f64,
+        /// The end of the range in seconds.
+        end: f64,
+    },
+}
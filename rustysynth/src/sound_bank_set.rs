@@ -0,0 +1,179 @@
+#![allow(dead_code)]
+
+use alloc::collections::BTreeSet;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::diagnostics::Diagnostics;
+use crate::error::SoundFontError;
+use crate::generator_type::GeneratorType;
+use crate::preset_lookup::PresetLookup;
+use crate::soundfont::SoundFont;
+
+/// Represents a set of SoundFonts which are layered into a single SoundFont.
+///
+/// # Remarks
+///
+/// The SoundFonts are added in priority order.
+/// If more than one SoundFont has a preset with the same bank and patch numbers,
+/// the one added first is used.
+/// This allows, for example, a piano SoundFont to override the piano of a GM SoundFont.
+#[derive(Default)]
+#[non_exhaustive]
+pub struct SoundBankSet {
+    sound_fonts: Vec<(Arc<SoundFont>, i32)>,
+}
+
+impl SoundBankSet {
+    /// Initializes a new empty set.
+    pub fn new() -> Self {
+        Self {
+            sound_fonts: Vec::new(),
+        }
+    }
+
+    /// Adds a SoundFont with a lower priority than the ones already added.
+    ///
+    /// # Arguments
+    ///
+    /// * `sound_font` - The SoundFont to be added.
+    /// * `bank_offset` - The value added to the bank numbers of the presets in the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// The bank offset moves the presets to other banks, so that they do not conflict with the others.
+    /// Note that the percussion presets are in bank 128 and later, so they are also moved.
+    pub fn add(&mut self, sound_font: &Arc<SoundFont>, bank_offset: i32) {
+        self.sound_fonts.push((Arc::clone(sound_font), bank_offset));
+    }
+
+    /// Gets the number of the SoundFonts in the set.
+    pub fn len(&self) -> usize {
+        self.sound_fonts.len()
+    }
+
+    /// Gets a value that indicates whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.sound_fonts.is_empty()
+    }
+
+    /// Creates a SoundFont which contains the presets of all the SoundFonts in the set.
+    ///
+    /// # Remarks
+    ///
+    /// The result can be used with the synthesizer in the same way as a loaded SoundFont.
+    /// The information of the first SoundFont is used for the result.
+    /// The SoundFonts whose presets are all overridden by the others are left out.
+    /// The sample data of the SoundFonts left are shared with the result instead of being copied,
+    /// so `get_sample_wave_data` should be used to get the sample data of each sample.
+    /// An error is returned if the result exceeds the limits of the SF2 format,
+    /// such as the number of the samples or the bank numbers moved by the bank offsets.
+    pub fn merge(&self) -> Result<SoundFont, SoundFontError> {
+        if self.sound_fonts.is_empty() {
+            panic!("At least one SoundFont must be added.");
+        }
+
+        // The presets from the SoundFonts with a higher priority win.
+        let mut preset_ids: BTreeSet<(i32, i32)> = BTreeSet::new();
+        let mut used: Vec<(&Arc<SoundFont>, i32, Vec<usize>)> = Vec::new();
+        for (sound_font, bank_offset) in self.sound_fonts.iter() {
+            let mut winners = Vec::new();
+            for (i, preset) in sound_font.presets.iter().enumerate() {
+                let bank_number = SoundBankSet::bank_number(preset.bank_number, *bank_offset)?;
+                if preset_ids.insert((bank_number, preset.patch_number)) {
+                    winners.push(i);
+                }
+            }
+            if !winners.is_empty() {
+                used.push((sound_font, *bank_offset, winners));
+            }
+        }
+
+        // The sample data of the first SoundFont becomes the source 0 of the result,
+        // and those of the others follow it.
+        let mut merged_wave_data = Vec::new();
+        let mut sample_headers = Vec::new();
+        let mut instruments = Vec::new();
+        let mut presets = Vec::new();
+        let mut diagnostics = Diagnostics::new();
+
+        for (i, (sound_font, bank_offset, winners)) in used.iter().enumerate() {
+            let source_offset = merged_wave_data.len() + usize::from(i > 0);
+            let sample_offset = sample_headers.len();
+            let instrument_offset = instruments.len();
+
+            if i > 0 {
+                merged_wave_data.push((
+                    Arc::clone(&sound_font.wave_data),
+                    sound_font.wave_data_24.clone(),
+                ));
+            }
+            merged_wave_data.extend(sound_font.merged_wave_data.iter().cloned());
+
+            for sample_header in sound_font.sample_headers.iter() {
+                let mut sample_header = sample_header.clone();
+                sample_header.source += source_offset;
+                sample_header.link =
+                    SoundBankSet::to_u16(sample_header.link as usize + sample_offset)?;
+                sample_headers.push(sample_header);
+            }
+
+            for instrument in sound_font.instruments.iter() {
+                let mut instrument = instrument.clone();
+                for region in instrument.regions.iter_mut() {
+                    region.sample += sample_offset;
+                    region.gs[GeneratorType::SAMPLE_ID as usize] =
+                        SoundBankSet::to_u16(region.sample)? as i16;
+                }
+                instruments.push(instrument);
+            }
+
+            for &i in winners.iter() {
+                let mut preset = sound_font.presets[i].clone();
+                preset.bank_number = SoundBankSet::bank_number(preset.bank_number, *bank_offset)?;
+                for region in preset.regions.iter_mut() {
+                    region.instrument += instrument_offset;
+                    region.gs[GeneratorType::INSTRUMENT as usize] =
+                        SoundBankSet::to_u16(region.instrument)? as i16;
+                }
+                presets.push(preset);
+            }
+
+            diagnostics.extend(&sound_font.diagnostics);
+        }
+
+        let first = &self.sound_fonts[0].0;
+        let base = used[0].0;
+
+        let mut sound_font = SoundFont {
+            info: first.info.clone(),
+            bits_per_sample: 16,
+            wave_data: Arc::clone(&base.wave_data),
+            wave_data_24: base.wave_data_24.clone(),
+            merged_wave_data,
+            sample_headers,
+            preset_lookup: PresetLookup::new(&presets),
+            presets,
+            instruments,
+            diagnostics,
+        };
+        if sound_font.has_wave_data_24() {
+            sound_font.bits_per_sample = 24;
+        }
+
+        Ok(sound_font)
+    }
+
+    // The bank numbers are stored as 16-bit words in the SF2 format.
+    fn bank_number(bank_number: i32, bank_offset: i32) -> Result<i32, SoundFontError> {
+        match bank_number.checked_add(bank_offset) {
+            Some(value) if (0..=u16::MAX as i32).contains(&value) => Ok(value),
+            _ => Err(SoundFontError::MergedTooLarge),
+        }
+    }
+
+    // The IDs are stored as 16-bit words in the SF2 format.
+    fn to_u16(value: usize) -> Result<u16, SoundFontError> {
+        u16::try_from(value).map_err(|_| SoundFontError::MergedTooLarge)
+    }
+}
@@ -1,27 +1,60 @@
 #![allow(dead_code)]
 
-use std::io::{Read, Seek};
-use std::sync::Arc;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp;
+#[cfg(feature = "memmap2")]
+use std::fs::File;
+#[cfg(feature = "memmap2")]
+use std::io::BufReader;
+#[cfg(feature = "tokio")]
+use tokio::io::AsyncRead;
 
+#[cfg(feature = "zip")]
+use crate::archive::Archive;
+#[cfg(feature = "tokio")]
+use crate::async_loader::AsyncLoader;
 use crate::binary_reader::BinaryReader;
+use crate::diagnostics::Diagnostics;
 use crate::error::SoundFontError;
 use crate::four_cc::FourCC;
 use crate::instrument::Instrument;
+use crate::io::{Cursor, Read, Seek, SeekFrom, Write};
+use crate::midifile::MidiFile;
+use crate::parser_limits::ParserLimits;
 use crate::preset::Preset;
+use crate::preset_lookup::PresetLookup;
 use crate::sample_header::SampleHeader;
 use crate::soundfont_info::SoundFontInfo;
 use crate::soundfont_parameters::SoundFontParameters;
+use crate::soundfont_sampledata::SampleDataSource;
 use crate::soundfont_sampledata::SoundFontSampleData;
+use crate::soundfont_writer::SoundFontWriter;
+use crate::test_bank::TestBank;
+use crate::trace::debug;
+use crate::trace::debug_span;
+use crate::wave_data::WaveData;
+use crate::wave_data::WaveSlice;
+
+// The 16-bit and the lower 8 bits of the 24-bit sample data of a SoundFont.
+pub(crate) type WaveSource = (Arc<WaveData>, Option<Arc<Vec<u8>>>);
 
 /// Reperesents a SoundFont.
 #[non_exhaustive]
 pub struct SoundFont {
     pub(crate) info: SoundFontInfo,
     pub(crate) bits_per_sample: i32,
-    pub(crate) wave_data: Arc<Vec<i16>>,
+    pub(crate) wave_data: Arc<WaveData>,
+    pub(crate) wave_data_24: Option<Arc<Vec<u8>>>,
+    // The sample data of the other SoundFonts merged by `SoundBankSet`,
+    // to which the samples with the source from 1 refer.
+    pub(crate) merged_wave_data: Vec<WaveSource>,
     pub(crate) sample_headers: Vec<SampleHeader>,
     pub(crate) presets: Vec<Preset>,
     pub(crate) instruments: Vec<Instrument>,
+    pub(crate) preset_lookup: PresetLookup,
+    pub(crate) diagnostics: Diagnostics,
 }
 
 impl SoundFont {
@@ -30,7 +63,305 @@ impl SoundFont {
     /// # Arguments
     ///
     /// * `reader` - The data stream used to load the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// With the zip feature, the stream can also be a zip archive,
+    /// from which the first SF2 file is extracted and loaded.
+    /// This applies to all the other loaders taking a stream.
+    /// The archives in the archive are not extracted, and the other formats such as 7z are not supported.
     pub fn new<R: Read + Seek>(reader: &mut R) -> Result<Self, SoundFontError> {
+        SoundFont::new_with_limits(reader, &ParserLimits::new())
+    }
+
+    /// Loads a SoundFont from the byte slice.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The content of the SoundFont file.
+    ///
+    /// # Remarks
+    ///
+    /// This is available without the std feature.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, SoundFontError> {
+        SoundFont::new(&mut Cursor::new(data))
+    }
+
+    /// Loads a SoundFont from the stream with the specified parser limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The data stream used to load the SoundFont.
+    /// * `limits` - The limits applied when parsing the SoundFont.
+    pub fn new_with_limits<R: Read + Seek>(
+        reader: &mut R,
+        limits: &ParserLimits,
+    ) -> Result<Self, SoundFontError> {
+        SoundFont::load(reader, limits, SampleDataSource::Read, None)
+    }
+
+    /// Loads a SoundFont from the stream, where only the sample data used by the selected presets is read.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The data stream used to load the SoundFont.
+    /// * `presets` - The pairs of the bank and patch numbers, such as the ones from `MidiFile::get_used_presets`.
+    ///
+    /// # Remarks
+    ///
+    /// The sample data is skipped by seeking, and then only the parts of it referenced by the selected presets
+    /// are read with a seek and a read for each part.
+    /// This is intended for a stream which fetches the data on demand, such as a reader of HTTP range requests,
+    /// so that only a small part of a large SoundFont is downloaded to render a MIDI file.
+    /// The presets and the instruments are parsed with many small reads, so such a stream should be buffered.
+    /// Each pair is resolved in the same way as the synthesizer, including the fallback to the GM sound set,
+    /// and the default preset is always loaded.
+    /// The other presets are still available, but their samples are silent.
+    pub fn new_partial<R: Read + Seek>(
+        reader: &mut R,
+        presets: &[(i32, i32)],
+    ) -> Result<Self, SoundFontError> {
+        SoundFont::new_partial_with_limits(reader, presets, &ParserLimits::new())
+    }
+
+    /// Loads a SoundFont from the stream with the specified parser limits,
+    /// where only the sample data used by the selected presets is read.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The data stream used to load the SoundFont.
+    /// * `presets` - The pairs of the bank and patch numbers, such as the ones from `MidiFile::get_used_presets`.
+    /// * `limits` - The limits applied when parsing the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// See `new_partial` for the details.
+    pub fn new_partial_with_limits<R: Read + Seek>(
+        reader: &mut R,
+        presets: &[(i32, i32)],
+        limits: &ParserLimits,
+    ) -> Result<Self, SoundFontError> {
+        SoundFont::load(reader, limits, SampleDataSource::Deferred, Some(presets))
+    }
+
+    /// Loads a SoundFont from the stream, where only the sample data needed to play the MIDI file is read.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The data stream used to load the SoundFont.
+    /// * `midi_file` - The MIDI file to be played with the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// The presets are selected by `MidiFile::get_used_presets`, and loaded in the same way as `new_partial`.
+    /// The memory for the samples which are not read is never written, so rendering a MIDI file
+    /// with a huge GM bank takes about as much physical memory as the samples actually played.
+    pub fn new_for_midi<R: Read + Seek>(
+        reader: &mut R,
+        midi_file: &MidiFile,
+    ) -> Result<Self, SoundFontError> {
+        SoundFont::new_for_midi_with_limits(reader, midi_file, &ParserLimits::new())
+    }
+
+    /// Loads a SoundFont from the stream with the specified parser limits,
+    /// where only the sample data needed to play the MIDI file is read.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The data stream used to load the SoundFont.
+    /// * `midi_file` - The MIDI file to be played with the SoundFont.
+    /// * `limits` - The limits applied when parsing the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// See `new_for_midi` for the details.
+    pub fn new_for_midi_with_limits<R: Read + Seek>(
+        reader: &mut R,
+        midi_file: &MidiFile,
+        limits: &ParserLimits,
+    ) -> Result<Self, SoundFontError> {
+        SoundFont::new_partial_with_limits(reader, &midi_file.get_used_presets(), limits)
+    }
+
+    /// Loads a SoundFont from the asynchronous stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The data stream used to load the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// The stream is read into the memory without blocking,
+    /// and then the SoundFont is parsed on the blocking threads of the Tokio runtime.
+    /// A stream longer than the maximum chunk size of the limits fails with `SoundFontError::FileTooLarge`
+    /// before it is parsed.
+    #[cfg(feature = "tokio")]
+    pub async fn from_async_reader<R: AsyncRead + Unpin>(
+        reader: &mut R,
+    ) -> Result<Self, SoundFontError> {
+        SoundFont::from_async_reader_with_limits(reader, &ParserLimits::new()).await
+    }
+
+    /// Loads a SoundFont from the asynchronous stream with the specified parser limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The data stream used to load the SoundFont.
+    /// * `limits` - The limits applied when parsing the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// See `from_async_reader` for the details.
+    #[cfg(feature = "tokio")]
+    pub async fn from_async_reader_with_limits<R: AsyncRead + Unpin>(
+        reader: &mut R,
+        limits: &ParserLimits,
+    ) -> Result<Self, SoundFontError> {
+        let limits = *limits;
+        // The RIFF chunk is the whole file except for its header.
+        let max_size = limits.max_chunk_size.saturating_add(8);
+        AsyncLoader::load(
+            reader,
+            max_size,
+            SoundFontError::FileTooLarge,
+            move |data| SoundFont::new_with_limits(&mut Cursor::new(data), &limits),
+        )
+        .await
+    }
+
+    /// Loads a SoundFont from the file, where the sample data is memory-mapped instead of being read.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The SoundFont file.
+    ///
+    /// # Remarks
+    ///
+    /// Only the presets and the instruments are read, so even a very large SoundFont loads instantly.
+    /// The sample data is paged in by the OS when it is played.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while the SoundFont is alive,
+    /// since the sample data is read directly from the file.
+    #[cfg(feature = "memmap2")]
+    pub unsafe fn new_mapped(file: &File) -> Result<Self, SoundFontError> {
+        SoundFont::new_mapped_with_limits(file, &ParserLimits::new())
+    }
+
+    /// Loads a SoundFont from the file with the specified parser limits,
+    /// where the sample data is memory-mapped instead of being read.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The SoundFont file.
+    /// * `limits` - The limits applied when parsing the SoundFont.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while the SoundFont is alive,
+    /// since the sample data is read directly from the file.
+    #[cfg(feature = "memmap2")]
+    pub unsafe fn new_mapped_with_limits(
+        file: &File,
+        limits: &ParserLimits,
+    ) -> Result<Self, SoundFontError> {
+        let mut reader = BufReader::new(file);
+        reader.seek(SeekFrom::Start(0))?;
+        SoundFont::load(&mut reader, limits, SampleDataSource::Mapped(file), None)
+    }
+
+    fn load<R: Read + Seek>(
+        reader: &mut R,
+        limits: &ParserLimits,
+        source: SampleDataSource,
+        presets: Option<&[(i32, i32)]>,
+    ) -> Result<Self, SoundFontError> {
+        let _span = debug_span!("soundfont_load").entered();
+
+        #[cfg(feature = "zip")]
+        if Archive::is_archive(reader)? {
+            let data = Archive::extract(
+                &mut *reader,
+                Archive::SOUNDFONT_EXTENSIONS,
+                limits.max_chunk_size.saturating_add(8),
+            )?;
+            // The sample data cannot be mapped from a compressed file.
+            let source = match source {
+                SampleDataSource::Mapped(_) => SampleDataSource::Read,
+                source => source,
+            };
+            return SoundFont::load_riff(&mut Cursor::new(data), limits, source, presets);
+        }
+
+        SoundFont::load_riff(reader, limits, source, presets)
+    }
+
+    // Loads the SoundFont from the RIFF file, which is not an archive.
+    fn load_riff<R: Read + Seek>(
+        reader: &mut R,
+        limits: &ParserLimits,
+        source: SampleDataSource,
+        presets: Option<&[(i32, i32)]>,
+    ) -> Result<Self, SoundFontError> {
+        let max_chunk_size = SoundFont::read_header(reader, limits)?;
+        let info = SoundFontInfo::new(reader, max_chunk_size)?;
+        let mut diagnostics = Diagnostics::new();
+        let sample_data =
+            SoundFontSampleData::new(reader, max_chunk_size, source, &mut diagnostics)?;
+        let parameters = SoundFontParameters::new(reader, max_chunk_size, &mut diagnostics)?;
+
+        let mut sound_font = Self {
+            info,
+            bits_per_sample: sample_data.bits_per_sample,
+            wave_data: Arc::new(sample_data.wave_data),
+            wave_data_24: sample_data.wave_data_24.map(Arc::new),
+            merged_wave_data: Vec::new(),
+            sample_headers: parameters.sample_headers,
+            preset_lookup: PresetLookup::new(&parameters.presets),
+            presets: parameters.presets,
+            instruments: parameters.instruments,
+            diagnostics,
+        };
+
+        if let (Some(deferred), Some(presets)) = (&sample_data.deferred, presets) {
+            let ranges = sound_font.get_sample_ranges(presets, deferred.length);
+            let (wave_data, wave_data_24) = deferred.read(reader, &ranges)?;
+            sound_font.wave_data = Arc::new(wave_data);
+            sound_font.wave_data_24 = wave_data_24.map(Arc::new);
+
+            debug!(
+                ranges = ranges.len(),
+                samples = ranges.iter().map(|(start, end)| end - start).sum::<usize>(),
+                "read the sample data used by the presets"
+            );
+        }
+
+        debug!(
+            presets = sound_font.presets.len(),
+            instruments = sound_font.instruments.len(),
+            samples = sound_font.sample_headers.len(),
+            bits_per_sample = sound_font.bits_per_sample,
+            "loaded the SoundFont"
+        );
+
+        Ok(sound_font)
+    }
+
+    // Reads the RIFF header, and returns the maximum size of the chunks.
+    fn read_header<R: Read + Seek>(
+        reader: &mut R,
+        limits: &ParserLimits,
+    ) -> Result<usize, SoundFontError> {
+        // No chunk can be larger than the rest of the stream.
+        let start = reader.stream_position()?;
+        let stream_length = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(start))?;
+        let max_chunk_size = cmp::min(
+            limits.max_chunk_size as u64,
+            stream_length.saturating_sub(start),
+        ) as usize;
+
         let chunk_id = BinaryReader::read_four_cc(reader)?;
         if chunk_id != b"RIFF" {
             return Err(SoundFontError::RiffChunkNotFound);
@@ -46,18 +377,138 @@ impl SoundFont {
             });
         }
 
-        let info = SoundFontInfo::new(reader)?;
-        let sample_data = SoundFontSampleData::new(reader)?;
-        let parameters = SoundFontParameters::new(reader)?;
+        Ok(max_chunk_size)
+    }
 
-        Ok(Self {
-            info,
-            bits_per_sample: 16,
-            wave_data: Arc::new(sample_data.wave_data),
-            sample_headers: parameters.sample_headers,
-            presets: parameters.presets,
-            instruments: parameters.instruments,
-        })
+    /// Reads only the information of a SoundFont from the stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The data stream of the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// Only the INFO list at the start of the SoundFont is read, and the sample data and the presets are not.
+    /// This is fast even for a very large SoundFont,
+    /// so the bank names and the copyrights can be shown before one of the SoundFonts is loaded.
+    pub fn read_info<R: Read + Seek>(reader: &mut R) -> Result<SoundFontInfo, SoundFontError> {
+        let limits = ParserLimits::new();
+
+        #[cfg(feature = "zip")]
+        if Archive::is_archive(reader)? {
+            let data = Archive::extract(
+                &mut *reader,
+                Archive::SOUNDFONT_EXTENSIONS,
+                limits.max_chunk_size.saturating_add(8),
+            )?;
+            let mut reader = Cursor::new(data);
+            let max_chunk_size = SoundFont::read_header(&mut reader, &limits)?;
+            return SoundFontInfo::new(&mut reader, max_chunk_size);
+        }
+
+        let max_chunk_size = SoundFont::read_header(reader, &limits)?;
+        SoundFontInfo::new(reader, max_chunk_size)
+    }
+
+    // Gets the ranges of the samples played by the selected presets, which are sorted and merged.
+    // The close ranges are also merged, since a read costs more than a few extra samples with a remote stream.
+    fn get_sample_ranges(&self, presets: &[(i32, i32)], length: usize) -> Vec<(usize, usize)> {
+        // The interpolation reads the sample after the current position.
+        const MARGIN: i64 = 8;
+        const MAX_GAP: usize = 4096;
+
+        let mut selected: Vec<bool> = vec![false; self.presets.len()];
+        selected[self.preset_lookup.get_default_preset()] = true;
+        for (bank_number, patch_number) in presets.iter() {
+            selected[self.preset_lookup.resolve(*bank_number, *patch_number)] = true;
+        }
+
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for (preset, _) in self.presets.iter().zip(selected.iter()).filter(|x| *x.1) {
+            for preset_region in preset.regions.iter() {
+                let instrument = &self.instruments[preset_region.instrument];
+                for region in instrument.regions.iter() {
+                    let start = cmp::min(region.get_sample_start(), region.get_sample_start_loop());
+                    let end = cmp::max(region.get_sample_end(), region.get_sample_end_loop());
+                    let start = (start as i64 - MARGIN).clamp(0, length as i64) as usize;
+                    let end = (end as i64 + MARGIN).clamp(start as i64, length as i64) as usize;
+                    if start < end {
+                        ranges.push((start, end));
+                    }
+                }
+            }
+        }
+
+        ranges.sort();
+
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 + MAX_GAP => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+
+        merged
+    }
+
+    /// Creates a tiny SoundFont with generated waveforms, which covers all the GM programs.
+    ///
+    /// # Remarks
+    ///
+    /// This is intended for the tests, the examples and the fuzzing,
+    /// which should not depend on a large SoundFont file.
+    /// The bank 0 has a preset for each of the 128 GM programs, named after it,
+    /// and the presets in the same family of 8 programs play the same simple sound,
+    /// such as a decaying sawtooth for the pianos.
+    /// The standard drum kit (128:0) has a sine kick on the keys 35 and 36,
+    /// and a noise pitched by the key on the other keys.
+    /// The waveforms are generated by code, so there is no license issue with the sample data.
+    /// The sample data is about 13 KB, and can be written to a file with `write`.
+    pub fn test_bank() -> Self {
+        TestBank::create()
+    }
+
+    /// Writes the SoundFont to the stream in the SF2 format.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The data stream used to write the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// The global zones are merged into each zone, and the sample data not used by any instrument is removed.
+    /// The modulators are not written, since they are ignored when loading.
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), SoundFontError> {
+        let presets: Vec<usize> = (0..self.presets.len()).collect();
+        SoundFontWriter::write(self, writer, &presets)
+    }
+
+    /// Writes a SoundFont which only contains the presets selected by the specified bank and patch numbers.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The data stream used to write the SoundFont.
+    /// * `presets` - The pairs of the bank and patch numbers, such as the ones from `MidiFile::get_used_presets`.
+    ///
+    /// # Remarks
+    ///
+    /// Each pair is resolved in the same way as the synthesizer, including the fallback to the GM sound set.
+    /// The default preset is always kept, so that the result sounds the same for the selected presets.
+    /// The instruments and the sample data not used by the kept presets are removed.
+    pub fn write_subset<W: Write>(
+        &self,
+        writer: &mut W,
+        presets: &[(i32, i32)],
+    ) -> Result<(), SoundFontError> {
+        let mut selected: Vec<bool> = vec![false; self.presets.len()];
+        selected[self.preset_lookup.get_default_preset()] = true;
+        for (bank_number, patch_number) in presets.iter() {
+            selected[self.preset_lookup.resolve(*bank_number, *patch_number)] = true;
+        }
+
+        let presets: Vec<usize> = (0..self.presets.len()).filter(|x| selected[*x]).collect();
+        SoundFontWriter::write(self, writer, &presets)
     }
 
     /// Gets the information of the SoundFont.
@@ -71,10 +522,67 @@ impl SoundFont {
     }
 
     /// Gets the sample data.
+    ///
+    /// # Remarks
+    ///
+    /// For a SoundFont created by `SoundBankSet::merge` from more than one SoundFont,
+    /// this is the sample data of the first one, and `get_sample_wave_data` gives those of the others.
     pub fn get_wave_data(&self) -> &[i16] {
         &self.wave_data[..]
     }
 
+    /// Gets the sample data to which the positions of the sample refer.
+    ///
+    /// # Arguments
+    ///
+    /// * `sample_id` - The index of the sample in `get_sample_headers`.
+    ///
+    /// # Remarks
+    ///
+    /// A SoundFont created by `SoundBankSet::merge` shares the sample data of the SoundFonts instead of copying them,
+    /// so the samples from each SoundFont refer to its own sample data.
+    /// For the other SoundFonts, this is the same as `get_wave_data`.
+    pub fn get_sample_wave_data(&self, sample_id: usize) -> &[i16] {
+        self.get_wave_slice(self.sample_headers[sample_id].source)
+            .data
+    }
+
+    /// Gets the lower 8 bits of the 24-bit sample data.
+    ///
+    /// # Remarks
+    ///
+    /// If the SoundFont has only the 16-bit sample data, `None` is returned.
+    /// Otherwise, each byte extends the sample at the same index of `get_wave_data`.
+    pub fn get_wave_data_24(&self) -> Option<&[u8]> {
+        self.wave_data_24.as_deref().map(|x| &x[..])
+    }
+
+    // Gets the sample data of the source, where the source 0 is the SoundFont itself.
+    pub(crate) fn get_wave_slice(&self, source: usize) -> WaveSlice<'_> {
+        let (data, data_24) = match source {
+            0 => (&self.wave_data, &self.wave_data_24),
+            _ => {
+                let (data, data_24) = &self.merged_wave_data[source - 1];
+                (data, data_24)
+            }
+        };
+
+        WaveSlice {
+            data: &data[..],
+            data_24: data_24.as_deref().map_or(&[], |x| &x[..]),
+        }
+    }
+
+    // Gets a value that indicates whether any source has the 24-bit sample data.
+    pub(crate) fn has_wave_data_24(&self) -> bool {
+        self.wave_data_24.is_some() || self.merged_wave_data.iter().any(|x| x.1.is_some())
+    }
+
+    /// Gets a value that indicates whether the sample data is memory-mapped from the file.
+    pub fn is_mapped(&self) -> bool {
+        self.wave_data.is_mapped()
+    }
+
     /// Gets the samples of the SoundFont.
     pub fn get_sample_headers(&self) -> &[SampleHeader] {
         &self.sample_headers[..]
@@ -85,6 +593,58 @@ impl SoundFont {
         &self.presets[..]
     }
 
+    /// Finds the preset with the specified bank and patch numbers.
+    ///
+    /// # Arguments
+    ///
+    /// * `bank_number` - The bank number of the preset.
+    /// * `patch_number` - The patch number of the preset.
+    pub fn find_preset(&self, bank_number: i32, patch_number: i32) -> Option<&Preset> {
+        self.preset_lookup
+            .get(bank_number, patch_number)
+            .map(|x| &self.presets[x])
+    }
+
+    /// Gets the preset which the synthesizer plays for the specified bank and patch numbers.
+    ///
+    /// # Arguments
+    ///
+    /// * `bank_number` - The bank number of the preset.
+    /// * `patch_number` - The patch number of the preset.
+    ///
+    /// # Remarks
+    ///
+    /// If the preset is not found, it falls back to the GM sound set.
+    /// A melodic preset falls back to the same patch in the bank 0.
+    /// A percussion preset (the bank number 128 or above) falls back to the same kit in the bank 128,
+    /// and then to the standard kit (128:0).
+    /// If none of them is found, the preset with the smallest bank and patch numbers is used.
+    pub fn resolve_preset(&self, bank_number: i32, patch_number: i32) -> &Preset {
+        &self.presets[self.preset_lookup.resolve(bank_number, patch_number)]
+    }
+
+    /// Finds the presets whose name contains the specified text.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The text to search for. The case of the ASCII letters is ignored.
+    ///
+    /// # Remarks
+    ///
+    /// The presets are returned in the order of the SoundFont.
+    pub fn find_presets_by_name(&self, name: &str) -> Vec<&Preset> {
+        let name = name.to_ascii_lowercase();
+        self.presets
+            .iter()
+            .filter(|x| x.get_name().to_ascii_lowercase().contains(&name))
+            .collect()
+    }
+
+    /// Gets the diagnostics reported when loading the SoundFont.
+    pub fn get_diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
     /// Gets the instruments of the SoundFont.
     pub fn get_instruments(&self) -> &[Instrument] {
         &self.instruments[..]
@@ -1,6 +1,8 @@
 #![allow(dead_code)]
 
-use std::io::{Read, Seek};
+use alloc::string::String;
+
+use crate::io::{Read, Seek};
 
 use crate::binary_reader::BinaryReader;
 use crate::error::SoundFontError;
@@ -9,6 +11,7 @@ use crate::read_counter::ReadCounter;
 use crate::soundfont_version::SoundFontVersion;
 
 /// The information of a SoundFont.
+#[derive(Clone, Debug)]
 #[non_exhaustive]
 pub struct SoundFontInfo {
     pub(crate) version: SoundFontVersion,
@@ -25,13 +28,20 @@ pub struct SoundFontInfo {
 }
 
 impl SoundFontInfo {
-    pub(crate) fn new<R: Read + Seek>(reader: &mut R) -> Result<Self, SoundFontError> {
+    pub(crate) fn new<R: Read + Seek>(
+        reader: &mut R,
+        max_chunk_size: usize,
+    ) -> Result<Self, SoundFontError> {
         let chunk_id = BinaryReader::read_four_cc(reader)?;
         if chunk_id != b"LIST" {
             return Err(SoundFontError::ListChunkNotFound);
         }
 
         let end = BinaryReader::read_u32(reader)? as usize;
+        if end > max_chunk_size {
+            return Err(SoundFontError::ChunkTooLarge(chunk_id));
+        }
+
         let reader = &mut ReadCounter::new(reader);
 
         let list_type = BinaryReader::read_four_cc(reader)?;
@@ -57,6 +67,9 @@ impl SoundFontInfo {
         while reader.bytes_read() < end {
             let id = BinaryReader::read_four_cc(reader)?;
             let size = BinaryReader::read_u32(reader)? as usize;
+            if size > max_chunk_size {
+                return Err(SoundFontError::ChunkTooLarge(id));
+            }
 
             match id.as_bytes() {
                 b"ifil" => version = Some(SoundFontVersion::new(reader)?),
@@ -138,7 +151,7 @@ impl SoundFontInfo {
         &self.creation_date
     }
 
-    /// Gets the auther of the SoundFont.
+    /// Gets the author of the SoundFont.
     pub fn get_author(&self) -> &str {
         &self.author
     }
@@ -0,0 +1,394 @@
+#![allow(dead_code)]
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp;
+
+use crate::binary_reader::BinaryReader;
+use crate::diagnostic::DiagnosticCode;
+use crate::diagnostic::DiagnosticLocation;
+use crate::diagnostic::DiagnosticSeverity;
+use crate::diagnostics::Diagnostics;
+use crate::error::SoundFontError;
+use crate::four_cc::FourCC;
+use crate::io::{Read, Seek, SeekFrom};
+
+/// Represents a chunk in the RIFF structure of a SoundFont found by `SoundFontInspector`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct RiffChunk {
+    pub(crate) id: FourCC,
+    pub(crate) list_type: Option<FourCC>,
+    pub(crate) offset: u64,
+    pub(crate) size: u32,
+    pub(crate) text: Option<String>,
+    pub(crate) children: Vec<RiffChunk>,
+}
+
+impl RiffChunk {
+    /// Gets the ID of the chunk.
+    pub fn get_id(&self) -> FourCC {
+        self.id
+    }
+
+    /// Gets the type of the list, or `None` if the chunk is not a RIFF or LIST chunk.
+    pub fn get_list_type(&self) -> Option<FourCC> {
+        self.list_type
+    }
+
+    /// Gets the position of the chunk header in the stream.
+    pub fn get_offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Gets the size of the chunk data declared in the header.
+    ///
+    /// # Remarks
+    ///
+    /// The size does not include the 8-byte header and the padding byte after an odd-sized chunk.
+    pub fn get_size(&self) -> u32 {
+        self.size
+    }
+
+    /// Gets the text of the chunk in the INFO list, or `None` for the other chunks.
+    ///
+    /// # Remarks
+    ///
+    /// The versions in the ifil and iver chunks are formatted as "major.minor".
+    pub fn get_text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    /// Gets the chunks in the RIFF or LIST chunk.
+    pub fn get_children(&self) -> &[RiffChunk] {
+        &self.children
+    }
+
+    /// Finds the first chunk in the RIFF or LIST chunk with the specified ID or list type.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the chunk, or the type of the LIST chunk, such as `b"pdta"`.
+    pub fn find(&self, id: &[u8; 4]) -> Option<&RiffChunk> {
+        self.children
+            .iter()
+            .find(|x| x.id == id || x.list_type.is_some_and(|x| x == id))
+    }
+}
+
+/// Reads the RIFF structure of a SoundFont without parsing its contents.
+///
+/// # Remarks
+///
+/// This is intended for investigating why a SoundFont fails to load.
+/// The problems in the structure are reported as diagnostics with the offsets of the chunks,
+/// instead of stopping at the first problem as the loader does.
+#[non_exhaustive]
+pub struct SoundFontInspector {
+    root: RiffChunk,
+    stream_length: u64,
+    diagnostics: Diagnostics,
+}
+
+impl SoundFontInspector {
+    // The SF2 structure has no deeper lists, so this only stops a crafted file.
+    const MAX_DEPTH: usize = 4;
+    const MAX_TEXT_LENGTH: u32 = 65536;
+
+    const INFO_IDS: [&'static [u8; 4]; 11] = [
+        b"ifil", b"isng", b"INAM", b"irom", b"iver", b"ICRD", b"IENG", b"IPRD", b"ICOP", b"ICMT",
+        b"ISFT",
+    ];
+
+    // The size of the records in each chunk in the pdta list.
+    const RECORD_SIZES: [(&'static [u8; 4], u32); 9] = [
+        (b"phdr", 38),
+        (b"pbag", 4),
+        (b"pmod", 10),
+        (b"pgen", 4),
+        (b"inst", 22),
+        (b"ibag", 4),
+        (b"imod", 10),
+        (b"igen", 4),
+        (b"shdr", 46),
+    ];
+
+    /// Reads the structure of a SoundFont from the stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The data stream of the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// Only the headers of the chunks and the texts in the INFO list are read.
+    /// An error is returned only if the stream cannot be read or does not start with a RIFF chunk.
+    pub fn new<R: Read + Seek>(reader: &mut R) -> Result<Self, SoundFontError> {
+        let start = reader.stream_position()?;
+        let stream_length = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(start))?;
+
+        let chunk_id = BinaryReader::read_four_cc(reader)?;
+        if chunk_id != b"RIFF" {
+            return Err(SoundFontError::RiffChunkNotFound);
+        }
+
+        let mut diagnostics = Diagnostics::new();
+        let root = SoundFontInspector::read_chunk(
+            reader,
+            start,
+            stream_length,
+            0,
+            None,
+            &mut diagnostics,
+        )?;
+        SoundFontInspector::check(&root, &mut diagnostics);
+
+        Ok(Self {
+            root,
+            stream_length,
+            diagnostics,
+        })
+    }
+
+    fn read_chunk<R: Read + Seek>(
+        reader: &mut R,
+        offset: u64,
+        end: u64,
+        depth: usize,
+        parent: Option<FourCC>,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<RiffChunk, SoundFontError> {
+        reader.seek(SeekFrom::Start(offset))?;
+        let id = BinaryReader::read_four_cc(reader)?;
+        let size = BinaryReader::read_u32(reader)?;
+
+        let data_start = offset + 8;
+        let data_end = data_start + size as u64;
+        if data_end > end {
+            diagnostics.push(
+                DiagnosticCode::SoundFontTruncatedChunk,
+                DiagnosticSeverity::Error,
+                DiagnosticLocation::Chunk(id),
+                format!(
+                    "the chunk '{}' at 0x{:X} declares {} bytes, but only {} bytes remain",
+                    id,
+                    offset,
+                    size,
+                    end.saturating_sub(data_start)
+                ),
+            );
+        }
+        let data_end = cmp::min(data_end, end);
+
+        let mut chunk = RiffChunk {
+            id,
+            list_type: None,
+            offset,
+            size,
+            text: None,
+            children: Vec::new(),
+        };
+
+        if (id == b"RIFF" || id == b"LIST") && depth < SoundFontInspector::MAX_DEPTH {
+            if data_end < data_start + 4 {
+                return Ok(chunk);
+            }
+
+            let list_type = BinaryReader::read_four_cc(reader)?;
+            chunk.list_type = Some(list_type);
+
+            let mut position = data_start + 4;
+            while position + 8 <= data_end {
+                let child = SoundFontInspector::read_chunk(
+                    reader,
+                    position,
+                    data_end,
+                    depth + 1,
+                    Some(list_type),
+                    diagnostics,
+                )?;
+                position = child.offset + 8 + child.size as u64 + (child.size & 1) as u64;
+                chunk.children.push(child);
+            }
+        } else if parent.is_some_and(|x| x == b"INFO") {
+            let length = cmp::min(
+                data_end - data_start,
+                SoundFontInspector::MAX_TEXT_LENGTH as u64,
+            );
+            let mut data: Vec<u8> = vec![0; length as usize];
+            reader.read_exact(&mut data)?;
+
+            chunk.text = Some(if (id == b"ifil" || id == b"iver") && data.len() >= 4 {
+                let major = u16::from_le_bytes([data[0], data[1]]);
+                let minor = u16::from_le_bytes([data[2], data[3]]);
+                format!("{}.{:02}", major, minor)
+            } else {
+                let length = data.iter().position(|x| *x == 0).unwrap_or(data.len());
+                String::from_utf8_lossy(&data[..length]).into_owned()
+            });
+        }
+
+        Ok(chunk)
+    }
+
+    // Checks the structure in the same way as the loader, so that each problem which makes it fail is reported.
+    fn check(root: &RiffChunk, diagnostics: &mut Diagnostics) {
+        let error = |diagnostics: &mut Diagnostics, id: FourCC, message: String| {
+            diagnostics.push(
+                DiagnosticCode::SoundFontInvalidStructure,
+                DiagnosticSeverity::Error,
+                DiagnosticLocation::Chunk(id),
+                message,
+            );
+        };
+
+        match root.list_type {
+            Some(form_type) if form_type == b"sfbk" => (),
+            Some(form_type) => error(
+                diagnostics,
+                root.id,
+                format!(
+                    "the form type of the RIFF chunk must be 'sfbk', but was '{}'",
+                    form_type
+                ),
+            ),
+            None => error(
+                diagnostics,
+                root.id,
+                "the RIFF chunk has no form type".into(),
+            ),
+        }
+
+        // The loader reads the three lists in this order from the start of the RIFF chunk.
+        let lists = [b"INFO", b"sdta", b"pdta"];
+        for (child, expected) in root.children.iter().zip(lists.iter()) {
+            if child.id != b"LIST" || child.list_type.map_or(true, |x| x != *expected) {
+                error(
+                    diagnostics,
+                    child.id,
+                    format!(
+                        "the LIST chunk of '{}' was expected at 0x{:X}, but the chunk '{}'{} was found",
+                        FourCC::from_bytes(**expected),
+                        child.offset,
+                        child.id,
+                        match child.list_type {
+                            Some(list_type) => format!(" of '{}'", list_type),
+                            None => String::new(),
+                        }
+                    ),
+                );
+                break;
+            }
+        }
+
+        for list in lists.iter() {
+            let chunk = match root.find(list) {
+                Some(chunk) if chunk.id == b"LIST" => chunk,
+                _ => {
+                    error(
+                        diagnostics,
+                        FourCC::from_bytes(**list),
+                        format!(
+                            "the LIST chunk of '{}' was not found",
+                            FourCC::from_bytes(**list)
+                        ),
+                    );
+                    continue;
+                }
+            };
+
+            for child in chunk.children.iter() {
+                let supported = match *list {
+                    b"INFO" => SoundFontInspector::INFO_IDS.contains(&child.id.as_bytes()),
+                    b"sdta" => child.id == b"smpl" || child.id == b"sm24",
+                    _ => SoundFontInspector::RECORD_SIZES
+                        .iter()
+                        .any(|(id, _)| child.id == *id),
+                };
+                if !supported {
+                    error(
+                        diagnostics,
+                        child.id,
+                        format!(
+                            "the chunk '{}' at 0x{:X} in the '{}' list is not supported",
+                            child.id,
+                            child.offset,
+                            FourCC::from_bytes(**list)
+                        ),
+                    );
+                }
+            }
+        }
+
+        if let Some(sdta) = root.find(b"sdta") {
+            match (sdta.find(b"smpl"), sdta.find(b"sm24")) {
+                (None, _) => error(
+                    diagnostics,
+                    sdta.id,
+                    "the sample data (smpl) was not found".into(),
+                ),
+                (Some(smpl), Some(sm24)) if (sm24.size as u64) < (smpl.size / 2) as u64 => {
+                    diagnostics.push(
+                        DiagnosticCode::SoundFont24BitSamplesIgnored,
+                        DiagnosticSeverity::Warning,
+                        DiagnosticLocation::Chunk(sm24.id),
+                        format!(
+                            "the sm24 chunk has {} bytes, but {} bytes are needed for the 24-bit samples",
+                            sm24.size,
+                            smpl.size / 2
+                        ),
+                    );
+                }
+                _ => (),
+            }
+        }
+
+        if let Some(pdta) = root.find(b"pdta") {
+            for (id, record_size) in SoundFontInspector::RECORD_SIZES.iter() {
+                let id = FourCC::from_bytes(**id);
+                match pdta.find(id.as_bytes()) {
+                    None => error(diagnostics, id, format!("the '{}' chunk was not found", id)),
+                    Some(chunk) if chunk.size % record_size != 0 => error(
+                        diagnostics,
+                        id,
+                        format!(
+                            "the size of the '{}' chunk must be a multiple of {}, but was {}",
+                            id, record_size, chunk.size
+                        ),
+                    ),
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    /// Gets the RIFF chunk, which contains all the other chunks.
+    pub fn get_root(&self) -> &RiffChunk {
+        &self.root
+    }
+
+    /// Gets the length of the stream in bytes.
+    pub fn get_stream_length(&self) -> u64 {
+        self.stream_length
+    }
+
+    /// Gets the pairs of the IDs and the texts of the chunks in the INFO list.
+    pub fn get_info_fields(&self) -> Vec<(FourCC, &str)> {
+        match self.root.find(b"INFO") {
+            Some(info) => info
+                .children
+                .iter()
+                .filter_map(|x| x.get_text().map(|text| (x.id, text)))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Gets the problems found in the structure.
+    pub fn get_diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+}
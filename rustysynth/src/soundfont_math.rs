@@ -1,6 +1,8 @@
 #![allow(dead_code)]
 
-use std::f32::consts;
+use core::f32::consts;
+
+use crate::float_math::FloatMath;
 
 #[allow(unused)]
 #[non_exhaustive]
@@ -20,23 +22,23 @@ impl SoundFontMath {
     }
 
     pub(crate) fn timecents_to_seconds(x: f32) -> f32 {
-        2_f32.powf(0.0008333 * x)
+        FloatMath::powf(2_f32, 0.0008333 * x)
     }
 
     pub(crate) fn cents_to_hertz(x: f32) -> f32 {
-        8.176_f32 * 2_f32.powf(0.0008333 * x)
+        8.176_f32 * FloatMath::powf(2_f32, 0.0008333 * x)
     }
 
     pub(crate) fn cents_to_multiplying_factor(x: f32) -> f32 {
-        2_f32.powf(0.0008333 * x)
+        FloatMath::powf(2_f32, 0.0008333 * x)
     }
 
     pub(crate) fn decibels_to_linear(x: f32) -> f32 {
-        10_f32.powf(0.05_f32 * x)
+        FloatMath::powf(10_f32, 0.05_f32 * x)
     }
 
     pub(crate) fn linear_to_decibels(x: f32) -> f32 {
-        20_f32 * x.log10()
+        20_f32 * FloatMath::log10(x)
     }
 
     pub(crate) fn key_number_to_multiplying_factor(cents: i32, key: i32) -> f32 {
@@ -47,7 +49,7 @@ impl SoundFontMath {
         if x < SoundFontMath::LOG_NON_AUDIBLE as f64 {
             0_f64
         } else {
-            x.exp()
+            FloatMath::exp_f64(x)
         }
     }
 }
@@ -1,11 +1,19 @@
 #![allow(dead_code)]
 
-use std::io::{Read, Seek};
+use alloc::format;
+use alloc::vec::Vec;
+
+use crate::io::{Read, Seek};
 
 use crate::binary_reader::BinaryReader;
+use crate::diagnostic::DiagnosticCode;
+use crate::diagnostic::DiagnosticLocation;
+use crate::diagnostic::DiagnosticSeverity;
+use crate::diagnostics::Diagnostics;
 use crate::error::SoundFontError;
 use crate::four_cc::FourCC;
 use crate::generator::Generator;
+use crate::generator_type::GeneratorType;
 use crate::instrument::Instrument;
 use crate::instrument_info::InstrumentInfo;
 use crate::preset::Preset;
@@ -23,13 +31,21 @@ pub(crate) struct SoundFontParameters {
 }
 
 impl SoundFontParameters {
-    pub(crate) fn new<R: Read + Seek>(reader: &mut R) -> Result<Self, SoundFontError> {
+    pub(crate) fn new<R: Read + Seek>(
+        reader: &mut R,
+        max_chunk_size: usize,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self, SoundFontError> {
         let chunk_id = BinaryReader::read_four_cc(reader)?;
         if chunk_id != b"LIST" {
             return Err(SoundFontError::ListChunkNotFound);
         }
 
         let end = BinaryReader::read_u32(reader)? as usize;
+        if end > max_chunk_size {
+            return Err(SoundFontError::ChunkTooLarge(chunk_id));
+        }
+
         let reader = &mut ReadCounter::new(reader);
 
         let list_type = BinaryReader::read_four_cc(reader)?;
@@ -41,64 +57,92 @@ impl SoundFontParameters {
         }
 
         let mut preset_infos: Option<Vec<PresetInfo>> = None;
-        let mut preset_bag: Option<Vec<ZoneInfo>> = None;
+        let mut preset_bag: Option<(Vec<ZoneInfo>, FourCC, u64)> = None;
         let mut preset_generators: Option<Vec<Generator>> = None;
         let mut instrument_infos: Option<Vec<InstrumentInfo>> = None;
-        let mut instrument_bag: Option<Vec<ZoneInfo>> = None;
+        let mut instrument_bag: Option<(Vec<ZoneInfo>, FourCC, u64)> = None;
         let mut instrument_generators: Option<Vec<Generator>> = None;
         let mut sample_headers: Option<Vec<SampleHeader>> = None;
 
         while reader.bytes_read() < end {
+            // The position of the chunk is given to the errors, so that the broken chunk can be found.
+            let offset = reader.stream_position()?;
             let id = BinaryReader::read_four_cc(reader)?;
             let size = BinaryReader::read_u32(reader)? as usize;
+            if size > max_chunk_size {
+                return Err(SoundFontError::ChunkTooLarge(id));
+            }
 
             match id.as_bytes() {
-                b"phdr" => preset_infos = Some(PresetInfo::read_from_chunk(reader, size)?),
-                b"pbag" => preset_bag = Some(ZoneInfo::read_from_chunk(reader, size)?),
-                b"pmod" => BinaryReader::discard_data(reader, size)?,
-                b"pgen" => preset_generators = Some(Generator::read_from_chunk(reader, size)?),
-                b"inst" => instrument_infos = Some(InstrumentInfo::read_from_chunk(reader, size)?),
-                b"ibag" => instrument_bag = Some(ZoneInfo::read_from_chunk(reader, size)?),
-                b"imod" => BinaryReader::discard_data(reader, size)?,
-                b"igen" => instrument_generators = Some(Generator::read_from_chunk(reader, size)?),
-                b"shdr" => sample_headers = Some(SampleHeader::read_from_chunk(reader, size)?),
+                b"phdr" => {
+                    preset_infos = Some(PresetInfo::read_from_chunk(reader, id, offset, size)?)
+                }
+                b"pbag" => {
+                    let zones = ZoneInfo::read_from_chunk(reader, id, offset, size)?;
+                    preset_bag = Some((zones, id, offset));
+                }
+                b"pmod" => SoundFontParameters::discard_modulators(reader, id, size, diagnostics)?,
+                b"pgen" => {
+                    preset_generators = Some(Generator::read_from_chunk(reader, id, offset, size)?)
+                }
+                b"inst" => {
+                    instrument_infos =
+                        Some(InstrumentInfo::read_from_chunk(reader, id, offset, size)?)
+                }
+                b"ibag" => {
+                    let zones = ZoneInfo::read_from_chunk(reader, id, offset, size)?;
+                    instrument_bag = Some((zones, id, offset));
+                }
+                b"imod" => SoundFontParameters::discard_modulators(reader, id, size, diagnostics)?,
+                b"igen" => {
+                    instrument_generators =
+                        Some(Generator::read_from_chunk(reader, id, offset, size)?)
+                }
+                b"shdr" => {
+                    sample_headers = Some(SampleHeader::read_from_chunk(reader, id, offset, size)?)
+                }
                 _ => return Err(SoundFontError::ListContainsUnknownId(id)),
             }
         }
 
         let preset_infos = preset_infos.ok_or(SoundFontError::SubChunkNotFound(
-            FourCC::from_bytes(*b"PHDR"),
+            FourCC::from_bytes(*b"phdr"),
         ))?;
 
         let preset_bag = preset_bag.ok_or(SoundFontError::SubChunkNotFound(FourCC::from_bytes(
-            *b"PBAG",
+            *b"pbag",
         )))?;
 
         let preset_generators = preset_generators.ok_or(SoundFontError::SubChunkNotFound(
-            FourCC::from_bytes(*b"PGEN"),
+            FourCC::from_bytes(*b"pgen"),
         ))?;
 
         let instrument_infos = instrument_infos.ok_or(SoundFontError::SubChunkNotFound(
-            FourCC::from_bytes(*b"INST"),
+            FourCC::from_bytes(*b"inst"),
         ))?;
 
         let instrument_bag = instrument_bag.ok_or(SoundFontError::SubChunkNotFound(
-            FourCC::from_bytes(*b"IBAG"),
+            FourCC::from_bytes(*b"ibag"),
         ))?;
 
         let instrument_generators = instrument_generators.ok_or(
-            SoundFontError::SubChunkNotFound(FourCC::from_bytes(*b"IGEN")),
+            SoundFontError::SubChunkNotFound(FourCC::from_bytes(*b"igen")),
         )?;
 
         let sample_headers = sample_headers.ok_or(SoundFontError::SubChunkNotFound(
-            FourCC::from_bytes(*b"SHDR"),
+            FourCC::from_bytes(*b"shdr"),
         ))?;
 
-        let instrument_zones = Zone::create(&instrument_bag, &instrument_generators)?;
+        SoundFontParameters::check_generators(&preset_generators, b"pgen", diagnostics);
+        SoundFontParameters::check_generators(&instrument_generators, b"igen", diagnostics);
+
+        let (instrument_bag, id, offset) = instrument_bag;
+        let instrument_zones = Zone::create(&instrument_bag, &instrument_generators, id, offset)?;
         let instruments =
             Instrument::create(&instrument_infos, &instrument_zones, &sample_headers)?;
 
-        let preset_zones = Zone::create(&preset_bag, &preset_generators)?;
+        let (preset_bag, id, offset) = preset_bag;
+        let preset_zones = Zone::create(&preset_bag, &preset_generators, id, offset)?;
         let presets = Preset::create(&preset_infos, &preset_zones, &instruments)?;
 
         Ok(Self {
@@ -107,4 +151,44 @@ impl SoundFontParameters {
             instruments,
         })
     }
+
+    fn discard_modulators<R: Read + Seek>(
+        reader: &mut R,
+        id: FourCC,
+        size: usize,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<(), SoundFontError> {
+        BinaryReader::discard_data(reader, size)?;
+
+        // A modulator list only with the terminator is empty.
+        if size > 10 {
+            diagnostics.push(
+                DiagnosticCode::SoundFontModulatorsIgnored,
+                DiagnosticSeverity::Info,
+                DiagnosticLocation::Chunk(id),
+                format!(
+                    "{} modulators are not supported and were ignored",
+                    size / 10 - 1
+                ),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn check_generators(generators: &[Generator], id: &[u8; 4], diagnostics: &mut Diagnostics) {
+        let count = generators
+            .iter()
+            .filter(|x| x.generator_type as usize >= GeneratorType::COUNT)
+            .count();
+
+        if count > 0 {
+            diagnostics.push(
+                DiagnosticCode::SoundFontUnknownGenerator,
+                DiagnosticSeverity::Warning,
+                DiagnosticLocation::Chunk(FourCC::from_bytes(*id)),
+                format!("{} generators of unknown types were ignored", count),
+            );
+        }
+    }
 }
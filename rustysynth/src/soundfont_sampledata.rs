@@ -1,27 +1,57 @@
 #![allow(dead_code)]
 
-use std::io::{Read, Seek};
-use std::slice;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::slice;
 
 use crate::binary_reader::BinaryReader;
+use crate::diagnostic::DiagnosticCode;
+use crate::diagnostic::DiagnosticLocation;
+use crate::diagnostic::DiagnosticSeverity;
+use crate::diagnostics::Diagnostics;
 use crate::error::SoundFontError;
 use crate::four_cc::FourCC;
+use crate::io::{Read, Seek, SeekFrom};
 use crate::read_counter::ReadCounter;
+use crate::wave_data::MappedFile;
+use crate::wave_data::WaveData;
+
+// Specifies how the sample data is loaded.
+pub(crate) enum SampleDataSource<'a> {
+    // The sample data is read into memory.
+    Read,
+    // The sample data is memory-mapped from the file.
+    Mapped(&'a MappedFile),
+    // The sample data is skipped, and only the needed parts are read later by `DeferredSampleData`.
+    Deferred,
+}
 
 #[non_exhaustive]
 pub struct SoundFontSampleData {
-    pub bits_per_sample: i32,
-    pub wave_data: Vec<i16>,
+    pub(crate) bits_per_sample: i32,
+    pub(crate) wave_data: WaveData,
+    pub(crate) wave_data_24: Option<Vec<u8>>,
+    pub(crate) deferred: Option<DeferredSampleData>,
 }
 
 impl SoundFontSampleData {
-    pub(crate) fn new<R: Read + Seek>(reader: &mut R) -> Result<Self, SoundFontError> {
+    pub(crate) fn new<R: Read + Seek>(
+        reader: &mut R,
+        max_chunk_size: usize,
+        source: SampleDataSource,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self, SoundFontError> {
         let chunk_id = BinaryReader::read_four_cc(reader)?;
         if chunk_id != b"LIST" {
             return Err(SoundFontError::ListChunkNotFound);
         }
 
         let end = BinaryReader::read_u32(reader)? as usize;
+        if end > max_chunk_size {
+            return Err(SoundFontError::ChunkTooLarge(chunk_id));
+        }
+
         let reader = &mut ReadCounter::new(reader);
 
         let list_type = BinaryReader::read_four_cc(reader)?;
@@ -32,15 +62,57 @@ impl SoundFontSampleData {
             });
         }
 
-        let mut wave_data: Option<Vec<i16>> = None;
+        let mut wave_data: Option<WaveData> = None;
+        let mut wave_data_24: Option<Vec<u8>> = None;
+        let mut position: u64 = 0;
+        let mut position_24: Option<u64> = None;
+        let mut size_24: Option<usize> = None;
+        let mut wave_length: usize = 0;
 
         while reader.bytes_read() < end {
             let id = BinaryReader::read_four_cc(reader)?;
             let size = BinaryReader::read_u32(reader)? as usize;
+            if size > max_chunk_size {
+                return Err(SoundFontError::ChunkTooLarge(id));
+            }
 
             match id.as_bytes() {
-                b"smpl" => wave_data = Some(BinaryReader::read_wave_data(reader, size)?),
-                b"sm24" => BinaryReader::discard_data(reader, size)?,
+                b"smpl" => {
+                    position = reader.stream_position()?;
+                    wave_length = size / 2;
+                    wave_data = Some(match source {
+                        // The sample data is mapped instead of being read.
+                        #[cfg(feature = "memmap2")]
+                        SampleDataSource::Mapped(file) => {
+                            BinaryReader::discard_data(reader, size)?;
+                            unsafe { WaveData::map(file, position, size)? }
+                        }
+                        // Only the head is read to check the format.
+                        SampleDataSource::Deferred => {
+                            let mut head = [0_u8; 4];
+                            let head_size = size.min(head.len());
+                            reader.read_exact(&mut head[..head_size])?;
+                            BinaryReader::skip_data(reader, size - head_size)?;
+                            if &head == b"OggS" {
+                                return Err(SoundFontError::UnsupportedSampleFormat);
+                            }
+                            WaveData::Owned(Vec::new())
+                        }
+                        _ => WaveData::Owned(BinaryReader::read_wave_data(reader, size)?),
+                    })
+                }
+                b"sm24" => match source {
+                    SampleDataSource::Deferred => {
+                        position_24 = Some(reader.stream_position()?);
+                        size_24 = Some(size);
+                        BinaryReader::skip_data(reader, size)?;
+                    }
+                    _ => {
+                        let mut data = vec![0_u8; size];
+                        reader.read_exact(&mut data)?;
+                        wave_data_24 = Some(data);
+                    }
+                },
                 _ => return Err(SoundFontError::ListContainsUnknownId(id)),
             }
         }
@@ -50,15 +122,89 @@ impl SoundFontSampleData {
             None => return Err(SoundFontError::SampleDataNotFound),
         };
 
-        let ptr = wave_data.as_ptr() as *const u8;
-        let four_cc = unsafe { slice::from_raw_parts(ptr, 4) };
-        if four_cc == b"OggS" {
-            return Err(SoundFontError::UnsupportedSampleFormat);
+        if wave_data.len() >= 2 {
+            let ptr = wave_data.as_ptr() as *const u8;
+            let four_cc = unsafe { slice::from_raw_parts(ptr, 4) };
+            if four_cc == b"OggS" {
+                return Err(SoundFontError::UnsupportedSampleFormat);
+            }
         }
 
+        // The chunk has one byte for each sample, and may be padded to an even size.
+        let has_24 = match wave_data_24.as_ref().map(|x| x.len()).or(size_24) {
+            Some(size) if size >= wave_length => true,
+            Some(_) => {
+                diagnostics.push(
+                    DiagnosticCode::SoundFont24BitSamplesIgnored,
+                    DiagnosticSeverity::Warning,
+                    DiagnosticLocation::Chunk(FourCC::from_bytes(*b"sm24")),
+                    "the 24-bit sample data is shorter than the 16-bit sample data and was ignored"
+                        .to_string(),
+                );
+                false
+            }
+            None => false,
+        };
+
+        let wave_data_24 = wave_data_24.filter(|_| has_24).map(|mut data| {
+            data.truncate(wave_length);
+            data
+        });
+
+        let deferred = match source {
+            SampleDataSource::Deferred => Some(DeferredSampleData {
+                position,
+                length: wave_length,
+                position_24: position_24.filter(|_| has_24),
+            }),
+            _ => None,
+        };
+
         Ok(Self {
-            bits_per_sample: 16,
+            bits_per_sample: if has_24 { 24 } else { 16 },
             wave_data,
+            wave_data_24,
+            deferred,
         })
     }
 }
+
+// The positions of the sample data skipped when loading.
+// Only the given ranges of the samples are read later, and the other samples are left silent,
+// so the sample headers can be used without any change.
+pub(crate) struct DeferredSampleData {
+    pub(crate) position: u64,
+    pub(crate) length: usize,
+    pub(crate) position_24: Option<u64>,
+}
+
+impl DeferredSampleData {
+    // The ranges are in samples, and must be sorted and not overlapping.
+    pub(crate) fn read<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        ranges: &[(usize, usize)],
+    ) -> Result<(WaveData, Option<Vec<u8>>), SoundFontError> {
+        // The memory of the zeroed samples is not touched until it is written,
+        // so the unread samples take no physical memory on most platforms.
+        let mut wave_data: Vec<i16> = vec![0; self.length];
+        for (start, end) in ranges.iter() {
+            reader.seek(SeekFrom::Start(self.position + 2 * *start as u64))?;
+            BinaryReader::read_samples(reader, &mut wave_data[*start..*end])?;
+        }
+
+        let wave_data_24 = match self.position_24 {
+            Some(position_24) => {
+                let mut data: Vec<u8> = vec![0; self.length];
+                for (start, end) in ranges.iter() {
+                    reader.seek(SeekFrom::Start(position_24 + *start as u64))?;
+                    reader.read_exact(&mut data[*start..*end])?;
+                }
+                Some(data)
+            }
+            None => None,
+        };
+
+        Ok((WaveData::Owned(wave_data), wave_data_24))
+    }
+}
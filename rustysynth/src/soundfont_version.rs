@@ -1,11 +1,14 @@
 #![allow(dead_code)]
 
-use std::io;
-use std::io::Read;
+use core::fmt;
+
+use crate::io;
+use crate::io::Read;
 
 use crate::binary_reader::BinaryReader;
 
 /// Reperesents the version of a SoundFont.
+#[derive(Clone, Debug)]
 #[non_exhaustive]
 pub struct SoundFontVersion {
     pub(crate) major: i16,
@@ -34,3 +37,9 @@ impl SoundFontVersion {
         self.minor as i32
     }
 }
+
+impl fmt::Display for SoundFontVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{:02}", self.major, self.minor)
+    }
+}
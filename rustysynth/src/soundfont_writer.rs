@@ -0,0 +1,511 @@
+#![allow(dead_code)]
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::io;
+use crate::io::Write;
+
+use crate::binary_writer::BinaryWriter;
+use crate::error::SoundFontError;
+use crate::four_cc::FourCC;
+use crate::generator_type::GeneratorType;
+use crate::instrument_region::InstrumentRegion;
+use crate::preset_region::PresetRegion;
+use crate::sample_header::SampleHeader;
+use crate::soundfont::SoundFont;
+
+// The loaded SoundFont no longer has the global zones, since they are merged into each region.
+// Therefore, each region is written as a local zone with the generators which differ from the defaults.
+// The modulators are not kept when loading, so they are not written.
+
+#[allow(unused)]
+#[non_exhaustive]
+pub(crate) struct SoundFontWriter {}
+
+// The moved sample header and the range of the original sample data to be written.
+struct TrimmedSample {
+    header: SampleHeader,
+    source: usize,
+    start: usize,
+    end: usize,
+}
+
+impl SoundFontWriter {
+    // Each sample must be followed by at least 46 zero samples.
+    const SAMPLE_PADDING: usize = 46;
+
+    const SAMPLE_TYPE_LINKED: u16 = 0x2 | 0x4 | 0x8;
+
+    pub(crate) fn write<W: Write>(
+        sound_font: &SoundFont,
+        writer: &mut W,
+        presets: &[usize],
+    ) -> Result<(), SoundFontError> {
+        let (instruments, instrument_map) =
+            SoundFontWriter::collect_instruments(sound_font, presets);
+        let (samples, sample_map) = SoundFontWriter::collect_samples(sound_font, &instruments);
+        let trimmed = SoundFontWriter::trim_samples(sound_font, &samples, &sample_map)?;
+
+        let info = SoundFontWriter::create_info(sound_font)?;
+        let parameters = SoundFontWriter::create_parameters(
+            sound_font,
+            presets,
+            &instruments,
+            &instrument_map,
+            &sample_map,
+            &trimmed,
+        )?;
+
+        let sample_count: usize = trimmed
+            .iter()
+            .map(|x| x.end - x.start + SoundFontWriter::SAMPLE_PADDING)
+            .sum();
+        let smpl_size = 2 * sample_count;
+        // The sm24 chunk has one byte for each sample, padded to an even size.
+        let has_24_bit = sound_font.has_wave_data_24();
+        let sm24_size = if has_24_bit {
+            sample_count + sample_count % 2
+        } else {
+            0
+        };
+        let sdta_size = if has_24_bit {
+            4 + 8 + smpl_size + 8 + sm24_size
+        } else {
+            4 + 8 + smpl_size
+        };
+        let riff_size = 4 + (12 + info.len()) + (8 + sdta_size) + (12 + parameters.len());
+        if riff_size > u32::MAX as usize {
+            return Err(SoundFontError::IoError(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the SoundFont is too large to be written",
+            )));
+        }
+
+        BinaryWriter::write_four_cc(writer, &FourCC::from_bytes(*b"RIFF"))?;
+        BinaryWriter::write_u32(writer, riff_size as u32)?;
+        BinaryWriter::write_four_cc(writer, &FourCC::from_bytes(*b"sfbk"))?;
+
+        SoundFontWriter::write_list_header(writer, b"INFO", info.len())?;
+        writer.write_all(&info)?;
+
+        SoundFontWriter::write_list_header(writer, b"sdta", sdta_size - 4)?;
+        BinaryWriter::write_four_cc(writer, &FourCC::from_bytes(*b"smpl"))?;
+        BinaryWriter::write_u32(writer, smpl_size as u32)?;
+        let mut data: Vec<u8> = Vec::new();
+        for sample in trimmed.iter() {
+            data.clear();
+            let wave_data = sound_font.get_wave_slice(sample.source).data;
+            for value in wave_data[sample.start..sample.end].iter() {
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+            data.resize(data.len() + 2 * SoundFontWriter::SAMPLE_PADDING, 0);
+            writer.write_all(&data)?;
+        }
+
+        if has_24_bit {
+            BinaryWriter::write_four_cc(writer, &FourCC::from_bytes(*b"sm24"))?;
+            BinaryWriter::write_u32(writer, sm24_size as u32)?;
+            for sample in trimmed.iter() {
+                // The samples from a merged SoundFont without the 24-bit sample data are extended with zeros.
+                data.clear();
+                let wave_data_24 = sound_font.get_wave_slice(sample.source).data_24;
+                if !wave_data_24.is_empty() {
+                    data.extend_from_slice(&wave_data_24[sample.start..sample.end]);
+                }
+                data.resize(
+                    sample.end - sample.start + SoundFontWriter::SAMPLE_PADDING,
+                    0,
+                );
+                writer.write_all(&data)?;
+            }
+            if sample_count % 2 != 0 {
+                writer.write_all(&[0])?;
+            }
+        }
+
+        SoundFontWriter::write_list_header(writer, b"pdta", parameters.len())?;
+        writer.write_all(&parameters)?;
+
+        Ok(())
+    }
+
+    // The instruments are ordered by their first use.
+    fn collect_instruments(
+        sound_font: &SoundFont,
+        presets: &[usize],
+    ) -> (Vec<usize>, Vec<Option<usize>>) {
+        let mut instruments: Vec<usize> = Vec::new();
+        let mut instrument_map: Vec<Option<usize>> = vec![None; sound_font.instruments.len()];
+
+        for preset_id in presets.iter() {
+            for region in sound_font.presets[*preset_id].regions.iter() {
+                if instrument_map[region.instrument].is_none() {
+                    instrument_map[region.instrument] = Some(instruments.len());
+                    instruments.push(region.instrument);
+                }
+            }
+        }
+
+        (instruments, instrument_map)
+    }
+
+    // The samples are ordered by their first use.
+    // The other side of a stereo pair is kept together, even if it is not used.
+    fn collect_samples(
+        sound_font: &SoundFont,
+        instruments: &[usize],
+    ) -> (Vec<usize>, Vec<Option<usize>>) {
+        let mut samples: Vec<usize> = Vec::new();
+        let mut sample_map: Vec<Option<usize>> = vec![None; sound_font.sample_headers.len()];
+
+        for instrument_id in instruments.iter() {
+            for region in sound_font.instruments[*instrument_id].regions.iter() {
+                let sample_id = region.get_sample_id();
+                if sample_map[sample_id].is_none() {
+                    sample_map[sample_id] = Some(samples.len());
+                    samples.push(sample_id);
+                }
+            }
+        }
+
+        let mut i: usize = 0;
+        while i < samples.len() {
+            let header = &sound_font.sample_headers[samples[i]];
+            let link = header.link as usize;
+            if header.sample_type & SoundFontWriter::SAMPLE_TYPE_LINKED != 0
+                && link < sample_map.len()
+                && sample_map[link].is_none()
+            {
+                sample_map[link] = Some(samples.len());
+                samples.push(link);
+            }
+            i += 1;
+        }
+
+        (samples, sample_map)
+    }
+
+    // Only the part of the sample data between the sample start and end points,
+    // including the loop, is kept, and the sample headers are moved accordingly.
+    fn trim_samples(
+        sound_font: &SoundFont,
+        samples: &[usize],
+        sample_map: &[Option<usize>],
+    ) -> Result<Vec<TrimmedSample>, SoundFontError> {
+        let mut trimmed: Vec<TrimmedSample> = Vec::new();
+        let mut position: i64 = 0;
+
+        for sample_id in samples.iter() {
+            let header = &sound_font.sample_headers[*sample_id];
+            let wave_length = sound_font.get_wave_slice(header.source).data.len() as i64;
+
+            let start = (i32::min(header.start, header.start_loop) as i64).clamp(0, wave_length);
+            let end = (i32::max(header.end, header.end_loop) as i64).clamp(start, wave_length);
+            let offset = position - start;
+
+            let moved = |value: i32| -> Result<i32, SoundFontError> {
+                match i32::try_from(value as i64 + offset) {
+                    Ok(value) => Ok(value),
+                    Err(_) => Err(SoundFontError::IoError(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "the sample data is too large to be written",
+                    ))),
+                }
+            };
+
+            let link = if header.sample_type & SoundFontWriter::SAMPLE_TYPE_LINKED != 0 {
+                sample_map
+                    .get(header.link as usize)
+                    .copied()
+                    .flatten()
+                    .unwrap_or(0) as u16
+            } else {
+                header.link
+            };
+
+            let header = SampleHeader {
+                name: header.name.clone(),
+                start: moved(header.start)?,
+                end: moved(header.end)?,
+                start_loop: moved(header.start_loop)?,
+                end_loop: moved(header.end_loop)?,
+                sample_rate: header.sample_rate,
+                original_pitch: header.original_pitch,
+                pitch_correction: header.pitch_correction,
+                link,
+                sample_type: header.sample_type,
+                source: 0,
+            };
+            trimmed.push(TrimmedSample {
+                header,
+                source: sound_font.sample_headers[*sample_id].source,
+                start: start as usize,
+                end: end as usize,
+            });
+
+            position += end - start + SoundFontWriter::SAMPLE_PADDING as i64;
+        }
+
+        Ok(trimmed)
+    }
+
+    fn create_info(sound_font: &SoundFont) -> Result<Vec<u8>, SoundFontError> {
+        let info = &sound_font.info;
+        let mut data: Vec<u8> = Vec::new();
+
+        // The version, the sound engine and the name are mandatory.
+        let (major, minor) = if info.version.major == 0 {
+            (2, 1)
+        } else {
+            (info.version.major, info.version.minor)
+        };
+        // The 24-bit sample data was introduced in the version 2.04.
+        let (major, minor) = if sound_font.has_wave_data_24() && (major, minor) < (2, 4) {
+            (2, 4)
+        } else {
+            (major, minor)
+        };
+        BinaryWriter::write_four_cc(&mut data, &FourCC::from_bytes(*b"ifil"))?;
+        BinaryWriter::write_u32(&mut data, 4)?;
+        BinaryWriter::write_i16(&mut data, major)?;
+        BinaryWriter::write_i16(&mut data, minor)?;
+
+        let target_sound_engine = if info.target_sound_engine.is_empty() {
+            "EMU8000"
+        } else {
+            &info.target_sound_engine
+        };
+        SoundFontWriter::write_string_chunk(&mut data, b"isng", target_sound_engine)?;
+
+        let bank_name = if info.bank_name.is_empty() {
+            "Untitled"
+        } else {
+            &info.bank_name
+        };
+        SoundFontWriter::write_string_chunk(&mut data, b"INAM", bank_name)?;
+
+        if !info.rom_name.is_empty() {
+            SoundFontWriter::write_string_chunk(&mut data, b"irom", &info.rom_name)?;
+            BinaryWriter::write_four_cc(&mut data, &FourCC::from_bytes(*b"iver"))?;
+            BinaryWriter::write_u32(&mut data, 4)?;
+            BinaryWriter::write_i16(&mut data, info.rom_version.major)?;
+            BinaryWriter::write_i16(&mut data, info.rom_version.minor)?;
+        }
+
+        let optional = [
+            (b"ICRD", &info.creation_date),
+            (b"IENG", &info.author),
+            (b"IPRD", &info.target_product),
+            (b"ICOP", &info.copyright),
+            (b"ICMT", &info.comments),
+            (b"ISFT", &info.tools),
+        ];
+        for (id, value) in optional.iter() {
+            if !value.is_empty() {
+                SoundFontWriter::write_string_chunk(&mut data, id, value)?;
+            }
+        }
+
+        Ok(data)
+    }
+
+    fn create_parameters(
+        sound_font: &SoundFont,
+        presets: &[usize],
+        instruments: &[usize],
+        instrument_map: &[Option<usize>],
+        sample_map: &[Option<usize>],
+        samples: &[TrimmedSample],
+    ) -> Result<Vec<u8>, SoundFontError> {
+        let mut phdr: Vec<u8> = Vec::new();
+        let mut pbag: Vec<u8> = Vec::new();
+        let mut pgen: Vec<u8> = Vec::new();
+        let mut bag_count: usize = 0;
+        let mut generator_count: usize = 0;
+
+        let defaults = PresetRegion::default_generators();
+        for preset_id in presets.iter() {
+            let preset = &sound_font.presets[*preset_id];
+            BinaryWriter::write_fixed_length_string(&mut phdr, &preset.name, 20)?;
+            BinaryWriter::write_u16(&mut phdr, preset.patch_number as u16)?;
+            BinaryWriter::write_u16(&mut phdr, preset.bank_number as u16)?;
+            BinaryWriter::write_u16(&mut phdr, SoundFontWriter::to_index(bag_count)?)?;
+            BinaryWriter::write_i32(&mut phdr, preset.library)?;
+            BinaryWriter::write_i32(&mut phdr, preset.genre)?;
+            BinaryWriter::write_i32(&mut phdr, preset.morphology)?;
+
+            for region in preset.regions.iter() {
+                let instrument_id = instrument_map[region.instrument].unwrap();
+                BinaryWriter::write_u16(&mut pbag, SoundFontWriter::to_index(generator_count)?)?;
+                BinaryWriter::write_u16(&mut pbag, 0)?;
+                generator_count += SoundFontWriter::write_generators(
+                    &mut pgen,
+                    &region.gs,
+                    &defaults,
+                    GeneratorType::INSTRUMENT,
+                    SoundFontWriter::to_index(instrument_id)?,
+                )?;
+                bag_count += 1;
+            }
+        }
+        BinaryWriter::write_fixed_length_string(&mut phdr, "EOP", 20)?;
+        BinaryWriter::write_u16(&mut phdr, 0)?;
+        BinaryWriter::write_u16(&mut phdr, 0)?;
+        BinaryWriter::write_u16(&mut phdr, SoundFontWriter::to_index(bag_count)?)?;
+        phdr.resize(phdr.len() + 12, 0);
+        BinaryWriter::write_u16(&mut pbag, SoundFontWriter::to_index(generator_count)?)?;
+        BinaryWriter::write_u16(&mut pbag, 0)?;
+        pgen.resize(pgen.len() + 4, 0);
+
+        let mut inst: Vec<u8> = Vec::new();
+        let mut ibag: Vec<u8> = Vec::new();
+        let mut igen: Vec<u8> = Vec::new();
+        let mut bag_count: usize = 0;
+        let mut generator_count: usize = 0;
+
+        let defaults = InstrumentRegion::default_generators();
+        for instrument_id in instruments.iter() {
+            let instrument = &sound_font.instruments[*instrument_id];
+            BinaryWriter::write_fixed_length_string(&mut inst, &instrument.name, 20)?;
+            BinaryWriter::write_u16(&mut inst, SoundFontWriter::to_index(bag_count)?)?;
+
+            for region in instrument.regions.iter() {
+                let sample_id = sample_map[region.get_sample_id()].unwrap();
+                BinaryWriter::write_u16(&mut ibag, SoundFontWriter::to_index(generator_count)?)?;
+                BinaryWriter::write_u16(&mut ibag, 0)?;
+                generator_count += SoundFontWriter::write_generators(
+                    &mut igen,
+                    &region.gs,
+                    &defaults,
+                    GeneratorType::SAMPLE_ID,
+                    SoundFontWriter::to_index(sample_id)?,
+                )?;
+                bag_count += 1;
+            }
+        }
+        BinaryWriter::write_fixed_length_string(&mut inst, "EOI", 20)?;
+        BinaryWriter::write_u16(&mut inst, SoundFontWriter::to_index(bag_count)?)?;
+        BinaryWriter::write_u16(&mut ibag, SoundFontWriter::to_index(generator_count)?)?;
+        BinaryWriter::write_u16(&mut ibag, 0)?;
+        igen.resize(igen.len() + 4, 0);
+
+        let mut shdr: Vec<u8> = Vec::new();
+        for sample in samples.iter() {
+            SoundFontWriter::write_sample_header(&mut shdr, &sample.header)?;
+        }
+        shdr.extend_from_slice(b"EOS");
+        shdr.resize(shdr.len() + 43, 0);
+
+        // Only the terminators of the modulator lists.
+        let modulators: [u8; 10] = [0; 10];
+
+        let mut data: Vec<u8> = Vec::new();
+        SoundFontWriter::write_chunk(&mut data, b"phdr", &phdr)?;
+        SoundFontWriter::write_chunk(&mut data, b"pbag", &pbag)?;
+        SoundFontWriter::write_chunk(&mut data, b"pmod", &modulators)?;
+        SoundFontWriter::write_chunk(&mut data, b"pgen", &pgen)?;
+        SoundFontWriter::write_chunk(&mut data, b"inst", &inst)?;
+        SoundFontWriter::write_chunk(&mut data, b"ibag", &ibag)?;
+        SoundFontWriter::write_chunk(&mut data, b"imod", &modulators)?;
+        SoundFontWriter::write_chunk(&mut data, b"igen", &igen)?;
+        SoundFontWriter::write_chunk(&mut data, b"shdr", &shdr)?;
+
+        Ok(data)
+    }
+
+    // The key range must be the first and the velocity range must be the second,
+    // and the instrument or the sample ID must be the last.
+    fn write_generators(
+        data: &mut Vec<u8>,
+        gs: &[i16; GeneratorType::COUNT],
+        defaults: &[i16; GeneratorType::COUNT],
+        last_type: u16,
+        last_value: u16,
+    ) -> Result<usize, SoundFontError> {
+        let mut count: usize = 0;
+
+        let mut types: Vec<u16> = vec![GeneratorType::KEY_RANGE, GeneratorType::VELOCITY_RANGE];
+        types.extend((0..GeneratorType::COUNT as u16).filter(|x| {
+            !(*x == GeneratorType::KEY_RANGE
+                || *x == GeneratorType::VELOCITY_RANGE
+                || *x == GeneratorType::INSTRUMENT
+                || *x == GeneratorType::SAMPLE_ID)
+        }));
+
+        for generator_type in types {
+            let index = generator_type as usize;
+            if gs[index] != defaults[index] {
+                BinaryWriter::write_u16(data, generator_type)?;
+                BinaryWriter::write_i16(data, gs[index])?;
+                count += 1;
+            }
+        }
+
+        BinaryWriter::write_u16(data, last_type)?;
+        BinaryWriter::write_u16(data, last_value)?;
+        count += 1;
+
+        Ok(count)
+    }
+
+    fn write_sample_header<W: Write>(
+        writer: &mut W,
+        header: &SampleHeader,
+    ) -> Result<(), SoundFontError> {
+        BinaryWriter::write_fixed_length_string(writer, &header.name, 20)?;
+        BinaryWriter::write_i32(writer, header.start)?;
+        BinaryWriter::write_i32(writer, header.end)?;
+        BinaryWriter::write_i32(writer, header.start_loop)?;
+        BinaryWriter::write_i32(writer, header.end_loop)?;
+        BinaryWriter::write_i32(writer, header.sample_rate)?;
+        BinaryWriter::write_u8(writer, header.original_pitch)?;
+        BinaryWriter::write_u8(writer, header.pitch_correction as u8)?;
+        BinaryWriter::write_u16(writer, header.link)?;
+        BinaryWriter::write_u16(writer, header.sample_type)?;
+
+        Ok(())
+    }
+
+    // The string is terminated with zero and padded to an even length.
+    fn write_string_chunk(
+        data: &mut Vec<u8>,
+        id: &[u8; 4],
+        value: &str,
+    ) -> Result<(), SoundFontError> {
+        let length = (value.len() + 2) & !1;
+        let mut body: Vec<u8> = Vec::new();
+        BinaryWriter::write_fixed_length_string(&mut body, value, length)?;
+        SoundFontWriter::write_chunk(data, id, &body)
+    }
+
+    fn write_chunk(data: &mut Vec<u8>, id: &[u8; 4], body: &[u8]) -> Result<(), SoundFontError> {
+        BinaryWriter::write_four_cc(data, &FourCC::from_bytes(*id))?;
+        BinaryWriter::write_u32(data, body.len() as u32)?;
+        data.extend_from_slice(body);
+
+        Ok(())
+    }
+
+    fn write_list_header<W: Write>(
+        writer: &mut W,
+        list_type: &[u8; 4],
+        size: usize,
+    ) -> Result<(), SoundFontError> {
+        BinaryWriter::write_four_cc(writer, &FourCC::from_bytes(*b"LIST"))?;
+        BinaryWriter::write_u32(writer, (size + 4) as u32)?;
+        BinaryWriter::write_four_cc(writer, &FourCC::from_bytes(*list_type))?;
+
+        Ok(())
+    }
+
+    fn to_index(value: usize) -> Result<u16, SoundFontError> {
+        match u16::try_from(value) {
+            Ok(value) => Ok(value),
+            Err(_) => Err(SoundFontError::IoError(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "too many items to be written to a SoundFont",
+            ))),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+#![allow(dead_code)]
+
+use core::f32::consts;
+
+use crate::filter_type::FilterType;
+use crate::float_math::FloatMath;
+use crate::synthesizer_settings::SynthesizerSettings;
+
+// The 2-pole state-variable filter, discretized with the trapezoidal integrators,
+// which stays stable while the cutoff is modulated.
+#[derive(Clone)]
+#[non_exhaustive]
+pub(crate) struct StateVariableFilter {
+    sample_rate: i32,
+
+    filter_type: FilterType,
+
+    k: f32,
+    a1: f32,
+    a2: f32,
+    a3: f32,
+
+    ic1: f32,
+    ic2: f32,
+}
+
+impl StateVariableFilter {
+    const RESONANCE_PEAK_OFFSET: f32 = 1_f32 - 1_f32 / core::f32::consts::SQRT_2;
+
+    pub(crate) fn new(settings: &SynthesizerSettings) -> Self {
+        Self {
+            sample_rate: settings.sample_rate,
+            filter_type: FilterType::StateVariableLowPass,
+            k: 0_f32,
+            a1: 0_f32,
+            a2: 0_f32,
+            a3: 0_f32,
+            ic1: 0_f32,
+            ic2: 0_f32,
+        }
+    }
+
+    pub(crate) fn clear_buffer(&mut self) {
+        self.ic1 = 0_f32;
+        self.ic2 = 0_f32;
+    }
+
+    pub(crate) fn set_filter(
+        &mut self,
+        filter_type: FilterType,
+        cutoff_frequency: f32,
+        resonance: f32,
+    ) {
+        self.filter_type = filter_type;
+
+        // The same Q value as the default filter, which makes the desired resonance peak.
+        let q = resonance
+            - StateVariableFilter::RESONANCE_PEAK_OFFSET / (1_f32 + 6_f32 * (resonance - 1_f32));
+
+        let cutoff_frequency = cutoff_frequency.min(0.49_f32 * self.sample_rate as f32);
+        let w = consts::PI * cutoff_frequency / self.sample_rate as f32;
+        let g = FloatMath::sin(w) / FloatMath::cos(w);
+
+        self.k = 1_f32 / q;
+        self.a1 = 1_f32 / (1_f32 + g * (g + self.k));
+        self.a2 = g * self.a1;
+        self.a3 = g * self.a2;
+    }
+
+    pub(crate) fn process(&mut self, block: &mut [f32]) {
+        for input in block.iter_mut() {
+            let v3 = *input - self.ic2;
+            let v1 = self.a1 * self.ic1 + self.a2 * v3;
+            let v2 = self.ic2 + self.a2 * self.ic1 + self.a3 * v3;
+            self.ic1 = 2_f32 * v1 - self.ic1;
+            self.ic2 = 2_f32 * v2 - self.ic2;
+
+            *input = match self.filter_type {
+                FilterType::StateVariableHighPass => *input - self.k * v1 - v2,
+                // Normalized so that the peak at the cutoff is unity regardless of the resonance.
+                FilterType::StateVariableBandPass => self.k * v1,
+                _ => v2,
+            };
+        }
+    }
+}
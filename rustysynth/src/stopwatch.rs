@@ -0,0 +1,28 @@
+#![allow(dead_code)]
+
+// Measures the time spent for rendering.
+// Without the std feature, no clock is available and the elapsed time is always zero.
+use core::time::Duration;
+
+#[non_exhaustive]
+pub(crate) struct Stopwatch {
+    #[cfg(feature = "std")]
+    start: std::time::Instant,
+}
+
+impl Stopwatch {
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(feature = "std")]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        #[cfg(feature = "std")]
+        return self.start.elapsed();
+
+        #[cfg(not(feature = "std"))]
+        return Duration::ZERO;
+    }
+}
@@ -1,34 +1,92 @@
 #![allow(dead_code)]
 
-use std::cmp;
-use std::collections::HashMap;
-use std::sync::Arc;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp;
+use core::mem;
+use core::time::Duration;
 
 use crate::array_math::ArrayMath;
+use crate::automation_target::AutomationTarget;
 use crate::channel::Channel;
+use crate::channel_state::ChannelState;
 use crate::chorus::Chorus;
+use crate::controller_target::ControllerTarget;
+use crate::device_profile::DeviceProfile;
+use crate::diagnostic::Diagnostic;
+use crate::diagnostic::DiagnosticCode;
+use crate::diagnostic::DiagnosticLocation;
+use crate::diagnostic::DiagnosticSeverity;
+use crate::diagnostics::Diagnostics;
+use crate::envelope_override::EnvelopeOverride;
 use crate::error::SynthesizerError;
+use crate::exclusive_class_mode::ExclusiveClassMode;
+use crate::filter_override::FilterOverride;
+use crate::generated_voice::GeneratedVoice;
+use crate::key_split::KeySplit;
+use crate::lfo_override::LfoOverride;
+use crate::parallel::*;
+use crate::preset_profile::PresetProfile;
 use crate::region_pair::RegionPair;
+use crate::release_layer::ReleaseLayer;
 use crate::reverb::Reverb;
+use crate::sample_voice::SampleVoice;
 use crate::soundfont::SoundFont;
 use crate::soundfont_math::SoundFontMath;
+use crate::stopwatch::Stopwatch;
 use crate::synthesizer_settings::SynthesizerSettings;
+use crate::synthesizer_state::SynthesizerState;
+use crate::synthesizer_state::VoiceSnapshot;
+use crate::synthesizer_statistics::PresetStatistics;
+use crate::synthesizer_statistics::SynthesizerStatistics;
+use crate::trace::trace_span;
+use crate::ump_message::UmpMessage;
+use crate::voice::Voice;
+use crate::voice_collection::ReleasedVoice;
 use crate::voice_collection::VoiceCollection;
+use crate::voice_event::VoiceEvent;
+use crate::voice_event::VoiceEventKind;
+use crate::voice_source::VoiceSource;
+use crate::voice_stealing_policy::VoiceStealingPolicy;
 
 /// An instance of the SoundFont synthesizer.
 #[non_exhaustive]
 pub struct Synthesizer {
     pub(crate) sound_font: Arc<SoundFont>,
+    previous_sound_font: Option<Arc<SoundFont>>,
     pub(crate) sample_rate: i32,
     pub(crate) block_size: usize,
     pub(crate) maximum_polyphony: usize,
 
-    preset_lookup: HashMap<i32, usize>,
-    default_preset: usize,
+    // The changes are deferred until the current block has been consumed.
+    pending_block_size: Option<usize>,
+    pending_maximum_polyphony: Option<usize>,
 
     channels: Vec<Channel>,
+    percussion_channels: Vec<bool>,
+    // The channel to which the messages of each channel are sent.
+    channel_map: Vec<usize>,
+    enable_gs_rhythm_parts: bool,
+    profile: DeviceProfile,
 
     voices: VoiceCollection,
+    samples: Vec<SampleVoice>,
+    generated_voices: Vec<GeneratedVoice>,
+
+    // The sources are keyed by the preset ID in the same way as the preset lookup.
+    voice_sources: BTreeMap<i32, Arc<dyn VoiceSource>>,
+    fallback_voice_source: Option<Arc<dyn VoiceSource>>,
+
+    // The layers are keyed by the preset ID of the released notes.
+    release_layers: BTreeMap<i32, ReleaseLayer>,
+    // The notes released in the last block with their layers and gains,
+    // whose layers are started before the next block.
+    pending_release_layers: Vec<(ReleasedVoice, ReleaseLayer, f32)>,
 
     block_left: Vec<f32>,
     block_right: Vec<f32>,
@@ -38,55 +96,68 @@ pub struct Synthesizer {
     block_read: usize,
 
     master_volume: f32,
+    tempo: f64,
+    reverb_level: f32,
+    chorus_level: f32,
+    stereo_width: f32,
 
     effects: Option<Effects>,
 
     empty_buffer: Vec<f32>,
+
+    diagnostics: Diagnostics,
+    diagnostics_read: usize,
+
+    block_count: u64,
+    last_block_render_time: Duration,
+    max_block_render_time: Duration,
+    total_render_time: Duration,
 }
 
 impl Synthesizer {
-    /// The number of channels.
+    /// The number of channels of a MIDI port, which is the default number of channels.
     pub const CHANNEL_COUNT: usize = 16;
+    /// The maximum number of channels, which is given by `SynthesizerSettings::channel_count`.
+    pub const MAX_CHANNEL_COUNT: usize = 128;
     /// The percussion channel.
     pub const PERCUSSION_CHANNEL: usize = 9;
 
+    const DEFAULT_TEMPO: f64 = 120.0;
+    const MAX_STEREO_WIDTH: f32 = 2_f32;
+
+    // The suffixes of the preset names for the release samples.
+    const RELEASE_SUFFIXES: [&[&str]; 4] = [&["release"], &["rel"], &["key", "off"], &["keyoff"]];
+
     /// Initializes a new synthesizer using a specified SoundFont and settings.
     ///
     /// # Arguments
     ///
     /// * `sound_font` - The SoundFont instance.
     /// * `settings` - The settings for synthesis.
+    ///
+    /// # Remarks
+    ///
+    /// The SoundFont is shared, not copied.
+    /// Any number of synthesizers can be created from the same SoundFont
+    /// without duplicating its sample data or preset tables.
     pub fn new(
         sound_font: &Arc<SoundFont>,
         settings: &SynthesizerSettings,
     ) -> Result<Self, SynthesizerError> {
         settings.validate()?;
 
-        let mut preset_lookup: HashMap<i32, usize> = HashMap::new();
-
-        let mut min_preset_id = i32::MAX;
-        let mut default_preset: usize = 0;
-        for i in 0..sound_font.presets.len() {
-            let preset = &sound_font.presets[i];
-
-            // The preset ID is Int32, where the upper 16 bits represent the bank number
-            // and the lower 16 bits represent the patch number.
-            // This ID is used to search for presets by the combination of bank number
-            // and patch number.
-            let preset_id = (preset.bank_number << 16) | preset.patch_number;
-            preset_lookup.insert(preset_id, i);
-
-            // The preset with the minimum ID number will be default.
-            // If the SoundFont is GM compatible, the piano will be chosen.
-            if preset_id < min_preset_id {
-                default_preset = i;
-                min_preset_id = preset_id;
-            }
-        }
+        // The percussion channels are the same in each port.
+        let percussion_channels: Vec<bool> = (0..settings.channel_count)
+            .map(|i| settings.percussion_channels[i % Synthesizer::CHANNEL_COUNT])
+            .collect();
 
         let mut channels: Vec<Channel> = Vec::new();
-        for i in 0..Synthesizer::CHANNEL_COUNT {
-            channels.push(Channel::new(i == Synthesizer::PERCUSSION_CHANNEL));
+        for percussion in percussion_channels.iter() {
+            let mut channel = Channel::new(*percussion, settings.profile);
+            channel.volume_curve = settings.volume_curve.clone();
+            channel.expression_curve = settings.expression_curve.clone();
+            channel.pedal_curve = settings.pedal_curve.clone();
+            channels.push(channel);
         }
 
         let voices = VoiceCollection::new(settings);
@@ -109,20 +180,41 @@ impl Synthesizer {
 
         Ok(Self {
             sound_font: Arc::clone(sound_font),
+            previous_sound_font: None,
             sample_rate: settings.sample_rate,
             block_size: settings.block_size,
             maximum_polyphony: settings.maximum_polyphony,
-            preset_lookup,
-            default_preset,
+            pending_block_size: None,
+            pending_maximum_polyphony: None,
             channels,
+            percussion_channels,
+            channel_map: (0..settings.channel_count).collect(),
+            enable_gs_rhythm_parts: settings.enable_gs_rhythm_parts,
+            profile: settings.profile,
             voices,
+            samples: Vec::new(),
+            generated_voices: Vec::with_capacity(settings.maximum_polyphony),
+            voice_sources: BTreeMap::new(),
+            fallback_voice_source: None,
+            release_layers: BTreeMap::new(),
+            pending_release_layers: Vec::new(),
             block_left,
             block_right,
             inverse_block_size,
             block_read,
             master_volume,
+            tempo: Synthesizer::DEFAULT_TEMPO,
+            reverb_level: 1_f32,
+            chorus_level: 1_f32,
+            stereo_width: 1_f32,
             effects,
             empty_buffer,
+            diagnostics: Diagnostics::new(),
+            diagnostics_read: 0,
+            block_count: 0,
+            last_block_render_time: Duration::ZERO,
+            max_block_render_time: Duration::ZERO,
+            total_render_time: Duration::ZERO,
         })
     }
 
@@ -134,11 +226,19 @@ impl Synthesizer {
     /// * `command` - The type of the message.
     /// * `data1` - The first data part of the message.
     /// * `data2` - The second data part of the message.
+    ///
+    /// # Remarks
+    ///
+    /// The message is sent to the channel given by `set_channel_remap`.
+    /// The channels from 16 are those of the following MIDI ports, up to `MAX_CHANNEL_COUNT`,
+    /// and the messages for the ports beyond the channel count are sent to the first ports.
     pub fn process_midi_message(&mut self, channel: i32, command: i32, data1: i32, data2: i32) {
-        if !(0 <= channel && channel < self.channels.len() as i32) {
-            return;
-        }
+        let channel = match self.fold_channel(channel) {
+            Some(value) => value,
+            None => return,
+        };
 
+        let channel = self.channel_map[channel] as i32;
         let channel_info = &mut self.channels[channel as usize];
 
         match command {
@@ -165,14 +265,163 @@ impl Synthesizer {
                 0x78 => self.note_off_all_channel(channel, true), // All Sound Off
                 0x79 => self.reset_all_controllers_channel(channel), // Reset All Controllers
                 0x7B => self.note_off_all_channel(channel, false), // All Note Off
-                _ => (),
+                _ => self.report_unsupported_controller(channel, data1),
             },
             0xC0 => channel_info.set_patch(data1), // Program Change
+            0xD0 => channel_info.set_pressure(data1), // Channel Pressure
             0xE0 => channel_info.set_pitch_bend(data1, data2), // Pitch Bend
             _ => (),
         }
     }
 
+    /// Processes the Universal MIDI Packets of MIDI 2.0.
+    ///
+    /// # Arguments
+    ///
+    /// * `words` - The 32-bit words of any number of packets.
+    ///
+    /// # Remarks
+    ///
+    /// The MIDI 1.0 and MIDI 2.0 channel voice messages are supported, and the other packets are skipped.
+    /// The MIDI 2.0 messages are mapped to the MIDI 1.0 ones at the highest resolution the synthesizer supports,
+    /// which is 14 bits for the pitch bend, the registered controllers and the controllers with the fine part,
+    /// and 7 bits for the velocity and the other controllers.
+    /// The per-note pitch bend is applied to the playing notes of the key,
+    /// with the range of 48 semitones unless changed by the registered controller 7.
+    /// The group of the packets is ignored, so the channels of all the groups are merged.
+    /// An incomplete packet at the end is ignored.
+    pub fn process_ump(&mut self, words: &[u32]) {
+        let mut position = 0;
+        while position < words.len() {
+            let size = UmpMessage::get_packet_size(words[position]);
+            let packet = match words.get(position..position + size) {
+                Some(value) => value,
+                None => break,
+            };
+
+            if let Some(message) = UmpMessage::decode(packet) {
+                self.process_ump_message(message);
+            }
+
+            position += size;
+        }
+    }
+
+    fn process_ump_message(&mut self, message: UmpMessage) {
+        match message {
+            UmpMessage::Midi1 {
+                channel,
+                command,
+                data1,
+                data2,
+            } => self.process_midi_message(
+                channel as i32,
+                command as i32,
+                data1 as i32,
+                data2 as i32,
+            ),
+            UmpMessage::NoteOff { channel, key } => {
+                self.process_midi_message(channel as i32, 0x80, key as i32, 0)
+            }
+            UmpMessage::NoteOn {
+                channel,
+                key,
+                velocity,
+            } => {
+                // Unlike MIDI 1.0, the zero velocity does not mean a note-off.
+                let velocity = cmp::max(velocity >> 9, 1);
+                self.process_midi_message(channel as i32, 0x90, key as i32, velocity as i32)
+            }
+            UmpMessage::ControlChange {
+                channel,
+                index,
+                value,
+            } => {
+                let (coarse, fine) = ((value >> 25) as i32, ((value >> 18) & 0x7F) as i32);
+                self.process_midi_message(channel as i32, 0xB0, index as i32, coarse);
+                if matches!(index, 0x01 | 0x07 | 0x0A | 0x0B) {
+                    self.process_midi_message(channel as i32, 0xB0, index as i32 + 0x20, fine);
+                }
+            }
+            UmpMessage::RegisteredController {
+                channel,
+                bank,
+                index,
+                value,
+            } => {
+                let (coarse, fine) = ((value >> 25) as i32, ((value >> 18) & 0x7F) as i32);
+                if bank == 0 && index == 7 {
+                    self.set_note_pitch_bend_range(channel as i32, coarse, fine);
+                    return;
+                }
+
+                // The RPN is reset to null afterwards, since MIDI 2.0 has no such state.
+                let channel = channel as i32;
+                self.process_midi_message(channel, 0xB0, 0x65, bank as i32);
+                self.process_midi_message(channel, 0xB0, 0x64, index as i32);
+                self.process_midi_message(channel, 0xB0, 0x06, coarse);
+                self.process_midi_message(channel, 0xB0, 0x26, fine);
+                self.process_midi_message(channel, 0xB0, 0x65, 0x7F);
+                self.process_midi_message(channel, 0xB0, 0x64, 0x7F);
+            }
+            UmpMessage::ProgramChange {
+                channel,
+                program,
+                bank,
+            } => {
+                if let Some(bank) = bank {
+                    self.process_midi_message(channel as i32, 0xB0, 0x00, bank as i32);
+                }
+                self.process_midi_message(channel as i32, 0xC0, program as i32, 0);
+            }
+            UmpMessage::ChannelPressure { channel, value } => {
+                self.process_midi_message(channel as i32, 0xD0, (value >> 25) as i32, 0)
+            }
+            UmpMessage::PitchBend { channel, value } => {
+                let value = (value >> 18) as i32;
+                self.process_midi_message(channel as i32, 0xE0, value & 0x7F, value >> 7)
+            }
+            UmpMessage::PerNotePitchBend {
+                channel,
+                key,
+                value,
+            } => {
+                let value = (value as f64 - 2_147_483_648_f64) / 2_147_483_648_f64;
+                self.set_note_pitch_bend(channel as i32, key as i32, value as f32);
+            }
+            UmpMessage::PerNoteReset { channel, key } => {
+                self.set_note_pitch_bend(channel as i32, key as i32, 0_f32)
+            }
+        }
+    }
+
+    // The value is between -1 and 1, which is multiplied by the range of the channel.
+    // The channel is folded and remapped in the same way as `process_midi_message`.
+    fn set_note_pitch_bend(&mut self, channel: i32, key: i32, value: f32) {
+        let channel = match self.fold_channel(channel) {
+            Some(value) => value,
+            None => return,
+        };
+
+        let channel = self.channel_map[channel] as i32;
+        let semitones = self.channels[channel as usize].get_note_pitch_bend_range() * value;
+        for voice in self.voices.get_active_voices().iter_mut() {
+            if voice.channel == channel && voice.key == key {
+                voice.note_pitch_bend = semitones;
+            }
+        }
+    }
+
+    fn set_note_pitch_bend_range(&mut self, channel: i32, semitones: i32, cents: i32) {
+        let channel = match self.fold_channel(channel) {
+            Some(value) => value,
+            None => return,
+        };
+
+        let channel = self.channel_map[channel];
+        self.channels[channel].set_note_pitch_bend_range(semitones, cents);
+    }
+
     /// Stops a note.
     ///
     /// # Arguments
@@ -185,6 +434,12 @@ impl Synthesizer {
         }
 
         for voice in self.voices.get_active_voices().iter_mut() {
+            if voice.channel == channel && voice.key == key && !voice.release_layer {
+                voice.end();
+            }
+        }
+
+        for voice in self.generated_voices.iter_mut() {
             if voice.channel == channel && voice.key == key {
                 voice.end();
             }
@@ -199,6 +454,68 @@ impl Synthesizer {
     /// * `key` - The key of the note.
     /// * `velocity` - The velocity of the note.
     pub fn note_on(&mut self, channel: i32, key: i32, velocity: i32) {
+        self.start_note(channel, key, velocity, 0_f32);
+    }
+
+    /// Starts a note with the pitch shifted from the key.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel of the note.
+    /// * `key` - The key of the note.
+    /// * `velocity` - The velocity of the note.
+    /// * `cents` - The pitch offset of the note in cents, such as 50 to play a quarter tone higher.
+    ///
+    /// # Remarks
+    ///
+    /// The offset is added to the pitch bend of the channel, and does not affect the other notes.
+    /// This allows to play the microtonal chords in a single channel.
+    /// The note is stopped by `note_off` with the same key.
+    pub fn note_on_detuned(&mut self, channel: i32, key: i32, velocity: i32, cents: f32) {
+        if !cents.is_finite() {
+            panic!("The detune must be a finite value.");
+        }
+
+        self.start_note(channel, key, velocity, 0.01_f32 * cents);
+    }
+
+    /// Changes the pitch offset of a playing note.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel of the note.
+    /// * `key` - The key of the note.
+    /// * `cents` - The pitch offset of the note in cents, where 0 plays the key at its own pitch.
+    ///
+    /// # Remarks
+    ///
+    /// All the voices of the note, including the ones being released, are changed.
+    /// This allows to glide a single note without the pitch bend of the channel.
+    pub fn set_note_detune(&mut self, channel: i32, key: i32, cents: f32) {
+        if !cents.is_finite() {
+            panic!("The detune must be a finite value.");
+        }
+
+        if !(0 <= channel && channel < self.channels.len() as i32) {
+            return;
+        }
+
+        let semitones = 0.01_f32 * cents;
+        for voice in self.voices.get_active_voices().iter_mut() {
+            if voice.channel == channel && voice.key == key {
+                voice.detune = semitones;
+            }
+        }
+
+        for voice in self.generated_voices.iter_mut() {
+            if voice.channel == channel && voice.key == key {
+                voice.detune = semitones;
+            }
+        }
+    }
+
+    // The detune is in semitones.
+    fn start_note(&mut self, channel: i32, key: i32, velocity: i32, detune: f32) {
         if velocity == 0 {
             self.note_off(channel, key);
             return;
@@ -210,28 +527,96 @@ impl Synthesizer {
 
         let channel_info = &self.channels[channel as usize];
 
-        let preset_id = (channel_info.get_bank_number() << 16) | channel_info.get_patch_number();
+        let (bank_number, patch_number) = channel_info.get_preset_number(key);
 
-        let mut preset = self.default_preset;
-        match self.preset_lookup.get(&preset_id) {
-            Some(value) => preset = *value,
-            None => {
-                // Try fallback to the GM sound set.
-                // Normally, the given patch number + the bank number 0 will work.
-                // For drums (bank number >= 128), it seems to be better to select the standard set (128:0).
-                let gm_preset_id = if channel_info.get_bank_number() < 128 {
-                    channel_info.get_patch_number()
-                } else {
-                    128 << 16
-                };
+        // The source of the channel takes precedence over the ones of the presets.
+        let source = match channel_info.voice_source.as_ref() {
+            Some(value) => Some(Arc::clone(value)),
+            None => self
+                .voice_sources
+                .get(&((bank_number << 16) | patch_number))
+                .cloned(),
+        };
+        if let Some(source) = source {
+            self.start_generated_voice(
+                &source,
+                channel,
+                bank_number,
+                patch_number,
+                key,
+                velocity,
+                detune,
+            );
+            return;
+        }
 
-                // If no corresponding preset was found. Use the default one...
-                if let Some(value) = self.preset_lookup.get(&gm_preset_id) {
-                    preset = *value
+        let preset_lookup = &self.sound_font.preset_lookup;
+        let preset = match preset_lookup.get(bank_number, patch_number) {
+            Some(value) => value,
+            None => {
+                if let Some(source) = self.fallback_voice_source.clone() {
+                    self.diagnostics.push_once(
+                        DiagnosticCode::PresetNotFound,
+                        DiagnosticSeverity::Warning,
+                        DiagnosticLocation::Preset {
+                            bank_number,
+                            patch_number,
+                        },
+                        || {
+                            format!(
+                            "the preset {}:{} was not found and the fallback voice source was used",
+                            bank_number, patch_number
+                        )
+                        },
+                    );
+
+                    self.start_generated_voice(
+                        &source,
+                        channel,
+                        bank_number,
+                        patch_number,
+                        key,
+                        velocity,
+                        detune,
+                    );
+                    return;
                 }
+
+                self.diagnostics.push_once(
+                    DiagnosticCode::PresetNotFound,
+                    DiagnosticSeverity::Warning,
+                    DiagnosticLocation::Preset {
+                        bank_number,
+                        patch_number,
+                    },
+                    || {
+                        format!(
+                            "the preset {}:{} was not found and a fallback preset was used",
+                            bank_number, patch_number
+                        )
+                    },
+                );
+
+                preset_lookup.get_fallback(bank_number, patch_number)
             }
-        }
+        };
 
+        self.cut_exclusive_notes(channel, key);
+
+        self.start_preset_voices(preset, channel, key, velocity, detune, None);
+    }
+
+    // Starts the voices of the preset in the SoundFont.
+    // With the gain in decibels, the voices are started as a release layer.
+    fn start_preset_voices(
+        &mut self,
+        preset: usize,
+        channel: i32,
+        key: i32,
+        velocity: i32,
+        detune: f32,
+        release_gain: Option<f32>,
+    ) {
         let preset = &self.sound_font.presets[preset];
         for preset_region in preset.regions.iter() {
             if preset_region.contains(key, velocity) {
@@ -240,8 +625,57 @@ impl Synthesizer {
                     if instrument_region.contains(key, velocity) {
                         let region_pair = RegionPair::new(preset_region, instrument_region);
 
-                        if let Some(value) = self.voices.request_new(instrument_region, channel) {
-                            value.start(&region_pair, channel, key, velocity)
+                        if let Some(value) =
+                            self.voices
+                                .request_new(instrument_region, channel, &self.channels)
+                        {
+                            let source =
+                                self.sound_font.sample_headers[instrument_region.sample].source;
+                            value.start(
+                                &region_pair,
+                                channel,
+                                key,
+                                velocity,
+                                self.profile,
+                                self.sound_font.get_wave_slice(source).data.len(),
+                            );
+                            value.source = source;
+                            value.detune = detune;
+                            if let Some(rate) = self.channels[channel as usize].vibrato_rate {
+                                value.set_vibrato_rate(&region_pair, rate);
+                            }
+                            if let Some(scale) = self.channels[channel as usize].envelope_override {
+                                value.set_envelope_override(&region_pair, key, velocity, &scale);
+                            }
+                            if let Some(lfo) = self.channels[channel as usize].vibrato_lfo_override
+                            {
+                                let frequency = lfo.rate.map(|x| x.get_frequency(self.tempo));
+                                value.set_vibrato_lfo_override(&region_pair, frequency, lfo.depth);
+                            }
+                            if let Some(lfo) =
+                                self.channels[channel as usize].modulation_lfo_override
+                            {
+                                let frequency = lfo.rate.map(|x| x.get_frequency(self.tempo));
+                                value.set_modulation_lfo_override(
+                                    &region_pair,
+                                    frequency,
+                                    lfo.depth,
+                                );
+                            }
+                            if let Some(filter) = self.channels[channel as usize].filter_override {
+                                value.set_filter_override(&filter);
+                            }
+                            value.bank_number = preset.get_bank_number();
+                            value.patch_number = preset.get_patch_number();
+                            if let Some(gain) = release_gain {
+                                value.start_release_layer(gain);
+                            }
+                            self.voices.events.push(
+                                VoiceEventKind::Started,
+                                channel,
+                                key,
+                                velocity,
+                            );
                         }
                     }
                 }
@@ -249,6 +683,121 @@ impl Synthesizer {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn start_generated_voice(
+        &mut self,
+        source: &Arc<dyn VoiceSource>,
+        channel: i32,
+        bank_number: i32,
+        patch_number: i32,
+        key: i32,
+        velocity: i32,
+        detune: f32,
+    ) {
+        self.cut_exclusive_notes(channel, key);
+
+        let generator = source.start(bank_number, patch_number, key, velocity, self.sample_rate);
+
+        // The notes of the sources have their own polyphony, in which the oldest note is stolen,
+        // and its buffer is reused to avoid the allocation.
+        let mut voice = if self.generated_voices.len() < self.maximum_polyphony {
+            GeneratedVoice::new(generator, channel, key, self.block_size)
+        } else {
+            let mut voice = self.generated_voices.remove(0);
+            voice.restart(generator, channel, key);
+            voice
+        };
+        voice.detune = detune;
+        self.generated_voices.push(voice);
+    }
+
+    fn report_unsupported_controller(&mut self, channel: i32, number: i32) {
+        self.diagnostics.push_once(
+            DiagnosticCode::UnsupportedController,
+            DiagnosticSeverity::Info,
+            DiagnosticLocation::Controller {
+                channel: channel as usize,
+                number,
+            },
+            || {
+                format!(
+                    "the controller {} in the channel {} is not supported and was ignored",
+                    number, channel
+                )
+            },
+        );
+    }
+
+    // The other notes in the same group are stopped in the same way as the exclusive class.
+    fn cut_exclusive_notes(&mut self, channel: i32, key: i32) {
+        let group = self.get_exclusive_note_group(channel, key);
+        if group == 0 {
+            return;
+        }
+
+        let fade_length = self.voices.get_fade_length();
+        for i in 0..self.voices.active_voice_count {
+            let voice = &self.voices.get_playing_voices()[i];
+            if voice.channel == channel
+                && voice.key != key
+                && !voice.is_choked()
+                && self.get_exclusive_note_group(channel, voice.key) == group
+            {
+                self.voices.get_active_voices()[i].choke(fade_length);
+            }
+        }
+    }
+
+    // The groups set by the user take precedence over the drum groups of the profile,
+    // which are negated so that they do not mix with the ones set by the user.
+    fn get_exclusive_note_group(&self, channel: i32, key: i32) -> i32 {
+        let channel_info = &self.channels[channel as usize];
+        match channel_info.exclusive_groups.get(key as usize) {
+            Some(0) | None => {
+                if channel_info.is_percussion_channel {
+                    -self.profile.get_exclusive_drum_group(key)
+                } else {
+                    0
+                }
+            }
+            Some(group) => *group as i32,
+        }
+    }
+
+    /// Plays a raw waveform once, without the SoundFont.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The waveform at the sample rate of the synthesizer.
+    /// * `root_key` - The key at which the waveform sounds at its original pitch.
+    ///   The waveform is played as the middle C (60), so 60 keeps the pitch, and 48 plays it an octave higher.
+    /// * `pan` - The balance between the left (-1) and the right (1).
+    /// * `gain` - The linear gain, where 1 plays the waveform as it is.
+    ///
+    /// # Remarks
+    ///
+    /// This allows to layer sound effects, such as UI clicks and stingers,
+    /// through the same master volume and effects as the notes.
+    /// The waveform is copied, and is sent to the reverb and the chorus
+    /// as a channel with the default controllers.
+    /// It does not use a voice, so it is not limited by the maximum polyphony,
+    /// and is not affected by the channels, the note offs and the voice events.
+    /// The waveforms stop by `stop_samples` and `reset`, and are not saved by `save_state`.
+    pub fn play_sample(&mut self, data: &[f32], root_key: i32, pan: f32, gain: f32) {
+        if data.is_empty() {
+            return;
+        }
+
+        let pitch = (60 - root_key) as f32;
+        self.samples
+            .push(SampleVoice::new(data, pitch, pan, gain, self.block_size));
+    }
+
+    /// Stops all the waveforms played by `play_sample` immediately.
+    pub fn stop_samples(&mut self) {
+        self.samples.clear();
+    }
+
     /// Stops all the notes in the specified channel.
     ///
     /// # Arguments
@@ -257,10 +806,14 @@ impl Synthesizer {
     pub fn note_off_all(&mut self, immediate: bool) {
         if immediate {
             self.voices.clear();
+            self.generated_voices.clear();
         } else {
             for voice in self.voices.get_active_voices().iter_mut() {
                 voice.end();
             }
+            for voice in self.generated_voices.iter_mut() {
+                voice.end();
+            }
         }
     }
 
@@ -277,12 +830,18 @@ impl Synthesizer {
                     voice.kill();
                 }
             }
+            self.generated_voices.retain(|x| x.channel != channel);
         } else {
             for voice in self.voices.get_active_voices().iter_mut() {
                 if voice.channel == channel {
                     voice.end();
                 }
             }
+            for voice in self.generated_voices.iter_mut() {
+                if voice.channel == channel {
+                    voice.end();
+                }
+            }
         }
     }
 
@@ -309,8 +868,16 @@ impl Synthesizer {
     /// Resets the synthesizer.
     pub fn reset(&mut self) {
         self.voices.clear();
-
-        for channel in &mut self.channels {
+        self.samples.clear();
+        self.generated_voices.clear();
+        self.pending_release_layers.clear();
+
+        for (channel, value) in self
+            .channels
+            .iter_mut()
+            .zip(self.percussion_channels.iter())
+        {
+            channel.set_percussion_channel(*value);
             channel.reset();
         }
 
@@ -322,6 +889,111 @@ impl Synthesizer {
         self.block_read = self.block_size;
     }
 
+    /// Takes a snapshot of the current state.
+    ///
+    /// # Arguments
+    ///
+    /// * `include_voices` - If `true`, the playing voices and the effects are also saved.
+    ///
+    /// # Remarks
+    ///
+    /// The controllers of all the channels and the master volume are always saved.
+    /// With the voices, restoring the snapshot continues the sound from the exact sample,
+    /// which allows gapless pause and resume, or resuming a long rendering from a checkpoint.
+    /// Such a snapshot keeps a reference to the SoundFont being played.
+    /// The notes of the voice sources are not saved, since their generators cannot be copied.
+    pub fn save_state(&self, include_voices: bool) -> SynthesizerState {
+        let voices = if include_voices {
+            Some(VoiceSnapshot {
+                sample_rate: self.sample_rate,
+                block_size: self.block_size,
+                sound_font: Arc::clone(&self.sound_font),
+                previous_sound_font: self.previous_sound_font.clone(),
+                voices: self.voices.get_snapshot(),
+                block_left: self.block_left.clone(),
+                block_right: self.block_right.clone(),
+                block_read: self.block_read,
+                effects: self.effects.clone(),
+            })
+        } else {
+            None
+        };
+
+        SynthesizerState {
+            channels: self.channels.clone(),
+            master_volume: self.master_volume,
+            voices,
+        }
+    }
+
+    /// Restores a snapshot taken by `save_state`.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The snapshot to be restored.
+    ///
+    /// # Remarks
+    ///
+    /// If the snapshot has no voice, the playing notes are stopped immediately
+    /// and only the controllers are restored.
+    /// If the snapshot has the voices, the SoundFont at the time of the snapshot is also restored.
+    /// In this case, the sample rate and the block size must be the same as those of the snapshot.
+    /// The voices exceeding the current maximum polyphony are discarded.
+    /// The notes of the voice sources are always stopped immediately, as they are not saved.
+    /// The number of the channels must always be the same as that of the snapshot.
+    pub fn load_state(&mut self, state: &SynthesizerState) -> Result<(), SynthesizerError> {
+        if state.channels.len() != self.channels.len() {
+            return Err(SynthesizerError::IncompatibleState);
+        }
+
+        if let Some(snapshot) = &state.voices {
+            if snapshot.sample_rate != self.sample_rate || snapshot.block_size != self.block_size {
+                return Err(SynthesizerError::IncompatibleState);
+            }
+        }
+
+        self.channels.clone_from(&state.channels);
+        self.master_volume = state.master_volume;
+        self.generated_voices.clear();
+        self.pending_release_layers.clear();
+
+        match &state.voices {
+            Some(snapshot) => {
+                self.sound_font = Arc::clone(&snapshot.sound_font);
+                self.previous_sound_font = snapshot.previous_sound_font.clone();
+                self.voices.restore(&snapshot.voices);
+                self.block_left.copy_from_slice(&snapshot.block_left);
+                self.block_right.copy_from_slice(&snapshot.block_right);
+                self.block_read = snapshot.block_read;
+                match (self.effects.as_mut(), &snapshot.effects) {
+                    (Some(effects), Some(value)) => effects.clone_from(value),
+                    _ => self.mute_effects(),
+                }
+            }
+            None => {
+                self.voices.clear();
+                self.mute_effects();
+                self.block_read = self.block_size;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Saves the controllers into the existing snapshot, which does not allocate if it has the same channels.
+    pub(crate) fn save_controllers(&self, state: &mut SynthesizerState) {
+        state.channels.clone_from(&self.channels);
+        state.master_volume = self.master_volume;
+        state.voices = None;
+    }
+
+    // Restores the controllers and the programs of the snapshot, keeping the playing notes.
+    pub(crate) fn restore_controllers(&mut self, state: &SynthesizerState) {
+        for (channel, value) in self.channels.iter_mut().zip(state.channels.iter()) {
+            channel.restore_controllers(value);
+        }
+    }
+
     /// Renders the waveform.
     ///
     /// # Arguments
@@ -342,7 +1014,10 @@ impl Synthesizer {
         let mut wrote = 0;
         while wrote < left_length {
             if self.block_read == self.block_size {
+                self.apply_pending_changes();
+                let stopwatch = Stopwatch::start();
                 self.render_block();
+                self.add_block_render_time(stopwatch.elapsed());
                 self.block_read = 0;
             }
 
@@ -360,40 +1035,227 @@ impl Synthesizer {
         }
     }
 
+    // This must be called only between blocks.
+    pub(crate) fn apply_pending_changes(&mut self) {
+        if let Some(block_size) = self.pending_block_size.take() {
+            self.block_size = block_size;
+            self.block_left = vec![0_f32; block_size];
+            self.block_right = vec![0_f32; block_size];
+            self.empty_buffer = vec![0_f32; block_size];
+            self.inverse_block_size = 1_f32 / block_size as f32;
+            self.block_read = block_size;
+            self.voices.set_block_size(block_size);
+            for sample in self.samples.iter_mut() {
+                sample.set_block_size(block_size);
+            }
+            for voice in self.generated_voices.iter_mut() {
+                voice.set_block_size(block_size);
+            }
+            if let Some(effects) = self.effects.as_mut() {
+                effects.set_block_size(block_size);
+            }
+        }
+
+        if let Some(maximum_polyphony) = self.pending_maximum_polyphony.take() {
+            self.maximum_polyphony = maximum_polyphony;
+            let mut settings = SynthesizerSettings::new(self.sample_rate);
+            settings.block_size = self.block_size;
+            settings.maximum_polyphony = maximum_polyphony;
+            self.voices.set_maximum_polyphony(&settings);
+
+            let excess = self
+                .generated_voices
+                .len()
+                .saturating_sub(maximum_polyphony);
+            self.generated_voices.drain(..excess);
+            self.generated_voices
+                .reserve(maximum_polyphony - self.generated_voices.len());
+        }
+    }
+
+    // The layers are started before the voices are processed, so they are one block behind the release.
+    fn start_release_layers(&mut self) {
+        if self.pending_release_layers.is_empty() {
+            return;
+        }
+
+        let mut pending = mem::take(&mut self.pending_release_layers);
+        for &(voice, layer, gain) in pending.iter() {
+            let preset = match self
+                .sound_font
+                .preset_lookup
+                .get(layer.bank_number, layer.patch_number)
+            {
+                Some(value) => value,
+                None => {
+                    self.diagnostics.push_once(
+                        DiagnosticCode::PresetNotFound,
+                        DiagnosticSeverity::Warning,
+                        DiagnosticLocation::Preset {
+                            bank_number: layer.bank_number,
+                            patch_number: layer.patch_number,
+                        },
+                        || format!(
+                            "the preset {}:{} of the release layer was not found and the layer was not played",
+                            layer.bank_number, layer.patch_number
+                        ),
+                    );
+                    continue;
+                }
+            };
+
+            self.start_preset_voices(
+                preset,
+                voice.channel,
+                voice.key,
+                voice.velocity,
+                voice.detune,
+                Some(gain),
+            );
+        }
+
+        // The buffer is kept to avoid the allocation in the next blocks.
+        pending.clear();
+        self.pending_release_layers = pending;
+    }
+
+    // The voices which finished in the same block as their release are also included.
+    // A note with multiple voices triggers its layer only once.
+    fn find_release_layers(&mut self) {
+        if self.release_layers.is_empty() {
+            return;
+        }
+
+        let sample_rate = self.sample_rate as f32;
+        for voice in self.voices.released_voices.iter() {
+            let layer = match self
+                .release_layers
+                .get(&((voice.bank_number << 16) | voice.patch_number))
+            {
+                Some(value) => *value,
+                None => continue,
+            };
+
+            if self
+                .pending_release_layers
+                .iter()
+                .any(|x| x.0.channel == voice.channel && x.0.key == voice.key)
+            {
+                continue;
+            }
+
+            if let Some(gain) = layer.get_gain(voice.voice_length as f32 / sample_rate) {
+                self.pending_release_layers.push((*voice, layer, gain));
+            }
+        }
+    }
+
     fn render_block(&mut self) {
-        self.voices
-            .process(&self.sound_font.wave_data, &self.channels);
+        let _span = trace_span!("render_block", voices = self.voices.active_voice_count).entered();
+
+        self.start_release_layers();
+
+        self.voices.process(
+            &self.sound_font,
+            self.previous_sound_font.as_deref(),
+            &self.channels,
+        );
+
+        self.find_release_layers();
+
+        // The previous SoundFont is no longer needed after its voices have finished.
+        if self.previous_sound_font.is_some() && !self.voices.has_previous_sound_font_voices() {
+            self.previous_sound_font = None;
+        }
+
+        self.voices.events.advance(self.block_size);
+
+        self.samples.retain_mut(|x| x.process());
+
+        let channels = &self.channels;
+        self.generated_voices.retain_mut(|x| x.process(channels));
 
         self.block_left = self.empty_buffer.clone();
         self.block_right = self.empty_buffer.clone();
 
-        for voice in self.voices.get_active_voices().iter_mut() {
-            let previous_gain_left = self.master_volume * voice.previous_mix_gain_left;
-            let current_gain_left = self.master_volume * voice.current_mix_gain_left;
+        let master_volume = self.master_volume;
+        let inverse_block_size = self.inverse_block_size;
+        let parallel = self.voices.parallel;
+        let voices = self.voices.get_active_voices();
+        if parallel && voices.len() >= VoiceCollection::PARALLEL_THRESHOLD {
+            // Each worker mixes a part of the voices into its own buffer,
+            // and then the partial buffers are summed.
+            let chunk_size = voices.len().div_ceil(current_num_threads()).max(8);
+            let empty_buffer = &self.empty_buffer;
+            let partials: Vec<(Vec<f32>, Vec<f32>)> = voices
+                .par_chunks(chunk_size)
+                .map(|chunk| {
+                    let mut left = empty_buffer.clone();
+                    let mut right = empty_buffer.clone();
+                    for voice in chunk {
+                        Synthesizer::write_voice(
+                            master_volume,
+                            voice,
+                            &mut left,
+                            &mut right,
+                            inverse_block_size,
+                        );
+                    }
+                    (left, right)
+                })
+                .collect();
+            for (left, right) in partials.iter() {
+                ArrayMath::sum(left, &mut self.block_left[..]);
+                ArrayMath::sum(right, &mut self.block_right[..]);
+            }
+        } else {
+            for voice in voices.iter() {
+                Synthesizer::write_voice(
+                    master_volume,
+                    voice,
+                    &mut self.block_left,
+                    &mut self.block_right,
+                    inverse_block_size,
+                );
+            }
+        }
+
+        for voice in self.generated_voices.iter() {
             Synthesizer::write_block(
-                previous_gain_left,
-                current_gain_left,
+                master_volume * voice.previous_mix_gain_left,
+                master_volume * voice.current_mix_gain_left,
                 &voice.block[..],
                 &mut self.block_left[..],
-                self.inverse_block_size,
+                inverse_block_size,
             );
-            let previous_gain_right = self.master_volume * voice.previous_mix_gain_right;
-            let current_gain_right = self.master_volume * voice.current_mix_gain_right;
             Synthesizer::write_block(
-                previous_gain_right,
-                current_gain_right,
+                master_volume * voice.previous_mix_gain_right,
+                master_volume * voice.current_mix_gain_right,
                 &voice.block[..],
                 &mut self.block_right[..],
-                self.inverse_block_size,
+                inverse_block_size,
             );
         }
 
-        if let Some(effects) = self.effects.as_mut() {
-            let chorus = &mut effects.chorus;
-            let chorus_input_left = &mut effects.chorus_input_left[..];
-            let chorus_input_right = &mut effects.chorus_input_right[..];
-            let chorus_output_left = &mut effects.chorus_output_left[..];
-            let chorus_output_right = &mut effects.chorus_output_right[..];
+        for sample in self.samples.iter() {
+            ArrayMath::multiply_add(
+                master_volume * sample.gain_left,
+                &sample.block[..],
+                &mut self.block_left[..],
+            );
+            ArrayMath::multiply_add(
+                master_volume * sample.gain_right,
+                &sample.block[..],
+                &mut self.block_right[..],
+            );
+        }
+
+        if let Some(effects) = self.effects.as_mut() {
+            let chorus = &mut effects.chorus;
+            let chorus_input_left = &mut effects.chorus_input_left[..];
+            let chorus_input_right = &mut effects.chorus_input_right[..];
+            let chorus_output_left = &mut effects.chorus_output_left[..];
+            let chorus_output_right = &mut effects.chorus_output_right[..];
             for i in 0..self.block_size {
                 chorus_input_left[i] = 0_f32;
                 chorus_input_right[i] = 0_f32;
@@ -419,22 +1281,44 @@ impl Synthesizer {
                     self.inverse_block_size,
                 );
             }
+            for voice in self.generated_voices.iter() {
+                Synthesizer::write_block(
+                    voice.previous_chorus_send * voice.previous_mix_gain_left,
+                    voice.current_chorus_send * voice.current_mix_gain_left,
+                    &voice.block[..],
+                    chorus_input_left,
+                    self.inverse_block_size,
+                );
+                Synthesizer::write_block(
+                    voice.previous_chorus_send * voice.previous_mix_gain_right,
+                    voice.current_chorus_send * voice.current_mix_gain_right,
+                    &voice.block[..],
+                    chorus_input_right,
+                    self.inverse_block_size,
+                );
+            }
+            let chorus_send = (1_f32 / 127_f32) * self.profile.get_default_chorus_send() as f32;
+            for sample in self.samples.iter() {
+                ArrayMath::multiply_add(
+                    chorus_send * sample.gain_left,
+                    &sample.block[..],
+                    chorus_input_left,
+                );
+                ArrayMath::multiply_add(
+                    chorus_send * sample.gain_right,
+                    &sample.block[..],
+                    chorus_input_right,
+                );
+            }
             chorus.process(
                 chorus_input_left,
                 chorus_input_right,
                 chorus_output_left,
                 chorus_output_right,
             );
-            ArrayMath::multiply_add(
-                self.master_volume,
-                chorus_output_left,
-                &mut self.block_left[..],
-            );
-            ArrayMath::multiply_add(
-                self.master_volume,
-                chorus_output_right,
-                &mut self.block_right[..],
-            );
+            let chorus_gain = self.master_volume * self.chorus_level;
+            ArrayMath::multiply_add(chorus_gain, chorus_output_left, &mut self.block_left[..]);
+            ArrayMath::multiply_add(chorus_gain, chorus_output_right, &mut self.block_right[..]);
 
             let reverb = &mut effects.reverb;
             let reverb_input = &mut effects.reverb_input[..];
@@ -458,21 +1342,120 @@ impl Synthesizer {
                     self.inverse_block_size,
                 );
             }
+            for voice in self.generated_voices.iter() {
+                Synthesizer::write_block(
+                    reverb.get_input_gain()
+                        * voice.previous_reverb_send
+                        * (voice.previous_mix_gain_left + voice.previous_mix_gain_right),
+                    reverb.get_input_gain()
+                        * voice.current_reverb_send
+                        * (voice.current_mix_gain_left + voice.current_mix_gain_right),
+                    &voice.block[..],
+                    &mut reverb_input[..],
+                    self.inverse_block_size,
+                );
+            }
+            let reverb_send = (1_f32 / 127_f32) * self.profile.get_default_reverb_send() as f32;
+            for sample in self.samples.iter() {
+                ArrayMath::multiply_add(
+                    reverb.get_input_gain() * reverb_send * (sample.gain_left + sample.gain_right),
+                    &sample.block[..],
+                    &mut reverb_input[..],
+                );
+            }
 
             reverb.process(reverb_input, reverb_output_left, reverb_output_right);
-            ArrayMath::multiply_add(
-                self.master_volume,
-                reverb_output_left,
-                &mut self.block_left[..],
-            );
-            ArrayMath::multiply_add(
-                self.master_volume,
-                reverb_output_right,
-                &mut self.block_right[..],
-            );
+            let reverb_gain = self.master_volume * self.reverb_level;
+            ArrayMath::multiply_add(reverb_gain, reverb_output_left, &mut self.block_left[..]);
+            ArrayMath::multiply_add(reverb_gain, reverb_output_right, &mut self.block_right[..]);
+        }
+
+        if self.stereo_width != 1_f32 {
+            // The side signal is scaled, while the mid signal is kept as it is.
+            let width = self.stereo_width;
+            for (left, right) in self.block_left.iter_mut().zip(self.block_right.iter_mut()) {
+                let mid = 0.5_f32 * (*left + *right);
+                let side = 0.5_f32 * width * (*left - *right);
+                *left = mid + side;
+                *right = mid - side;
+            }
+        }
+    }
+
+    // The synthesizer is silent if no voice is playing and the last block was not audible.
+    // The remaining tail of the effects is not audible either in that case.
+    pub(crate) fn is_silent(&self) -> bool {
+        if self.voices.active_voice_count > 0
+            || !self.samples.is_empty()
+            || !self.generated_voices.is_empty()
+        {
+            return false;
+        }
+
+        self.block_left
+            .iter()
+            .chain(self.block_right.iter())
+            .all(|x| x.abs() < SoundFontMath::NON_AUDIBLE)
+    }
+
+    // The approximate size of the synthesizer in bytes, excluding the SoundFont.
+    pub(crate) fn get_memory_size(&self) -> usize {
+        let effects = match self.effects.as_ref() {
+            Some(effects) => effects.get_memory_size(),
+            None => 0,
+        };
+        let blocks = self.block_left.len() + self.block_right.len() + self.empty_buffer.len();
+        mem::size_of::<Synthesizer>()
+            + self.channels.len() * mem::size_of::<Channel>()
+            + blocks * mem::size_of::<f32>()
+            + self.voices.get_memory_size()
+            + self
+                .samples
+                .iter()
+                .map(|x| x.get_memory_size())
+                .sum::<usize>()
+            + self
+                .generated_voices
+                .iter()
+                .map(|x| x.get_memory_size())
+                .sum::<usize>()
+            + effects
+    }
+
+    pub(crate) fn mute_effects(&mut self) {
+        if let Some(effects) = self.effects.as_mut() {
+            effects.reverb.mute();
+            effects.chorus.mute();
         }
     }
 
+    fn write_voice(
+        master_volume: f32,
+        voice: &Voice,
+        left: &mut [f32],
+        right: &mut [f32],
+        inverse_block_size: f32,
+    ) {
+        let previous_gain_left = master_volume * voice.previous_mix_gain_left;
+        let current_gain_left = master_volume * voice.current_mix_gain_left;
+        Synthesizer::write_block(
+            previous_gain_left,
+            current_gain_left,
+            &voice.block[..],
+            left,
+            inverse_block_size,
+        );
+        let previous_gain_right = master_volume * voice.previous_mix_gain_right;
+        let current_gain_right = master_volume * voice.current_mix_gain_right;
+        Synthesizer::write_block(
+            previous_gain_right,
+            current_gain_right,
+            &voice.block[..],
+            right,
+            inverse_block_size,
+        );
+    }
+
     fn write_block(
         previous_gain: f32,
         current_gain: f32,
@@ -492,11 +1475,211 @@ impl Synthesizer {
         }
     }
 
+    /// Replaces the SoundFont used as the audio source.
+    ///
+    /// # Arguments
+    ///
+    /// * `sound_font` - The new SoundFont instance.
+    /// * `reset_voices` - If `true`, the playing notes stop immediately.
+    ///
+    /// # Remarks
+    ///
+    /// The channel states, such as the volumes, the programs and the pitch bends, are kept,
+    /// and the subsequent notes are played with the new SoundFont.
+    /// If `reset_voices` is `false`, the playing notes continue with the previous SoundFont until they finish.
+    /// Only one previous SoundFont is kept, so the notes still playing from the one before it
+    /// stop immediately if the SoundFont is replaced again.
+    pub fn set_sound_font(&mut self, sound_font: &Arc<SoundFont>, reset_voices: bool) {
+        if reset_voices {
+            self.voices.clear();
+            self.generated_voices.clear();
+            self.previous_sound_font = None;
+        } else {
+            self.voices.kill_previous_sound_font_voices();
+            self.voices.mark_previous_sound_font_voices();
+            self.previous_sound_font = Some(Arc::clone(&self.sound_font));
+        }
+
+        self.sound_font = Arc::clone(sound_font);
+    }
+
     /// Gets the SoundFont used as the audio source.
     pub fn get_sound_font(&self) -> &SoundFont {
         &self.sound_font
     }
 
+    /// Gets the voice source registered for the specified preset.
+    ///
+    /// # Arguments
+    ///
+    /// * `bank_number` - The bank number of the preset.
+    /// * `patch_number` - The patch number of the preset.
+    pub fn get_voice_source(
+        &self,
+        bank_number: i32,
+        patch_number: i32,
+    ) -> Option<&Arc<dyn VoiceSource>> {
+        self.voice_sources
+            .get(&((bank_number << 16) | patch_number))
+    }
+
+    /// Registers a voice source which plays the specified preset in place of the SoundFont.
+    ///
+    /// # Arguments
+    ///
+    /// * `bank_number` - The bank number of the preset, which is 128 for the percussion presets.
+    /// * `patch_number` - The patch number of the preset.
+    /// * `value` - The voice source, or `None` to play the preset with the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// The source is used even if the SoundFont has the preset.
+    /// The notes of the sources are limited by the maximum polyphony separately from the SoundFont,
+    /// and the oldest one is stopped to start a new note beyond the limit.
+    /// They do not produce the voice events or get saved by `save_state`.
+    /// The source is applied to the notes started after the change, and is kept on reset.
+    pub fn set_voice_source(
+        &mut self,
+        bank_number: i32,
+        patch_number: i32,
+        value: Option<Arc<dyn VoiceSource>>,
+    ) {
+        if !(0..128).contains(&patch_number) {
+            panic!("The patch number must be between 0 and 127.");
+        }
+
+        let preset_id = (bank_number << 16) | patch_number;
+        match value {
+            Some(source) => {
+                self.voice_sources.insert(preset_id, source);
+            }
+            None => {
+                self.voice_sources.remove(&preset_id);
+            }
+        }
+    }
+
+    /// Gets the voice source which plays the presets missing from the SoundFont.
+    pub fn get_fallback_voice_source(&self) -> Option<&Arc<dyn VoiceSource>> {
+        self.fallback_voice_source.as_ref()
+    }
+
+    /// Sets the voice source which plays the presets missing from the SoundFont.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The voice source, or `None` to use the fallback preset of the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// Without the source, the missing presets are played with the GM preset or the default one of the SoundFont.
+    /// With the source, such notes are generated by it instead,
+    /// so that a small SoundFont does not replace the missing instruments with an unrelated one.
+    /// The source is kept on reset.
+    pub fn set_fallback_voice_source(&mut self, value: Option<Arc<dyn VoiceSource>>) {
+        self.fallback_voice_source = value;
+    }
+
+    /// Gets the release layer of the specified preset.
+    ///
+    /// # Arguments
+    ///
+    /// * `bank_number` - The bank number of the preset.
+    /// * `patch_number` - The patch number of the preset.
+    pub fn get_release_layer(&self, bank_number: i32, patch_number: i32) -> Option<&ReleaseLayer> {
+        self.release_layers
+            .get(&((bank_number << 16) | patch_number))
+    }
+
+    /// Sets the preset which is played when the notes of the specified preset are released.
+    ///
+    /// # Arguments
+    ///
+    /// * `bank_number` - The bank number of the preset, which is 128 for the percussion presets.
+    /// * `patch_number` - The patch number of the preset.
+    /// * `value` - The release layer, or `None` to play nothing at the release.
+    ///
+    /// # Remarks
+    ///
+    /// The layer is applied to the notes played with the SoundFont, and not to the ones of the voice sources.
+    /// The layers are kept on reset and when the SoundFont is changed.
+    pub fn set_release_layer(
+        &mut self,
+        bank_number: i32,
+        patch_number: i32,
+        value: Option<ReleaseLayer>,
+    ) {
+        if !(0..128).contains(&patch_number) {
+            panic!("The patch number must be between 0 and 127.");
+        }
+
+        let preset_id = (bank_number << 16) | patch_number;
+        match value {
+            Some(layer) => {
+                if !layer.is_valid() {
+                    panic!("The release layer must have a patch number between 0 and 127, a finite gain, and a non-negative decay.");
+                }
+                self.release_layers.insert(preset_id, layer);
+            }
+            None => {
+                self.release_layers.remove(&preset_id);
+            }
+        }
+    }
+
+    /// Sets the release layers of the presets by the naming convention of the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// A preset whose name ends with "Release", "Rel", or "Key Off" is used as the release layer of
+    /// the preset which has the rest of the name in the same bank,
+    /// such as "Grand Piano Release" for "Grand Piano".
+    /// The names are compared word by word, ignoring the case and the punctuation.
+    /// The layers found replace the existing ones of the same presets, and the number of them is returned.
+    pub fn detect_release_layers(&mut self) -> usize {
+        let presets = self.sound_font.get_presets();
+        let names: Vec<Vec<String>> = presets
+            .iter()
+            .map(|x| Synthesizer::get_name_words(x.get_name()))
+            .collect();
+
+        let mut count: usize = 0;
+        for (preset, words) in presets.iter().zip(names.iter()) {
+            if words.is_empty() {
+                continue;
+            }
+
+            let layer = presets
+                .iter()
+                .zip(names.iter())
+                .find(|(layer, layer_words)| {
+                    layer.get_bank_number() == preset.get_bank_number()
+                        && layer_words.len() > words.len()
+                        && layer_words.starts_with(words)
+                        && Synthesizer::RELEASE_SUFFIXES
+                            .iter()
+                            .any(|x| layer_words[words.len()..] == **x)
+                });
+
+            if let Some((layer, _)) = layer {
+                self.release_layers.insert(
+                    (preset.get_bank_number() << 16) | preset.get_patch_number(),
+                    ReleaseLayer::new(layer.get_bank_number(), layer.get_patch_number()),
+                );
+                count += 1;
+            }
+        }
+
+        count
+    }
+
+    fn get_name_words(name: &str) -> Vec<String> {
+        name.split(|c: char| !c.is_alphanumeric())
+            .filter(|x| !x.is_empty())
+            .map(|x| x.to_lowercase())
+            .collect()
+    }
+
     /// Gets the sample rate for synthesis.
     pub fn get_sample_rate(&self) -> i32 {
         self.sample_rate
@@ -512,51 +1695,946 @@ impl Synthesizer {
         self.maximum_polyphony
     }
 
-    /// Gets the value indicating whether reverb and chorus are enabled.
-    pub fn get_enable_reverb_and_chorus(&self) -> bool {
-        self.effects.is_some()
+    /// Gets the policy to choose a voice to be reused when the maximum polyphony is exceeded.
+    pub fn get_voice_stealing(&self) -> VoiceStealingPolicy {
+        self.voices.voice_stealing
     }
 
-    /// Gets the master volume.
-    pub fn get_master_volume(&self) -> f32 {
-        self.master_volume
+    /// Sets the policy to choose a voice to be reused when the maximum polyphony is exceeded.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The new policy.
+    pub fn set_voice_stealing(&mut self, value: VoiceStealingPolicy) {
+        self.voices.voice_stealing = value;
     }
 
-    /// Sets the master volume.
+    /// Gets how a voice is stopped by a new note in the same exclusive class.
+    pub fn get_exclusive_class_mode(&self) -> ExclusiveClassMode {
+        self.voices.exclusive_class
+    }
+
+    /// Sets how a voice is stopped by a new note in the same exclusive class.
     ///
     /// # Arguments
     ///
-    /// * `value` - The new value of the master volume.
-    pub fn set_master_volume(&mut self, value: f32) {
-        self.master_volume = value;
+    /// * `value` - The new mode. The fade length must be between 0 and 1 second.
+    ///
+    /// # Remarks
+    ///
+    /// The mode also applies to the exclusive groups set by `set_exclusive_group`,
+    /// and to the drum notes which cut each other off in the device profile.
+    pub fn set_exclusive_class_mode(
+        &mut self,
+        value: ExclusiveClassMode,
+    ) -> Result<(), SynthesizerError> {
+        SynthesizerSettings::check_exclusive_class(value)?;
+        self.voices.exclusive_class = value;
+
+        Ok(())
     }
-}
 
-struct Effects {
-    reverb: Reverb,
-    reverb_input: Vec<f32>,
-    reverb_output_left: Vec<f32>,
-    reverb_output_right: Vec<f32>,
+    /// Gets the exclusive group of the specified key in the channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    /// * `key` - The key.
+    ///
+    /// # Remarks
+    ///
+    /// Zero means that no group is set by `set_exclusive_group`.
+    pub fn get_exclusive_group(&self, channel: i32, key: i32) -> u8 {
+        self.check_channel(channel);
+        Synthesizer::check_key(key);
+        self.channels[channel as usize].exclusive_groups[key as usize]
+    }
 
-    chorus: Chorus,
-    chorus_input_left: Vec<f32>,
-    chorus_input_right: Vec<f32>,
-    chorus_output_left: Vec<f32>,
-    chorus_output_right: Vec<f32>,
-}
+    /// Puts the specified key in the channel into an exclusive group.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    /// * `key` - The key.
+    /// * `group` - The group, or zero to remove the key from the group.
+    ///
+    /// # Remarks
+    ///
+    /// A note on a key stops the notes on the other keys in the same group of the channel,
+    /// in the same way as the exclusive class of the SoundFont.
+    /// This allows, for example, the hi-hats of a drum kit without the exclusive classes to choke each other.
+    /// The groups work on any channel, and take precedence over the drum groups of the device profile.
+    /// Unlike the MIDI controllers, the groups are kept on reset.
+    pub fn set_exclusive_group(&mut self, channel: i32, key: i32, group: u8) {
+        self.check_channel(channel);
+        Synthesizer::check_key(key);
+        self.channels[channel as usize].exclusive_groups[key as usize] = group;
+    }
 
-impl Effects {
-    fn new(settings: &SynthesizerSettings) -> Effects {
-        Self {
-            reverb: Reverb::new(settings.sample_rate),
-            reverb_input: vec![0_f32; settings.block_size],
-            reverb_output_left: vec![0_f32; settings.block_size],
-            reverb_output_right: vec![0_f32; settings.block_size],
-            chorus: Chorus::new(settings.sample_rate, 0.002, 0.0019, 0.4),
-            chorus_input_left: vec![0_f32; settings.block_size],
-            chorus_input_right: vec![0_f32; settings.block_size],
-            chorus_output_left: vec![0_f32; settings.block_size],
-            chorus_output_right: vec![0_f32; settings.block_size],
-        }
+    /// Removes all the exclusive groups of the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    pub fn clear_exclusive_groups(&mut self, channel: i32) {
+        self.check_channel(channel);
+        self.channels[channel as usize].exclusive_groups = [0; 128];
+    }
+
+    /// Gets the priority of the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    pub fn get_channel_priority(&self, channel: i32) -> i32 {
+        self.check_channel(channel);
+        self.channels[channel as usize].priority
+    }
+
+    /// Sets the priority of the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    /// * `priority` - The new priority. The default value is zero.
+    ///
+    /// # Remarks
+    ///
+    /// The priority is used by `VoiceStealingPolicy::LowestChannelPriority`.
+    /// The voices on a channel with a higher priority are less likely to be reused.
+    /// Unlike the MIDI controllers, the priority is kept on reset.
+    pub fn set_channel_priority(&mut self, channel: i32, priority: i32) {
+        self.check_channel(channel);
+        self.channels[channel as usize].priority = priority;
+    }
+
+    /// Gets what the modulation wheel (CC #1) of the specified channel modulates.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    ///
+    /// # Remarks
+    ///
+    /// The result is the target and the depth at the maximum value of the controller.
+    pub fn get_modulation_target(&self, channel: i32) -> (ControllerTarget, f32) {
+        self.check_channel(channel);
+        let channel_info = &self.channels[channel as usize];
+        (
+            channel_info.modulation_target,
+            channel_info.modulation_depth,
+        )
+    }
+
+    /// Sets what the modulation wheel (CC #1) of the specified channel modulates.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    /// * `target` - The target of the controller.
+    /// * `depth` - The depth at the maximum value of the controller, in the unit of the target.
+    ///
+    /// # Remarks
+    ///
+    /// The default is the vibrato with the depth of 50 cents, as the default modulator of the SoundFont spec.
+    /// A negative depth lowers the cutoff frequency or inverts the tremolo.
+    /// Unlike the MIDI controllers, the routing is kept on reset.
+    pub fn set_modulation_target(&mut self, channel: i32, target: ControllerTarget, depth: f32) {
+        self.check_channel(channel);
+        let channel_info = &mut self.channels[channel as usize];
+        channel_info.modulation_target = target;
+        channel_info.modulation_depth = depth;
+    }
+
+    /// Gets what the channel pressure (aftertouch) of the specified channel modulates.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    ///
+    /// # Remarks
+    ///
+    /// The result is the target and the depth at the maximum pressure.
+    pub fn get_pressure_target(&self, channel: i32) -> (ControllerTarget, f32) {
+        self.check_channel(channel);
+        let channel_info = &self.channels[channel as usize];
+        (channel_info.pressure_target, channel_info.pressure_depth)
+    }
+
+    /// Sets what the channel pressure (aftertouch) of the specified channel modulates.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    /// * `target` - The target of the pressure.
+    /// * `depth` - The depth at the maximum pressure, in the unit of the target.
+    ///
+    /// # Remarks
+    ///
+    /// The pressure has no effect by default.
+    /// When both the pressure and the modulation wheel are routed to the same target, their amounts are added.
+    /// Unlike the MIDI controllers, the routing is kept on reset.
+    pub fn set_pressure_target(&mut self, channel: i32, target: ControllerTarget, depth: f32) {
+        self.check_channel(channel);
+        let channel_info = &mut self.channels[channel as usize];
+        channel_info.pressure_target = target;
+        channel_info.pressure_depth = depth;
+    }
+
+    /// Gets the rate of the vibrato LFO of the specified channel in hertz.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    pub fn get_vibrato_rate(&self, channel: i32) -> Option<f32> {
+        self.check_channel(channel);
+        self.channels[channel as usize].vibrato_rate
+    }
+
+    /// Sets the rate of the vibrato LFO of the specified channel in hertz.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    /// * `value` - The rate, or `None` to use the rate given by the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// The rate is applied to the notes started after the change,
+    /// and also changes the vibrato which the SoundFont adds by itself.
+    /// Unlike the MIDI controllers, the rate is kept on reset.
+    pub fn set_vibrato_rate(&mut self, channel: i32, value: Option<f32>) {
+        self.check_channel(channel);
+        if let Some(rate) = value {
+            if rate <= 0_f32 || rate.is_nan() {
+                panic!("The vibrato rate must be greater than zero.");
+            }
+        }
+        self.channels[channel as usize].vibrato_rate = value;
+    }
+
+    /// Gets the scales of the volume envelope of the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    pub fn get_envelope_override(&self, channel: i32) -> Option<EnvelopeOverride> {
+        self.check_channel(channel);
+        self.channels[channel as usize].envelope_override
+    }
+
+    /// Scales the attack, decay, sustain and release of the volume envelope of the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    /// * `value` - The scales, or `None` to use the envelope given by the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// This allows quick tweaks, such as a longer release of a piano, without editing the SoundFont.
+    /// The scales are applied to the notes started after the change.
+    /// Unlike the MIDI controllers, the scales are kept on reset.
+    pub fn set_envelope_override(&mut self, channel: i32, value: Option<EnvelopeOverride>) {
+        self.check_channel(channel);
+        if let Some(scale) = value.as_ref() {
+            scale.check();
+        }
+        self.channels[channel as usize].envelope_override = value;
+    }
+
+    /// Gets the rate and the depth of the vibrato LFO of the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    pub fn get_vibrato_lfo_override(&self, channel: i32) -> Option<LfoOverride> {
+        self.check_channel(channel);
+        self.channels[channel as usize].vibrato_lfo_override
+    }
+
+    /// Overrides the rate and the depth of the vibrato LFO of the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    /// * `value` - The override, or `None` to use the LFO given by the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// The rate takes precedence over `set_vibrato_rate`.
+    /// The depth scales the vibrato given by the SoundFont,
+    /// while the vibrato by the modulation wheel is set by `set_modulation_target`.
+    /// The override is applied to the notes started after the change,
+    /// and the rate in beats is fixed at the tempo when the note starts.
+    /// Unlike the MIDI controllers, the override is kept on reset.
+    pub fn set_vibrato_lfo_override(&mut self, channel: i32, value: Option<LfoOverride>) {
+        self.check_channel(channel);
+        if let Some(lfo) = value.as_ref() {
+            lfo.check();
+        }
+        self.channels[channel as usize].vibrato_lfo_override = value;
+    }
+
+    /// Gets the rate and the depth of the modulation LFO of the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    pub fn get_modulation_lfo_override(&self, channel: i32) -> Option<LfoOverride> {
+        self.check_channel(channel);
+        self.channels[channel as usize].modulation_lfo_override
+    }
+
+    /// Overrides the rate and the depth of the modulation LFO of the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    /// * `value` - The override, or `None` to use the LFO given by the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// The depth scales the pitch, filter and volume modulations given by the SoundFont.
+    /// The override is applied to the notes started after the change,
+    /// and the rate in beats is fixed at the tempo when the note starts.
+    /// Unlike the MIDI controllers, the override is kept on reset.
+    pub fn set_modulation_lfo_override(&mut self, channel: i32, value: Option<LfoOverride>) {
+        self.check_channel(channel);
+        if let Some(lfo) = value.as_ref() {
+            lfo.check();
+        }
+        self.channels[channel as usize].modulation_lfo_override = value;
+    }
+
+    /// Gets the filter of the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    pub fn get_filter_override(&self, channel: i32) -> Option<FilterOverride> {
+        self.check_channel(channel);
+        self.channels[channel as usize].filter_override
+    }
+
+    /// Replaces the filter of the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    /// * `value` - The filter, or `None` to use the low-pass filter given by the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// This allows to brighten or fatten a preset at render time, without editing the SoundFont.
+    /// See `FilterType` for the available filters.
+    /// The filter is applied to the notes started after the change.
+    /// Unlike the MIDI controllers, the filter is kept on reset.
+    pub fn set_filter_override(&mut self, channel: i32, value: Option<FilterOverride>) {
+        self.check_channel(channel);
+        if let Some(filter) = value.as_ref() {
+            filter.check();
+        }
+        self.channels[channel as usize].filter_override = value;
+    }
+
+    /// Gets the voice source which plays all the notes of the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    pub fn get_channel_voice_source(&self, channel: i32) -> Option<&Arc<dyn VoiceSource>> {
+        self.check_channel(channel);
+        self.channels[channel as usize].voice_source.as_ref()
+    }
+
+    /// Plays all the notes of the specified channel with the voice source instead of the SoundFont.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    /// * `value` - The voice source, or `None` to play the channel with the SoundFont.
+    ///
+    /// # Remarks
+    ///
+    /// The source receives the bank and the patch selected on the channel,
+    /// and takes precedence over the sources registered by `set_voice_source`.
+    /// The source is applied to the notes started after the change, and is kept on reset.
+    pub fn set_channel_voice_source(&mut self, channel: i32, value: Option<Arc<dyn VoiceSource>>) {
+        self.check_channel(channel);
+        self.channels[channel as usize].voice_source = value;
+    }
+
+    /// Gets the tempo in beats per minute, which the LFO rates in beats follow.
+    ///
+    /// # Remarks
+    ///
+    /// The default value is 120.
+    pub fn get_tempo(&self) -> f64 {
+        self.tempo
+    }
+
+    /// Sets the tempo in beats per minute, which the LFO rates in beats follow.
+    ///
+    /// # Remarks
+    ///
+    /// The value must be greater than zero.
+    /// `MidiFileSequencer` updates the tempo while playing if a tempo map is given by `set_tempo_map`.
+    /// Unlike the MIDI controllers, the tempo is kept on reset.
+    pub fn set_tempo(&mut self, value: f64) {
+        if value.is_nan() || value <= 0.0 {
+            panic!("The tempo must be greater than zero.");
+        }
+
+        self.tempo = value;
+    }
+
+    /// Gets the state of the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    ///
+    /// # Remarks
+    ///
+    /// The state contains the raw controller values,
+    /// which is useful to show a mixer view or to save the session.
+    pub fn get_channel_state(&self, channel: i32) -> ChannelState {
+        self.check_channel(channel);
+        self.channels[channel as usize].get_state()
+    }
+
+    /// Gets a value that indicates whether the channel is a percussion channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    pub fn is_percussion_channel(&self, channel: i32) -> bool {
+        self.check_channel(channel);
+        self.channels[channel as usize].is_percussion_channel
+    }
+
+    /// Switches the channel between the percussion and melodic.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    /// * `value` - If `true`, the channel plays the percussion presets.
+    ///
+    /// # Remarks
+    ///
+    /// The selected bank is moved to the percussion or melodic side, and the playing notes are kept.
+    /// The change is undone by `reset`, which restores the percussion channels given by the settings.
+    /// The GS "use for rhythm part" SysEx messages in a MIDI file are processed in the same way,
+    /// unless they are disabled by the settings.
+    pub fn set_percussion_channel(&mut self, channel: i32, value: bool) {
+        self.check_channel(channel);
+        self.channels[channel as usize].set_percussion_channel(value);
+    }
+
+    /// Gets the bank and the program which override the program changes of the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    pub fn get_program_override(&self, channel: i32) -> Option<(i32, i32)> {
+        self.check_channel(channel);
+        self.channels[channel as usize].program_override
+    }
+
+    /// Plays the specified preset on the channel regardless of the program changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    /// * `bank` - The bank number of the preset in the SoundFont, which is 128 for the percussion presets.
+    /// * `program` - The program number of the preset.
+    ///
+    /// # Remarks
+    ///
+    /// This allows to audition a MIDI file with the different instruments without editing it.
+    /// The bank select and the program change messages are still processed,
+    /// and take effect again when the override is cleared.
+    /// The override is applied to the notes started after the change.
+    /// If the preset does not exist, the fallback preset is used in the same way as the program changes.
+    /// Unlike the MIDI controllers, the override is kept on reset.
+    pub fn set_program_override(&mut self, channel: i32, bank: i32, program: i32) {
+        self.check_channel(channel);
+        if !(0..128).contains(&program) {
+            panic!("The program must be between 0 and 127.");
+        }
+        self.channels[channel as usize].program_override = Some((bank, program));
+    }
+
+    /// Removes the program override of the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    pub fn clear_program_override(&mut self, channel: i32) {
+        self.check_channel(channel);
+        self.channels[channel as usize].program_override = None;
+    }
+
+    /// Gets the key split of the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    pub fn get_key_split(&self, channel: i32) -> Option<KeySplit> {
+        self.check_channel(channel);
+        self.channels[channel as usize].key_split
+    }
+
+    /// Plays the different presets below and above the split point of the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    /// * `value` - The split, or `None` to play a single preset on the whole keyboard.
+    ///
+    /// # Remarks
+    ///
+    /// This allows to play, for example, the bass with the left hand and the piano with the right hand
+    /// on a single MIDI keyboard.
+    /// The split takes precedence over the program override and the program changes.
+    /// The split is applied to the notes started after the change.
+    /// Unlike the MIDI controllers, the split is kept on reset.
+    pub fn set_key_split(&mut self, channel: i32, value: Option<KeySplit>) {
+        self.check_channel(channel);
+        if let Some(split) = value.as_ref() {
+            split.check();
+        }
+        self.channels[channel as usize].key_split = value;
+    }
+
+    /// Gets the channel to which the messages of the specified channel are sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel of the incoming messages.
+    pub fn get_channel_remap(&self, channel: i32) -> i32 {
+        self.check_channel(channel);
+        self.channel_map[channel as usize] as i32
+    }
+
+    /// Sends the messages of the specified channel to another channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel of the incoming messages.
+    /// * `destination` - The channel which processes the messages.
+    ///
+    /// # Remarks
+    ///
+    /// The remapping is applied to `process_midi_message` and the GS rhythm part messages,
+    /// so it changes the channels of the MIDI files played by the sequencer.
+    /// More than one channel can be sent to the same channel,
+    /// and setting the destination to the channel itself removes the remapping.
+    /// The methods called directly with a channel, such as `note_on`, are not remapped.
+    /// Unlike the MIDI controllers, the remapping is kept on reset.
+    pub fn set_channel_remap(&mut self, channel: i32, destination: i32) {
+        self.check_channel(channel);
+        self.check_channel(destination);
+        self.channel_map[channel as usize] = destination as usize;
+    }
+
+    /// Removes the remapping of all the channels.
+    pub fn clear_channel_remaps(&mut self) {
+        self.channel_map = (0..self.channels.len()).collect();
+    }
+
+    pub(crate) fn process_gs_rhythm_part(&mut self, channel: i32, value: bool) {
+        if self.enable_gs_rhythm_parts {
+            if let Some(channel) = self.fold_channel(channel) {
+                let channel = self.channel_map[channel] as i32;
+                self.set_percussion_channel(channel, value);
+            }
+        }
+    }
+
+    /// Gets the number of the channels.
+    pub fn get_channel_count(&self) -> usize {
+        self.channels.len()
+    }
+
+    // The channel count is a multiple of 16, so the channel in a port is kept.
+    fn fold_channel(&self, channel: i32) -> Option<usize> {
+        if !(0 <= channel && channel < Synthesizer::MAX_CHANNEL_COUNT as i32) {
+            return None;
+        }
+
+        Some(channel as usize % self.channels.len())
+    }
+
+    fn check_channel(&self, channel: i32) {
+        if !(0 <= channel && channel < self.channels.len() as i32) {
+            panic!("The channel must be less than the channel count.");
+        }
+    }
+
+    fn check_key(key: i32) {
+        if !(0..128).contains(&key) {
+            panic!("The key must be between 0 and 127.");
+        }
+    }
+
+    /// Sets the block size for rendering waveform.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The new block size.
+    ///
+    /// # Remarks
+    ///
+    /// The change takes effect when the current block has been consumed,
+    /// so the playing notes and the channel states are kept.
+    pub fn set_block_size(&mut self, value: usize) -> Result<(), SynthesizerError> {
+        SynthesizerSettings::check_block_size(value)?;
+        self.pending_block_size = Some(value);
+        Ok(())
+    }
+
+    /// Sets the number of maximum polyphony.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The new number of maximum polyphony.
+    ///
+    /// # Remarks
+    ///
+    /// The change takes effect when the current block has been consumed.
+    /// If the new limit is less than the number of the playing voices,
+    /// the voices exceeding the limit are stopped immediately.
+    pub fn set_maximum_polyphony(&mut self, value: usize) -> Result<(), SynthesizerError> {
+        SynthesizerSettings::check_maximum_polyphony(value)?;
+        self.pending_maximum_polyphony = Some(value);
+        Ok(())
+    }
+
+    /// Gets the value indicating whether the voices are rendered in parallel.
+    pub fn get_parallel_voices(&self) -> bool {
+        self.voices.parallel
+    }
+
+    /// Sets the value indicating whether the voices are rendered in parallel.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - If `true`, the active voices are distributed across the rayon thread pool for each block.
+    ///
+    /// # Remarks
+    ///
+    /// This allows a single synthesizer to use multiple cores for a high-polyphony song.
+    /// The parallel rendering is used only when many voices are playing,
+    /// since the overhead exceeds the gain otherwise.
+    /// The result may differ slightly from the sequential rendering due to the order of the summation.
+    /// Without the std feature, the voices are always rendered sequentially.
+    pub fn set_parallel_voices(&mut self, value: bool) {
+        self.voices.parallel = value;
+    }
+
+    /// Gets the value indicating whether reverb and chorus are enabled.
+    pub fn get_enable_reverb_and_chorus(&self) -> bool {
+        self.effects.is_some()
+    }
+
+    /// Gets the diagnostics reported during playback.
+    ///
+    /// # Remarks
+    ///
+    /// Each problem is reported only once until the diagnostics are cleared.
+    pub fn get_diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    /// Gets the diagnostics reported since the last call of this method.
+    ///
+    /// # Remarks
+    ///
+    /// Calling this after each block allows to be notified of the problems as they happen,
+    /// for example, to find out why a channel is silent.
+    /// The diagnostics are kept, so `get_diagnostics` still returns all of them.
+    pub fn get_new_diagnostics(&mut self) -> &[Diagnostic] {
+        let items = &self.diagnostics.get_items()[self.diagnostics_read..];
+        self.diagnostics_read = self.diagnostics.len();
+        items
+    }
+
+    /// Clears the diagnostics reported during playback.
+    pub fn clear_diagnostics(&mut self) {
+        self.diagnostics.clear();
+        self.diagnostics_read = 0;
+    }
+
+    /// Gets the maximum number of the voice events kept until they are drained.
+    ///
+    /// # Remarks
+    ///
+    /// The default value is zero, which means the voice events are not recorded.
+    pub fn get_voice_event_capacity(&self) -> usize {
+        self.voices.events.get_capacity()
+    }
+
+    /// Sets the maximum number of the voice events kept until they are drained.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The new capacity. Zero disables the recording.
+    ///
+    /// # Remarks
+    ///
+    /// The memory for the events is allocated here, not during rendering.
+    /// If the events are not drained in time, the oldest ones are discarded.
+    pub fn set_voice_event_capacity(&mut self, value: usize) {
+        self.voices.events.set_capacity(value);
+    }
+
+    /// Takes the voice events recorded since the last call, in the order they occurred.
+    ///
+    /// # Remarks
+    ///
+    /// The events show the voices started and stopped by the synthesizer,
+    /// which is useful for visualizers and debugging tools.
+    /// The recording is enabled by `set_voice_event_capacity`.
+    pub fn drain_voice_events(&mut self) -> impl Iterator<Item = VoiceEvent> + '_ {
+        self.voices.events.drain()
+    }
+
+    // The silence written instead of rendering still advances the position of the events.
+    pub(crate) fn skip_voice_event_position(&mut self, sample_count: usize) {
+        self.voices.events.advance(sample_count);
+    }
+
+    fn add_block_render_time(&mut self, time: Duration) {
+        self.block_count += 1;
+        self.last_block_render_time = time;
+        self.max_block_render_time = cmp::max(self.max_block_render_time, time);
+        self.total_render_time += time;
+    }
+
+    /// Gets the statistics of the voices and the rendering.
+    ///
+    /// # Remarks
+    ///
+    /// The counters and the times are accumulated until `reset_statistics` is called.
+    pub fn get_statistics(&self) -> SynthesizerStatistics {
+        let mut channel_voice_counts = vec![0_usize; self.channels.len()];
+        for voice in self.voices.get_playing_voices().iter() {
+            channel_voice_counts[voice.channel as usize] += 1;
+        }
+
+        let mut presets: Vec<PresetStatistics> = Vec::new();
+        if let Some(profile) = &self.voices.profile {
+            for (&(bank_number, patch_number), &(render_time, voice_block_count)) in
+                profile.items.iter()
+            {
+                let name = match self.sound_font.preset_lookup.get(bank_number, patch_number) {
+                    Some(index) => self.sound_font.presets[index].get_name().to_string(),
+                    None => String::new(),
+                };
+                presets.push(PresetStatistics {
+                    bank_number,
+                    patch_number,
+                    name,
+                    render_time,
+                    voice_block_count,
+                });
+            }
+            presets.sort_by_key(|x| cmp::Reverse(x.render_time));
+        }
+
+        SynthesizerStatistics {
+            active_voice_count: self.voices.active_voice_count,
+            voices: self
+                .voices
+                .get_playing_voices()
+                .iter()
+                .map(|x| x.get_info())
+                .collect(),
+            channel_voice_counts,
+            stolen_voice_count: self.voices.stolen_voice_count,
+            block_count: self.block_count,
+            last_block_render_time: self.last_block_render_time,
+            max_block_render_time: self.max_block_render_time,
+            total_render_time: self.total_render_time,
+            presets,
+        }
+    }
+
+    /// Resets the counters and the times of the statistics.
+    pub fn reset_statistics(&mut self) {
+        self.voices.stolen_voice_count = 0;
+        if let Some(profile) = self.voices.profile.as_mut() {
+            profile.clear();
+        }
+        self.block_count = 0;
+        self.last_block_render_time = Duration::ZERO;
+        self.max_block_render_time = Duration::ZERO;
+        self.total_render_time = Duration::ZERO;
+    }
+
+    /// Gets a value that indicates whether the time spent for each preset is measured.
+    pub fn get_preset_profiling(&self) -> bool {
+        self.voices.profile.is_some()
+    }
+
+    /// Sets a value that indicates whether the time spent for each preset is measured.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - If `true`, the time is measured for each voice.
+    ///
+    /// # Remarks
+    ///
+    /// The default value is `false`, since measuring each voice adds a small overhead.
+    /// The result is given by `get_statistics`, which helps to find expensive presets.
+    pub fn set_preset_profiling(&mut self, value: bool) {
+        if value != self.voices.profile.is_some() {
+            self.voices.profile = if value {
+                Some(PresetProfile::new())
+            } else {
+                None
+            };
+        }
+    }
+
+    /// Gets the master volume.
+    pub fn get_master_volume(&self) -> f32 {
+        self.master_volume
+    }
+
+    /// Sets the master volume.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The new value of the master volume.
+    pub fn set_master_volume(&mut self, value: f32) {
+        self.master_volume = value;
+    }
+
+    /// Gets the output level of the reverb.
+    pub fn get_reverb_level(&self) -> f32 {
+        self.reverb_level
+    }
+
+    /// Sets the output level of the reverb.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The linear gain applied to the reverb output. The default value is 1.
+    pub fn set_reverb_level(&mut self, value: f32) {
+        self.reverb_level = value;
+    }
+
+    /// Gets the output level of the chorus.
+    pub fn get_chorus_level(&self) -> f32 {
+        self.chorus_level
+    }
+
+    /// Sets the output level of the chorus.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The linear gain applied to the chorus output. The default value is 1.
+    pub fn set_chorus_level(&mut self, value: f32) {
+        self.chorus_level = value;
+    }
+
+    /// Gets the stereo width of the master output.
+    pub fn get_stereo_width(&self) -> f32 {
+        self.stereo_width
+    }
+
+    /// Sets the stereo width of the master output.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The scale of the side signal, between 0 and 2. The default value is 1.
+    ///
+    /// # Remarks
+    ///
+    /// The output is processed as mid and side, after the reverb and chorus.
+    /// 0 folds the output down to mono, and values above 1 widen the stereo image.
+    /// Narrowing the output reduces the phasey sound which collapses badly on mono speakers.
+    /// Use `LevelReport::get_correlation` to check the mono compatibility of the output.
+    pub fn set_stereo_width(&mut self, value: f32) {
+        if !(0_f32..=Synthesizer::MAX_STEREO_WIDTH).contains(&value) {
+            panic!("The stereo width must be between 0 and 2.");
+        }
+
+        self.stereo_width = value;
+    }
+
+    // The value of the automation is applied between blocks.
+    pub(crate) fn apply_automation(&mut self, target: AutomationTarget, value: f32) {
+        match target {
+            AutomationTarget::MasterVolume => self.master_volume = value,
+            AutomationTarget::ReverbLevel => self.reverb_level = value,
+            AutomationTarget::ChorusLevel => self.chorus_level = value,
+            AutomationTarget::StereoWidth => {
+                self.stereo_width = value.clamp(0_f32, Synthesizer::MAX_STEREO_WIDTH)
+            }
+            AutomationTarget::ChannelVolume(channel) => {
+                self.channels[channel as usize].set_volume(value)
+            }
+            AutomationTarget::ChannelExpression(channel) => {
+                self.channels[channel as usize].set_expression(value)
+            }
+            AutomationTarget::ChannelPan(channel) => self.channels[channel as usize].set_pan(value),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Effects {
+    reverb: Reverb,
+    reverb_input: Vec<f32>,
+    reverb_output_left: Vec<f32>,
+    reverb_output_right: Vec<f32>,
+
+    chorus: Chorus,
+    chorus_input_left: Vec<f32>,
+    chorus_input_right: Vec<f32>,
+    chorus_output_left: Vec<f32>,
+    chorus_output_right: Vec<f32>,
+}
+
+impl Effects {
+    fn new(settings: &SynthesizerSettings) -> Effects {
+        Self {
+            reverb: Reverb::new(settings.sample_rate),
+            reverb_input: vec![0_f32; settings.block_size],
+            reverb_output_left: vec![0_f32; settings.block_size],
+            reverb_output_right: vec![0_f32; settings.block_size],
+            chorus: Chorus::new(settings.sample_rate, 0.002, 0.0019, 0.4),
+            chorus_input_left: vec![0_f32; settings.block_size],
+            chorus_input_right: vec![0_f32; settings.block_size],
+            chorus_output_left: vec![0_f32; settings.block_size],
+            chorus_output_right: vec![0_f32; settings.block_size],
+        }
+    }
+
+    fn get_memory_size(&self) -> usize {
+        let blocks = self.reverb_input.len()
+            + self.reverb_output_left.len()
+            + self.reverb_output_right.len()
+            + self.chorus_input_left.len()
+            + self.chorus_input_right.len()
+            + self.chorus_output_left.len()
+            + self.chorus_output_right.len();
+        self.reverb.get_memory_size()
+            + self.chorus.get_memory_size()
+            + blocks * mem::size_of::<f32>()
+    }
+
+    fn set_block_size(&mut self, block_size: usize) {
+        self.reverb_input.resize(block_size, 0_f32);
+        self.reverb_output_left.resize(block_size, 0_f32);
+        self.reverb_output_right.resize(block_size, 0_f32);
+        self.chorus_input_left.resize(block_size, 0_f32);
+        self.chorus_input_right.resize(block_size, 0_f32);
+        self.chorus_output_left.resize(block_size, 0_f32);
+        self.chorus_output_right.resize(block_size, 0_f32);
     }
 }
@@ -1,8 +1,14 @@
 #![allow(dead_code)]
 
+use crate::device_profile::DeviceProfile;
 use crate::error::SynthesizerError;
+use crate::exclusive_class_mode::ExclusiveClassMode;
+use crate::pedal_curve::PedalCurve;
+use crate::voice_stealing_policy::VoiceStealingPolicy;
+use crate::volume_curve::VolumeCurve;
 
 /// Specifies a set of parameters for synthesis.
+#[derive(Clone)]
 #[non_exhaustive]
 pub struct SynthesizerSettings {
     /// The sample rate for synthesis.
@@ -13,12 +19,54 @@ pub struct SynthesizerSettings {
     pub maximum_polyphony: usize,
     /// The value indicating whether reverb and chorus are enabled.
     pub enable_reverb_and_chorus: bool,
+    /// The policy to choose a voice to be reused when the maximum polyphony is exceeded.
+    pub voice_stealing: VoiceStealingPolicy,
+    /// The value indicating whether the voices are rendered in parallel.
+    pub parallel_voices: bool,
+    /// The number of the channels, which is a multiple of 16 between 16 and 128.
+    ///
+    /// Each group of 16 channels receives the messages of a MIDI port,
+    /// which is selected by the port meta event (FF 21) in a MIDI file.
+    /// The messages for the ports beyond the channels are sent to the first ports.
+    pub channel_count: usize,
+    /// The values indicating whether each channel is a percussion channel.
+    ///
+    /// By default, only the channel 10 (index 9) is.
+    /// The values are applied to the channels of each port.
+    /// Set all of them to `false`, and disable the GS rhythm parts,
+    /// to play the drum parts with the melodic presets.
+    pub percussion_channels: [bool; 16],
+    /// The value indicating whether the GS "use for rhythm part" SysEx messages in a MIDI file
+    /// switch the channels between the percussion and melodic.
+    pub enable_gs_rhythm_parts: bool,
+    /// The curve to convert the channel volume (CC #7) to the gain.
+    pub volume_curve: VolumeCurve,
+    /// The curve to convert the expression (CC #11) to the gain.
+    pub expression_curve: VolumeCurve,
+    /// The curve to convert the hold pedal (CC #64) to the amount of the sustain.
+    pub pedal_curve: PedalCurve,
+    /// The hardware whose interpretation of the MIDI messages is approximated.
+    pub profile: DeviceProfile,
+    /// How a voice is stopped by a new note in the same exclusive class.
+    pub exclusive_class: ExclusiveClassMode,
 }
 
 impl SynthesizerSettings {
     const DEFAULT_BLOCK_SIZE: usize = 64;
     const DEFAULT_MAXIMUM_POLYPHONY: usize = 64;
+    const DEFAULT_CHANNEL_COUNT: usize = 16;
     const DEFAULT_ENABLE_REVERB_AND_CHORUS: bool = true;
+    const DEFAULT_VOICE_STEALING: VoiceStealingPolicy = VoiceStealingPolicy::Quietest;
+    const DEFAULT_PARALLEL_VOICES: bool = false;
+    const DEFAULT_ENABLE_GS_RHYTHM_PARTS: bool = true;
+    const DEFAULT_VOLUME_CURVE: VolumeCurve = VolumeCurve::Square;
+    const DEFAULT_PEDAL_CURVE: PedalCurve = PedalCurve::Switch;
+    const DEFAULT_PROFILE: DeviceProfile = DeviceProfile::GeneralMidi;
+    const DEFAULT_EXCLUSIVE_CLASS: ExclusiveClassMode = ExclusiveClassMode::Cut;
+    const DEFAULT_PERCUSSION_CHANNELS: [bool; 16] = [
+        false, false, false, false, false, false, false, false, false, true, false, false, false,
+        false, false, false,
+    ];
 
     /// Initializes a new instance of synthesizer settings.
     ///
@@ -31,6 +79,16 @@ impl SynthesizerSettings {
             block_size: SynthesizerSettings::DEFAULT_BLOCK_SIZE,
             maximum_polyphony: SynthesizerSettings::DEFAULT_MAXIMUM_POLYPHONY,
             enable_reverb_and_chorus: SynthesizerSettings::DEFAULT_ENABLE_REVERB_AND_CHORUS,
+            voice_stealing: SynthesizerSettings::DEFAULT_VOICE_STEALING,
+            parallel_voices: SynthesizerSettings::DEFAULT_PARALLEL_VOICES,
+            channel_count: SynthesizerSettings::DEFAULT_CHANNEL_COUNT,
+            percussion_channels: SynthesizerSettings::DEFAULT_PERCUSSION_CHANNELS,
+            enable_gs_rhythm_parts: SynthesizerSettings::DEFAULT_ENABLE_GS_RHYTHM_PARTS,
+            volume_curve: SynthesizerSettings::DEFAULT_VOLUME_CURVE,
+            expression_curve: SynthesizerSettings::DEFAULT_VOLUME_CURVE,
+            pedal_curve: SynthesizerSettings::DEFAULT_PEDAL_CURVE,
+            profile: SynthesizerSettings::DEFAULT_PROFILE,
+            exclusive_class: SynthesizerSettings::DEFAULT_EXCLUSIVE_CLASS,
         }
     }
 
@@ -38,6 +96,11 @@ impl SynthesizerSettings {
         SynthesizerSettings::check_sample_rate(self.sample_rate)?;
         SynthesizerSettings::check_block_size(self.block_size)?;
         SynthesizerSettings::check_maximum_polyphony(self.maximum_polyphony)?;
+        SynthesizerSettings::check_channel_count(self.channel_count)?;
+        SynthesizerSettings::check_volume_curve(&self.volume_curve)?;
+        SynthesizerSettings::check_volume_curve(&self.expression_curve)?;
+        SynthesizerSettings::check_pedal_curve(&self.pedal_curve)?;
+        SynthesizerSettings::check_exclusive_class(self.exclusive_class)?;
 
         Ok(())
     }
@@ -50,7 +113,7 @@ impl SynthesizerSettings {
         Ok(())
     }
 
-    fn check_block_size(value: usize) -> Result<(), SynthesizerError> {
+    pub(crate) fn check_block_size(value: usize) -> Result<(), SynthesizerError> {
         if !(8..=1024).contains(&value) {
             return Err(SynthesizerError::BlockSizeOutOfRange(value));
         }
@@ -58,11 +121,43 @@ impl SynthesizerSettings {
         Ok(())
     }
 
-    fn check_maximum_polyphony(value: usize) -> Result<(), SynthesizerError> {
+    pub(crate) fn check_maximum_polyphony(value: usize) -> Result<(), SynthesizerError> {
         if !(8..=256).contains(&value) {
             return Err(SynthesizerError::MaximumPolyphonyOutOfRange(value));
         }
 
         Ok(())
     }
+
+    fn check_channel_count(value: usize) -> Result<(), SynthesizerError> {
+        if !(16..=128).contains(&value) || value % 16 != 0 {
+            return Err(SynthesizerError::ChannelCountOutOfRange(value));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn check_exclusive_class(value: ExclusiveClassMode) -> Result<(), SynthesizerError> {
+        if !value.is_valid() {
+            return Err(SynthesizerError::InvalidExclusiveClassMode);
+        }
+
+        Ok(())
+    }
+
+    fn check_volume_curve(value: &VolumeCurve) -> Result<(), SynthesizerError> {
+        if !value.is_valid() {
+            return Err(SynthesizerError::InvalidVolumeCurve);
+        }
+
+        Ok(())
+    }
+
+    fn check_pedal_curve(value: &PedalCurve) -> Result<(), SynthesizerError> {
+        if !value.is_valid() {
+            return Err(SynthesizerError::InvalidPedalCurve);
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,76 @@
+#![allow(dead_code)]
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::channel::Channel;
+use crate::channel_state::ChannelState;
+use crate::soundfont::SoundFont;
+use crate::synthesizer::Effects;
+use crate::voice::Voice;
+
+/// Represents a snapshot of the synthesizer state.
+///
+/// # Remarks
+///
+/// The snapshot is taken by `Synthesizer::save_state`,
+/// and can be restored any number of times by `Synthesizer::load_state`.
+#[derive(Clone)]
+#[non_exhaustive]
+pub struct SynthesizerState {
+    pub(crate) channels: Vec<Channel>,
+    pub(crate) master_volume: f32,
+    pub(crate) voices: Option<VoiceSnapshot>,
+}
+
+// The sound being played, which is valid only for the same sample rate and block size.
+#[derive(Clone)]
+#[non_exhaustive]
+pub(crate) struct VoiceSnapshot {
+    pub(crate) sample_rate: i32,
+    pub(crate) block_size: usize,
+
+    pub(crate) sound_font: Arc<SoundFont>,
+    pub(crate) previous_sound_font: Option<Arc<SoundFont>>,
+
+    pub(crate) voices: Vec<Voice>,
+
+    pub(crate) block_left: Vec<f32>,
+    pub(crate) block_right: Vec<f32>,
+    pub(crate) block_read: usize,
+
+    pub(crate) effects: Option<Effects>,
+}
+
+impl SynthesizerState {
+    /// Gets the state of the specified channel at the time of the snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    pub fn get_channel_state(&self, channel: i32) -> ChannelState {
+        if !(0 <= channel && channel < self.channels.len() as i32) {
+            panic!("The channel must be less than the channel count.");
+        }
+
+        self.channels[channel as usize].get_state()
+    }
+
+    /// Gets the master volume at the time of the snapshot.
+    pub fn get_master_volume(&self) -> f32 {
+        self.master_volume
+    }
+
+    /// Gets a value that indicates whether the snapshot contains the playing voices.
+    pub fn has_voices(&self) -> bool {
+        self.voices.is_some()
+    }
+
+    /// Gets the number of the playing voices in the snapshot.
+    pub fn get_voice_count(&self) -> usize {
+        match &self.voices {
+            Some(value) => value.voices.len(),
+            None => 0,
+        }
+    }
+}
@@ -0,0 +1,130 @@
+#![allow(dead_code)]
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::voice_info::VoiceInfo;
+
+/// Represents the time spent for the voices playing a preset.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct PresetStatistics {
+    pub(crate) bank_number: i32,
+    pub(crate) patch_number: i32,
+    pub(crate) name: String,
+    pub(crate) render_time: Duration,
+    pub(crate) voice_block_count: u64,
+}
+
+impl PresetStatistics {
+    /// Gets the bank number of the preset.
+    pub fn get_bank_number(&self) -> i32 {
+        self.bank_number
+    }
+
+    /// Gets the patch number of the preset.
+    pub fn get_patch_number(&self) -> i32 {
+        self.patch_number
+    }
+
+    /// Gets the name of the preset.
+    ///
+    /// # Remarks
+    ///
+    /// The name is empty if the preset is not in the current SoundFont.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the total time spent for the voices playing the preset.
+    pub fn get_render_time(&self) -> Duration {
+        self.render_time
+    }
+
+    /// Gets the total number of the blocks rendered by the voices playing the preset.
+    pub fn get_voice_block_count(&self) -> u64 {
+        self.voice_block_count
+    }
+}
+
+/// Represents the statistics of the synthesizer.
+///
+/// # Remarks
+///
+/// Without the std feature, no clock is available and the times are always zero.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct SynthesizerStatistics {
+    pub(crate) active_voice_count: usize,
+    pub(crate) voices: Vec<VoiceInfo>,
+    pub(crate) channel_voice_counts: Vec<usize>,
+    pub(crate) stolen_voice_count: u64,
+    pub(crate) block_count: u64,
+    pub(crate) last_block_render_time: Duration,
+    pub(crate) max_block_render_time: Duration,
+    pub(crate) total_render_time: Duration,
+    pub(crate) presets: Vec<PresetStatistics>,
+}
+
+impl SynthesizerStatistics {
+    /// Gets the number of the voices currently playing.
+    pub fn get_active_voice_count(&self) -> usize {
+        self.active_voice_count
+    }
+
+    /// Gets the states of the voices currently playing, such as the phases of their envelopes.
+    pub fn get_voices(&self) -> &[VoiceInfo] {
+        &self.voices[..]
+    }
+
+    /// Gets the number of the voices currently playing on the specified channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel.
+    pub fn get_channel_voice_count(&self, channel: i32) -> usize {
+        if !(0 <= channel && channel < self.channel_voice_counts.len() as i32) {
+            panic!("The channel must be less than the channel count.");
+        }
+
+        self.channel_voice_counts[channel as usize]
+    }
+
+    /// Gets the number of the voices stopped to play a new note
+    /// because the maximum polyphony was exceeded.
+    pub fn get_stolen_voice_count(&self) -> u64 {
+        self.stolen_voice_count
+    }
+
+    /// Gets the number of the rendered blocks.
+    pub fn get_block_count(&self) -> u64 {
+        self.block_count
+    }
+
+    /// Gets the time spent for rendering the last block.
+    pub fn get_last_block_render_time(&self) -> Duration {
+        self.last_block_render_time
+    }
+
+    /// Gets the longest time spent for rendering a block.
+    pub fn get_max_block_render_time(&self) -> Duration {
+        self.max_block_render_time
+    }
+
+    /// Gets the total time spent for rendering the blocks.
+    pub fn get_total_render_time(&self) -> Duration {
+        self.total_render_time
+    }
+
+    /// Gets the time spent for each preset, in descending order of the time.
+    ///
+    /// # Remarks
+    ///
+    /// This is empty unless the preset profiling is enabled by `Synthesizer::set_preset_profiling`.
+    /// The time includes the oscillator, the filter, and the envelopes of the voices,
+    /// but not the mixing and the effects.
+    pub fn get_preset_statistics(&self) -> &[PresetStatistics] {
+        &self.presets[..]
+    }
+}
@@ -0,0 +1,303 @@
+#![allow(dead_code)]
+
+use alloc::vec::Vec;
+
+use crate::float_math::FloatMath;
+use crate::midifile::{Message, MidiFile};
+
+/// Represents a tempo change in a MIDI file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct TempoChange {
+    pub(crate) tick: i32,
+    pub(crate) time: f64,
+    pub(crate) tempo: f64,
+}
+
+impl TempoChange {
+    /// Gets the position of the tempo change in ticks.
+    pub fn get_tick(&self) -> i32 {
+        self.tick
+    }
+
+    /// Gets the position of the tempo change in seconds.
+    pub fn get_time(&self) -> f64 {
+        self.time
+    }
+
+    /// Gets the tempo in beats per minute.
+    pub fn get_tempo(&self) -> f64 {
+        self.tempo
+    }
+}
+
+/// Represents a time signature change in a MIDI file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TimeSignature {
+    pub(crate) tick: i32,
+    pub(crate) numerator: i32,
+    pub(crate) denominator: i32,
+}
+
+impl TimeSignature {
+    /// Gets the position of the time signature in ticks.
+    pub fn get_tick(&self) -> i32 {
+        self.tick
+    }
+
+    /// Gets the number of the beats in a bar.
+    pub fn get_numerator(&self) -> i32 {
+        self.numerator
+    }
+
+    /// Gets the note value of a beat, such as 4 for a quarter note.
+    pub fn get_denominator(&self) -> i32 {
+        self.denominator
+    }
+}
+
+/// Represents the tempo changes and the time signatures of a MIDI file,
+/// which converts the positions between seconds, ticks, and bars and beats.
+///
+/// # Remarks
+///
+/// The tempo is 120 BPM and the time signature is 4/4 until the first change.
+/// The times are computed in the same way as the sequencer plays the file.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct TempoMap {
+    pub(crate) resolution: i32,
+    pub(crate) tempo_changes: Vec<TempoChange>,
+    pub(crate) time_signatures: Vec<TimeSignature>,
+}
+
+impl TempoMap {
+    const DEFAULT_TEMPO: f64 = 120.0;
+
+    // The events must be sorted by the tick.
+    pub(crate) fn new(resolution: i32, events: &[(Message, i32)]) -> Self {
+        let mut tempo_map = Self {
+            resolution,
+            tempo_changes: Vec::new(),
+            time_signatures: Vec::new(),
+        };
+
+        for (message, tick) in events.iter() {
+            match message.get_message_type() {
+                Message::TEMPO_CHANGE => {
+                    let tempo_change = TempoChange {
+                        tick: *tick,
+                        time: tempo_map.ticks_to_seconds(*tick as f64),
+                        tempo: message.get_tempo(),
+                    };
+                    // The last one wins if the tempo changes more than once at the same tick.
+                    match tempo_map.tempo_changes.last_mut() {
+                        Some(last) if last.tick == *tick => *last = tempo_change,
+                        _ => tempo_map.tempo_changes.push(tempo_change),
+                    }
+                }
+                Message::TIME_SIGNATURE => {
+                    let time_signature = TimeSignature {
+                        tick: *tick,
+                        numerator: message.command as i32,
+                        denominator: 1 << message.data1.min(6),
+                    };
+                    match tempo_map.time_signatures.last_mut() {
+                        Some(last) if last.tick == *tick => *last = time_signature,
+                        _ => tempo_map.time_signatures.push(time_signature),
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        tempo_map
+    }
+
+    /// Gets the number of the ticks per quarter note.
+    ///
+    /// # Remarks
+    ///
+    /// A negative value means the SMPTE-based time division,
+    /// where the tempo is ignored and the bars and beats are not available.
+    pub fn get_resolution(&self) -> i32 {
+        self.resolution
+    }
+
+    /// Gets the tempo changes in the order of the time.
+    pub fn get_tempo_changes(&self) -> &[TempoChange] {
+        &self.tempo_changes
+    }
+
+    /// Gets the time signatures in the order of the time.
+    pub fn get_time_signatures(&self) -> &[TimeSignature] {
+        &self.time_signatures
+    }
+
+    /// Gets the tempo in beats per minute at the specified time.
+    ///
+    /// # Arguments
+    ///
+    /// * `seconds` - The time in seconds.
+    pub fn get_tempo_at(&self, seconds: f64) -> f64 {
+        let index = self.tempo_changes.partition_point(|x| x.time <= seconds);
+        match index {
+            0 => TempoMap::DEFAULT_TEMPO,
+            _ => self.tempo_changes[index - 1].tempo,
+        }
+    }
+
+    /// Converts a position in ticks to seconds.
+    ///
+    /// # Arguments
+    ///
+    /// * `ticks` - The position in ticks, which can be fractional.
+    pub fn ticks_to_seconds(&self, ticks: f64) -> f64 {
+        if let Some(tick_length) = MidiFile::get_smpte_tick_length(self.resolution) {
+            return tick_length * ticks;
+        }
+
+        let index = self
+            .tempo_changes
+            .partition_point(|x| x.tick as f64 <= ticks);
+        let (tick, time, tempo) = match index {
+            0 => (0.0, 0.0, TempoMap::DEFAULT_TEMPO),
+            _ => {
+                let last = &self.tempo_changes[index - 1];
+                (last.tick as f64, last.time, last.tempo)
+            }
+        };
+
+        time + 60.0 / (self.resolution as f64 * tempo) * (ticks - tick)
+    }
+
+    /// Converts a position in seconds to ticks.
+    ///
+    /// # Arguments
+    ///
+    /// * `seconds` - The position in seconds.
+    ///
+    /// # Remarks
+    ///
+    /// The result is fractional if the position is between the ticks.
+    pub fn seconds_to_ticks(&self, seconds: f64) -> f64 {
+        if let Some(tick_length) = MidiFile::get_smpte_tick_length(self.resolution) {
+            return seconds / tick_length;
+        }
+
+        let index = self.tempo_changes.partition_point(|x| x.time <= seconds);
+        let (tick, time, tempo) = match index {
+            0 => (0.0, 0.0, TempoMap::DEFAULT_TEMPO),
+            _ => {
+                let last = &self.tempo_changes[index - 1];
+                (last.tick as f64, last.time, last.tempo)
+            }
+        };
+
+        tick + (seconds - time) * self.resolution as f64 * tempo / 60.0
+    }
+
+    /// Converts a position in ticks to bars and beats.
+    ///
+    /// # Arguments
+    ///
+    /// * `ticks` - The position in ticks.
+    ///
+    /// # Remarks
+    ///
+    /// The result is the zero-based bar and the fractional zero-based beat in the bar,
+    /// or `None` for the SMPTE-based time division.
+    /// A time signature in the middle of a bar starts a new bar.
+    pub fn ticks_to_bars(&self, ticks: f64) -> Option<(i32, f64)> {
+        if self.resolution < 0 {
+            return None;
+        }
+
+        let (bar, signature) = self.find_time_signature(|_, tick| tick <= ticks);
+        let (start_tick, beat_length, bar_length) = self.get_bar_length(&signature);
+
+        let bars = FloatMath::floor_f64((ticks - start_tick) / bar_length);
+        let beat = (ticks - start_tick - bars * bar_length) / beat_length;
+
+        Some((bar + bars as i32, beat))
+    }
+
+    /// Converts a position in bars and beats to ticks.
+    ///
+    /// # Arguments
+    ///
+    /// * `bar` - The zero-based bar.
+    /// * `beat` - The zero-based beat in the bar, which can be fractional.
+    ///
+    /// # Remarks
+    ///
+    /// The result is `None` for the SMPTE-based time division.
+    pub fn bars_to_ticks(&self, bar: i32, beat: f64) -> Option<f64> {
+        if self.resolution < 0 {
+            return None;
+        }
+
+        let (start_bar, signature) = self.find_time_signature(|start_bar, _| start_bar <= bar);
+        let (start_tick, beat_length, bar_length) = self.get_bar_length(&signature);
+
+        Some(start_tick + (bar - start_bar) as f64 * bar_length + beat * beat_length)
+    }
+
+    /// Converts a position in seconds to bars and beats.
+    ///
+    /// # Arguments
+    ///
+    /// * `seconds` - The position in seconds.
+    pub fn seconds_to_bars(&self, seconds: f64) -> Option<(i32, f64)> {
+        self.ticks_to_bars(self.seconds_to_ticks(seconds))
+    }
+
+    /// Converts a position in bars and beats to seconds.
+    ///
+    /// # Arguments
+    ///
+    /// * `bar` - The zero-based bar.
+    /// * `beat` - The zero-based beat in the bar, which can be fractional.
+    pub fn bars_to_seconds(&self, bar: i32, beat: f64) -> Option<f64> {
+        self.bars_to_ticks(bar, beat)
+            .map(|ticks| self.ticks_to_seconds(ticks))
+    }
+
+    // The number of the beats in the specified bar.
+    pub(crate) fn get_beat_count(&self, bar: i32) -> i32 {
+        let (_, signature) = self.find_time_signature(|start_bar, _| start_bar <= bar);
+        signature.numerator.max(1)
+    }
+
+    // Finds the last time signature which satisfies the condition,
+    // which takes the bar and the tick where each time signature starts.
+    fn find_time_signature(&self, condition: impl Fn(i32, f64) -> bool) -> (i32, TimeSignature) {
+        let mut bar: i32 = 0;
+        let mut current = TimeSignature {
+            tick: 0,
+            numerator: 4,
+            denominator: 4,
+        };
+
+        for signature in self.time_signatures.iter() {
+            let (start_tick, _, bar_length) = self.get_bar_length(&current);
+            let bars = (signature.tick as f64 - start_tick) / bar_length;
+            let next_bar = bar + -FloatMath::floor_f64(-bars) as i32;
+            if !condition(next_bar, signature.tick as f64) {
+                break;
+            }
+            bar = next_bar;
+            current = *signature;
+        }
+
+        (bar, current)
+    }
+
+    // Returns the start tick, and the lengths of a beat and a bar in ticks.
+    fn get_bar_length(&self, signature: &TimeSignature) -> (f64, f64, f64) {
+        let beat_length = 4.0 * self.resolution as f64 / signature.denominator as f64;
+        let bar_length = beat_length * signature.numerator.max(1) as f64;
+        (signature.tick as f64, beat_length, bar_length)
+    }
+}
@@ -0,0 +1,595 @@
+#![allow(dead_code)]
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::diagnostics::Diagnostics;
+use crate::float_math::FloatMath;
+use crate::generator_type::GeneratorType;
+use crate::instrument::Instrument;
+use crate::instrument_region::InstrumentRegion;
+use crate::preset::Preset;
+use crate::preset_lookup::PresetLookup;
+use crate::preset_region::PresetRegion;
+use crate::sample_header::SampleHeader;
+use crate::soundfont::SoundFont;
+use crate::soundfont_info::SoundFontInfo;
+use crate::soundfont_version::SoundFontVersion;
+use crate::wave_data::WaveData;
+
+// A tiny SoundFont built in memory from generated waveforms.
+// It does not depend on any file, so the results are reproducible everywhere.
+#[allow(unused)]
+#[non_exhaustive]
+pub(crate) struct TestBank {}
+
+// A generated sample.
+struct TestSample {
+    name: &'static str,
+    data: Vec<i16>,
+    is_looped: bool,
+    sample_rate: i32,
+    original_pitch: u8,
+}
+
+// A region of an instrument, which plays a sample in the key range with the generators.
+struct TestRegion {
+    sample: usize,
+    key_range: (u8, u8),
+    generators: &'static [(u16, i16)],
+}
+
+struct TestInstrument {
+    name: &'static str,
+    regions: &'static [TestRegion],
+}
+
+impl TestBank {
+    // One cycle of the looped waveforms, which is 440 Hz at the sample rate.
+    const CYCLE_LENGTH: usize = 64;
+    const CYCLE_COUNT: usize = 8;
+    const LOOPED_SAMPLE_RATE: i32 = 440 * TestBank::CYCLE_LENGTH as i32;
+
+    // Each sample must be followed by at least 46 zero samples.
+    const SAMPLE_PADDING: usize = 46;
+
+    const SINE: usize = 0;
+    const TRIANGLE: usize = 1;
+    const SQUARE: usize = 2;
+    const SAW: usize = 3;
+    const NOISE: usize = 4;
+
+    // The envelope times are in timecents, and the sustain levels are in centibels.
+    const INSTRUMENTS: [TestInstrument; 12] = [
+        TestInstrument {
+            name: "Piano",
+            regions: &[TestRegion::full(
+                TestBank::SAW,
+                &[
+                    (GeneratorType::SAMPLE_MODES, 1),
+                    (GeneratorType::DECAY_VOLUME_ENVELOPE, 1200),
+                    (GeneratorType::SUSTAIN_VOLUME_ENVELOPE, 1000),
+                    (GeneratorType::RELEASE_VOLUME_ENVELOPE, -2800),
+                ],
+            )],
+        },
+        TestInstrument {
+            name: "Mallet",
+            regions: &[TestRegion::full(
+                TestBank::SINE,
+                &[
+                    (GeneratorType::SAMPLE_MODES, 1),
+                    (GeneratorType::DECAY_VOLUME_ENVELOPE, -600),
+                    (GeneratorType::SUSTAIN_VOLUME_ENVELOPE, 1000),
+                    (GeneratorType::RELEASE_VOLUME_ENVELOPE, -2800),
+                ],
+            )],
+        },
+        TestInstrument {
+            name: "Organ",
+            regions: &[TestRegion::full(
+                TestBank::SQUARE,
+                &[
+                    (GeneratorType::SAMPLE_MODES, 1),
+                    (GeneratorType::RELEASE_VOLUME_ENVELOPE, -4000),
+                ],
+            )],
+        },
+        TestInstrument {
+            name: "Pluck",
+            regions: &[TestRegion::full(
+                TestBank::SAW,
+                &[
+                    (GeneratorType::SAMPLE_MODES, 1),
+                    (GeneratorType::DECAY_VOLUME_ENVELOPE, -1200),
+                    (GeneratorType::SUSTAIN_VOLUME_ENVELOPE, 1000),
+                    (GeneratorType::RELEASE_VOLUME_ENVELOPE, -2800),
+                ],
+            )],
+        },
+        TestInstrument {
+            name: "Bass",
+            regions: &[TestRegion::full(
+                TestBank::TRIANGLE,
+                &[
+                    (GeneratorType::SAMPLE_MODES, 1),
+                    (GeneratorType::DECAY_VOLUME_ENVELOPE, 0),
+                    (GeneratorType::SUSTAIN_VOLUME_ENVELOPE, 1000),
+                    (GeneratorType::RELEASE_VOLUME_ENVELOPE, -2800),
+                ],
+            )],
+        },
+        TestInstrument {
+            name: "Strings",
+            regions: &[TestRegion::full(
+                TestBank::SAW,
+                &[
+                    (GeneratorType::SAMPLE_MODES, 1),
+                    (GeneratorType::ATTACK_VOLUME_ENVELOPE, -2800),
+                    (GeneratorType::RELEASE_VOLUME_ENVELOPE, -1200),
+                ],
+            )],
+        },
+        TestInstrument {
+            name: "Brass",
+            regions: &[TestRegion::full(
+                TestBank::SQUARE,
+                &[
+                    (GeneratorType::SAMPLE_MODES, 1),
+                    (GeneratorType::ATTACK_VOLUME_ENVELOPE, -5200),
+                    (GeneratorType::RELEASE_VOLUME_ENVELOPE, -2800),
+                ],
+            )],
+        },
+        TestInstrument {
+            name: "Flute",
+            regions: &[TestRegion::full(
+                TestBank::SINE,
+                &[
+                    (GeneratorType::SAMPLE_MODES, 1),
+                    (GeneratorType::ATTACK_VOLUME_ENVELOPE, -5200),
+                    (GeneratorType::RELEASE_VOLUME_ENVELOPE, -2800),
+                ],
+            )],
+        },
+        TestInstrument {
+            name: "Lead",
+            regions: &[TestRegion::full(
+                TestBank::SAW,
+                &[
+                    (GeneratorType::SAMPLE_MODES, 1),
+                    (GeneratorType::RELEASE_VOLUME_ENVELOPE, -2800),
+                ],
+            )],
+        },
+        TestInstrument {
+            name: "Pad",
+            regions: &[TestRegion::full(
+                TestBank::TRIANGLE,
+                &[
+                    (GeneratorType::SAMPLE_MODES, 1),
+                    (GeneratorType::ATTACK_VOLUME_ENVELOPE, -1200),
+                    (GeneratorType::RELEASE_VOLUME_ENVELOPE, 0),
+                ],
+            )],
+        },
+        TestInstrument {
+            name: "Noise",
+            regions: &[TestRegion::full(
+                TestBank::NOISE,
+                &[(GeneratorType::RELEASE_VOLUME_ENVELOPE, -2800)],
+            )],
+        },
+        // The kicks are a sine at 55 Hz, and the other drums are the noise pitched by the key.
+        TestInstrument {
+            name: "Drums",
+            regions: &[
+                TestRegion {
+                    sample: TestBank::SINE,
+                    key_range: (35, 36),
+                    generators: &[
+                        (GeneratorType::SAMPLE_MODES, 1),
+                        (GeneratorType::SCALE_TUNING, 0),
+                        (GeneratorType::COARSE_TUNE, -36),
+                        (GeneratorType::DECAY_VOLUME_ENVELOPE, -1600),
+                        (GeneratorType::SUSTAIN_VOLUME_ENVELOPE, 1000),
+                        (GeneratorType::RELEASE_VOLUME_ENVELOPE, -2800),
+                    ],
+                },
+                TestRegion {
+                    sample: TestBank::NOISE,
+                    key_range: (0, 34),
+                    generators: &[(GeneratorType::RELEASE_VOLUME_ENVELOPE, -2800)],
+                },
+                TestRegion {
+                    sample: TestBank::NOISE,
+                    key_range: (37, 127),
+                    generators: &[(GeneratorType::RELEASE_VOLUME_ENVELOPE, -2800)],
+                },
+            ],
+        },
+    ];
+
+    // The instrument for each family of 8 GM programs.
+    const FAMILY_INSTRUMENTS: [usize; 16] = [
+        0,  // Piano
+        1,  // Chromatic Percussion
+        2,  // Organ
+        3,  // Guitar
+        4,  // Bass
+        5,  // Strings
+        5,  // Ensemble
+        6,  // Brass
+        6,  // Reed
+        7,  // Pipe
+        8,  // Synth Lead
+        9,  // Synth Pad
+        9,  // Synth Effects
+        3,  // Ethnic
+        1,  // Percussive
+        10, // Sound Effects
+    ];
+    const DRUM_INSTRUMENT: usize = 11;
+
+    const PROGRAM_NAMES: [&'static str; 128] = [
+        "Acoustic Grand Piano",
+        "Bright Acoustic Piano",
+        "Electric Grand Piano",
+        "Honky-tonk Piano",
+        "Electric Piano 1",
+        "Electric Piano 2",
+        "Harpsichord",
+        "Clavi",
+        "Celesta",
+        "Glockenspiel",
+        "Music Box",
+        "Vibraphone",
+        "Marimba",
+        "Xylophone",
+        "Tubular Bells",
+        "Dulcimer",
+        "Drawbar Organ",
+        "Percussive Organ",
+        "Rock Organ",
+        "Church Organ",
+        "Reed Organ",
+        "Accordion",
+        "Harmonica",
+        "Tango Accordion",
+        "Acoustic Guitar (nylon)",
+        "Acoustic Guitar (steel)",
+        "Electric Guitar (jazz)",
+        "Electric Guitar (clean)",
+        "Electric Guitar (muted)",
+        "Overdriven Guitar",
+        "Distortion Guitar",
+        "Guitar Harmonics",
+        "Acoustic Bass",
+        "Electric Bass (finger)",
+        "Electric Bass (pick)",
+        "Fretless Bass",
+        "Slap Bass 1",
+        "Slap Bass 2",
+        "Synth Bass 1",
+        "Synth Bass 2",
+        "Violin",
+        "Viola",
+        "Cello",
+        "Contrabass",
+        "Tremolo Strings",
+        "Pizzicato Strings",
+        "Orchestral Harp",
+        "Timpani",
+        "String Ensemble 1",
+        "String Ensemble 2",
+        "Synth Strings 1",
+        "Synth Strings 2",
+        "Choir Aahs",
+        "Voice Oohs",
+        "Synth Voice",
+        "Orchestra Hit",
+        "Trumpet",
+        "Trombone",
+        "Tuba",
+        "Muted Trumpet",
+        "French Horn",
+        "Brass Section",
+        "Synth Brass 1",
+        "Synth Brass 2",
+        "Soprano Sax",
+        "Alto Sax",
+        "Tenor Sax",
+        "Baritone Sax",
+        "Oboe",
+        "English Horn",
+        "Bassoon",
+        "Clarinet",
+        "Piccolo",
+        "Flute",
+        "Recorder",
+        "Pan Flute",
+        "Blown Bottle",
+        "Shakuhachi",
+        "Whistle",
+        "Ocarina",
+        "Lead 1 (square)",
+        "Lead 2 (sawtooth)",
+        "Lead 3 (calliope)",
+        "Lead 4 (chiff)",
+        "Lead 5 (charang)",
+        "Lead 6 (voice)",
+        "Lead 7 (fifths)",
+        "Lead 8 (bass + lead)",
+        "Pad 1 (new age)",
+        "Pad 2 (warm)",
+        "Pad 3 (polysynth)",
+        "Pad 4 (choir)",
+        "Pad 5 (bowed)",
+        "Pad 6 (metallic)",
+        "Pad 7 (halo)",
+        "Pad 8 (sweep)",
+        "FX 1 (rain)",
+        "FX 2 (soundtrack)",
+        "FX 3 (crystal)",
+        "FX 4 (atmosphere)",
+        "FX 5 (brightness)",
+        "FX 6 (goblins)",
+        "FX 7 (echoes)",
+        "FX 8 (sci-fi)",
+        "Sitar",
+        "Banjo",
+        "Shamisen",
+        "Koto",
+        "Kalimba",
+        "Bag pipe",
+        "Fiddle",
+        "Shanai",
+        "Tinkle Bell",
+        "Agogo",
+        "Steel Drums",
+        "Woodblock",
+        "Taiko Drum",
+        "Melodic Tom",
+        "Synth Drum",
+        "Reverse Cymbal",
+        "Guitar Fret Noise",
+        "Breath Noise",
+        "Seashore",
+        "Bird Tweet",
+        "Telephone Ring",
+        "Helicopter",
+        "Applause",
+        "Gunshot",
+    ];
+
+    pub(crate) fn create() -> SoundFont {
+        let samples = [
+            TestBank::create_looped_sample("Sine", &[1_f32]),
+            TestBank::create_looped_sample(
+                "Triangle",
+                &[1_f32, 0_f32, -1_f32 / 9_f32, 0_f32, 1_f32 / 25_f32],
+            ),
+            TestBank::create_looped_sample(
+                "Square",
+                &[
+                    1_f32,
+                    0_f32,
+                    1_f32 / 3_f32,
+                    0_f32,
+                    1_f32 / 5_f32,
+                    0_f32,
+                    1_f32 / 7_f32,
+                ],
+            ),
+            TestBank::create_looped_sample(
+                "Saw",
+                &[
+                    1_f32,
+                    1_f32 / 2_f32,
+                    1_f32 / 3_f32,
+                    1_f32 / 4_f32,
+                    1_f32 / 5_f32,
+                    1_f32 / 6_f32,
+                    1_f32 / 7_f32,
+                    1_f32 / 8_f32,
+                ],
+            ),
+            TestSample {
+                name: "Noise",
+                data: TestBank::create_noise(4410),
+                is_looped: false,
+                sample_rate: 44100,
+                original_pitch: 60,
+            },
+        ];
+
+        let mut wave_data: Vec<i16> = Vec::new();
+        let mut sample_headers: Vec<SampleHeader> = Vec::new();
+        for sample in samples.iter() {
+            let start = wave_data.len() as i32;
+            let end = start + sample.data.len() as i32;
+            // The first cycle is skipped, so that the loop starts with the same phase as it ends.
+            let start_loop = if sample.is_looped {
+                start + TestBank::CYCLE_LENGTH as i32
+            } else {
+                start
+            };
+            sample_headers.push(SampleHeader {
+                name: String::from(sample.name),
+                start,
+                end,
+                start_loop,
+                end_loop: end,
+                sample_rate: sample.sample_rate,
+                original_pitch: sample.original_pitch,
+                pitch_correction: 0,
+                link: 0,
+                sample_type: 1,
+                source: 0,
+            });
+            wave_data.extend_from_slice(&sample.data);
+            wave_data.resize(wave_data.len() + TestBank::SAMPLE_PADDING, 0);
+        }
+
+        let instruments: Vec<Instrument> = TestBank::INSTRUMENTS
+            .iter()
+            .map(|instrument| TestBank::create_instrument(instrument, &sample_headers))
+            .collect();
+
+        let mut presets: Vec<Preset> = TestBank::PROGRAM_NAMES
+            .iter()
+            .enumerate()
+            .map(|(program, name)| {
+                let instrument = TestBank::FAMILY_INSTRUMENTS[program / 8];
+                TestBank::create_preset(name, 0, program as i32, instrument)
+            })
+            .collect();
+        presets.push(TestBank::create_preset(
+            "Standard",
+            128,
+            0,
+            TestBank::DRUM_INSTRUMENT,
+        ));
+
+        SoundFont {
+            info: TestBank::create_info(),
+            bits_per_sample: 16,
+            wave_data: Arc::new(WaveData::Owned(wave_data)),
+            wave_data_24: None,
+            merged_wave_data: Vec::new(),
+            sample_headers,
+            preset_lookup: PresetLookup::new(&presets),
+            presets,
+            instruments,
+            diagnostics: Diagnostics::new(),
+        }
+    }
+
+    // The cycle is repeated, so that the interpolation works across the loop end.
+    fn create_looped_sample(name: &'static str, amplitudes: &[f32]) -> TestSample {
+        let total: f32 = amplitudes.iter().map(|x| x.abs()).sum();
+        let length = TestBank::CYCLE_LENGTH * TestBank::CYCLE_COUNT;
+        let data = (0..length)
+            .map(|t| {
+                let phase =
+                    2_f32 * core::f32::consts::PI * t as f32 / TestBank::CYCLE_LENGTH as f32;
+                let value: f32 = amplitudes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, amplitude)| amplitude * FloatMath::sin((i + 1) as f32 * phase))
+                    .sum();
+                (16000_f32 * value / total) as i16
+            })
+            .collect();
+
+        TestSample {
+            name,
+            data,
+            is_looped: true,
+            sample_rate: TestBank::LOOPED_SAMPLE_RATE,
+            original_pitch: 69,
+        }
+    }
+
+    // A linear congruential generator is used, so that the noise is the same everywhere.
+    fn create_noise(length: usize) -> Vec<i16> {
+        let mut state: u32 = 1;
+        (0..length)
+            .map(|t| {
+                state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+                let value = (state >> 16) as i16 as f32;
+                let decay = 1_f32 - t as f32 / length as f32;
+                (0.5_f32 * value * decay * decay) as i16
+            })
+            .collect()
+    }
+
+    fn create_instrument(instrument: &TestInstrument, samples: &[SampleHeader]) -> Instrument {
+        let regions = instrument
+            .regions
+            .iter()
+            .map(|region| {
+                let sample = &samples[region.sample];
+
+                let mut gs = InstrumentRegion::default_generators();
+                gs[GeneratorType::SAMPLE_ID as usize] = region.sample as i16;
+                gs[GeneratorType::KEY_RANGE as usize] =
+                    region.key_range.0 as i16 | ((region.key_range.1 as i16) << 8);
+                for (generator_type, value) in region.generators.iter() {
+                    gs[*generator_type as usize] = *value;
+                }
+
+                InstrumentRegion {
+                    gs,
+                    sample: region.sample,
+                    sample_start: sample.start,
+                    sample_end: sample.end,
+                    sample_start_loop: sample.start_loop,
+                    sample_end_loop: sample.end_loop,
+                    sample_sample_rate: sample.sample_rate,
+                    sample_original_pitch: sample.original_pitch as i32,
+                    sample_pitch_correction: sample.pitch_correction as i32,
+                }
+            })
+            .collect();
+
+        Instrument {
+            name: String::from(instrument.name),
+            regions,
+        }
+    }
+
+    fn create_preset(
+        name: &str,
+        bank_number: i32,
+        patch_number: i32,
+        instrument_id: usize,
+    ) -> Preset {
+        let mut gs = PresetRegion::default_generators();
+        gs[GeneratorType::INSTRUMENT as usize] = instrument_id as i16;
+
+        Preset {
+            name: String::from(name),
+            patch_number,
+            bank_number,
+            library: 0,
+            genre: 0,
+            morphology: 0,
+            regions: vec![PresetRegion {
+                gs,
+                instrument: instrument_id,
+            }],
+        }
+    }
+
+    fn create_info() -> SoundFontInfo {
+        SoundFontInfo {
+            version: SoundFontVersion { major: 2, minor: 1 },
+            target_sound_engine: String::from("EMU8000"),
+            bank_name: String::from("RustySynth Test Bank"),
+            rom_name: String::new(),
+            rom_version: SoundFontVersion::default(),
+            creation_date: String::new(),
+            author: String::new(),
+            target_product: String::new(),
+            copyright: String::new(),
+            comments: String::from(
+                "Generated waveforms for the tests, the examples and the benchmarks.",
+            ),
+            tools: String::from("RustySynth"),
+        }
+    }
+}
+
+impl TestRegion {
+    // The region for all the keys.
+    const fn full(sample: usize, generators: &'static [(u16, i16)]) -> Self {
+        Self {
+            sample,
+            key_range: (0, 127),
+            generators,
+        }
+    }
+}
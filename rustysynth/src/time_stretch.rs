@@ -0,0 +1,368 @@
+#![allow(dead_code)]
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts;
+
+use crate::float_math::FloatMath;
+
+/// Changes the tempo of a rendered waveform without changing the pitch.
+///
+/// # Remarks
+///
+/// The waveform is cut into overlapping frames, which are placed at the new tempo
+/// after being shifted slightly to match the waveform of the previous frame (WSOLA).
+/// Unlike the speed of the sequencer, the envelopes and the reverb are stretched together with the notes.
+/// The blocks are processed one by one, so the speed can be changed during playback.
+/// The output is delayed by a few tens of milliseconds, which is emitted by `flush` at the end.
+#[non_exhaustive]
+pub struct TimeStretch {
+    speed: f64,
+
+    frame_length: usize,
+    hop_length: usize,
+    tolerance: usize,
+    window: Vec<f32>,
+
+    // The ring of the input which may still be used, whose length is a power of two.
+    // The samples are indexed by the position in the input, from the first one kept to the end of the input.
+    input_left: Vec<f32>,
+    input_right: Vec<f32>,
+    input_offset: usize,
+    input_length: usize,
+
+    // The ideal position of the next frame in the input, and the actual position of the previous frame.
+    position: f64,
+    previous_position: Option<usize>,
+
+    // The frames being overlapped, whose first hop is complete.
+    overlap_left: Vec<f32>,
+    overlap_right: Vec<f32>,
+
+    // The first output is dropped, as it is faded in by the window.
+    skip_length: usize,
+    // The output length expected from the input at the speeds, and the actual output length.
+    expected_length: f64,
+    output_length: usize,
+}
+
+impl TimeStretch {
+    // The frame length in seconds.
+    const FRAME_LENGTH: f64 = 0.04;
+    // Every other sample is compared to find the best position, which is enough for the audible range.
+    const DECIMATION: usize = 2;
+
+    /// Initializes a new instance of the time stretch.
+    ///
+    /// # Arguments
+    ///
+    /// * `sample_rate` - The sample rate of the waveform.
+    /// * `speed` - The playback speed, such as 0.8 to play at 80% of the original tempo.
+    pub fn new(sample_rate: i32, speed: f64) -> Self {
+        if sample_rate <= 0 {
+            panic!("The sample rate must be greater than zero.");
+        }
+
+        TimeStretch::check_speed(speed);
+
+        let frame_length =
+            2 * (((sample_rate as f64 * TimeStretch::FRAME_LENGTH) as usize / 2).max(1));
+        let hop_length = frame_length / 2;
+
+        // The periodic Hann window, whose overlaps at the half length sum up to 1.
+        let window = (0..frame_length)
+            .map(|i| {
+                0.5_f32
+                    - 0.5_f32 * FloatMath::cos(2_f32 * consts::PI * i as f32 / frame_length as f32)
+            })
+            .collect();
+
+        let mut time_stretch = Self {
+            speed,
+            frame_length,
+            hop_length,
+            tolerance: frame_length / 4,
+            window,
+            input_left: Vec::new(),
+            input_right: Vec::new(),
+            input_offset: 0,
+            input_length: 0,
+            position: 0.0,
+            previous_position: None,
+            overlap_left: vec![0_f32; frame_length],
+            overlap_right: vec![0_f32; frame_length],
+            skip_length: 0,
+            expected_length: 0.0,
+            output_length: 0,
+        };
+        time_stretch.reserve_input(speed);
+        time_stretch.reset();
+        time_stretch
+    }
+
+    /// Stretches the block of the waveform.
+    ///
+    /// # Arguments
+    ///
+    /// * `left` - The left channel of the input.
+    /// * `right` - The right channel of the input.
+    /// * `left_output` - The buffer which the left channel of the output is appended to.
+    /// * `right_output` - The buffer which the right channel of the output is appended to.
+    ///
+    /// # Remarks
+    ///
+    /// The input buffers for the left and right must be the same length.
+    /// The output is appended in steps of the frame, so its length varies with each block.
+    pub fn process(
+        &mut self,
+        left: &[f32],
+        right: &[f32],
+        left_output: &mut Vec<f32>,
+        right_output: &mut Vec<f32>,
+    ) {
+        if left.len() != right.len() {
+            panic!("The input buffers for the left and right must be the same length.");
+        }
+
+        self.expected_length += left.len() as f64 / self.speed;
+
+        // The input is added as far as the ring has room, and the frames using it make room for the rest.
+        let mut written: usize = 0;
+        while written < left.len() {
+            let count = self.get_input_room().min(left.len() - written);
+            for i in 0..count {
+                self.write_input(left[written + i], right[written + i]);
+            }
+            written += count;
+
+            self.process_frames(left_output, right_output, usize::MAX);
+        }
+    }
+
+    /// Emits the rest of the output, and resets the time stretch for the next waveform.
+    ///
+    /// # Arguments
+    ///
+    /// * `left_output` - The buffer which the left channel of the output is appended to.
+    /// * `right_output` - The buffer which the right channel of the output is appended to.
+    ///
+    /// # Remarks
+    ///
+    /// In total, the output has the length of the input divided by the speed.
+    pub fn flush(&mut self, left_output: &mut Vec<f32>, right_output: &mut Vec<f32>) {
+        let expected_length = FloatMath::round_f64(self.expected_length) as usize;
+
+        // The input is padded with silence until the whole input has passed through the frames.
+        while self.output_length < expected_length {
+            let padding = self.hop_length.min(self.get_input_room());
+            for _ in 0..padding {
+                self.write_input(0_f32, 0_f32);
+            }
+            let limit = expected_length - self.output_length;
+            self.process_frames(left_output, right_output, limit);
+        }
+
+        self.reset();
+    }
+
+    /// Stretches the whole waveform.
+    ///
+    /// # Arguments
+    ///
+    /// * `left` - The left channel of the waveform.
+    /// * `right` - The right channel of the waveform.
+    ///
+    /// # Remarks
+    ///
+    /// The input buffers for the left and right must be the same length.
+    /// The time stretch is reset before and after the processing.
+    pub fn stretch(&mut self, left: &[f32], right: &[f32]) -> (Vec<f32>, Vec<f32>) {
+        self.reset();
+
+        let mut left_output: Vec<f32> = Vec::new();
+        let mut right_output: Vec<f32> = Vec::new();
+        self.process(left, right, &mut left_output, &mut right_output);
+        self.flush(&mut left_output, &mut right_output);
+
+        (left_output, right_output)
+    }
+
+    /// Discards the input and the output being processed.
+    pub fn reset(&mut self) {
+        // The input starts with silence of a hop, so that the first output is fully overlapped.
+        self.input_left.fill(0_f32);
+        self.input_right.fill(0_f32);
+        self.input_offset = 0;
+        self.input_length = self.hop_length;
+
+        self.position = 0.0;
+        self.previous_position = None;
+
+        self.overlap_left.fill(0_f32);
+        self.overlap_right.fill(0_f32);
+
+        self.skip_length = self.hop_length;
+        self.expected_length = 0.0;
+        self.output_length = 0;
+    }
+
+    // Processes the frames whose input is available, up to the specified output length.
+    fn process_frames(
+        &mut self,
+        left_output: &mut Vec<f32>,
+        right_output: &mut Vec<f32>,
+        limit: usize,
+    ) {
+        let mut emitted: usize = 0;
+        loop {
+            let ideal = FloatMath::round_f64(self.position) as usize;
+            if ideal + self.tolerance + self.frame_length > self.input_length {
+                break;
+            }
+
+            let position = match self.previous_position {
+                Some(previous) => self.find_position(ideal, previous + self.hop_length),
+                None => ideal,
+            };
+
+            let mask = self.input_left.len() - 1;
+            for i in 0..self.frame_length {
+                let j = (position + i) & mask;
+                self.overlap_left[i] += self.window[i] * self.input_left[j];
+                self.overlap_right[i] += self.window[i] * self.input_right[j];
+            }
+
+            // The first hop has been overlapped by all the frames.
+            let skip = self.skip_length.min(self.hop_length);
+            self.skip_length -= skip;
+            let count = (self.hop_length - skip).min(limit - emitted);
+            left_output.extend_from_slice(&self.overlap_left[skip..skip + count]);
+            right_output.extend_from_slice(&self.overlap_right[skip..skip + count]);
+            emitted += count;
+            self.output_length += count;
+
+            self.overlap_left.copy_within(self.hop_length.., 0);
+            self.overlap_right.copy_within(self.hop_length.., 0);
+            self.overlap_left[self.hop_length..].fill(0_f32);
+            self.overlap_right[self.hop_length..].fill(0_f32);
+
+            self.previous_position = Some(position);
+            self.position += self.speed * self.hop_length as f64;
+
+            if emitted == limit {
+                break;
+            }
+        }
+
+        // The input before both the next candidates and the continuation of the last frame is no longer used.
+        if let Some(previous) = self.previous_position {
+            let next_ideal = FloatMath::round_f64(self.position) as usize;
+            self.input_offset = (previous + self.hop_length)
+                .min(next_ideal.saturating_sub(self.tolerance))
+                .max(self.input_offset);
+        }
+    }
+
+    // The ring keeps the input from the continuation of the previous frame to the end of the next frame,
+    // which is a step of the speed apart, and a hop of the padding.
+    // It is reallocated only when the speed exceeds the ones before.
+    fn reserve_input(&mut self, speed: f64) {
+        let step = (speed * self.hop_length as f64) as usize + 1;
+        let capacity = (step + 2 * self.tolerance + 2 * self.frame_length + self.hop_length + 2)
+            .next_power_of_two();
+        if capacity <= self.input_left.len() {
+            return;
+        }
+
+        let mut input_left = vec![0_f32; capacity];
+        let mut input_right = vec![0_f32; capacity];
+        for position in self.input_offset..self.input_length {
+            let i = position & (self.input_left.len() - 1);
+            input_left[position & (capacity - 1)] = self.input_left[i];
+            input_right[position & (capacity - 1)] = self.input_right[i];
+        }
+        self.input_left = input_left;
+        self.input_right = input_right;
+    }
+
+    fn get_input_room(&self) -> usize {
+        self.input_left.len() - (self.input_length - self.input_offset)
+    }
+
+    fn write_input(&mut self, left: f32, right: f32) {
+        let i = self.input_length & (self.input_left.len() - 1);
+        self.input_left[i] = left;
+        self.input_right[i] = right;
+        self.input_length += 1;
+    }
+
+    // Finds the position around the ideal one, where the waveform is the most similar to
+    // the natural continuation of the previous frame.
+    fn find_position(&self, ideal: usize, continuation: usize) -> usize {
+        let first = ideal.saturating_sub(self.tolerance).max(self.input_offset);
+        let last = ideal + self.tolerance;
+
+        let mut best_position = ideal;
+        let mut best_score = self.get_similarity(continuation, ideal);
+        for candidate in first..=last {
+            if candidate == ideal {
+                continue;
+            }
+
+            let score = self.get_similarity(continuation, candidate);
+            if score > best_score {
+                best_position = candidate;
+                best_score = score;
+            }
+        }
+
+        best_position
+    }
+
+    // The squared normalized cross-correlation with the sign, which needs no square root.
+    fn get_similarity(&self, target: usize, candidate: usize) -> f32 {
+        let mask = self.input_left.len() - 1;
+        let mut xy = 0_f32;
+        let mut yy = 0_f32;
+        for i in (0..self.hop_length).step_by(TimeStretch::DECIMATION) {
+            let xl = self.input_left[(target + i) & mask];
+            let xr = self.input_right[(target + i) & mask];
+            let yl = self.input_left[(candidate + i) & mask];
+            let yr = self.input_right[(candidate + i) & mask];
+            xy += xl * yl + xr * yr;
+            yy += yl * yl + yr * yr;
+        }
+
+        if yy > 0_f32 {
+            xy * xy.abs() / yy
+        } else {
+            0_f32
+        }
+    }
+
+    fn check_speed(speed: f64) {
+        if speed.is_nan() || speed <= 0.0 {
+            panic!("The speed must be greater than zero.");
+        }
+    }
+
+    /// Gets the playback speed.
+    pub fn get_speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Sets the playback speed.
+    ///
+    /// # Remarks
+    ///
+    /// The value must be greater than zero.
+    /// The change takes effect from the next block.
+    /// The buffer of the input is allocated beforehand for the speed,
+    /// and is reallocated only when the speed is higher than any before.
+    pub fn set_speed(&mut self, value: f64) {
+        TimeStretch::check_speed(value);
+
+        self.reserve_input(value);
+        self.speed = value;
+    }
+}
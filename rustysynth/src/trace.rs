@@ -0,0 +1,41 @@
+// The spans and events for profiling.
+// With the tracing feature, they are the ones of the tracing crate.
+// Without it, the same macros expand to nothing,
+// so that the callers do not have to care about the feature.
+
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::{debug, debug_span, trace_span};
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) use self::disabled::*;
+
+#[cfg(not(feature = "tracing"))]
+mod disabled {
+    pub(crate) struct Span;
+
+    impl Span {
+        pub(crate) fn entered(self) -> Self {
+            self
+        }
+    }
+
+    macro_rules! debug {
+        ($($arg:tt)*) => {};
+    }
+
+    macro_rules! debug_span {
+        ($($arg:tt)*) => {
+            $crate::trace::Span
+        };
+    }
+
+    macro_rules! trace_span {
+        ($($arg:tt)*) => {
+            $crate::trace::Span
+        };
+    }
+
+    pub(crate) use debug;
+    pub(crate) use debug_span;
+    pub(crate) use trace_span;
+}
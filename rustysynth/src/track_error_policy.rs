@@ -0,0 +1,17 @@
+/// Specifies how `ThreadedRender` handles a track which fails to render.
+///
+/// # Remarks
+///
+/// A track fails if it cannot be read, if the synthesizer for it cannot be created,
+/// or if the rendering of it panics.
+/// In either case, the failure is reported by `get_track_errors` and the diagnostics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TrackErrorPolicy {
+    /// The track is skipped, and the other tracks are rendered as usual.
+    Skip,
+
+    /// The rendering stops at the first failure.
+    /// The tracks being rendered are finished, but the remaining ones are not started.
+    FailFast,
+}
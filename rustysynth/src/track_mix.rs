@@ -0,0 +1,160 @@
+#![allow(dead_code)]
+
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+use crate::midifile::{Message, MidiTrack};
+use crate::synthesizer::Synthesizer;
+use crate::synthesizer_settings::SynthesizerSettings;
+
+/// Specifies how a track is changed before it is mixed by `ThreadedRender`.
+///
+/// # Remarks
+///
+/// The transposition and the program override are not applied to the percussion channels,
+/// which are given by the settings and the GS rhythm parts switched in the track.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct TrackMix {
+    /// The gain in decibels.
+    ///
+    /// Negative infinity mutes the track.
+    pub gain: f32,
+    /// The balance between the left (-1) and the right (1).
+    ///
+    /// The opposite side is attenuated, and the center (0) keeps the track as it is.
+    pub pan: f32,
+    /// The number of semitones by which the notes are transposed.
+    ///
+    /// The notes out of the MIDI range are dropped.
+    pub transpose: i32,
+    /// The program which replaces all the program changes in the track, between 0 and 127.
+    ///
+    /// The program is also selected at the start of the track.
+    pub program: Option<u8>,
+}
+
+impl TrackMix {
+    /// Initializes a new instance of the track mix, which does not change the track.
+    pub fn new() -> Self {
+        Self {
+            gain: 0_f32,
+            pan: 0_f32,
+            transpose: 0,
+            program: None,
+        }
+    }
+
+    pub(crate) fn check(&self) {
+        if !(self.gain.is_finite() || self.gain == f32::NEG_INFINITY) {
+            panic!("The gain must be a finite value or negative infinity.");
+        }
+
+        if !(-1_f32..=1_f32).contains(&self.pan) {
+            panic!("The pan must be between -1 and 1.");
+        }
+
+        if matches!(self.program, Some(program) if program > 127) {
+            panic!("The program must be between 0 and 127.");
+        }
+    }
+
+    pub(crate) fn apply_events(
+        &self,
+        track: &MidiTrack,
+        settings: &SynthesizerSettings,
+    ) -> MidiTrack {
+        if self.transpose == 0 && self.program.is_none() {
+            return track.clone();
+        }
+
+        let mut messages = Vec::with_capacity(track.messages.len());
+        let mut times = Vec::with_capacity(track.times.len());
+
+        if let Some(program) = self.program {
+            let mut percussion_channels = TrackMix::get_percussion_channels(settings);
+            let channels = track
+                .messages
+                .iter()
+                .filter(|x| TrackMix::is_melodic(x, &mut percussion_channels, settings))
+                .map(|x| x.channel)
+                .collect::<BTreeSet<u8>>();
+            for channel in channels {
+                let message = Message::common1(0xC0 | (channel % 16), program);
+                messages.push(message.with_port(channel / 16));
+                times.push(0.0);
+            }
+        }
+
+        let mut percussion_channels = TrackMix::get_percussion_channels(settings);
+        for (message, time) in track.messages.iter().zip(track.times.iter()) {
+            let mut message = *message;
+            if TrackMix::is_melodic(&message, &mut percussion_channels, settings) {
+                match message.command {
+                    0x80 | 0x90 | 0xA0 => {
+                        let key = message.data1 as i32 + self.transpose;
+                        if !(0..128).contains(&key) {
+                            continue;
+                        }
+                        message.data1 = key as u8;
+                    }
+                    0xC0 => {
+                        if let Some(program) = self.program {
+                            message.data1 = program;
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            messages.push(message);
+            times.push(*time);
+        }
+
+        MidiTrack {
+            messages,
+            times,
+            origins: Vec::new(),
+        }
+    }
+
+    pub(crate) fn apply_gain(&self, left: &mut [f32], right: &mut [f32]) {
+        let gain = 10_f32.powf(self.gain / 20_f32);
+        let left_gain = gain * (1_f32 - self.pan.max(0_f32));
+        let right_gain = gain * (1_f32 + self.pan.min(0_f32));
+        left.iter_mut().for_each(|x| *x *= left_gain);
+        right.iter_mut().for_each(|x| *x *= right_gain);
+    }
+
+    // The percussion channels are followed in the same way as the synthesizer.
+    fn get_percussion_channels(settings: &SynthesizerSettings) -> Vec<bool> {
+        (0..settings.channel_count)
+            .map(|i| settings.percussion_channels[i % Synthesizer::CHANNEL_COUNT])
+            .collect()
+    }
+
+    // The GS rhythm parts switched in the track are applied to the state as they come.
+    fn is_melodic(
+        message: &Message,
+        percussion_channels: &mut [bool],
+        settings: &SynthesizerSettings,
+    ) -> bool {
+        let channel_count = percussion_channels.len();
+        match message.get_message_type() {
+            Message::NORMAL => !percussion_channels[message.channel as usize % channel_count],
+            Message::PERCUSSION_CHANNEL => {
+                if settings.enable_gs_rhythm_parts {
+                    percussion_channels[message.command as usize % channel_count] =
+                        message.data1 != 0;
+                }
+                false
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Default for TrackMix {
+    fn default() -> Self {
+        TrackMix::new()
+    }
+}
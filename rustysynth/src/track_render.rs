@@ -0,0 +1,172 @@
+#![allow(dead_code)]
+
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp;
+
+use crate::midifile_sequencer::MidiFileSequencer;
+use crate::render_progress::RenderProgress;
+use crate::render_tail::RenderTail;
+use crate::trace::debug;
+use crate::trace::debug_span;
+
+// Renders a track of ThreadedRender, followed by the tail after the last event.
+// The rendering can be split into any number of parts, which join seamlessly.
+#[non_exhaustive]
+pub(crate) struct TrackRender {
+    pub(crate) sequencer: MidiFileSequencer,
+
+    index: usize,
+    event_length: usize,
+    max_length: usize,
+    silence_threshold: Option<f32>,
+    chunk_length: usize,
+
+    position: usize,
+    finished: bool,
+
+    progress: Arc<RenderProgress>,
+}
+
+impl TrackRender {
+    pub(crate) fn new(
+        index: usize,
+        sequencer: MidiFileSequencer,
+        length: f64,
+        tail: RenderTail,
+        progress: &Arc<RenderProgress>,
+    ) -> Self {
+        let sample_rate = sequencer.get_synthesizer().get_sample_rate();
+
+        let silence_threshold = match tail {
+            RenderTail::UntilSilence { threshold, .. } => {
+                Some(10_f64.powf(threshold / 20.0) as f32)
+            }
+            _ => None,
+        };
+
+        Self {
+            sequencer,
+            index,
+            event_length: (sample_rate as f64 * length) as usize,
+            max_length: TrackRender::get_max_length(sample_rate, length, tail),
+            silence_threshold,
+            chunk_length: cmp::max(sample_rate as usize / 10, 1),
+            position: 0,
+            finished: false,
+            progress: Arc::clone(progress),
+        }
+    }
+
+    // The length including the longest possible tail.
+    pub(crate) fn get_max_length(sample_rate: i32, length: f64, tail: RenderTail) -> usize {
+        let tail_length = match tail {
+            RenderTail::None => 0.0,
+            RenderTail::Fixed(tail_length) => tail_length,
+            RenderTail::UntilSilence { max_length, .. } => max_length,
+        };
+
+        (sample_rate as f64 * length) as usize + (sample_rate as f64 * tail_length) as usize
+    }
+
+    // Renders up to the specified number of samples.
+    // The result is shorter only if the track has finished.
+    pub(crate) fn render(&mut self, limit: usize) -> (Vec<f32>, Vec<f32>) {
+        let _span =
+            debug_span!("track_render", index = self.index, position = self.position).entered();
+
+        let mut left: Vec<f32> = Vec::new();
+        let mut right: Vec<f32> = Vec::new();
+
+        while !self.finished && left.len() < limit {
+            if self.sequencer.is_cancelled() {
+                self.finished = true;
+                break;
+            }
+
+            // The waveform is rendered chunk by chunk to report the progress.
+            // The events part is never mixed with the tail in a chunk.
+            let count = if self.position < self.event_length {
+                cmp::min(self.event_length - self.position, self.chunk_length)
+            } else {
+                self.chunk_length
+            };
+            let count = cmp::min(count, limit - left.len());
+            let count = cmp::min(count, self.max_length - self.position);
+            if count == 0 {
+                self.finished = true;
+                break;
+            }
+
+            let start = left.len();
+            left.resize(start + count, 0_f32);
+            right.resize(start + count, 0_f32);
+            self.sequencer
+                .render(&mut left[start..], &mut right[start..]);
+            self.position += count;
+            self.progress.add(count);
+
+            if self.position > self.event_length {
+                if let Some(threshold) = self.silence_threshold {
+                    if TrackRender::is_silent(&left[start..], &right[start..], threshold) {
+                        self.finished = true;
+                        self.progress.add(self.max_length - self.position);
+                    }
+                }
+            }
+        }
+
+        if self.finished {
+            // Only the tail can be trimmed, and the events part is kept as it is.
+            if let Some(threshold) = self.silence_threshold {
+                let tail_start =
+                    left.len() - cmp::min(self.position - self.event_length, left.len());
+                let length = TrackRender::get_audible_length(&left, &right, threshold);
+                let length = cmp::max(length, tail_start);
+                left.truncate(length);
+                right.truncate(length);
+            }
+        }
+
+        debug!(
+            samples = left.len(),
+            finished = self.finished,
+            "rendered the track"
+        );
+
+        (left, right)
+    }
+
+    // Renders and discards the samples before the first output sample,
+    // which are not counted in the length or the progress.
+    pub(crate) fn skip_lead(&mut self, sample_count: usize) {
+        let mut left = vec![0_f32; sample_count];
+        let mut right = vec![0_f32; sample_count];
+        self.sequencer.render(&mut left, &mut right);
+    }
+
+    pub(crate) fn get_event_length(&self) -> usize {
+        self.event_length
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub(crate) fn is_silent(left: &[f32], right: &[f32], threshold: f32) -> bool {
+        left.iter().chain(right.iter()).all(|x| x.abs() < threshold)
+    }
+
+    // The length without the trailing samples below the threshold.
+    pub(crate) fn get_audible_length(left: &[f32], right: &[f32], threshold: f32) -> usize {
+        let mut length = left.len();
+        while length > 0
+            && left[length - 1].abs() < threshold
+            && right[length - 1].abs() < threshold
+        {
+            length -= 1;
+        }
+        length
+    }
+}
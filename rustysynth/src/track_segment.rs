@@ -0,0 +1,17 @@
+#![allow(dead_code)]
+
+use crate::midifile::MidiTrack;
+
+// A part of a track of ThreadedRender, rendered by its own synthesizer.
+#[non_exhaustive]
+pub(crate) struct TrackSegment {
+    // The index of the track, or the channel in the channel split mode.
+    pub(crate) index: usize,
+    // The start of the segment in samples, which is a multiple of the block size.
+    pub(crate) offset: usize,
+    pub(crate) track: MidiTrack,
+    // The length of the events in seconds.
+    pub(crate) length: f64,
+    // Only the last segment of a track has the tail.
+    pub(crate) is_last: bool,
+}
@@ -0,0 +1,144 @@
+#![allow(dead_code)]
+
+// A channel voice message decoded from a Universal MIDI Packet.
+// The values of the MIDI 2.0 messages keep their full resolution,
+// and are scaled down to the MIDI 1.0 ones by the synthesizer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum UmpMessage {
+    Midi1 {
+        channel: u8,
+        command: u8,
+        data1: u8,
+        data2: u8,
+    },
+    NoteOff {
+        channel: u8,
+        key: u8,
+    },
+    NoteOn {
+        channel: u8,
+        key: u8,
+        velocity: u16,
+    },
+    ControlChange {
+        channel: u8,
+        index: u8,
+        value: u32,
+    },
+    RegisteredController {
+        channel: u8,
+        bank: u8,
+        index: u8,
+        value: u32,
+    },
+    ProgramChange {
+        channel: u8,
+        program: u8,
+        bank: Option<u8>,
+    },
+    ChannelPressure {
+        channel: u8,
+        value: u32,
+    },
+    PitchBend {
+        channel: u8,
+        value: u32,
+    },
+    PerNotePitchBend {
+        channel: u8,
+        key: u8,
+        value: u32,
+    },
+    PerNoteReset {
+        channel: u8,
+        key: u8,
+    },
+}
+
+impl UmpMessage {
+    pub(crate) const MIDI1_CHANNEL_VOICE: u32 = 0x2;
+    pub(crate) const MIDI2_CHANNEL_VOICE: u32 = 0x4;
+
+    // Gets the number of the 32-bit words in the packet, from the message type in the first word.
+    pub(crate) fn get_packet_size(word: u32) -> usize {
+        match word >> 28 {
+            0x0 | 0x1 | 0x2 | 0x6 | 0x7 => 1,
+            0x3 | 0x4 | 0x8 | 0x9 | 0xA => 2,
+            0xB | 0xC => 3,
+            _ => 4,
+        }
+    }
+
+    // Decodes a whole packet. The messages other than the channel voice messages are skipped.
+    pub(crate) fn decode(packet: &[u32]) -> Option<Self> {
+        let word = packet[0];
+        let opcode = ((word >> 20) & 0x0F) as u8;
+        let channel = ((word >> 16) & 0x0F) as u8;
+        let byte3 = ((word >> 8) & 0x7F) as u8;
+        let byte4 = (word & 0xFF) as u8;
+
+        match word >> 28 {
+            UmpMessage::MIDI1_CHANNEL_VOICE => Some(UmpMessage::Midi1 {
+                channel,
+                command: opcode << 4,
+                data1: byte3,
+                data2: byte4 & 0x7F,
+            }),
+            UmpMessage::MIDI2_CHANNEL_VOICE => {
+                let data = packet[1];
+                match opcode {
+                    0x2 => Some(UmpMessage::RegisteredController {
+                        channel,
+                        bank: byte3,
+                        index: byte4 & 0x7F,
+                        value: data,
+                    }),
+                    0x6 => Some(UmpMessage::PerNotePitchBend {
+                        channel,
+                        key: byte3,
+                        value: data,
+                    }),
+                    0x8 => Some(UmpMessage::NoteOff {
+                        channel,
+                        key: byte3,
+                    }),
+                    0x9 => Some(UmpMessage::NoteOn {
+                        channel,
+                        key: byte3,
+                        velocity: (data >> 16) as u16,
+                    }),
+                    0xB => Some(UmpMessage::ControlChange {
+                        channel,
+                        index: byte3,
+                        value: data,
+                    }),
+                    0xC => Some(UmpMessage::ProgramChange {
+                        channel,
+                        program: ((data >> 24) & 0x7F) as u8,
+                        // The bank is valid only if the flag is set.
+                        bank: if byte4 & 0x01 != 0 {
+                            Some(((data >> 8) & 0x7F) as u8)
+                        } else {
+                            None
+                        },
+                    }),
+                    0xD => Some(UmpMessage::ChannelPressure {
+                        channel,
+                        value: data,
+                    }),
+                    0xE => Some(UmpMessage::PitchBend {
+                        channel,
+                        value: data,
+                    }),
+                    // Only the reset of the per-note controllers matters, and the detach is not needed.
+                    0xF if byte4 & 0x01 != 0 => Some(UmpMessage::PerNoteReset {
+                        channel,
+                        key: byte3,
+                    }),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
@@ -1,18 +1,32 @@
 #![allow(dead_code)]
 
-use std::f32::consts;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts;
+use core::time::Duration;
 
 use crate::bi_quad_filter::BiQuadFilter;
 use crate::channel::Channel;
+use crate::controller_target::ControllerTarget;
+use crate::device_profile::DeviceProfile;
+use crate::envelope_override::EnvelopeOverride;
+use crate::filter_override::FilterOverride;
+use crate::filter_type::FilterType;
+use crate::float_math::FloatMath;
+use crate::ladder_filter::LadderFilter;
 use crate::lfo::Lfo;
 use crate::modulation_envelope::ModulationEnvelope;
 use crate::oscillator::Oscillator;
 use crate::region_ex::RegionEx;
 use crate::region_pair::RegionPair;
 use crate::soundfont_math::SoundFontMath;
+use crate::state_variable_filter::StateVariableFilter;
 use crate::synthesizer_settings::SynthesizerSettings;
+use crate::voice_info::VoiceInfo;
 use crate::volume_envelope::VolumeEnvelope;
+use crate::wave_data::WaveSlice;
 
+#[derive(Clone)]
 #[non_exhaustive]
 pub(crate) struct Voice {
     sample_rate: i32,
@@ -26,6 +40,9 @@ pub(crate) struct Voice {
 
     oscillator: Oscillator,
     filter: BiQuadFilter,
+    ladder_filter: LadderFilter,
+    state_variable_filter: StateVariableFilter,
+    filter_type: FilterType,
 
     pub(crate) block: Vec<f32>,
 
@@ -47,9 +64,27 @@ pub(crate) struct Voice {
     pub(crate) channel: i32,
     pub(crate) key: i32,
     pub(crate) velocity: i32,
+    // The pitch bend of the note alone in semitones, given by the MIDI 2.0 per-note pitch bend.
+    pub(crate) note_pitch_bend: f32,
+    // The detune of the note in semitones, given by `Synthesizer::note_on_detuned`.
+    pub(crate) detune: f32,
+
+    // The preset which started the voice, used for the profiling.
+    pub(crate) bank_number: i32,
+    pub(crate) patch_number: i32,
+    pub(crate) process_time: Duration,
+
+    // Whether the sample data of the previous SoundFont is used after the SoundFont was replaced.
+    pub(crate) from_previous_sound_font: bool,
+    // The sample data played by the voice. See `SoundFont::get_wave_slice`.
+    pub(crate) source: usize,
 
     note_gain: f32,
 
+    // The gain and its decrease per sample while the voice is choked by the exclusive class.
+    fade_gain: f32,
+    fade_step: f32,
+
     cutoff: f32,
     resonance: f32,
 
@@ -74,6 +109,10 @@ pub(crate) struct Voice {
 
     voice_state: i32,
     pub(crate) voice_length: usize,
+    // Whether the release began in the last processed block.
+    pub(crate) just_released: bool,
+    // Whether the voice is a release layer, which has no note to be stopped.
+    pub(crate) release_layer: bool,
     min_voice_length: usize,
 }
 
@@ -88,6 +127,9 @@ impl Voice {
             mod_lfo: Lfo::new(settings),
             oscillator: Oscillator::new(settings),
             filter: BiQuadFilter::new(settings),
+            ladder_filter: LadderFilter::new(settings),
+            state_variable_filter: StateVariableFilter::new(settings),
+            filter_type: FilterType::LowPass,
             block: vec![0_f32; settings.block_size],
             previous_mix_gain_left: 0_f32,
             previous_mix_gain_right: 0_f32,
@@ -101,7 +143,16 @@ impl Voice {
             channel: 0,
             key: 0,
             velocity: 0,
+            note_pitch_bend: 0_f32,
+            detune: 0_f32,
+            bank_number: 0,
+            patch_number: 0,
+            process_time: Duration::ZERO,
+            from_previous_sound_font: false,
+            source: 0,
             note_gain: 0_f32,
+            fade_gain: 1_f32,
+            fade_step: 0_f32,
             cutoff: 0_f32,
             resonance: 0_f32,
             vib_lfo_to_pitch: 0_f32,
@@ -118,28 +169,42 @@ impl Voice {
             smoothed_cutoff: 0_f32,
             voice_state: 0,
             voice_length: 0,
+            just_released: false,
+            release_layer: false,
             min_voice_length: (settings.sample_rate / 500) as usize,
         }
     }
 
-    pub(crate) fn start(&mut self, region: &RegionPair, channel: i32, key: i32, velocity: i32) {
+    pub(crate) fn start(
+        &mut self,
+        region: &RegionPair,
+        channel: i32,
+        key: i32,
+        velocity: i32,
+        profile: DeviceProfile,
+        sample_count: usize,
+    ) {
         self.exclusive_class = region.get_exclusive_class();
         self.channel = channel;
         self.key = key;
         self.velocity = velocity;
+        self.note_pitch_bend = 0_f32;
+        self.detune = 0_f32;
+        self.from_previous_sound_font = false;
 
         if velocity > 0 {
             // According to the Polyphone's implementation, the initial attenuation should be reduced to 40%.
             // I'm not sure why, but this indeed improves the loudness variability.
             let sample_attenuation = 0.4_f32 * region.get_initial_attenuation();
             let filter_attenuation = 0.5_f32 * region.get_initial_filter_q();
-            let decibels = 2_f32 * SoundFontMath::linear_to_decibels(velocity as f32 / 127_f32)
-                - sample_attenuation
-                - filter_attenuation;
+            let decibels =
+                profile.velocity_to_decibels(velocity) - sample_attenuation - filter_attenuation;
             self.note_gain = SoundFontMath::decibels_to_linear(decibels);
         } else {
             self.note_gain = 0_f32;
         }
+        self.fade_gain = 1_f32;
+        self.fade_step = 0_f32;
 
         self.cutoff = region.get_initial_filter_cutoff_frequency();
         self.resonance = SoundFontMath::decibels_to_linear(region.get_initial_filter_q());
@@ -163,7 +228,8 @@ impl Voice {
         RegionEx::start_modulation_envelope(&mut self.mod_env, region, key, velocity);
         RegionEx::start_vibrato(&mut self.vib_lfo, region, key, velocity);
         RegionEx::start_modulation(&mut self.mod_lfo, region, key, velocity);
-        RegionEx::start_oscillator(&mut self.oscillator, region);
+        RegionEx::start_oscillator(&mut self.oscillator, region, sample_count);
+        self.filter_type = FilterType::LowPass;
         self.filter.clear_buffer();
         self.filter.set_low_pass_filter(self.cutoff, self.resonance);
 
@@ -171,6 +237,109 @@ impl Voice {
 
         self.voice_state = VoiceState::PLAYING;
         self.voice_length = 0;
+        self.just_released = false;
+        self.release_layer = false;
+    }
+
+    // Marks the voice as a release layer, whose gain is in decibels.
+    // The layer has no note to be stopped, so the sample is played once and the voice ends with it.
+    pub(crate) fn start_release_layer(&mut self, gain: f32) {
+        self.note_gain *= SoundFontMath::decibels_to_linear(gain);
+        self.oscillator.play_once();
+        self.release_layer = true;
+    }
+
+    // Restarts the volume envelope scaled by the channel.
+    pub(crate) fn set_envelope_override(
+        &mut self,
+        region: &RegionPair,
+        key: i32,
+        velocity: i32,
+        scale: &EnvelopeOverride,
+    ) {
+        RegionEx::start_scaled_volume_envelope(&mut self.vol_env, region, key, velocity, scale);
+    }
+
+    // Replaces the filter with the one given by the channel.
+    pub(crate) fn set_filter_override(&mut self, value: &FilterOverride) {
+        if let Some(cutoff) = value.cutoff {
+            self.cutoff = cutoff;
+            self.smoothed_cutoff = cutoff;
+        }
+        if let Some(resonance) = value.resonance {
+            self.resonance = SoundFontMath::decibels_to_linear(resonance);
+        }
+
+        self.filter_type = value.filter_type;
+        self.ladder_filter.clear_buffer();
+        self.state_variable_filter.clear_buffer();
+        self.set_filter(self.smoothed_cutoff);
+    }
+
+    fn set_filter(&mut self, cutoff: f32) {
+        match self.filter_type {
+            FilterType::LowPass => self.filter.set_low_pass_filter(cutoff, self.resonance),
+            FilterType::LadderLowPass => self
+                .ladder_filter
+                .set_low_pass_filter(cutoff, self.resonance),
+            filter_type => {
+                self.state_variable_filter
+                    .set_filter(filter_type, cutoff, self.resonance)
+            }
+        }
+    }
+
+    pub(crate) fn get_info(&self) -> VoiceInfo {
+        VoiceInfo {
+            channel: self.channel,
+            key: self.key,
+            velocity: self.velocity,
+            volume_phase: self.vol_env.get_phase(),
+            volume_level: self.vol_env.get_value(),
+            modulation_phase: self.mod_env.get_phase(),
+            modulation_level: self.mod_env.get_value(),
+        }
+    }
+
+    // Restarts the vibrato LFO at the rate given by the channel instead of the region.
+    pub(crate) fn set_vibrato_rate(&mut self, region: &RegionPair, frequency: f32) {
+        self.vib_lfo
+            .start(region.get_delay_vibrato_lfo(), frequency);
+    }
+
+    // Restarts the vibrato LFO at the frequency given by the channel if any, and scales its depth.
+    pub(crate) fn set_vibrato_lfo_override(
+        &mut self,
+        region: &RegionPair,
+        frequency: Option<f32>,
+        depth: f32,
+    ) {
+        if let Some(frequency) = frequency {
+            self.vib_lfo
+                .start(region.get_delay_vibrato_lfo(), frequency);
+        }
+
+        self.vib_lfo_to_pitch *= depth;
+    }
+
+    // Restarts the modulation LFO at the frequency given by the channel if any, and scales its depth.
+    pub(crate) fn set_modulation_lfo_override(
+        &mut self,
+        region: &RegionPair,
+        frequency: Option<f32>,
+        depth: f32,
+    ) {
+        if let Some(frequency) = frequency {
+            self.mod_lfo
+                .start(region.get_delay_modulation_lfo(), frequency);
+        }
+
+        self.mod_lfo_to_pitch *= depth;
+        self.mod_lfo_to_cutoff = (depth * self.mod_lfo_to_cutoff as f32) as i32;
+        self.dynamic_cutoff = self.mod_lfo_to_cutoff != 0 || self.mod_env_to_cutoff != 0;
+
+        self.mod_lfo_to_volume *= depth;
+        self.dynamic_volume = self.mod_lfo_to_volume > 0.05_f32;
     }
 
     pub(crate) fn end(&mut self) {
@@ -183,8 +352,31 @@ impl Voice {
         self.note_gain = 0_f32;
     }
 
-    pub(crate) fn process(&mut self, data: &[i16], channels: &[Channel]) -> bool {
-        if self.note_gain < SoundFontMath::NON_AUDIBLE {
+    // Fades out the voice in the specified number of samples, or kills it if the length is zero.
+    pub(crate) fn choke(&mut self, length: usize) {
+        if length == 0 {
+            self.kill();
+            return;
+        }
+
+        self.fade_step = self.fade_step.max(1_f32 / length as f32);
+    }
+
+    pub(crate) fn is_choked(&self) -> bool {
+        self.fade_step > 0_f32
+    }
+
+    pub(crate) fn set_block_size(&mut self, block_size: usize) {
+        self.block_size = block_size;
+        self.block.resize(block_size, 0_f32);
+        self.vib_lfo.set_block_size(block_size);
+        self.mod_lfo.set_block_size(block_size);
+    }
+
+    pub(crate) fn process(&mut self, data: WaveSlice, channels: &[Channel]) -> bool {
+        self.just_released = false;
+
+        if self.note_gain < SoundFontMath::NON_AUDIBLE || self.fade_gain <= 0_f32 {
             return false;
         }
 
@@ -200,19 +392,27 @@ impl Voice {
         self.vib_lfo.process();
         self.mod_lfo.process();
 
-        let vib_pitch_change = (0.01_f32 * channel_info.get_modulation() + self.vib_lfo_to_pitch)
-            * self.vib_lfo.get_value();
+        let vib_depth = 0.01_f32 * channel_info.get_controller_amount(ControllerTarget::Vibrato);
+        let vib_pitch_change = (vib_depth + self.vib_lfo_to_pitch) * self.vib_lfo.get_value();
         let mod_pitch_change = self.mod_lfo_to_pitch * self.mod_lfo.get_value()
             + self.mod_env_to_pitch * self.mod_env.get_value();
-        let channel_pitch_change = channel_info.get_tune() + channel_info.get_pitch_bend();
+        let channel_pitch_change = channel_info.get_tune()
+            + channel_info.get_pitch_bend()
+            + self.note_pitch_bend
+            + self.detune;
         let pitch = self.key as f32 + vib_pitch_change + mod_pitch_change + channel_pitch_change;
         if !self.oscillator.process(data, &mut self.block[..], pitch) {
             return false;
         }
 
-        if self.dynamic_cutoff {
-            let cents = self.mod_lfo_to_cutoff as f32 * self.mod_lfo.get_value()
+        // The cutoff is updated while the controller is routed to it, so that it returns when released.
+        let cutoff_modulation = channel_info.is_controller_target(ControllerTarget::FilterCutoff);
+        if self.dynamic_cutoff || cutoff_modulation {
+            let mut cents = self.mod_lfo_to_cutoff as f32 * self.mod_lfo.get_value()
                 + self.mod_env_to_cutoff as f32 * self.mod_env.get_value();
+            if cutoff_modulation {
+                cents += channel_info.get_controller_amount(ControllerTarget::FilterCutoff);
+            }
             let factor = SoundFontMath::cents_to_multiplying_factor(cents);
             let new_cutoff = factor * self.cutoff;
 
@@ -221,25 +421,40 @@ impl Voice {
             let upper_limit = 2_f32 * self.smoothed_cutoff;
             self.smoothed_cutoff = SoundFontMath::clamp(new_cutoff, lower_limit, upper_limit);
 
-            self.filter
-                .set_low_pass_filter(self.smoothed_cutoff, self.resonance);
+            self.set_filter(self.smoothed_cutoff);
+        }
+        match self.filter_type {
+            FilterType::LowPass => self.filter.process(&mut self.block[..]),
+            FilterType::LadderLowPass => self.ladder_filter.process(&mut self.block[..]),
+            _ => self.state_variable_filter.process(&mut self.block[..]),
         }
-        self.filter.process(&mut self.block[..]);
 
         self.previous_mix_gain_left = self.current_mix_gain_left;
         self.previous_mix_gain_right = self.current_mix_gain_right;
         self.previous_reverb_send = self.current_reverb_send;
         self.previous_chorus_send = self.current_chorus_send;
 
-        // According to the GM spec, the following value should be squared.
-        let ve = channel_info.get_volume() * channel_info.get_expression();
-        let channel_gain = ve * ve;
+        let channel_gain = channel_info.get_gain();
 
         let mut mix_gain = self.note_gain * channel_gain * self.vol_env.get_value();
         if self.dynamic_volume {
             let decibels = self.mod_lfo_to_volume * self.mod_lfo.get_value();
             mix_gain *= SoundFontMath::decibels_to_linear(decibels);
         }
+        let tremolo = channel_info.get_controller_amount(ControllerTarget::Tremolo);
+        if tremolo != 0_f32 {
+            let decibels = tremolo * self.vib_lfo.get_value();
+            mix_gain *= SoundFontMath::decibels_to_linear(decibels);
+        }
+        let volume = channel_info.get_controller_amount(ControllerTarget::Volume);
+        if volume != 0_f32 {
+            mix_gain *= SoundFontMath::decibels_to_linear(volume);
+        }
+        if self.fade_step > 0_f32 {
+            // The gain reaches zero at the end of the last block, and the voice ends in the next one.
+            self.fade_gain = (self.fade_gain - self.fade_step * self.block_size as f32).max(0_f32);
+            mix_gain *= self.fade_gain;
+        }
 
         let angle =
             (consts::PI / 200_f32) * (channel_info.get_pan() + self.instrument_pan + 50_f32);
@@ -250,8 +465,8 @@ impl Voice {
             self.current_mix_gain_left = 0_f32;
             self.current_mix_gain_right = mix_gain;
         } else {
-            self.current_mix_gain_left = mix_gain * angle.cos();
-            self.current_mix_gain_right = mix_gain * angle.sin();
+            self.current_mix_gain_left = mix_gain * FloatMath::cos(angle);
+            self.current_mix_gain_right = mix_gain * FloatMath::sin(angle);
         }
 
         self.current_reverb_send = SoundFontMath::clamp(
@@ -288,11 +503,20 @@ impl Voice {
             self.oscillator.release();
 
             self.voice_state = VoiceState::RELEASED;
+            self.just_released = true;
+        }
+
+        // With a continuous pedal, the released notes decay slower while the pedal is partly down,
+        // and are held again when it is fully down.
+        if self.voice_state == VoiceState::RELEASED && channel_info.pedal_curve.is_continuous() {
+            self.vol_env
+                .set_release_damping(1_f32 - channel_info.get_sustain_amount());
         }
     }
 
     pub(crate) fn get_priority(&self) -> f32 {
-        if self.note_gain < SoundFontMath::NON_AUDIBLE {
+        // The choked voices are reused first, since they are fading out anyway.
+        if self.note_gain < SoundFontMath::NON_AUDIBLE || self.is_choked() {
             0_f32
         } else {
             self.vol_env.get_priority()
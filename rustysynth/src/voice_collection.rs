@@ -1,17 +1,49 @@
 #![allow(dead_code)]
 
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem;
+
 use crate::channel::Channel;
+use crate::exclusive_class_mode::ExclusiveClassMode;
 use crate::instrument_region::InstrumentRegion;
+use crate::parallel::*;
+use crate::preset_profile::PresetProfile;
+use crate::soundfont::SoundFont;
+use crate::stopwatch::Stopwatch;
 use crate::synthesizer_settings::SynthesizerSettings;
 use crate::voice::Voice;
+use crate::voice_event::VoiceEventKind;
+use crate::voice_events::VoiceEvents;
+use crate::voice_stealing_policy::VoiceStealingPolicy;
+use crate::wave_data::WaveSlice;
 
 #[non_exhaustive]
 pub(crate) struct VoiceCollection {
     voices: Vec<Voice>,
     pub(crate) active_voice_count: usize,
+    pub(crate) voice_stealing: VoiceStealingPolicy,
+    pub(crate) exclusive_class: ExclusiveClassMode,
+    pub(crate) parallel: bool,
+    sample_rate: i32,
+
+    // Used to remove the finished voices after the parallel processing.
+    playing: Vec<bool>,
+
+    pub(crate) events: VoiceEvents,
+
+    // The voices whose release began in the last block, including the ones which finished in it.
+    // The capacity is the polyphony, so that no allocation happens while processing.
+    pub(crate) released_voices: Vec<ReleasedVoice>,
+
+    pub(crate) stolen_voice_count: u64,
+    pub(crate) profile: Option<PresetProfile>,
 }
 
 impl VoiceCollection {
+    // Below this, the overhead of the parallel processing exceeds the gain.
+    pub(crate) const PARALLEL_THRESHOLD: usize = 16;
+
     pub(crate) fn new(settings: &SynthesizerSettings) -> Self {
         let mut voices: Vec<Voice> = Vec::new();
         for _i in 0..settings.maximum_polyphony {
@@ -21,6 +53,15 @@ impl VoiceCollection {
         Self {
             voices,
             active_voice_count: 0,
+            voice_stealing: settings.voice_stealing,
+            exclusive_class: settings.exclusive_class,
+            parallel: settings.parallel_voices,
+            sample_rate: settings.sample_rate,
+            playing: vec![false; settings.maximum_polyphony],
+            events: VoiceEvents::new(),
+            released_voices: Vec::with_capacity(settings.maximum_polyphony),
+            stolen_voice_count: 0,
+            profile: None,
         }
     }
 
@@ -28,15 +69,26 @@ impl VoiceCollection {
         &mut self,
         region: &InstrumentRegion,
         channel: i32,
+        channels: &[Channel],
     ) -> Option<&mut Voice> {
         // If an exclusive class is assigned to the region, find a voice with the same class.
-        // If found, reuse it to avoid playing multiple voices with the same class at a time.
+        // If found, reuse it to avoid playing multiple voices with the same class at a time,
+        // or fade it out while the new note starts in another voice.
         let exclusive_class = region.get_exclusive_class();
         if exclusive_class != 0 {
+            let fade_length = self.get_fade_length();
             for i in 0..self.active_voice_count {
-                let voice = &self.voices[i];
-                if voice.exclusive_class == exclusive_class && voice.channel == channel {
-                    return Some(&mut self.voices[i]);
+                let voice = &mut self.voices[i];
+                if voice.exclusive_class == exclusive_class
+                    && voice.channel == channel
+                    && !voice.is_choked()
+                {
+                    self.events
+                        .push_voice(VoiceEventKind::ExclusiveClassKilled, voice);
+                    if fade_length == 0 {
+                        return Some(&mut self.voices[i]);
+                    }
+                    voice.choke(fade_length);
                 }
             }
         }
@@ -49,27 +101,91 @@ impl VoiceCollection {
         }
 
         // Too many active voices...
-        // Find one which has the lowest priority.
-        let mut candidate: usize = 0;
+        // Find one to be reused according to the policy.
+        let candidate = match self.voice_stealing {
+            VoiceStealingPolicy::Oldest => self.find_oldest(|_| true),
+            VoiceStealingPolicy::Quietest => self.find_quietest(|_| true),
+            VoiceStealingPolicy::LowestChannelPriority => {
+                let lowest_priority = self.voices[0..self.active_voice_count]
+                    .iter()
+                    .map(|voice| channels[voice.channel as usize].priority)
+                    .min()?;
+                // The new note is less important than any playing one.
+                if channels[channel as usize].priority < lowest_priority {
+                    return None;
+                }
+                self.find_quietest(|voice| {
+                    channels[voice.channel as usize].priority == lowest_priority
+                })
+            }
+            VoiceStealingPolicy::None => return None,
+        };
+        let candidate = candidate?;
+        self.stolen_voice_count += 1;
+        self.events
+            .push_voice(VoiceEventKind::Stolen, &self.voices[candidate]);
+        Some(&mut self.voices[candidate])
+    }
+
+    // The fade length in samples for the voices stopped by the exclusive class.
+    pub(crate) fn get_fade_length(&self) -> usize {
+        self.exclusive_class.get_fade_length(self.sample_rate)
+    }
+
+    fn find_oldest(&self, filter: impl Fn(&Voice) -> bool) -> Option<usize> {
+        let mut candidate: Option<usize> = None;
+        for i in 0..self.active_voice_count {
+            let voice = &self.voices[i];
+            if !filter(voice) {
+                continue;
+            }
+            match candidate {
+                Some(c) if voice.voice_length <= self.voices[c].voice_length => (),
+                _ => candidate = Some(i),
+            }
+        }
+        candidate
+    }
+
+    fn find_quietest(&self, filter: impl Fn(&Voice) -> bool) -> Option<usize> {
+        let mut candidate: Option<usize> = None;
         let mut lowest_priority = f32::MAX;
         for i in 0..self.active_voice_count {
             let voice = &self.voices[i];
+            if !filter(voice) {
+                continue;
+            }
             let priority = voice.get_priority();
             if priority < lowest_priority {
                 lowest_priority = priority;
-                candidate = i;
+                candidate = Some(i);
             } else if priority == lowest_priority {
                 // Same priority...
                 // The older one should be more suitable for reuse.
-                if voice.voice_length > self.voices[candidate].voice_length {
-                    candidate = i;
+                if let Some(c) = candidate {
+                    if voice.voice_length > self.voices[c].voice_length {
+                        candidate = Some(i);
+                    }
                 }
             }
         }
-        Some(&mut self.voices[candidate])
+        candidate
     }
 
-    pub(crate) fn process(&mut self, data: &[i16], channels: &[Channel]) {
+    pub(crate) fn process(
+        &mut self,
+        sound_font: &SoundFont,
+        previous_sound_font: Option<&SoundFont>,
+        channels: &[Channel],
+    ) {
+        self.released_voices.clear();
+
+        if self.parallel && self.active_voice_count >= VoiceCollection::PARALLEL_THRESHOLD {
+            self.process_parallel(sound_font, previous_sound_font, channels);
+            return;
+        }
+
+        let profiling = self.profile.is_some();
         let mut i: usize = 0;
 
         loop {
@@ -77,20 +193,216 @@ impl VoiceCollection {
                 return;
             }
 
-            if self.voices[i].process(data, channels) {
+            let voice = &mut self.voices[i];
+            let data = VoiceCollection::get_data(voice, sound_font, previous_sound_font);
+
+            let playing = VoiceCollection::process_voice(voice, data, channels, profiling);
+            if let Some(profile) = self.profile.as_mut() {
+                profile.add(voice);
+            }
+            if voice.just_released && !voice.release_layer {
+                self.released_voices.push(ReleasedVoice::new(voice));
+            }
+
+            if playing {
+                if voice.just_released {
+                    self.events.push_voice(VoiceEventKind::Released, voice);
+                }
+                i += 1;
+            } else {
+                self.events.push_voice(VoiceEventKind::Finished, voice);
+                self.active_voice_count -= 1;
+                self.voices.swap(i, self.active_voice_count);
+            }
+        }
+    }
+
+    fn process_parallel(
+        &mut self,
+        sound_font: &SoundFont,
+        previous_sound_font: Option<&SoundFont>,
+        channels: &[Channel],
+    ) {
+        let profiling = self.profile.is_some();
+        let count = self.active_voice_count;
+        self.voices[0..count]
+            .par_iter_mut()
+            .zip(self.playing[0..count].par_iter_mut())
+            .for_each(|(voice, playing)| {
+                let data = VoiceCollection::get_data(voice, sound_font, previous_sound_font);
+                *playing = VoiceCollection::process_voice(voice, data, channels, profiling);
+            });
+
+        if let Some(profile) = self.profile.as_mut() {
+            for voice in self.voices[0..count].iter() {
+                profile.add(voice);
+            }
+        }
+
+        // Remove the finished voices in the same way as the sequential processing,
+        // so that the order of the voices does not depend on the setting.
+        let mut i: usize = 0;
+        while i < self.active_voice_count {
+            if self.voices[i].just_released && !self.voices[i].release_layer {
+                self.released_voices
+                    .push(ReleasedVoice::new(&self.voices[i]));
+            }
+            if self.playing[i] {
+                if self.voices[i].just_released {
+                    self.events
+                        .push_voice(VoiceEventKind::Released, &self.voices[i]);
+                }
                 i += 1;
             } else {
+                self.events
+                    .push_voice(VoiceEventKind::Finished, &self.voices[i]);
                 self.active_voice_count -= 1;
                 self.voices.swap(i, self.active_voice_count);
+                self.playing.swap(i, self.active_voice_count);
             }
         }
     }
 
+    // Gets the sample data played by the voice.
+    fn get_data<'a>(
+        voice: &Voice,
+        sound_font: &'a SoundFont,
+        previous_sound_font: Option<&'a SoundFont>,
+    ) -> WaveSlice<'a> {
+        let sound_font = if voice.from_previous_sound_font {
+            previous_sound_font
+        } else {
+            Some(sound_font)
+        };
+
+        match sound_font {
+            Some(value) => value.get_wave_slice(voice.source),
+            None => WaveSlice::EMPTY,
+        }
+    }
+
+    fn process_voice(
+        voice: &mut Voice,
+        data: WaveSlice,
+        channels: &[Channel],
+        profiling: bool,
+    ) -> bool {
+        if !profiling {
+            return voice.process(data, channels);
+        }
+
+        let stopwatch = Stopwatch::start();
+        let playing = voice.process(data, channels);
+        voice.process_time = stopwatch.elapsed();
+        playing
+    }
+
+    pub(crate) fn get_memory_size(&self) -> usize {
+        let voices: usize = self
+            .voices
+            .iter()
+            .map(|x| mem::size_of::<Voice>() + x.block.len() * mem::size_of::<f32>())
+            .sum();
+        voices + self.playing.len()
+    }
+
     pub(crate) fn get_active_voices(&mut self) -> &mut [Voice] {
         &mut self.voices[0..self.active_voice_count]
     }
 
+    // The voices playing at the time the SoundFont is replaced keep using the previous one.
+    pub(crate) fn mark_previous_sound_font_voices(&mut self) {
+        for voice in self.get_active_voices().iter_mut() {
+            voice.from_previous_sound_font = true;
+        }
+    }
+
+    pub(crate) fn kill_previous_sound_font_voices(&mut self) {
+        for voice in self.get_active_voices().iter_mut() {
+            if voice.from_previous_sound_font {
+                voice.kill();
+            }
+        }
+    }
+
+    pub(crate) fn has_previous_sound_font_voices(&self) -> bool {
+        self.voices[0..self.active_voice_count]
+            .iter()
+            .any(|x| x.from_previous_sound_font)
+    }
+
+    // The voices exceeding the new limit are stopped immediately.
+    pub(crate) fn set_maximum_polyphony(&mut self, settings: &SynthesizerSettings) {
+        for voice in self.voices[0..self.active_voice_count]
+            .iter()
+            .skip(settings.maximum_polyphony)
+        {
+            self.events.push_voice(VoiceEventKind::Finished, voice);
+        }
+        self.voices
+            .resize_with(settings.maximum_polyphony, || Voice::new(settings));
+        self.playing.resize(settings.maximum_polyphony, false);
+        self.released_voices.clear();
+        self.released_voices.reserve(settings.maximum_polyphony);
+        self.active_voice_count = self.active_voice_count.min(self.voices.len());
+    }
+
+    pub(crate) fn set_block_size(&mut self, block_size: usize) {
+        for voice in self.voices.iter_mut() {
+            voice.set_block_size(block_size);
+        }
+    }
+
+    pub(crate) fn get_snapshot(&self) -> Vec<Voice> {
+        self.voices[0..self.active_voice_count].to_vec()
+    }
+
+    pub(crate) fn get_playing_voices(&self) -> &[Voice] {
+        &self.voices[0..self.active_voice_count]
+    }
+
+    // The voices exceeding the current limit are not restored.
+    pub(crate) fn restore(&mut self, voices: &[Voice]) {
+        self.clear();
+        let count = voices.len().min(self.voices.len());
+        self.voices[0..count].clone_from_slice(&voices[0..count]);
+        self.active_voice_count = count;
+        for voice in self.voices[0..count].iter() {
+            self.events.push_voice(VoiceEventKind::Started, voice);
+        }
+    }
+
     pub(crate) fn clear(&mut self) {
+        for voice in self.voices[0..self.active_voice_count].iter() {
+            self.events.push_voice(VoiceEventKind::Finished, voice);
+        }
         self.active_voice_count = 0;
     }
 }
+
+// The note of a voice whose release began, which is kept after the voice has finished.
+#[derive(Clone, Copy)]
+#[non_exhaustive]
+pub(crate) struct ReleasedVoice {
+    pub(crate) channel: i32,
+    pub(crate) key: i32,
+    pub(crate) velocity: i32,
+    pub(crate) detune: f32,
+    pub(crate) bank_number: i32,
+    pub(crate) patch_number: i32,
+    pub(crate) voice_length: usize,
+}
+
+impl ReleasedVoice {
+    fn new(voice: &Voice) -> Self {
+        Self {
+            channel: voice.channel,
+            key: voice.key,
+            velocity: voice.velocity,
+            detune: voice.detune,
+            bank_number: voice.bank_number,
+            patch_number: voice.patch_number,
+            voice_length: voice.voice_length,
+        }
+    }
+}
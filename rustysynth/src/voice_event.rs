@@ -0,0 +1,59 @@
+#![allow(dead_code)]
+
+/// Specifies the kind of a voice event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VoiceEventKind {
+    /// A voice started playing a note.
+    Started,
+    /// A voice entered the release phase, after the note off and the hold pedal.
+    Released,
+    /// A voice finished and became free.
+    Finished,
+    /// A voice was stopped to play a new note with the same exclusive class.
+    ExclusiveClassKilled,
+    /// A voice was stopped to play a new note because the maximum polyphony was exceeded.
+    Stolen,
+}
+
+/// Represents a change in a voice of the synthesizer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct VoiceEvent {
+    pub(crate) kind: VoiceEventKind,
+    pub(crate) channel: i32,
+    pub(crate) key: i32,
+    pub(crate) velocity: i32,
+    pub(crate) position: u64,
+}
+
+impl VoiceEvent {
+    /// Gets the kind of the event.
+    pub fn get_kind(&self) -> VoiceEventKind {
+        self.kind
+    }
+
+    /// Gets the channel of the note played by the voice.
+    pub fn get_channel(&self) -> i32 {
+        self.channel
+    }
+
+    /// Gets the key of the note played by the voice.
+    pub fn get_key(&self) -> i32 {
+        self.key
+    }
+
+    /// Gets the velocity of the note played by the voice.
+    pub fn get_velocity(&self) -> i32 {
+        self.velocity
+    }
+
+    /// Gets the position of the block in which the event took effect.
+    ///
+    /// # Remarks
+    ///
+    /// The position is the number of samples rendered by the synthesizer before the block.
+    pub fn get_position(&self) -> u64 {
+        self.position
+    }
+}
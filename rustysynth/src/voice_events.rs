@@ -0,0 +1,70 @@
+#![allow(dead_code)]
+
+use alloc::collections::VecDeque;
+
+use crate::voice::Voice;
+use crate::voice_event::VoiceEvent;
+use crate::voice_event::VoiceEventKind;
+
+// The events are kept in a bounded queue, so that nothing is allocated during rendering.
+// If the queue is full, the oldest event is discarded.
+#[non_exhaustive]
+pub(crate) struct VoiceEvents {
+    items: VecDeque<VoiceEvent>,
+    capacity: usize,
+
+    // The number of samples rendered before the current block.
+    position: u64,
+}
+
+impl VoiceEvents {
+    pub(crate) fn new() -> Self {
+        Self {
+            items: VecDeque::new(),
+            capacity: 0,
+            position: 0,
+        }
+    }
+
+    pub(crate) fn push(&mut self, kind: VoiceEventKind, channel: i32, key: i32, velocity: i32) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.items.len() == self.capacity {
+            self.items.pop_front();
+        }
+
+        self.items.push_back(VoiceEvent {
+            kind,
+            channel,
+            key,
+            velocity,
+            position: self.position,
+        });
+    }
+
+    pub(crate) fn push_voice(&mut self, kind: VoiceEventKind, voice: &Voice) {
+        self.push(kind, voice.channel, voice.key, voice.velocity);
+    }
+
+    pub(crate) fn advance(&mut self, sample_count: usize) {
+        self.position += sample_count as u64;
+    }
+
+    pub(crate) fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub(crate) fn set_capacity(&mut self, value: usize) {
+        self.capacity = value;
+        while self.items.len() > value {
+            self.items.pop_front();
+        }
+        self.items.reserve(value - self.items.len());
+    }
+
+    pub(crate) fn drain(&mut self) -> impl Iterator<Item = VoiceEvent> + '_ {
+        self.items.drain(..)
+    }
+}
@@ -0,0 +1,53 @@
+#![allow(dead_code)]
+
+use crate::envelope_phase::EnvelopePhase;
+
+/// Represents the state of a voice playing in the synthesizer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct VoiceInfo {
+    pub(crate) channel: i32,
+    pub(crate) key: i32,
+    pub(crate) velocity: i32,
+    pub(crate) volume_phase: EnvelopePhase,
+    pub(crate) volume_level: f32,
+    pub(crate) modulation_phase: EnvelopePhase,
+    pub(crate) modulation_level: f32,
+}
+
+impl VoiceInfo {
+    /// Gets the channel of the note played by the voice.
+    pub fn get_channel(&self) -> i32 {
+        self.channel
+    }
+
+    /// Gets the key of the note played by the voice.
+    pub fn get_key(&self) -> i32 {
+        self.key
+    }
+
+    /// Gets the velocity of the note played by the voice.
+    pub fn get_velocity(&self) -> i32 {
+        self.velocity
+    }
+
+    /// Gets the phase of the volume envelope.
+    pub fn get_volume_phase(&self) -> EnvelopePhase {
+        self.volume_phase
+    }
+
+    /// Gets the level of the volume envelope, between 0 and 1.
+    pub fn get_volume_level(&self) -> f32 {
+        self.volume_level
+    }
+
+    /// Gets the phase of the modulation envelope.
+    pub fn get_modulation_phase(&self) -> EnvelopePhase {
+        self.modulation_phase
+    }
+
+    /// Gets the level of the modulation envelope, between 0 and 1.
+    pub fn get_modulation_level(&self) -> f32 {
+        self.modulation_level
+    }
+}
@@ -0,0 +1,59 @@
+#![allow(dead_code)]
+
+use alloc::boxed::Box;
+
+/// Generates the sound of the notes in place of the SoundFont.
+///
+/// # Remarks
+///
+/// A source is registered to the synthesizer for a bank and a patch number with `Synthesizer::set_voice_source`,
+/// for a whole channel with `Synthesizer::set_channel_voice_source`,
+/// or as the fallback for the presets missing from the SoundFont with `Synthesizer::set_fallback_voice_source`.
+/// `BasicVoiceSource` provides the simple built-in waveforms.
+pub trait VoiceSource: Send + Sync {
+    /// Starts a new note, and returns the generator of its sound.
+    ///
+    /// # Arguments
+    ///
+    /// * `bank_number` - The bank number selected on the channel, which is 128 or more for the percussion.
+    /// * `patch_number` - The patch number selected on the channel.
+    /// * `key` - The key of the note.
+    /// * `velocity` - The velocity of the note, between 1 and 127.
+    /// * `sample_rate` - The sample rate of the synthesizer.
+    fn start(
+        &self,
+        bank_number: i32,
+        patch_number: i32,
+        key: i32,
+        velocity: i32,
+        sample_rate: i32,
+    ) -> Box<dyn VoiceGenerator>;
+}
+
+/// Generates the sound of a note started by a `VoiceSource`.
+///
+/// # Remarks
+///
+/// The synthesizer applies the volume, the expression, the pan and the effect sends of the channel to the sound,
+/// so the generator only needs to shape the waveform and the envelope of the note.
+pub trait VoiceGenerator: Send {
+    /// Renders the next block of the note.
+    ///
+    /// # Arguments
+    ///
+    /// * `frequency` - The frequency of the note in hertz, including the pitch bend and the tuning of the channel.
+    /// * `block` - The buffer to store the rendered waveform, which is filled with zeros.
+    ///
+    /// # Remarks
+    ///
+    /// Returns `false` if the note has finished, and then the generator is dropped.
+    fn process(&mut self, frequency: f32, block: &mut [f32]) -> bool;
+
+    /// Releases the note.
+    ///
+    /// # Remarks
+    ///
+    /// This is called once when the note is turned off and the hold pedal is not pressed.
+    /// The generator should finish after its release sound.
+    fn release(&mut self);
+}
@@ -0,0 +1,23 @@
+/// Specifies how to choose a voice to be reused when the maximum polyphony is exceeded.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum VoiceStealingPolicy {
+    /// Reuses the oldest voice.
+    Oldest,
+
+    /// Reuses the quietest voice, judged by the volume envelope.
+    /// The voices being released are reused before the ones still held.
+    /// If more than one voice is the quietest, the oldest one among them is reused.
+    /// This is the default policy.
+    Quietest,
+
+    /// Reuses a voice on the channel with the lowest priority.
+    /// Among the voices on that channel, the quietest one is reused.
+    /// If the channel of the new note has a lower priority than any playing voice,
+    /// the new note is dropped.
+    LowestChannelPriority,
+
+    /// Never reuses a playing voice.
+    /// The new note is dropped instead.
+    None,
+}
@@ -0,0 +1,62 @@
+#![allow(dead_code)]
+
+use alloc::boxed::Box;
+
+use crate::soundfont_math::SoundFontMath;
+
+/// Specifies how the value of the channel volume (CC #7) or the expression (CC #11)
+/// is converted to the gain.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum VolumeCurve {
+    /// The gain is the square of the value, that is, `40 * log10(value / 127)` in decibels.
+    /// This is the curve recommended by the GM spec, and the default.
+    Square,
+    /// The gain is proportional to the value.
+    Linear,
+    /// The gain changes linearly in decibels, from the given range below the full scale at the value of 1,
+    /// to the full scale at 127. The value of 0 is silent.
+    /// This concave curve is used by some hardware modules.
+    Decibels(f32),
+    /// The gains for each 7-bit value, where the 14-bit values are interpolated between them.
+    /// The gain at 127 is usually 1.
+    Custom(Box<[f32; 128]>),
+}
+
+impl VolumeCurve {
+    // The value is the 14-bit value of the controller.
+    pub(crate) fn get_gain(&self, value: i16) -> f32 {
+        // The coarse value of 127 is the full scale regardless of the fine value.
+        let position = (value as f32 / 128_f32).min(127_f32);
+        match self {
+            VolumeCurve::Square => {
+                let x = (1_f32 / 16383_f32) * value as f32;
+                x * x
+            }
+            VolumeCurve::Linear => (1_f32 / 16383_f32) * value as f32,
+            VolumeCurve::Decibels(range) => {
+                if position <= 0_f32 {
+                    return 0_f32;
+                }
+                let x = (position - 1_f32).max(0_f32) / 126_f32;
+                SoundFontMath::decibels_to_linear(-range * (1_f32 - x))
+            }
+            VolumeCurve::Custom(table) => {
+                let index = position as usize;
+                if index >= 127 {
+                    return table[127];
+                }
+                let t = position - index as f32;
+                table[index] + t * (table[index + 1] - table[index])
+            }
+        }
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        match self {
+            VolumeCurve::Square | VolumeCurve::Linear => true,
+            VolumeCurve::Decibels(range) => range.is_finite() && *range > 0_f32,
+            VolumeCurve::Custom(table) => table.iter().all(|x| x.is_finite() && *x >= 0_f32),
+        }
+    }
+}
@@ -1,9 +1,11 @@
 #![allow(dead_code)]
 
+use crate::envelope_phase::EnvelopePhase;
 use crate::envelope_stage::EnvelopeStage;
 use crate::soundfont_math::SoundFontMath;
 use crate::synthesizer_settings::SynthesizerSettings;
 
+#[derive(Clone)]
 #[non_exhaustive]
 pub(crate) struct VolumeEnvelope {
     sample_rate: i32,
@@ -19,6 +21,8 @@ pub(crate) struct VolumeEnvelope {
 
     sustain_level: f32,
     release_level: f32,
+    // The rate of the release relative to the one of the region, which is lowered by the half pedal.
+    release_damping: f32,
 
     processed_sample_count: usize,
     stage: i32,
@@ -40,6 +44,7 @@ impl VolumeEnvelope {
             release_start_time: 0_f64,
             sustain_level: 0_f32,
             release_level: 0_f32,
+            release_damping: 1_f32,
             processed_sample_count: 0,
             stage: 0,
             value: 0_f32,
@@ -67,6 +72,7 @@ impl VolumeEnvelope {
 
         self.sustain_level = SoundFontMath::clamp(sustain, 0_f32, 1_f32);
         self.release_level = 0_f32;
+        self.release_damping = 1_f32;
 
         self.processed_sample_count = 0;
         self.stage = EnvelopeStage::DELAY;
@@ -81,6 +87,17 @@ impl VolumeEnvelope {
         self.release_level = self.value;
     }
 
+    // The release continues from the current value at the new rate, and stops at 0.
+    pub(crate) fn set_release_damping(&mut self, value: f32) {
+        if self.stage != EnvelopeStage::RELEASE || value == self.release_damping {
+            return;
+        }
+
+        self.release_start_time = self.processed_sample_count as f64 / self.sample_rate as f64;
+        self.release_level = self.value;
+        self.release_damping = value;
+    }
+
     pub(crate) fn process(&mut self, sample_count: usize) -> bool {
         self.processed_sample_count += sample_count;
 
@@ -124,7 +141,9 @@ impl VolumeEnvelope {
         } else if self.stage == EnvelopeStage::RELEASE {
             self.value = (self.release_level as f64
                 * SoundFontMath::exp_cutoff(
-                    self.release_slope * (current_time - self.release_start_time),
+                    self.release_slope
+                        * self.release_damping as f64
+                        * (current_time - self.release_start_time),
                 )) as f32;
             self.priority = self.value;
             self.value > SoundFontMath::NON_AUDIBLE
@@ -137,6 +156,17 @@ impl VolumeEnvelope {
         self.value
     }
 
+    pub(crate) fn get_phase(&self) -> EnvelopePhase {
+        match self.stage {
+            EnvelopeStage::DELAY => EnvelopePhase::Delay,
+            EnvelopeStage::ATTACK => EnvelopePhase::Attack,
+            EnvelopeStage::HOLD => EnvelopePhase::Hold,
+            EnvelopeStage::DECAY if self.value <= self.sustain_level => EnvelopePhase::Sustain,
+            EnvelopeStage::DECAY => EnvelopePhase::Decay,
+            _ => EnvelopePhase::Release,
+        }
+    }
+
     pub(crate) fn get_priority(&self) -> f32 {
         self.priority
     }
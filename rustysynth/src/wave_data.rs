@@ -0,0 +1,108 @@
+#![allow(dead_code)]
+
+use alloc::vec::Vec;
+use core::ops::Deref;
+
+#[cfg(feature = "memmap2")]
+use std::fs::File;
+#[cfg(feature = "memmap2")]
+use std::io;
+
+#[cfg(feature = "memmap2")]
+use memmap2::Mmap;
+
+// The file from which the sample data is mapped.
+// Without the memmap2 feature, no file can be given.
+#[cfg(feature = "memmap2")]
+pub(crate) type MappedFile = File;
+#[cfg(not(feature = "memmap2"))]
+pub(crate) enum MappedFile {}
+
+// The sample data is either read into memory or memory-mapped from the file.
+// In both cases, it is accessed as a slice of 16-bit samples.
+#[non_exhaustive]
+pub(crate) enum WaveData {
+    Owned(Vec<i16>),
+    #[cfg(feature = "memmap2")]
+    Mapped {
+        map: Mmap,
+        offset: usize,
+        length: usize,
+    },
+}
+
+impl WaveData {
+    // The file must not be modified while the map is alive.
+    #[cfg(feature = "memmap2")]
+    pub(crate) unsafe fn map(file: &File, offset: u64, size: usize) -> Result<Self, io::Error> {
+        let map = Mmap::map(file)?;
+
+        let offset = offset as usize;
+        let length = size / 2;
+        if offset + 2 * length > map.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the sample data exceeds the end of the file",
+            ));
+        }
+
+        // The samples cannot be accessed in place if they are not aligned.
+        // This only happens with a malformed file, so just read them into memory.
+        if offset % 2 != 0 {
+            let samples = map[offset..offset + 2 * length]
+                .chunks_exact(2)
+                .map(|x| i16::from_le_bytes([x[0], x[1]]))
+                .collect();
+            return Ok(WaveData::Owned(samples));
+        }
+
+        Ok(WaveData::Mapped {
+            map,
+            offset,
+            length,
+        })
+    }
+
+    pub(crate) fn is_mapped(&self) -> bool {
+        match self {
+            WaveData::Owned(_) => false,
+            #[cfg(feature = "memmap2")]
+            WaveData::Mapped { .. } => true,
+        }
+    }
+}
+
+impl Deref for WaveData {
+    type Target = [i16];
+
+    fn deref(&self) -> &[i16] {
+        match self {
+            WaveData::Owned(samples) => &samples[..],
+            #[cfg(feature = "memmap2")]
+            WaveData::Mapped {
+                map,
+                offset,
+                length,
+            } => {
+                // Same as when reading, the samples are assumed to be little-endian.
+                let ptr = map[*offset..].as_ptr() as *const i16;
+                unsafe { core::slice::from_raw_parts(ptr, *length) }
+            }
+        }
+    }
+}
+
+// The sample data given to the voices.
+// The lower 8 bits of the 24-bit samples are empty if the SoundFont has only the 16-bit samples.
+#[derive(Clone, Copy)]
+pub(crate) struct WaveSlice<'a> {
+    pub(crate) data: &'a [i16],
+    pub(crate) data_24: &'a [u8],
+}
+
+impl WaveSlice<'_> {
+    pub(crate) const EMPTY: WaveSlice<'static> = WaveSlice {
+        data: &[],
+        data_24: &[],
+    };
+}
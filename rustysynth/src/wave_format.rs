@@ -0,0 +1,16 @@
+/// Specifies the sample format of the WAV files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WaveFormat {
+    /// The 16-bit integer.
+    Int16,
+
+    /// The 24-bit integer.
+    Int24,
+
+    /// The 32-bit integer.
+    Int32,
+
+    /// The 32-bit floating point, which keeps the samples exceeding the full scale.
+    Float32,
+}
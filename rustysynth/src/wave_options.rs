@@ -0,0 +1,82 @@
+#![allow(dead_code)]
+
+use crate::broadcast_info::BroadcastInfo;
+use crate::io;
+use crate::io::Write;
+use crate::wave_format::WaveFormat;
+use crate::wave_writer::WaveWriter;
+
+/// Specifies how the stereo waveforms are written as WAV files.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct WaveOptions {
+    /// The sample format.
+    pub format: WaveFormat,
+    /// The broadcast WAV metadata, or `None` to write no `bext` chunk.
+    pub broadcast: Option<BroadcastInfo>,
+}
+
+impl WaveOptions {
+    /// Initializes a new instance of WAV options, which writes the 32-bit floating-point samples.
+    pub fn new() -> Self {
+        Self {
+            format: WaveFormat::Float32,
+            broadcast: None,
+        }
+    }
+
+    pub(crate) fn check(&self) {
+        if let Some(broadcast) = &self.broadcast {
+            broadcast.check();
+        }
+    }
+
+    /// Writes the stereo waveform to the stream as a WAV file.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The data stream used to write the WAV file.
+    /// * `left` - The left channel of the waveform.
+    /// * `right` - The right channel of the waveform.
+    /// * `sample_rate` - The sample rate of the waveform.
+    /// * `loop_points` - The start and the end of the loop in samples, where the end is exclusive.
+    ///
+    /// # Remarks
+    ///
+    /// The samples are clipped and rounded for the integer formats.
+    /// The loop is written as a sampler loop in the `smpl` chunk and as two cue points in the `cue ` chunk,
+    /// so that game engines can loop the file seamlessly.
+    /// The channels must be the same length, and the loop must be within them.
+    pub fn write<W: Write>(
+        &self,
+        writer: &mut W,
+        left: &[f32],
+        right: &[f32],
+        sample_rate: i32,
+        loop_points: Option<(usize, usize)>,
+    ) -> Result<(), io::Error> {
+        if left.len() != right.len() {
+            panic!("The output buffers for the left and right must be the same length.");
+        }
+
+        if sample_rate <= 0 {
+            panic!("The sample rate must be greater than zero.");
+        }
+
+        if let Some((start, end)) = loop_points {
+            if !(start < end && end <= left.len()) {
+                panic!("The loop points must be within the waveform.");
+            }
+        }
+
+        self.check();
+
+        WaveWriter::write(writer, left, right, sample_rate, loop_points, self)
+    }
+}
+
+impl Default for WaveOptions {
+    fn default() -> Self {
+        WaveOptions::new()
+    }
+}
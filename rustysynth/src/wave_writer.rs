@@ -0,0 +1,207 @@
+#![allow(dead_code)]
+
+use alloc::vec::Vec;
+
+use crate::io;
+use crate::io::Write;
+
+use crate::binary_writer::BinaryWriter;
+use crate::broadcast_info::BroadcastInfo;
+use crate::float_math::FloatMath;
+use crate::four_cc::FourCC;
+use crate::wave_format::WaveFormat;
+use crate::wave_options::WaveOptions;
+
+// Writes a stereo WAV file in the format given by the options.
+// The loop is written both as a sampler loop and as a pair of cue points,
+// since some engines only read one of them.
+
+#[allow(unused)]
+#[non_exhaustive]
+pub(crate) struct WaveWriter {}
+
+impl WaveWriter {
+    const FORMAT_PCM: u16 = 1;
+    const FORMAT_IEEE_FLOAT: u16 = 3;
+    const CHANNEL_COUNT: u16 = 2;
+    const BEXT_SIZE: u32 = 602;
+
+    // The samples are converted in blocks, so that each sample is not written separately.
+    const BLOCK_LENGTH: usize = 4096;
+
+    pub(crate) fn write<W: Write>(
+        writer: &mut W,
+        left: &[f32],
+        right: &[f32],
+        sample_rate: i32,
+        loop_points: Option<(usize, usize)>,
+        options: &WaveOptions,
+    ) -> Result<(), io::Error> {
+        let format = options.format;
+        let bytes_per_sample = WaveWriter::get_bytes_per_sample(format);
+        let is_float = format == WaveFormat::Float32;
+
+        let block_align = WaveWriter::CHANNEL_COUNT as u32 * bytes_per_sample;
+        let data_size = (left.len() as u64) * block_align as u64;
+        let format_size: u64 = if is_float { (8 + 18) + (8 + 4) } else { 8 + 16 };
+        let bext_size: u64 = match options.broadcast {
+            Some(_) => 8 + WaveWriter::BEXT_SIZE as u64,
+            None => 0,
+        };
+        let loop_size: u64 = match loop_points {
+            Some(_) => (8 + 4 + 2 * 24) + (8 + 36 + 24),
+            None => 0,
+        };
+        let riff_size = 4 + bext_size + format_size + loop_size + 8 + data_size;
+        if riff_size > u32::MAX as u64 || left.len() > u32::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the waveform is too long to be written to a WAV file",
+            ));
+        }
+
+        BinaryWriter::write_four_cc(writer, &FourCC::from_bytes(*b"RIFF"))?;
+        BinaryWriter::write_u32(writer, riff_size as u32)?;
+        BinaryWriter::write_four_cc(writer, &FourCC::from_bytes(*b"WAVE"))?;
+
+        // The broadcast WAV specification recommends the bext chunk to be the first.
+        if let Some(broadcast) = &options.broadcast {
+            WaveWriter::write_bext(writer, broadcast)?;
+        }
+
+        // The size of the extension is written only for the non-PCM format.
+        BinaryWriter::write_four_cc(writer, &FourCC::from_bytes(*b"fmt "))?;
+        BinaryWriter::write_u32(writer, if is_float { 18 } else { 16 })?;
+        BinaryWriter::write_u16(
+            writer,
+            if is_float {
+                WaveWriter::FORMAT_IEEE_FLOAT
+            } else {
+                WaveWriter::FORMAT_PCM
+            },
+        )?;
+        BinaryWriter::write_u16(writer, WaveWriter::CHANNEL_COUNT)?;
+        BinaryWriter::write_u32(writer, sample_rate as u32)?;
+        BinaryWriter::write_u32(writer, sample_rate as u32 * block_align)?;
+        BinaryWriter::write_u16(writer, block_align as u16)?;
+        BinaryWriter::write_u16(writer, (8 * bytes_per_sample) as u16)?;
+        if is_float {
+            BinaryWriter::write_u16(writer, 0)?;
+
+            BinaryWriter::write_four_cc(writer, &FourCC::from_bytes(*b"fact"))?;
+            BinaryWriter::write_u32(writer, 4)?;
+            BinaryWriter::write_u32(writer, left.len() as u32)?;
+        }
+
+        if let Some((loop_start, loop_end)) = loop_points {
+            WaveWriter::write_cue(writer, loop_start, loop_end)?;
+            WaveWriter::write_smpl(writer, sample_rate, loop_start, loop_end)?;
+        }
+
+        BinaryWriter::write_four_cc(writer, &FourCC::from_bytes(*b"data"))?;
+        BinaryWriter::write_u32(writer, data_size as u32)?;
+
+        let mut data: Vec<u8> = Vec::with_capacity(WaveWriter::BLOCK_LENGTH * block_align as usize);
+        for (left, right) in left
+            .chunks(WaveWriter::BLOCK_LENGTH)
+            .zip(right.chunks(WaveWriter::BLOCK_LENGTH))
+        {
+            data.clear();
+            for (left, right) in left.iter().zip(right.iter()) {
+                WaveWriter::write_sample(&mut data, *left, format);
+                WaveWriter::write_sample(&mut data, *right, format);
+            }
+            writer.write_all(&data)?;
+        }
+
+        Ok(())
+    }
+
+    fn get_bytes_per_sample(format: WaveFormat) -> u32 {
+        match format {
+            WaveFormat::Int16 => 2,
+            WaveFormat::Int24 => 3,
+            WaveFormat::Int32 | WaveFormat::Float32 => 4,
+        }
+    }
+
+    // The integer formats are clipped and rounded.
+    // The conversion is done in f64, since f32 cannot represent the full scale of the 32-bit integer.
+    fn write_sample(data: &mut Vec<u8>, value: f32, format: WaveFormat) {
+        let to_int = |bits_per_sample: i32| {
+            let max = ((1_i64 << (bits_per_sample - 1)) - 1) as f64;
+            FloatMath::round_f64(max * (value as f64).clamp(-1_f64, 1_f64)) as i32
+        };
+
+        match format {
+            WaveFormat::Int16 => data.extend_from_slice(&(to_int(16) as i16).to_le_bytes()),
+            WaveFormat::Int24 => data.extend_from_slice(&to_int(24).to_le_bytes()[0..3]),
+            WaveFormat::Int32 => data.extend_from_slice(&to_int(32).to_le_bytes()),
+            WaveFormat::Float32 => data.extend_from_slice(&value.to_le_bytes()),
+        }
+    }
+
+    // The chunk is written in the version 1, which has the UMID but no loudness fields.
+    fn write_bext<W: Write>(writer: &mut W, broadcast: &BroadcastInfo) -> Result<(), io::Error> {
+        BinaryWriter::write_four_cc(writer, &FourCC::from_bytes(*b"bext"))?;
+        BinaryWriter::write_u32(writer, WaveWriter::BEXT_SIZE)?;
+        BinaryWriter::write_padded_string(writer, &broadcast.description, 256)?;
+        BinaryWriter::write_padded_string(writer, &broadcast.originator, 32)?;
+        BinaryWriter::write_padded_string(writer, &broadcast.originator_reference, 32)?;
+        BinaryWriter::write_padded_string(writer, &broadcast.origination_date, 10)?;
+        BinaryWriter::write_padded_string(writer, &broadcast.origination_time, 8)?;
+        BinaryWriter::write_u32(writer, broadcast.time_reference as u32)?;
+        BinaryWriter::write_u32(writer, (broadcast.time_reference >> 32) as u32)?;
+        BinaryWriter::write_u16(writer, 1)?;
+        writer.write_all(&[0; 64])?;
+        writer.write_all(&[0; 190])?;
+        Ok(())
+    }
+
+    fn write_cue<W: Write>(
+        writer: &mut W,
+        loop_start: usize,
+        loop_end: usize,
+    ) -> Result<(), io::Error> {
+        BinaryWriter::write_four_cc(writer, &FourCC::from_bytes(*b"cue "))?;
+        BinaryWriter::write_u32(writer, 4 + 2 * 24)?;
+        BinaryWriter::write_u32(writer, 2)?;
+        for (id, position) in [(1, loop_start), (2, loop_end)] {
+            BinaryWriter::write_u32(writer, id)?;
+            BinaryWriter::write_u32(writer, position as u32)?;
+            BinaryWriter::write_four_cc(writer, &FourCC::from_bytes(*b"data"))?;
+            BinaryWriter::write_u32(writer, 0)?;
+            BinaryWriter::write_u32(writer, 0)?;
+            BinaryWriter::write_u32(writer, position as u32)?;
+        }
+        Ok(())
+    }
+
+    // The end point of the sampler loop is the last sample played in the loop.
+    fn write_smpl<W: Write>(
+        writer: &mut W,
+        sample_rate: i32,
+        loop_start: usize,
+        loop_end: usize,
+    ) -> Result<(), io::Error> {
+        BinaryWriter::write_four_cc(writer, &FourCC::from_bytes(*b"smpl"))?;
+        BinaryWriter::write_u32(writer, 36 + 24)?;
+        BinaryWriter::write_u32(writer, 0)?;
+        BinaryWriter::write_u32(writer, 0)?;
+        BinaryWriter::write_u32(writer, (1_000_000_000 / sample_rate as i64) as u32)?;
+        BinaryWriter::write_u32(writer, 60)?;
+        BinaryWriter::write_u32(writer, 0)?;
+        BinaryWriter::write_u32(writer, 0)?;
+        BinaryWriter::write_u32(writer, 0)?;
+        BinaryWriter::write_u32(writer, 1)?;
+        BinaryWriter::write_u32(writer, 0)?;
+
+        BinaryWriter::write_u32(writer, 0)?;
+        BinaryWriter::write_u32(writer, 0)?;
+        BinaryWriter::write_u32(writer, loop_start as u32)?;
+        BinaryWriter::write_u32(writer, (loop_end - 1) as u32)?;
+        BinaryWriter::write_u32(writer, 0)?;
+        BinaryWriter::write_u32(writer, 0)?;
+        Ok(())
+    }
+}
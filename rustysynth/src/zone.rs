@@ -1,4 +1,7 @@
+use alloc::vec::Vec;
+
 use crate::error::SoundFontError;
+use crate::four_cc::FourCC;
 use crate::generator::Generator;
 use crate::zone_info::ZoneInfo;
 
@@ -14,24 +17,34 @@ impl Zone {
         }
     }
 
-    fn new(info: &ZoneInfo, generators: &[Generator]) -> Self {
+    fn new(
+        info: &ZoneInfo,
+        generators: &[Generator],
+        id: FourCC,
+        offset: u64,
+    ) -> Result<Self, SoundFontError> {
         let mut segment: Vec<Generator> = Vec::new();
 
         for i in 0..info.generator_count {
-            segment.push(generators[(info.generator_index + i) as usize]);
+            match generators.get((info.generator_index + i) as usize) {
+                Some(generator) => segment.push(*generator),
+                None => return Err(SoundFontError::InvalidZoneList { id, offset }),
+            }
         }
 
-        Self {
+        Ok(Self {
             generators: segment,
-        }
+        })
     }
 
     pub(crate) fn create(
         infos: &[ZoneInfo],
         generators: &[Generator],
+        id: FourCC,
+        offset: u64,
     ) -> Result<Vec<Zone>, SoundFontError> {
         if infos.len() <= 1 {
-            return Err(SoundFontError::ZoneNotFound);
+            return Err(SoundFontError::ZoneNotFound { id, offset });
         }
 
         // The last one is the terminator.
@@ -39,7 +52,7 @@ impl Zone {
 
         let mut zones: Vec<Zone> = Vec::new();
         for info in infos.iter().take(count) {
-            zones.push(Zone::new(info, generators));
+            zones.push(Zone::new(info, generators, id, offset)?);
         }
 
         Ok(zones)
@@ -1,7 +1,10 @@
-use std::io::Read;
+use alloc::vec::Vec;
+
+use crate::io::Read;
 
 use crate::binary_reader::BinaryReader;
 use crate::error::SoundFontError;
+use crate::four_cc::FourCC;
 
 #[non_exhaustive]
 pub(crate) struct ZoneInfo {
@@ -26,10 +29,12 @@ impl ZoneInfo {
 
     pub(crate) fn read_from_chunk<R: Read>(
         reader: &mut R,
+        id: FourCC,
+        offset: u64,
         size: usize,
     ) -> Result<Vec<ZoneInfo>, SoundFontError> {
-        if size % 4 != 0 {
-            return Err(SoundFontError::InvalidZoneList);
+        if size % 4 != 0 || size == 0 {
+            return Err(SoundFontError::InvalidZoneList { id, offset });
         }
 
         let count = size / 4;
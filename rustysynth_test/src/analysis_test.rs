@@ -0,0 +1,67 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use rustysynth::MidiFile;
+use std::io::Cursor;
+
+fn load(events: &[u8]) -> MidiFile {
+    MidiFile::from_bytes(&midi_util::format0(events)).unwrap()
+}
+
+#[test]
+fn channel_statistics() {
+    let midi_file = load(&[
+        0, 0xC1, 40, 0, 0xB1, 7, 100, 0, 0x91, 60, 100, 0, 0x91, 72, 100, 0x83, 0x60, 0x91, 60,
+        100, 0, 0x81, 72, 0, 0x83, 0x60, 0x81, 60, 0, 0, 0x92, 50, 100, 0, 0xFF, 0x2F, 0,
+    ]);
+    let analysis = midi_file.analyze();
+
+    assert_eq!(analysis.get_note_count(), 4);
+    assert_eq!(analysis.get_length(), 1.0);
+    assert_eq!(analysis.get_channels().len(), 2);
+
+    let channel = &analysis.get_channels()[0];
+    assert_eq!(channel.get_channel(), 1);
+    assert_eq!(channel.get_event_count(), 7);
+    assert_eq!(channel.get_note_count(), 3);
+    assert_eq!(channel.get_key_range(), Some((60, 72)));
+    assert_eq!(channel.get_controllers(), [(7, 1)]);
+    assert_eq!(channel.get_program_changes(), [(0.0, 40)]);
+    assert_eq!(channel.get_overlapping_note_count(), 1);
+    assert_eq!(channel.get_unterminated_note_count(), 1);
+
+    let channel = &analysis.get_channels()[1];
+    assert_eq!(channel.get_channel(), 2);
+    assert_eq!(channel.get_unterminated_note_count(), 1);
+
+    assert_eq!(analysis.get_diagnostics().len(), 3);
+}
+
+#[test]
+fn all_notes_off_terminates_notes() {
+    let midi_file = load(&[
+        0, 0x90, 60, 100, 0, 0x90, 64, 100, 0x83, 0x60, 0xB0, 0x7B, 0, 0, 0xFF, 0x2F, 0,
+    ]);
+    let analysis = midi_file.analyze();
+
+    let channel = &analysis.get_channels()[0];
+    assert_eq!(channel.get_note_count(), 2);
+    assert_eq!(channel.get_unterminated_note_count(), 0);
+    assert!(analysis.get_diagnostics().is_empty());
+}
+
+#[test]
+fn data_bytes_above_127_are_masked() {
+    // The parser passes the bytes from 0x80 to 0xF7 through as the data bytes.
+    let midi_file = load(&[
+        0, 0x90, 0xC8, 100, 0, 0xB0, 0xF0, 0, 0x83, 0x60, 0x80, 0xC8, 0, 0, 0xFF, 0x2F, 0,
+    ]);
+    let analysis = midi_file.analyze();
+
+    let channel = &analysis.get_channels()[0];
+    assert_eq!(channel.get_note_count(), 1);
+    assert_eq!(channel.get_key_range(), Some((0x48, 0x48)));
+    assert_eq!(channel.get_controllers(), [(0x70, 1)]);
+    assert_eq!(channel.get_unterminated_note_count(), 0);
+}
@@ -0,0 +1,85 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use crate::render_util;
+use crate::render_util::TempFile;
+use crate::synth_util;
+use rustysynth::MidiFile;
+use rustysynth::MidiFileOptions;
+use rustysynth::ParserLimits;
+use rustysynth::SoundFont;
+use rustysynth::ThreadedRender;
+use std::io::Cursor;
+use std::io::Write;
+use zip::write::SimpleFileOptions;
+use zip::CompressionMethod;
+use zip::ZipWriter;
+
+// A zip archive of the files, compressed with deflate.
+fn archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, data) in files {
+        writer.start_file(*name, options).unwrap();
+        writer.write_all(data).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+#[test]
+fn sound_font_is_extracted_from_archive() {
+    let data = synth_util::test_bank_file();
+    let archive = archive(&[
+        ("readme.txt", b"The test bank."),
+        ("__MACOSX/._Bank.SF2", b"resource fork"),
+        ("Bank.SF2", &data),
+    ]);
+
+    let sound_font = SoundFont::new(&mut Cursor::new(archive)).unwrap();
+    let expected = SoundFont::from_bytes(&data).unwrap();
+    assert_eq!(sound_font.get_presets().len(), expected.get_presets().len());
+    assert_eq!(sound_font.get_wave_data(), expected.get_wave_data());
+}
+
+#[test]
+fn midi_file_is_extracted_from_archive() {
+    let data = midi_util::format1_with_notes_in_tempo_track();
+    let archive = archive(&[("bank.sf2", b"not a MIDI file"), ("song.mid", &data)]);
+
+    let midi_file = MidiFile::from_bytes(&archive).unwrap();
+    let expected = MidiFile::from_bytes(&data).unwrap();
+    assert_eq!(midi_util::events(&midi_file), midi_util::events(&expected));
+}
+
+#[test]
+fn archive_without_file_is_error() {
+    let archive = archive(&[("readme.txt", b"No MIDI file.")]);
+    assert!(MidiFile::from_bytes(&archive).is_err());
+    assert!(SoundFont::new(&mut Cursor::new(archive)).is_err());
+}
+
+#[test]
+fn file_larger_than_limit_in_archive_is_error() {
+    let archive = archive(&[("bank.sf2", &synth_util::test_bank_file())]);
+    let mut limits = ParserLimits::new();
+    limits.max_chunk_size = 100;
+    assert!(SoundFont::new_with_limits(&mut Cursor::new(archive), &limits).is_err());
+}
+
+#[test]
+fn renderer_extracts_midi_file_from_archive() {
+    let data = midi_util::flute_note(69);
+    let file = TempFile::new("archive", &data);
+    let expected = render_util::renderer(&file).render();
+
+    let file = TempFile::new("archive_zip", &archive(&[("song.MID", &data)]));
+    assert_eq!(render_util::renderer(&file).render(), expected);
+}
+
+#[test]
+fn info_is_read_from_archive() {
+    let archive = archive(&[("Bank.sf2", &synth_util::test_bank_file())]);
+    let info = SoundFont::read_info(&mut Cursor::new(archive)).unwrap();
+    assert_eq!(info.get_bank_name(), "RustySynth Test Bank");
+}
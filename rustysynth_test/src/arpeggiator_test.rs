@@ -0,0 +1,106 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::synth_util;
+use rustysynth::Arpeggiator;
+use rustysynth::ArpeggiatorPattern;
+use rustysynth::VoiceEventKind;
+
+fn arpeggiator() -> Arpeggiator {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.set_voice_event_capacity(64);
+    Arpeggiator::new(synthesizer, 0)
+}
+
+// Renders the arpeggio and gets the keys of the started notes.
+fn play(arpeggiator: &mut Arpeggiator, seconds: f64) -> Vec<i32> {
+    let mut left = vec![0_f32; synth_util::samples(seconds)];
+    let mut right = vec![0_f32; synth_util::samples(seconds)];
+    arpeggiator.render(&mut left, &mut right);
+    arpeggiator
+        .get_synthesizer_mut()
+        .drain_voice_events()
+        .filter(|x| x.get_kind() == VoiceEventKind::Started)
+        .map(|x| x.get_key())
+        .collect()
+}
+
+// Holds a chord in the order which is not sorted, and plays 6 steps of the sixteenth notes at 120 BPM.
+fn play_chord(pattern: ArpeggiatorPattern) -> Vec<i32> {
+    let mut arpeggiator = arpeggiator();
+    arpeggiator.set_pattern(pattern);
+    assert_eq!(arpeggiator.get_pattern(), pattern);
+    arpeggiator.note_on(64, 100);
+    arpeggiator.note_on(60, 100);
+    arpeggiator.note_on(67, 100);
+    play(&mut arpeggiator, 0.7)
+}
+
+#[test]
+fn arpeggiator_plays_held_notes_in_pattern() {
+    assert_eq!(play_chord(ArpeggiatorPattern::Up), [60, 64, 67, 60, 64, 67]);
+    assert_eq!(
+        play_chord(ArpeggiatorPattern::Down),
+        [67, 64, 60, 67, 64, 60]
+    );
+    assert_eq!(
+        play_chord(ArpeggiatorPattern::UpDown),
+        [60, 64, 67, 64, 60, 64]
+    );
+    assert_eq!(
+        play_chord(ArpeggiatorPattern::AsPlayed),
+        [64, 60, 67, 64, 60, 67]
+    );
+
+    let keys = play_chord(ArpeggiatorPattern::Random);
+    assert_eq!(keys.len(), 6);
+    assert!(keys.iter().all(|x| [60, 64, 67].contains(x)));
+}
+
+#[test]
+fn arpeggiator_repeats_notes_over_octaves() {
+    let mut arpeggiator = arpeggiator();
+    assert_eq!(arpeggiator.get_octaves(), 1);
+    arpeggiator.set_octaves(2);
+    arpeggiator.note_on(60, 100);
+    assert_eq!(play(&mut arpeggiator, 0.45), [60, 72, 60, 72]);
+}
+
+#[test]
+fn arpeggiator_follows_tempo_and_rate() {
+    let mut arpeggiator = arpeggiator();
+    assert_eq!(arpeggiator.get_tempo(), 120.0);
+    assert_eq!(arpeggiator.get_rate(), 4);
+    assert_eq!(arpeggiator.get_gate(), 0.5);
+
+    // The eighth notes at 60 BPM are 0.5 seconds long.
+    arpeggiator.set_tempo(60.0);
+    arpeggiator.set_rate(2);
+    arpeggiator.set_gate(1.0);
+    arpeggiator.note_on(60, 100);
+    assert_eq!(play(&mut arpeggiator, 0.9).len(), 2);
+}
+
+#[test]
+fn arpeggiator_stops_when_notes_are_released() {
+    let mut arpeggiator = arpeggiator();
+    assert_eq!(arpeggiator.get_channel(), 0);
+    arpeggiator.process_midi_message(0, 0x90, 60, 100);
+    assert_eq!(play(&mut arpeggiator, 0.2), [60, 60]);
+
+    arpeggiator.process_midi_message(0, 0x80, 60, 0);
+    assert!(play(&mut arpeggiator, 0.5).is_empty());
+    assert_eq!(
+        arpeggiator
+            .get_synthesizer()
+            .get_statistics()
+            .get_active_voice_count(),
+        0
+    );
+}
+
+#[test]
+#[should_panic(expected = "The tempo must be greater than zero.")]
+fn zero_tempo_panics() {
+    arpeggiator().set_tempo(0.0);
+}
@@ -0,0 +1,84 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use crate::synth_util;
+use rustysynth::MidiFile;
+use rustysynth::MidiFileError;
+use rustysynth::MidiFileOptions;
+use rustysynth::MidiFileSequencer;
+use rustysynth::ParserLimits;
+use rustysynth::SoundFont;
+use rustysynth::SoundFontError;
+use std::future::Future;
+use tokio::runtime::Builder;
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+#[test]
+fn sound_font_from_async_reader_is_same_as_sync() {
+    let data = synth_util::test_bank_file();
+    let expected = SoundFont::from_bytes(&data).unwrap();
+    let actual = block_on(SoundFont::from_async_reader(&mut data.as_slice())).unwrap();
+
+    assert_eq!(actual.get_presets().len(), expected.get_presets().len());
+    assert_eq!(actual.get_wave_data(), expected.get_wave_data());
+}
+
+#[test]
+fn midi_file_from_async_reader_is_same_as_sync() {
+    let data = midi_util::format1_with_notes_in_tempo_track();
+    let expected = MidiFile::from_bytes(&data).unwrap();
+    let actual = block_on(MidiFile::from_async_reader(&mut data.as_slice())).unwrap();
+
+    assert_eq!(midi_util::events(&actual), midi_util::events(&expected));
+}
+
+#[test]
+fn async_reader_larger_than_limit_is_rejected() {
+    let data = synth_util::test_bank_file();
+    let mut limits = ParserLimits::new();
+    limits.max_chunk_size = 100;
+    let result = block_on(SoundFont::from_async_reader_with_limits(
+        &mut data.as_slice(),
+        &limits,
+    ));
+    assert!(matches!(result, Err(SoundFontError::FileTooLarge)));
+
+    let data = midi_util::flute_note(69);
+    let mut options = MidiFileOptions::new();
+    options.limits.max_chunk_size = 10;
+    let result = block_on(MidiFile::from_async_reader_with_options(
+        &mut data.as_slice(),
+        &options,
+    ));
+    assert!(matches!(result, Err(MidiFileError::FileTooLarge)));
+}
+
+#[test]
+fn invalid_async_data_is_error() {
+    let result = block_on(MidiFile::from_async_reader(&mut b"RIFF".as_slice()));
+    assert!(result.is_err());
+}
+
+#[test]
+fn render_async_is_same_as_render() {
+    let midi_file = MidiFile::from_bytes(&midi_util::flute_note(69)).unwrap();
+    let settings = synth_util::settings();
+
+    let mut sequencer = MidiFileSequencer::new(synth_util::synthesizer(&settings));
+    sequencer.play(midi_file.get_tracks()[0].clone(), false);
+    let expected = synth_util::play(&mut sequencer, 1.0);
+
+    let mut sequencer = MidiFileSequencer::new(synth_util::synthesizer(&settings));
+    sequencer.play(midi_file.get_tracks()[0].clone(), false);
+    let mut left = vec![0_f32; synth_util::samples(1.0)];
+    let mut right = vec![0_f32; synth_util::samples(1.0)];
+    block_on(sequencer.render_async(&mut left, &mut right));
+    assert_eq!(left, expected);
+}
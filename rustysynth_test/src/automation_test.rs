@@ -0,0 +1,41 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use rustysynth::Automation;
+
+#[test]
+fn automation_interpolates_between_points() {
+    let mut automation = Automation::new();
+    assert_eq!(automation.get_value(0.0), None);
+
+    automation.push(2.0, 0.0);
+    automation.push(1.0, 1.0);
+    assert_eq!(automation.get_points(), [(1.0, 1.0), (2.0, 0.0)]);
+
+    // The value is held outside the points.
+    assert_eq!(automation.get_value(0.0), Some(1.0));
+    assert_eq!(automation.get_value(1.5), Some(0.5));
+    assert_eq!(automation.get_value(3.0), Some(0.0));
+
+    automation.clear();
+    assert!(automation.get_points().is_empty());
+}
+
+#[test]
+fn points_at_same_time_make_step() {
+    let mut automation = Automation::new();
+    automation.push(0.0, 0.0);
+    automation.push(1.0, 1.0);
+    automation.push(1.0, 0.25);
+    automation.push(2.0, 0.25);
+
+    assert_eq!(automation.get_value(0.5), Some(0.5));
+    assert_eq!(automation.get_value(1.0), Some(0.25));
+    assert_eq!(automation.get_value(1.5), Some(0.25));
+}
+
+#[test]
+#[should_panic(expected = "The time must be a non-negative value.")]
+fn negative_time_panics() {
+    Automation::new().push(-1.0, 0.0);
+}
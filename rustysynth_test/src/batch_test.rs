@@ -0,0 +1,163 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use crate::render_util;
+use crate::render_util::TempFile;
+use crate::synth_util;
+use crate::wave_util;
+use rustysynth::BatchFileState;
+use rustysynth::BatchRenderer;
+use rustysynth::BroadcastInfo;
+use rustysynth::MidiFileError;
+use rustysynth::RenderTail;
+use rustysynth::SynthesizerError;
+use rustysynth::WaveFormat;
+use rustysynth::WaveOptions;
+use std::env;
+use std::fs;
+use std::process;
+use std::sync::Mutex;
+
+fn batch_renderer() -> BatchRenderer {
+    BatchRenderer::new(&synth_util::sound_font(), synth_util::settings()).unwrap()
+}
+
+#[test]
+fn batch_renders_each_file_as_threaded_render() {
+    let first = TempFile::new("batch_first", &midi_util::flute_note(69));
+    let second = TempFile::new("batch_second", &midi_util::looped());
+    let files = [first.get_path(), second.get_path(), "missing.mid"];
+
+    let mut batch = batch_renderer();
+    assert_eq!(batch.get_worker_count(), 2);
+    batch.set_tail(RenderTail::Fixed(0.5));
+    assert_eq!(batch.get_tail(), RenderTail::Fixed(0.5));
+
+    let outputs = Mutex::new(Vec::new());
+    let results = batch.render(&files, |index, _, left, _| {
+        outputs.lock().unwrap().push((index, left));
+        Ok(())
+    });
+
+    assert!(results[0].is_ok());
+    assert!(results[1].is_ok());
+    assert!(matches!(results[2], Err(MidiFileError::IoError(_))));
+
+    let mut outputs = outputs.into_inner().unwrap();
+    outputs.sort_by_key(|(index, _)| *index);
+    assert_eq!(outputs.len(), 2);
+    for ((index, left), file) in outputs.iter().zip([&first, &second]) {
+        let mut renderer = render_util::renderer(file);
+        renderer.set_tail(RenderTail::Fixed(0.5));
+        assert_eq!(*left, renderer.render().0, "file {}", index);
+    }
+}
+
+#[test]
+fn batch_progress_follows_files() {
+    let file = TempFile::new("batch_progress", &midi_util::flute_note(69));
+    let files = [file.get_path(), "missing.mid"];
+
+    let batch = batch_renderer();
+    let progress = batch.get_progress();
+    assert_eq!(progress.get_ratio(), 0.0);
+    batch.render(&files, |_, _, _, _| Ok(()));
+
+    assert_eq!(progress.get_file_count(), 2);
+    assert_eq!(progress.get_completed_count(), 1);
+    assert_eq!(progress.get_failed_count(), 1);
+    assert_eq!(progress.get_state(0), BatchFileState::Completed);
+    assert_eq!(progress.get_state(1), BatchFileState::Failed);
+    assert_eq!(progress.get_file_ratio(0), 1.0);
+    assert_eq!(progress.get_ratio(), 1.0);
+}
+
+#[test]
+fn failed_output_fails_file() {
+    let file = TempFile::new("batch_output", &midi_util::flute_note(69));
+    let batch = batch_renderer();
+    let results = batch.render(&[file.get_path()], |_, _, _, _| {
+        Err(std::io::Error::other("the disk is full"))
+    });
+
+    assert!(matches!(results[0], Err(MidiFileError::IoError(_))));
+    assert_eq!(batch.get_progress().get_failed_count(), 1);
+}
+
+#[test]
+fn render_to_directory_writes_wave_files() {
+    let file = TempFile::new("batch_directory", &midi_util::flute_note(69));
+    let directory = env::temp_dir().join(format!("rustysynth_test_batch_{}", process::id()));
+
+    let mut batch = batch_renderer();
+    batch.set_worker_count(1);
+    batch.set_thread_count(1).unwrap();
+    assert!(batch.get_thread_pool().is_some());
+    let results = batch
+        .render_to_directory(&[file.get_path()], &directory)
+        .unwrap();
+    assert!(results[0].is_ok());
+
+    let name = format!("rustysynth_test_batch_directory_{}.wav", process::id());
+    let data = fs::read(directory.join(name)).unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+
+    let (expected, _) = render_util::renderer(&file).render();
+    let samples = wave_util::find_chunk(&data, b"data").unwrap();
+    assert_eq!(wave_util::read_f32_channel(samples, 0), expected);
+}
+
+#[test]
+fn render_to_directory_follows_wave_options() {
+    let file = TempFile::new("batch_options", &midi_util::flute_note(69));
+    let directory = env::temp_dir().join(format!("rustysynth_test_options_{}", process::id()));
+
+    let mut batch = batch_renderer();
+    assert_eq!(*batch.get_wave_options(), WaveOptions::new());
+    let mut options = WaveOptions::new();
+    options.format = WaveFormat::Int16;
+    batch.set_wave_options(options.clone());
+    assert_eq!(*batch.get_wave_options(), options);
+    let results = batch
+        .render_to_directory(&[file.get_path()], &directory)
+        .unwrap();
+    assert!(results[0].is_ok());
+
+    let name = format!("rustysynth_test_batch_options_{}.wav", process::id());
+    let data = fs::read(directory.join(name)).unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+
+    let (expected, _) = render_util::renderer(&file).render();
+    let format = wave_util::find_chunk(&data, b"fmt ").unwrap();
+    assert_eq!(wave_util::read_u16(format, 14), 16);
+    let samples = wave_util::find_chunk(&data, b"data").unwrap();
+    assert_eq!(samples.len(), 4 * expected.len());
+}
+
+#[test]
+#[should_panic(expected = "The origination time must be in the format of hh:mm:ss.")]
+fn invalid_wave_options_panic() {
+    let mut broadcast = BroadcastInfo::new();
+    broadcast.origination_time = "noon".to_string();
+    let mut options = WaveOptions::new();
+    options.broadcast = Some(broadcast);
+    batch_renderer().set_wave_options(options);
+}
+
+#[test]
+fn invalid_settings_are_error() {
+    let mut settings = synth_util::settings();
+    settings.block_size = 4;
+    let result = BatchRenderer::new(&synth_util::sound_font(), settings);
+    assert!(matches!(
+        result,
+        Err(SynthesizerError::BlockSizeOutOfRange(4))
+    ));
+}
+
+#[test]
+#[should_panic(expected = "The worker count must be greater than zero.")]
+fn zero_worker_count_panics() {
+    batch_renderer().set_worker_count(0);
+}
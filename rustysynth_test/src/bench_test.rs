@@ -0,0 +1,63 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::synth_util;
+use rustysynth::bench;
+use rustysynth::bench::BenchOptions;
+use rustysynth::SynthesizerError;
+
+fn options() -> BenchOptions {
+    let mut options = BenchOptions::new(synth_util::SAMPLE_RATE);
+    options.length = 1.0;
+    options
+}
+
+#[test]
+fn bench_render_is_reproducible() {
+    let (left, right) = bench::render(&options()).unwrap();
+    assert_eq!(left.len(), synth_util::samples(1.0));
+    assert!(synth_util::rms(&left) > 0.01);
+    assert!(synth_util::rms(&right) > 0.01);
+
+    assert_eq!(bench::render(&options()).unwrap(), (left, right));
+}
+
+#[test]
+fn render_with_is_same_as_render() {
+    let expected = bench::render(&options()).unwrap();
+
+    let sound_font = bench::create_sound_font();
+    let sequence = bench::create_sequence(1.0);
+    assert!(sequence.get_length() <= 1.0);
+    let actual = bench::render_with(&sound_font, &sequence, &options()).unwrap();
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn thread_count_keeps_output() {
+    let mut options = options();
+    options.parallel_voices = true;
+    let (expected, _) = bench::render(&options).unwrap();
+
+    options.thread_count = Some(2);
+    let (actual, _) = bench::render(&options).unwrap();
+
+    // Only the order of the summation differs.
+    let difference = actual
+        .iter()
+        .zip(expected.iter())
+        .fold(0_f32, |max, (x, y)| max.max((x - y).abs()));
+    assert!(difference < 1.0E-5);
+    assert!(bench::measure(&options).unwrap().as_nanos() > 0);
+}
+
+#[test]
+fn invalid_bench_options_are_error() {
+    let mut options = options();
+    options.block_size = 4;
+    assert!(matches!(
+        bench::render(&options),
+        Err(SynthesizerError::BlockSizeOutOfRange(4))
+    ));
+}
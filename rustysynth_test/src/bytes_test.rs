@@ -0,0 +1,35 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use crate::synth_util;
+use rustysynth::MidiFile;
+use rustysynth::SoundFont;
+use std::io::Cursor;
+
+#[test]
+fn sound_font_from_bytes_is_same_as_from_reader() {
+    let data = synth_util::test_bank_file();
+    let from_reader = SoundFont::new(&mut Cursor::new(&data)).unwrap();
+    let from_bytes = SoundFont::from_bytes(&data).unwrap();
+
+    assert_eq!(from_bytes.get_wave_data(), from_reader.get_wave_data());
+    assert_eq!(
+        from_bytes.get_presets().len(),
+        from_reader.get_presets().len()
+    );
+    assert!(SoundFont::from_bytes(&data[..data.len() / 2]).is_err());
+}
+
+#[test]
+fn midi_file_from_bytes_is_same_as_from_reader() {
+    let data = midi_util::format1_with_notes_in_tempo_track();
+    let from_reader = MidiFile::new(&mut Cursor::new(&data)).unwrap();
+    let from_bytes = MidiFile::from_bytes(&data).unwrap();
+
+    assert_eq!(from_bytes.get_length(), from_reader.get_length());
+    for (x, y) in from_bytes.get_tracks().iter().zip(from_reader.get_tracks()) {
+        assert_eq!(midi_util::note_ons(x), midi_util::note_ons(y));
+    }
+    assert!(MidiFile::from_bytes(&data[..10]).is_err());
+}
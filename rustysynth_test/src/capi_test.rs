@@ -0,0 +1,116 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use crate::synth_util;
+use crate::synth_util::FLUTE;
+use rustysynth_capi::*;
+use std::ffi::CStr;
+use std::ptr;
+
+#[test]
+fn synthesizer_plays_through_c_api() {
+    let data = synth_util::test_bank_file();
+    let mut left = vec![0_f32; 4410];
+    let mut right = vec![0_f32; 4410];
+    unsafe {
+        let sound_font = rustysynth_soundfont_load(data.as_ptr(), data.len());
+        assert!(!sound_font.is_null());
+        let synthesizer = rustysynth_synthesizer_create(sound_font, 44100);
+        assert!(!synthesizer.is_null());
+
+        // The synthesizer is still valid after the SoundFont is released.
+        rustysynth_soundfont_destroy(sound_font);
+        assert!(rustysynth_synthesizer_process_midi_message(
+            synthesizer,
+            0,
+            0xC0,
+            FLUTE,
+            0
+        ));
+        assert!(rustysynth_synthesizer_note_on(synthesizer, 0, 69, 100));
+        assert!(rustysynth_synthesizer_render(
+            synthesizer,
+            left.as_mut_ptr(),
+            right.as_mut_ptr(),
+            left.len()
+        ));
+        rustysynth_synthesizer_destroy(synthesizer);
+    }
+
+    assert!(synth_util::rms(&left) > 0.01);
+}
+
+#[test]
+fn sequencer_plays_through_c_api() {
+    let sound_font_data = synth_util::test_bank_file();
+    let midi_file_data = midi_util::format1_with_notes_in_tempo_track();
+    let mut left = vec![0_f32; 44100];
+    let mut right = vec![0_f32; 44100];
+    unsafe {
+        let sound_font = rustysynth_soundfont_load(sound_font_data.as_ptr(), sound_font_data.len());
+        let midi_file = rustysynth_midifile_load(midi_file_data.as_ptr(), midi_file_data.len());
+        assert!(!midi_file.is_null());
+        assert_eq!(rustysynth_midifile_get_length(midi_file), 0.5);
+        assert_eq!(rustysynth_midifile_get_track_count(midi_file), 3);
+
+        let sequencer = rustysynth_sequencer_create(sound_font, 44100);
+        assert!(rustysynth_sequencer_play(sequencer, midi_file, 0, false));
+        assert!(rustysynth_sequencer_render(
+            sequencer,
+            left.as_mut_ptr(),
+            right.as_mut_ptr(),
+            left.len()
+        ));
+        assert!((rustysynth_sequencer_get_position(sequencer) - 1.0).abs() < 0.01);
+        assert!(rustysynth_sequencer_end_of_sequence(sequencer));
+
+        rustysynth_sequencer_destroy(sequencer);
+        rustysynth_midifile_destroy(midi_file);
+        rustysynth_soundfont_destroy(sound_font);
+    }
+
+    assert!(synth_util::peak(&left) > 0.01);
+}
+
+#[test]
+fn failures_are_reported_as_last_error() {
+    let data = [0_u8; 16];
+    unsafe {
+        assert!(rustysynth_soundfont_load(data.as_ptr(), data.len()).is_null());
+        assert!(!last_error().is_empty());
+        assert!(rustysynth_midifile_load(ptr::null(), 16).is_null());
+        assert!(!last_error().is_empty());
+    }
+}
+
+#[test]
+fn invalid_arguments_are_reported_as_failures() {
+    let sound_font_data = synth_util::test_bank_file();
+    let midi_file_data = midi_util::format1_with_notes_in_tempo_track();
+    unsafe {
+        let sound_font = rustysynth_soundfont_load(sound_font_data.as_ptr(), sound_font_data.len());
+        let midi_file = rustysynth_midifile_load(midi_file_data.as_ptr(), midi_file_data.len());
+        let sequencer = rustysynth_sequencer_create(sound_font, 44100);
+
+        assert!(!rustysynth_sequencer_play(sequencer, midi_file, 3, false));
+        assert_eq!(last_error(), "the track index is out of range");
+        assert!(!rustysynth_sequencer_render(
+            sequencer,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            16
+        ));
+
+        rustysynth_sequencer_destroy(sequencer);
+        rustysynth_midifile_destroy(midi_file);
+        rustysynth_soundfont_destroy(sound_font);
+    }
+}
+
+fn last_error() -> String {
+    unsafe { CStr::from_ptr(rustysynth_get_last_error()) }
+        .to_str()
+        .unwrap()
+        .to_string()
+}
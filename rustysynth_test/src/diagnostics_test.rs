@@ -0,0 +1,96 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use crate::synth_util;
+use rustysynth::DiagnosticCode;
+use rustysynth::DiagnosticLocation;
+use rustysynth::DiagnosticSeverity;
+use rustysynth::MidiFile;
+use rustysynth::MidiFileOptions;
+
+#[test]
+fn midi_file_warnings_are_diagnostics() {
+    // The end of track is missing.
+    let data = midi_util::format0(&[0, 0x90, 60, 100, 0x83, 0x60, 0x80, 60, 0]);
+    let mut options = MidiFileOptions::new();
+    options.lenient = true;
+    let midi_file = MidiFile::new_with_options(&mut data.as_slice(), &options).unwrap();
+
+    let diagnostics = midi_file.get_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert!(!diagnostics.has_errors());
+
+    let diagnostic = &diagnostics.get_items()[0];
+    assert_eq!(diagnostic.get_code(), DiagnosticCode::MidiMissingEndOfTrack);
+    assert_eq!(diagnostic.get_severity(), DiagnosticSeverity::Warning);
+    assert_eq!(diagnostic.get_location(), DiagnosticLocation::Track(0));
+    assert_eq!(diagnostic.get_message(), "the track 0 has no end of track");
+    assert_eq!(
+        diagnostic.to_string(),
+        "warning: the track 0 has no end of track"
+    );
+}
+
+#[test]
+fn missing_preset_is_reported_once() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    assert!(synthesizer.get_diagnostics().is_empty());
+
+    // The test bank has no bank 5, so the fallback preset is used for each note.
+    synthesizer.process_midi_message(0, 0xB0, 0x00, 5);
+    synthesizer.process_midi_message(0, 0xC0, 0, 0);
+    synthesizer.note_on(0, 60, 100);
+    synthesizer.note_on(0, 64, 100);
+
+    let diagnostics = synthesizer.get_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    let diagnostic = &diagnostics.get_items()[0];
+    assert_eq!(diagnostic.get_code(), DiagnosticCode::PresetNotFound);
+    assert_eq!(
+        diagnostic.get_location(),
+        DiagnosticLocation::Preset {
+            bank_number: 5,
+            patch_number: 0
+        }
+    );
+    assert!(synth_util::rms(&synth_util::render(&mut synthesizer, 0.1)) > 0.01);
+
+    // The problem is reported again after the diagnostics are cleared.
+    synthesizer.clear_diagnostics();
+    assert!(synthesizer.get_diagnostics().is_empty());
+    synthesizer.note_on(0, 67, 100);
+    assert_eq!(synthesizer.get_diagnostics().len(), 1);
+}
+
+#[test]
+fn new_diagnostics_are_returned_once() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    assert!(synthesizer.get_new_diagnostics().is_empty());
+
+    synthesizer.process_midi_message(0, 0xB0, 3, 0);
+    let diagnostics = synthesizer.get_new_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].get_code(),
+        DiagnosticCode::UnsupportedController
+    );
+    assert_eq!(
+        diagnostics[0].get_location(),
+        DiagnosticLocation::Controller {
+            channel: 0,
+            number: 3
+        }
+    );
+    assert!(synthesizer.get_new_diagnostics().is_empty());
+
+    synthesizer.process_midi_message(0, 0xB0, 0x00, 5);
+    synthesizer.process_midi_message(0, 0xC0, 0, 0);
+    synthesizer.note_on(0, 60, 100);
+    let diagnostics = synthesizer.get_new_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].get_code(), DiagnosticCode::PresetNotFound);
+
+    // All the diagnostics are kept.
+    assert_eq!(synthesizer.get_diagnostics().len(), 2);
+}
@@ -0,0 +1,132 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::synth_util;
+use rustysynth::GeneratorOverrides;
+use rustysynth::GeneratorType;
+use rustysynth::SoundFont;
+use rustysynth::Synthesizer;
+use std::sync::Arc;
+
+// Plays the key 69 with the flute and returns the frequency.
+fn flute_frequency(sound_font: SoundFont) -> f64 {
+    let settings = synth_util::settings();
+    let mut synthesizer = Synthesizer::new(&Arc::new(sound_font), &settings).unwrap();
+    synthesizer.process_midi_message(0, 0xC0, synth_util::FLUTE, 0);
+    synthesizer.note_on(0, 69, 100);
+    synth_util::frequency(&synth_util::render(&mut synthesizer, 0.5))
+}
+
+// The index of the instrument used by the flute.
+fn flute_instrument_id(sound_font: &SoundFont) -> usize {
+    let preset = sound_font
+        .get_presets()
+        .iter()
+        .find(|x| x.get_bank_number() == 0 && x.get_patch_number() == synth_util::FLUTE)
+        .unwrap();
+    let instrument = preset.get_regions()[0].get_instrument(sound_font);
+    sound_font
+        .get_instruments()
+        .iter()
+        .position(|x| std::ptr::eq(x, instrument))
+        .unwrap()
+}
+
+#[test]
+fn overrides_can_be_removed_and_cleared() {
+    let mut overrides = GeneratorOverrides::new();
+    assert!(overrides.is_empty());
+
+    overrides.set_preset_generator(0, 0, None, GeneratorType::PAN, 100);
+    overrides.set_preset_generator(0, 0, None, GeneratorType::PAN, 200);
+    overrides.set_preset_generator(0, 0, Some(0), GeneratorType::PAN, 100);
+    overrides.set_instrument_generator(0, None, GeneratorType::PAN, 100);
+    assert_eq!(overrides.len(), 3);
+
+    overrides.remove_preset_generator(0, 0, Some(0), GeneratorType::PAN);
+    assert_eq!(overrides.len(), 2);
+    overrides.remove_instrument_generator(0, None, GeneratorType::COARSE_TUNE);
+    assert_eq!(overrides.len(), 2);
+    overrides.remove_instrument_generator(0, None, GeneratorType::PAN);
+    assert_eq!(overrides.len(), 1);
+
+    overrides.clear();
+    assert!(overrides.is_empty());
+}
+
+#[test]
+fn preset_override_is_added_to_instrument() {
+    let sound_font = SoundFont::test_bank();
+    let mut overrides = GeneratorOverrides::new();
+    overrides.set_preset_generator(0, synth_util::FLUTE, None, GeneratorType::COARSE_TUNE, 12);
+    let frequency = flute_frequency(overrides.apply(&sound_font));
+    assert!((frequency - 880.0).abs() < 10.0);
+}
+
+#[test]
+fn instrument_override_replaces_value() {
+    let sound_font = SoundFont::test_bank();
+    let instrument_id = flute_instrument_id(&sound_font);
+    let mut overrides = GeneratorOverrides::new();
+    overrides.set_instrument_generator(instrument_id, None, GeneratorType::COARSE_TUNE, -12);
+    let applied = overrides.apply(&sound_font);
+
+    let region = &applied.get_instruments()[instrument_id].get_regions()[0];
+    assert_eq!(region.get_coarse_tune(), -12);
+    let frequency = flute_frequency(applied);
+    assert!((frequency - 220.0).abs() < 10.0);
+}
+
+#[test]
+fn region_override_wins_over_all_regions() {
+    let sound_font = SoundFont::test_bank();
+    let mut overrides = GeneratorOverrides::new();
+    overrides.set_preset_generator(
+        0,
+        synth_util::FLUTE,
+        Some(0),
+        GeneratorType::COARSE_TUNE,
+        12,
+    );
+    overrides.set_preset_generator(0, synth_util::FLUTE, None, GeneratorType::COARSE_TUNE, 24);
+    let frequency = flute_frequency(overrides.apply(&sound_font));
+    assert!((frequency - 880.0).abs() < 10.0);
+}
+
+#[test]
+fn apply_does_not_modify_original() {
+    let sound_font = SoundFont::test_bank();
+    let instrument_id = flute_instrument_id(&sound_font);
+    let mut overrides = GeneratorOverrides::new();
+    overrides.set_instrument_generator(instrument_id, None, GeneratorType::COARSE_TUNE, 12);
+    overrides.apply(&sound_font);
+
+    let region = &sound_font.get_instruments()[instrument_id].get_regions()[0];
+    assert_eq!(region.get_coarse_tune(), 0);
+    let frequency = flute_frequency(sound_font);
+    assert!((frequency - 440.0).abs() < 10.0);
+}
+
+#[test]
+fn unmatched_overrides_are_ignored() {
+    let sound_font = SoundFont::test_bank();
+    let mut overrides = GeneratorOverrides::new();
+    overrides.set_preset_generator(5, 99, None, GeneratorType::COARSE_TUNE, 12);
+    overrides.set_preset_generator(
+        0,
+        synth_util::FLUTE,
+        Some(99),
+        GeneratorType::COARSE_TUNE,
+        12,
+    );
+    overrides.set_instrument_generator(9999, None, GeneratorType::COARSE_TUNE, 12);
+    let frequency = flute_frequency(overrides.apply(&sound_font));
+    assert!((frequency - 440.0).abs() < 10.0);
+}
+
+#[test]
+#[should_panic]
+fn sample_modes_in_preset_panics() {
+    let mut overrides = GeneratorOverrides::new();
+    overrides.set_preset_generator(0, 0, None, GeneratorType::SAMPLE_MODES, 1);
+}
@@ -0,0 +1,87 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::synth_util;
+use rustysynth::DiagnosticCode;
+use rustysynth::DiagnosticSeverity;
+use rustysynth::SoundFont;
+use rustysynth::SoundFontInspector;
+use std::io::Cursor;
+
+fn inspect(data: &[u8]) -> SoundFontInspector {
+    SoundFontInspector::new(&mut Cursor::new(data)).unwrap()
+}
+
+#[test]
+fn inspector_reads_chunk_tree() {
+    let data = synth_util::test_bank_file();
+    let inspector = inspect(&data);
+    assert_eq!(inspector.get_stream_length(), data.len() as u64);
+    assert!(inspector.get_diagnostics().is_empty());
+
+    let root = inspector.get_root();
+    assert_eq!(root.get_id(), b"RIFF");
+    assert_eq!(root.get_list_type().unwrap(), b"sfbk");
+    assert_eq!(root.get_offset(), 0);
+    assert_eq!(root.get_size() as usize, data.len() - 8);
+    let lists: Vec<_> = root
+        .get_children()
+        .iter()
+        .map(|x| x.get_list_type().unwrap())
+        .collect();
+    assert_eq!(lists, [b"INFO", b"sdta", b"pdta"]);
+
+    // The offsets point to the chunk headers in the stream.
+    let phdr = root.find(b"pdta").unwrap().find(b"phdr").unwrap();
+    let offset = phdr.get_offset() as usize;
+    assert_eq!(&data[offset..offset + 4], b"phdr");
+    let preset_count = SoundFont::test_bank().get_presets().len();
+    assert_eq!(phdr.get_size() as usize, 38 * (preset_count + 1));
+    assert!(phdr.get_text().is_none());
+    assert!(phdr.get_children().is_empty());
+}
+
+#[test]
+fn inspector_reads_info_texts() {
+    let inspector = inspect(&synth_util::test_bank_file());
+    let fields = inspector.get_info_fields();
+    let version = fields.iter().find(|(id, _)| *id == b"ifil").unwrap().1;
+    assert_eq!(version.split('.').count(), 2);
+
+    let info = inspector.get_root().find(b"INFO").unwrap();
+    assert_eq!(info.find(b"ifil").unwrap().get_text(), Some(version));
+    assert_eq!(fields.len(), info.get_children().len());
+}
+
+#[test]
+fn inspector_reports_truncated_chunk() {
+    let data = synth_util::test_bank_file();
+    let data = &data[..data.len() - 100];
+    assert!(SoundFont::from_bytes(data).is_err());
+
+    let inspector = inspect(data);
+    let diagnostics = inspector.get_diagnostics();
+    assert!(diagnostics.has_errors());
+    assert!(diagnostics
+        .get_items()
+        .iter()
+        .any(|x| x.get_code() == DiagnosticCode::SoundFontTruncatedChunk));
+}
+
+#[test]
+fn inspector_reports_short_sm24() {
+    let inspector = inspect(&synth_util::test_bank_file_with_sm24(0, 10));
+    let diagnostics = inspector.get_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    let diagnostic = &diagnostics.get_items()[0];
+    assert_eq!(
+        diagnostic.get_code(),
+        DiagnosticCode::SoundFont24BitSamplesIgnored
+    );
+    assert_eq!(diagnostic.get_severity(), DiagnosticSeverity::Warning);
+}
+
+#[test]
+fn stream_without_riff_is_error() {
+    assert!(SoundFontInspector::new(&mut Cursor::new(b"MThd\0\0\0\x06")).is_err());
+}
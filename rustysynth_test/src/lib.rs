@@ -1,6 +1,10 @@
 mod instrument_util;
+mod midi_util;
 mod preset_util;
 mod sample_util;
+mod render_util;
+mod synth_util;
+mod wave_util;
 
 mod timgm6mb_info_test;
 mod timgm6mb_instrument_test;
@@ -13,3 +17,71 @@ mod musescore_preset_test;
 mod musescore_sample_test;
 
 mod soundfont3_test;
+
+mod test_bank_test;
+
+mod midifile_test;
+
+mod midifile_writer_test;
+
+mod midifile_lenient_test;
+
+mod analysis_test;
+
+mod transform_test;
+
+mod sample_range_test;
+
+mod parser_limits_test;
+
+mod soundfont_error_test;
+
+mod sharing_test;
+
+mod time_stretch_test;
+
+mod sequencer_test;
+
+mod render_test;
+
+mod diagnostics_test;
+
+mod soundfont_writer_test;
+
+mod prelude_test;
+
+mod synthesizer_test;
+
+mod mapped_test;
+
+mod bytes_test;
+
+mod capi_test;
+
+mod automation_test;
+
+mod sm24_test;
+
+mod bench_test;
+
+mod arpeggiator_test;
+
+mod batch_test;
+
+mod playlist_test;
+
+mod lyrics_test;
+
+mod generator_overrides_test;
+
+mod voice_source_test;
+
+mod async_test;
+
+mod partial_test;
+
+mod archive_test;
+
+mod inspector_test;
+
+mod wave_options_test;
@@ -0,0 +1,130 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use rustysynth::MidiFile;
+
+// A text meta event of the kind after the delta time of 1 byte.
+fn text(delta: u8, kind: u8, value: &str) -> Vec<u8> {
+    let mut event = vec![delta, 0xFF, kind, value.len() as u8];
+    event.extend(value.as_bytes());
+    event
+}
+
+fn with_end_of_track(events: &[Vec<u8>]) -> Vec<u8> {
+    let mut data = events.concat();
+    data.extend([0, 0xFF, 0x2F, 0]);
+    data
+}
+
+// A karaoke file, where the syllables are a quarter of a beat apart at 120 BPM.
+fn karaoke() -> MidiFile {
+    let mut data = b"MThd\0\0\0\x06\0\x01\0\x02\x00\x78".to_vec();
+    data.extend(midi_util::track(&with_end_of_track(&[])));
+    data.extend(midi_util::track(&with_end_of_track(&[
+        text(0, 0x01, "@KMIDI KARAOKE FILE"),
+        text(0, 0x01, "@LENGL"),
+        text(0, 0x01, "@TSong"),
+        text(0, 0x01, "@TArtist"),
+        text(0, 0x01, "@IThe information"),
+        text(0, 0x01, "Hel"),
+        text(30, 0x01, "lo "),
+        text(30, 0x01, "/world"),
+        text(30, 0x01, "\\Next"),
+        text(30, 0x01, "song"),
+    ])));
+    MidiFile::from_bytes(&data).unwrap()
+}
+
+#[test]
+fn karaoke_lyrics_are_read_from_text_events() {
+    let midi_file = karaoke();
+    let lyrics = midi_file.get_lyrics();
+    assert!(lyrics.is_karaoke());
+    assert!(!lyrics.is_empty());
+    assert_eq!(lyrics.get_titles(), ["Song", "Artist"]);
+    assert_eq!(lyrics.get_information(), ["The information"]);
+    assert_eq!(lyrics.get_language(), Some("ENGL"));
+
+    let syllables: Vec<_> = lyrics
+        .get_syllables()
+        .iter()
+        .map(|x| {
+            (
+                x.get_time(),
+                x.get_text(),
+                x.is_line_start(),
+                x.is_paragraph_start(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        syllables,
+        [
+            (0.0, "Hel", true, true),
+            (0.125, "lo ", false, false),
+            (0.25, "world", true, false),
+            (0.375, "Next", true, true),
+            (0.5, "song", false, false)
+        ]
+    );
+}
+
+#[test]
+fn syllables_follow_playback_position() {
+    let midi_file = karaoke();
+    let lyrics = midi_file.get_lyrics();
+
+    assert_eq!(lyrics.get_syllable_index(0.1), Some(0));
+    assert_eq!(lyrics.get_syllable_index(0.125), Some(1));
+    assert_eq!(lyrics.get_syllable_index(10.0), Some(4));
+    assert_eq!(lyrics.get_line(0), 0..2);
+    assert_eq!(lyrics.get_line(1), 0..2);
+    assert_eq!(lyrics.get_line(2), 2..3);
+    assert_eq!(lyrics.get_line(4), 3..5);
+}
+
+#[test]
+fn lyric_events_break_lines_by_line_feeds() {
+    let midi_file = MidiFile::from_bytes(&midi_util::format0(&with_end_of_track(&[
+        text(0x78, 0x05, "One "),
+        text(0x78, 0x05, "two\r"),
+        text(0x78, 0x05, "three"),
+        text(0x78, 0x05, "\r\n\r\nfour"),
+    ])))
+    .unwrap();
+
+    let lyrics = midi_file.get_lyrics();
+    assert!(!lyrics.is_karaoke());
+    assert_eq!(lyrics.get_syllable_index(0.0), None);
+
+    let syllables: Vec<_> = lyrics
+        .get_syllables()
+        .iter()
+        .map(|x| (x.get_text(), x.is_line_start(), x.is_paragraph_start()))
+        .collect();
+    assert_eq!(
+        syllables,
+        [
+            ("One ", true, true),
+            ("two", false, false),
+            ("three", true, false),
+            ("four", true, true)
+        ]
+    );
+}
+
+#[test]
+fn file_without_lyrics_has_empty_lyrics() {
+    let midi_file = MidiFile::from_bytes(&midi_util::flute_note(69)).unwrap();
+    let lyrics = midi_file.get_lyrics();
+    assert!(lyrics.is_empty());
+    assert!(!lyrics.is_karaoke());
+    assert_eq!(lyrics.get_language(), None);
+}
+
+#[test]
+#[should_panic(expected = "The syllable index is out of range.")]
+fn line_of_invalid_syllable_panics() {
+    karaoke().get_lyrics().get_line(5);
+}
@@ -0,0 +1,31 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::render_util::TempFile;
+use crate::synth_util;
+use rustysynth::SoundFont;
+use rustysynth::Synthesizer;
+use std::fs::File;
+use std::io::Cursor;
+use std::sync::Arc;
+
+#[test]
+fn mapped_sound_font_sounds_same() {
+    let data = synth_util::test_bank_file();
+    let file = TempFile::new("mapped", &data);
+
+    let loaded = SoundFont::new(&mut Cursor::new(&data)).unwrap();
+    let mapped = unsafe { SoundFont::new_mapped(&File::open(file.get_path()).unwrap()) }.unwrap();
+    assert!(!loaded.is_mapped());
+    assert!(mapped.is_mapped());
+
+    assert_eq!(render(loaded), render(mapped));
+}
+
+fn render(sound_font: SoundFont) -> Vec<f32> {
+    let settings = synth_util::settings();
+    let mut synthesizer = Synthesizer::new(&Arc::new(sound_font), &settings).unwrap();
+    synthesizer.process_midi_message(0, 0xC0, synth_util::FLUTE, 0);
+    synthesizer.note_on(0, 69, 100);
+    synth_util::render(&mut synthesizer, 0.2)
+}
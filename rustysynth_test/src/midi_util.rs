@@ -0,0 +1,98 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use rustysynth::MidiEvent;
+use rustysynth::MidiFile;
+use rustysynth::MidiMessage;
+use rustysynth::MidiTrack;
+
+pub fn track(events: &[u8]) -> Vec<u8> {
+    let mut data = b"MTrk".to_vec();
+    data.extend((events.len() as u32).to_be_bytes());
+    data.extend(events);
+    data
+}
+
+// A file with the specified content of the header chunk and a single track.
+pub fn with_header(header: &[u8], events: &[u8]) -> Vec<u8> {
+    let mut data = b"MThd".to_vec();
+    data.extend((header.len() as u32).to_be_bytes());
+    data.extend(header);
+    data.extend(track(events));
+    data
+}
+
+// A 3/4 file where the tempo changes from 120 BPM to 60 BPM after two beats.
+pub const TEMPO_CHANGE: [u8; 28] = [
+    0, 0xFF, 0x51, 3, 0x07, 0xA1, 0x20, 0, 0xFF, 0x58, 4, 3, 2, 24, 8, 0x87, 0x40, 0xFF, 0x51, 3,
+    0x0F, 0x42, 0x40, 0x87, 0x40, 0xFF, 0x2F, 0,
+];
+
+// A format 0 file with 480 ticks per beat, which is at 120 BPM unless the events change the tempo.
+pub fn format0(events: &[u8]) -> Vec<u8> {
+    with_header(b"\0\x00\0\x01\x01\xE0", events)
+}
+
+// A format 0 file of one second, where the loop starts at 0.5 seconds by CC #111.
+// The flute plays the first note until 0.25 seconds, and the second note from the loop start to the end.
+pub fn looped() -> Vec<u8> {
+    format0(&[
+        0, 0xC0, 72, 0, 0x90, 69, 100, 0x81, 0x70, 0x80, 69, 0, 0x81, 0x70, 0xB0, 111, 0, 0, 0x90,
+        76, 100, 0x83, 0x60, 0x80, 76, 0, 0, 0xFF, 0x2F, 0,
+    ])
+}
+
+// A format 0 file where the flute plays the key for 0.5 seconds.
+pub fn flute_note(key: u8) -> Vec<u8> {
+    format0(&[
+        0, 0xC0, 72, 0, 0x90, key, 100, 0x83, 0x60, 0x80, key, 0, 0, 0xFF, 0x2F, 0,
+    ])
+}
+
+// A format 1 file where the tempo track also has a note,
+// another track has the same note, and the last track has a tempo change of its own.
+pub fn format1_with_notes_in_tempo_track() -> Vec<u8> {
+    let mut data = b"MThd\0\0\0\x06\0\x01\0\x03\x01\xE0".to_vec();
+    data.extend(track(&[
+        0, 0xFF, 0x51, 3, 0x07, 0xA1, 0x20, 0, 0x90, 60, 100, 0x83, 0x60, 0x80, 60, 0, 0, 0xFF,
+        0x2F, 0,
+    ]));
+    data.extend(track(&[
+        0, 0x90, 60, 100, 0x83, 0x60, 0x80, 60, 0, 0, 0xFF, 0x2F, 0,
+    ]));
+    data.extend(track(&[
+        0, 0x90, 62, 100, 0x83, 0x60, 0xFF, 0x51, 3, 0x07, 0xA1, 0x20, 0, 0x80, 62, 0, 0, 0xFF,
+        0x2F, 0,
+    ]));
+    data
+}
+
+pub fn note_on_count(midi_file: &MidiFile, index: usize) -> usize {
+    midi_file.get_tracks()[index]
+        .get_events()
+        .filter(|(_, event)| matches!(event, MidiEvent::Message(MidiMessage { command: 0x90, .. })))
+        .count()
+}
+
+// Gets the times and the keys of the note-on events in the track.
+pub fn note_ons(track: &MidiTrack) -> Vec<(f64, u8)> {
+    track
+        .get_events()
+        .filter_map(|(time, event)| match event {
+            MidiEvent::Message(MidiMessage {
+                command: 0x90,
+                data1,
+                ..
+            }) => Some((time, data1)),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn events(midi_file: &MidiFile) -> Vec<Vec<(f64, MidiEvent)>> {
+    midi_file
+        .get_tracks()
+        .iter()
+        .map(|track| track.get_events().collect())
+        .collect()
+}
@@ -0,0 +1,87 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use rustysynth::MidiFile;
+use rustysynth::MidiFileOptions;
+use rustysynth::MidiFileWarning;
+
+const NOTE: [u8; 13] = [0, 0x90, 60, 100, 0x83, 0x60, 0x80, 60, 0, 0, 0xFF, 0x2F, 0];
+
+fn lenient(data: &[u8]) -> MidiFile {
+    let mut options = MidiFileOptions::new();
+    options.lenient = true;
+    MidiFile::new_with_options(&mut &data[..], &options).unwrap()
+}
+
+#[test]
+fn truncated_track_is_loaded_in_lenient_mode() {
+    // The size of the track is kept, but the end of track is cut off.
+    let mut data = midi_util::format0(&NOTE);
+    data.truncate(data.len() - 4);
+
+    assert!(MidiFile::from_bytes(&data).is_err());
+
+    let midi_file = lenient(&data);
+    assert_eq!(
+        midi_file.get_warnings(),
+        [
+            MidiFileWarning::TruncatedTrack(0),
+            MidiFileWarning::MissingEndOfTrack(0)
+        ]
+    );
+    assert_eq!(midi_util::note_ons(&midi_file.get_tracks()[0]), [(0.0, 60)]);
+    assert_eq!(midi_file.get_diagnostics().len(), 2);
+}
+
+#[test]
+fn missing_end_of_track_is_reported() {
+    let data = midi_util::format0(&NOTE[..NOTE.len() - 4]);
+
+    let midi_file = lenient(&data);
+    assert_eq!(
+        midi_file.get_warnings(),
+        [MidiFileWarning::MissingEndOfTrack(0)]
+    );
+    assert_eq!(midi_file.get_length(), 0.5);
+}
+
+#[test]
+fn missing_tracks_are_reported() {
+    let mut data = b"MThd\0\0\0\x06\0\x01\0\x02\x01\xE0".to_vec();
+    data.extend(midi_util::track(&NOTE));
+
+    assert!(MidiFile::from_bytes(&data).is_err());
+
+    let midi_file = lenient(&data);
+    assert_eq!(
+        midi_file.get_warnings(),
+        [MidiFileWarning::MissingTracks {
+            expected: 2,
+            actual: 1
+        }]
+    );
+    assert_eq!(midi_file.get_tracks().len(), 1);
+}
+
+#[test]
+fn unknown_chunk_is_skipped() {
+    let mut data = b"MThd\0\0\0\x06\0\x00\0\x01\x01\xE0".to_vec();
+    data.extend(b"XFIH\0\0\0\x02\x12\x34");
+    data.extend(midi_util::track(&NOTE));
+
+    let midi_file = lenient(&data);
+    assert!(matches!(
+        midi_file.get_warnings(),
+        [MidiFileWarning::UnknownChunk(id)] if *id == b"XFIH"
+    ));
+    assert_eq!(midi_util::note_ons(&midi_file.get_tracks()[0]), [(0.0, 60)]);
+}
+
+#[test]
+fn strict_mode_has_no_warnings() {
+    let midi_file = MidiFile::from_bytes(&midi_util::format0(&NOTE)).unwrap();
+
+    assert!(midi_file.get_warnings().is_empty());
+    assert!(midi_file.get_diagnostics().is_empty());
+}
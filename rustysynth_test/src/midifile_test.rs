@@ -0,0 +1,348 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use rustysynth::MidiEvent;
+use rustysynth::MidiFile;
+use rustysynth::MidiFileError;
+use rustysynth::MidiFileSequencer;
+use rustysynth::MidiMessage;
+use rustysynth::MidiTrack;
+use rustysynth::SoundFont;
+use rustysynth::Synthesizer;
+use rustysynth::SynthesizerSettings;
+use std::io::Cursor;
+use std::sync::Arc;
+
+#[test]
+fn tempo_track_is_not_merged_into_itself() {
+    let midi_file = MidiFile::new(&mut Cursor::new(
+        midi_util::format1_with_notes_in_tempo_track(),
+    ))
+    .unwrap();
+
+    // The note on, the note off, and the end of track.
+    assert_eq!(midi_file.get_tracks()[0].get_event_count(), 3);
+    assert_eq!(midi_util::note_on_count(&midi_file, 0), 1);
+
+    // The other tracks contain the note of the tempo track.
+    assert_eq!(midi_util::note_on_count(&midi_file, 1), 2);
+    assert_eq!(midi_util::note_on_count(&midi_file, 2), 2);
+}
+
+#[test]
+fn events_at_same_tick_keep_file_order() {
+    // The tempo track has a time signature, and each channel is set up before its note.
+    // There are enough events at the same tick for the order not to be kept by chance.
+    let mut data = b"MThd\0\0\0\x06\0\x01\0\x02\x01\xE0".to_vec();
+    data.extend(midi_util::track(&[
+        0, 0xFF, 0x51, 3, 0x07, 0xA1, 0x20, 0, 0xFF, 0x58, 4, 3, 2, 24, 8, 0, 0xFF, 0x2F, 0,
+    ]));
+    let mut events = Vec::new();
+    for channel in 0..16 {
+        events.extend([0, 0xC0 | channel, 48, 0, 0xB0 | channel, 7, 100]);
+        events.extend([0, 0xB0 | channel, 10, 64, 0, 0x90 | channel, 68, 100]);
+    }
+    events.extend([0x83, 0x60, 0xFF, 0x2F, 0]);
+    data.extend(midi_util::track(&events));
+    let midi_file = MidiFile::new(&mut Cursor::new(data)).unwrap();
+
+    for channel in 0..16 {
+        let messages: Vec<(u8, u8)> = midi_file.get_tracks()[1]
+            .get_events()
+            .filter_map(|(_, event)| match event {
+                MidiEvent::Message(MidiMessage {
+                    channel: x,
+                    command,
+                    data1,
+                    ..
+                }) if x == channel => Some((command, data1)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(messages, [(0xC0, 48), (0xB0, 7), (0xB0, 10), (0x90, 68)]);
+    }
+
+    // The time signature is only in the tempo map.
+    assert_eq!(midi_file.get_tempo_map().get_time_signatures().len(), 1);
+}
+
+#[test]
+fn tracks_are_in_file_order() {
+    let mut data = b"MThd\0\0\0\x06\0\x01\0\x03\x01\xE0".to_vec();
+    data.extend(midi_util::track(&[
+        0, 0xFF, 0x51, 3, 0x07, 0xA1, 0x20, 0, 0xFF, 0x2F, 0,
+    ]));
+    data.extend(midi_util::track(&[
+        0, 0x90, 60, 100, 0x83, 0x60, 0x80, 60, 0, 0, 0xFF, 0x2F, 0,
+    ]));
+    data.extend(midi_util::track(&[
+        0, 0x91, 62, 100, 0x83, 0x60, 0x81, 62, 0, 0, 0xFF, 0x2F, 0,
+    ]));
+    let midi_file = MidiFile::new(&mut Cursor::new(data)).unwrap();
+
+    assert_eq!(midi_file.get_format(), 1);
+    assert_eq!(midi_file.get_pattern_count(), 0);
+    assert_eq!(midi_file.get_tracks().len(), 3);
+    assert!(midi_util::note_ons(&midi_file.get_tracks()[0]).is_empty());
+    assert_eq!(midi_util::note_ons(&midi_file.get_tracks()[1]), [(0.0, 60)]);
+    assert_eq!(midi_util::note_ons(&midi_file.get_tracks()[2]), [(0.0, 62)]);
+}
+
+#[test]
+fn format0_has_single_track() {
+    let mut data = b"MThd\0\0\0\x06\0\x00\0\x01\x01\xE0".to_vec();
+    data.extend(midi_util::track(&[
+        0, 0x90, 60, 100, 0, 0x99, 36, 100, 0x83, 0x60, 0x80, 60, 0, 0, 0x89, 36, 0, 0, 0xFF, 0x2F,
+        0,
+    ]));
+    let midi_file = MidiFile::new(&mut Cursor::new(data)).unwrap();
+
+    assert_eq!(midi_file.get_format(), 0);
+    assert_eq!(midi_file.get_tracks().len(), 1);
+    assert_eq!(
+        midi_util::note_ons(&midi_file.get_tracks()[0]),
+        [(0.0, 60), (0.0, 36)]
+    );
+    assert_eq!(midi_file.get_length(), 0.5);
+}
+
+// A format 2 file with a pattern of 0.5 seconds at 120 BPM and one of 1 second at 60 BPM.
+fn format2_with_two_patterns() -> MidiFile {
+    let mut data = b"MThd\0\0\0\x06\0\x02\0\x02\x01\xE0".to_vec();
+    data.extend(midi_util::track(&[
+        0, 0xFF, 0x51, 3, 0x07, 0xA1, 0x20, 0, 0x90, 60, 100, 0x83, 0x60, 0x80, 60, 0, 0, 0xFF,
+        0x2F, 0,
+    ]));
+    data.extend(midi_util::track(&[
+        0, 0xFF, 0x51, 3, 0x0F, 0x42, 0x40, 0, 0x90, 64, 100, 0x83, 0x60, 0x80, 64, 0, 0, 0xFF,
+        0x2F, 0,
+    ]));
+    MidiFile::new(&mut Cursor::new(data)).unwrap()
+}
+
+#[test]
+fn format2_patterns_are_in_file_order() {
+    let midi_file = format2_with_two_patterns();
+
+    assert_eq!(midi_file.get_format(), 2);
+    assert_eq!(midi_file.get_pattern_count(), 2);
+    assert_eq!(midi_util::note_ons(&midi_file.get_tracks()[0]), [(0.0, 60)]);
+    assert_eq!(midi_util::note_ons(&midi_file.get_tracks()[1]), [(0.0, 64)]);
+    assert_eq!(midi_file.get_tracks()[0].get_length(), 0.5);
+    assert_eq!(midi_file.get_tracks()[1].get_length(), 1.0);
+}
+
+#[test]
+fn selected_patterns_are_played_in_order() {
+    let midi_file = format2_with_two_patterns();
+    let sound_font = Arc::new(SoundFont::test_bank());
+    let settings = SynthesizerSettings::new(44100);
+    let mut sequencer = MidiFileSequencer::new(Synthesizer::new(&sound_font, &settings).unwrap());
+
+    sequencer.play_patterns(&midi_file, &[1, 0, 1], false);
+    let track = sequencer.get_midi_track().unwrap();
+    assert_eq!(
+        midi_util::note_ons(track),
+        [(0.0, 64), (1.0, 60), (1.5, 64)]
+    );
+    assert_eq!(track.get_length(), 2.5);
+
+    sequencer.play_pattern(&midi_file, 0, false);
+    let track = sequencer.get_midi_track().unwrap();
+    assert_eq!(midi_util::note_ons(track), [(0.0, 60)]);
+}
+
+#[test]
+#[should_panic(expected = "The pattern index is out of range.")]
+fn selecting_missing_pattern_panics() {
+    let midi_file = format2_with_two_patterns();
+    let sound_font = Arc::new(SoundFont::test_bank());
+    let settings = SynthesizerSettings::new(44100);
+    let mut sequencer = MidiFileSequencer::new(Synthesizer::new(&sound_font, &settings).unwrap());
+
+    sequencer.play_patterns(&midi_file, &[0, 2], false);
+}
+
+// A note of 480 ticks, preceded by a tempo of 60 BPM.
+const NOTE_AT_60_BPM: [u8; 20] = [
+    0, 0xFF, 0x51, 3, 0x0F, 0x42, 0x40, 0, 0x90, 60, 100, 0x83, 0x60, 0x80, 60, 0, 0, 0xFF, 0x2F, 0,
+];
+
+#[test]
+fn smpte_division_is_independent_of_tempo() {
+    // 25 frames per second with 40 ticks per frame, which is 1 ms per tick.
+    let data = midi_util::with_header(b"\0\0\0\x01\xE7\x28", &NOTE_AT_60_BPM);
+    let midi_file = MidiFile::from_bytes(&data).unwrap();
+
+    assert!((midi_file.get_length() - 0.48).abs() < 1.0E-9);
+    assert!((midi_file.get_tracks()[0].get_length() - 0.48).abs() < 1.0E-9);
+}
+
+#[test]
+fn smpte_division_without_ticks_is_rejected() {
+    // 25 frames per second with zero ticks per frame.
+    let data = midi_util::with_header(b"\0\0\0\x01\xE7\0", &NOTE_AT_60_BPM);
+    let result = MidiFile::from_bytes(&data);
+
+    assert!(matches!(result, Err(MidiFileError::InvalidChunkData(id)) if id == b"MThd"));
+}
+
+#[test]
+fn tempo_map_converts_positions() {
+    let midi_file = MidiFile::from_bytes(&midi_util::format0(&midi_util::TEMPO_CHANGE)).unwrap();
+    let tempo_map = midi_file.get_tempo_map();
+
+    assert_eq!(tempo_map.get_resolution(), 480);
+    let last = tempo_map.get_tempo_changes().last().unwrap();
+    assert_eq!(last.get_tick(), 960);
+    assert_eq!(last.get_time(), 1.0);
+    assert_eq!(last.get_tempo(), 60.0);
+    let time_signature = &tempo_map.get_time_signatures()[0];
+    assert_eq!(time_signature.get_tick(), 0);
+    assert_eq!(time_signature.get_numerator(), 3);
+    assert_eq!(time_signature.get_denominator(), 4);
+
+    assert_eq!(tempo_map.get_tempo_at(0.5), 120.0);
+    assert_eq!(tempo_map.get_tempo_at(1.5), 60.0);
+    assert_eq!(tempo_map.ticks_to_seconds(1440.0), 2.0);
+    assert_eq!(tempo_map.seconds_to_ticks(2.0), 1440.0);
+
+    // A bar of 3/4 is 1440 ticks.
+    assert_eq!(tempo_map.ticks_to_bars(2160.0), Some((1, 1.5)));
+    assert_eq!(tempo_map.bars_to_ticks(1, 1.5), Some(2160.0));
+    assert_eq!(tempo_map.seconds_to_bars(1.0), Some((0, 2.0)));
+    assert_eq!(tempo_map.bars_to_seconds(1, 0.0), Some(2.0));
+}
+
+#[test]
+fn smpte_division_has_no_bars() {
+    let data = midi_util::with_header(b"\0\0\0\x01\xE7\x28", &NOTE_AT_60_BPM);
+    let midi_file = MidiFile::from_bytes(&data).unwrap();
+    let tempo_map = midi_file.get_tempo_map();
+
+    assert!(tempo_map.get_resolution() < 0);
+    assert_eq!(tempo_map.ticks_to_seconds(1000.0), 1.0);
+    assert_eq!(tempo_map.ticks_to_bars(1000.0), None);
+    assert_eq!(tempo_map.bars_to_seconds(1, 0.0), None);
+}
+
+#[test]
+fn port_meta_event_moves_channels() {
+    // The note on the channel 1 is sent to the second port.
+    let data = midi_util::format0(&[
+        0, 0x90, 60, 100, 0, 0xFF, 0x21, 1, 1, 0, 0x91, 62, 100, 0, 0xFF, 0x2F, 0,
+    ]);
+    let midi_file = MidiFile::from_bytes(&data).unwrap();
+    assert_eq!(midi_file.get_port_count(), 2);
+
+    let channels: Vec<u8> = midi_file.get_tracks()[0]
+        .get_events()
+        .filter_map(|(_, event)| match event {
+            MidiEvent::Message(message) => Some(message.channel),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(channels, [0, 17]);
+}
+
+#[test]
+fn file_without_port_has_single_port() {
+    let midi_file = MidiFile::from_bytes(&midi_util::flute_note(69)).unwrap();
+    assert_eq!(midi_file.get_port_count(), 1);
+}
+
+#[test]
+fn running_status_survives_meta_events() {
+    // The second note-on has no status byte after a marker.
+    let data = midi_util::format0(&[
+        0, 0x90, 60, 100, 0, 0xFF, 0x06, 1, b'A', 0, 62, 100, 0, 0xFF, 0x2F, 0,
+    ]);
+    let midi_file = MidiFile::from_bytes(&data).unwrap();
+    assert_eq!(
+        midi_util::note_ons(&midi_file.get_tracks()[0]),
+        [(0.0, 60), (0.0, 62)]
+    );
+}
+
+#[test]
+fn realtime_bytes_are_skipped() {
+    // The clock bytes are found between the events and the data bytes.
+    let data = midi_util::format0(&[
+        0, 0xF8, 0, 0x90, 0xF8, 60, 0xFE, 100, 0, 0xF2, 0, 0, 0, 0xFF, 0x2F, 0,
+    ]);
+    let midi_file = MidiFile::from_bytes(&data).unwrap();
+    assert_eq!(midi_util::note_ons(&midi_file.get_tracks()[0]), [(0.0, 60)]);
+}
+
+#[test]
+fn empty_track_is_loaded() {
+    let mut data = b"MThd\0\0\0\x06\0\x01\0\x02\x01\xE0".to_vec();
+    data.extend(midi_util::track(&[]));
+    data.extend(midi_util::track(&[
+        0, 0x90, 60, 100, 0x83, 0x60, 0x80, 60, 0, 0, 0xFF, 0x2F, 0,
+    ]));
+    let midi_file = MidiFile::from_bytes(&data).unwrap();
+    assert_eq!(midi_file.get_tracks().len(), 2);
+    assert!(midi_util::note_ons(&midi_file.get_tracks()[0]).is_empty());
+    assert_eq!(midi_file.get_length(), 0.5);
+}
+
+#[test]
+fn track_without_end_of_track_is_error() {
+    let data = midi_util::format0(&[0, 0x90, 60, 100]);
+    assert!(MidiFile::from_bytes(&data).is_err());
+}
+
+#[test]
+fn track_error_reports_position() {
+    // The second track ends without the EOT event after a note of 480 ticks.
+    let mut data = b"MThd\0\0\0\x06\0\x01\0\x02\x01\xE0".to_vec();
+    data.extend(midi_util::track(&[0, 0xFF, 0x2F, 0]));
+    data.extend(midi_util::track(&[
+        0, 0x90, 60, 100, 0x83, 0x60, 0x80, 60, 0,
+    ]));
+
+    let error = MidiFile::from_bytes(&data).err().unwrap();
+    match &error {
+        MidiFileError::TrackError {
+            track,
+            offset,
+            tick,
+            error,
+        } => {
+            assert_eq!((*track, *offset, *tick), (1, 17, 480));
+            assert!(matches!(error.as_ref(), MidiFileError::IoError(_)));
+        }
+        _ => panic!("The error must have the position."),
+    }
+    assert!(error
+        .to_string()
+        .starts_with("track 1, offset 0x11, tick 480: "));
+    assert!(std::error::Error::source(&error).is_some());
+}
+
+#[test]
+fn tempo_changes_in_several_tracks_are_merged() {
+    // The first track sets 60 BPM, and the second track sets 120 BPM after a beat.
+    let mut data = b"MThd\0\0\0\x06\0\x01\0\x03\x01\xE0".to_vec();
+    data.extend(midi_util::track(&[
+        0, 0xFF, 0x51, 3, 0x0F, 0x42, 0x40, 0, 0xFF, 0x2F, 0,
+    ]));
+    data.extend(midi_util::track(&[
+        0x83, 0x60, 0xFF, 0x51, 3, 0x07, 0xA1, 0x20, 0, 0xFF, 0x2F, 0,
+    ]));
+    data.extend(midi_util::track(&[
+        0x87, 0x40, 0x90, 60, 100, 0x83, 0x60, 0x80, 60, 0, 0, 0xFF, 0x2F, 0,
+    ]));
+    let midi_file = MidiFile::from_bytes(&data).unwrap();
+
+    let tempo_map = midi_file.get_tempo_map();
+    assert_eq!(tempo_map.get_tempo_changes().len(), 2);
+    assert_eq!(tempo_map.get_tempo_at(0.5), 60.0);
+    assert_eq!(tempo_map.get_tempo_at(1.5), 120.0);
+
+    // The note starts after a beat at each tempo.
+    assert_eq!(midi_file.get_length(), 2.0);
+    assert_eq!(midi_util::note_ons(&midi_file.get_tracks()[2]), [(1.5, 60)]);
+}
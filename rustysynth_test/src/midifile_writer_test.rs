@@ -0,0 +1,62 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use rustysynth::MidiEvent;
+use rustysynth::MidiFile;
+use rustysynth::MidiFileLoopType;
+
+fn assert_close(actual: &[(f64, u8)], expected: &[(f64, u8)]) {
+    assert_eq!(actual.len(), expected.len());
+    for (actual, expected) in actual.iter().zip(expected.iter()) {
+        // The written file has a resolution of about 0.5 ms.
+        assert!((actual.0 - expected.0).abs() < 1.0E-3);
+        assert_eq!(actual.1, expected.1);
+    }
+}
+
+#[test]
+fn written_file_has_same_notes() {
+    let midi_file = MidiFile::from_bytes(&midi_util::format1_with_notes_in_tempo_track()).unwrap();
+
+    let mut data = Vec::new();
+    midi_file.write(&mut data, 1).unwrap();
+    let written = MidiFile::from_bytes(&data).unwrap();
+
+    // The first track only contains the tempo.
+    assert_eq!(written.get_format(), 1);
+    assert_eq!(written.get_tracks().len(), midi_file.get_tracks().len() + 1);
+    assert!(midi_util::note_ons(&written.get_tracks()[0]).is_empty());
+    for (i, track) in midi_file.get_tracks().iter().enumerate() {
+        assert_close(
+            &midi_util::note_ons(&written.get_tracks()[i + 1]),
+            &midi_util::note_ons(track),
+        );
+    }
+    assert!((written.get_length() - midi_file.get_length()).abs() < 1.0E-3);
+}
+
+#[test]
+fn loop_is_unrolled() {
+    let midi_file = MidiFile::new_with_loop_type(
+        &mut midi_util::looped().as_slice(),
+        MidiFileLoopType::RpgMaker,
+    )
+    .unwrap();
+
+    let mut data = Vec::new();
+    midi_file.write(&mut data, 3).unwrap();
+    let written =
+        MidiFile::new_with_loop_type(&mut data.as_slice(), MidiFileLoopType::RpgMaker).unwrap();
+
+    // The loop section from 0.5 to 1 second is played three times, and the loop markers are removed.
+    let track = &written.get_tracks()[1];
+    assert_close(
+        &midi_util::note_ons(track),
+        &[(0.0, 69), (0.5, 76), (1.0, 76), (1.5, 76)],
+    );
+    assert!(track
+        .get_events()
+        .all(|(_, event)| event != MidiEvent::LoopStart && event != MidiEvent::LoopEnd));
+    assert!((written.get_length() - 2.0).abs() < 1.0E-3);
+}
@@ -0,0 +1,114 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use crate::synth_util;
+use rustysynth::MidiFile;
+use rustysynth::MidiFileError;
+use rustysynth::MidiFileOptions;
+use rustysynth::ParserLimits;
+use rustysynth::SoundFont;
+use rustysynth::SoundFontError;
+use std::io::Cursor;
+
+fn load(data: &[u8], options: &MidiFileOptions) -> Result<MidiFile, MidiFileError> {
+    MidiFile::new_with_options(&mut Cursor::new(data), options)
+}
+
+#[test]
+fn midi_file_with_too_many_tracks_is_rejected() {
+    let data = midi_util::format1_with_notes_in_tempo_track();
+    let mut options = MidiFileOptions::new();
+
+    options.limits.max_track_count = 3;
+    assert!(load(&data, &options).is_ok());
+
+    options.limits.max_track_count = 2;
+    assert!(matches!(
+        load(&data, &options),
+        Err(MidiFileError::TooManyTracks(3))
+    ));
+}
+
+#[test]
+fn midi_file_with_too_many_events_is_rejected() {
+    // The note on, the note off, and the end of track.
+    let data = midi_util::format0(&[0, 0x90, 60, 100, 0x83, 0x60, 0x80, 60, 0, 0, 0xFF, 0x2F, 0]);
+    let mut options = MidiFileOptions::new();
+
+    options.limits.max_event_count = 3;
+    assert!(load(&data, &options).is_ok());
+
+    options.limits.max_event_count = 2;
+    assert!(matches!(
+        load(&data, &options),
+        Err(MidiFileError::TooManyEvents)
+    ));
+}
+
+#[test]
+fn track_larger_than_file_is_rejected() {
+    // The track claims 4 GB, which must not be allocated.
+    let mut data = b"MThd\0\0\0\x06\0\x00\0\x01\x01\xE0MTrk\xFF\xFF\xFF\xFF".to_vec();
+    data.extend([0, 0xFF, 0x2F, 0]);
+
+    let result = MidiFile::from_bytes(&data);
+    assert!(matches!(result, Err(MidiFileError::ChunkTooLarge(id)) if id == b"MTrk"));
+}
+
+#[test]
+fn sound_font_chunk_larger_than_limit_is_rejected() {
+    let data = synth_util::test_bank_file();
+    let mut limits = ParserLimits::new();
+
+    limits.max_chunk_size = data.len();
+    assert!(SoundFont::new_with_limits(&mut Cursor::new(&data), &limits).is_ok());
+
+    limits.max_chunk_size = 1024;
+    assert!(matches!(
+        SoundFont::new_with_limits(&mut Cursor::new(&data), &limits),
+        Err(SoundFontError::ChunkTooLarge(_))
+    ));
+}
+
+#[test]
+fn broken_sound_fonts_do_not_panic() {
+    let data = synth_util::test_bank_file();
+
+    // The file is truncated at every 97 bytes.
+    for length in (0..data.len()).step_by(97) {
+        let _ = SoundFont::new(&mut Cursor::new(&data[..length]));
+    }
+
+    // The bytes are overwritten at random positions.
+    let mut seed: u32 = 1;
+    for _ in 0..200 {
+        let mut data = data.clone();
+        for _ in 0..8 {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            let position = (seed >> 8) as usize % data.len();
+            data[position] = (seed >> 24) as u8;
+        }
+        let _ = SoundFont::new(&mut Cursor::new(&data));
+    }
+}
+
+#[test]
+fn broken_midi_files_do_not_panic() {
+    let data = midi_util::format1_with_notes_in_tempo_track();
+
+    for length in 0..data.len() {
+        let _ = MidiFile::from_bytes(&data[..length]);
+    }
+
+    let mut seed: u32 = 1;
+    for _ in 0..1000 {
+        let mut data = data.clone();
+        for _ in 0..4 {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            let position = (seed >> 8) as usize % data.len();
+            data[position] = (seed >> 24) as u8;
+        }
+        let _ = MidiFile::from_bytes(&data);
+    }
+}
@@ -0,0 +1,122 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use crate::synth_util;
+use crate::synth_util::FLUTE;
+use crate::synth_util::NOISE;
+use rustysynth::MidiFile;
+use rustysynth::ParserLimits;
+use rustysynth::SoundFont;
+use rustysynth::SoundFontError;
+use rustysynth::Synthesizer;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::sync::Arc;
+
+// A stream which counts the bytes read, in place of a reader of HTTP range requests.
+struct CountingReader {
+    inner: Cursor<Vec<u8>>,
+    read_count: usize,
+}
+
+impl CountingReader {
+    fn new(data: Vec<u8>) -> Self {
+        Self {
+            inner: Cursor::new(data),
+            read_count: 0,
+        }
+    }
+}
+
+impl Read for CountingReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        self.read_count += count;
+        Ok(count)
+    }
+}
+
+impl Seek for CountingReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+// Plays the key 69 with the program on the first channel.
+fn play_program(sound_font: SoundFont, program: i32) -> Vec<f32> {
+    let settings = synth_util::settings();
+    let mut synthesizer = Synthesizer::new(&Arc::new(sound_font), &settings).unwrap();
+    synthesizer.process_midi_message(0, 0xC0, program, 0);
+    synthesizer.note_on(0, 69, 100);
+    synth_util::render(&mut synthesizer, 0.5)
+}
+
+#[test]
+fn partial_sound_font_reads_selected_samples() {
+    let data = synth_util::test_bank_file();
+    let mut reader = CountingReader::new(data.clone());
+    let sound_font = SoundFont::new_partial(&mut reader, &[(0, FLUTE)]).unwrap();
+    assert!(reader.read_count < data.len() / 2);
+
+    // The other presets are available, but silent.
+    assert_eq!(
+        sound_font.get_presets().len(),
+        SoundFont::test_bank().get_presets().len()
+    );
+    assert_eq!(
+        play_program(sound_font, FLUTE),
+        play_program(SoundFont::test_bank(), FLUTE)
+    );
+    let sound_font = SoundFont::new_partial(&mut Cursor::new(&data), &[(0, FLUTE)]).unwrap();
+    assert_eq!(synth_util::peak(&play_program(sound_font, NOISE)), 0.0);
+}
+
+#[test]
+fn partial_sound_font_resolves_missing_presets() {
+    // The missing bank falls back to the GM sound set.
+    let data = synth_util::test_bank_file();
+    let sound_font = SoundFont::new_partial(&mut Cursor::new(&data), &[(5, NOISE)]).unwrap();
+    assert_eq!(
+        play_program(sound_font, NOISE),
+        play_program(SoundFont::test_bank(), NOISE)
+    );
+}
+
+#[test]
+fn partial_sound_font_follows_limits() {
+    let data = synth_util::test_bank_file();
+    let mut limits = ParserLimits::new();
+    limits.max_chunk_size = 100;
+    let result =
+        SoundFont::new_partial_with_limits(&mut Cursor::new(&data), &[(0, FLUTE)], &limits);
+    assert!(result.is_err());
+}
+
+#[test]
+fn sound_font_for_midi_reads_used_presets() {
+    let data = synth_util::test_bank_file();
+    let midi_file = MidiFile::from_bytes(&midi_util::flute_note(69)).unwrap();
+    let mut reader = CountingReader::new(data.clone());
+    let sound_font = SoundFont::new_for_midi(&mut reader, &midi_file).unwrap();
+    assert!(reader.read_count < data.len() / 2);
+    assert_eq!(
+        play_program(sound_font, FLUTE),
+        play_program(SoundFont::test_bank(), FLUTE)
+    );
+
+    let sound_font = SoundFont::new_for_midi(&mut Cursor::new(&data), &midi_file).unwrap();
+    assert_eq!(synth_util::peak(&play_program(sound_font, NOISE)), 0.0);
+}
+
+#[test]
+fn sound_font_for_midi_follows_limits() {
+    let data = synth_util::test_bank_file();
+    let midi_file = MidiFile::from_bytes(&midi_util::flute_note(69)).unwrap();
+    let mut limits = ParserLimits::new();
+    limits.max_chunk_size = 100;
+    let result = SoundFont::new_for_midi_with_limits(&mut Cursor::new(&data), &midi_file, &limits);
+    assert!(result.is_err());
+}
@@ -0,0 +1,120 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use crate::synth_util;
+use rustysynth::MidiFile;
+use rustysynth::PlaylistRender;
+use rustysynth::PlaylistTransition;
+use rustysynth::RenderTail;
+use rustysynth::Sequence;
+
+fn playlist() -> PlaylistRender {
+    PlaylistRender::new(&synth_util::sound_font(), synth_util::settings())
+}
+
+fn flute_note() -> MidiFile {
+    MidiFile::from_bytes(&midi_util::flute_note(69)).unwrap()
+}
+
+fn range(data: &[f32], start: f64, end: f64) -> &[f32] {
+    &data[synth_util::samples(start)..synth_util::samples(end)]
+}
+
+fn max_difference(x: &[f32], y: &[f32]) -> f32 {
+    x.iter()
+        .zip(y.iter())
+        .fold(0_f32, |max, (x, y)| max.max((x - y).abs()))
+}
+
+#[test]
+fn gap_places_files_one_after_another() {
+    let mut single = playlist();
+    single.push(&flute_note());
+    let (expected, _) = single.render().unwrap();
+
+    let mut playlist = playlist();
+    assert_eq!(playlist.get_transition(), PlaylistTransition::Gap(0.0));
+    playlist.set_transition(PlaylistTransition::Gap(0.5));
+    playlist.push(&flute_note());
+    playlist.push(&flute_note());
+    assert_eq!(playlist.get_file_count(), 2);
+    assert_eq!(playlist.get_start_times(), [0.0, 1.0]);
+
+    let (left, _) = playlist.render().unwrap();
+    assert_eq!(left.len(), synth_util::samples(1.5));
+    assert!(max_difference(range(&left, 0.0, 0.5), &expected) < 1.0E-6);
+    assert!(max_difference(range(&left, 1.0, 1.5), &expected) < 1.0E-6);
+    assert!(synth_util::peak(range(&left, 0.75, 1.0)) < 0.001);
+    assert_eq!(playlist.get_render_progress().get_ratio(), 1.0);
+
+    playlist.clear();
+    assert_eq!(playlist.get_file_count(), 0);
+}
+
+#[test]
+fn crossfade_overlaps_files() {
+    let mut playlist = playlist();
+    playlist.set_transition(PlaylistTransition::Crossfade(0.25));
+    playlist.push(&flute_note());
+    playlist.push(&flute_note());
+    assert_eq!(playlist.get_start_times(), [0.0, 0.25]);
+
+    let (left, _) = playlist.render().unwrap();
+    assert_eq!(left.len(), synth_util::samples(0.75));
+    assert!(synth_util::rms(range(&left, 0.3, 0.45)) > 0.01);
+}
+
+#[test]
+fn tail_follows_last_file() {
+    let mut playlist = playlist();
+    assert_eq!(playlist.get_tail(), RenderTail::None);
+    playlist.set_tail(RenderTail::Fixed(0.5));
+    assert_eq!(playlist.get_tail(), RenderTail::Fixed(0.5));
+    playlist.push(&flute_note());
+
+    let (left, _) = playlist.render().unwrap();
+    assert_eq!(left.len(), synth_util::samples(1.0));
+}
+
+// The first file selects the noise, and the second one plays a note without the program change.
+fn program_and_note() -> (Sequence, Sequence) {
+    let mut first = Sequence::new();
+    first.push(0.0, 0, 0xC0, synth_util::NOISE, 0);
+    first.push(0.0, 0, 0x90, 69, 100);
+    first.push(0.5, 0, 0x80, 69, 0);
+    let mut second = Sequence::new();
+    second.push(0.0, 0, 0x90, 69, 100);
+    second.push(0.5, 0, 0x80, 69, 0);
+    (first, second)
+}
+
+#[test]
+fn keep_state_passes_programs_to_next_file() {
+    let (first, second) = program_and_note();
+    let render_alone = |sequence: &Sequence| {
+        let mut playlist = playlist();
+        playlist.push_track(sequence.to_track());
+        playlist.render().unwrap().0
+    };
+
+    let render = |keep_state| {
+        let mut playlist = playlist();
+        playlist.set_transition(PlaylistTransition::Gap(0.5));
+        playlist.set_keep_state(keep_state);
+        assert_eq!(playlist.get_keep_state(), keep_state);
+        playlist.push_track(first.to_track());
+        playlist.push_track(second.to_track());
+        let (left, _) = playlist.render().unwrap();
+        range(&left, 1.0, 1.5).to_vec()
+    };
+
+    assert!(max_difference(&render(false), &render_alone(&second)) < 1.0E-6);
+    assert!(max_difference(&render(true), &render_alone(&first)) < 1.0E-6);
+}
+
+#[test]
+#[should_panic(expected = "The transition length must be a non-negative value.")]
+fn negative_transition_panics() {
+    playlist().set_transition(PlaylistTransition::Crossfade(-1.0));
+}
@@ -0,0 +1,40 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use rustysynth::prelude::*;
+use std::sync::Arc;
+
+#[test]
+fn prelude_is_enough_to_play_midi_file() {
+    let sound_font = Arc::new(SoundFont::test_bank());
+    let midi_file = MidiFile::from_bytes(&midi_util::format1_with_notes_in_tempo_track()).unwrap();
+    let settings = SynthesizerSettings::new(44100);
+    let synthesizer = Synthesizer::new(&sound_font, &settings).unwrap();
+    let mut sequencer = MidiFileSequencer::new(synthesizer);
+
+    sequencer.play(midi_file.get_tracks()[1].clone(), false);
+    let mut left = vec![0_f32; 4410];
+    let mut right = vec![0_f32; 4410];
+    sequencer.render(&mut left, &mut right);
+
+    assert!(left.iter().any(|x| *x != 0_f32));
+}
+
+#[test]
+fn track_events_are_exposed() {
+    let midi_file = MidiFile::from_bytes(&midi_util::format1_with_notes_in_tempo_track()).unwrap();
+    let track = &midi_file.get_tracks()[0];
+
+    // The tempo changes are taken into the times and are not repeated as events.
+    let events: Vec<(f64, MidiEvent)> = track.get_events().collect();
+    assert_eq!(track.get_event_count(), events.len());
+    assert_eq!(
+        events,
+        [
+            (0.0, MidiEvent::Message(MidiMessage::new(0, 0x90, 60, 100))),
+            (0.5, MidiEvent::Message(MidiMessage::new(0, 0x80, 60, 0))),
+            (0.5, MidiEvent::EndOfTrack),
+        ]
+    );
+}
@@ -0,0 +1,981 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use crate::render_util;
+use crate::render_util::TempFile;
+use crate::synth_util;
+use crate::wave_util;
+use rustysynth::Automation;
+use rustysynth::AutomationTarget;
+use rustysynth::BroadcastInfo;
+use rustysynth::CancellationToken;
+use rustysynth::LevelReport;
+use rustysynth::LoopRenderMode;
+use rustysynth::MidiFileLoopType;
+use rustysynth::Normalization;
+use rustysynth::RenderCache;
+use rustysynth::RenderSplit;
+use rustysynth::RenderTail;
+use rustysynth::ThreadedRender;
+use rustysynth::TrackErrorPolicy;
+use rustysynth::TrackMix;
+use rustysynth::TrackRenderError;
+use rustysynth::WaveFormat;
+use rustysynth::WaveOptions;
+use std::fs;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+#[test]
+fn progressive_render_is_same_as_render() {
+    let file = TempFile::new(
+        "progressive",
+        &midi_util::format1_with_notes_in_tempo_track(),
+    );
+    let mut renderer = render_util::renderer(&file);
+
+    let (expected, _) = renderer.render();
+    let progressive = renderer.render_progressive(0.25);
+
+    // The preview is the beginning of the whole waveform.
+    assert_eq!(
+        progressive.get_preview_left().len(),
+        synth_util::samples(0.25)
+    );
+    assert_eq!(
+        progressive.get_preview_right().len(),
+        synth_util::samples(0.25)
+    );
+    assert_eq!(
+        progressive.get_preview_left(),
+        &expected[..synth_util::samples(0.25)]
+    );
+    let (left, right) = progressive.wait();
+    assert_eq!(left.len(), right.len());
+    assert!(synth_util::peak(&left) > 0.01);
+    assert_eq!(left, expected);
+}
+
+#[test]
+fn progressive_render_of_short_file_has_whole_waveform_as_preview() {
+    let file = TempFile::new(
+        "progressive_short",
+        &midi_util::format1_with_notes_in_tempo_track(),
+    );
+    let mut renderer = render_util::renderer(&file);
+
+    let (expected, _) = renderer.render();
+    let progressive = renderer.render_progressive(10.0);
+
+    assert_eq!(progressive.get_preview_left(), &expected[..]);
+    let (left, _) = progressive.wait();
+    assert!(synth_util::peak(&left) > 0.01);
+    assert_eq!(left, expected);
+}
+
+#[test]
+#[should_panic(expected = "The normalization is not supported by the progressive rendering.")]
+fn progressive_render_with_normalization_panics() {
+    let file = TempFile::new(
+        "progressive_normalization",
+        &midi_util::format1_with_notes_in_tempo_track(),
+    );
+    let mut renderer = render_util::renderer(&file);
+    renderer.set_normalization(Normalization::Peak(-1.0));
+
+    renderer.render_progressive(0.25);
+}
+
+#[test]
+fn progress_counts_rendered_tracks() {
+    let file = TempFile::new("progress", &midi_util::format1_with_notes_in_tempo_track());
+    let mut renderer = render_util::renderer(&file);
+    let progress = renderer.get_progress();
+
+    assert_eq!(renderer.get_track_count(), 3);
+    assert_eq!(progress.load(Ordering::SeqCst), 0);
+    renderer.render();
+    assert_eq!(progress.load(Ordering::SeqCst), 3);
+}
+
+// The renderer of the looped file, where the loop is given by CC #111.
+fn looped_renderer(file: &TempFile) -> ThreadedRender {
+    let mut renderer = render_util::renderer(file);
+    renderer.set_loop_type(MidiFileLoopType::RpgMaker);
+    renderer
+}
+
+#[test]
+fn once_ignores_loop() {
+    let file = TempFile::new("once", &midi_util::looped());
+    let mut renderer = looped_renderer(&file);
+    assert_eq!(renderer.get_loop_mode(), LoopRenderMode::Once);
+    assert!(matches!(
+        renderer.get_loop_type(),
+        MidiFileLoopType::RpgMaker
+    ));
+
+    let (left, right) = renderer.render();
+
+    assert_eq!(left.len(), synth_util::samples(1.0));
+    assert_eq!(right.len(), synth_util::samples(1.0));
+    assert_eq!(renderer.get_loop_points(), None);
+}
+
+#[test]
+fn count_repeats_loop() {
+    let file = TempFile::new("count", &midi_util::looped());
+    let mut renderer = looped_renderer(&file);
+    renderer.set_loop_mode(LoopRenderMode::Count(3));
+    assert_eq!(renderer.get_loop_mode(), LoopRenderMode::Count(3));
+
+    let (left, _) = renderer.render();
+
+    assert_eq!(left.len(), synth_util::samples(2.0));
+    assert_eq!(
+        renderer.get_loop_points(),
+        Some((synth_util::samples(0.5), synth_util::samples(1.0)))
+    );
+
+    // The second note sounds in every pass of the loop.
+    for pass in 0..3 {
+        let start = synth_util::samples(0.5 + 0.5 * pass as f64);
+        let end = start + synth_util::samples(0.4);
+        assert!(synth_util::rms(&left[start + synth_util::samples(0.1)..end]) > 0.01);
+    }
+}
+
+#[test]
+fn loop_type_selects_loop_points() {
+    let file = TempFile::new("loop_type", &midi_util::looped());
+    let mut renderer = render_util::renderer(&file);
+    renderer.set_loop_mode(LoopRenderMode::Count(3));
+
+    // Without CC #111, the whole file is the loop.
+    let (left, _) = renderer.render();
+    assert_eq!(left.len(), synth_util::samples(3.0));
+    assert_eq!(
+        renderer.get_loop_points(),
+        Some((0, synth_util::samples(1.0)))
+    );
+}
+
+#[test]
+fn duration_repeats_loop_until_length() {
+    let file = TempFile::new("duration", &midi_util::looped());
+    let mut renderer = looped_renderer(&file);
+    renderer.set_loop_mode(LoopRenderMode::Duration(1.8));
+
+    let (left, _) = renderer.render();
+
+    assert!(left.len() >= synth_util::samples(1.8));
+    assert!(left.len() < synth_util::samples(1.8 + 0.5));
+}
+
+#[test]
+fn tail_ends_at_loop_end() {
+    let file = TempFile::new("tail", &midi_util::looped());
+    let mut renderer = looped_renderer(&file);
+    renderer.set_loop_mode(LoopRenderMode::Tail(0.1));
+
+    let (left, _) = renderer.render();
+
+    assert_eq!(left.len(), synth_util::samples(1.0));
+    assert_eq!(
+        renderer.get_loop_points(),
+        Some((synth_util::samples(0.5), synth_util::samples(1.0)))
+    );
+
+    // The release of the note stopped at the loop end is mixed into the loop start.
+    let (expected, _) = looped_renderer(&file).render();
+    let start = synth_util::samples(0.5);
+    let end = start + synth_util::samples(0.05);
+    assert!(synth_util::rms(&left[start..end]) > synth_util::rms(&expected[start..end]));
+}
+
+#[test]
+fn fade_out_ends_after_fade() {
+    let file = TempFile::new("fade_out", &midi_util::looped());
+    let mut renderer = looped_renderer(&file);
+    renderer.set_loop_mode(LoopRenderMode::FadeOut {
+        count: 2,
+        fade: 0.25,
+    });
+
+    let (left, _) = renderer.render();
+
+    assert_eq!(left.len(), synth_util::samples(1.75));
+
+    // The next pass of the loop fades out.
+    let fade_start = synth_util::samples(1.5);
+    let first = synth_util::rms(&left[fade_start..fade_start + synth_util::samples(0.05)]);
+    let last = synth_util::rms(&left[left.len() - synth_util::samples(0.05)..]);
+    assert!(last < 0.5 * first);
+}
+
+#[test]
+#[should_panic(expected = "The loop count must be greater than zero.")]
+fn zero_loop_count_panics() {
+    let file = TempFile::new("zero_count", &midi_util::looped());
+    looped_renderer(&file).set_loop_mode(LoopRenderMode::Count(0));
+}
+
+#[test]
+fn fixed_tail_keeps_release() {
+    let file = TempFile::new("fixed_tail", &midi_util::looped());
+    let mut renderer = render_util::renderer(&file);
+    assert_eq!(renderer.get_tail(), RenderTail::None);
+    renderer.set_tail(RenderTail::Fixed(0.5));
+    assert_eq!(renderer.get_tail(), RenderTail::Fixed(0.5));
+
+    let (left, _) = renderer.render();
+
+    // The release of the last note follows the last event, and the silence after it is trimmed.
+    assert!(left.len() > synth_util::samples(1.0));
+    assert!(left.len() <= synth_util::samples(1.5));
+    let end = synth_util::samples(1.0);
+    assert!(synth_util::rms(&left[end..end + synth_util::samples(0.01)]) > 0.001);
+}
+
+#[test]
+fn tail_until_silence_stops_at_threshold() {
+    let file = TempFile::new("silence_tail", &midi_util::looped());
+    let mut renderer = render_util::renderer(&file);
+    renderer.set_tail(RenderTail::UntilSilence {
+        threshold: -60.0,
+        max_length: 10.0,
+    });
+
+    let (left, _) = renderer.render();
+
+    assert!(left.len() > synth_util::samples(1.0));
+    assert!(left.len() < synth_util::samples(11.0));
+    assert!(synth_util::peak(&left[left.len() - synth_util::samples(0.01)..]) < 0.01);
+}
+
+#[test]
+#[should_panic(expected = "The tail length must be a non-negative value.")]
+fn negative_tail_panics() {
+    let file = TempFile::new("negative_tail", &midi_util::looped());
+    render_util::renderer(&file).set_tail(RenderTail::Fixed(-1.0));
+}
+
+#[test]
+fn render_progress_reaches_total() {
+    let file = TempFile::new(
+        "render_progress",
+        &midi_util::format1_with_notes_in_tempo_track(),
+    );
+    let mut renderer = render_util::renderer(&file);
+    let progress = renderer.get_render_progress();
+    assert_eq!(progress.get_total_sample_count(), 0);
+    assert_eq!(progress.get_ratio(), 0.0);
+
+    renderer.render();
+
+    // Each of the three tracks is half a second.
+    let total = 3 * synth_util::samples(0.5) as u64;
+    assert_eq!(progress.get_total_sample_count(), total);
+    assert_eq!(progress.get_rendered_sample_count(), total);
+    assert_eq!(progress.get_ratio(), 1.0);
+}
+
+#[test]
+fn cancelled_render_stops_early() {
+    let file = TempFile::new("cancelled", &midi_util::looped());
+    let mut renderer = render_util::renderer(&file);
+    assert!(renderer.get_cancellation_token().is_none());
+    assert!(!renderer.is_cancelled());
+
+    let token = CancellationToken::new();
+    renderer.set_cancellation_token(Some(token.clone()));
+    assert!(!renderer.is_cancelled());
+    token.cancel();
+    assert!(renderer.is_cancelled());
+    assert!(renderer.get_cancellation_token().unwrap().is_cancelled());
+
+    let (left, _) = renderer.render();
+    assert!(left.len() < synth_util::samples(1.0));
+}
+
+#[test]
+fn thread_count_confines_rendering() {
+    let file = TempFile::new(
+        "thread_count",
+        &midi_util::format1_with_notes_in_tempo_track(),
+    );
+    let mut renderer = render_util::renderer(&file);
+    assert!(renderer.get_thread_pool().is_none());
+    let (expected, _) = renderer.render();
+
+    renderer.set_thread_count(2).unwrap();
+    assert_eq!(renderer.get_thread_pool().unwrap().current_num_threads(), 2);
+    let (actual, _) = renderer.render();
+    assert_eq!(actual, expected);
+
+    renderer.set_thread_pool(None);
+    assert!(renderer.get_thread_pool().is_none());
+}
+
+#[test]
+fn channel_split_is_same_as_track_split() {
+    // A format 0 file where the flute and the piano play on their own channels.
+    let data = midi_util::format0(&[
+        0, 0xC0, 72, 0, 0x90, 69, 100, 0, 0x91, 64, 100, 0x83, 0x60, 0x80, 69, 0, 0, 0x81, 64, 0,
+        0, 0xFF, 0x2F, 0,
+    ]);
+    let file = TempFile::new("channel_split", &data);
+    let mut renderer = render_util::renderer(&file);
+    assert_eq!(renderer.get_split(), RenderSplit::Track);
+    let (expected, _) = renderer.render();
+
+    renderer.set_split(RenderSplit::Channel);
+    assert_eq!(renderer.get_split(), RenderSplit::Channel);
+    let (actual, _) = renderer.render();
+
+    // Only the order of the summation differs.
+    assert_eq!(actual.len(), expected.len());
+    for (x, y) in actual.iter().zip(expected.iter()) {
+        assert!((x - y).abs() < 1.0E-4);
+    }
+}
+
+#[test]
+fn channel_split_applies_controllers_of_other_tracks() {
+    // The first track mutes the channel, and the second track plays a note on it.
+    let mut data = b"MThd\0\0\0\x06\0\x01\0\x02\x01\xE0".to_vec();
+    data.extend(midi_util::track(&[0, 0xB0, 0x07, 0, 0, 0xFF, 0x2F, 0]));
+    data.extend(midi_util::track(&[
+        0, 0xC0, 72, 0, 0x90, 69, 100, 0x83, 0x60, 0x80, 69, 0, 0, 0xFF, 0x2F, 0,
+    ]));
+    let file = TempFile::new("channel_split_controllers", &data);
+    let mut renderer = render_util::renderer(&file);
+
+    let (left, _) = renderer.render();
+    assert!(synth_util::rms(&left) > 0.01);
+
+    renderer.set_split(RenderSplit::Channel);
+    let (left, _) = renderer.render();
+    assert_eq!(synth_util::peak(&left), 0.0);
+}
+
+#[test]
+fn segments_are_same_as_whole_track() {
+    // The flute plays four notes with the silence between them.
+    let data = midi_util::format0(&[
+        0, 0xC0, 72, 0, 0x90, 69, 100, 0x81, 0x70, 0x80, 69, 0, 0x87, 0x40, 0x90, 72, 100, 0x81,
+        0x70, 0x80, 72, 0, 0x87, 0x40, 0x90, 76, 100, 0x81, 0x70, 0x80, 76, 0, 0x87, 0x40, 0x90,
+        81, 100, 0x81, 0x70, 0x80, 81, 0, 0, 0xFF, 0x2F, 0,
+    ]);
+    let file = TempFile::new("segments", &data);
+    let mut renderer = render_util::renderer(&file);
+    renderer.set_tail(RenderTail::Fixed(1.0));
+    assert_eq!(renderer.get_segment_length(), None);
+    let (expected, _) = renderer.render();
+
+    renderer.set_segment_length(Some(0.5));
+    assert_eq!(renderer.get_segment_length(), Some(0.5));
+    let (actual, _) = renderer.render();
+
+    assert_eq!(actual.len(), expected.len());
+    for (x, y) in actual.iter().zip(expected.iter()) {
+        assert!((x - y).abs() < 1.0E-4);
+    }
+}
+
+#[test]
+#[should_panic]
+fn zero_segment_length_panics() {
+    let file = TempFile::new("zero_segment", &midi_util::looped());
+    render_util::renderer(&file).set_segment_length(Some(0.0));
+}
+
+#[test]
+fn analysis_estimates_rendering() {
+    let file = TempFile::new("analysis", &midi_util::format1_with_notes_in_tempo_track());
+    let mut renderer = render_util::renderer(&file);
+    renderer.set_thread_count(2).unwrap();
+
+    let analysis = renderer.analyze();
+
+    let tracks = analysis.get_tracks();
+    assert_eq!(tracks.len(), 3);
+    for (i, track) in tracks.iter().enumerate() {
+        assert_eq!(track.get_index(), i);
+        assert_eq!(track.get_note_count(), 1);
+        assert_eq!(track.get_length(), 0.5);
+        assert_eq!(track.get_sample_count(), synth_util::samples(0.5));
+        assert_eq!(track.get_segment_count(), 1);
+        assert_eq!(track.get_used_presets(), [(0, 0)]);
+    }
+    assert_eq!(tracks[0].get_event_count(), 3);
+    assert_eq!(analysis.get_sample_count(), synth_util::samples(0.5));
+    assert_eq!(analysis.get_thread_count(), 2);
+    assert!(analysis.get_estimated_peak_memory() > 0);
+    assert!(analysis.get_diagnostics().is_empty());
+
+    // The analysis agrees with the actual rendering.
+    renderer.render();
+    assert_eq!(
+        analysis.get_total_sample_count(),
+        renderer.get_render_progress().get_total_sample_count()
+    );
+}
+
+#[test]
+fn peak_normalization_scales_to_level() {
+    let file = TempFile::new("peak_normalization", &midi_util::looped());
+    let mut renderer = render_util::renderer(&file);
+    assert_eq!(renderer.get_normalization(), Normalization::None);
+    renderer.set_normalization(Normalization::Peak(-6.0));
+    assert_eq!(renderer.get_normalization(), Normalization::Peak(-6.0));
+
+    let (left, right) = renderer.render();
+
+    let peak = synth_util::peak(&left).max(synth_util::peak(&right));
+    assert!((peak - 10_f32.powf(-6.0 / 20.0)).abs() < 1.0E-3);
+}
+
+#[test]
+fn loudness_normalization_keeps_peak_under_maximum() {
+    let file = TempFile::new("loudness_normalization", &midi_util::looped());
+    let mut renderer = render_util::renderer(&file);
+    let (expected, _) = renderer.render();
+
+    // The target is too loud for the file, so the gain is limited by the peak.
+    renderer.set_normalization(Normalization::Loudness {
+        target: 0.0,
+        max_peak: -1.0,
+    });
+    let (left, right) = renderer.render();
+
+    let peak = synth_util::peak(&left).max(synth_util::peak(&right));
+    assert!(peak <= 10_f32.powf(-1.0 / 20.0) + 1.0E-6);
+    assert!(synth_util::rms(&left) > synth_util::rms(&expected));
+}
+
+#[test]
+#[should_panic(expected = "The peak level must be a non-positive value.")]
+fn positive_peak_level_panics() {
+    let file = TempFile::new("positive_peak", &midi_util::looped());
+    render_util::renderer(&file).set_normalization(Normalization::Peak(1.0));
+}
+
+#[test]
+fn render_report_measures_output() {
+    let file = TempFile::new(
+        "render_report",
+        &midi_util::format1_with_notes_in_tempo_track(),
+    );
+    let mut renderer = render_util::renderer(&file);
+    assert!(renderer.get_render_report().is_none());
+
+    let (left, right) = renderer.render();
+
+    let report = renderer.get_render_report().unwrap();
+    let master = report.get_master();
+    let peak = synth_util::peak(&left).max(synth_util::peak(&right));
+    assert_eq!(master.get_sample_count(), 2 * left.len());
+    assert_eq!(master.get_peak(), peak);
+    assert!(master.get_true_peak() >= peak);
+    assert!(master.get_rms() > 0.0 && master.get_rms() < peak);
+    assert_eq!(master.get_clipped_sample_count(), 0);
+    assert!(master.get_dc_offset().abs() < 0.01);
+
+    // The three tracks play the same note, so the mix is louder than each of them.
+    assert_eq!(report.get_tracks().len(), 3);
+    for track in report.get_tracks() {
+        assert_eq!(track.get_sample_count(), 2 * left.len());
+        assert!(track.get_peak() < peak);
+    }
+}
+
+#[test]
+fn write_wave_writes_loop_points() {
+    let file = TempFile::new("write_wave", &midi_util::looped());
+    let mut renderer = looped_renderer(&file);
+    renderer.set_loop_mode(LoopRenderMode::Tail(0.1));
+    let (left, right) = renderer.render();
+
+    let mut data = Vec::new();
+    renderer.write_wave(&mut data, &left, &right).unwrap();
+
+    let format = wave_util::find_chunk(&data, b"fmt ").unwrap();
+    assert_eq!(wave_util::read_u16(format, 0), 3);
+    assert_eq!(wave_util::read_u16(format, 2), 2);
+    assert_eq!(
+        wave_util::read_u32(format, 4),
+        synth_util::SAMPLE_RATE as u32
+    );
+
+    let samples = wave_util::find_chunk(&data, b"data").unwrap();
+    assert_eq!(samples.len(), 8 * left.len());
+    assert_eq!(wave_util::read_f32_channel(samples, 0), left);
+    assert_eq!(wave_util::read_f32_channel(samples, 1), right);
+
+    // The sampler loop has the inclusive end.
+    let sampler = wave_util::find_chunk(&data, b"smpl").unwrap();
+    assert_eq!(wave_util::read_u32(sampler, 28), 1);
+    assert_eq!(
+        wave_util::read_u32(sampler, 44) as usize,
+        synth_util::samples(0.5)
+    );
+    assert_eq!(
+        wave_util::read_u32(sampler, 48) as usize,
+        synth_util::samples(1.0) - 1
+    );
+
+    let cue = wave_util::find_chunk(&data, b"cue ").unwrap();
+    assert_eq!(wave_util::read_u32(cue, 0), 2);
+    assert_eq!(
+        wave_util::read_u32(cue, 4 + 20) as usize,
+        synth_util::samples(0.5)
+    );
+    assert_eq!(
+        wave_util::read_u32(cue, 28 + 20) as usize,
+        synth_util::samples(1.0)
+    );
+}
+
+#[test]
+fn write_wave_without_loop_has_no_loop_points() {
+    let file = TempFile::new("write_wave_once", &midi_util::looped());
+    let mut renderer = looped_renderer(&file);
+    let (left, right) = renderer.render();
+
+    let mut data = Vec::new();
+    renderer.write_wave(&mut data, &left, &right).unwrap();
+
+    assert!(wave_util::find_chunk(&data, b"data").is_some());
+    assert!(wave_util::find_chunk(&data, b"smpl").is_none());
+    assert!(wave_util::find_chunk(&data, b"cue ").is_none());
+}
+
+#[test]
+fn write_wave_with_options_writes_format() {
+    let file = TempFile::new("write_wave_options", &midi_util::looped());
+    let mut renderer = looped_renderer(&file);
+    renderer.set_loop_mode(LoopRenderMode::Tail(0.1));
+    let (left, right) = renderer.render();
+
+    let mut options = WaveOptions::new();
+    options.format = WaveFormat::Int16;
+    options.broadcast = Some(BroadcastInfo::new());
+    let mut data = Vec::new();
+    renderer
+        .write_wave_with_options(&mut data, &left, &right, &options)
+        .unwrap();
+
+    let format = wave_util::find_chunk(&data, b"fmt ").unwrap();
+    assert_eq!(wave_util::read_u16(format, 0), 1);
+    assert_eq!(wave_util::read_u16(format, 14), 16);
+    let samples = wave_util::find_chunk(&data, b"data").unwrap();
+    assert_eq!(samples.len(), 4 * left.len());
+    assert!(wave_util::find_chunk(&data, b"bext").is_some());
+    let sampler = wave_util::find_chunk(&data, b"smpl").unwrap();
+    assert_eq!(
+        wave_util::read_u32(sampler, 44) as usize,
+        synth_util::samples(0.5)
+    );
+}
+
+#[test]
+fn write_resampled_wave_with_options_writes_format() {
+    let file = TempFile::new("write_resampled_wave_options", &midi_util::looped());
+    let mut renderer = looped_renderer(&file);
+    renderer.set_loop_mode(LoopRenderMode::Tail(0.1));
+    let outputs = renderer.render_resampled(&[48000]);
+    let (left, right) = &outputs[0];
+
+    let mut options = WaveOptions::new();
+    options.format = WaveFormat::Int24;
+    let mut data = Vec::new();
+    renderer
+        .write_resampled_wave_with_options(&mut data, left, right, 48000, &options)
+        .unwrap();
+
+    let format = wave_util::find_chunk(&data, b"fmt ").unwrap();
+    assert_eq!(wave_util::read_u32(format, 4), 48000);
+    assert_eq!(wave_util::read_u16(format, 14), 24);
+    let samples = wave_util::find_chunk(&data, b"data").unwrap();
+    assert_eq!(samples.len(), 6 * left.len());
+    let sampler = wave_util::find_chunk(&data, b"smpl").unwrap();
+    assert_eq!(wave_util::read_u32(sampler, 44), 24000);
+}
+
+#[test]
+fn track_mix_gain_and_pan_scale_track() {
+    let file = TempFile::new("track_mix_gain", &midi_util::flute_note(69));
+    let (_, expected) = render_util::renderer(&file).render();
+
+    let mut renderer = render_util::renderer(&file);
+    let mut track_mix = TrackMix::new();
+    track_mix.gain = -20_f32;
+    track_mix.pan = 1_f32;
+    renderer.set_track_mix(0, Some(track_mix));
+    assert_eq!(renderer.get_track_mix(0), Some(track_mix));
+    let (left, right) = renderer.render();
+
+    assert_eq!(synth_util::peak(&left), 0_f32);
+    for (actual, expected) in right.iter().zip(expected.iter()) {
+        assert!((actual - 0.1 * expected).abs() < 1.0E-6);
+    }
+
+    renderer.set_track_mix(0, None);
+    assert_eq!(renderer.get_track_mix(0), None);
+}
+
+#[test]
+fn track_mix_transpose_shifts_notes() {
+    let file = TempFile::new("track_mix_transpose", &midi_util::flute_note(69));
+    let mut renderer = render_util::renderer(&file);
+    let mut track_mix = TrackMix::new();
+    track_mix.transpose = 12;
+    renderer.set_track_mix(0, Some(track_mix));
+    let (left, _) = renderer.render();
+
+    let frequency = synth_util::frequency(&left[..synth_util::samples(0.5)]);
+    assert!((frequency - 880.0).abs() < 20.0);
+}
+
+#[test]
+fn track_mix_program_replaces_program_changes() {
+    let file = TempFile::new("track_mix_program", &midi_util::flute_note(69));
+    let mut renderer = render_util::renderer(&file);
+    let mut track_mix = TrackMix::new();
+    track_mix.program = Some(synth_util::NOISE as u8);
+    renderer.set_track_mix(0, Some(track_mix));
+    let (left, _) = renderer.render();
+
+    let mut data = midi_util::flute_note(69);
+    let position = data.iter().position(|x| *x == 0xC0).unwrap();
+    data[position + 1] = synth_util::NOISE as u8;
+    let file = TempFile::new("track_mix_noise", &data);
+    let (expected, _) = render_util::renderer(&file).render();
+    assert!(synth_util::peak(&left) > 0.01);
+    assert_eq!(left, expected);
+}
+
+#[test]
+#[should_panic]
+fn track_mix_with_invalid_pan_panics() {
+    let file = TempFile::new("track_mix_pan", &midi_util::flute_note(69));
+    let mut renderer = render_util::renderer(&file);
+    let mut track_mix = TrackMix::new();
+    track_mix.pan = 2_f32;
+    renderer.set_track_mix(0, Some(track_mix));
+}
+
+#[test]
+fn channel_pan_automation_moves_track() {
+    let file = TempFile::new("automation", &midi_util::flute_note(69));
+    let mut automation = Automation::new();
+    automation.push(0.0, -1.0);
+
+    let mut renderer = render_util::renderer(&file);
+    renderer.set_automation(AutomationTarget::ChannelPan(0), Some(automation.clone()));
+    assert_eq!(
+        renderer.get_automation(AutomationTarget::ChannelPan(0)),
+        Some(&automation)
+    );
+    let (left, right) = renderer.render();
+
+    assert!(synth_util::rms(&left) > 0.01);
+    assert!(synth_util::rms(&right) < 0.001 * synth_util::rms(&left));
+}
+
+#[test]
+fn render_parts_sum_up_to_mix() {
+    let file = TempFile::new(
+        "render_parts",
+        &midi_util::format1_with_notes_in_tempo_track(),
+    );
+    let mut renderer = render_util::renderer(&file);
+    let (left, right, parts) = renderer.render_parts();
+    assert_eq!(renderer.render(), (left.clone(), right.clone()));
+
+    assert_eq!(parts.len(), 3);
+    for (part_left, part_right) in parts.iter() {
+        assert_eq!(part_left.len(), left.len());
+        assert_eq!(part_right.len(), right.len());
+        assert!(synth_util::rms(part_left) > 0.001);
+    }
+    for t in 0..left.len() {
+        let sum: f32 = parts.iter().map(|(x, _)| x[t]).sum();
+        assert!((sum - left[t]).abs() < 1.0E-5);
+    }
+}
+
+#[test]
+fn level_report_measures_stereo_image() {
+    let sine: Vec<f32> = (0..1000).map(|t| (0.05 * t as f32).sin()).collect();
+    let cosine: Vec<f32> = (0..1000).map(|t| (0.05 * t as f32).cos()).collect();
+    let inverted: Vec<f32> = sine.iter().map(|x| -x).collect();
+
+    let mono = LevelReport::measure(&sine, &sine);
+    assert_eq!(mono.get_sample_count(), 2000);
+    assert!((mono.get_correlation() - 1.0).abs() < 1.0E-6);
+    assert!(mono.get_mono_fold_down_loss().abs() < 1.0E-6);
+
+    // The sine and the cosine are unrelated over whole cycles.
+    let unrelated = LevelReport::measure(&sine, &cosine);
+    assert!(unrelated.get_correlation().abs() < 0.05);
+    assert!((unrelated.get_mono_fold_down_loss() - 3.0).abs() < 0.2);
+
+    let out_of_phase = LevelReport::measure(&sine, &inverted);
+    assert!((out_of_phase.get_correlation() + 1.0).abs() < 1.0E-6);
+    assert_eq!(out_of_phase.get_mono_fold_down_loss(), f32::INFINITY);
+
+    let silence = LevelReport::measure(&[0.0; 100], &[0.0; 100]);
+    assert_eq!(silence.get_correlation(), 1.0);
+    assert_eq!(silence.get_mono_fold_down_loss(), 0.0);
+}
+
+#[test]
+#[should_panic(expected = "The output buffers for the left and right must be the same length.")]
+fn level_report_of_different_lengths_panics() {
+    LevelReport::measure(&[0.0; 100], &[0.0; 99]);
+}
+
+#[test]
+fn render_resampled_converts_mix() {
+    let file = TempFile::new("render_resampled", &midi_util::flute_note(69));
+    let expected = render_util::renderer(&file).render();
+
+    let outputs = render_util::renderer(&file).render_resampled(&[48000, 44100, 22050]);
+    assert_eq!(outputs.len(), 3);
+    assert_eq!(outputs[1], expected);
+
+    for ((left, right), sample_rate) in outputs.iter().zip([48000, 44100, 22050]) {
+        let length = expected.0.len() as f64 * sample_rate as f64 / synth_util::SAMPLE_RATE as f64;
+        assert!((left.len() as f64 - length).abs() <= 1.0);
+        assert_eq!(left.len(), right.len());
+
+        // The flute keeps the pitch of 440 Hz.
+        let crossings = synth_util::crossings(&left[..sample_rate / 2]);
+        assert!((crossings as f64 - 220.0).abs() < 5.0);
+    }
+}
+
+#[test]
+fn write_resampled_wave_converts_loop_points() {
+    let file = TempFile::new("write_resampled_wave", &midi_util::looped());
+    let mut renderer = looped_renderer(&file);
+    renderer.set_loop_mode(LoopRenderMode::Tail(0.1));
+    let outputs = renderer.render_resampled(&[48000]);
+    let (left, right) = &outputs[0];
+
+    let mut data = Vec::new();
+    renderer
+        .write_resampled_wave(&mut data, left, right, 48000)
+        .unwrap();
+
+    let format = wave_util::find_chunk(&data, b"fmt ").unwrap();
+    assert_eq!(wave_util::read_u32(format, 4), 48000);
+    let samples = wave_util::find_chunk(&data, b"data").unwrap();
+    assert_eq!(wave_util::read_f32_channel(samples, 0), *left);
+
+    let sampler = wave_util::find_chunk(&data, b"smpl").unwrap();
+    assert_eq!(wave_util::read_u32(sampler, 44), 24000);
+    assert_eq!(wave_util::read_u32(sampler, 48), 48000 - 1);
+}
+
+#[test]
+fn range_is_same_as_part_of_whole() {
+    let file = TempFile::new("range", &midi_util::looped());
+    let mut renderer = looped_renderer(&file);
+
+    let (whole, _) = renderer.render();
+    let (left, right) = renderer.render_range(0.5, 0.75);
+    assert_eq!(left.len(), synth_util::samples(0.25));
+    assert_eq!(right.len(), synth_util::samples(0.25));
+
+    let expected = synth_util::rms(&whole[synth_util::samples(0.5)..synth_util::samples(0.75)]);
+    assert!((synth_util::rms(&left) - expected).abs() < 0.05 * expected);
+}
+
+#[test]
+fn range_restarts_held_notes() {
+    let file = TempFile::new("range_held", &midi_util::looped());
+    let mut renderer = looped_renderer(&file);
+
+    let (left, _) = renderer.render_range(0.75, 1.0);
+    assert!(synth_util::rms(&left) > 0.01);
+
+    // No note is held in the gap between the notes.
+    let (left, _) = renderer.render_range(0.45, 0.5);
+    assert!(synth_util::rms(&left) < 1.0E-3);
+}
+
+#[test]
+fn range_follows_loop_mode() {
+    let file = TempFile::new("range_loop", &midi_util::looped());
+    let mut renderer = looped_renderer(&file);
+
+    // The range is beyond the end of the file, but in the second pass of the loop.
+    renderer.set_loop_mode(LoopRenderMode::Count(2));
+    let (left, _) = renderer.render_range(1.25, 1.5);
+    assert_eq!(left.len(), synth_util::samples(0.25));
+    assert!(synth_util::rms(&left) > 0.01);
+}
+
+#[test]
+fn render_cache_renders_only_modified_tracks() {
+    let file = TempFile::new("render_cache", &midi_util::flute_note(69));
+    let expected = render_util::renderer(&file).render();
+
+    let cache = Arc::new(RenderCache::new());
+    let mut renderer = render_util::renderer(&file);
+    assert!(renderer.get_render_cache().is_none());
+    renderer.set_render_cache(Some(cache.clone()));
+    assert!(Arc::ptr_eq(renderer.get_render_cache().unwrap(), &cache));
+
+    assert_eq!(renderer.render(), expected);
+    assert_eq!((cache.get_hit_count(), cache.get_miss_count()), (0, 1));
+    assert_eq!(renderer.render(), expected);
+    assert_eq!((cache.get_hit_count(), cache.get_miss_count()), (1, 1));
+
+    // The gain is applied after the cache, while the transpose changes the sound.
+    let mut track_mix = TrackMix::new();
+    track_mix.gain = -6_f32;
+    renderer.set_track_mix(0, Some(track_mix));
+    assert_ne!(renderer.render(), expected);
+    assert_eq!((cache.get_hit_count(), cache.get_miss_count()), (2, 1));
+    track_mix.transpose = 12;
+    renderer.set_track_mix(0, Some(track_mix));
+    renderer.render();
+    assert_eq!((cache.get_hit_count(), cache.get_miss_count()), (2, 2));
+
+    cache.clear().unwrap();
+    renderer.set_track_mix(0, None);
+    assert_eq!(renderer.render(), expected);
+    assert_eq!((cache.get_hit_count(), cache.get_miss_count()), (2, 3));
+}
+
+#[test]
+fn render_cache_directory_is_shared_by_caches() {
+    let file = TempFile::new("render_cache_directory", &midi_util::flute_note(69));
+    let directory = std::env::temp_dir().join(format!(
+        "rustysynth_test_render_cache_{}",
+        std::process::id()
+    ));
+    let expected = render_util::renderer(&file).render();
+
+    let mut renderer = render_util::renderer(&file);
+    renderer.set_render_cache(Some(Arc::new(
+        RenderCache::with_directory(&directory).unwrap(),
+    )));
+    renderer.render();
+    assert!(std::fs::read_dir(&directory).unwrap().count() > 0);
+
+    // Another cache with the same directory reuses the file.
+    let cache = Arc::new(RenderCache::with_directory(&directory).unwrap());
+    let mut renderer = render_util::renderer(&file);
+    renderer.set_render_cache(Some(cache.clone()));
+    assert_eq!(renderer.render(), expected);
+    assert_eq!((cache.get_hit_count(), cache.get_miss_count()), (1, 0));
+
+    cache.clear().unwrap();
+    assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 0);
+    std::fs::remove_dir(&directory).unwrap();
+}
+
+// A format 1 file where the first track plays the flute and the second track ends without the EOT event.
+fn with_broken_track() -> Vec<u8> {
+    let mut data = b"MThd\0\0\0\x06\0\x01\0\x02\x01\xE0".to_vec();
+    data.extend(midi_util::track(&[
+        0, 0xC0, 72, 0, 0x90, 69, 100, 0x83, 0x60, 0x80, 69, 0, 0, 0xFF, 0x2F, 0,
+    ]));
+    data.extend(midi_util::track(&[0, 0x91, 60, 100]));
+    data
+}
+
+#[test]
+fn broken_track_is_skipped_and_reported() {
+    let file = TempFile::new("broken_track", &with_broken_track());
+    let mut renderer = render_util::renderer(&file);
+    assert_eq!(renderer.get_error_policy(), TrackErrorPolicy::Skip);
+    assert!(renderer.get_track_errors().is_empty());
+
+    let (left, _) = renderer.try_render().unwrap();
+    let errors = renderer.get_track_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].get_track(), 1);
+    assert!(matches!(errors[0], TrackRenderError::ReadFailed { .. }));
+
+    // The other track is rendered as usual.
+    let file = TempFile::new("broken_track_expected", &midi_util::flute_note(69));
+    let (expected, _) = render_util::renderer(&file).render();
+    assert_eq!(left.len(), expected.len());
+    assert!((synth_util::rms(&left) - synth_util::rms(&expected)).abs() < 0.01);
+}
+
+#[test]
+fn fail_fast_returns_first_error() {
+    let file = TempFile::new("broken_track_fail_fast", &with_broken_track());
+    let mut renderer = render_util::renderer(&file);
+    renderer.set_error_policy(TrackErrorPolicy::FailFast);
+    assert_eq!(renderer.get_error_policy(), TrackErrorPolicy::FailFast);
+
+    let error = renderer.try_render().err().unwrap();
+    assert_eq!(error.get_track(), 1);
+    assert!(error
+        .to_string()
+        .starts_with("failed to read the track 1: "));
+
+    // A file without errors renders in the fail-fast mode.
+    let file = TempFile::new("fail_fast_valid", &midi_util::flute_note(69));
+    let mut renderer = render_util::renderer(&file);
+    renderer.set_error_policy(TrackErrorPolicy::FailFast);
+    assert!(renderer.try_render().is_ok());
+    assert!(renderer.get_track_errors().is_empty());
+}
+
+#[test]
+fn file_is_read_only_once() {
+    let file = TempFile::new("read_once", &midi_util::flute_note(69));
+    let mut renderer = render_util::renderer(&file);
+    let expected = renderer.render();
+
+    // The tracks are read from memory after the file is removed.
+    fs::remove_file(file.get_path()).unwrap();
+    assert_eq!(renderer.render(), expected);
+}
+
+#[test]
+fn longer_header_is_skipped() {
+    let data = midi_util::flute_note(69);
+    let file = TempFile::new("short_header", &data);
+    let expected = render_util::renderer(&file).render();
+
+    // The header has two extra bytes after the resolution.
+    let mut padded = b"MThd\0\0\0\x08".to_vec();
+    padded.extend(&data[8..14]);
+    padded.extend([0, 0]);
+    padded.extend(&data[14..]);
+    let file = TempFile::new("long_header", &padded);
+    assert_eq!(render_util::renderer(&file).render(), expected);
+}
+
+#[test]
+fn file_without_tempo_is_rendered_at_120_bpm() {
+    let file = TempFile::new("no_tempo", &midi_util::flute_note(69));
+    let (left, _) = render_util::renderer(&file).render();
+    assert_eq!(left.len(), synth_util::samples(0.5));
+}
+
+#[test]
+fn tempo_in_other_track_applies_to_all_tracks() {
+    // The note of 480 ticks is at 60 BPM set by the second track.
+    let mut data = b"MThd\0\0\0\x06\0\x01\0\x02\x01\xE0".to_vec();
+    data.extend(midi_util::track(&[
+        0, 0xC0, 72, 0, 0x90, 69, 100, 0x83, 0x60, 0x80, 69, 0, 0, 0xFF, 0x2F, 0,
+    ]));
+    data.extend(midi_util::track(&[
+        0, 0xFF, 0x51, 3, 0x0F, 0x42, 0x40, 0, 0xFF, 0x2F, 0,
+    ]));
+    let file = TempFile::new("tempo_in_other_track", &data);
+    let (left, _) = render_util::renderer(&file).render();
+    assert_eq!(left.len(), synth_util::samples(1.0));
+}
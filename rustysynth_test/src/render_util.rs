@@ -0,0 +1,40 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::synth_util;
+use rustysynth::ThreadedRender;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+// The renderer reads the MIDI file from the path, so each test writes its own file.
+pub struct TempFile(PathBuf);
+
+impl TempFile {
+    pub fn new(name: &str, data: &[u8]) -> Self {
+        let file_name = format!("rustysynth_test_{}_{}.mid", name, process::id());
+        let path = env::temp_dir().join(file_name);
+        fs::write(&path, data).unwrap();
+        TempFile(path)
+    }
+
+    pub fn get_path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+pub fn renderer(file: &TempFile) -> ThreadedRender {
+    ThreadedRender::new(
+        &synth_util::sound_font(),
+        file.get_path(),
+        synth_util::settings(),
+    )
+    .unwrap()
+}
@@ -0,0 +1,139 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::synth_util;
+use rustysynth::GeneratorOverrides;
+use rustysynth::GeneratorType;
+use rustysynth::SoundFont;
+use rustysynth::Synthesizer;
+use rustysynth::SynthesizerSettings;
+use std::io::Cursor;
+use std::sync::Arc;
+
+// Plays the highest and the lowest notes of every instrument in the test bank,
+// and checks that the output is finite.
+fn play_all(sound_font: SoundFont) {
+    let sound_font = Arc::new(sound_font);
+    let settings = SynthesizerSettings::new(44100);
+    let mut synthesizer = Synthesizer::new(&sound_font, &settings).unwrap();
+
+    for channel in 0..16 {
+        synthesizer.process_midi_message(channel, 0xC0, 8 * channel, 0);
+        synthesizer.note_on(channel, 0, 100);
+        synthesizer.note_on(channel, 127, 100);
+    }
+
+    let mut left = vec![0_f32; 4410];
+    let mut right = vec![0_f32; 4410];
+    synthesizer.render(&mut left[..], &mut right[..]);
+    for channel in 0..16 {
+        synthesizer.note_off_all_channel(channel, false);
+    }
+    synthesizer.render(&mut left[..], &mut right[..]);
+
+    assert!(left.iter().chain(right.iter()).all(|x| x.is_finite()));
+}
+
+// Gets the written test bank and the position of the first sample header.
+fn written_test_bank() -> (Vec<u8>, usize) {
+    let data = synth_util::test_bank_file();
+    let position = data.windows(4).position(|x| x == b"shdr").unwrap() + 8;
+    (data, position)
+}
+
+// Sets a field of a sample header, where the fields from 0 to 3 are the start, the end,
+// the start of the loop and the end of the loop.
+fn set_field(data: &mut [u8], position: usize, sample: usize, field: usize, value: u32) {
+    let offset = position + 46 * sample + 20 + 4 * field;
+    data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+#[test]
+fn sample_positions_past_wave_data_are_clamped() {
+    let (mut data, position) = written_test_bank();
+    let sample_count = SoundFont::test_bank().get_wave_data().len() as u32;
+    for sample in 0..5 {
+        set_field(&mut data, position, sample, 1, sample_count + 1000);
+        set_field(&mut data, position, sample, 3, sample_count + 1000);
+    }
+
+    let sound_font = SoundFont::new(&mut Cursor::new(data)).unwrap();
+    play_all(sound_font);
+}
+
+#[test]
+fn random_sample_positions_do_not_panic() {
+    let (data, position) = written_test_bank();
+    let sample_count = SoundFont::test_bank().get_wave_data().len() as u32;
+
+    // The random numbers are given by a linear congruential generator, so the test is repeatable.
+    let mut seed: u64 = 1;
+    let mut random = |max: u32| {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((seed >> 33) % max as u64) as u32
+    };
+
+    for _ in 0..50 {
+        let mut data = data.clone();
+        for sample in 0..5 {
+            for field in 0..4 {
+                let value = match random(6) {
+                    0 => random(sample_count),
+                    1 => sample_count - 1,
+                    2 => sample_count,
+                    3 => sample_count + random(100),
+                    4 => u32::MAX - random(100),
+                    _ => continue,
+                };
+                set_field(&mut data, position, sample, field, value);
+            }
+        }
+
+        if let Ok(sound_font) = SoundFont::new(&mut Cursor::new(data)) {
+            play_all(sound_font);
+        }
+    }
+}
+
+#[test]
+fn address_offsets_past_wave_data_are_clamped() {
+    let sound_font = SoundFont::test_bank();
+    let instrument_count = sound_font.get_instruments().len();
+
+    for (generator_type, value) in [
+        (GeneratorType::START_ADDRESS_COARSE_OFFSET, i16::MAX),
+        (GeneratorType::START_ADDRESS_COARSE_OFFSET, i16::MIN),
+        (GeneratorType::END_ADDRESS_COARSE_OFFSET, i16::MAX),
+        (GeneratorType::START_LOOP_ADDRESS_OFFSET, i16::MIN),
+        (GeneratorType::END_LOOP_ADDRESS_COARSE_OFFSET, i16::MAX),
+        (GeneratorType::END_LOOP_ADDRESS_OFFSET, i16::MIN),
+    ] {
+        let mut overrides = GeneratorOverrides::new();
+        for instrument_id in 0..instrument_count {
+            overrides.set_instrument_generator(instrument_id, None, generator_type, value);
+        }
+        play_all(overrides.apply(&sound_font));
+    }
+}
+
+#[test]
+fn short_loop_at_high_pitch() {
+    // The loop of two samples is passed many times in each output sample.
+    let (mut data, position) = written_test_bank();
+    for sample in 0..5 {
+        let offset = position + 46 * sample + 20;
+        let start = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+        set_field(&mut data, position, sample, 2, start + 10);
+        set_field(&mut data, position, sample, 3, start + 12);
+    }
+    let sound_font = SoundFont::new(&mut Cursor::new(data)).unwrap();
+
+    let mut overrides = GeneratorOverrides::new();
+    for instrument_id in 0..sound_font.get_instruments().len() {
+        overrides.set_instrument_generator(instrument_id, None, GeneratorType::SAMPLE_MODES, 1);
+        overrides.set_instrument_generator(instrument_id, None, GeneratorType::COARSE_TUNE, 120);
+    }
+    play_all(overrides.apply(&sound_font));
+}
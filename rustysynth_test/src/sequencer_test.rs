@@ -0,0 +1,521 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use crate::synth_util;
+use rustysynth::Automation;
+use rustysynth::AutomationTarget;
+use rustysynth::CancellationToken;
+use rustysynth::Metronome;
+use rustysynth::MidiFile;
+use rustysynth::MidiFileLoopType;
+use rustysynth::MidiFileSequencer;
+use rustysynth::MidiMessage;
+use rustysynth::Sequence;
+use rustysynth::SequencerLoopMode;
+use rustysynth::Synthesizer;
+use rustysynth::VoiceEventKind;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+// The flute plays from 0 to 0.25 seconds and from 1.5 to 1.75 seconds, with the silence between them.
+fn sparse() -> MidiFile {
+    MidiFile::from_bytes(&midi_util::format0(&[
+        0, 0xC0, 72, 0, 0x90, 69, 100, 0x81, 0x70, 0x80, 69, 0, 0x89, 0x30, 0x90, 69, 100, 0x81,
+        0x70, 0x80, 69, 0, 0, 0xFF, 0x2F, 0,
+    ]))
+    .unwrap()
+}
+
+fn sequencer() -> MidiFileSequencer {
+    MidiFileSequencer::new(synth_util::synthesizer(&synth_util::settings()))
+}
+
+#[test]
+fn skipping_silence_keeps_output() {
+    let midi_file = sparse();
+
+    let mut sequencer = self::sequencer();
+    sequencer.play(midi_file.get_tracks()[0].clone(), false);
+    let expected = synth_util::play(&mut sequencer, 2.0);
+
+    let mut sequencer = self::sequencer();
+    assert!(!sequencer.get_skip_silence());
+    sequencer.set_skip_silence(true);
+    assert!(sequencer.get_skip_silence());
+    sequencer.play(midi_file.get_tracks()[0].clone(), false);
+    let actual = synth_util::play(&mut sequencer, 2.0);
+
+    // Both notes are played at the same timing, and the silence between them is written.
+    let start = synth_util::samples(1.5);
+    assert!(synth_util::rms(&actual[..synth_util::samples(0.25)]) > 0.01);
+    assert!(synth_util::rms(&actual[start..start + synth_util::samples(0.25)]) > 0.01);
+    assert!(actual[synth_util::samples(1.0)..start]
+        .iter()
+        .all(|x| *x == 0_f32));
+    let difference = actual
+        .iter()
+        .zip(expected.iter())
+        .fold(0_f32, |max, (x, y)| max.max((x - y).abs()));
+    assert!(difference < 1.0E-6);
+}
+
+#[test]
+fn voice_events_are_recorded_during_playback() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.set_voice_event_capacity(16);
+    let mut sequencer = MidiFileSequencer::new(synthesizer);
+    sequencer.play(sparse().get_tracks()[0].clone(), false);
+    synth_util::play(&mut sequencer, 1.0);
+
+    let started: Vec<_> = sequencer
+        .drain_voice_events()
+        .filter(|x| x.get_kind() == VoiceEventKind::Started)
+        .map(|x| x.get_key())
+        .collect();
+    assert_eq!(started, [69]);
+}
+
+#[test]
+fn cancelled_sequencer_writes_silence() {
+    let mut sequencer = self::sequencer();
+    let token = CancellationToken::new();
+    sequencer.set_cancellation_token(Some(token.clone()));
+    sequencer.play(sparse().get_tracks()[0].clone(), false);
+    assert!(synth_util::rms(&synth_util::play(&mut sequencer, 0.1)) > 0.01);
+
+    token.cancel();
+    assert!(sequencer.is_cancelled());
+    assert!(sequencer.get_cancellation_token().is_some());
+
+    // The rest of the current block is written before the cancellation is checked.
+    synth_util::play(&mut sequencer, 0.01);
+    let position = sequencer.get_position();
+    assert_eq!(
+        synth_util::peak(&synth_util::play(&mut sequencer, 0.1)),
+        0.0
+    );
+    assert_eq!(sequencer.get_position(), position);
+}
+
+// The same notes as the sparse file, pushed out of order.
+fn sparse_sequence() -> Sequence {
+    let mut sequence = Sequence::new();
+    sequence.push(1.5, 0, 0x90, 69, 100);
+    sequence.push(1.75, 0, 0x80, 69, 0);
+    sequence.push(0.0, 0, 0xC0, 72, 0);
+    sequence.push(0.0, 0, 0x90, 69, 100);
+    sequence.push(0.25, 0, 0x80, 69, 0);
+    sequence
+}
+
+#[test]
+fn sequence_is_sorted_into_track() {
+    let mut sequence = sparse_sequence();
+    assert_eq!(sequence.get_event_count(), 5);
+    assert_eq!(sequence.get_length(), 1.75);
+    assert_eq!(
+        midi_util::note_ons(&sequence.to_track()),
+        [(0.0, 69), (1.5, 69)]
+    );
+
+    sequence.clear();
+    assert_eq!(sequence.get_event_count(), 0);
+    assert_eq!(sequence.get_length(), 0.0);
+}
+
+#[test]
+fn sequence_plays_same_as_midi_file() {
+    let mut sequencer = self::sequencer();
+    sequencer.play(sparse().get_tracks()[0].clone(), false);
+    let expected = synth_util::play(&mut sequencer, 2.0);
+
+    let mut sequencer = self::sequencer();
+    sequencer.play_sequence(&sparse_sequence(), false);
+    let actual = synth_util::play(&mut sequencer, 2.0);
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+#[should_panic(expected = "The command must be a channel message type between 0x80 and 0xE0.")]
+fn system_message_in_sequence_panics() {
+    Sequence::new().push(0.0, 0, 0xF0, 0, 0);
+}
+
+// The GS "use for rhythm part" SysEx switches the part 1 to the drums before the note.
+fn rhythm_part() -> MidiFile {
+    MidiFile::from_bytes(&midi_util::format0(&[
+        0, 0xF0, 10, 0x41, 0x10, 0x42, 0x12, 0x40, 0x11, 0x15, 0x02, 0x18, 0xF7, 0, 0x90, 69, 100,
+        0x83, 0x60, 0x80, 69, 0, 0, 0xFF, 0x2F, 0,
+    ]))
+    .unwrap()
+}
+
+#[test]
+fn gs_rhythm_part_switches_channel() {
+    let mut sequencer = self::sequencer();
+    sequencer.play(rhythm_part().get_tracks()[0].clone(), false);
+    synth_util::play(&mut sequencer, 0.1);
+    assert!(sequencer.get_synthesizer().is_percussion_channel(0));
+
+    let mut settings = synth_util::settings();
+    settings.enable_gs_rhythm_parts = false;
+    let mut sequencer = MidiFileSequencer::new(synth_util::synthesizer(&settings));
+    sequencer.play(rhythm_part().get_tracks()[0].clone(), false);
+    synth_util::play(&mut sequencer, 0.1);
+    assert!(!sequencer.get_synthesizer().is_percussion_channel(0));
+}
+
+#[test]
+fn master_volume_automation_fades_out() {
+    let midi_file = MidiFile::from_bytes(&midi_util::flute_note(69)).unwrap();
+    let mut automation = Automation::new();
+    automation.push(0.0, 1.0);
+    automation.push(0.25, 0.0);
+
+    let mut sequencer = self::sequencer();
+    sequencer.set_automation(AutomationTarget::MasterVolume, Some(automation.clone()));
+    assert_eq!(
+        sequencer.get_automation(AutomationTarget::MasterVolume),
+        Some(&automation)
+    );
+    sequencer.play(midi_file.get_tracks()[0].clone(), false);
+    let left = synth_util::play(&mut sequencer, 0.5);
+
+    assert!(synth_util::rms(&left[..synth_util::samples(0.1)]) > 0.01);
+    assert_eq!(synth_util::peak(&left[synth_util::samples(0.3)..]), 0.0);
+
+    sequencer.clear_automations();
+    assert_eq!(
+        sequencer.get_automation(AutomationTarget::MasterVolume),
+        None
+    );
+}
+
+#[test]
+#[should_panic(expected = "The channel must be less than the channel count.")]
+fn automation_of_invalid_channel_panics() {
+    self::sequencer().set_automation(AutomationTarget::ChannelPan(16), Some(Automation::new()));
+}
+
+#[test]
+fn play_loops_fades_out_after_loops() {
+    let midi_file = MidiFile::new_with_loop_type(
+        &mut midi_util::looped().as_slice(),
+        MidiFileLoopType::RpgMaker,
+    )
+    .unwrap();
+    let mut sequencer = self::sequencer();
+    sequencer.play_loops(midi_file.get_tracks()[0].clone(), 2, 0.5);
+    let left = synth_util::play(&mut sequencer, 2.5);
+
+    // The second pass of the loop is from 1 to 1.5 seconds, and the fade-out is from 1.5 to 2 seconds.
+    let range = |start: f64, end: f64| &left[synth_util::samples(start)..synth_util::samples(end)];
+    let loop_level = synth_util::rms(range(1.0, 1.5));
+    assert!(loop_level > 0.01);
+    assert!(synth_util::rms(range(1.5, 1.75)) < loop_level);
+    assert!(synth_util::rms(range(1.75, 2.0)) < synth_util::rms(range(1.5, 1.75)));
+    assert_eq!(synth_util::peak(range(2.1, 2.5)), 0.0);
+    assert!(sequencer.end_of_sequence());
+}
+
+#[test]
+fn event_filter_sees_channel_messages() {
+    let midi_file = MidiFile::from_bytes(&midi_util::flute_note(69)).unwrap();
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let recorded = messages.clone();
+
+    let mut sequencer = self::sequencer();
+    sequencer.set_event_filter(move |message| {
+        recorded.lock().unwrap().push(*message);
+        true
+    });
+    sequencer.play(midi_file.get_tracks()[0].clone(), false);
+    synth_util::play(&mut sequencer, 1.0);
+
+    assert_eq!(
+        *messages.lock().unwrap(),
+        [
+            MidiMessage::new(0, 0xC0, 72, 0),
+            MidiMessage::new(0, 0x90, 69, 100),
+            MidiMessage::new(0, 0x80, 69, 0)
+        ]
+    );
+}
+
+#[test]
+fn event_filter_changes_messages() {
+    let midi_file = MidiFile::from_bytes(&midi_util::flute_note(69)).unwrap();
+
+    let mut sequencer = self::sequencer();
+    sequencer.set_event_filter(|message| {
+        if message.command == 0x90 || message.command == 0x80 {
+            message.data1 += 12;
+        }
+        true
+    });
+    sequencer.play(midi_file.get_tracks()[0].clone(), false);
+    let left = synth_util::play(&mut sequencer, 0.5);
+    assert!((synth_util::frequency(&left) - 880.0).abs() < 20.0);
+}
+
+#[test]
+fn event_filter_drops_messages() {
+    let midi_file = MidiFile::from_bytes(&midi_util::flute_note(69)).unwrap();
+
+    let mut sequencer = self::sequencer();
+    sequencer.set_event_filter(|message| message.command != 0x90);
+    sequencer.play(midi_file.get_tracks()[0].clone(), false);
+    assert_eq!(
+        synth_util::peak(&synth_util::play(&mut sequencer, 0.5)),
+        0.0
+    );
+
+    sequencer.clear_event_filter();
+    sequencer.play(midi_file.get_tracks()[0].clone(), false);
+    assert!(synth_util::rms(&synth_util::play(&mut sequencer, 0.5)) > 0.01);
+}
+
+// A silent file of a 4/4 bar at 120 BPM, which is 2 seconds long.
+fn silent_bar() -> MidiFile {
+    MidiFile::from_bytes(&midi_util::format0(&[0x8F, 0x00, 0xFF, 0x2F, 0])).unwrap()
+}
+
+#[test]
+fn metronome_clicks_on_beats() {
+    let midi_file = silent_bar();
+    let mut metronome = Metronome::new(midi_file.get_tempo_map());
+    assert_eq!(metronome.get_volume(), 0.5);
+    metronome.set_volume(1.0);
+    metronome.set_sounds(&[1.0], &[0.5]);
+
+    let mut sequencer = self::sequencer();
+    sequencer.set_metronome(Some(metronome));
+    assert!(sequencer.get_metronome().is_some());
+    sequencer.play(midi_file.get_tracks()[0].clone(), false);
+    let left = synth_util::play(&mut sequencer, 2.0);
+
+    let clicks: Vec<_> = left
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| **x != 0.0)
+        .map(|(t, x)| (t, *x))
+        .collect();
+    assert_eq!(
+        clicks,
+        [
+            (0, 1.0),
+            (synth_util::samples(0.5), 0.5),
+            (synth_util::samples(1.0), 0.5),
+            (synth_util::samples(1.5), 0.5)
+        ]
+    );
+}
+
+#[test]
+fn metronome_synthesizes_clicks() {
+    let midi_file = silent_bar();
+    let mut metronome = Metronome::new(midi_file.get_tempo_map());
+    metronome.set_sounds(&[1.0], &[1.0]);
+    metronome.clear_sounds();
+    assert_eq!(metronome.get_tempo_map().get_resolution(), 480);
+
+    let mut sequencer = self::sequencer();
+    sequencer.set_metronome(Some(metronome));
+    sequencer.play(midi_file.get_tracks()[0].clone(), false);
+    let left = synth_util::play(&mut sequencer, 1.0);
+
+    assert!(synth_util::rms(&left[..synth_util::samples(0.03)]) > 0.01);
+    assert_eq!(
+        synth_util::peak(&left[synth_util::samples(0.1)..synth_util::samples(0.5)]),
+        0.0
+    );
+    assert!(synth_util::rms(&left[synth_util::samples(0.5)..synth_util::samples(0.53)]) > 0.01);
+
+    sequencer.set_metronome(None);
+    assert!(sequencer.get_metronome().is_none());
+    assert_eq!(
+        synth_util::peak(&synth_util::play(&mut sequencer, 0.5)),
+        0.0
+    );
+}
+
+#[test]
+fn tempo_map_sets_synthesizer_tempo() {
+    let midi_file = MidiFile::from_bytes(&midi_util::format0(&midi_util::TEMPO_CHANGE)).unwrap();
+    let mut sequencer = sequencer();
+    sequencer.play(midi_file.get_tracks()[0].clone(), false);
+    synth_util::play(&mut sequencer, 0.5);
+    assert!(sequencer.get_tempo_map().is_none());
+    assert_eq!(sequencer.get_synthesizer().get_tempo(), 120.0);
+
+    sequencer.set_tempo_map(Some(midi_file.get_tempo_map().clone()));
+    sequencer.set_speed(2.0);
+    synth_util::play(&mut sequencer, 0.1);
+    assert_eq!(sequencer.get_synthesizer().get_tempo(), 240.0);
+    synth_util::play(&mut sequencer, 0.4);
+    assert_eq!(sequencer.get_synthesizer().get_tempo(), 120.0);
+
+    // Without the tempo map, the tempo stays as it is.
+    sequencer.set_tempo_map(None);
+    sequencer.set_speed(1.0);
+    synth_util::play(&mut sequencer, 0.1);
+    assert_eq!(sequencer.get_synthesizer().get_tempo(), 120.0);
+}
+
+// The channel 0 selects the program, and plays the key 69 from 0.5 to 1 second.
+fn late_note(program: u8) -> MidiFile {
+    MidiFile::from_bytes(&midi_util::format0(&[
+        0, 0xC0, program, 0x83, 0x60, 0x90, 69, 100, 0x83, 0x60, 0x80, 69, 0, 0, 0xFF, 0x2F, 0,
+    ]))
+    .unwrap()
+}
+
+#[test]
+fn seek_restarts_held_notes() {
+    let midi_file = MidiFile::from_bytes(&midi_util::looped()).unwrap();
+    let mut sequencer = sequencer();
+    sequencer.play(midi_file.get_tracks()[0].clone(), false);
+
+    sequencer.seek(0.75);
+    assert_eq!(sequencer.get_position(), 0.75);
+    assert!(synth_util::rms(&synth_util::play(&mut sequencer, 0.1)) > 0.01);
+
+    // No note is held in the gap between the notes.
+    sequencer.seek(0.45);
+    assert!(synth_util::rms(&synth_util::play(&mut sequencer, 0.05)) < 1.0E-3);
+}
+
+#[test]
+fn seek_sends_program_changes() {
+    for (program, noise) in [
+        (synth_util::FLUTE as u8, false),
+        (synth_util::NOISE as u8, true),
+    ] {
+        let settings = synth_util::settings();
+        let synthesizer = Synthesizer::new(&synth_util::sound_font(), &settings).unwrap();
+        let mut sequencer = MidiFileSequencer::new(synthesizer);
+        sequencer.play(late_note(program).get_tracks()[0].clone(), false);
+        sequencer.seek(0.6);
+
+        // The noise crosses zero much more often than the flute at 440 Hz.
+        let data = synth_util::play(&mut sequencer, 0.1);
+        assert_eq!(synth_util::crossings(&data) > 200, noise);
+    }
+}
+
+#[test]
+fn sequencer_render_range_continues_from_end() {
+    let midi_file = MidiFile::from_bytes(&midi_util::looped()).unwrap();
+    let mut sequencer = sequencer();
+    sequencer.play(midi_file.get_tracks()[0].clone(), false);
+
+    let (left, right) = sequencer.render_range(0.5, 0.75);
+    assert_eq!(left.len(), synth_util::samples(0.25));
+    assert_eq!(right.len(), synth_util::samples(0.25));
+    assert!(synth_util::rms(&left) > 0.01);
+    assert!((sequencer.get_position() - 0.75).abs() < 1.0E-3);
+}
+
+#[test]
+#[should_panic(expected = "The position must be a non-negative value.")]
+fn negative_seek_panics() {
+    let mut sequencer = sequencer();
+    sequencer.seek(-1.0);
+}
+
+fn looped_sequencer(midi_file: &[u8], loop_mode: SequencerLoopMode) -> MidiFileSequencer {
+    let midi_file =
+        MidiFile::new_with_loop_type(&mut &midi_file[..], MidiFileLoopType::RpgMaker).unwrap();
+    let mut sequencer = self::sequencer();
+    sequencer.play_with_loop_mode(midi_file.get_tracks()[0].clone(), loop_mode);
+    sequencer
+}
+
+// Renders the blocks of 10 ms until the end of the sequence, and returns the elapsed time.
+// The position of the sequencer goes back at each loop, so the blocks are counted instead.
+fn play_to_end(sequencer: &mut MidiFileSequencer, max_length: f64) -> f64 {
+    let mut left = vec![0_f32; synth_util::samples(0.01)];
+    let mut right = vec![0_f32; synth_util::samples(0.01)];
+    let mut block_count = 0;
+    while !sequencer.end_of_sequence() && 0.01 * (block_count as f64) < max_length {
+        sequencer.render(&mut left, &mut right);
+        block_count += 1;
+    }
+    0.01 * block_count as f64
+}
+
+#[test]
+fn loop_mode_once_ignores_loop() {
+    let mut sequencer = looped_sequencer(&midi_util::looped(), SequencerLoopMode::Once);
+    let elapsed = play_to_end(&mut sequencer, 10.0);
+    assert!(sequencer.end_of_sequence());
+    assert!((elapsed - 1.0).abs() < 0.02);
+}
+
+#[test]
+fn loop_mode_count_plays_loop_count_times() {
+    let mut sequencer = looped_sequencer(&midi_util::looped(), SequencerLoopMode::Count(3));
+    let elapsed = play_to_end(&mut sequencer, 10.0);
+    assert!(sequencer.end_of_sequence());
+    assert!((elapsed - 2.0).abs() < 0.02);
+}
+
+#[test]
+fn loop_mode_infinite_keeps_looping() {
+    let mut sequencer = looped_sequencer(&midi_util::looped(), SequencerLoopMode::Infinite);
+    let left = synth_util::play(&mut sequencer, 3.0);
+    assert!(!sequencer.end_of_sequence());
+    assert!((0.5..1.0).contains(&sequencer.get_position()));
+
+    // The loop note sounds in the last pass.
+    let start = synth_util::samples(2.6);
+    assert!(synth_util::rms(&left[start..start + synth_util::samples(0.2)]) > 0.01);
+}
+
+#[test]
+fn loop_mode_range_repeats_range() {
+    let loop_mode = SequencerLoopMode::Range {
+        start: 0.5,
+        end: 0.75,
+    };
+    let mut sequencer = looped_sequencer(&midi_util::looped(), loop_mode);
+    let left = synth_util::play(&mut sequencer, 2.0);
+
+    // The range is repeated forever, so the position stays within the range.
+    assert!(!sequencer.end_of_sequence());
+    assert!((0.5..0.75).contains(&sequencer.get_position()));
+    assert!(synth_util::rms(&left[synth_util::samples(1.9)..]) > 0.01);
+}
+
+#[test]
+fn loop_restores_program_at_loop_start() {
+    // The program is changed to the noise after the note in the loop.
+    let data = midi_util::format0(&[
+        0, 0xC0, 72, 0x83, 0x60, 0xB0, 111, 0, 0, 0x90, 69, 100, 0x81, 0x70, 0x80, 69, 0, 0, 0xC0,
+        120, 0x81, 0x70, 0xFF, 0x2F, 0,
+    ]);
+    let mut sequencer = looped_sequencer(&data, SequencerLoopMode::Count(2));
+    let left = synth_util::play(&mut sequencer, 1.5);
+
+    // The second pass plays the flute again.
+    let pass = &left[synth_util::samples(1.0)..synth_util::samples(1.25)];
+    assert!((synth_util::frequency(pass) - 440.0).abs() < 5.0);
+}
+
+#[test]
+#[should_panic(expected = "The loop count must be greater than zero.")]
+fn zero_loop_count_panics() {
+    looped_sequencer(&midi_util::looped(), SequencerLoopMode::Count(0));
+}
+
+#[test]
+#[should_panic(expected = "The loop end must be after the loop start.")]
+fn empty_loop_range_panics() {
+    let loop_mode = SequencerLoopMode::Range {
+        start: 0.5,
+        end: 0.5,
+    };
+    looped_sequencer(&midi_util::looped(), loop_mode);
+}
@@ -0,0 +1,161 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use rustysynth::SoundBankSet;
+use rustysynth::SoundFont;
+use rustysynth::SoundFontError;
+use rustysynth::Synthesizer;
+use rustysynth::SynthesizerSettings;
+use std::io::Cursor;
+use std::ptr;
+use std::sync::Arc;
+
+#[test]
+fn synthesizers_borrow_sound_font() {
+    let sound_font = Arc::new(SoundFont::test_bank());
+    let settings = SynthesizerSettings::new(44100);
+
+    let synthesizer1 = Synthesizer::new(&sound_font, &settings).unwrap();
+    let synthesizer2 = Synthesizer::new(&sound_font, &settings).unwrap();
+
+    // Both synthesizers refer to the same SoundFont, instead of their own copies.
+    assert_eq!(Arc::strong_count(&sound_font), 3);
+    assert!(ptr::eq(synthesizer1.get_sound_font(), &*sound_font));
+    assert!(ptr::eq(synthesizer2.get_sound_font(), &*sound_font));
+    assert!(ptr::eq(
+        synthesizer1.get_sound_font().get_wave_data(),
+        synthesizer2.get_sound_font().get_wave_data()
+    ));
+
+    drop(synthesizer1);
+    drop(synthesizer2);
+    assert_eq!(Arc::strong_count(&sound_font), 1);
+}
+
+#[test]
+fn set_counts_added_sound_fonts() {
+    let sound_font = Arc::new(SoundFont::test_bank());
+
+    let mut set = SoundBankSet::new();
+    assert!(set.is_empty());
+
+    set.add(&sound_font, 0);
+    set.add(&sound_font, 1);
+    assert!(!set.is_empty());
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+#[should_panic]
+fn merge_of_empty_set_panics() {
+    let _ = SoundBankSet::new().merge();
+}
+
+#[test]
+fn merge_shares_wave_data_of_single_sound_font() {
+    let sound_font = Arc::new(SoundFont::test_bank());
+
+    // The second SoundFont has no preset which is not overridden by the first one.
+    let mut set = SoundBankSet::new();
+    set.add(&sound_font, 0);
+    set.add(&sound_font, 0);
+    let merged = set.merge().unwrap();
+
+    assert!(ptr::eq(merged.get_wave_data(), sound_font.get_wave_data()));
+    assert_eq!(merged.get_presets().len(), sound_font.get_presets().len());
+}
+
+#[test]
+fn merge_shares_wave_data_of_multiple_sound_fonts() {
+    let sound_font1 = Arc::new(SoundFont::test_bank());
+    let sound_font2 = Arc::new(SoundFont::test_bank());
+
+    let mut set = SoundBankSet::new();
+    set.add(&sound_font1, 0);
+    set.add(&sound_font2, 1);
+    let merged = set.merge().unwrap();
+
+    // Each sample refers to the sample data of the SoundFont it came from.
+    let sample_count = sound_font1.get_sample_headers().len();
+    assert_eq!(merged.get_sample_headers().len(), 2 * sample_count);
+    assert!(ptr::eq(merged.get_wave_data(), sound_font1.get_wave_data()));
+    assert!(ptr::eq(
+        merged.get_sample_wave_data(0),
+        sound_font1.get_wave_data()
+    ));
+    assert!(ptr::eq(
+        merged.get_sample_wave_data(sample_count),
+        sound_font2.get_wave_data()
+    ));
+    assert_eq!(
+        merged.get_presets().len(),
+        2 * sound_font1.get_presets().len()
+    );
+}
+
+#[test]
+fn merge_plays_presets_of_second_sound_font() {
+    let sound_font1 = Arc::new(SoundFont::test_bank());
+    let sound_font2 = Arc::new(SoundFont::test_bank());
+
+    let mut set = SoundBankSet::new();
+    set.add(&sound_font1, 0);
+    set.add(&sound_font2, 1);
+    let merged = Arc::new(set.merge().unwrap());
+
+    // The preset moved to bank 1 sounds the same as the original one.
+    let expected = render(&sound_font2, 0);
+    let actual = render(&merged, 1);
+    assert!(expected.iter().any(|&x| x != 0.0));
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn merge_can_be_written() {
+    let sound_font1 = Arc::new(SoundFont::test_bank());
+    let sound_font2 = Arc::new(SoundFont::test_bank());
+
+    let mut set = SoundBankSet::new();
+    set.add(&sound_font1, 0);
+    set.add(&sound_font2, 1);
+    let merged = Arc::new(set.merge().unwrap());
+
+    let mut data: Vec<u8> = Vec::new();
+    merged.write(&mut data).unwrap();
+    let written = Arc::new(SoundFont::new(&mut Cursor::new(&data)).unwrap());
+
+    // The sample data of both SoundFonts are written into a single chunk.
+    assert_eq!(
+        written.get_wave_data().len(),
+        2 * sound_font1.get_wave_data().len()
+    );
+    assert_eq!(render(&written, 1), render(&merged, 1));
+}
+
+#[test]
+fn merge_rejects_bank_offset_overflow() {
+    let sound_font = Arc::new(SoundFont::test_bank());
+
+    let mut set = SoundBankSet::new();
+    set.add(&sound_font, 0);
+    set.add(&sound_font, i32::MAX);
+    assert!(matches!(set.merge(), Err(SoundFontError::MergedTooLarge)));
+
+    let mut set = SoundBankSet::new();
+    set.add(&sound_font, 0);
+    set.add(&sound_font, 65536);
+    assert!(matches!(set.merge(), Err(SoundFontError::MergedTooLarge)));
+}
+
+fn render(sound_font: &Arc<SoundFont>, bank: i32) -> Vec<f32> {
+    let settings = SynthesizerSettings::new(44100);
+    let mut synthesizer = Synthesizer::new(sound_font, &settings).unwrap();
+    synthesizer.process_midi_message(0, 0xB0, 0x00, bank);
+    synthesizer.process_midi_message(0, 0xC0, 0, 0);
+    synthesizer.note_on(0, 60, 100);
+
+    let mut left = vec![0_f32; 4410];
+    let mut right = vec![0_f32; 4410];
+    synthesizer.render(&mut left, &mut right);
+    left
+}
@@ -0,0 +1,68 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::synth_util;
+use rustysynth::SoundFont;
+use rustysynth::Synthesizer;
+use std::sync::Arc;
+
+// Plays the flute with the SoundFont.
+fn play(sound_font: SoundFont) -> Vec<f32> {
+    let settings = synth_util::settings();
+    let mut synthesizer = Synthesizer::new(&Arc::new(sound_font), &settings).unwrap();
+    synthesizer.process_midi_message(0, 0xC0, synth_util::FLUTE, 0);
+    synthesizer.note_on(0, 69, 100);
+    synth_util::render(&mut synthesizer, 0.5)
+}
+
+#[test]
+fn sixteen_bit_sound_font_has_no_24_bit_data() {
+    assert_eq!(SoundFont::test_bank().get_wave_data_24(), None);
+}
+
+#[test]
+fn sm24_extends_samples() {
+    let length = SoundFont::test_bank().get_wave_data().len();
+    let sound_font =
+        SoundFont::from_bytes(&synth_util::test_bank_file_with_sm24(0, length)).unwrap();
+    assert_eq!(sound_font.get_wave_data_24(), Some(&vec![0_u8; length][..]));
+    let expected = play(sound_font);
+
+    // The lower bits change the output by less than the 16-bit step.
+    let sound_font =
+        SoundFont::from_bytes(&synth_util::test_bank_file_with_sm24(0xFF, length)).unwrap();
+    let actual = play(sound_font);
+    assert_ne!(actual, expected);
+    let difference = actual
+        .iter()
+        .zip(expected.iter())
+        .fold(0_f32, |max, (x, y)| max.max((x - y).abs()));
+    assert!(difference < 1_f32 / 32768_f32);
+}
+
+#[test]
+fn short_sm24_is_ignored() {
+    let length = SoundFont::test_bank().get_wave_data().len();
+    let sound_font =
+        SoundFont::from_bytes(&synth_util::test_bank_file_with_sm24(0xFF, length / 2)).unwrap();
+    assert_eq!(sound_font.get_wave_data_24(), None);
+}
+
+#[test]
+fn sm24_is_written() {
+    let length = SoundFont::test_bank().get_wave_data().len();
+    let data = synth_util::test_bank_file_with_sm24(0x80, length);
+    let mut written = Vec::new();
+    SoundFont::from_bytes(&data)
+        .unwrap()
+        .write(&mut written)
+        .unwrap();
+
+    // The samples are laid out again, but they sound the same.
+    let written = SoundFont::from_bytes(&written).unwrap();
+    assert_eq!(
+        written.get_wave_data_24().map(|x| x.len()),
+        Some(written.get_wave_data().len())
+    );
+    assert_eq!(play(written), play(SoundFont::from_bytes(&data).unwrap()));
+}
@@ -0,0 +1,110 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use rustysynth::SoundFont;
+use rustysynth::SoundFontError;
+use std::io::Cursor;
+
+// Loads the test bank with the size of a chunk reduced by one byte,
+// and returns the error and the position of the chunk.
+fn load_with_broken_chunk(id: &[u8; 4]) -> (SoundFontError, u64) {
+    let mut data: Vec<u8> = Vec::new();
+    SoundFont::test_bank().write(&mut data).unwrap();
+
+    let position = data.windows(4).position(|x| x == id).unwrap();
+    let size = u32::from_le_bytes(data[position + 4..position + 8].try_into().unwrap());
+    data[position + 4..position + 8].copy_from_slice(&(size - 1).to_le_bytes());
+
+    match SoundFont::new(&mut Cursor::new(data)) {
+        Ok(_) => panic!(
+            "the broken '{}' chunk was loaded",
+            String::from_utf8_lossy(id)
+        ),
+        Err(error) => (error, position as u64),
+    }
+}
+
+#[test]
+fn invalid_lists_report_chunk_and_offset() {
+    let (error, position) = load_with_broken_chunk(b"phdr");
+    match error {
+        SoundFontError::InvalidPresetList { id, offset } => {
+            assert_eq!(id, b"phdr");
+            assert_eq!(offset, position);
+        }
+        error => panic!("unexpected error: {error}"),
+    }
+
+    let (error, position) = load_with_broken_chunk(b"inst");
+    match error {
+        SoundFontError::InvalidInstrumentList { id, offset } => {
+            assert_eq!(id, b"inst");
+            assert_eq!(offset, position);
+        }
+        error => panic!("unexpected error: {error}"),
+    }
+
+    let (error, position) = load_with_broken_chunk(b"shdr");
+    match error {
+        SoundFontError::InvalidSampleHeaderList { id, offset } => {
+            assert_eq!(id, b"shdr");
+            assert_eq!(offset, position);
+        }
+        error => panic!("unexpected error: {error}"),
+    }
+
+    for chunk in [b"pbag", b"ibag"] {
+        let (error, position) = load_with_broken_chunk(chunk);
+        match error {
+            SoundFontError::InvalidZoneList { id, offset } => {
+                assert_eq!(id, chunk);
+                assert_eq!(offset, position);
+            }
+            error => panic!("unexpected error: {error}"),
+        }
+    }
+
+    for chunk in [b"pgen", b"igen"] {
+        let (error, position) = load_with_broken_chunk(chunk);
+        match error {
+            SoundFontError::InvalidGeneratorList { id, offset } => {
+                assert_eq!(id, chunk);
+                assert_eq!(offset, position);
+            }
+            error => panic!("unexpected error: {error}"),
+        }
+    }
+}
+
+#[test]
+fn invalid_lists_display_chunk_and_offset() {
+    let (error, position) = load_with_broken_chunk(b"phdr");
+    assert_eq!(
+        error.to_string(),
+        format!("the preset list in the 'phdr' chunk at 0x{position:X} is invalid")
+    );
+
+    let (error, position) = load_with_broken_chunk(b"inst");
+    assert_eq!(
+        error.to_string(),
+        format!("the instrument list in the 'inst' chunk at 0x{position:X} is invalid")
+    );
+
+    let (error, position) = load_with_broken_chunk(b"shdr");
+    assert_eq!(
+        error.to_string(),
+        format!("the sample header list in the 'shdr' chunk at 0x{position:X} is invalid")
+    );
+
+    let (error, position) = load_with_broken_chunk(b"ibag");
+    assert_eq!(
+        error.to_string(),
+        format!("the zone list in the 'ibag' chunk at 0x{position:X} is invalid")
+    );
+
+    let (error, position) = load_with_broken_chunk(b"pgen");
+    assert_eq!(
+        error.to_string(),
+        format!("the generator list in the 'pgen' chunk at 0x{position:X} is invalid")
+    );
+}
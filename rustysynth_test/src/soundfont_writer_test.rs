@@ -0,0 +1,94 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use crate::synth_util;
+use rustysynth::MidiFile;
+use rustysynth::SoundFont;
+use rustysynth::Synthesizer;
+use std::io::Cursor;
+use std::sync::Arc;
+
+// Plays the program on the first channel and the kick on the percussion channel.
+fn play(sound_font: SoundFont, program: i32) -> Vec<f32> {
+    let sound_font = Arc::new(sound_font);
+    let mut synthesizer = Synthesizer::new(&sound_font, &synth_util::settings()).unwrap();
+    synthesizer.process_midi_message(0, 0xC0, program, 0);
+    synthesizer.note_on(0, 69, 100);
+    synthesizer.note_on(9, 36, 100);
+    synth_util::render(&mut synthesizer, 0.2)
+}
+
+#[test]
+fn written_sound_font_sounds_same() {
+    let data = synth_util::test_bank_file();
+    let load = || SoundFont::new(&mut Cursor::new(&data)).unwrap();
+
+    // The samples may be reordered and moved, but their data are kept.
+    let original = SoundFont::test_bank();
+    let written = load();
+    assert_eq!(
+        written.get_sample_headers().len(),
+        original.get_sample_headers().len()
+    );
+    for (i, x) in original.get_sample_headers().iter().enumerate() {
+        let j = written
+            .get_sample_headers()
+            .iter()
+            .position(|y| y.get_name() == x.get_name())
+            .unwrap();
+        let y = &written.get_sample_headers()[j];
+        assert_eq!(
+            y.get_end_loop() - y.get_start_loop(),
+            x.get_end_loop() - x.get_start_loop()
+        );
+        assert_eq!(
+            &written.get_sample_wave_data(j)[y.get_start() as usize..y.get_end() as usize],
+            &original.get_sample_wave_data(i)[x.get_start() as usize..x.get_end() as usize]
+        );
+    }
+
+    for program in [0, 16, 72, 120] {
+        assert_eq!(play(load(), program), play(SoundFont::test_bank(), program));
+    }
+}
+
+#[test]
+fn used_presets_are_found() {
+    // The flute on the first channel and the kick on the percussion channel.
+    let midi_file = MidiFile::from_bytes(&midi_util::format0(&[
+        0, 0xC0, 72, 0, 0x90, 69, 100, 0, 0x99, 36, 100, 0x83, 0x60, 0x80, 69, 0, 0, 0x89, 36, 0,
+        0, 0xFF, 0x2F, 0,
+    ]))
+    .unwrap();
+
+    assert_eq!(midi_file.get_used_presets(), [(0, 72), (128, 0)]);
+}
+
+#[test]
+fn subset_contains_only_selected_presets() {
+    let mut data = Vec::new();
+    SoundFont::test_bank()
+        .write_subset(&mut data, &[(0, 72), (128, 0)])
+        .unwrap();
+    let subset = SoundFont::new(&mut Cursor::new(&data)).unwrap();
+
+    // The default piano is kept, and the flute and the kick share the sine wave.
+    let mut presets: Vec<(i32, i32)> = subset
+        .get_presets()
+        .iter()
+        .map(|x| (x.get_bank_number(), x.get_patch_number()))
+        .collect();
+    presets.sort();
+    assert_eq!(presets, [(0, 0), (0, 72), (128, 0)]);
+    let mut samples: Vec<&str> = subset
+        .get_sample_headers()
+        .iter()
+        .map(|x| x.get_name())
+        .collect();
+    samples.sort();
+    assert_eq!(samples, ["Noise", "Saw", "Sine"]);
+    assert!(data.len() < synth_util::test_bank_file().len());
+
+    assert_eq!(play(subset, 72), play(SoundFont::test_bank(), 72));
+}
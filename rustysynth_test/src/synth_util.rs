@@ -0,0 +1,119 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use rustysynth::MidiFileSequencer;
+use rustysynth::SoundFont;
+use rustysynth::Synthesizer;
+use rustysynth::SynthesizerSettings;
+use std::sync::Arc;
+
+pub const SAMPLE_RATE: i32 = 44100;
+
+// The flute of the test bank is a looped sine wave, which is 440 Hz at the key 69.
+pub const FLUTE: i32 = 72;
+// The sound effects of the test bank are noise without a loop.
+pub const NOISE: i32 = 120;
+
+pub fn sound_font() -> Arc<SoundFont> {
+    Arc::new(SoundFont::test_bank())
+}
+
+// The test bank written in the SF2 format.
+pub fn test_bank_file() -> Vec<u8> {
+    let mut data: Vec<u8> = Vec::new();
+    SoundFont::test_bank().write(&mut data).unwrap();
+    data
+}
+
+// The effects are disabled so that the output ends with the voices.
+pub fn settings() -> SynthesizerSettings {
+    let mut settings = SynthesizerSettings::new(SAMPLE_RATE);
+    settings.enable_reverb_and_chorus = false;
+    settings
+}
+
+// A synthesizer where the first channel plays the flute.
+pub fn synthesizer(settings: &SynthesizerSettings) -> Synthesizer {
+    let mut synthesizer = Synthesizer::new(&sound_font(), settings).unwrap();
+    synthesizer.process_midi_message(0, 0xC0, FLUTE, 0);
+    synthesizer
+}
+
+pub fn samples(seconds: f64) -> usize {
+    (SAMPLE_RATE as f64 * seconds) as usize
+}
+
+// Renders the specified length and returns the left channel.
+pub fn render(synthesizer: &mut Synthesizer, seconds: f64) -> Vec<f32> {
+    let mut left = vec![0_f32; samples(seconds)];
+    let mut right = vec![0_f32; samples(seconds)];
+    synthesizer.render(&mut left, &mut right);
+    left
+}
+
+// Renders the specified length and returns the left channel.
+pub fn play(sequencer: &mut MidiFileSequencer, seconds: f64) -> Vec<f32> {
+    let mut left = vec![0_f32; samples(seconds)];
+    let mut right = vec![0_f32; samples(seconds)];
+    sequencer.render(&mut left, &mut right);
+    left
+}
+
+pub fn rms(data: &[f32]) -> f32 {
+    (data.iter().map(|x| x * x).sum::<f32>() / data.len() as f32).sqrt()
+}
+
+pub fn peak(data: &[f32]) -> f32 {
+    data.iter().fold(0_f32, |max, x| max.max(x.abs()))
+}
+
+// Estimates the frequency from the zero crossings after the attack.
+pub fn frequency(data: &[f32]) -> f64 {
+    let data = &data[samples(0.1)..];
+    crossings(data) as f64 * SAMPLE_RATE as f64 / data.len() as f64
+}
+
+// Counts the rising zero crossings.
+pub fn crossings(data: &[f32]) -> usize {
+    data.windows(2)
+        .filter(|x| x[0] <= 0.0 && x[1] > 0.0)
+        .count()
+}
+
+// A sine wave at the full scale.
+pub fn sine(frequency: f64, seconds: f64) -> Vec<f32> {
+    (0..samples(seconds))
+        .map(|t| {
+            (2.0 * std::f64::consts::PI * frequency * t as f64 / SAMPLE_RATE as f64).sin() as f32
+        })
+        .collect()
+}
+
+// The test bank with the sm24 chunk of the specified length, filled with the value.
+pub fn test_bank_file_with_sm24(value: u8, length: usize) -> Vec<u8> {
+    let data = test_bank_file();
+    let read_u32 = |position: usize| {
+        u32::from_le_bytes(data[position..position + 4].try_into().unwrap()) as usize
+    };
+
+    let mut position = 12;
+    while &data[position + 8..position + 12] != b"sdta" {
+        position += 8 + read_u32(position + 4);
+    }
+    let end = position + 8 + read_u32(position + 4);
+
+    let padded = length + length % 2;
+    let mut chunk = b"sm24".to_vec();
+    chunk.extend((length as u32).to_le_bytes());
+    chunk.extend(vec![value; length]);
+    chunk.resize(8 + padded, 0);
+
+    let mut result = data[..end].to_vec();
+    result.extend(&chunk);
+    result.extend(&data[end..]);
+    let riff_size = (read_u32(4) + chunk.len()) as u32;
+    let list_size = (read_u32(position + 4) + chunk.len()) as u32;
+    result[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    result[position + 4..position + 8].copy_from_slice(&list_size.to_le_bytes());
+    result
+}
@@ -0,0 +1,1524 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::synth_util;
+use crate::synth_util::FLUTE;
+use crate::synth_util::NOISE;
+use rustysynth::ControllerTarget;
+use rustysynth::DeviceProfile;
+use rustysynth::EnvelopeOverride;
+use rustysynth::EnvelopePhase;
+use rustysynth::ExclusiveClassMode;
+use rustysynth::FilterOverride;
+use rustysynth::FilterType;
+use rustysynth::GeneratorOverrides;
+use rustysynth::GeneratorType;
+use rustysynth::KeySplit;
+use rustysynth::LevelReport;
+use rustysynth::LfoOverride;
+use rustysynth::LfoRate;
+use rustysynth::PedalCurve;
+use rustysynth::ReleaseLayer;
+use rustysynth::SoundFont;
+use rustysynth::Synthesizer;
+use rustysynth::SynthesizerError;
+use rustysynth::SynthesizerSettings;
+use rustysynth::VoiceEventKind;
+use rustysynth::VoiceStealingPolicy;
+use rustysynth::VolumeCurve;
+use std::ptr;
+use std::sync::Arc;
+
+#[test]
+fn set_sound_font_keeps_channel_states() {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    let sound_font = synth_util::sound_font();
+
+    synthesizer.set_sound_font(&sound_font, true);
+    assert!(ptr::eq(synthesizer.get_sound_font(), &*sound_font));
+
+    // The program selected before the replacement is still the flute.
+    synthesizer.note_on(0, 69, 100);
+    let data = synth_util::render(&mut synthesizer, 0.5);
+    assert!((synth_util::frequency(&data) - 440.0).abs() < 5.0);
+}
+
+#[test]
+fn set_sound_font_continues_playing_notes() {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    synthesizer.note_on(0, 69, 100);
+    synth_util::render(&mut synthesizer, 0.1);
+
+    synthesizer.set_sound_font(&synth_util::sound_font(), false);
+    let data = synth_util::render(&mut synthesizer, 0.1);
+    assert!(synth_util::rms(&data) > 0.01);
+}
+
+#[test]
+fn set_sound_font_with_reset_stops_notes() {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    synthesizer.note_on(0, 69, 100);
+    synth_util::render(&mut synthesizer, 0.1);
+
+    synthesizer.set_sound_font(&synth_util::sound_font(), true);
+    let data = synth_util::render(&mut synthesizer, 0.1);
+    // The rest of the block rendered before the replacement is still output.
+    let block_size = settings.block_size;
+    assert_eq!(synth_util::peak(&data[block_size..]), 0.0);
+}
+
+#[test]
+fn voice_stealing_none_drops_new_note() {
+    let synthesizer = full_synthesizer(VoiceStealingPolicy::None);
+    assert_eq!(synthesizer.get_voice_stealing(), VoiceStealingPolicy::None);
+
+    assert!(!steals_voice(|_| {}));
+}
+
+#[test]
+fn voice_stealing_oldest_replaces_note() {
+    assert!(steals_voice(|synthesizer| {
+        synthesizer.set_voice_stealing(VoiceStealingPolicy::Oldest)
+    }));
+}
+
+#[test]
+fn voice_stealing_respects_channel_priority() {
+    let mut synthesizer = full_synthesizer(VoiceStealingPolicy::None);
+    assert_eq!(synthesizer.get_channel_priority(0), 0);
+    synthesizer.set_channel_priority(0, 1);
+    assert_eq!(synthesizer.get_channel_priority(0), 1);
+
+    // The new note on the second channel has a lower priority than the playing ones.
+    assert!(!steals_voice(|synthesizer| {
+        synthesizer.set_voice_stealing(VoiceStealingPolicy::LowestChannelPriority);
+        synthesizer.set_channel_priority(0, 1);
+    }));
+    assert!(steals_voice(|synthesizer| {
+        synthesizer.set_voice_stealing(VoiceStealingPolicy::LowestChannelPriority);
+        synthesizer.set_channel_priority(1, 1);
+    }));
+}
+
+// A synthesizer where all the voices are used by the flute on the first channel.
+fn full_synthesizer(voice_stealing: VoiceStealingPolicy) -> Synthesizer {
+    let mut settings = synth_util::settings();
+    settings.maximum_polyphony = 8;
+    settings.voice_stealing = voice_stealing;
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    synthesizer.process_midi_message(1, 0xC0, NOISE, 0);
+    for key in 60..68 {
+        synthesizer.note_on(0, key, 100);
+    }
+    synth_util::render(&mut synthesizer, 0.05);
+    synthesizer
+}
+
+// Checks whether a note on the second channel changes the output of the full synthesizer.
+fn steals_voice(setup: impl Fn(&mut Synthesizer)) -> bool {
+    let mut without = full_synthesizer(VoiceStealingPolicy::None);
+    let mut with = full_synthesizer(VoiceStealingPolicy::None);
+    setup(&mut without);
+    setup(&mut with);
+    with.note_on(1, 60, 100);
+    synth_util::render(&mut without, 0.1) != synth_util::render(&mut with, 0.1)
+}
+
+#[test]
+fn set_block_size_keeps_playing_notes() {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    synthesizer.note_on(0, 69, 100);
+    synth_util::render(&mut synthesizer, 0.1);
+
+    synthesizer.set_block_size(256).unwrap();
+    let data = synth_util::render(&mut synthesizer, 0.5);
+    assert_eq!(synthesizer.get_block_size(), 256);
+    assert!((synth_util::frequency(&data) - 440.0).abs() < 5.0);
+
+    assert!(matches!(
+        synthesizer.set_block_size(4),
+        Err(SynthesizerError::BlockSizeOutOfRange(4))
+    ));
+}
+
+#[test]
+fn set_maximum_polyphony_takes_effect_at_next_block() {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    synthesizer.set_maximum_polyphony(8).unwrap();
+    assert_eq!(synthesizer.get_maximum_polyphony(), 64);
+
+    synth_util::render(&mut synthesizer, 0.01);
+    assert_eq!(synthesizer.get_maximum_polyphony(), 8);
+
+    assert!(matches!(
+        synthesizer.set_maximum_polyphony(1000),
+        Err(SynthesizerError::MaximumPolyphonyOutOfRange(1000))
+    ));
+}
+
+#[test]
+fn parallel_voices_sound_same() {
+    let settings = synth_util::settings();
+    let mut sequential = synth_util::synthesizer(&settings);
+    let mut parallel = synth_util::synthesizer(&settings);
+    parallel.set_parallel_voices(true);
+    assert!(!sequential.get_parallel_voices());
+    assert!(parallel.get_parallel_voices());
+
+    // Enough voices are played to exceed the threshold of the parallel rendering.
+    for key in 40..72 {
+        sequential.note_on(0, key, 100);
+        parallel.note_on(0, key, 100);
+    }
+    let expected = synth_util::render(&mut sequential, 0.2);
+    let actual = synth_util::render(&mut parallel, 0.2);
+
+    // Only the order of the summation differs.
+    for (x, y) in expected.iter().zip(actual.iter()) {
+        assert!((x - y).abs() < 1.0E-4);
+    }
+}
+
+#[test]
+fn channel_state_reflects_messages() {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    synthesizer.process_midi_message(0, 0xB0, 0x07, 64);
+    synthesizer.process_midi_message(0, 0xB0, 0x0A, 0);
+    synthesizer.process_midi_message(0, 0xB0, 0x40, 127);
+    synthesizer.process_midi_message(0, 0xE0, 0, 96);
+
+    let state = synthesizer.get_channel_state(0);
+    assert!(!state.is_percussion_channel);
+    assert_eq!(state.patch_number, FLUTE);
+    assert_eq!(state.volume, 64 << 7);
+    assert_eq!(state.pan, 0);
+    assert!(state.hold_pedal);
+    assert_eq!(state.pitch_bend, 96 << 7);
+
+    let state = synthesizer.get_channel_state(9);
+    assert!(state.is_percussion_channel);
+    assert_eq!(state.bank_number, 128);
+    assert_eq!(state.pitch_bend, 8192);
+}
+
+#[test]
+fn load_state_with_voices_resumes_sound() {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    synthesizer.note_on(0, 69, 100);
+    synth_util::render(&mut synthesizer, 0.1);
+
+    let state = synthesizer.save_state(true);
+    assert!(state.has_voices());
+    assert!(state.get_voice_count() > 0);
+    let expected = synth_util::render(&mut synthesizer, 0.1);
+
+    // The sound continues from the exact sample after the snapshot.
+    synthesizer.note_off_all(true);
+    synthesizer.load_state(&state).unwrap();
+    assert_eq!(synth_util::render(&mut synthesizer, 0.1), expected);
+}
+
+#[test]
+fn load_state_without_voices_restores_controllers() {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    synthesizer.set_master_volume(0.25);
+    synthesizer.process_midi_message(0, 0xB0, 0x07, 64);
+    synthesizer.note_on(0, 69, 100);
+
+    let state = synthesizer.save_state(false);
+    assert!(!state.has_voices());
+    assert_eq!(state.get_voice_count(), 0);
+    assert_eq!(state.get_master_volume(), 0.25);
+    assert_eq!(state.get_channel_state(0).volume, 64 << 7);
+
+    synthesizer.reset();
+    synthesizer.set_master_volume(1.0);
+    synthesizer.note_on(0, 69, 100);
+    synthesizer.load_state(&state).unwrap();
+    assert_eq!(synthesizer.get_master_volume(), 0.25);
+    assert_eq!(synthesizer.get_channel_state(0).patch_number, FLUTE);
+    assert_eq!(synthesizer.get_channel_state(0).volume, 64 << 7);
+
+    // The playing notes are stopped.
+    let data = synth_util::render(&mut synthesizer, 0.1);
+    assert_eq!(synth_util::peak(&data), 0.0);
+}
+
+#[test]
+fn load_state_rejects_different_channel_count() {
+    let settings = synth_util::settings();
+    let synthesizer = synth_util::synthesizer(&settings);
+    let state = synthesizer.save_state(false);
+
+    let mut settings = synth_util::settings();
+    settings.channel_count = 32;
+    let mut other = synth_util::synthesizer(&settings);
+    assert!(matches!(
+        other.load_state(&state),
+        Err(SynthesizerError::IncompatibleState)
+    ));
+}
+
+#[test]
+fn channels_of_each_port_are_separate() {
+    let mut settings = synth_util::settings();
+    settings.channel_count = 32;
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    assert_eq!(synthesizer.get_channel_count(), 32);
+    synthesizer.process_midi_message(16, 0xC0, NOISE, 0);
+    assert_eq!(synthesizer.get_channel_state(0).patch_number, FLUTE);
+    assert_eq!(synthesizer.get_channel_state(16).patch_number, NOISE);
+
+    // With 16 channels, the second port is sent to the first one.
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    assert_eq!(synthesizer.get_channel_count(), 16);
+    synthesizer.process_midi_message(16, 0xC0, NOISE, 0);
+    assert_eq!(synthesizer.get_channel_state(0).patch_number, NOISE);
+}
+
+#[test]
+fn invalid_channel_count_is_error() {
+    let mut settings = synth_util::settings();
+    settings.channel_count = 24;
+    let result = Synthesizer::new(&synth_util::sound_font(), &settings);
+    assert!(matches!(
+        result,
+        Err(SynthesizerError::ChannelCountOutOfRange(24))
+    ));
+}
+
+#[test]
+fn voice_events_follow_note() {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    assert_eq!(synthesizer.get_voice_event_capacity(), 0);
+    synthesizer.note_on(0, 60, 100);
+    synth_util::render(&mut synthesizer, 0.1);
+    synthesizer.note_off_all(true);
+    synth_util::render(&mut synthesizer, 0.1);
+    assert_eq!(synthesizer.drain_voice_events().count(), 0);
+
+    synthesizer.set_voice_event_capacity(16);
+    assert_eq!(synthesizer.get_voice_event_capacity(), 16);
+    synthesizer.note_on(0, 69, 100);
+    synth_util::render(&mut synthesizer, 0.1);
+    synthesizer.note_off(0, 69);
+    synth_util::render(&mut synthesizer, 2.0);
+
+    let events: Vec<_> = synthesizer.drain_voice_events().collect();
+    let kinds: Vec<_> = events.iter().map(|x| x.get_kind()).collect();
+    assert_eq!(
+        kinds,
+        [
+            VoiceEventKind::Started,
+            VoiceEventKind::Released,
+            VoiceEventKind::Finished
+        ]
+    );
+    assert!(events
+        .iter()
+        .all(|x| x.get_channel() == 0 && x.get_key() == 69 && x.get_velocity() == 100));
+    assert!(events[1].get_position() >= synth_util::samples(0.3) as u64);
+    assert!(events[2].get_position() > events[1].get_position());
+
+    // The events are taken only once.
+    assert_eq!(synthesizer.drain_voice_events().count(), 0);
+}
+
+#[test]
+fn statistics_count_voices_and_blocks() {
+    let mut settings = synth_util::settings();
+    settings.maximum_polyphony = 8;
+    settings.voice_stealing = VoiceStealingPolicy::Oldest;
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    synthesizer.process_midi_message(1, 0xC0, NOISE, 0);
+    synthesizer.note_on(0, 60, 100);
+    synthesizer.note_on(1, 60, 100);
+    synth_util::render(&mut synthesizer, 0.1);
+
+    let statistics = synthesizer.get_statistics();
+    let count0 = statistics.get_channel_voice_count(0);
+    let count1 = statistics.get_channel_voice_count(1);
+    assert!(count0 > 0 && count1 > 0);
+    assert_eq!(statistics.get_active_voice_count(), count0 + count1);
+    assert_eq!(statistics.get_stolen_voice_count(), 0);
+    assert_eq!(
+        statistics.get_block_count(),
+        synth_util::samples(0.1).div_ceil(settings.block_size) as u64
+    );
+    assert!(statistics.get_max_block_render_time() >= statistics.get_last_block_render_time());
+    assert!(statistics.get_total_render_time() >= statistics.get_max_block_render_time());
+
+    // The new notes exceed the maximum polyphony.
+    for key in 61..69 {
+        synthesizer.note_on(0, key, 100);
+    }
+    synth_util::render(&mut synthesizer, 0.01);
+    assert!(synthesizer.get_statistics().get_stolen_voice_count() > 0);
+
+    synthesizer.reset_statistics();
+    let statistics = synthesizer.get_statistics();
+    assert_eq!(statistics.get_block_count(), 0);
+    assert_eq!(statistics.get_stolen_voice_count(), 0);
+    assert_eq!(statistics.get_active_voice_count(), 8);
+}
+
+#[test]
+fn preset_profiling_measures_each_preset() {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    synthesizer.note_on(0, 69, 100);
+    synth_util::render(&mut synthesizer, 0.1);
+    assert!(synthesizer
+        .get_statistics()
+        .get_preset_statistics()
+        .is_empty());
+
+    assert!(!synthesizer.get_preset_profiling());
+    synthesizer.set_preset_profiling(true);
+    assert!(synthesizer.get_preset_profiling());
+    synth_util::render(&mut synthesizer, 0.1);
+
+    let statistics = synthesizer.get_statistics();
+    let presets = statistics.get_preset_statistics();
+    assert_eq!(presets.len(), 1);
+    assert_eq!(presets[0].get_bank_number(), 0);
+    assert_eq!(presets[0].get_patch_number(), FLUTE);
+    assert_eq!(presets[0].get_name(), "Piccolo");
+    assert!(presets[0].get_voice_block_count() > 0);
+    assert!(presets[0].get_render_time() <= statistics.get_total_render_time());
+}
+
+#[test]
+fn settings_select_percussion_channels() {
+    let mut settings = synth_util::settings();
+    settings.percussion_channels[9] = false;
+    settings.percussion_channels[15] = true;
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    assert!(!synthesizer.is_percussion_channel(9));
+    assert!(synthesizer.is_percussion_channel(15));
+
+    synthesizer.set_percussion_channel(0, true);
+    assert!(synthesizer.is_percussion_channel(0));
+
+    // The reset restores the channels of the settings.
+    synthesizer.reset();
+    assert!(!synthesizer.is_percussion_channel(0));
+    assert!(synthesizer.is_percussion_channel(15));
+}
+
+#[test]
+fn set_percussion_channel_switches_to_drums() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.note_on(0, 69, 100);
+    let melodic = synth_util::render(&mut synthesizer, 0.5);
+
+    // The channel 10 plays the melodic presets once it is switched.
+    let mut drums = synth_util::synthesizer(&synth_util::settings());
+    drums.set_percussion_channel(9, false);
+    drums.process_midi_message(9, 0xC0, FLUTE, 0);
+    drums.note_on(9, 69, 100);
+    assert_eq!(synth_util::render(&mut drums, 0.5), melodic);
+
+    let mut drums = synth_util::synthesizer(&synth_util::settings());
+    drums.set_percussion_channel(0, true);
+    drums.note_on(0, 69, 100);
+    let actual = synth_util::render(&mut drums, 0.5);
+    assert!(synth_util::peak(&actual) > 0.01);
+    assert_ne!(actual, melodic);
+}
+
+// Plays a note on the flute channel after the setup.
+fn play_note(setup: impl Fn(&mut Synthesizer)) -> Vec<f32> {
+    play_note_with(&synth_util::settings(), setup)
+}
+
+fn play_note_with(settings: &SynthesizerSettings, setup: impl Fn(&mut Synthesizer)) -> Vec<f32> {
+    let mut synthesizer = synth_util::synthesizer(settings);
+    setup(&mut synthesizer);
+    synthesizer.note_on(0, 69, 100);
+    synth_util::render(&mut synthesizer, 0.5)
+}
+
+#[test]
+fn modulation_wheel_adds_vibrato_by_default() {
+    let synthesizer = synth_util::synthesizer(&synth_util::settings());
+    assert_eq!(
+        synthesizer.get_modulation_target(0),
+        (ControllerTarget::Vibrato, 50_f32)
+    );
+    assert_eq!(synthesizer.get_vibrato_rate(0), None);
+
+    let expected = play_note(|_| ());
+    let actual = play_note(|x| x.process_midi_message(0, 0xB0, 1, 127));
+    assert_ne!(actual, expected);
+
+    // The vibrato keeps the pitch on average.
+    let frequency = synth_util::frequency(&actual);
+    assert!((frequency - 440.0).abs() < 10.0);
+}
+
+#[test]
+fn modulation_target_reroutes_modulation_wheel() {
+    let expected = play_note(|_| ());
+
+    let actual = play_note(|x| {
+        x.set_modulation_target(0, ControllerTarget::None, 0_f32);
+        x.process_midi_message(0, 0xB0, 1, 127);
+    });
+    assert_eq!(actual, expected);
+
+    let actual = play_note(|x| {
+        x.set_modulation_target(0, ControllerTarget::Volume, -20_f32);
+        x.process_midi_message(0, 0xB0, 1, 127);
+    });
+    let ratio = synth_util::rms(&actual) / synth_util::rms(&expected);
+    assert!((ratio - 0.1).abs() < 0.01);
+}
+
+#[test]
+fn vibrato_rate_changes_vibrato() {
+    let expected = play_note(|x| x.process_midi_message(0, 0xB0, 1, 127));
+    let actual = play_note(|x| {
+        x.set_vibrato_rate(0, Some(20_f32));
+        x.process_midi_message(0, 0xB0, 1, 127);
+    });
+    assert_ne!(actual, expected);
+
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.set_vibrato_rate(0, Some(20_f32));
+    synthesizer.reset();
+    assert_eq!(synthesizer.get_vibrato_rate(0), Some(20_f32));
+}
+
+#[test]
+#[should_panic(expected = "The vibrato rate must be greater than zero.")]
+fn zero_vibrato_rate_panics() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.set_vibrato_rate(0, Some(0_f32));
+}
+
+#[test]
+fn channel_pressure_has_no_effect_by_default() {
+    let synthesizer = synth_util::synthesizer(&synth_util::settings());
+    assert_eq!(synthesizer.get_pressure_target(0).0, ControllerTarget::None);
+
+    let expected = play_note(|_| ());
+    let actual = play_note(|x| x.process_midi_message(0, 0xD0, 127, 0));
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn pressure_target_routes_channel_pressure() {
+    let expected = play_note(|_| ());
+    let actual = play_note(|x| {
+        x.set_pressure_target(0, ControllerTarget::Volume, -20_f32);
+        x.process_midi_message(0, 0xD0, 127, 0);
+    });
+    let ratio = synth_util::rms(&actual) / synth_util::rms(&expected);
+    assert!((ratio - 0.1).abs() < 0.01);
+
+    // The pressure and the modulation wheel are added on the same target.
+    let actual = play_note(|x| {
+        x.set_modulation_target(0, ControllerTarget::Volume, -10_f32);
+        x.set_pressure_target(0, ControllerTarget::Volume, -10_f32);
+        x.process_midi_message(0, 0xB0, 1, 127);
+        x.process_midi_message(0, 0xD0, 127, 0);
+    });
+    let ratio = synth_util::rms(&actual) / synth_util::rms(&expected);
+    assert!((ratio - 0.1).abs() < 0.01);
+}
+
+// Gets the gain of the controller value relative to the full scale.
+fn controller_gain(settings: &SynthesizerSettings, controller: i32, value: i32) -> f32 {
+    let expected = play_note_with(settings, |x| {
+        x.process_midi_message(0, 0xB0, controller, 127)
+    });
+    let actual = play_note_with(settings, |x| {
+        x.process_midi_message(0, 0xB0, controller, value)
+    });
+    synth_util::rms(&actual) / synth_util::rms(&expected)
+}
+
+#[test]
+fn volume_curve_converts_volume_to_gain() {
+    let mut settings = synth_util::settings();
+    assert_eq!(settings.volume_curve, VolumeCurve::Square);
+    assert!((controller_gain(&settings, 7, 64) - 0.254).abs() < 0.01);
+
+    settings.volume_curve = VolumeCurve::Linear;
+    assert!((controller_gain(&settings, 7, 64) - 0.504).abs() < 0.01);
+
+    // The value of 64 is halfway between 1 and 127 in decibels.
+    settings.volume_curve = VolumeCurve::Decibels(40_f32);
+    assert!((controller_gain(&settings, 7, 64) - 0.1).abs() < 0.01);
+    assert_eq!(controller_gain(&settings, 7, 0), 0_f32);
+}
+
+#[test]
+fn expression_curve_converts_expression_to_gain() {
+    let mut settings = synth_util::settings();
+    let mut table = Box::new([0.5_f32; 128]);
+    table[127] = 1_f32;
+    settings.expression_curve = VolumeCurve::Custom(table);
+    assert!((controller_gain(&settings, 11, 64) - 0.5).abs() < 0.01);
+
+    // The volume keeps the default curve.
+    assert!((controller_gain(&settings, 7, 64) - 0.254).abs() < 0.01);
+}
+
+#[test]
+fn invalid_volume_curve_is_error() {
+    let mut settings = synth_util::settings();
+    settings.volume_curve = VolumeCurve::Decibels(0_f32);
+    let result = Synthesizer::new(&synth_util::sound_font(), &settings);
+    assert!(matches!(result, Err(SynthesizerError::InvalidVolumeCurve)));
+}
+
+fn profile_settings(profile: DeviceProfile) -> SynthesizerSettings {
+    let mut settings = synth_util::settings();
+    settings.profile = profile;
+    settings
+}
+
+#[test]
+fn sc55_velocity_curve_is_gentler() {
+    for (profile, expected) in [
+        (DeviceProfile::GeneralMidi, 0.254),
+        (DeviceProfile::Sc55, 0.358),
+    ] {
+        let settings = profile_settings(profile);
+        let mut synthesizer = synth_util::synthesizer(&settings);
+        synthesizer.note_on(0, 69, 127);
+        let loud = synth_util::render(&mut synthesizer, 0.5);
+        let mut synthesizer = synth_util::synthesizer(&settings);
+        synthesizer.note_on(0, 69, 64);
+        let soft = synth_util::render(&mut synthesizer, 0.5);
+
+        let ratio = synth_util::rms(&soft) / synth_util::rms(&loud);
+        assert!((ratio - expected).abs() < 0.01);
+    }
+}
+
+#[test]
+fn sc55_latches_bank_select() {
+    let settings = synth_util::settings();
+    assert_eq!(settings.profile, DeviceProfile::GeneralMidi);
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    synthesizer.process_midi_message(0, 0xB0, 0, 8);
+    assert_eq!(synthesizer.get_channel_state(0).bank_number, 8);
+
+    let mut synthesizer = synth_util::synthesizer(&profile_settings(DeviceProfile::Sc55));
+    synthesizer.process_midi_message(0, 0xB0, 0, 8);
+    assert_eq!(synthesizer.get_channel_state(0).bank_number, 0);
+    synthesizer.process_midi_message(0, 0xC0, FLUTE, 0);
+    assert_eq!(synthesizer.get_channel_state(0).bank_number, 8);
+
+    // The drum kit is selected only by the program change.
+    synthesizer.process_midi_message(9, 0xB0, 0, 8);
+    synthesizer.process_midi_message(9, 0xC0, 0, 0);
+    assert_eq!(synthesizer.get_channel_state(9).bank_number, 128);
+}
+
+#[test]
+fn sc55_hi_hats_cut_each_other_off() {
+    for (profile, expected) in [(DeviceProfile::GeneralMidi, 2), (DeviceProfile::Sc55, 1)] {
+        let mut synthesizer = synth_util::synthesizer(&profile_settings(profile));
+        synthesizer.note_on(9, 42, 100);
+        synthesizer.note_on(9, 46, 100);
+        synth_util::render(&mut synthesizer, 0.01);
+        assert_eq!(
+            synthesizer.get_statistics().get_active_voice_count(),
+            expected
+        );
+    }
+}
+
+#[test]
+fn sc88_surdos_cut_each_other_off() {
+    for (profile, expected) in [(DeviceProfile::Sc55, 2), (DeviceProfile::Sc88, 1)] {
+        let mut synthesizer = synth_util::synthesizer(&profile_settings(profile));
+        synthesizer.note_on(9, 86, 100);
+        synthesizer.note_on(9, 87, 100);
+        synth_util::render(&mut synthesizer, 0.01);
+        assert_eq!(
+            synthesizer.get_statistics().get_active_voice_count(),
+            expected
+        );
+    }
+}
+
+#[test]
+fn reverb_level_scales_reverb_output() {
+    let expected = play_note(|_| ());
+
+    let mut settings = synth_util::settings();
+    settings.enable_reverb_and_chorus = true;
+    let with_reverb = play_note_with(&settings, |_| ());
+    assert_ne!(with_reverb, expected);
+
+    let actual = play_note_with(&settings, |x| {
+        assert_eq!(x.get_reverb_level(), 1_f32);
+        assert_eq!(x.get_chorus_level(), 1_f32);
+        x.set_reverb_level(0_f32);
+        x.set_chorus_level(0_f32);
+    });
+    let difference = actual
+        .iter()
+        .zip(expected.iter())
+        .fold(0_f32, |max, (x, y)| max.max((x - y).abs()));
+    assert!(difference < 1.0E-6);
+}
+
+#[test]
+fn program_override_replaces_program_changes() {
+    let expected = play_note(|x| x.process_midi_message(0, 0xC0, NOISE, 0));
+
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    assert_eq!(synthesizer.get_program_override(0), None);
+    synthesizer.set_program_override(0, 0, NOISE);
+    assert_eq!(synthesizer.get_program_override(0), Some((0, NOISE)));
+    synthesizer.process_midi_message(0, 0xC0, FLUTE, 0);
+    synthesizer.note_on(0, 69, 100);
+    assert_eq!(synth_util::render(&mut synthesizer, 0.5), expected);
+
+    // The last program change takes effect again.
+    synthesizer.note_off_all(true);
+    synthesizer.clear_program_override(0);
+    assert_eq!(synthesizer.get_program_override(0), None);
+    synth_util::render(&mut synthesizer, 0.1);
+    synthesizer.note_on(0, 69, 100);
+    let actual = synth_util::render(&mut synthesizer, 0.5);
+    assert!((synth_util::frequency(&actual) - 440.0).abs() < 10.0);
+}
+
+#[test]
+fn channel_remap_sends_messages_to_destination() {
+    let expected = play_note(|_| ());
+
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    assert_eq!(synthesizer.get_channel_remap(1), 1);
+    synthesizer.set_channel_remap(1, 0);
+    assert_eq!(synthesizer.get_channel_remap(1), 0);
+    synthesizer.process_midi_message(1, 0x90, 69, 100);
+    assert_eq!(synth_util::render(&mut synthesizer, 0.5), expected);
+
+    synthesizer.clear_channel_remaps();
+    assert_eq!(synthesizer.get_channel_remap(1), 1);
+}
+
+#[test]
+fn key_split_selects_preset_by_key() {
+    let split = KeySplit::new(60, (0, NOISE), (0, FLUTE));
+    let with_split = |key| {
+        let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+        synthesizer.process_midi_message(0, 0xC0, 0, 0);
+        synthesizer.set_key_split(0, Some(split));
+        assert_eq!(synthesizer.get_key_split(0), Some(split));
+        synthesizer.note_on(0, key, 100);
+        synth_util::render(&mut synthesizer, 0.5)
+    };
+    let without_split = |program, key| {
+        let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+        synthesizer.process_midi_message(0, 0xC0, program, 0);
+        synthesizer.note_on(0, key, 100);
+        synth_util::render(&mut synthesizer, 0.5)
+    };
+
+    assert_eq!(with_split(59), without_split(NOISE, 59));
+    assert_eq!(with_split(60), without_split(FLUTE, 60));
+}
+
+#[test]
+#[should_panic(expected = "The split key must be between 0 and 127.")]
+fn key_split_with_invalid_key_panics() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.set_key_split(0, Some(KeySplit::new(128, (0, 0), (0, 0))));
+}
+
+#[test]
+fn midi1_packets_are_same_as_messages() {
+    let expected = play_note(|_| ());
+
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.process_ump(&[0x2090_4564]);
+    assert_eq!(synth_util::render(&mut synthesizer, 0.5), expected);
+}
+
+#[test]
+fn midi2_note_on_maps_velocity() {
+    let expected = play_note(|_| ());
+
+    // The 16-bit velocity is reduced to 7 bits.
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.process_ump(&[0x4090_4500, 0xC800_0000]);
+    assert_eq!(synth_util::render(&mut synthesizer, 0.5), expected);
+
+    // The zero velocity is not a note-off.
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.set_voice_event_capacity(16);
+    synthesizer.process_ump(&[0x4090_4500, 0x0000_0000]);
+    let started: Vec<_> = synthesizer
+        .drain_voice_events()
+        .filter(|x| x.get_kind() == VoiceEventKind::Started)
+        .map(|x| x.get_velocity())
+        .collect();
+    assert_eq!(started, [1]);
+}
+
+#[test]
+fn midi2_controllers_keep_high_resolution() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.process_ump(&[
+        0x40B0_0700,
+        0x4010_0000,
+        0x40E0_0000,
+        0xC004_0000,
+        0x40D0_0000,
+        0x8000_0000,
+    ]);
+
+    let state = synthesizer.get_channel_state(0);
+    assert_eq!(state.volume, 0x1004);
+    assert_eq!(state.pitch_bend, 0x3001);
+    assert_eq!(state.pressure, 64);
+}
+
+#[test]
+fn per_note_pitch_bend_shifts_key() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.note_on(0, 69, 100);
+    synthesizer.note_on(0, 57, 100);
+
+    // A quarter of the range of 48 semitones raises only the key 69 by an octave.
+    synthesizer.process_ump(&[0x4060_4500, 0xA000_0000]);
+    synthesizer.note_off(0, 57);
+    synth_util::render(&mut synthesizer, 0.5);
+    let left = synth_util::render(&mut synthesizer, 0.5);
+    assert!((synth_util::frequency(&left) - 880.0).abs() < 20.0);
+}
+
+#[test]
+fn incomplete_packet_is_ignored() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.process_ump(&[0x1000_0000, 0x2090_4564, 0x4090_4700]);
+    synth_util::render(&mut synthesizer, 0.01);
+
+    // The system message is skipped, and the last note-on lacks its velocity.
+    assert_eq!(synthesizer.get_statistics().get_active_voice_count(), 1);
+}
+
+#[test]
+fn exclusive_group_cuts_other_keys() {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    synthesizer.set_exclusive_group(0, 60, 1);
+    synthesizer.set_exclusive_group(0, 62, 1);
+    assert_eq!(synthesizer.get_exclusive_group(0, 60), 1);
+    assert_eq!(synthesizer.get_exclusive_group(0, 64), 0);
+
+    synthesizer.note_on(0, 60, 100);
+    synthesizer.note_on(0, 62, 100);
+    synthesizer.note_on(0, 64, 100);
+    synth_util::render(&mut synthesizer, 0.01);
+    assert_eq!(synthesizer.get_statistics().get_active_voice_count(), 2);
+}
+
+#[test]
+fn exclusive_groups_are_kept_on_reset_and_cleared() {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    synthesizer.set_exclusive_group(0, 60, 1);
+    synthesizer.reset();
+    assert_eq!(synthesizer.get_exclusive_group(0, 60), 1);
+
+    synthesizer.clear_exclusive_groups(0);
+    assert_eq!(synthesizer.get_exclusive_group(0, 60), 0);
+}
+
+#[test]
+fn exclusive_class_fade_keeps_voice_during_fade() {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    assert_eq!(
+        synthesizer.get_exclusive_class_mode(),
+        ExclusiveClassMode::Cut
+    );
+    synthesizer
+        .set_exclusive_class_mode(ExclusiveClassMode::Fade(0.1))
+        .unwrap();
+    synthesizer.set_exclusive_group(0, 60, 1);
+    synthesizer.set_exclusive_group(0, 62, 1);
+
+    synthesizer.note_on(0, 60, 100);
+    synthesizer.note_on(0, 62, 100);
+    synth_util::render(&mut synthesizer, 0.01);
+    assert_eq!(synthesizer.get_statistics().get_active_voice_count(), 2);
+    synth_util::render(&mut synthesizer, 0.2);
+    assert_eq!(synthesizer.get_statistics().get_active_voice_count(), 1);
+}
+
+#[test]
+fn invalid_exclusive_class_fade_is_error() {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    assert!(synthesizer
+        .set_exclusive_class_mode(ExclusiveClassMode::Fade(2.0))
+        .is_err());
+    assert_eq!(
+        synthesizer.get_exclusive_class_mode(),
+        ExclusiveClassMode::Cut
+    );
+
+    let mut settings = synth_util::settings();
+    settings.exclusive_class = ExclusiveClassMode::Fade(-1.0);
+    assert!(Synthesizer::new(&synth_util::sound_font(), &settings).is_err());
+}
+
+// Plays the sine wave of 0.5 seconds, and returns the left and the right channels.
+fn play_sine(root_key: i32, pan: f32, gain: f32) -> (Vec<f32>, Vec<f32>) {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    synthesizer.play_sample(&synth_util::sine(440.0, 0.5), root_key, pan, gain);
+    let mut left = vec![0_f32; synth_util::samples(1.0)];
+    let mut right = vec![0_f32; synth_util::samples(1.0)];
+    synthesizer.render(&mut left, &mut right);
+    assert_eq!(synthesizer.get_statistics().get_active_voice_count(), 0);
+    (left, right)
+}
+
+#[test]
+fn play_sample_plays_waveform_once() {
+    let (left, right) = play_sine(60, 0.0, 1.0);
+    let sound = &left[..synth_util::samples(0.5)];
+    assert!((synth_util::frequency(sound) - 440.0).abs() < 5.0);
+    assert!(synth_util::rms(sound) > 0.0);
+    assert_eq!(left, right);
+    assert_eq!(synth_util::peak(&left[synth_util::samples(0.5) + 1..]), 0.0);
+}
+
+#[test]
+fn play_sample_transposes_by_root_key() {
+    let (left, _) = play_sine(48, 0.0, 1.0);
+    let sound = &left[..synth_util::samples(0.25)];
+    assert!((synth_util::frequency(sound) - 880.0).abs() < 10.0);
+    assert_eq!(
+        synth_util::peak(&left[synth_util::samples(0.25) + 1..]),
+        0.0
+    );
+}
+
+#[test]
+fn play_sample_applies_pan_and_gain() {
+    let (left, right) = play_sine(60, -1.0, 1.0);
+    assert!(synth_util::rms(&left) > 0.0);
+    assert!(synth_util::peak(&right) < 1.0E-3 * synth_util::peak(&left));
+
+    let (full, _) = play_sine(60, 0.0, 1.0);
+    let (half, _) = play_sine(60, 0.0, 0.5);
+    let ratio = synth_util::rms(&half) / synth_util::rms(&full);
+    assert!((ratio - 0.5).abs() < 1.0E-3);
+}
+
+#[test]
+fn stop_samples_silences_waveforms() {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    synthesizer.play_sample(&synth_util::sine(440.0, 0.5), 60, 0.0, 1.0);
+    synthesizer.play_sample(&synth_util::sine(440.0, 0.5), 48, 0.0, 1.0);
+    synth_util::render(&mut synthesizer, 0.1);
+    synthesizer.stop_samples();
+
+    // The rest of the rendered block is output before the waveforms stop.
+    let data = synth_util::render(&mut synthesizer, 0.1);
+    let block_size = synthesizer.get_block_size();
+    assert_eq!(synth_util::peak(&data[block_size..]), 0.0);
+}
+
+// The level of the volume envelope after the note off, with the specified release scale.
+fn release_level(release: f32) -> f32 {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    let mut value = EnvelopeOverride::new();
+    value.release = release;
+    synthesizer.set_envelope_override(0, Some(value));
+    synthesizer.note_on(0, 69, 100);
+    synth_util::render(&mut synthesizer, 0.2);
+    synthesizer.note_off(0, 69);
+    synth_util::render(&mut synthesizer, 0.05);
+    synthesizer.get_statistics().get_voices()[0].get_volume_level()
+}
+
+#[test]
+fn get_voices_reports_envelope_phases() {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    synthesizer.note_on(0, 69, 100);
+    synth_util::render(&mut synthesizer, 0.2);
+
+    let statistics = synthesizer.get_statistics();
+    assert_eq!(statistics.get_voices().len(), 1);
+    let voice = statistics.get_voices()[0];
+    assert_eq!(voice.get_channel(), 0);
+    assert_eq!(voice.get_key(), 69);
+    assert_eq!(voice.get_velocity(), 100);
+    assert_ne!(voice.get_volume_phase(), EnvelopePhase::Release);
+    assert!(voice.get_volume_level() > 0.0 && voice.get_volume_level() <= 1.0);
+    assert_ne!(voice.get_modulation_phase(), EnvelopePhase::Release);
+    assert!((0.0..=1.0).contains(&voice.get_modulation_level()));
+
+    synthesizer.note_off(0, 69);
+    synth_util::render(&mut synthesizer, 0.01);
+    let voice = synthesizer.get_statistics().get_voices()[0];
+    assert_eq!(voice.get_volume_phase(), EnvelopePhase::Release);
+    assert_eq!(voice.get_modulation_phase(), EnvelopePhase::Release);
+}
+
+#[test]
+fn envelope_override_scales_release() {
+    assert!(release_level(4.0) > 2.0 * release_level(1.0));
+}
+
+#[test]
+fn envelope_override_is_kept_on_reset() {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    assert_eq!(synthesizer.get_envelope_override(0), None);
+
+    let mut value = EnvelopeOverride::new();
+    value.sustain = 0.5;
+    synthesizer.set_envelope_override(0, Some(value));
+    synthesizer.reset();
+    assert_eq!(synthesizer.get_envelope_override(0), Some(value));
+    assert_eq!(synthesizer.get_envelope_override(1), None);
+
+    synthesizer.set_envelope_override(0, None);
+    assert_eq!(synthesizer.get_envelope_override(0), None);
+}
+
+#[test]
+#[should_panic]
+fn zero_envelope_time_scale_panics() {
+    let settings = synth_util::settings();
+    let mut synthesizer = synth_util::synthesizer(&settings);
+    let mut value = EnvelopeOverride::new();
+    value.attack = 0.0;
+    synthesizer.set_envelope_override(0, Some(value));
+}
+
+fn lfo_override(rate: Option<LfoRate>, depth: f32) -> Option<LfoOverride> {
+    let mut value = LfoOverride::new();
+    value.rate = rate;
+    value.depth = depth;
+    Some(value)
+}
+
+// Replaces the SoundFont by the one where the flute has the LFO given by the generator.
+fn add_flute_lfo(synthesizer: &mut Synthesizer, generator_type: u16, value: i16) {
+    let mut overrides = GeneratorOverrides::new();
+    overrides.set_preset_generator(0, FLUTE, None, generator_type, value);
+    let sound_font = Arc::new(overrides.apply(&SoundFont::test_bank()));
+    synthesizer.set_sound_font(&sound_font, true);
+}
+
+#[test]
+fn vibrato_lfo_rate_in_beats_follows_tempo() {
+    let synthesizer = synth_util::synthesizer(&synth_util::settings());
+    assert_eq!(synthesizer.get_tempo(), 120.0);
+
+    let expected = play_note(|x| {
+        x.set_vibrato_lfo_override(0, lfo_override(Some(LfoRate::Hertz(4.0)), 1.0));
+        x.process_midi_message(0, 0xB0, 1, 127);
+    });
+    let actual = play_note(|x| {
+        x.set_vibrato_lfo_override(0, lfo_override(Some(LfoRate::Beats(0.5)), 1.0));
+        x.process_midi_message(0, 0xB0, 1, 127);
+    });
+    assert_eq!(actual, expected);
+
+    let actual = play_note(|x| {
+        x.set_tempo(240.0);
+        x.set_vibrato_lfo_override(0, lfo_override(Some(LfoRate::Beats(1.0)), 1.0));
+        x.process_midi_message(0, 0xB0, 1, 127);
+    });
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn vibrato_lfo_rate_takes_precedence_over_vibrato_rate() {
+    let expected = play_note(|x| {
+        x.set_vibrato_lfo_override(0, lfo_override(Some(LfoRate::Hertz(4.0)), 1.0));
+        x.process_midi_message(0, 0xB0, 1, 127);
+    });
+    let actual = play_note(|x| {
+        x.set_vibrato_rate(0, Some(20_f32));
+        x.set_vibrato_lfo_override(0, lfo_override(Some(LfoRate::Hertz(4.0)), 1.0));
+        x.process_midi_message(0, 0xB0, 1, 127);
+    });
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn lfo_depth_scales_sound_font_lfo() {
+    let expected = play_note(|_| ());
+
+    for generator_type in [
+        GeneratorType::VIBRATO_LFO_TO_PITCH,
+        GeneratorType::MODULATION_LFO_TO_VOLUME,
+    ] {
+        let modulated = play_note(|x| add_flute_lfo(x, generator_type, 100));
+        assert_ne!(modulated, expected);
+
+        let actual = play_note(|x| {
+            add_flute_lfo(x, generator_type, 100);
+            x.set_vibrato_lfo_override(0, lfo_override(None, 0.0));
+            x.set_modulation_lfo_override(0, lfo_override(None, 0.0));
+        });
+        assert_eq!(actual, expected);
+    }
+}
+
+#[test]
+fn lfo_overrides_and_tempo_are_kept_on_reset() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    assert_eq!(synthesizer.get_vibrato_lfo_override(0), None);
+    assert_eq!(synthesizer.get_modulation_lfo_override(0), None);
+
+    let value = lfo_override(Some(LfoRate::Beats(0.25)), 2.0);
+    synthesizer.set_vibrato_lfo_override(0, value);
+    synthesizer.set_modulation_lfo_override(1, value);
+    synthesizer.set_tempo(90.0);
+    synthesizer.reset();
+    assert_eq!(synthesizer.get_vibrato_lfo_override(0), value);
+    assert_eq!(synthesizer.get_modulation_lfo_override(1), value);
+    assert_eq!(synthesizer.get_modulation_lfo_override(0), None);
+    assert_eq!(synthesizer.get_tempo(), 90.0);
+}
+
+#[test]
+#[should_panic]
+fn zero_lfo_rate_panics() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.set_modulation_lfo_override(0, lfo_override(Some(LfoRate::Beats(0.0)), 1.0));
+}
+
+#[test]
+#[should_panic]
+fn zero_tempo_panics() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.set_tempo(0.0);
+}
+
+fn filter_override(filter_type: FilterType, cutoff: f32) -> Option<FilterOverride> {
+    let mut value = FilterOverride::new();
+    value.filter_type = filter_type;
+    value.cutoff = Some(cutoff);
+    Some(value)
+}
+
+// The RMS of the flute at 440 Hz through the filter.
+fn filtered_rms(filter_type: FilterType, cutoff: f32) -> f32 {
+    let data = play_note(|x| x.set_filter_override(0, filter_override(filter_type, cutoff)));
+    synth_util::rms(&data[synth_util::samples(0.1)..])
+}
+
+#[test]
+fn default_filter_override_keeps_sound() {
+    let expected = play_note(|_| ());
+    let actual = play_note(|x| x.set_filter_override(0, Some(FilterOverride::new())));
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn low_pass_filters_cut_above_cutoff() {
+    let expected = synth_util::rms(&play_note(|_| ())[synth_util::samples(0.1)..]);
+    for filter_type in [
+        FilterType::LowPass,
+        FilterType::LadderLowPass,
+        FilterType::StateVariableLowPass,
+    ] {
+        assert!(filtered_rms(filter_type, 5000.0) > 0.7 * expected);
+        assert!(filtered_rms(filter_type, 100.0) < 0.3 * expected);
+    }
+
+    // The ladder filter has the steeper slope.
+    assert!(
+        filtered_rms(FilterType::LadderLowPass, 100.0) < filtered_rms(FilterType::LowPass, 100.0)
+    );
+}
+
+#[test]
+fn high_pass_and_band_pass_filters_keep_band() {
+    let expected = synth_util::rms(&play_note(|_| ())[synth_util::samples(0.1)..]);
+
+    assert!(filtered_rms(FilterType::StateVariableHighPass, 50.0) > 0.7 * expected);
+    assert!(filtered_rms(FilterType::StateVariableHighPass, 5000.0) < 0.3 * expected);
+
+    assert!(filtered_rms(FilterType::StateVariableBandPass, 440.0) > 0.5 * expected);
+    assert!(filtered_rms(FilterType::StateVariableBandPass, 5000.0) < 0.3 * expected);
+}
+
+#[test]
+fn filter_override_is_kept_on_reset() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    assert_eq!(synthesizer.get_filter_override(0), None);
+
+    let value = filter_override(FilterType::StateVariableBandPass, 1000.0);
+    synthesizer.set_filter_override(0, value);
+    synthesizer.reset();
+    assert_eq!(synthesizer.get_filter_override(0), value);
+
+    synthesizer.set_filter_override(0, None);
+    assert_eq!(synthesizer.get_filter_override(0), None);
+}
+
+#[test]
+#[should_panic(expected = "The resonance must be between 0 and 96 dB.")]
+fn invalid_filter_resonance_panics() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    let mut value = FilterOverride::new();
+    value.resonance = Some(100.0);
+    synthesizer.set_filter_override(0, Some(value));
+}
+
+// Plays the flute panned hard left with the stereo width, and returns both channels.
+fn play_left_with_width(width: f32) -> (Vec<f32>, Vec<f32>) {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.set_stereo_width(width);
+    synthesizer.process_midi_message(0, 0xB0, 10, 0);
+    synthesizer.note_on(0, 69, 100);
+    let mut left = vec![0_f32; synth_util::samples(0.5)];
+    let mut right = vec![0_f32; synth_util::samples(0.5)];
+    synthesizer.render(&mut left, &mut right);
+    (left, right)
+}
+
+#[test]
+fn stereo_width_scales_side_signal() {
+    let synthesizer = synth_util::synthesizer(&synth_util::settings());
+    assert_eq!(synthesizer.get_stereo_width(), 1.0);
+
+    let (left, right) = play_left_with_width(1.0);
+    assert!(synth_util::peak(&right) < 1.0E-3 * synth_util::peak(&left));
+
+    // The zero width folds the output down to mono.
+    let (left, right) = play_left_with_width(0.0);
+    assert!(synth_util::rms(&left) > 0.0);
+    for (l, r) in left.iter().zip(right.iter()) {
+        assert!((l - r).abs() < 1.0E-6);
+    }
+    let report = LevelReport::measure(&left, &right);
+    assert!(report.get_correlation() > 0.999);
+    assert!(report.get_mono_fold_down_loss().abs() < 0.01);
+
+    // The double width puts the channels out of phase.
+    let (left, right) = play_left_with_width(2.0);
+    let report = LevelReport::measure(&left, &right);
+    assert!(report.get_correlation() < -0.99);
+    assert!(report.get_mono_fold_down_loss() > 6.0);
+}
+
+#[test]
+#[should_panic(expected = "The stereo width must be between 0 and 2.")]
+fn invalid_stereo_width_panics() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.set_stereo_width(3.0);
+}
+
+#[test]
+fn detuned_note_is_shifted() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.note_on(0, 70, 100);
+    let expected = synth_util::frequency(&synth_util::render(&mut synthesizer, 0.5));
+
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.note_on_detuned(0, 69, 100, 100_f32);
+    let actual = synth_util::frequency(&synth_util::render(&mut synthesizer, 0.5));
+
+    assert!((expected - 440.0 * 2_f64.powf(1.0 / 12.0)).abs() < 5.0);
+    assert!((actual - expected).abs() < 5.0);
+}
+
+#[test]
+fn detune_does_not_affect_next_notes() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.note_on_detuned(0, 69, 100, -100_f32);
+    let detuned = synth_util::frequency(&synth_util::render(&mut synthesizer, 0.5));
+    synthesizer.note_off(0, 69);
+    synth_util::render(&mut synthesizer, 0.5);
+
+    synthesizer.note_on(0, 69, 100);
+    let plain = synth_util::frequency(&synth_util::render(&mut synthesizer, 0.5));
+
+    assert!((detuned - 440.0 * 2_f64.powf(-1.0 / 12.0)).abs() < 5.0);
+    assert!((plain - 440.0).abs() < 5.0);
+}
+
+#[test]
+fn detuned_note_is_stopped_by_key() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.note_on_detuned(0, 69, 100, 50_f32);
+    synth_util::render(&mut synthesizer, 0.1);
+    synthesizer.note_off(0, 69);
+
+    let data = synth_util::render(&mut synthesizer, 0.5);
+    assert!(synth_util::rms(&data[data.len() / 2..]) < 1.0E-4);
+}
+
+#[test]
+fn set_note_detune_changes_playing_note() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.note_on(0, 69, 100);
+    synthesizer.note_on(0, 72, 100);
+    synth_util::render(&mut synthesizer, 0.1);
+
+    // Only the note of the key is shifted.
+    synthesizer.note_off(0, 72);
+    synthesizer.set_note_detune(0, 69, 100_f32);
+    let shifted = synth_util::frequency(&synth_util::render(&mut synthesizer, 0.5));
+    assert!((shifted - 440.0 * 2_f64.powf(1.0 / 12.0)).abs() < 5.0);
+
+    synthesizer.set_note_detune(0, 69, 0_f32);
+    let restored = synth_util::frequency(&synth_util::render(&mut synthesizer, 0.5));
+    assert!((restored - 440.0).abs() < 5.0);
+}
+
+#[test]
+#[should_panic(expected = "The detune must be a finite value.")]
+fn non_finite_detune_panics() {
+    synth_util::synthesizer(&synth_util::settings()).note_on_detuned(0, 69, 100, f32::NAN);
+}
+
+#[test]
+#[should_panic(expected = "The detune must be a finite value.")]
+fn non_finite_note_detune_panics() {
+    synth_util::synthesizer(&synth_util::settings()).set_note_detune(0, 69, f32::INFINITY);
+}
+
+// Plays a note, sets the hold pedal, releases the note, and then returns the output after the release.
+fn play_with_pedal(curve: PedalCurve, pedal: &[(f64, i32)]) -> Vec<f32> {
+    let mut settings = synth_util::settings();
+    settings.pedal_curve = curve;
+    let mut synthesizer = synth_util::synthesizer(&settings);
+
+    synthesizer.note_on(0, 69, 100);
+    synth_util::render(&mut synthesizer, 0.1);
+    synthesizer.note_off(0, 69);
+
+    let mut data = Vec::new();
+    let mut time = 0.0;
+    for (next, value) in pedal {
+        data.extend(synth_util::render(&mut synthesizer, next - time));
+        synthesizer.process_midi_message(0, 0xB0, 64, *value);
+        time = *next;
+    }
+    data.extend(synth_util::render(&mut synthesizer, 0.5 - time));
+    data
+}
+
+// The level at 0.3 seconds after the release, which is longer than the release of the flute.
+fn level_after_release(data: &[f32]) -> f32 {
+    let start = synth_util::samples(0.3);
+    synth_util::rms(&data[start..start + synth_util::samples(0.05)])
+}
+
+#[test]
+fn switch_pedal_holds_or_releases_notes() {
+    let held = play_with_pedal(PedalCurve::Switch, &[(0.0, 64)]);
+    let released = play_with_pedal(PedalCurve::Switch, &[(0.0, 63)]);
+
+    assert!(level_after_release(&held) > 0.01);
+    assert!(level_after_release(&released) < 1.0E-4);
+}
+
+#[test]
+fn half_pedal_slows_down_release() {
+    let curve = PedalCurve::HalfDamper { start: 32, end: 96 };
+    let held = level_after_release(&play_with_pedal(curve.clone(), &[(0.0, 96)]));
+    let half = level_after_release(&play_with_pedal(curve.clone(), &[(0.0, 80)]));
+    let released = level_after_release(&play_with_pedal(curve.clone(), &[(0.0, 32)]));
+
+    assert!(released < 1.0E-4);
+    assert!(half > 1.0E-4);
+    assert!(half < 0.5 * held);
+}
+
+#[test]
+fn repedaling_catches_decaying_notes() {
+    // The pedal is pressed again while the released note is decaying.
+    let curve = PedalCurve::HalfDamper { start: 32, end: 96 };
+    let caught = play_with_pedal(curve, &[(0.0, 80), (0.05, 127)]);
+    let switched = play_with_pedal(PedalCurve::Switch, &[(0.0, 0), (0.05, 127)]);
+
+    assert!(level_after_release(&caught) > 1.0E-3);
+    assert!(level_after_release(&switched) < 1.0E-4);
+}
+
+#[test]
+fn custom_pedal_curve_gives_amounts() {
+    // The pedal is fully down for any value.
+    let curve = PedalCurve::Custom(Box::new([1_f32; 128]));
+    let held = play_with_pedal(curve, &[(0.0, 0)]);
+    assert!(level_after_release(&held) > 0.01);
+}
+
+#[test]
+fn invalid_pedal_curve_is_rejected() {
+    for curve in [
+        PedalCurve::HalfDamper { start: 90, end: 90 },
+        PedalCurve::Custom(Box::new([2_f32; 128])),
+    ] {
+        let mut settings = synth_util::settings();
+        settings.pedal_curve = curve;
+        let result = Synthesizer::new(&synth_util::sound_font(), &settings);
+        assert!(matches!(result, Err(SynthesizerError::InvalidPedalCurve)));
+    }
+}
+
+fn count_voice_events(synthesizer: &mut Synthesizer, kind: VoiceEventKind) -> usize {
+    synthesizer
+        .drain_voice_events()
+        .filter(|event| event.get_kind() == kind && event.get_key() == 69)
+        .count()
+}
+
+fn synthesizer_with_release_layer(layer: Option<ReleaseLayer>) -> Synthesizer {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.set_voice_event_capacity(64);
+    synthesizer.set_release_layer(0, FLUTE, layer);
+    synthesizer
+}
+
+#[test]
+fn release_layer_is_played_at_note_off() {
+    for (layer, expected) in [(Some(ReleaseLayer::new(0, NOISE)), 1), (None, 0)] {
+        let mut synthesizer = synthesizer_with_release_layer(layer);
+        assert_eq!(synthesizer.get_release_layer(0, FLUTE), layer.as_ref());
+
+        synthesizer.note_on(0, 69, 100);
+        synth_util::render(&mut synthesizer, 0.1);
+        assert_eq!(
+            count_voice_events(&mut synthesizer, VoiceEventKind::Started),
+            1
+        );
+
+        synthesizer.note_off(0, 69);
+        synth_util::render(&mut synthesizer, 0.1);
+        assert_eq!(
+            count_voice_events(&mut synthesizer, VoiceEventKind::Started),
+            expected
+        );
+    }
+}
+
+#[test]
+fn release_layer_waits_for_hold_pedal() {
+    let mut synthesizer = synthesizer_with_release_layer(Some(ReleaseLayer::new(0, NOISE)));
+
+    synthesizer.process_midi_message(0, 0xB0, 64, 127);
+    synthesizer.note_on(0, 69, 100);
+    synthesizer.note_off(0, 69);
+    synth_util::render(&mut synthesizer, 0.1);
+    assert_eq!(
+        count_voice_events(&mut synthesizer, VoiceEventKind::Started),
+        1
+    );
+
+    synthesizer.process_midi_message(0, 0xB0, 64, 0);
+    synth_util::render(&mut synthesizer, 0.1);
+    assert_eq!(
+        count_voice_events(&mut synthesizer, VoiceEventKind::Started),
+        1
+    );
+}
+
+#[test]
+fn release_layer_is_played_once() {
+    // The flute loops, but the layer stops at the end of the sample.
+    let mut synthesizer = synthesizer_with_release_layer(Some(ReleaseLayer::new(0, FLUTE)));
+
+    synthesizer.note_on(0, 69, 100);
+    synth_util::render(&mut synthesizer, 0.1);
+    synthesizer.note_off(0, 69);
+    synth_util::render(&mut synthesizer, 0.5);
+
+    // Both the note and the layer are finished.
+    let kinds: Vec<VoiceEventKind> = synthesizer
+        .drain_voice_events()
+        .map(|event| event.get_kind())
+        .collect();
+    let count = |kind| kinds.iter().filter(|x| **x == kind).count();
+    assert_eq!(count(VoiceEventKind::Started), 2);
+    assert_eq!(count(VoiceEventKind::Finished), 2);
+}
+
+#[test]
+fn release_layer_decays_while_note_is_held() {
+    let mut layer = ReleaseLayer::new(0, NOISE);
+    layer.decay = 200_f32;
+
+    // The layer of a short note is played, but the one of a long note is too quiet to be played.
+    for (length, expected) in [(0.1, 1), (1.0, 0)] {
+        let mut synthesizer = synthesizer_with_release_layer(Some(layer));
+
+        synthesizer.note_on(0, 69, 100);
+        synth_util::render(&mut synthesizer, length);
+        count_voice_events(&mut synthesizer, VoiceEventKind::Started);
+
+        synthesizer.note_off(0, 69);
+        synth_util::render(&mut synthesizer, 0.1);
+        assert_eq!(
+            count_voice_events(&mut synthesizer, VoiceEventKind::Started),
+            expected
+        );
+    }
+}
+
+#[test]
+fn release_layers_are_detected_by_name() {
+    let sound_font = SoundFont::test_bank();
+    let flute_name = sound_font.find_preset(0, FLUTE).unwrap().get_name();
+    let noise_name = sound_font.find_preset(0, NOISE).unwrap().get_name();
+
+    // The noise is renamed to the release of the flute in the preset headers.
+    let mut data = synth_util::test_bank_file();
+    let phdr = data.windows(4).position(|x| x == b"phdr").unwrap();
+    let position = phdr
+        + data[phdr..]
+            .windows(noise_name.len())
+            .position(|x| x == noise_name.as_bytes())
+            .unwrap();
+    let name = format!("{}-Rel", flute_name);
+    data[position..position + 20].fill(0);
+    data[position..position + name.len()].copy_from_slice(name.as_bytes());
+
+    let sound_font = Arc::new(SoundFont::from_bytes(&data).unwrap());
+    let mut synthesizer = Synthesizer::new(&sound_font, &synth_util::settings()).unwrap();
+    assert_eq!(synthesizer.detect_release_layers(), 1);
+    assert_eq!(
+        synthesizer.get_release_layer(0, FLUTE),
+        Some(&ReleaseLayer::new(0, NOISE))
+    );
+
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    assert_eq!(synthesizer.detect_release_layers(), 0);
+    assert_eq!(synthesizer.get_release_layer(0, FLUTE), None);
+}
+
+#[test]
+#[should_panic(
+    expected = "The release layer must have a patch number between 0 and 127, a finite gain, and a non-negative decay."
+)]
+fn invalid_release_layer_panics() {
+    let mut layer = ReleaseLayer::new(0, NOISE);
+    layer.decay = -1_f32;
+    synth_util::synthesizer(&synth_util::settings()).set_release_layer(0, FLUTE, Some(layer));
+}
@@ -0,0 +1,170 @@
+#![allow(unused_imports)]
+
+use crate::synth_util;
+use rustysynth::GeneratorType;
+use rustysynth::SoundFont;
+use std::io::Cursor;
+
+#[test]
+fn soundfont_info() {
+    let sound_font = SoundFont::test_bank();
+    let info = sound_font.get_info();
+
+    assert_eq!(info.get_version().get_major(), 2);
+    assert_eq!(info.get_version().get_minor(), 1);
+    assert_eq!(info.get_target_sound_engine(), "EMU8000");
+    assert_eq!(info.get_bank_name(), "RustySynth Test Bank");
+    assert_eq!(info.get_tools(), "RustySynth");
+
+    assert_eq!(sound_font.get_sample_headers().len(), 5);
+    assert_eq!(sound_font.get_instruments().len(), 12);
+    assert!(sound_font.get_diagnostics().is_empty());
+}
+
+#[test]
+fn presets() {
+    let sound_font = SoundFont::test_bank();
+
+    for program in 0..128 {
+        let preset = sound_font.find_preset(0, program).unwrap();
+        assert_eq!(preset.get_regions().len(), 1);
+    }
+    assert_eq!(
+        sound_font.find_preset(0, 0).unwrap().get_name(),
+        "Acoustic Grand Piano"
+    );
+    assert_eq!(
+        sound_font.find_preset(0, 127).unwrap().get_name(),
+        "Gunshot"
+    );
+    assert_eq!(
+        sound_font.find_preset(128, 0).unwrap().get_name(),
+        "Standard"
+    );
+    assert_eq!(sound_font.get_presets().len(), 129);
+}
+
+#[test]
+fn write() {
+    let sound_font = SoundFont::test_bank();
+
+    let mut data: Vec<u8> = Vec::new();
+    sound_font.write(&mut data).unwrap();
+    let written = SoundFont::new(&mut Cursor::new(&data)).unwrap();
+
+    assert_eq!(written.get_presets().len(), sound_font.get_presets().len());
+    assert_eq!(
+        written.get_instruments().len(),
+        sound_font.get_instruments().len()
+    );
+    assert_eq!(
+        written.get_wave_data().len(),
+        sound_font.get_wave_data().len()
+    );
+}
+
+#[test]
+fn regions_lead_to_samples() {
+    let sound_font = SoundFont::test_bank();
+
+    // The piano plays the saw wave.
+    let preset = sound_font.find_preset(0, 0).unwrap();
+    let preset_region = &preset.get_regions()[0];
+    let instrument = preset_region.get_instrument(&sound_font);
+    assert_eq!(instrument.get_name(), "Piano");
+    assert_eq!(
+        preset_region.get_generator(GeneratorType::INSTRUMENT) as usize,
+        preset_region.get_instrument_id()
+    );
+
+    let instrument_region = &instrument.get_regions()[0];
+    assert_eq!(instrument_region.get_sample(&sound_font).get_name(), "Saw");
+    assert_eq!(
+        instrument_region.get_generator(GeneratorType::SAMPLE_ID) as usize,
+        instrument_region.get_sample_id()
+    );
+    assert_eq!(
+        instrument_region.get_generator(GeneratorType::SAMPLE_MODES),
+        1
+    );
+}
+
+#[test]
+fn generator_names() {
+    assert_eq!(
+        GeneratorType::get_name(GeneratorType::INITIAL_FILTER_CUTOFF_FREQUENCY),
+        Some("initialFilterFc")
+    );
+    assert_eq!(
+        GeneratorType::get_name(GeneratorType::SAMPLE_ID),
+        Some("sampleID")
+    );
+    assert_eq!(GeneratorType::get_name(61), None);
+}
+
+#[test]
+fn missing_preset_is_not_found() {
+    let sound_font = SoundFont::test_bank();
+
+    assert!(sound_font.find_preset(1, 0).is_none());
+    assert!(sound_font.find_preset(128, 1).is_none());
+}
+
+#[test]
+fn resolve_preset_falls_back_to_gm() {
+    let sound_font = SoundFont::test_bank();
+
+    let resolve = |bank, patch| sound_font.resolve_preset(bank, patch).get_name();
+    assert_eq!(resolve(0, 72), "Piccolo");
+    assert_eq!(resolve(8, 72), "Piccolo");
+    assert_eq!(resolve(136, 0), "Standard");
+    assert_eq!(resolve(128, 25), "Standard");
+    assert!(sound_font.find_preset(8, 72).is_none());
+}
+
+#[test]
+fn find_presets_by_name_ignores_case() {
+    let sound_font = SoundFont::test_bank();
+
+    let names: Vec<_> = sound_font
+        .find_presets_by_name("PIANO")
+        .iter()
+        .map(|x| x.get_name())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "Acoustic Grand Piano",
+            "Bright Acoustic Piano",
+            "Electric Grand Piano",
+            "Honky-tonk Piano",
+            "Electric Piano 1",
+            "Electric Piano 2"
+        ]
+    );
+    assert!(sound_font.find_presets_by_name("Theremin").is_empty());
+}
+
+#[test]
+fn read_info_reads_only_info_list() {
+    let data = synth_util::test_bank_file();
+    let expected = SoundFont::test_bank();
+    let expected = expected.get_info();
+
+    // The data after the INFO list is not needed.
+    let info_size = u32::from_le_bytes(data[16..20].try_into().unwrap()) as usize;
+    let info = SoundFont::read_info(&mut Cursor::new(&data[..20 + info_size])).unwrap();
+    assert_eq!(info.get_bank_name(), expected.get_bank_name());
+    assert_eq!(info.get_tools(), expected.get_tools());
+    assert_eq!(info.get_version().to_string(), "2.01");
+    assert_eq!(
+        info.get_target_sound_engine(),
+        expected.get_target_sound_engine()
+    );
+}
+
+#[test]
+fn read_info_of_invalid_data_is_error() {
+    let result = SoundFont::read_info(&mut Cursor::new(b"MThd\0\0\0\x06"));
+    assert!(result.is_err());
+}
@@ -0,0 +1,104 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::synth_util;
+use crate::synth_util::SAMPLE_RATE;
+use rustysynth::TimeStretch;
+
+// Estimates the frequency from the zero crossings of the whole data.
+fn frequency(data: &[f32]) -> f64 {
+    synth_util::crossings(data) as f64 * SAMPLE_RATE as f64 / data.len() as f64
+}
+
+#[test]
+fn output_length() {
+    let input = synth_util::sine(440.0, 0.5);
+
+    for speed in [0.5, 0.8, 1.0, 1.25, 2.0, 3.7] {
+        let mut time_stretch = TimeStretch::new(SAMPLE_RATE, speed);
+        let (left, right) = time_stretch.stretch(&input, &input);
+
+        let expected = (input.len() as f64 / speed).round() as usize;
+        assert_eq!(left.len(), expected);
+        assert_eq!(right.len(), expected);
+    }
+}
+
+#[test]
+fn pitch_is_kept() {
+    let input = synth_util::sine(440.0, 0.5);
+
+    for speed in [0.5, 0.8, 1.25, 2.0] {
+        let mut time_stretch = TimeStretch::new(SAMPLE_RATE, speed);
+        let (left, _) = time_stretch.stretch(&input, &input);
+
+        // The edges are skipped, as they are faded by the window.
+        let middle = &left[left.len() / 4..3 * left.len() / 4];
+        let error = (frequency(middle) - 440.0).abs();
+        assert!(error < 5.0, "speed {}: {} Hz", speed, frequency(middle));
+    }
+}
+
+#[test]
+fn blocks_match_whole_waveform() {
+    let input = synth_util::sine(330.0, 0.5);
+    let mut time_stretch = TimeStretch::new(SAMPLE_RATE, 0.8);
+    let (whole_left, whole_right) = time_stretch.stretch(&input, &input);
+
+    // The blocks of various lengths, including the ones longer than the buffer of the input.
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+    let mut position = 0;
+    for length in [1, 64, 512, 20000].iter().cycle() {
+        if position == input.len() {
+            break;
+        }
+        let end = (position + length).min(input.len());
+        let block = &input[position..end];
+        time_stretch.process(block, block, &mut left, &mut right);
+        position = end;
+    }
+    time_stretch.flush(&mut left, &mut right);
+
+    assert_eq!(left, whole_left);
+    assert_eq!(right, whole_right);
+}
+
+#[test]
+fn speed_change_during_playback() {
+    let input = synth_util::sine(440.0, 0.5);
+    let mut time_stretch = TimeStretch::new(SAMPLE_RATE, 1.0);
+
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+    let half = input.len() / 2;
+    time_stretch.process(&input[..half], &input[..half], &mut left, &mut right);
+    time_stretch.set_speed(4.0);
+    time_stretch.process(&input[half..], &input[half..], &mut left, &mut right);
+    time_stretch.flush(&mut left, &mut right);
+
+    let expected = (half as f64 + half as f64 / 4.0).round() as usize;
+    assert_eq!(left.len(), expected);
+    assert_eq!(right.len(), expected);
+}
+
+#[test]
+fn reset_discards_input() {
+    let input = synth_util::sine(440.0, 0.5);
+    let mut time_stretch = TimeStretch::new(SAMPLE_RATE, 1.5);
+    assert_eq!(time_stretch.get_speed(), 1.5);
+    let expected = time_stretch.stretch(&input, &input);
+
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+    let other = synth_util::sine(1000.0, 0.1);
+    time_stretch.process(&other, &other, &mut left, &mut right);
+    time_stretch.reset();
+    assert_eq!(time_stretch.stretch(&input, &input), expected);
+}
+
+#[test]
+#[should_panic(expected = "The speed must be greater than zero.")]
+fn zero_speed_panics() {
+    TimeStretch::new(SAMPLE_RATE, 1.0).set_speed(0.0);
+}
@@ -0,0 +1,294 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use rustysynth::HumanizeOptions;
+use rustysynth::MidiEvent;
+use rustysynth::MidiFile;
+use rustysynth::MidiMessage;
+use rustysynth::MidiTrack;
+
+// Two notes at 120 BPM and 480 ticks per beat, where a tick is 1/960 seconds.
+// The first note is from the tick 10 to 250, and the second one is from 470 to 700.
+fn two_notes() -> MidiFile {
+    MidiFile::from_bytes(&midi_util::format0(&[
+        10, 0x90, 60, 100, 0x81, 0x70, 0x80, 60, 0, 0x81, 0x5C, 0x90, 64, 100, 0x81, 0x66, 0x80,
+        64, 0, 0, 0xFF, 0x2F, 0,
+    ]))
+    .unwrap()
+}
+
+// Gets the ticks, the commands, the keys and the velocities of the notes.
+fn notes(midi_file: &MidiFile) -> Vec<(f64, u8, u8, u8)> {
+    midi_file.get_tracks()[0]
+        .get_events()
+        .filter_map(|(time, event)| match event {
+            MidiEvent::Message(MidiMessage {
+                command,
+                data1,
+                data2,
+                ..
+            }) => Some(((time * 960.0).round(), command, data1, data2)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn quantize_moves_notes_to_grid() {
+    let midi_file = two_notes().quantize(240, 1.0);
+
+    assert_eq!(
+        notes(&midi_file),
+        [
+            (0.0, 0x90, 60, 100),
+            (240.0, 0x80, 60, 0),
+            (480.0, 0x90, 64, 100),
+            (710.0, 0x80, 64, 0)
+        ]
+    );
+}
+
+#[test]
+fn quantize_with_half_strength() {
+    let midi_file = two_notes().quantize(240, 0.5);
+
+    assert_eq!(
+        notes(&midi_file),
+        [
+            (5.0, 0x90, 60, 100),
+            (245.0, 0x80, 60, 0),
+            (475.0, 0x90, 64, 100),
+            (705.0, 0x80, 64, 0)
+        ]
+    );
+}
+
+#[test]
+fn humanize_keeps_lengths_and_ranges() {
+    let mut options = HumanizeOptions::new();
+    options.timing = 20.0;
+    options.velocity = 10;
+    options.seed = 1;
+    let original = notes(&two_notes());
+    let humanized = notes(&two_notes().humanize(&options));
+
+    // The first note-on and the first note-off are followed by the others within the range.
+    for (note_on, note_off) in [(0, 1), (2, 3)] {
+        let (_, _, _, velocity) = humanized[note_on];
+        assert!((90..=110).contains(&velocity));
+        let movement = humanized[note_on].0 - original[note_on].0;
+        assert!(movement.abs() <= 20.0);
+        assert_eq!(humanized[note_off].0 - original[note_off].0, movement);
+        assert_eq!(humanized[note_off].3, 0);
+    }
+}
+
+#[test]
+fn humanize_is_same_for_same_seed() {
+    let mut options = HumanizeOptions::new();
+    options.timing = 20.0;
+    options.velocity = 10;
+    options.seed = 1;
+    let first = notes(&two_notes().humanize(&options));
+    let second = notes(&two_notes().humanize(&options));
+    assert_eq!(first, second);
+
+    options.seed = 2;
+    let third = notes(&two_notes().humanize(&options));
+    assert_ne!(first, third);
+}
+
+#[test]
+fn humanize_with_default_options_changes_nothing() {
+    let midi_file = two_notes();
+    let humanized = midi_file.humanize(&HumanizeOptions::default());
+    assert_eq!(notes(&humanized), notes(&midi_file));
+}
+
+#[test]
+#[should_panic(expected = "The velocity must be between 0 and 127.")]
+fn humanize_with_invalid_velocity_panics() {
+    let mut options = HumanizeOptions::new();
+    options.velocity = 128;
+    two_notes().humanize(&options);
+}
+
+#[test]
+fn quantize_masks_data_bytes_above_127() {
+    // The parser passes the bytes from 0x80 to 0xF7 through as the data bytes.
+    let data = midi_util::format0(&[
+        10, 0x9F, 0xF7, 100, 0x81, 0x70, 0x8F, 0xF7, 0, 0, 0xFF, 0x2F, 0,
+    ]);
+    let midi_file = MidiFile::from_bytes(&data).unwrap().quantize(240, 1.0);
+
+    assert_eq!(
+        notes(&midi_file),
+        [(0.0, 0x90, 0xF7, 100), (240.0, 0x80, 0xF7, 0)]
+    );
+}
+
+// The notes and the polyphonic key pressure on the channels 1 and 10 after a tempo change.
+fn two_channels() -> MidiFile {
+    MidiFile::from_bytes(&midi_util::format0(&[
+        0, 0xFF, 0x51, 3, 0x0F, 0x42, 0x40, 0, 0x90, 60, 100, 0, 0x99, 36, 100, 0, 0xA0, 60, 50,
+        0x83, 0x60, 0x80, 60, 0, 0, 0x89, 36, 0, 0, 0xFF, 0x2F, 0,
+    ]))
+    .unwrap()
+}
+
+// Gets the channels, the commands and the keys of the channel messages.
+fn keys(midi_file: &MidiFile) -> Vec<(u8, u8, u8)> {
+    midi_file.get_tracks()[0]
+        .get_events()
+        .filter_map(|(_, event)| match event {
+            MidiEvent::Message(MidiMessage {
+                channel,
+                command,
+                data1,
+                ..
+            }) => Some((channel, command, data1)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn transposed_shifts_notes_and_key_pressure() {
+    let midi_file = two_channels().transposed(12, false);
+    assert_eq!(
+        keys(&midi_file),
+        [
+            (0, 0x90, 72),
+            (9, 0x90, 48),
+            (0, 0xA0, 72),
+            (0, 0x80, 72),
+            (9, 0x80, 48)
+        ]
+    );
+    assert_eq!(midi_file.get_length(), two_channels().get_length());
+
+    // The drums are kept with the skip.
+    let midi_file = two_channels().transposed(12, true);
+    assert_eq!(keys(&midi_file)[1], (9, 0x90, 36));
+    assert_eq!(keys(&midi_file)[4], (9, 0x80, 36));
+}
+
+#[test]
+fn transposed_removes_notes_out_of_range() {
+    let midi_file = two_channels().transposed(-40, false);
+    assert_eq!(
+        keys(&midi_file),
+        [(0, 0x90, 20), (0, 0xA0, 20), (0, 0x80, 20)]
+    );
+}
+
+#[test]
+fn with_channels_keeps_selected_channels() {
+    let midi_file = two_channels().with_channels(1 << 9);
+    assert_eq!(keys(&midi_file), [(9, 0x90, 36), (9, 0x80, 36)]);
+
+    // The tempo change is kept.
+    assert_eq!(midi_file.get_length(), two_channels().get_length());
+    assert_eq!(midi_file.get_tempo_map().get_tempo_changes().len(), 1);
+
+    assert!(keys(&two_channels().with_channels(0)).is_empty());
+}
+
+// The format 1 file whose tracks share the note of the tempo track.
+fn tracks() -> MidiFile {
+    MidiFile::from_bytes(&midi_util::format1_with_notes_in_tempo_track()).unwrap()
+}
+
+// The end of track events of the tracks are merged into one.
+fn messages(track: &MidiTrack) -> Vec<(f64, MidiEvent)> {
+    track
+        .get_events()
+        .filter(|(_, event)| *event != MidiEvent::EndOfTrack)
+        .collect()
+}
+
+#[test]
+fn extracted_track_is_same_as_original() {
+    let midi_file = tracks();
+
+    for index in 0..3 {
+        let extracted = midi_file.extract_track(index);
+
+        assert_eq!(extracted.get_tracks().len(), 1);
+        assert_eq!(
+            messages(&extracted.get_tracks()[0]),
+            messages(&midi_file.get_tracks()[index])
+        );
+    }
+}
+
+#[test]
+fn extracted_track_keeps_tempo_changes_of_other_tracks() {
+    let midi_file = tracks();
+
+    // The tempo change in the last track is kept, even though the first track is extracted.
+    let extracted = midi_file.extract_track(0);
+    assert_eq!(extracted.get_length(), midi_file.get_length());
+    assert_eq!(
+        extracted.get_tempo_map().get_tempo_changes().len(),
+        midi_file.get_tempo_map().get_tempo_changes().len()
+    );
+}
+
+#[test]
+fn merged_tracks_have_events_of_tempo_track_once() {
+    let midi_file = tracks();
+
+    let merged = midi_file.merge_tracks(&[1, 2]);
+    assert_eq!(merged.get_tracks().len(), 1);
+    assert_eq!(midi_util::note_on_count(&merged, 0), 3);
+
+    let merged = midi_file.merge_tracks(&[0, 1]);
+    assert_eq!(midi_util::note_on_count(&merged, 0), 2);
+
+    let merged = midi_file.merge_tracks(&[0, 1, 2]);
+    assert_eq!(midi_util::note_on_count(&merged, 0), 3);
+}
+
+#[test]
+fn merged_tracks_are_sorted_and_deduplicated() {
+    let midi_file = tracks();
+
+    let expected = midi_file.merge_tracks(&[1, 2]);
+    let actual = midi_file.merge_tracks(&[2, 1, 2]);
+
+    assert_eq!(midi_util::events(&actual), midi_util::events(&expected));
+
+    let times: Vec<f64> = actual.get_tracks()[0]
+        .get_events()
+        .map(|(time, _)| time)
+        .collect();
+    assert!(times.windows(2).all(|x| x[0] <= x[1]));
+}
+
+#[test]
+fn merged_tracks_can_be_written_and_read_again() {
+    let merged = tracks().merge_tracks(&[1, 2]);
+
+    let mut data = Vec::new();
+    merged.write(&mut data, 1).unwrap();
+    let midi_file = MidiFile::from_bytes(&data).unwrap();
+
+    // The first track of the written file only contains the tempo.
+    assert_eq!(midi_file.get_tracks().len(), 2);
+    assert_eq!(midi_util::note_on_count(&midi_file, 1), 3);
+    assert!((midi_file.get_length() - merged.get_length()).abs() < 1.0E-3);
+}
+
+#[test]
+#[should_panic(expected = "The track index must be less than the number of the tracks.")]
+fn extracting_missing_track_panics() {
+    tracks().extract_track(3);
+}
+
+#[test]
+#[should_panic(expected = "The track index must be less than the number of the tracks.")]
+fn merging_missing_track_panics() {
+    tracks().merge_tracks(&[0, 3]);
+}
@@ -0,0 +1,253 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::midi_util;
+use crate::render_util;
+use crate::render_util::TempFile;
+use crate::synth_util;
+use crate::synth_util::FLUTE;
+use rustysynth::BasicVoiceSource;
+use rustysynth::BasicWaveform;
+use rustysynth::OplOperator;
+use rustysynth::OplPatch;
+use rustysynth::OplVoiceSource;
+use rustysynth::Synthesizer;
+use rustysynth::VoiceGenerator;
+use rustysynth::VoiceSource;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+// The bank, the patch, the key, the velocity and the sample rate of a started note.
+type Note = (i32, i32, i32, i32, i32);
+
+// A source which records the started notes, and plays a constant level until the note off.
+#[derive(Default)]
+struct Recorder {
+    notes: Arc<Mutex<Vec<Note>>>,
+    frequencies: Arc<Mutex<Vec<f32>>>,
+}
+
+struct ConstantGenerator {
+    frequencies: Arc<Mutex<Vec<f32>>>,
+    released: bool,
+}
+
+impl VoiceSource for Recorder {
+    fn start(
+        &self,
+        bank_number: i32,
+        patch_number: i32,
+        key: i32,
+        velocity: i32,
+        sample_rate: i32,
+    ) -> Box<dyn VoiceGenerator> {
+        self.notes
+            .lock()
+            .unwrap()
+            .push((bank_number, patch_number, key, velocity, sample_rate));
+        Box::new(ConstantGenerator {
+            frequencies: self.frequencies.clone(),
+            released: false,
+        })
+    }
+}
+
+impl VoiceGenerator for ConstantGenerator {
+    fn process(&mut self, frequency: f32, block: &mut [f32]) -> bool {
+        if self.released {
+            return false;
+        }
+        self.frequencies.lock().unwrap().push(frequency);
+        block.fill(0.5);
+        true
+    }
+
+    fn release(&mut self) {
+        self.released = true;
+    }
+}
+
+#[test]
+fn voice_source_plays_preset() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    let recorder = Arc::new(Recorder::default());
+    let notes = recorder.notes.clone();
+    let frequencies = recorder.frequencies.clone();
+    assert!(synthesizer.get_voice_source(0, FLUTE).is_none());
+    synthesizer.set_voice_source(0, FLUTE, Some(recorder));
+    assert!(synthesizer.get_voice_source(0, FLUTE).is_some());
+
+    synthesizer.note_on(0, 69, 100);
+    let data = synth_util::render(&mut synthesizer, 0.1);
+    assert_eq!(
+        *notes.lock().unwrap(),
+        vec![(0, FLUTE, 69, 100, synth_util::SAMPLE_RATE)]
+    );
+    assert!((frequencies.lock().unwrap()[0] - 440.0).abs() < 0.1);
+    assert!(synth_util::peak(&data) > 0.0);
+
+    // The generator finishes by the note off.
+    synthesizer.note_off(0, 69);
+    synth_util::render(&mut synthesizer, 0.01);
+    let data = synth_util::render(&mut synthesizer, 0.1);
+    assert_eq!(synth_util::peak(&data), 0.0);
+
+    // Removing the source plays the SoundFont again.
+    synthesizer.set_voice_source(0, FLUTE, None);
+    synthesizer.note_on(0, 69, 100);
+    synth_util::render(&mut synthesizer, 0.1);
+    assert_eq!(notes.lock().unwrap().len(), 1);
+    assert_eq!(synthesizer.get_statistics().get_active_voice_count(), 1);
+}
+
+#[test]
+fn fallback_voice_source_plays_missing_preset() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    let recorder = Arc::new(Recorder::default());
+    let notes = recorder.notes.clone();
+    assert!(synthesizer.get_fallback_voice_source().is_none());
+    synthesizer.set_fallback_voice_source(Some(recorder));
+    assert!(synthesizer.get_fallback_voice_source().is_some());
+
+    // The existing preset is played with the SoundFont.
+    synthesizer.note_on(0, 69, 100);
+    assert!(notes.lock().unwrap().is_empty());
+
+    synthesizer.process_midi_message(1, 0xB0, 0, 5);
+    synthesizer.process_midi_message(1, 0xC0, 0, 0);
+    synthesizer.note_on(1, 60, 64);
+    assert_eq!(
+        *notes.lock().unwrap(),
+        vec![(5, 0, 60, 64, synth_util::SAMPLE_RATE)]
+    );
+}
+
+#[test]
+fn basic_voice_source_plays_waveforms() {
+    for waveform in [
+        BasicWaveform::Square,
+        BasicWaveform::Sawtooth,
+        BasicWaveform::Fm {
+            ratio: 1.0,
+            index: 1.0,
+        },
+    ] {
+        let source = BasicVoiceSource::new(waveform, 0.01, 0.1);
+        assert_eq!(source.get_waveform(), waveform);
+        assert_eq!(source.get_attack(), 0.01);
+        assert_eq!(source.get_release(), 0.1);
+
+        let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+        synthesizer.set_voice_source(0, FLUTE, Some(Arc::new(source)));
+        synthesizer.note_on(0, 69, 100);
+        let data = synth_util::render(&mut synthesizer, 0.5);
+        assert!((synth_util::frequency(&data) - 440.0).abs() < 5.0);
+
+        // The note fades out in the release time.
+        synthesizer.note_off(0, 69);
+        synth_util::render(&mut synthesizer, 0.15);
+        assert_eq!(
+            synth_util::peak(&synth_util::render(&mut synthesizer, 0.1)),
+            0.0
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "The attack and release times must be non-negative.")]
+fn negative_attack_panics() {
+    BasicVoiceSource::new(BasicWaveform::Square, -1.0, 0.1);
+}
+
+// The OPL source whose flute and bass drum are a sine wave at the note frequency and at 440 Hz.
+fn sine_opl() -> OplVoiceSource {
+    let mut source = OplVoiceSource::new();
+    source.set_patch(FLUTE, OplPatch::new());
+    let mut drum = OplPatch::new();
+    drum.fixed_key = Some(69);
+    source.set_percussion_patch(36, Some(drum));
+    source
+}
+
+#[test]
+fn opl_patches_can_be_replaced() {
+    let mut source = OplVoiceSource::new();
+    assert_ne!(source.get_patch(FLUTE), OplPatch::new());
+    source.set_patch(FLUTE, OplPatch::new());
+    assert_eq!(source.get_patch(FLUTE), OplPatch::new());
+
+    assert!(source.get_percussion_patch(36).is_some());
+    source.set_percussion_patch(36, None);
+    assert_eq!(source.get_percussion_patch(36), None);
+}
+
+#[test]
+fn channel_voice_source_plays_opl_patches() {
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    assert!(synthesizer.get_channel_voice_source(0).is_none());
+    let source: Arc<dyn VoiceSource> = Arc::new(sine_opl());
+    synthesizer.set_channel_voice_source(0, Some(source.clone()));
+    synthesizer.set_channel_voice_source(9, Some(source));
+    assert!(synthesizer.get_channel_voice_source(0).is_some());
+
+    synthesizer.note_on(0, 69, 100);
+    let data = synth_util::render(&mut synthesizer, 0.5);
+    assert!((synth_util::frequency(&data) - 440.0).abs() < 5.0);
+    assert_eq!(synthesizer.get_statistics().get_active_voice_count(), 0);
+    synthesizer.note_off_all(true);
+
+    // The percussion patch is played at the fixed key.
+    synthesizer.note_on(9, 36, 100);
+    let data = synth_util::render(&mut synthesizer, 0.5);
+    assert!((synth_util::frequency(&data) - 440.0).abs() < 5.0);
+}
+
+#[test]
+fn four_operator_patch_changes_sound() {
+    let mut patch = OplPatch::new();
+    patch.four_operator = true;
+    patch.connection = 3;
+    patch.operators[1].total_level = 63;
+    let mut source = OplVoiceSource::new();
+    source.set_patch(FLUTE, OplPatch::new());
+
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.set_channel_voice_source(0, Some(Arc::new(source.clone())));
+    synthesizer.note_on(0, 69, 100);
+    let expected = synth_util::render(&mut synthesizer, 0.5);
+
+    source.set_patch(FLUTE, patch);
+    let mut synthesizer = synth_util::synthesizer(&synth_util::settings());
+    synthesizer.set_channel_voice_source(0, Some(Arc::new(source)));
+    synthesizer.note_on(0, 69, 100);
+    let actual = synth_util::render(&mut synthesizer, 0.5);
+
+    // The second operator is silent, so the last two sine waves are mixed at the same frequency.
+    assert!((synth_util::frequency(&actual) - 440.0).abs() < 5.0);
+    assert!(synth_util::rms(&actual) > 1.5 * synth_util::rms(&expected));
+}
+
+#[test]
+fn renderer_channel_voice_source_plays_whole_file() {
+    let file = TempFile::new("opl", &midi_util::flute_note(69));
+    let expected = render_util::renderer(&file).render();
+
+    let mut renderer = render_util::renderer(&file);
+    assert!(renderer.get_channel_voice_source(0).is_none());
+    renderer.set_channel_voice_source(0, Some(Arc::new(OplVoiceSource::new())));
+    assert!(renderer.get_channel_voice_source(0).is_some());
+    let actual = renderer.render();
+    assert_ne!(actual, expected);
+    assert!(synth_util::rms(&actual.0) > 0.0);
+
+    renderer.set_channel_voice_source(0, None);
+    assert_eq!(renderer.render(), expected);
+}
+
+#[test]
+#[should_panic(expected = "The multiplier must be between 0 and 15.")]
+fn invalid_opl_operator_panics() {
+    let mut patch = OplPatch::new();
+    patch.operators[1].multiplier = 16;
+    OplVoiceSource::new().set_patch(0, patch);
+}
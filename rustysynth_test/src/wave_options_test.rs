@@ -0,0 +1,121 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+use crate::wave_util;
+use rustysynth::BroadcastInfo;
+use rustysynth::WaveFormat;
+use rustysynth::WaveOptions;
+
+const LEFT: [f32; 3] = [0.5, -1.0, 2.0];
+const RIGHT: [f32; 3] = [0.0, 0.25, -2.0];
+
+fn write(options: &WaveOptions, loop_points: Option<(usize, usize)>) -> Vec<u8> {
+    let mut data = Vec::new();
+    options
+        .write(&mut data, &LEFT, &RIGHT, 22050, loop_points)
+        .unwrap();
+    data
+}
+
+fn write_format(format: WaveFormat) -> Vec<u8> {
+    let mut options = WaveOptions::new();
+    options.format = format;
+    write(&options, None)
+}
+
+#[test]
+fn default_options_write_float_samples() {
+    let options = WaveOptions::default();
+    assert_eq!(options.format, WaveFormat::Float32);
+    assert_eq!(options.broadcast, None);
+
+    let data = write(&options, None);
+    let format = wave_util::find_chunk(&data, b"fmt ").unwrap();
+    assert_eq!(wave_util::read_u16(format, 0), 3);
+    assert_eq!(wave_util::read_u32(format, 4), 22050);
+    assert_eq!(wave_util::read_u16(format, 14), 32);
+
+    // The samples exceeding the full scale are kept.
+    let samples = wave_util::find_chunk(&data, b"data").unwrap();
+    assert_eq!(wave_util::read_f32_channel(samples, 0), LEFT);
+    assert_eq!(wave_util::read_f32_channel(samples, 1), RIGHT);
+    assert!(wave_util::find_chunk(&data, b"bext").is_none());
+}
+
+#[test]
+fn integer_formats_are_clipped_and_rounded() {
+    let data = write_format(WaveFormat::Int16);
+    let format = wave_util::find_chunk(&data, b"fmt ").unwrap();
+    assert_eq!(wave_util::read_u16(format, 0), 1);
+    assert_eq!(wave_util::read_u16(format, 12), 4);
+    assert_eq!(wave_util::read_u16(format, 14), 16);
+    let samples = wave_util::find_chunk(&data, b"data").unwrap();
+    let left: Vec<i16> = samples
+        .chunks_exact(4)
+        .map(|x| i16::from_le_bytes([x[0], x[1]]))
+        .collect();
+    assert_eq!(left, [16384, -32767, 32767]);
+
+    let data = write_format(WaveFormat::Int24);
+    let samples = wave_util::find_chunk(&data, b"data").unwrap();
+    assert_eq!(samples.len(), 6 * LEFT.len());
+    let left: Vec<i32> = samples
+        .chunks_exact(6)
+        .map(|x| i32::from_le_bytes([0, x[0], x[1], x[2]]) >> 8)
+        .collect();
+    assert_eq!(left, [4194304, -8388607, 8388607]);
+
+    let data = write_format(WaveFormat::Int32);
+    let samples = wave_util::find_chunk(&data, b"data").unwrap();
+    let left: Vec<i32> = samples
+        .chunks_exact(8)
+        .map(|x| wave_util::read_u32(x, 0) as i32)
+        .collect();
+    assert_eq!(left, [1073741824, -2147483647, 2147483647]);
+}
+
+#[test]
+fn broadcast_info_is_written_in_bext_chunk() {
+    let mut broadcast = BroadcastInfo::new();
+    broadcast.description = "Theme".to_string();
+    broadcast.originator = "RustySynth".to_string();
+    broadcast.origination_date = "2024-01-31".to_string();
+    broadcast.origination_time = "12:34:56".to_string();
+    broadcast.time_reference = 1 << 32 | 5;
+    let mut options = WaveOptions::new();
+    options.broadcast = Some(broadcast);
+
+    let data = write(&options, Some((1, 3)));
+    assert_eq!(&data[12..16], b"bext");
+    let bext = wave_util::find_chunk(&data, b"bext").unwrap();
+    assert_eq!(bext.len(), 602);
+    assert_eq!(&bext[0..6], b"Theme\0");
+    assert_eq!(&bext[256..266], b"RustySynth");
+    assert_eq!(&bext[320..330], b"2024-01-31");
+    assert_eq!(&bext[330..338], b"12:34:56");
+    assert_eq!(wave_util::read_u32(bext, 338), 5);
+    assert_eq!(wave_util::read_u32(bext, 342), 1);
+
+    // The other chunks are written as without the metadata.
+    let sampler = wave_util::find_chunk(&data, b"smpl").unwrap();
+    assert_eq!(wave_util::read_u32(sampler, 44), 1);
+    assert_eq!(wave_util::read_u32(sampler, 48), 2);
+    let samples = wave_util::find_chunk(&data, b"data").unwrap();
+    assert_eq!(wave_util::read_f32_channel(samples, 0), LEFT);
+}
+
+#[test]
+#[should_panic(expected = "The origination date must be in the format of yyyy-mm-dd.")]
+fn invalid_origination_date_panics() {
+    let mut broadcast = BroadcastInfo::default();
+    broadcast.origination_date = "31/01/2024".to_string();
+    let mut options = WaveOptions::new();
+    options.broadcast = Some(broadcast);
+    write(&options, None);
+}
+
+#[test]
+#[should_panic(expected = "The loop points must be within the waveform.")]
+fn loop_outside_waveform_panics() {
+    write(&WaveOptions::new(), Some((1, 4)));
+}
@@ -0,0 +1,34 @@
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+// Finds the chunk in the RIFF file and returns its content.
+pub fn find_chunk<'a>(data: &'a [u8], id: &[u8; 4]) -> Option<&'a [u8]> {
+    assert_eq!(&data[0..4], b"RIFF");
+    assert_eq!(&data[8..12], b"WAVE");
+
+    let mut position = 12;
+    while position + 8 <= data.len() {
+        let size = read_u32(data, position + 4) as usize;
+        let start = position + 8;
+        if &data[position..position + 4] == id {
+            return Some(&data[start..start + size]);
+        }
+        position = start + size + size % 2;
+    }
+    None
+}
+
+pub fn read_u16(data: &[u8], position: usize) -> u16 {
+    u16::from_le_bytes(data[position..position + 2].try_into().unwrap())
+}
+
+pub fn read_u32(data: &[u8], position: usize) -> u32 {
+    u32::from_le_bytes(data[position..position + 4].try_into().unwrap())
+}
+
+// Reads the 32-bit floating-point samples of a channel.
+pub fn read_f32_channel(data: &[u8], channel: usize) -> Vec<f32> {
+    data.chunks_exact(8)
+        .map(|x| f32::from_le_bytes(x[4 * channel..4 * channel + 4].try_into().unwrap()))
+        .collect()
+}